@@ -1,4 +1,5 @@
-use clap::{Parser, Subcommand};
+use clap::{Arg, ArgAction, ArgMatches, Args, Command, FromArgMatches, Parser, Subcommand, ValueEnum};
+use lakefs_local::{Compression, FilterAction, PathFilter};
 
 #[derive(Parser)]
 #[command(name = "lakectl")]
@@ -19,6 +20,11 @@ pub struct Cli {
     /// Disable color output
     #[arg(global = true, long)]
     pub no_color: bool,
+    
+    /// Write sync metrics in Prometheus text format to this file on exit
+    /// (local commands and daemon start)
+    #[arg(global = true, long, value_name = "FILE")]
+    pub metrics_file: Option<String>,
 }
 
 #[derive(Subcommand)]
@@ -41,13 +47,29 @@ pub enum Commands {
         #[arg(value_name = "BRANCH_URI")]
         branch: String,
         
-        /// Commit message
-        #[arg(short, long)]
-        message: String,
+        /// Commit message (with --amend, defaults to the amended commit's message)
+        #[arg(short, long, required_unless_present = "amend")]
+        message: Option<String>,
         
         /// Allow empty commit
         #[arg(long)]
         allow_empty: bool,
+        
+        /// Commit metadata entry (repeatable)
+        #[arg(long = "meta", value_name = "KEY=VALUE")]
+        metadata: Vec<String>,
+        
+        /// Replace the commit at the branch tip, keeping its changes
+        #[arg(long)]
+        amend: bool,
+        
+        /// With --amend, fail unless the branch tip is this commit
+        #[arg(long, value_name = "COMMIT_ID", requires = "amend")]
+        expected_head: Option<String>,
+        
+        /// Skip the trailers the `commit` section of the config requires
+        #[arg(long)]
+        no_verify: bool,
     },
     
     /// View commit logs
@@ -59,6 +81,14 @@ pub enum Commands {
         /// Number of commits to show
         #[arg(short, long, default_value = "10")]
         amount: usize,
+        
+        /// Follow every parent across merges and draw the history as a graph
+        #[arg(long)]
+        graph: bool,
+        
+        /// Like --graph, but print the graph in Graphviz DOT format
+        #[arg(long, conflicts_with = "graph")]
+        dot: bool,
     },
     
     /// File system operations
@@ -76,6 +106,22 @@ pub enum Commands {
         /// Right reference
         #[arg(value_name = "RIGHT_REF")]
         right: Option<String>,
+        
+        /// Only show paths under this prefix
+        #[arg(long)]
+        prefix: Option<String>,
+        
+        /// Print changed paths only
+        #[arg(long, conflicts_with_all = ["stat", "two_column"])]
+        name_only: bool,
+        
+        /// Print counts by change type and the total size change
+        #[arg(long, conflicts_with = "two_column")]
+        stat: bool,
+        
+        /// Print left and right paths side by side
+        #[arg(long)]
+        two_column: bool,
     },
     
     /// Merge branches
@@ -88,9 +134,114 @@ pub enum Commands {
         #[arg(value_name = "DEST_BRANCH")]
         destination: String,
         
-        /// Merge strategy
+        /// Conflict resolution strategy (dest-wins or source-wins)
         #[arg(long)]
         strategy: Option<String>,
+        
+        /// Show what the merge would change without merging
+        #[arg(long)]
+        dry_run: bool,
+        
+        /// Combine the source's changes into a single commit on the destination
+        #[arg(long)]
+        squash: bool,
+        
+        /// Commit message for the merge
+        #[arg(short, long)]
+        message: Option<String>,
+    },
+    
+    /// Copy objects to another repository, possibly on another lakeFS server
+    Replicate {
+        /// Source reference URI (lakefs://repo/ref[/path])
+        #[arg(value_name = "SOURCE_URI")]
+        source: String,
+        
+        /// Destination branch URI (lakefs://repo/branch[/path])
+        #[arg(value_name = "DEST_BRANCH_URI")]
+        destination: String,
+        
+        /// Only replicate objects under this prefix of the source
+        #[arg(long)]
+        prefix: Option<String>,
+        
+        /// Config file for the source server (defaults to the main configuration)
+        #[arg(long, value_name = "FILE")]
+        source_config: Option<String>,
+        
+        /// Transfer manifest; objects it records as done are skipped on rerun
+        #[arg(long, value_name = "FILE", default_value = "lakectl-replicate.jsonl")]
+        manifest: String,
+        
+        /// Number of objects to transfer concurrently
+        #[arg(short, long)]
+        parallelism: Option<usize>,
+    },
+    
+    /// Copy the objects of a reference to a plain bucket prefix or directory
+    Export {
+        /// Source reference URI (lakefs://repo/ref[/path])
+        #[arg(value_name = "SOURCE_URI")]
+        source: String,
+        
+        /// Destination: s3://bucket/prefix or file://path
+        #[arg(value_name = "DESTINATION")]
+        destination: String,
+        
+        /// Number of objects to copy concurrently
+        #[arg(short, long)]
+        parallelism: Option<usize>,
+    },
+    
+    /// Bring a local directory or bucket prefix onto a branch in bulk
+    Ingest {
+        /// Local directory, or s3://bucket/prefix
+        #[arg(long, value_name = "SOURCE")]
+        from: String,
+        
+        /// Destination (lakefs://repo/branch[/prefix])
+        #[arg(long, value_name = "DEST_URI")]
+        to: String,
+        
+        /// Stage bucket objects in place or copy them into the repository
+        /// (default: stage for buckets, copy for directories)
+        #[arg(long, value_enum)]
+        mode: Option<IngestMode>,
+        
+        /// Write the ingested paths, sizes and checksums to this JSON file
+        #[arg(long, value_name = "FILE")]
+        manifest: Option<String>,
+        
+        /// Commit afterwards with this message, recording the ingest in the commit metadata
+        #[arg(long, value_name = "MESSAGE")]
+        commit: Option<String>,
+        
+        /// Number of objects to ingest concurrently
+        #[arg(short, long)]
+        parallelism: Option<usize>,
+    },
+    
+    /// Show who committed and merged what, from the history of every branch
+    Audit {
+        /// Repository name or lakefs:// URI
+        #[arg(long = "repo", value_name = "REPOSITORY")]
+        repository: String,
+        
+        /// How far back to look (e.g. 12h, 7d)
+        #[arg(long, default_value = "7d")]
+        since: String,
+        
+        /// Only show commits by this user
+        #[arg(long)]
+        committer: Option<String>,
+        
+        /// Diff each commit against its parent to count added, changed and removed objects
+        #[arg(long)]
+        changes: bool,
+        
+        /// Print each event as a JSON object on its own line
+        #[arg(long)]
+        json: bool,
     },
     
     /// Local directory operations
@@ -98,6 +249,193 @@ pub enum Commands {
         #[command(subcommand)]
         command: LocalCommands,
     },
+    
+    /// Browse repositories, branches, commits and objects in the terminal
+    Browse {
+        /// Where to start: lakefs://repo, or lakefs://repo/ref[/path]
+        #[arg(value_name = "URI")]
+        uri: Option<String>,
+    },
+    
+    /// Back up and restore repository refs
+    Refs {
+        #[command(subcommand)]
+        command: RefsCommands,
+    },
+    
+    /// Inspect commit metadata (metaranges and ranges)
+    Metadata {
+        #[command(subcommand)]
+        command: MetadataCommands,
+    },
+    
+    /// Generate load against the server and report latency percentiles
+    Abuse {
+        #[command(subcommand)]
+        command: AbuseCommands,
+    },
+    
+    /// Measure transfer throughput on synthetic data
+    Bench {
+        #[command(subcommand)]
+        command: BenchCommands,
+    },
+    
+    /// Manage lakectl configuration
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommands,
+    },
+    
+    /// Run or control the background sync daemon
+    Daemon {
+        #[command(subcommand)]
+        command: DaemonCommands,
+    },
+    
+    /// Print repository names, one per line (shell completion plumbing)
+    #[command(name = "__list-repos", hide = true)]
+    ListRepos {
+        /// Only names starting with this prefix
+        #[arg(value_name = "PREFIX")]
+        prefix: Option<String>,
+    },
+    
+    /// Print branch and tag names of a repository, one per line (shell completion plumbing)
+    #[command(name = "__list-refs", hide = true)]
+    ListRefs {
+        /// Repository name or lakefs:// URI
+        #[arg(value_name = "REPOSITORY")]
+        repository: String,
+        
+        /// Only names starting with this prefix
+        #[arg(value_name = "PREFIX")]
+        prefix: Option<String>,
+    },
+}
+
+impl Commands {
+    /// Top-level command name, as typed on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            Commands::Repo { .. } => "repo",
+            Commands::Branch { .. } => "branch",
+            Commands::Commit { .. } => "commit",
+            Commands::Log { .. } => "log",
+            Commands::Fs { .. } => "fs",
+            Commands::Diff { .. } => "diff",
+            Commands::Merge { .. } => "merge",
+            Commands::Replicate { .. } => "replicate",
+            Commands::Export { .. } => "export",
+            Commands::Ingest { .. } => "ingest",
+            Commands::Audit { .. } => "audit",
+            Commands::Local { .. } => "local",
+            Commands::Browse { .. } => "browse",
+            Commands::Refs { .. } => "refs",
+            Commands::Metadata { .. } => "metadata",
+            Commands::Abuse { .. } => "abuse",
+            Commands::Bench { .. } => "bench",
+            Commands::Config { .. } => "config",
+            Commands::Daemon { .. } => "daemon",
+            Commands::ListRepos { .. } => "__list-repos",
+            Commands::ListRefs { .. } => "__list-refs",
+        }
+    }
+    
+    /// Repository the command works on, whose `repositories` section of the
+    /// config applies. Commands on a local checkout only name their directory,
+    /// so they have none, as do commands spanning repositories.
+    pub fn repository(&self) -> Option<String> {
+        let uri = self.target()?;
+        let name = uri.strip_prefix("lakefs://").unwrap_or(uri).split('/').next()?;
+        (!name.is_empty()).then(|| name.to_string())
+    }
+    
+    /// Branch, tag or commit the command works on, when it names one.
+    pub fn reference(&self) -> Option<String> {
+        let uri = match self {
+            // The branch being created is not expected to exist, its source is
+            Commands::Branch { command: BranchCommands::Create { source, .. } } => match source.strip_prefix("lakefs://") {
+                Some(_) => source.as_str(),
+                None => return Some(source.clone()),
+            },
+            Commands::Local { command: LocalCommands::Pin { commit, .. } } => return Some(commit.clone()),
+            _ => self.target()?,
+        };
+        let reference = uri.strip_prefix("lakefs://")?.split('/').nth(1)?;
+        (!reference.is_empty()).then(|| reference.to_string())
+    }
+    
+    /// Directory of the existing local checkout a `local` command works in.
+    /// Its index records the repository and branch the command works on.
+    pub fn checkout(&self) -> Option<&str> {
+        let Commands::Local { command } = self else {
+            return None;
+        };
+        match command {
+            LocalCommands::Pin { path, .. }
+            | LocalCommands::Status { path }
+            | LocalCommands::Clean { path, .. }
+            | LocalCommands::Pull { path, .. }
+            | LocalCommands::Push { path, .. }
+            | LocalCommands::Commit { path, .. } => Some(path),
+            LocalCommands::Stash { command } => match command {
+                StashCommands::Push { path, .. } | StashCommands::Pop { path, .. } | StashCommands::List { path, .. } => Some(path),
+            },
+            LocalCommands::Init { .. } | LocalCommands::Clone { .. } => None,
+        }
+    }
+    
+    /// The repository name or `lakefs://` URI the command works on.
+    fn target(&self) -> Option<&str> {
+        let uri = match self {
+            Commands::Repo { command } => match command {
+                RepoCommands::Create { name, .. }
+                | RepoCommands::Bootstrap { name, .. }
+                | RepoCommands::Delete { name, .. } => name,
+                RepoCommands::Stats { repository, .. } => repository,
+                RepoCommands::List { .. } => return None,
+            },
+            Commands::Branch { command } => match command {
+                BranchCommands::Create { uri, .. } | BranchCommands::Delete { uri, .. } | BranchCommands::Show { uri } => uri,
+                BranchCommands::List { repository, .. } => repository,
+            },
+            Commands::Commit { branch, .. } | Commands::Log { branch, .. } => branch,
+            Commands::Fs { command } => match command {
+                FsCommands::Upload { destination, .. } => destination,
+                FsCommands::Sync { source, destination, .. } => {
+                    return [source, destination].into_iter().map(String::as_str).find(|uri| uri.starts_with("lakefs://"));
+                }
+                FsCommands::Stat { paths, .. } => return paths.first().map(String::as_str).filter(|path| *path != "-"),
+                FsCommands::Checksum { target, .. } => return Some(target.as_str()).filter(|uri| uri.starts_with("lakefs://")),
+                FsCommands::Ls { path, .. }
+                | FsCommands::Du { path, .. }
+                | FsCommands::Find { path, .. }
+                | FsCommands::Rm { path, .. }
+                | FsCommands::Blame { path, .. }
+                | FsCommands::Presign { path, .. }
+                | FsCommands::Archive { path, .. } => path,
+                FsCommands::Download { source, .. } => source,
+            },
+            Commands::Diff { left, .. } => left,
+            Commands::Merge { destination, .. } | Commands::Replicate { destination, .. } => destination,
+            Commands::Export { source, .. } => source,
+            Commands::Ingest { to, .. } => to,
+            Commands::Audit { repository, .. } | Commands::ListRefs { repository, .. } => repository,
+            Commands::Local { command: LocalCommands::Init { remote, .. } | LocalCommands::Clone { remote, .. } } => remote,
+            Commands::Browse { uri: Some(uri) } => uri,
+            Commands::Refs { command: RefsCommands::Dump { repository, .. } | RefsCommands::Restore { repository, .. } } => repository,
+            Commands::Metadata { command: MetadataCommands::Inspect { commit, .. } } => commit,
+            Commands::Abuse { command: AbuseCommands::RandomRead { uri, .. } | AbuseCommands::RandomWrite { uri, .. } } => uri,
+            Commands::Bench { command: BenchCommands::Sync { uri, .. } } => uri,
+            Commands::Local { .. }
+            | Commands::Browse { uri: None }
+            | Commands::Config { .. }
+            | Commands::Daemon { .. }
+            | Commands::ListRepos { .. } => return None,
+        };
+        Some(uri)
+    }
 }
 
 #[derive(Subcommand)]
@@ -115,6 +453,31 @@ pub enum RepoCommands {
         /// Default branch name
         #[arg(long, default_value = "main")]
         default_branch: String,
+        
+        /// Only validate the storage namespace, don't create the repository
+        #[arg(long)]
+        dry_run: bool,
+    },
+    
+    /// Create a repository from a template and commit it as the first
+    /// commit on the default branch
+    Bootstrap {
+        /// Repository name
+        #[arg(value_name = "REPOSITORY")]
+        name: String,
+        
+        /// Storage namespace
+        #[arg(value_name = "STORAGE_NAMESPACE")]
+        storage_namespace: String,
+        
+        /// Template directory, or a git URL to clone one from; `.git` is
+        /// never uploaded
+        #[arg(long, value_name = "DIR_OR_GIT_URL")]
+        template: String,
+        
+        /// Message of the initial commit
+        #[arg(short, long, default_value = "Bootstrap repository from template")]
+        message: String,
     },
     
     /// List repositories
@@ -126,6 +489,14 @@ pub enum RepoCommands {
         /// Start after this value
         #[arg(long)]
         after: Option<String>,
+        
+        /// Only show repositories whose name starts with this prefix
+        #[arg(long)]
+        prefix: Option<String>,
+        
+        /// Sort all matching repositories, reading every page first
+        #[arg(long, value_enum)]
+        sort: Option<RepoSort>,
     },
     
     /// Delete a repository
@@ -138,6 +509,58 @@ pub enum RepoCommands {
         #[arg(short, long)]
         yes: bool,
     },
+    
+    /// Show object counts and sizes per branch and prefix, and recent commit activity
+    Stats {
+        /// Repository name or lakefs:// URI
+        #[arg(value_name = "REPOSITORY")]
+        repository: String,
+        
+        /// Window to count commits and committers over (e.g. 12h, 30d)
+        #[arg(long, default_value = "30d")]
+        since: String,
+        
+        /// Directory levels to break sizes down by (0 for none)
+        #[arg(long, default_value = "1")]
+        depth: usize,
+        
+        /// Number of most active committers to show
+        #[arg(long, default_value = "5")]
+        top: usize,
+        
+        /// Number of branches to list concurrently
+        #[arg(short, long)]
+        parallelism: Option<usize>,
+        
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum IngestMode {
+    /// Link objects into the branch where they are stored
+    Stage,
+    /// Stream objects through the client into the repository's storage
+    Copy,
+}
+
+impl IngestMode {
+    pub fn name(self) -> &'static str {
+        match self {
+            IngestMode::Stage => "stage",
+            IngestMode::Copy => "copy",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum RepoSort {
+    /// Oldest first
+    Created,
+    /// Alphabetical by name
+    Name,
 }
 
 #[derive(Subcommand)]
@@ -162,6 +585,19 @@ pub enum BranchCommands {
         /// Show only this many results
         #[arg(long)]
         amount: Option<usize>,
+        
+        /// Start after this branch name
+        #[arg(long)]
+        after: Option<String>,
+        
+        /// Only show branches whose name starts with this prefix
+        #[arg(long)]
+        prefix: Option<String>,
+        
+        /// Show how many commits each branch is ahead/behind this base ref,
+        /// counted back to their merge base (over 1000 shows as `>1000`)
+        #[arg(long, value_name = "BASE_REF")]
+        show_ahead_behind: Option<String>,
     },
     
     /// Delete a branch
@@ -191,18 +627,60 @@ pub enum FsCommands {
         #[arg(value_name = "PATH_URI")]
         path: String,
         
-        /// List recursively
+        /// List every object beneath the path instead of its directories
         #[arg(short, long)]
         recursive: bool,
     },
     
+    /// Summarize object count and size per directory
+    Du {
+        /// Path URI
+        #[arg(value_name = "PATH_URI")]
+        path: String,
+        
+        /// Directory levels below the path to report
+        #[arg(short, long, default_value = "1")]
+        depth: usize,
+        
+        /// Print sizes in bytes instead of human-readable units
+        #[arg(short, long)]
+        bytes: bool,
+    },
+    
+    /// Find objects by name, size and user metadata
+    Find {
+        /// Path URI to search under
+        #[arg(value_name = "PATH_URI")]
+        path: String,
+        
+        /// Glob matched against the object's file name (e.g. '*.csv')
+        #[arg(long, value_name = "PATTERN")]
+        name: Option<String>,
+        
+        /// Smallest matching object (e.g. 512, 10MB)
+        #[arg(long, value_name = "SIZE")]
+        min_size: Option<String>,
+        
+        /// Largest matching object (e.g. 512, 10MB)
+        #[arg(long, value_name = "SIZE")]
+        max_size: Option<String>,
+        
+        /// User metadata entry the object must have (repeatable)
+        #[arg(long = "meta", value_name = "KEY=VALUE")]
+        metadata: Vec<String>,
+        
+        /// Print each match as a JSON object on its own line
+        #[arg(long)]
+        json: bool,
+    },
+    
     /// Download object
     Download {
         /// Source path URI
         #[arg(value_name = "SOURCE_URI")]
         source: String,
         
-        /// Destination path
+        /// Destination path, or `-` to write the object to standard output
         #[arg(value_name = "DEST_PATH")]
         destination: Option<String>,
         
@@ -213,11 +691,14 @@ pub enum FsCommands {
         /// Number of parallel downloads
         #[arg(short, long, default_value = "10")]
         parallelism: usize,
+        
+        #[command(flatten)]
+        filters: FilterArgs,
     },
     
     /// Upload object
     Upload {
-        /// Source file/directory
+        /// Source file/directory, or `-` to read standard input
         #[arg(value_name = "SOURCE_PATH")]
         source: String,
         
@@ -232,6 +713,32 @@ pub enum FsCommands {
         /// Number of parallel uploads
         #[arg(short, long, default_value = "10")]
         parallelism: usize,
+        
+        /// Compress content before uploading (gzip or zstd); downloads
+        /// decompress it again
+        #[arg(long, value_name = "ENCODING")]
+        compress: Option<Compression>,
+        
+        /// Expected size of standard input (e.g. 512MB), to show progress
+        /// against when uploading from `-`
+        #[arg(long, value_name = "SIZE")]
+        size_hint: Option<String>,
+        
+        /// Content type to store the objects with, instead of guessing it
+        #[arg(long, value_name = "TYPE", conflicts_with = "no_sniff")]
+        content_type: Option<String>,
+        
+        /// Do not guess content types from file extensions and signatures
+        #[arg(long)]
+        no_sniff: bool,
+        
+        /// Upload the files inside the SOURCE_PATH archive (.tar, .tar.gz,
+        /// .tgz or .zip) instead of the archive itself, without extracting it
+        #[arg(long, conflicts_with_all = ["recursive", "compress", "size_hint"])]
+        expand_archive: bool,
+        
+        #[command(flatten)]
+        filters: FilterArgs,
     },
     
     /// Remove object
@@ -245,12 +752,121 @@ pub enum FsCommands {
         recursive: bool,
     },
     
+    /// Make a destination prefix match a source prefix, copying server-side
+    Sync {
+        /// Source URI (lakefs://repo/ref/prefix)
+        #[arg(value_name = "SOURCE_URI")]
+        source: String,
+        
+        /// Destination branch URI (lakefs://repo/branch/prefix)
+        #[arg(value_name = "DEST_URI")]
+        destination: String,
+        
+        /// Print the copies and deletes without applying them
+        #[arg(long)]
+        dry_run: bool,
+        
+        /// Keep destination objects that are missing from the source
+        #[arg(long)]
+        no_delete: bool,
+        
+        /// Number of parallel copies and deletes
+        #[arg(short, long, default_value = "10")]
+        parallelism: usize,
+        
+        #[command(flatten)]
+        filters: FilterArgs,
+    },
+    
     /// Show object metadata
     Stat {
+        /// Path URIs, or `-` to read them from standard input, one per line
+        #[arg(value_name = "PATH_URI", required = true)]
+        paths: Vec<String>,
+        
+        /// Number of concurrent stat requests
+        #[arg(short, long, default_value = "10")]
+        parallelism: usize,
+        
+        /// Print the objects as a JSON array
+        #[arg(long)]
+        json: bool,
+    },
+    
+    /// Show the last commit that changed a path (end it with / for a directory)
+    Blame {
+        /// Path URI on a branch
+        #[arg(value_name = "PATH_URI")]
+        path: String,
+        
+        /// List every commit that changed the path, newest first
+        #[arg(long)]
+        history: bool,
+    },
+    
+    /// Generate a presigned URL for direct object access
+    Presign {
         /// Path URI
         #[arg(value_name = "PATH_URI")]
         path: String,
+        
+        /// How long the URL must remain valid (e.g. 30m, 1h, 1d; at most 7d).
+        /// Without a `storage` section lakeFS sets the lifetime, and one
+        /// shorter than this is an error; signing locally defaults to 1h
+        #[arg(long)]
+        expiry: Option<String>,
+        
+        /// Also generate an upload URL (path must be on a branch)
+        #[arg(long)]
+        write: bool,
+    },
+    
+    /// Write the objects under a path into a tar or zip archive
+    Archive {
+        /// Path URI (lakefs://repo/ref[/prefix])
+        #[arg(value_name = "PATH_URI")]
+        path: String,
+        
+        /// Archive format
+        #[arg(long, value_enum, default_value = "tar.gz")]
+        format: ArchiveFormat,
+        
+        /// Archive file to write, or `-` for standard output (tar formats only)
+        #[arg(short, long, value_name = "FILE")]
+        output: String,
+        
+        /// Add a `<path>.metadata.json` file next to each object with its
+        /// checksum, mtime, content type and user metadata
+        #[arg(long)]
+        metadata: bool,
     },
+    
+    /// Write a manifest of the MD5 checksums (multipart ETags for objects
+    /// uploaded in parts) of the objects under a path, in `md5sum` format,
+    /// or check a ref or local directory against one; these are not SHA-256
+    Checksum {
+        /// Path URI; with --verify, a path URI or local directory to check
+        #[arg(value_name = "TARGET")]
+        target: String,
+        
+        /// File to write the manifest to (default: standard output)
+        #[arg(short, long, value_name = "FILE", conflicts_with = "verify")]
+        output: Option<String>,
+        
+        /// Manifest to check TARGET against
+        #[arg(long, value_name = "MANIFEST")]
+        verify: Option<String>,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum ArchiveFormat {
+    Tar,
+    /// Gzip-compressed tar
+    #[value(name = "tar.gz", alias = "tgz")]
+    TarGz,
+    /// Zip with deflated entries; needs a seekable output file
+    Zip,
 }
 
 #[derive(Subcommand)]
@@ -275,15 +891,64 @@ pub enum LocalCommands {
         /// Local directory
         #[arg(value_name = "LOCAL_PATH")]
         path: Option<String>,
+        
+        /// Pin the checkout to the ref's current commit; it can be pulled
+        /// from with `local pin` but never pushed or committed
+        #[arg(long)]
+        snapshot: bool,
+        
+        /// Limit transfer rate per direction (e.g. 500K, 10M bytes/sec)
+        #[arg(long, value_name = "RATE")]
+        limit_rate: Option<String>,
+        
+        /// Write a JSON transfer report to this file
+        #[arg(long, value_name = "FILE")]
+        summary_json: Option<String>,
+        
+        #[command(flatten)]
+        error_policy: ErrorPolicyArgs,
     },
     
-    /// Show local status
+    /// Switch a snapshot checkout to another commit
+    Pin {
+        /// Commit ID or any ref resolving to one
+        #[arg(value_name = "COMMIT")]
+        commit: String,
+        
+        /// Local directory
+        #[arg(value_name = "LOCAL_PATH", default_value = ".")]
+        path: String,
+        
+        /// Limit download rate (e.g. 500K, 10M bytes/sec)
+        #[arg(long, value_name = "RATE")]
+        limit_rate: Option<String>,
+    },
+    
+    /// Show what changed locally and on the branch since the last sync
     Status {
         /// Local directory
         #[arg(value_name = "LOCAL_PATH", default_value = ".")]
         path: String,
     },
     
+    /// Delete leftover partial downloads and forget tracked files that are
+    /// gone both locally and from the branch
+    Clean {
+        /// Local directory
+        #[arg(value_name = "LOCAL_PATH", default_value = ".")]
+        path: String,
+        
+        /// Only report what would be removed
+        #[arg(long)]
+        dry_run: bool,
+    },
+    
+    /// Set local changes aside and bring them back later
+    Stash {
+        #[command(subcommand)]
+        command: StashCommands,
+    },
+    
     /// Pull changes from remote
     Pull {
         /// Local directory
@@ -293,6 +958,57 @@ pub enum LocalCommands {
         /// Force pull (overwrite local changes)
         #[arg(long)]
         force: bool,
+        
+        /// Delete local files whose objects were removed from the branch
+        #[arg(long, overrides_with = "no_prune")]
+        prune: bool,
+        
+        /// Keep local files whose objects were removed from the branch (default)
+        #[arg(long, overrides_with = "prune")]
+        no_prune: bool,
+        
+        /// Stat each tracked remote object instead of trusting the listing
+        #[arg(long)]
+        verify_remote: bool,
+        
+        /// Limit transfer rate per direction (e.g. 500K, 10M bytes/sec)
+        #[arg(long, value_name = "RATE")]
+        limit_rate: Option<String>,
+        
+        /// Write a JSON transfer report to this file
+        #[arg(long, value_name = "FILE")]
+        summary_json: Option<String>,
+        
+        #[command(flatten)]
+        error_policy: ErrorPolicyArgs,
+        
+        #[command(flatten)]
+        filters: FilterArgs,
+    },
+    
+    /// Upload local changes to the branch's staging area without committing
+    Push {
+        /// Local directory
+        #[arg(value_name = "LOCAL_PATH", default_value = ".")]
+        path: String,
+        
+        /// Stat each tracked remote object instead of trusting the listing
+        #[arg(long)]
+        verify_remote: bool,
+        
+        /// Limit upload rate (e.g. 500K, 10M bytes/sec)
+        #[arg(long, value_name = "RATE")]
+        limit_rate: Option<String>,
+        
+        /// Write a JSON transfer report to this file
+        #[arg(long, value_name = "FILE")]
+        summary_json: Option<String>,
+        
+        #[command(flatten)]
+        error_policy: ErrorPolicyArgs,
+        
+        #[command(flatten)]
+        filters: FilterArgs,
     },
     
     /// Commit and push local changes
@@ -301,8 +1017,481 @@ pub enum LocalCommands {
         #[arg(value_name = "LOCAL_PATH", default_value = ".")]
         path: String,
         
-        /// Commit message
+        /// Commit message. Without it or --file, $EDITOR is opened with a
+        /// summary of the changes to write one
         #[arg(short, long)]
-        message: String,
+        message: Option<String>,
+        
+        /// Read the commit message from this file ("-" for stdin)
+        #[arg(short = 'F', long, value_name = "FILE", conflicts_with = "message")]
+        file: Option<String>,
+        
+        /// Commit metadata entry (repeatable)
+        #[arg(long = "meta", value_name = "KEY=VALUE")]
+        metadata: Vec<String>,
+        
+        /// Run the branch's pre-commit hooks from `_lakefs_actions/` first
+        /// and don't commit if any fail
+        #[arg(long)]
+        pre_check: bool,
+        
+        /// Skip the trailers the `commit` section of the config requires
+        #[arg(long)]
+        no_verify: bool,
+        
+        /// Commit the changes that were uploaded even if others failed to
+        /// sync. Without it a failed change means no commit is created.
+        #[arg(long)]
+        allow_partial: bool,
+        
+        /// Before committing, check that the branch's uncommitted changes
+        /// are exactly the ones this checkout staged. If another writer's
+        /// show up, revert what this push staged instead of committing.
+        #[arg(long)]
+        transactional: bool,
+        
+        /// Commit on a new branch from the checkout's head commit and merge
+        /// it into the tracked branch, so the changes land at once even
+        /// while others write to it. The branch's merge hooks run as for
+        /// any merge.
+        #[arg(long, conflicts_with = "transactional")]
+        via_branch: bool,
+        
+        /// Stat each tracked remote object instead of trusting the listing
+        #[arg(long)]
+        verify_remote: bool,
+        
+        /// Limit transfer rate per direction (e.g. 500K, 10M bytes/sec)
+        #[arg(long, value_name = "RATE")]
+        limit_rate: Option<String>,
+        
+        /// Write a JSON transfer report to this file
+        #[arg(long, value_name = "FILE")]
+        summary_json: Option<String>,
+        
+        #[command(flatten)]
+        error_policy: ErrorPolicyArgs,
+        
+        #[command(flatten)]
+        filters: FilterArgs,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum StashCommands {
+    /// Archive added, modified and deleted files and revert them to the
+    /// last synced state
+    Push {
+        /// Local directory
+        #[arg(value_name = "LOCAL_PATH", default_value = ".")]
+        path: String,
+        
+        /// Note to show in `stash list`
+        #[arg(short, long)]
+        message: Option<String>,
+    },
+    
+    /// Restore stashed changes and delete the stash
+    Pop {
+        /// Local directory
+        #[arg(value_name = "LOCAL_PATH", default_value = ".")]
+        path: String,
+        
+        /// Stash to restore instead of the newest
+        #[arg(long)]
+        id: Option<String>,
+    },
+    
+    /// List stashes, newest first
+    List {
+        /// Local directory
+        #[arg(value_name = "LOCAL_PATH", default_value = ".")]
+        path: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum RefsCommands {
+    /// Dump branches, tags and commits to the storage namespace and print the manifest
+    Dump {
+        /// Repository URI (lakefs://repo)
+        #[arg(value_name = "REPOSITORY_URI")]
+        repository: String,
+        
+        /// Write the manifest to this file instead of stdout
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<String>,
+    },
+    
+    /// Restore refs from a dump manifest into an empty repository
+    Restore {
+        /// Repository URI (lakefs://repo)
+        #[arg(value_name = "REPOSITORY_URI")]
+        repository: String,
+        
+        /// Manifest file produced by `refs dump` ("-" for stdin)
+        #[arg(long, value_name = "FILE")]
+        manifest: String,
     },
 }
+
+#[derive(Subcommand)]
+pub enum MetadataCommands {
+    /// Show the metarange of a commit and where its files are stored
+    Inspect {
+        /// Commit URI (lakefs://repo/<commit-or-ref>)
+        #[arg(value_name = "COMMIT_URI")]
+        commit: String,
+        
+        /// Also resolve the location of these range IDs
+        #[arg(long = "range", value_name = "RANGE_ID")]
+        ranges: Vec<String>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum AbuseCommands {
+    /// Stat randomly chosen objects of a ref, reading their metadata but not
+    /// their content
+    RandomRead {
+        /// Ref to read, and optionally a prefix to read under (lakefs://repo/main/data/)
+        #[arg(long = "repo", value_name = "URI")]
+        uri: String,
+        
+        /// Number of reads
+        #[arg(long, default_value = "10000")]
+        amount: usize,
+        
+        /// Number of reads in flight at once
+        #[arg(short, long, default_value = "50")]
+        parallelism: usize,
+        
+        /// Most objects listed to choose from
+        #[arg(long, default_value = "10000")]
+        sample: usize,
+        
+        /// Print the summary as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    
+    /// Upload objects with random names and content to a branch
+    RandomWrite {
+        /// Branch to write to, and optionally a prefix to write under (lakefs://repo/main/abuse/)
+        #[arg(long = "repo", value_name = "URI")]
+        uri: String,
+        
+        /// Number of writes
+        #[arg(long, default_value = "10000")]
+        amount: usize,
+        
+        /// Number of writes in flight at once
+        #[arg(short, long, default_value = "50")]
+        parallelism: usize,
+        
+        /// Size of each object (e.g. 1KB, 4MB)
+        #[arg(long, default_value = "1KB")]
+        size: String,
+        
+        /// Print the summary as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum BenchCommands {
+    /// Commit, clone and pull a generated tree through a scratch branch,
+    /// reporting the throughput of each
+    Sync {
+        /// Branch the scratch branch is created from (lakefs://repo/main)
+        #[arg(long = "repo", value_name = "URI")]
+        uri: String,
+        
+        /// Number of files to generate
+        #[arg(long, default_value = "1000")]
+        files: usize,
+        
+        /// Size of each file (e.g. 64KB, 1MB)
+        #[arg(long, default_value = "1MB")]
+        size: String,
+        
+        /// Number of parallel transfers (default: options.parallelism)
+        #[arg(short, long)]
+        parallelism: Option<usize>,
+        
+        /// Keep the scratch branch instead of deleting it afterwards
+        #[arg(long)]
+        keep: bool,
+        
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ConfigCommands {
+    /// Store the secret access key in the OS keyring and reference it from the config file
+    SetCredentials {
+        /// Access key ID
+        #[arg(long)]
+        access_key_id: String,
+        
+        /// Secret access key (prompted for when omitted)
+        #[arg(long)]
+        secret_access_key: Option<String>,
+    },
+    
+    /// Check the config file and LAKECTL_* environment variables for mistakes
+    Validate,
+    
+    /// Print the configuration in effect, from the config file and LAKECTL_* variables, with secrets masked
+    Show {
+        /// Show the configuration commands on this repository run with
+        #[arg(long, value_name = "REPOSITORY")]
+        repository: Option<String>,
+    },
+    
+    /// Print one configuration value
+    Get {
+        /// Dotted key, such as server.endpoint_url
+        #[arg(value_name = "KEY")]
+        key: String,
+        
+        /// Get the value commands on this repository run with
+        #[arg(long, value_name = "REPOSITORY")]
+        repository: Option<String>,
+    },
+    
+    /// Set a value in the config file
+    Set {
+        /// Dotted key, such as server.endpoint_url
+        #[arg(value_name = "KEY")]
+        key: String,
+        
+        /// New value; numbers and true/false are stored as such
+        #[arg(value_name = "VALUE")]
+        value: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum DaemonCommands {
+    /// Run the daemon in the foreground, syncing local checkouts on a schedule
+    Start {
+        /// Local directories to keep in sync (set up with `local init` or `local clone`)
+        #[arg(value_name = "LOCAL_PATH", required = true)]
+        paths: Vec<String>,
+        
+        /// Time between scheduled syncs (e.g. 30s, 5m, 1h)
+        #[arg(long, default_value = "5m")]
+        interval: String,
+        
+        /// Control socket path
+        #[arg(long, value_name = "PATH")]
+        socket: Option<String>,
+        
+        /// Serve sync metrics over HTTP on this address (e.g. 127.0.0.1:9464)
+        /// for Prometheus to scrape
+        #[arg(long, value_name = "ADDR")]
+        metrics_listen: Option<std::net::SocketAddr>,
+    },
+    
+    /// Show the daemon's checkouts and their last sync
+    Status {
+        /// Control socket path
+        #[arg(long, value_name = "PATH")]
+        socket: Option<String>,
+    },
+    
+    /// Sync now instead of waiting for the next scheduled run
+    SyncNow {
+        /// Only sync this checkout (default: all)
+        #[arg(value_name = "LOCAL_PATH")]
+        path: Option<String>,
+        
+        /// Control socket path
+        #[arg(long, value_name = "PATH")]
+        socket: Option<String>,
+    },
+    
+    /// Stop the daemon
+    Stop {
+        /// Control socket path
+        #[arg(long, value_name = "PATH")]
+        socket: Option<String>,
+    },
+}
+
+/// What a sync does when transfers fail. By default every change is
+/// attempted and the failures are reported at the end.
+#[derive(Args, Clone, Debug, Default, PartialEq)]
+pub struct ErrorPolicyArgs {
+    /// Stop at the first failed transfer
+    #[arg(long, conflicts_with = "max_errors")]
+    pub fail_fast: bool,
+    
+    /// Stop once N transfers have failed
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u64).range(1..))]
+    pub max_errors: Option<u64>,
+}
+
+impl ErrorPolicyArgs {
+    pub fn max_errors(&self) -> Option<usize> {
+        if self.fail_fast {
+            Some(1)
+        } else {
+            self.max_errors.map(|n| n as usize)
+        }
+    }
+}
+
+/// Repeatable `--include`/`--exclude` GLOB flags.
+///
+/// Derive would collect each flag into its own list and lose how they were
+/// interleaved, which rsync-style first-match precedence depends on, so the
+/// rules are rebuilt in command-line order from the argument indices.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FilterArgs {
+    pub rules: Vec<(FilterAction, String)>,
+}
+
+impl FilterArgs {
+    pub fn to_filter(&self) -> lakefs_local::Result<PathFilter> {
+        let mut filter = PathFilter::new();
+        for (action, pattern) in &self.rules {
+            filter.add(*action, pattern)?;
+        }
+        Ok(filter)
+    }
+}
+
+impl FromArgMatches for FilterArgs {
+    fn from_arg_matches(matches: &ArgMatches) -> Result<Self, clap::Error> {
+        let mut indexed = Vec::new();
+        for (id, action) in [("include", FilterAction::Include), ("exclude", FilterAction::Exclude)] {
+            if let (Some(values), Some(indices)) = (matches.get_many::<String>(id), matches.indices_of(id)) {
+                indexed.extend(indices.zip(values).map(|(index, value)| (index, action, value.clone())));
+            }
+        }
+        indexed.sort_by_key(|(index, _, _)| *index);
+        
+        Ok(Self {
+            rules: indexed.into_iter().map(|(_, action, pattern)| (action, pattern)).collect(),
+        })
+    }
+    
+    fn update_from_arg_matches(&mut self, matches: &ArgMatches) -> Result<(), clap::Error> {
+        *self = Self::from_arg_matches(matches)?;
+        Ok(())
+    }
+}
+
+impl Args for FilterArgs {
+    fn augment_args(cmd: Command) -> Command {
+        cmd.arg(
+            Arg::new("include")
+                .long("include")
+                .value_name("GLOB")
+                .action(ArgAction::Append)
+                .help("Include paths matching GLOB (repeatable; the first matching rule wins)"),
+        )
+        .arg(
+            Arg::new("exclude")
+                .long("exclude")
+                .value_name("GLOB")
+                .action(ArgAction::Append)
+                .help("Exclude paths matching GLOB (repeatable; the first matching rule wins)"),
+        )
+    }
+    
+    fn augment_args_for_update(cmd: Command) -> Command {
+        Self::augment_args(cmd)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_policy() {
+        let policy = |args: &[&str]| {
+            let cli = Cli::try_parse_from(["lakectl", "local", "push"].iter().chain(args))?;
+            let Commands::Local { command: LocalCommands::Push { error_policy, .. } } = cli.command else {
+                unreachable!()
+            };
+            Ok::<_, clap::Error>(error_policy.max_errors())
+        };
+        assert_eq!(policy(&[]).unwrap(), None);
+        assert_eq!(policy(&["--fail-fast"]).unwrap(), Some(1));
+        assert_eq!(policy(&["--max-errors", "5"]).unwrap(), Some(5));
+        assert!(policy(&["--max-errors", "0"]).is_err());
+        assert!(policy(&["--fail-fast", "--max-errors", "5"]).is_err());
+    }
+
+    #[test]
+    fn test_pull_prune_last_flag_wins() {
+        let prune = |args: &[&str]| {
+            let cli = Cli::try_parse_from(["lakectl", "local", "pull"].iter().chain(args)).unwrap();
+            let Commands::Local { command: LocalCommands::Pull { prune, .. } } = cli.command else {
+                panic!("expected local pull");
+            };
+            prune
+        };
+        
+        assert!(!prune(&[]));
+        assert!(prune(&["--prune"]));
+        assert!(!prune(&["--prune", "--no-prune"]));
+        assert!(prune(&["--no-prune", "--prune"]));
+    }
+
+    #[test]
+    fn test_command_repository() {
+        let repository = |args: &[&str]| Cli::try_parse_from(["lakectl"].iter().chain(args)).unwrap().command.repository();
+        assert_eq!(repository(&["fs", "ls", "lakefs://analytics/main/data/"]).as_deref(), Some("analytics"));
+        assert_eq!(repository(&["fs", "sync", "./out", "lakefs://analytics/main/"]).as_deref(), Some("analytics"));
+        assert_eq!(repository(&["repo", "create", "analytics", "s3://bucket/analytics"]).as_deref(), Some("analytics"));
+        assert_eq!(repository(&["merge", "lakefs://analytics/dev", "lakefs://analytics/main"]).as_deref(), Some("analytics"));
+        assert_eq!(repository(&["repo", "list"]), None);
+        assert_eq!(repository(&["local", "pull", "."]), None);
+    }
+
+    #[test]
+    fn test_command_reference() {
+        let reference = |args: &[&str]| Cli::try_parse_from(["lakectl"].iter().chain(args)).unwrap().command.reference();
+        assert_eq!(reference(&["fs", "ls", "lakefs://analytics/main/data/"]).as_deref(), Some("main"));
+        assert_eq!(reference(&["branch", "create", "lakefs://analytics/feature", "--source", "main"]).as_deref(), Some("main"));
+        assert_eq!(reference(&["audit", "--repo", "lakefs://analytics"]), None);
+        assert_eq!(reference(&["repo", "create", "analytics", "s3://bucket/analytics"]), None);
+        assert_eq!(reference(&["local", "pin", "v1", "data"]).as_deref(), Some("v1"));
+    }
+
+    #[test]
+    fn test_command_checkout() {
+        let checkout = |args: &[&str]| Cli::try_parse_from(["lakectl"].iter().chain(args)).unwrap().command.checkout().map(str::to_string);
+        assert_eq!(checkout(&["local", "pull"]).as_deref(), Some("."));
+        assert_eq!(checkout(&["local", "stash", "pop", "data"]).as_deref(), Some("data"));
+        assert_eq!(checkout(&["local", "clone", "lakefs://analytics/main", "data"]), None);
+        assert_eq!(checkout(&["fs", "ls", "lakefs://analytics/main/"]), None);
+    }
+
+    #[test]
+    fn test_filter_args_keep_order() {
+        let cli = Cli::try_parse_from([
+            "lakectl", "local", "pull", ".",
+            "--include", "*.csv", "--exclude", "tmp", "--include=keep/**", "--exclude", "*",
+        ]).unwrap();
+        let Commands::Local { command: LocalCommands::Pull { filters, .. } } = cli.command else {
+            panic!("expected local pull");
+        };
+        
+        assert_eq!(filters.rules, vec![
+            (FilterAction::Include, "*.csv".to_string()),
+            (FilterAction::Exclude, "tmp".to_string()),
+            (FilterAction::Include, "keep/**".to_string()),
+            (FilterAction::Exclude, "*".to_string()),
+        ]);
+    }
+}
@@ -0,0 +1,166 @@
+use super::fs::directory_prefix;
+use crate::cli::AbuseCommands;
+use crate::latency::{Latencies, LatencySummary};
+use crate::utils::{parse_size, resolve_uri};
+use anyhow::Result;
+use bytes::Bytes;
+use futures::StreamExt;
+use lakefs_api::models::PathType;
+use lakefs_api::{LakeFSClient, PaginationParams};
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+pub async fn execute(command: AbuseCommands, client: LakeFSClient) -> Result<()> {
+    let (summary, json) = match command {
+        AbuseCommands::RandomRead { uri, amount, parallelism, sample, json } => {
+            (random_read(&client, &uri, amount, parallelism, sample).await?, json)
+        }
+        AbuseCommands::RandomWrite { uri, amount, parallelism, size, json } => {
+            let size = parse_size(&size)?;
+            (random_write(&client, &uri, amount, parallelism, size).await?, json)
+        }
+    };
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&summary)?);
+    } else {
+        println!("{}", summary);
+    }
+    Ok(())
+}
+
+/// Stat `amount` objects picked at random from up to `sample` listed under
+/// the URI's path, `parallelism` at a time. Each read is a `get_object`
+/// call, the `objects/stat` endpoint; no content is downloaded.
+async fn random_read(client: &LakeFSClient, uri: &str, amount: usize, parallelism: usize, sample: usize) -> Result<LatencySummary> {
+    let uri = resolve_uri(client, uri).await?;
+    let prefix = directory_prefix(uri.path.as_deref());
+    let paths = list_paths(client, &uri.repository, &uri.reference, &prefix, sample).await?;
+    if paths.is_empty() {
+        anyhow::bail!("No objects to read under {}", uri);
+    }
+
+    eprintln!("Reading {} times from {} objects under {}", amount, paths.len(), uri);
+    let summary = measure(amount, parallelism, |_| {
+        let path = &paths[fastrand::usize(..paths.len())];
+        client.get_object(&uri.repository, &uri.reference, path)
+    }).await;
+    Ok(summary)
+}
+
+/// Upload `amount` objects of `size` random bytes under the URI's path,
+/// `parallelism` at a time. Every object gets a new random name, so nothing
+/// is overwritten and the branch is left with `amount` uncommitted objects.
+async fn random_write(client: &LakeFSClient, uri: &str, amount: usize, parallelism: usize, size: u64) -> Result<LatencySummary> {
+    let uri = resolve_uri(client, uri).await?;
+    let prefix = directory_prefix(uri.path.as_deref());
+    let content: Bytes = std::iter::repeat_with(|| fastrand::u8(..)).take(size as usize).collect();
+    let run = format!("{:016x}", fastrand::u64(..));
+
+    eprintln!("Writing {} objects of {} bytes under {}", amount, size, uri);
+    let summary = measure(amount, parallelism, |i| {
+        let path = format!("{}{}/{:08}-{:016x}", prefix, run, i, fastrand::u64(..));
+        let (client, uri, content) = (client, &uri, content.clone());
+        async move { client.upload_object(&uri.repository, &uri.reference, &path, content).await }
+    }).await;
+    Ok(summary)
+}
+
+/// Paths of up to `limit` objects under `prefix`.
+async fn list_paths(client: &LakeFSClient, repository: &str, reference: &str, prefix: &str, limit: usize) -> Result<Vec<String>> {
+    let mut paths = Vec::new();
+    let mut params = PaginationParams {
+        prefix: Some(prefix.to_string()).filter(|p| !p.is_empty()),
+        after: None,
+        amount: None,
+        ..Default::default()
+    };
+    while paths.len() < limit {
+        let page = client.list_objects_with(repository, reference, &params).await?;
+        paths.extend(
+            page.results
+                .into_iter()
+                .filter(|o| o.path_type == PathType::Object)
+                .map(|o| o.path),
+        );
+
+        match page.pagination.next_offset {
+            Some(next) if page.pagination.has_more => params.after = Some(next),
+            _ => break,
+        }
+    }
+    paths.truncate(limit);
+    Ok(paths)
+}
+
+/// Run `request(i)` for `i` in `0..amount`, `parallelism` at a time, timing
+/// each one.
+async fn measure<F, T, E>(amount: usize, parallelism: usize, request: impl Fn(usize) -> F) -> LatencySummary
+where
+    F: Future<Output = Result<T, E>>,
+    E: std::fmt::Display,
+{
+    let started = Instant::now();
+    let mut latencies = Latencies::default();
+    let mut results = futures::stream::iter(0..amount)
+        .map(|i| {
+            let request = request(i);
+            async move {
+                let sent = Instant::now();
+                let result = request.await;
+                (result, sent.elapsed())
+            }
+        })
+        .buffer_unordered(parallelism.max(1));
+    while let Some((result, latency)) = results.next().await {
+        match result {
+            Ok(_) => latencies.record(latency),
+            Err(e) => latencies.record_error(e),
+        }
+    }
+    latencies.summary(started.elapsed().max(Duration::from_nanos(1)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lakefs_test_utils::scenario::MockLakeFS;
+    use wiremock::matchers::{method, path_regex};
+    use wiremock::{Mock, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_random_read_stats_listed_objects() {
+        let server = MockLakeFS::new()
+            .with_repo("repo")
+            .with_branch("main")
+            .with_object("data/a.csv", "a")
+            .with_object("data/b.csv", "b")
+            .with_object("other/c.csv", "c")
+            .start()
+            .await;
+        let client = LakeFSClient::new(server.uri(), "Bearer test-token");
+
+        let summary = random_read(&client, "lakefs://repo/main/data", 20, 4, 100).await.unwrap();
+        assert_eq!((summary.requests, summary.errors), (20, 0));
+        assert!(summary.min_ms <= summary.p50_ms && summary.p50_ms <= summary.max_ms);
+
+        let error = random_read(&client, "lakefs://repo/main/missing", 20, 4, 100).await.unwrap_err();
+        assert!(error.to_string().contains("No objects to read"));
+    }
+
+    #[tokio::test]
+    async fn test_random_write_counts_failures() {
+        let server = MockLakeFS::new().with_repo("repo").with_branch("main").start().await;
+        Mock::given(method("PUT"))
+            .and(path_regex(r"^/repositories/repo/branches/main/objects$"))
+            .respond_with(ResponseTemplate::new(403).set_body_json(serde_json::json!({"message": "write denied"})))
+            .expect(5)
+            .mount(&server)
+            .await;
+        let client = LakeFSClient::new(server.uri(), "Bearer test-token");
+
+        let summary = random_write(&client, "lakefs://repo/main/abuse", 5, 2, 16).await.unwrap();
+        assert_eq!((summary.requests, summary.errors), (5, 5));
+        assert!(summary.first_error.unwrap().contains("write denied"));
+    }
+}
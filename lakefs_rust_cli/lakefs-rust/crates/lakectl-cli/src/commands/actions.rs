@@ -0,0 +1,303 @@
+//! Running a branch's pre-commit hooks from the client, so a commit the
+//! server would reject fails before anything is committed.
+
+use super::fs::list_all_objects;
+use anyhow::Result;
+use globset::Glob;
+use lakefs_api::LakeFSClient;
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::time::Duration;
+use tabled::{Table, Tabled};
+
+const ACTIONS_PREFIX: &str = "_lakefs_actions/";
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// An action definition file, as lakeFS reads it.
+#[derive(Debug, Deserialize)]
+struct Action {
+    name: Option<String>,
+    #[serde(default)]
+    on: HashMap<String, Option<EventFilter>>,
+    #[serde(default)]
+    hooks: Vec<Hook>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct EventFilter {
+    #[serde(default)]
+    branches: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Hook {
+    id: String,
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    properties: HashMap<String, serde_yaml::Value>,
+}
+
+/// The commit about to be made.
+pub(super) struct PendingCommit<'a> {
+    pub repository: &'a str,
+    pub branch: &'a str,
+    pub message: &'a str,
+    pub metadata: &'a HashMap<String, String>,
+}
+
+#[derive(Debug, PartialEq)]
+enum Outcome {
+    Passed,
+    Failed(String),
+    Skipped(String),
+}
+
+#[derive(Tabled)]
+struct CheckRow {
+    action: String,
+    hook: String,
+    result: String,
+}
+
+/// Run the pre-commit hooks defined on the branch against `commit`, print
+/// what each did and fail if any check failed.
+///
+/// Webhooks are called with the event lakeFS would send; ones that cannot
+/// be reached from here are skipped. Lua hooks run only on the server,
+/// except the `commit_metadata_validator` script, whose required keys and
+/// patterns are checked here.
+pub(super) async fn pre_check(client: &LakeFSClient, commit: &PendingCommit<'_>) -> Result<()> {
+    let mut results = Vec::new();
+    for object in list_all_objects(client, commit.repository, commit.branch, ACTIONS_PREFIX).await? {
+        if !(object.path.ends_with(".yaml") || object.path.ends_with(".yml")) {
+            continue;
+        }
+        let content = client.download_object(commit.repository, commit.branch, &object.path).await?;
+        let action: Action = match serde_yaml::from_slice(&content) {
+            Ok(action) => action,
+            Err(e) => {
+                results.push((object.path.clone(), "-".to_string(), Outcome::Failed(format!("invalid action: {}", e))));
+                continue;
+            }
+        };
+        if !triggers_pre_commit(&action, commit.branch) {
+            continue;
+        }
+        let name = action.name.clone().unwrap_or_else(|| object.path.clone());
+        for hook in &action.hooks {
+            results.push((name.clone(), hook.id.clone(), run_hook(hook, &name, commit).await));
+        }
+    }
+
+    if results.is_empty() {
+        println!("No pre-commit hooks apply to {}", commit.branch);
+        return Ok(());
+    }
+    let failed = results.iter().filter(|(_, _, outcome)| matches!(outcome, Outcome::Failed(_))).count();
+    let rows = results.into_iter().map(|(action, hook, outcome)| CheckRow {
+        action,
+        hook,
+        result: match outcome {
+            Outcome::Passed => "passed".to_string(),
+            Outcome::Failed(reason) => format!("failed: {}", reason),
+            Outcome::Skipped(reason) => format!("skipped: {}", reason),
+        },
+    });
+    println!("{}", Table::new(rows));
+    if failed > 0 {
+        anyhow::bail!("{} pre-commit checks failed; nothing was committed", failed);
+    }
+    Ok(())
+}
+
+/// Whether `action` runs on pre-commit to `branch`. An event without a
+/// branch list applies to every branch.
+fn triggers_pre_commit(action: &Action, branch: &str) -> bool {
+    let Some(filter) = action.on.get("pre-commit") else {
+        return false;
+    };
+    let branches = filter.as_ref().map(|filter| filter.branches.as_slice()).unwrap_or_default();
+    branches.is_empty()
+        || branches.iter().any(|pattern| {
+            Glob::new(pattern).is_ok_and(|glob| glob.compile_matcher().is_match(branch))
+        })
+}
+
+async fn run_hook(hook: &Hook, action: &str, commit: &PendingCommit<'_>) -> Outcome {
+    match hook.kind.as_str() {
+        "webhook" => call_webhook(hook, action, commit).await,
+        "lua" if is_metadata_validator(hook) => validate_metadata(hook, commit.metadata),
+        kind => Outcome::Skipped(format!("{} hooks only run on the server", kind)),
+    }
+}
+
+async fn call_webhook(hook: &Hook, action: &str, commit: &PendingCommit<'_>) -> Outcome {
+    let Some(url) = hook.properties.get("url").and_then(|url| url.as_str()) else {
+        return Outcome::Failed("webhook has no url".to_string());
+    };
+    let query: Vec<(String, String)> = hook
+        .properties
+        .get("query_params")
+        .and_then(|params| params.as_mapping())
+        .into_iter()
+        .flatten()
+        .filter_map(|(key, value)| Some((key.as_str()?.to_string(), yaml_scalar(value)?)))
+        .collect();
+    let event = serde_json::json!({
+        "event_type": "pre-commit",
+        "event_time": chrono::Utc::now().to_rfc3339(),
+        "action_name": action,
+        "hook_id": hook.id,
+        "repository_id": commit.repository,
+        "branch_id": commit.branch,
+        "source_ref": commit.branch,
+        "commit_message": commit.message,
+        "commit_metadata": commit.metadata,
+    });
+
+    let response = reqwest::Client::new()
+        .post(url)
+        .query(&query)
+        .timeout(WEBHOOK_TIMEOUT)
+        .json(&event)
+        .send()
+        .await;
+    match response {
+        Ok(response) if response.status().is_success() => Outcome::Passed,
+        Ok(response) => {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            Outcome::Failed(format!("{} {}", status, body.trim()))
+        }
+        Err(e) if e.is_connect() || e.is_timeout() => Outcome::Skipped(format!("{} unreachable", url)),
+        Err(e) => Outcome::Failed(e.to_string()),
+    }
+}
+
+fn is_metadata_validator(hook: &Hook) -> bool {
+    ["script_path", "script"].iter().any(|key| {
+        hook.properties
+            .get(*key)
+            .and_then(|value| value.as_str())
+            .is_some_and(|script| script.contains("commit_metadata_validator"))
+    })
+}
+
+/// The `commit_metadata_validator` arguments map each required metadata key
+/// to an optional `pattern` its value must match.
+fn validate_metadata(hook: &Hook, metadata: &HashMap<String, String>) -> Outcome {
+    let Some(args) = hook.properties.get("args").and_then(|args| args.as_mapping()) else {
+        return Outcome::Passed;
+    };
+    let mut problems = Vec::new();
+    for (key, rule) in args {
+        let Some(key) = key.as_str() else { continue };
+        let Some(value) = metadata.get(key) else {
+            problems.push(format!("missing metadata '{}'", key));
+            continue;
+        };
+        let pattern = rule.get("pattern").and_then(|pattern| pattern.as_str());
+        if let Some(pattern) = pattern {
+            match Regex::new(pattern) {
+                Ok(regex) if regex.is_match(value) => {}
+                Ok(_) => problems.push(format!("metadata '{}' does not match '{}'", key, pattern)),
+                Err(e) => problems.push(format!("invalid pattern for '{}': {}", key, e)),
+            }
+        }
+    }
+    if problems.is_empty() {
+        Outcome::Passed
+    } else {
+        Outcome::Failed(problems.join(", "))
+    }
+}
+
+fn yaml_scalar(value: &serde_yaml::Value) -> Option<String> {
+    match value {
+        serde_yaml::Value::String(s) => Some(s.clone()),
+        serde_yaml::Value::Number(n) => Some(n.to_string()),
+        serde_yaml::Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lakefs_test_utils::scenario::MockLakeFS;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const ACTION: &str = r#"
+name: Checks
+on:
+  pre-commit:
+    branches: ["main", "release-*"]
+hooks:
+  - id: metadata
+    type: lua
+    properties:
+      script_path: scripts/commit_metadata_validator.lua
+      args:
+        owner: {}
+        ticket: {"pattern": "^DATA-[0-9]+$"}
+  - id: lint
+    type: webhook
+    properties:
+      url: "WEBHOOK_URL/lint"
+"#;
+
+    #[test]
+    fn test_triggers_pre_commit() {
+        let action: Action = serde_yaml::from_str(ACTION).unwrap();
+        assert!(triggers_pre_commit(&action, "main"));
+        assert!(triggers_pre_commit(&action, "release-2"));
+        assert!(!triggers_pre_commit(&action, "dev"));
+
+        let action: Action = serde_yaml::from_str("on:\n  pre-commit:\nhooks: []\n").unwrap();
+        assert!(triggers_pre_commit(&action, "dev"));
+        let action: Action = serde_yaml::from_str("on:\n  pre-merge: {}\nhooks: []\n").unwrap();
+        assert!(!triggers_pre_commit(&action, "dev"));
+    }
+
+    #[test]
+    fn test_validate_metadata() {
+        let action: Action = serde_yaml::from_str(ACTION).unwrap();
+        let hook = &action.hooks[0];
+        assert!(is_metadata_validator(hook));
+
+        let metadata = HashMap::from([("owner".to_string(), "ana".to_string()), ("ticket".to_string(), "DATA-12".to_string())]);
+        assert_eq!(validate_metadata(hook, &metadata), Outcome::Passed);
+        let metadata = HashMap::from([("ticket".to_string(), "12".to_string())]);
+        assert_eq!(
+            validate_metadata(hook, &metadata),
+            Outcome::Failed("missing metadata 'owner', metadata 'ticket' does not match '^DATA-[0-9]+$'".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_pre_check_fails_on_rejecting_webhook() {
+        let webhook = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/lint"))
+            .respond_with(ResponseTemplate::new(400).set_body_string("bad schema"))
+            .expect(1)
+            .mount(&webhook)
+            .await;
+        let server = MockLakeFS::new()
+            .with_repo("repo")
+            .with_branch("main")
+            .with_object("_lakefs_actions/checks.yaml", ACTION.replace("WEBHOOK_URL", &webhook.uri()))
+            .with_object("_lakefs_actions/README.md", "not an action")
+            .start()
+            .await;
+        let client = LakeFSClient::new(server.uri(), "Bearer test-token");
+
+        let metadata = HashMap::from([("owner".to_string(), "ana".to_string()), ("ticket".to_string(), "DATA-1".to_string())]);
+        let commit = PendingCommit { repository: "repo", branch: "main", message: "m", metadata: &metadata };
+        let error = pre_check(&client, &commit).await.unwrap_err();
+        assert_eq!(error.to_string(), "1 pre-commit checks failed; nothing was committed");
+    }
+}
@@ -0,0 +1,516 @@
+//! `fs archive`: the objects under a path, streamed one at a time into a tar
+//! or zip archive, so memory use does not grow with their size. And the
+//! reverse for `fs upload --expand-archive`: the files in an archive,
+//! uploaded as they are read from it, without extracting them to disk.
+
+use super::fs::{directory_prefix, join_transfers, list_all_objects, ContentType};
+use crate::cli::ArchiveFormat;
+use anyhow::{Context, Result};
+use bytes::Bytes;
+use chrono::{DateTime, Datelike, Timelike, Utc};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use futures::{StreamExt, TryStreamExt};
+use lakefs_api::{models::ObjectStats, LakeFSClient, LakeFSUri};
+use lakefs_local::transfer::{self, IN_MEMORY_UPLOAD_LIMIT};
+use lakefs_local::{paths, PathFilter, ProgressReporter};
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Component, Path};
+use std::sync::Arc;
+use tokio::runtime::Handle;
+use tokio::sync::{mpsc, Semaphore};
+use tokio::task::JoinSet;
+use tokio_util::io::{StreamReader, SyncIoBridge};
+use zip::write::SimpleFileOptions;
+
+/// Appended to an object's path to name the file describing it
+const METADATA_SUFFIX: &str = ".metadata.json";
+
+/// Size of the chunks a file too large to hold in memory is uploaded in
+const STREAM_CHUNK_SIZE: usize = 1024 * 1024;
+
+/// Chunks read ahead of the upload of such a file
+const STREAM_CHUNKS_AHEAD: usize = 4;
+
+impl ArchiveFormat {
+    /// The format the name of the file at `path` implies.
+    pub fn of(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_string_lossy().to_lowercase();
+        if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Some(ArchiveFormat::TarGz)
+        } else if name.ends_with(".tar") {
+            Some(ArchiveFormat::Tar)
+        } else if name.ends_with(".zip") {
+            Some(ArchiveFormat::Zip)
+        } else {
+            None
+        }
+    }
+}
+
+/// Write every object under `uri` into an archive at `output`, or to
+/// standard output for `-`, keeping paths relative to the URI's path. With
+/// `metadata`, each object is followed by a JSON file of its stats. Returns
+/// the number of objects and their total size.
+pub(super) async fn write_archive(
+    client: &LakeFSClient,
+    uri: &LakeFSUri,
+    format: ArchiveFormat,
+    output: &str,
+    metadata: bool,
+) -> Result<(usize, i64)> {
+    if format == ArchiveFormat::Zip && output == "-" {
+        anyhow::bail!("Zip archives cannot be written to standard output; use --format tar or tar.gz");
+    }
+    let prefix = directory_prefix(uri.path.as_deref());
+    let objects = list_all_objects(client, &uri.repository, &uri.reference, &prefix).await?;
+    if objects.is_empty() {
+        anyhow::bail!("No objects under {}", uri);
+    }
+    let count = objects.len();
+    let bytes = objects.iter().map(|stats| stats.size_bytes).sum();
+
+    // The archive writers block, so they run on their own thread, which
+    // reads each object as its entry is written
+    let client = client.clone();
+    let uri = uri.clone();
+    let path = output.to_string();
+    let handle = Handle::current();
+    let written = tokio::task::spawn_blocking(move || {
+        let mut archive = ArchiveWriter::create(format, &path)?;
+        for stats in &objects {
+            let name = &stats.path[prefix.len()..];
+            let content = handle
+                .block_on(client.download_object_stream(&uri.repository, &uri.reference, &stats.path))
+                .with_context(|| format!("Failed to download {}", stats.path))?;
+            let reader = StreamReader::new(Box::pin(content.map_err(io::Error::other)));
+            archive
+                .append(name, stats, stats.size_bytes as u64, SyncIoBridge::new_with_handle(reader, handle.clone()))
+                .with_context(|| format!("Failed to archive {}", stats.path))?;
+            if metadata {
+                let description = serde_json::to_vec_pretty(stats)?;
+                let name = format!("{}{}", name, METADATA_SUFFIX);
+                archive.append(&name, stats, description.len() as u64, description.as_slice())?;
+            }
+        }
+        archive.finish()
+    })
+    .await?;
+
+    if let Err(e) = written {
+        // Leave no truncated archive behind
+        if output != "-" {
+            let _ = std::fs::remove_file(output);
+        }
+        return Err(e);
+    }
+    Ok((count, bytes))
+}
+
+enum ArchiveWriter {
+    Tar(tar::Builder<Box<dyn Write + Send>>),
+    TarGz(tar::Builder<GzEncoder<Box<dyn Write + Send>>>),
+    Zip(zip::ZipWriter<BufWriter<File>>),
+}
+
+impl ArchiveWriter {
+    fn create(format: ArchiveFormat, path: &str) -> Result<Self> {
+        let open = || File::create(path).with_context(|| format!("Failed to create {}", path));
+        let sink = || -> Result<Box<dyn Write + Send>> {
+            Ok(match path {
+                "-" => Box::new(io::stdout()),
+                _ => Box::new(BufWriter::new(open()?)),
+            })
+        };
+        Ok(match format {
+            ArchiveFormat::Tar => ArchiveWriter::Tar(tar::Builder::new(sink()?)),
+            ArchiveFormat::TarGz => {
+                let encoder = GzEncoder::new(sink()?, flate2::Compression::default());
+                ArchiveWriter::TarGz(tar::Builder::new(encoder))
+            }
+            ArchiveFormat::Zip => ArchiveWriter::Zip(zip::ZipWriter::new(BufWriter::new(open()?))),
+        })
+    }
+
+    /// Add a file at `name` holding the `size` bytes `content` yields, dated
+    /// by the object `stats` describes.
+    fn append(&mut self, name: &str, stats: &ObjectStats, size: u64, mut content: impl Read) -> Result<()> {
+        match self {
+            ArchiveWriter::Tar(builder) => builder.append_data(&mut tar_header(stats, size), name, content)?,
+            ArchiveWriter::TarGz(builder) => builder.append_data(&mut tar_header(stats, size), name, content)?,
+            ArchiveWriter::Zip(writer) => {
+                let options = SimpleFileOptions::default()
+                    .compression_method(zip::CompressionMethod::Deflated)
+                    .last_modified_time(zip_time(stats.mtime))
+                    .large_file(size >= u32::MAX as u64);
+                writer.start_file(name, options)?;
+                io::copy(&mut content, writer)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Result<()> {
+        match self {
+            ArchiveWriter::Tar(builder) => builder.into_inner()?.flush()?,
+            ArchiveWriter::TarGz(builder) => builder.into_inner()?.finish()?.flush()?,
+            ArchiveWriter::Zip(writer) => writer.finish()?.flush()?,
+        }
+        Ok(())
+    }
+}
+
+fn tar_header(stats: &ObjectStats, size: u64) -> tar::Header {
+    let mut header = tar::Header::new_gnu();
+    header.set_entry_type(tar::EntryType::Regular);
+    header.set_size(size);
+    header.set_mode(0o644);
+    header.set_mtime(stats.mtime.timestamp().max(0) as u64);
+    header
+}
+
+/// Zip timestamps only cover 1980 to 2107; others fall back to 1980.
+fn zip_time(mtime: DateTime<Utc>) -> zip::DateTime {
+    zip::DateTime::from_date_and_time(
+        mtime.year().clamp(0, u16::MAX as i32) as u16,
+        mtime.month() as u8,
+        mtime.day() as u8,
+        mtime.hour() as u8,
+        mtime.minute() as u8,
+        mtime.second() as u8,
+    )
+    .unwrap_or_default()
+}
+
+/// A file read out of an archive, on its way to being uploaded.
+struct ArchiveEntry {
+    /// Path in the archive, with `/` separators
+    name: String,
+    size: u64,
+    body: EntryBody,
+}
+
+enum EntryBody {
+    /// Files up to [`IN_MEMORY_UPLOAD_LIMIT`], read whole, so several can
+    /// be uploaded while the archive is read on
+    Buffered(Bytes),
+    /// Larger files, passed on chunk by chunk as they are read
+    Streamed(mpsc::Receiver<io::Result<Bytes>>),
+}
+
+/// Upload every file in the archive at `source` that passes `filter` to
+/// under the URI's path, keeping its path in the archive, with at most
+/// `parallelism` uploads at once. Returns the number of files uploaded.
+#[allow(clippy::too_many_arguments)]
+pub(super) async fn expand_archive(
+    client: &LakeFSClient,
+    uri: &LakeFSUri,
+    source: &Path,
+    parallelism: usize,
+    content_type: &ContentType,
+    filter: PathFilter,
+    reporter: &Arc<dyn ProgressReporter>,
+) -> Result<usize> {
+    let format = ArchiveFormat::of(source).ok_or_else(|| {
+        anyhow::anyhow!("Cannot tell the format of {}: expected .tar, .tar.gz, .tgz or .zip", source.display())
+    })?;
+    let file = File::open(source).with_context(|| format!("Failed to open {}", source.display()))?;
+    
+    // Archives are read in order on a thread of their own, which waits once
+    // `parallelism` files are read ahead of their uploads
+    let (entries, mut received) = mpsc::channel(parallelism.max(1));
+    let reader = tokio::task::spawn_blocking(move || {
+        let sink = EntrySink { entries, filter };
+        match format {
+            ArchiveFormat::Tar => read_tar(BufReader::new(file), &sink),
+            ArchiveFormat::TarGz => read_tar(GzDecoder::new(BufReader::new(file)), &sink),
+            ArchiveFormat::Zip => read_zip(file, &sink),
+        }
+    });
+    
+    let semaphore = Arc::new(Semaphore::new(parallelism.max(1)));
+    let mut tasks = JoinSet::new();
+    while let Some(entry) = received.recv().await {
+        let permit = semaphore.clone().acquire_owned().await?;
+        let client = client.clone();
+        let uri = uri.clone();
+        let reporter = reporter.clone();
+        let path = paths::to_remote(uri.path.as_deref(), &entry.name);
+        let options = content_type.upload_options(&path, None);
+        tasks.spawn(async move {
+            let _permit = permit;
+            let label = entry.name;
+            match entry.body {
+                EntryBody::Buffered(data) => {
+                    transfer::upload(&client, &uri.repository, &uri.reference, &path, data, &label, &options, None, &reporter).await?;
+                }
+                EntryBody::Streamed(mut chunks) => {
+                    reporter.file_started(&label, entry.size);
+                    let progress = reporter.clone();
+                    let owned_label = label.clone();
+                    let body = futures::stream::poll_fn(move |cx| chunks.poll_recv(cx)).inspect(move |chunk| {
+                        if let Ok(chunk) = chunk {
+                            progress.bytes_transferred(&owned_label, chunk.len() as u64);
+                        }
+                    });
+                    client.upload_object_stream_with(&uri.repository, &uri.reference, &path, body, &options).await?;
+                    reporter.file_finished(&label);
+                }
+            }
+            anyhow::Ok(())
+        });
+    }
+    
+    let uploaded = join_transfers(tasks, "upload").await;
+    reader.await?.with_context(|| format!("Failed to read {}", source.display()))?;
+    uploaded
+}
+
+/// Where the archive reader hands files over to the uploads.
+struct EntrySink {
+    entries: mpsc::Sender<ArchiveEntry>,
+    filter: PathFilter,
+}
+
+impl EntrySink {
+    /// Pass on the file at `name`, reading its `size` bytes from `content`.
+    /// False once the uploads are no longer taking files.
+    fn send(&self, name: String, size: u64, mut content: impl Read) -> Result<bool> {
+        if !self.filter.is_included(&name) {
+            return Ok(true);
+        }
+        if size <= IN_MEMORY_UPLOAD_LIMIT {
+            let mut data = Vec::with_capacity(size as usize);
+            content.read_to_end(&mut data)?;
+            let entry = ArchiveEntry { name, size, body: EntryBody::Buffered(data.into()) };
+            return Ok(self.entries.blocking_send(entry).is_ok());
+        }
+        
+        let (chunks, received) = mpsc::channel(STREAM_CHUNKS_AHEAD);
+        let entry = ArchiveEntry { name, size, body: EntryBody::Streamed(received) };
+        if self.entries.blocking_send(entry).is_err() {
+            return Ok(false);
+        }
+        let mut buffer = vec![0; STREAM_CHUNK_SIZE];
+        loop {
+            let read = match content.read(&mut buffer) {
+                Ok(0) => return Ok(true),
+                Ok(read) => read,
+                Err(e) => {
+                    // Fail the upload too, rather than store a truncated file
+                    let _ = chunks.blocking_send(Err(io::Error::new(e.kind(), e.to_string())));
+                    return Err(e.into());
+                }
+            };
+            // A failed upload reports its own error
+            if chunks.blocking_send(Ok(Bytes::copy_from_slice(&buffer[..read]))).is_err() {
+                return Ok(true);
+            }
+        }
+    }
+}
+
+fn read_tar(reader: impl Read, sink: &EntrySink) -> Result<()> {
+    let mut archive = tar::Archive::new(reader);
+    for entry in archive.entries()? {
+        let entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let name = entry_key(&entry.path()?)?;
+        let size = entry.size();
+        if !sink.send(name, size, entry)? {
+            break;
+        }
+    }
+    Ok(())
+}
+
+fn read_zip(file: File, sink: &EntrySink) -> Result<()> {
+    let mut archive = zip::ZipArchive::new(BufReader::new(file))?;
+    for index in 0..archive.len() {
+        let entry = archive.by_index(index)?;
+        if !entry.is_file() {
+            continue;
+        }
+        let name = entry_key(Path::new(entry.name()))?;
+        let size = entry.size();
+        if !sink.send(name, size, entry)? {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Object key for the archive entry at `path`. Entries that would climb out
+/// of the destination are refused, as `tar` and `unzip` do.
+fn entry_key(path: &Path) -> Result<String> {
+    let mut parts = Vec::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => parts.push(part.to_string_lossy()),
+            Component::CurDir => {}
+            _ => anyhow::bail!("Archive entry {} is not a relative path inside the archive", path.display()),
+        }
+    }
+    if parts.is_empty() {
+        anyhow::bail!("Archive entry {} has no file name", path.display());
+    }
+    Ok(parts.join("/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::GzDecoder;
+    use lakefs_local::NoopProgress;
+    use lakefs_test_utils::scenario::MockLakeFS;
+    use std::collections::BTreeMap;
+    use tempfile::TempDir;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, ResponseTemplate};
+
+    fn server() -> MockLakeFS {
+        MockLakeFS::new()
+            .with_repo("repo")
+            .with_branch("main")
+            .with_object("data/a.csv", "a,b\n1,2\n")
+            .with_object("data/nested/b.json", "{}")
+            .with_object("other/c.txt", "c")
+    }
+
+    #[tokio::test]
+    async fn test_tar_gz_archive_with_metadata() {
+        let server = server().start().await;
+        let client = LakeFSClient::new(server.uri(), "Bearer test-token");
+        let temp_dir = TempDir::new().unwrap();
+        let output = temp_dir.path().join("data.tgz");
+        let uri = LakeFSUri::new("repo", "main").with_path("data");
+
+        let (count, bytes) = write_archive(&client, &uri, ArchiveFormat::TarGz, output.to_str().unwrap(), true).await.unwrap();
+        assert_eq!((count, bytes), (2, 10));
+
+        let mut archive = tar::Archive::new(GzDecoder::new(File::open(&output).unwrap()));
+        let mut entries = BTreeMap::new();
+        for entry in archive.entries().unwrap() {
+            let mut entry = entry.unwrap();
+            let mut content = String::new();
+            entry.read_to_string(&mut content).unwrap();
+            entries.insert(entry.path().unwrap().to_string_lossy().into_owned(), content);
+        }
+        let names: Vec<_> = entries.keys().map(String::as_str).collect();
+        assert_eq!(names, ["a.csv", "a.csv.metadata.json", "nested/b.json", "nested/b.json.metadata.json"]);
+        assert_eq!(entries["a.csv"], "a,b\n1,2\n");
+        let description: ObjectStats = serde_json::from_str(&entries["a.csv.metadata.json"]).unwrap();
+        assert_eq!(description.path, "data/a.csv");
+    }
+
+    #[tokio::test]
+    async fn test_zip_archive() {
+        let server = server().start().await;
+        let client = LakeFSClient::new(server.uri(), "Bearer test-token");
+        let temp_dir = TempDir::new().unwrap();
+        let output = temp_dir.path().join("data.zip");
+        let uri = LakeFSUri::new("repo", "main");
+
+        write_archive(&client, &uri, ArchiveFormat::Zip, output.to_str().unwrap(), false).await.unwrap();
+
+        let mut archive = zip::ZipArchive::new(File::open(&output).unwrap()).unwrap();
+        assert_eq!(archive.len(), 3);
+        let mut content = String::new();
+        archive.by_name("other/c.txt").unwrap().read_to_string(&mut content).unwrap();
+        assert_eq!(content, "c");
+
+        let error = write_archive(&client, &uri, ArchiveFormat::Zip, "-", false).await.unwrap_err();
+        assert!(error.to_string().contains("standard output"));
+        let missing = LakeFSUri::new("repo", "main").with_path("nothing");
+        assert!(write_archive(&client, &missing, ArchiveFormat::Tar, output.to_str().unwrap(), false).await.is_err());
+    }
+
+    #[test]
+    fn test_entry_key() {
+        assert_eq!(entry_key(Path::new("./data/a.csv")).unwrap(), "data/a.csv");
+        assert!(entry_key(Path::new("../a.csv")).is_err());
+        assert!(entry_key(Path::new("/etc/passwd")).is_err());
+        assert!(entry_key(Path::new(".")).is_err());
+        assert_eq!(ArchiveFormat::of(Path::new("data.TGZ")), Some(ArchiveFormat::TarGz));
+        assert_eq!(ArchiveFormat::of(Path::new("data.csv")), None);
+    }
+
+    #[tokio::test]
+    async fn test_expand_archives() {
+        let server = MockLakeFS::new().with_repo("repo").with_branch("main").start().await;
+        Mock::given(method("PUT"))
+            .and(path("/repositories/repo/branches/main/objects"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!({
+                "path": "uploaded",
+                "path_type": "object",
+                "physical_address": "s3://bucket/x",
+                "checksum": "abc",
+                "size_bytes": 1,
+                "mtime": "2024-01-01T00:00:00Z"
+            })))
+            .mount(&server)
+            .await;
+        let client = LakeFSClient::new(server.uri(), "Bearer test-token");
+        let temp_dir = TempDir::new().unwrap();
+        let reporter: Arc<dyn ProgressReporter> = Arc::new(NoopProgress);
+        let files = [("./data/a.csv", "a,b\n"), ("data/tmp/skip.tmp", "skip"), ("readme.md", "hello")];
+
+        let tgz = temp_dir.path().join("dataset.tgz");
+        let mut builder = tar::Builder::new(GzEncoder::new(File::create(&tgz).unwrap(), flate2::Compression::default()));
+        let mut header = tar::Header::new_gnu();
+        header.set_entry_type(tar::EntryType::Directory);
+        header.set_size(0);
+        builder.append_data(&mut header, "data/", io::empty()).unwrap();
+        for (name, content) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            builder.append_data(&mut header, name, content.as_bytes()).unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap();
+
+        let zip_path = temp_dir.path().join("dataset.zip");
+        let mut writer = zip::ZipWriter::new(File::create(&zip_path).unwrap());
+        writer.add_directory("data/", SimpleFileOptions::default()).unwrap();
+        for (name, content) in files {
+            writer.start_file(name.trim_start_matches("./"), SimpleFileOptions::default()).unwrap();
+            writer.write_all(content.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap();
+
+        let uri = LakeFSUri::new("repo", "main").with_path("in/");
+        for source in [&tgz, &zip_path] {
+            let filter = PathFilter::new().exclude("*.tmp").unwrap();
+            let count = expand_archive(&client, &uri, source, 2, &ContentType::Sniffed, filter, &reporter).await.unwrap();
+            assert_eq!(count, 2);
+        }
+
+        let mut uploads: Vec<(String, String, Option<String>)> = server
+            .received_requests()
+            .await
+            .unwrap()
+            .into_iter()
+            .filter(|request| request.method == wiremock::http::Method::Put)
+            .map(|request| {
+                let path = request.url.query_pairs().find(|(key, _)| key == "path").unwrap().1.into_owned();
+                let content_type = request
+                    .headers
+                    .iter()
+                    .find(|(name, _)| name.as_str().eq_ignore_ascii_case("content-type"))
+                    .map(|(_, values)| values.last().as_str().to_string());
+                (path, String::from_utf8(request.body).unwrap(), content_type)
+            })
+            .collect();
+        uploads.sort();
+        uploads.dedup();
+        assert_eq!(uploads, [
+            ("in/data/a.csv".to_string(), "a,b\n".to_string(), Some("text/csv".to_string())),
+            ("in/readme.md".to_string(), "hello".to_string(), Some("text/markdown".to_string())),
+        ]);
+
+        let error = expand_archive(&client, &uri, Path::new("dataset.rar"), 2, &ContentType::Unset, PathFilter::new(), &reporter).await.unwrap_err();
+        assert!(error.to_string().contains("Cannot tell the format"));
+    }
+}
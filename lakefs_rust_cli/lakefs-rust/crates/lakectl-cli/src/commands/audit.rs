@@ -0,0 +1,259 @@
+use crate::utils::{parse_duration, parse_repository_uri};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use lakefs_api::models::{Commit, DiffType};
+use lakefs_api::{LakeFSClient, PaginationParams};
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::collections::HashSet;
+use tabled::{Table, Tabled};
+
+/// Something that happened to a repository, derived from one commit.
+#[derive(Debug, Serialize)]
+struct AuditEvent {
+    time: DateTime<Utc>,
+    /// `create` for the initial commit, `merge` for commits with several
+    /// parents and `commit` otherwise
+    action: &'static str,
+    committer: String,
+    /// First branch, default branch first, whose history contains the commit
+    branch: String,
+    commit: String,
+    message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    changes: Option<Changes>,
+}
+
+/// What a commit changed relative to its first parent.
+#[derive(Debug, Default, Serialize)]
+struct Changes {
+    added: usize,
+    changed: usize,
+    /// Paths the commit deleted
+    removed: Vec<String>,
+}
+
+#[derive(Tabled)]
+struct EventRow {
+    time: String,
+    action: String,
+    committer: String,
+    branch: String,
+    commit: String,
+    changes: String,
+    message: String,
+}
+
+/// Show who committed and merged what in a repository recently.
+///
+/// The lakeFS API has no audit log, so the trail is reconstructed from the
+/// commit history of every branch. Operations that leave no commit, such as
+/// deleting a branch, do not appear.
+pub async fn execute(
+    repository: String,
+    since: String,
+    committer: Option<String>,
+    changes: bool,
+    json: bool,
+    client: LakeFSClient,
+) -> Result<()> {
+    let repository = parse_repository_uri(&repository)?;
+    let cutoff = Utc::now() - chrono::Duration::from_std(parse_duration(&since)?)?;
+
+    let mut events = collect_events(&client, &repository, cutoff).await?;
+    events.retain(|event| committer.as_ref().is_none_or(|c| event.committer == *c));
+    if changes {
+        for event in events.iter_mut() {
+            event.changes = commit_changes(&client, &repository, &event.commit).await?;
+        }
+    }
+
+    if json {
+        for event in &events {
+            println!("{}", serde_json::to_string(event)?);
+        }
+        return Ok(());
+    }
+
+    let rows: Vec<EventRow> = events
+        .into_iter()
+        .map(|event| EventRow {
+            time: event.time.format("%Y-%m-%d %H:%M:%S").to_string(),
+            action: event.action.to_string(),
+            committer: event.committer,
+            branch: event.branch,
+            commit: event.commit.chars().take(8).collect(),
+            changes: event
+                .changes
+                .map(|c| format!("+{} ~{} -{}", c.added, c.changed, c.removed.len()))
+                .unwrap_or_default(),
+            message: event.message.lines().next().unwrap_or("").to_string(),
+        })
+        .collect();
+    println!("{}", Table::new(rows));
+    Ok(())
+}
+
+/// Commits made after `cutoff` on any branch, newest first, each listed once.
+async fn collect_events(client: &LakeFSClient, repository: &str, cutoff: DateTime<Utc>) -> Result<Vec<AuditEvent>> {
+    let default_branch = client.get_repository(repository).await?.default_branch;
+    let mut branches = vec![default_branch.clone()];
+    let mut params = PaginationParams::default();
+    loop {
+        let page = client.list_branches_with(repository, &params).await?;
+        branches.extend(page.results.into_iter().map(|b| b.id).filter(|id| *id != default_branch));
+
+        match page.pagination.next_offset {
+            Some(next) if page.pagination.has_more => params.after = Some(next),
+            _ => break,
+        }
+    }
+
+    let mut seen = HashSet::new();
+    let mut events = Vec::new();
+    for branch in branches {
+        for commit in commits_since(client, repository, &branch, cutoff).await? {
+            if seen.insert(commit.id.clone()) {
+                events.push(AuditEvent::from_commit(commit, &branch));
+            }
+        }
+    }
+    events.sort_by_key(|event| Reverse(event.time));
+    Ok(events)
+}
+
+/// The branch log up to the first commit older than `cutoff`.
+pub(super) async fn commits_since(
+    client: &LakeFSClient,
+    repository: &str,
+    branch: &str,
+    cutoff: DateTime<Utc>,
+) -> Result<Vec<Commit>> {
+    let mut commits = Vec::new();
+    let mut params = PaginationParams::default();
+    loop {
+        let page = client.log_commits_with(repository, branch, &params).await?;
+        for commit in page.results {
+            if commit.creation_date < cutoff {
+                return Ok(commits);
+            }
+            commits.push(commit);
+        }
+
+        match page.pagination.next_offset {
+            Some(next) if page.pagination.has_more => params.after = Some(next),
+            _ => break,
+        }
+    }
+    Ok(commits)
+}
+
+/// Diff a commit against its first parent; `None` for the initial commit.
+async fn commit_changes(client: &LakeFSClient, repository: &str, commit_id: &str) -> Result<Option<Changes>> {
+    let commit = client.get_commit(repository, commit_id).await?;
+    let Some(parent) = commit.parents.first() else {
+        return Ok(None);
+    };
+
+    let mut changes = Changes::default();
+    let mut params = PaginationParams::default();
+    loop {
+        let page = client.diff_with(repository, parent, commit_id, &params).await?;
+        for diff in page.results {
+            match diff.diff_type {
+                DiffType::Added => changes.added += 1,
+                DiffType::Changed => changes.changed += 1,
+                DiffType::Removed => changes.removed.push(diff.path),
+            }
+        }
+
+        match page.pagination.next_offset {
+            Some(next) if page.pagination.has_more => params.after = Some(next),
+            _ => break,
+        }
+    }
+    Ok(Some(changes))
+}
+
+impl AuditEvent {
+    fn from_commit(commit: Commit, branch: &str) -> Self {
+        let action = match commit.parents.len() {
+            0 => "create",
+            1 => "commit",
+            _ => "merge",
+        };
+        Self {
+            time: commit.creation_date,
+            action,
+            committer: commit.committer,
+            branch: branch.to_string(),
+            commit: commit.id,
+            message: commit.message,
+            changes: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lakefs_test_utils::scenario::MockLakeFS;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, ResponseTemplate};
+
+    fn commit(id: &str, parents: &[&str], committer: &str, age_days: i64) -> serde_json::Value {
+        serde_json::json!({
+            "id": id,
+            "parents": parents,
+            "committer": committer,
+            "message": format!("change {}", id),
+            "creation_date": (Utc::now() - chrono::Duration::days(age_days)).timestamp(),
+            "meta_range_id": "",
+            "metadata": {}
+        })
+    }
+
+    #[tokio::test]
+    async fn test_collect_events_across_branches() {
+        let server = MockLakeFS::new()
+            .with_repo("repo")
+            .with_branch("main")
+            .with_branch("feature")
+            .start()
+            .await;
+        let logs = [
+            ("main", vec![
+                commit("m2", &["m1", "f1"], "alice", 1),
+                commit("f1", &["m1"], "carol", 2),
+                commit("m1", &["m0"], "bob", 3),
+                commit("m0", &[], "admin", 30),
+            ]),
+            ("feature", vec![
+                commit("f2", &["f1"], "carol", 0),
+                commit("f1", &["m1"], "carol", 2),
+                commit("m1", &["m0"], "bob", 3),
+                commit("m0", &[], "admin", 30),
+            ]),
+        ];
+        for (branch, commits) in logs {
+            Mock::given(method("GET"))
+                .and(path(format!("/repositories/repo/branches/{}/commits", branch)))
+                .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                    "results": commits,
+                    "pagination": {"has_more": false, "max_per_page": 100, "results": 4}
+                })))
+                .mount(&server)
+                .await;
+        }
+        let client = LakeFSClient::new(server.uri(), "Bearer test-token");
+
+        let events = collect_events(&client, "repo", Utc::now() - chrono::Duration::days(7)).await.unwrap();
+        let summary: Vec<_> = events.iter().map(|e| (e.commit.as_str(), e.action, e.branch.as_str())).collect();
+        assert_eq!(summary, [
+            ("f2", "commit", "feature"),
+            ("m2", "merge", "main"),
+            ("f1", "commit", "main"),
+            ("m1", "commit", "main"),
+        ]);
+    }
+}
@@ -0,0 +1,228 @@
+use super::local::check_errors;
+use crate::cli::BenchCommands;
+use crate::config::OptionsConfig;
+use crate::utils::{format_size, parse_size, resolve_uri};
+use anyhow::{Context, Result};
+use lakefs_api::{LakeFSClient, LakeFSUri};
+use lakefs_local::{LocalIndex, PathFilter, SyncConfig, SyncManager};
+use serde::Serialize;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tabled::{Table, Tabled};
+use tokio_util::sync::CancellationToken;
+
+/// Files per directory of a generated tree
+const FAN_OUT: usize = 100;
+
+/// Throughput of one phase of a benchmark.
+#[derive(Debug, Serialize)]
+struct Phase {
+    phase: &'static str,
+    files: usize,
+    bytes: u64,
+    seconds: f64,
+    files_per_second: f64,
+    bytes_per_second: f64,
+}
+
+impl Phase {
+    fn new(phase: &'static str, files: usize, bytes: u64, elapsed: Duration) -> Self {
+        let seconds = elapsed.as_secs_f64();
+        let rate = |amount: f64| if seconds > 0.0 { amount / seconds } else { 0.0 };
+        Self {
+            phase,
+            files,
+            bytes,
+            seconds,
+            files_per_second: rate(files as f64),
+            bytes_per_second: rate(bytes as f64),
+        }
+    }
+}
+
+#[derive(Tabled)]
+struct PhaseRow {
+    phase: &'static str,
+    files: usize,
+    size: String,
+    seconds: String,
+    #[tabled(rename = "files/s")]
+    files_per_second: String,
+    throughput: String,
+}
+
+/// Where and what a sync benchmark runs.
+struct SyncBench<'a> {
+    client: &'a LakeFSClient,
+    repository: &'a str,
+    branch: &'a str,
+    /// Directory of the generated tree, under the root of the branch
+    prefix: &'a str,
+    files: usize,
+    size: u64,
+    parallelism: usize,
+    cancel: CancellationToken,
+}
+
+pub async fn execute(
+    command: BenchCommands,
+    client: LakeFSClient,
+    options: &OptionsConfig,
+    cancel: CancellationToken,
+) -> Result<()> {
+    match command {
+        BenchCommands::Sync { uri, files, size, parallelism, keep, json } => {
+            let source = resolve_uri(&client, &uri).await?;
+            let size = parse_size(&size)?;
+            let branch = format!("lakectl-bench-{:08x}", fastrand::u32(..));
+
+            client.create_branch(&source.repository, &branch, &source.reference).await?;
+            eprintln!("Benchmarking {} files of {} on scratch branch {}", files, format_size(size as i64), branch);
+            let bench = SyncBench {
+                client: &client,
+                repository: &source.repository,
+                branch: &branch,
+                prefix: &branch,
+                files,
+                size,
+                parallelism: parallelism.unwrap_or(options.parallelism),
+                cancel,
+            };
+            let phases = bench.run().await;
+
+            // The scratch branch goes whether or not the run succeeded, but a
+            // failed run is reported ahead of a failed cleanup
+            let cleanup = match keep {
+                true => {
+                    eprintln!("Kept scratch branch {}", branch);
+                    Ok(())
+                }
+                false => client.delete_branch(&source.repository, &branch).await,
+            };
+            let phases = phases.inspect_err(|_| {
+                if let Err(e) = &cleanup {
+                    eprintln!("Failed to delete scratch branch {}: {}", branch, e);
+                }
+            })?;
+            print_phases(&phases, json)?;
+            cleanup.with_context(|| format!("Failed to delete scratch branch {}", branch))?;
+        }
+    }
+    Ok(())
+}
+
+impl SyncBench<'_> {
+    /// Generate the tree, commit it, clone it into an empty directory and
+    /// pull the clone again with nothing changed.
+    async fn run(&self) -> Result<Vec<Phase>> {
+        let work = tempfile::tempdir()?;
+        let (source, clone) = (work.path().join("source"), work.path().join("clone"));
+        let bytes = self.files as u64 * self.size;
+        let mut phases = Vec::new();
+
+        let started = Instant::now();
+        let (root, files, size) = (source.join(self.prefix), self.files, self.size);
+        tokio::task::spawn_blocking(move || generate_tree(&root, files, size)).await??;
+        phases.push(Phase::new("generate", self.files, bytes, started.elapsed()));
+
+        let head = self.client.get_branch(self.repository, self.branch).await?;
+        LocalIndex::new(self.repository, self.branch, &head.commit_id).save(&source)?;
+        let started = Instant::now();
+        let result = self.manager(PathFilter::default()).push(&source, &self.remote()).await?;
+        check_errors(&result)?;
+        let commit = self.client.commit(self.repository, self.branch, "lakectl bench sync").await?;
+        phases.push(Phase::new("commit", result.uploaded, bytes, started.elapsed()));
+
+        // Only the generated tree, not whatever the branch was created with
+        let filter = PathFilter::new()
+            .include(&format!("{}/**", self.prefix))?
+            .exclude("*")?;
+        LocalIndex::new(self.repository, self.branch, &commit.id).save(&clone)?;
+        let started = Instant::now();
+        let result = self.manager(filter.clone()).pull(&clone, &self.remote()).await?;
+        check_errors(&result)?;
+        phases.push(Phase::new("clone", result.downloaded, bytes, started.elapsed()));
+
+        let started = Instant::now();
+        let result = self.manager(filter).pull(&clone, &self.remote()).await?;
+        check_errors(&result)?;
+        phases.push(Phase::new("pull (unchanged)", self.files, 0, started.elapsed()));
+
+        Ok(phases)
+    }
+
+    fn remote(&self) -> LakeFSUri {
+        LakeFSUri::new(self.repository, self.branch)
+    }
+
+    fn manager(&self, filter: PathFilter) -> SyncManager {
+        let config = SyncConfig {
+            parallelism: self.parallelism,
+            filter,
+            ..Default::default()
+        };
+        SyncManager::new(Arc::new(self.client.clone()), config).with_cancellation(self.cancel.clone())
+    }
+}
+
+/// `files` files of `size` random bytes under `root`, `FAN_OUT` to a
+/// directory. Random content keeps compression and deduplication from
+/// flattering the numbers.
+fn generate_tree(root: &Path, files: usize, size: u64) -> Result<()> {
+    let mut content = vec![0u8; size as usize];
+    for i in 0..files {
+        let directory = root.join(format!("d{:04}", i / FAN_OUT));
+        if i % FAN_OUT == 0 {
+            std::fs::create_dir_all(&directory)?;
+        }
+        fastrand::fill(&mut content);
+        std::fs::write(directory.join(format!("f{:06}.bin", i)), &content)?;
+    }
+    Ok(())
+}
+
+fn print_phases(phases: &[Phase], json: bool) -> Result<()> {
+    if json {
+        println!("{}", serde_json::to_string_pretty(phases)?);
+        return Ok(());
+    }
+    let rows = phases.iter().map(|p| PhaseRow {
+        phase: p.phase,
+        files: p.files,
+        size: format_size(p.bytes as i64),
+        seconds: format!("{:.2}", p.seconds),
+        files_per_second: format!("{:.1}", p.files_per_second),
+        throughput: format!("{}/s", format_size(p.bytes_per_second as i64)),
+    });
+    println!("{}", Table::new(rows));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_generate_tree() {
+        let temp_dir = TempDir::new().unwrap();
+        generate_tree(temp_dir.path(), 150, 64).unwrap();
+
+        let files: Vec<_> = walkdir::WalkDir::new(temp_dir.path())
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+            .collect();
+        assert_eq!(files.len(), 150);
+        assert!(files.iter().all(|e| e.metadata().unwrap().len() == 64));
+        assert!(temp_dir.path().join("d0001/f000149.bin").exists());
+    }
+
+    #[test]
+    fn test_phase_rates() {
+        let phase = Phase::new("clone", 100, 4 << 20, Duration::from_secs(2));
+        assert_eq!((phase.files_per_second, phase.bytes_per_second), (50.0, (2 << 20) as f64));
+        assert_eq!(Phase::new("pull", 0, 0, Duration::ZERO).files_per_second, 0.0);
+    }
+}
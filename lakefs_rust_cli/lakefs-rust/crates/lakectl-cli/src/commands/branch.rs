@@ -1,7 +1,7 @@
 use crate::cli::BranchCommands;
-use crate::utils::parse_uri;
+use crate::utils::{parse_uri, parse_repository_uri};
 use anyhow::Result;
-use lakefs_api::LakeFSClient;
+use lakefs_api::{resolve_reference, Branch, LakeFSClient, PaginationParams};
 use tabled::{Table, Tabled};
 
 #[derive(Tabled)]
@@ -10,17 +10,89 @@ struct BranchRow {
     commit_id: String,
 }
 
+#[derive(Tabled)]
+struct BranchAheadBehindRow {
+    name: String,
+    commit_id: String,
+    ahead: String,
+    behind: String,
+}
+
+/// How many commits of a log are walked looking for the merge base before
+/// giving up and reporting `>AHEAD_BEHIND_LIMIT`.
+const AHEAD_BEHIND_LIMIT: usize = 1000;
+
+/// Count the commits in the log of `reference` before `base`, walking at most
+/// `limit` of them. `None` if `limit` commits were walked without reaching it,
+/// an error if the log ends without it.
+async fn commits_until(
+    client: &LakeFSClient,
+    repository: &str,
+    reference: &str,
+    base: &str,
+    limit: usize,
+) -> Result<Option<usize>> {
+    let mut count = 0;
+    let mut params = PaginationParams::default();
+    
+    loop {
+        let page = client.log_commits_with(repository, reference, &params).await?;
+        for commit in page.results {
+            if commit.id == base {
+                return Ok(Some(count));
+            }
+            count += 1;
+            if count >= limit {
+                return Ok(None);
+            }
+        }
+        
+        match page.pagination.next_offset {
+            Some(next) if page.pagination.has_more => params.after = Some(next),
+            _ => anyhow::bail!("Merge base {} not found in the log of {}", base, reference),
+        }
+    }
+}
+
+fn format_count(count: Option<usize>) -> String {
+    count.map_or_else(|| format!(">{}", AHEAD_BEHIND_LIMIT), |count| count.to_string())
+}
+
+/// Rows for `branch list --show-ahead-behind`, counting each branch's commits
+/// ahead of and behind `base` from their merge base.
+async fn ahead_behind_rows(
+    client: &LakeFSClient,
+    repository: &str,
+    branches: Vec<Branch>,
+    base: &str,
+) -> Result<Vec<BranchAheadBehindRow>> {
+    let mut rows = Vec::new();
+    
+    for b in branches {
+        let merge_base = client.find_merge_base(repository, &b.id, base).await?;
+        let base_commit = &merge_base.base_commit_id;
+        let ahead = commits_until(client, repository, &b.id, base_commit, AHEAD_BEHIND_LIMIT).await?;
+        let behind = commits_until(client, repository, base, base_commit, AHEAD_BEHIND_LIMIT).await?;
+        rows.push(BranchAheadBehindRow {
+            ahead: format_count(ahead),
+            behind: format_count(behind),
+            name: b.id,
+            commit_id: b.commit_id,
+        });
+    }
+    
+    Ok(rows)
+}
+
 pub async fn execute(command: BranchCommands, client: LakeFSClient) -> Result<()> {
     match command {
         BranchCommands::Create { uri, source } => {
             let parsed = parse_uri(&uri)?;
-            let branch_name = parsed.path.ok_or_else(|| {
-                anyhow::anyhow!("Invalid branch URI: must include branch name")
-            })?;
+            let source = resolve_reference(&client, &parsed.repository, &source).await?;
             
             let branch = client.create_branch(
                 &parsed.repository,
-                &branch_name,
+                &parsed.reference,
                 &source,
             ).await?;
             
@@ -28,20 +100,29 @@ pub async fn execute(command: BranchCommands, client: LakeFSClient) -> Result<()
             println!("Commit ID: {}", branch.commit_id);
         }
         
-        BranchCommands::List { repository, amount: _ } => {
-            let parsed = parse_uri(&repository)?;
-            let response = client.list_branches(&parsed.repository).await?;
+        BranchCommands::List { repository, amount, after, prefix, show_ahead_behind } => {
+            let repository = parse_repository_uri(&repository)?;
+            let params = PaginationParams { prefix, after, amount, ..Default::default() };
+            let response = client.list_branches_with(&repository, &params).await?;
             
-            let rows: Vec<BranchRow> = response
-                .results
-                .into_iter()
-                .map(|b| BranchRow {
-                    name: b.id,
-                    commit_id: b.commit_id,
-                })
-                .collect();
+            let table = match show_ahead_behind {
+                Some(base) => {
+                    Table::new(ahead_behind_rows(&client, &repository, response.results, &base).await?)
+                }
+                None => {
+                    let rows: Vec<BranchRow> = response
+                        .results
+                        .into_iter()
+                        .map(|b| BranchRow {
+                            name: b.id,
+                            commit_id: b.commit_id,
+                        })
+                        .collect();
+                    
+                    Table::new(rows)
+                }
+            };
             
-            let table = Table::new(rows);
             println!("{}", table);
             
             if response.pagination.has_more {
@@ -89,8 +170,7 @@ pub async fn execute(command: BranchCommands, client: LakeFSClient) -> Result<()
 #[cfg(test)]
 mod tests {
     use super::*;
-    use super::*;
-    use wiremock::matchers::{method, path};
+    use wiremock::matchers::{method, path, query_param};
     use wiremock::{MockServer, Mock, ResponseTemplate};
 
     #[tokio::test]
@@ -145,9 +225,97 @@ mod tests {
         let command = BranchCommands::List {
             repository: "lakefs://test-repo".to_string(),
             amount: None,
+            after: None,
+            prefix: None,
+            show_ahead_behind: None,
         };
         
         let result = execute(command, client).await;
         assert!(result.is_ok());
     }
-}
\ No newline at end of file
+
+    fn commit_json(id: &str) -> serde_json::Value {
+        serde_json::json!({
+            "id": id,
+            "parents": [],
+            "committer": "user",
+            "message": "msg",
+            "creation_date": "2024-01-01T00:00:00Z",
+            "meta_range_id": "",
+            "metadata": {}
+        })
+    }
+
+    #[tokio::test]
+    async fn test_commits_until_follows_pagination()  {
+        let mock_server = MockServer::start().await;
+        
+        Mock::given(method("GET"))
+            .and(path("/repositories/test-repo/branches/feature/commits"))
+            .and(query_param("after", "c2"))
+            .respond_with(ResponseTemplate::new(200)
+                .set_body_json(serde_json::json!({
+                    "results": [commit_json("c1"), commit_json("c0")],
+                    "pagination": {"has_more": false, "max_per_page": 2, "results": 2, "next_offset": null}
+                })))
+            .mount(&mock_server)
+            .await;
+        
+        Mock::given(method("GET"))
+            .and(path("/repositories/test-repo/branches/feature/commits"))
+            .respond_with(ResponseTemplate::new(200)
+                .set_body_json(serde_json::json!({
+                    "results": [commit_json("c3"), commit_json("c2")],
+                    "pagination": {"has_more": true, "max_per_page": 2, "results": 2, "next_offset": "c2"}
+                })))
+            .mount(&mock_server)
+            .await;
+
+        let client = LakeFSClient::new(mock_server.uri(), "Bearer test-token");
+        assert_eq!(commits_until(&client, "test-repo", "feature", "c1", 10).await.unwrap(), Some(2));
+        assert_eq!(commits_until(&client, "test-repo", "feature", "c3", 10).await.unwrap(), Some(0));
+        assert_eq!(commits_until(&client, "test-repo", "feature", "c0", 3).await.unwrap(), None);
+        assert!(commits_until(&client, "test-repo", "feature", "missing", 10).await.is_err());
+        assert_eq!(format_count(None), ">1000");
+    }
+
+    #[tokio::test]
+    async fn test_list_branches_ahead_behind_uses_merge_base()  {
+        let mock_server = MockServer::start().await;
+        
+        Mock::given(method("GET"))
+            .and(path("/repositories/test-repo/refs/feature/merge/main"))
+            .respond_with(ResponseTemplate::new(200)
+                .set_body_json(serde_json::json!({
+                    "source_commit_id": "f2",
+                    "destination_commit_id": "m1",
+                    "base_commit_id": "b0"
+                })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        
+        for (branch, commits) in [("feature", vec!["f2", "f1", "b0"]), ("main", vec!["m1", "b0"])] {
+            let results: Vec<_> = commits.into_iter().map(commit_json).collect();
+            Mock::given(method("GET"))
+                .and(path(format!("/repositories/test-repo/branches/{}/commits", branch)))
+                .respond_with(ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({
+                        "results": results,
+                        "pagination": {"has_more": true, "max_per_page": 3, "results": 3, "next_offset": "b0"}
+                    })))
+                .expect(1)
+                .mount(&mock_server)
+                .await;
+        }
+
+        let client = LakeFSClient::new(mock_server.uri(), "Bearer test-token");
+        let branches = vec![Branch { id: "feature".to_string(), commit_id: "f2".to_string() }];
+        let rows = ahead_behind_rows(&client, "test-repo", branches, "main").await.unwrap();
+        
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].name, "feature");
+        assert_eq!(rows[0].ahead, "2");
+        assert_eq!(rows[0].behind, "1");
+    }
+}
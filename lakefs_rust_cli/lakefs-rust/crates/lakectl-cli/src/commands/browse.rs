@@ -0,0 +1,772 @@
+use super::commit::short_id;
+use super::diff::collect_diffs;
+use super::fs::{directory_prefix, list_directory};
+use crate::utils::{format_size, parse_repository_uri, parse_uri};
+use anyhow::{Context, Result};
+use futures::StreamExt;
+use lakefs_api::models::{Branch, Commit, Diff, DiffType, ObjectStats, PathType, Repository};
+use lakefs_api::{resolve_uri, LakeFSClient, PaginationParams};
+use lakefs_local::{transfer, NoopProgress, ProgressReporter};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::sync::Arc;
+
+// `lakectl browse`: repositories, branches, commits and the object tree in
+// one screen. Everything runs on the command's task; each key is handled to
+// completion before the screen is drawn again.
+
+/// Repositories and branches listed; only the first page is shown.
+const PAGE_SIZE: usize = 1000;
+/// Commits listed per branch
+const COMMITS_SHOWN: usize = 100;
+/// Bytes of an object read for its preview
+const PREVIEW_BYTES: usize = 64 * 1024;
+/// Rows moved by PageUp and PageDown
+const PAGE_STEP: isize = 10;
+
+const KEY_HINTS: &str =
+    "tab: pane  enter: open  backspace: up  d: download  x: delete  c: diff  r: refresh  q: quit";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Pane {
+    Repositories,
+    Branches,
+    Commits,
+    Objects,
+    Preview,
+}
+
+impl Pane {
+    const ALL: [Pane; 5] = [Pane::Repositories, Pane::Branches, Pane::Commits, Pane::Objects, Pane::Preview];
+
+    fn cycle(self, step: isize) -> Pane {
+        let index = Self::ALL.iter().position(|pane| *pane == self).unwrap_or_default() as isize;
+        Self::ALL[(index + step).rem_euclid(Self::ALL.len() as isize) as usize]
+    }
+}
+
+/// A list with a selected row, kept within bounds.
+struct Selectable<T> {
+    items: Vec<T>,
+    state: ListState,
+}
+
+impl<T> Default for Selectable<T> {
+    fn default() -> Self {
+        Self { items: Vec::new(), state: ListState::default() }
+    }
+}
+
+impl<T> Selectable<T> {
+    fn new(items: Vec<T>) -> Self {
+        let selected = (!items.is_empty()).then_some(0);
+        Self { items, state: ListState::default().with_selected(selected) }
+    }
+
+    fn selected(&self) -> Option<&T> {
+        self.state.selected().and_then(|index| self.items.get(index))
+    }
+
+    fn move_by(&mut self, delta: isize) {
+        if self.items.is_empty() {
+            return;
+        }
+        let current = self.state.selected().unwrap_or_default() as isize;
+        let last = self.items.len() as isize - 1;
+        self.state.select(Some((current + delta).clamp(0, last) as usize));
+    }
+
+    fn select_where(&mut self, f: impl Fn(&T) -> bool) {
+        if let Some(index) = self.items.iter().position(f) {
+            self.state.select(Some(index));
+        }
+    }
+}
+
+/// A row of the object pane.
+pub(super) enum Entry {
+    Parent,
+    /// Name of a directory directly under the current prefix
+    Directory(String),
+    Object { name: String, stats: ObjectStats },
+}
+
+impl Entry {
+    pub(super) fn label(&self) -> String {
+        match self {
+            Entry::Parent => "..".to_string(),
+            Entry::Directory(name) => format!("{}/", name),
+            Entry::Object { name, .. } => name.clone(),
+        }
+    }
+}
+
+/// Rows for the objects under `prefix`: the parent directory unless at the
+/// root, then subdirectories, then the objects directly under it.
+pub(super) fn tree_entries(objects: Vec<ObjectStats>, prefix: &str) -> Vec<Entry> {
+    let mut directories = BTreeSet::new();
+    let mut files = Vec::new();
+    for stats in objects {
+        let relative = stats.path.strip_prefix(prefix).unwrap_or(&stats.path);
+        // Delimited listings send directories as common prefixes
+        match relative.split_once('/') {
+            Some((directory, _)) => {
+                directories.insert(directory.to_string());
+            }
+            None if stats.path_type == PathType::Directory => {
+                directories.insert(relative.to_string());
+            }
+            None => files.push(Entry::Object { name: relative.to_string(), stats }),
+        }
+    }
+    files.sort_by_key(Entry::label);
+
+    let parent = (!prefix.is_empty()).then_some(Entry::Parent);
+    parent.into_iter()
+        .chain(directories.into_iter().map(Entry::Directory))
+        .chain(files)
+        .collect()
+}
+
+/// The reference and directory shown in the object pane.
+#[derive(Debug, Clone, PartialEq)]
+struct Location {
+    repository: String,
+    reference: String,
+    prefix: String,
+    /// Objects can only be deleted on branches
+    on_branch: bool,
+}
+
+impl Location {
+    fn uri(&self, path: &str) -> String {
+        format!("lakefs://{}/{}/{}", self.repository, self.reference, path)
+    }
+}
+
+#[derive(Default)]
+struct Preview {
+    title: String,
+    lines: Vec<Line<'static>>,
+    scroll: u16,
+}
+
+enum Status {
+    Info(String),
+    Error(String),
+}
+
+struct Browser {
+    client: LakeFSClient,
+    focus: Pane,
+    repositories: Selectable<Repository>,
+    branches: Selectable<Branch>,
+    commits: Selectable<Commit>,
+    objects: Selectable<Entry>,
+    location: Option<Location>,
+    preview: Preview,
+    status: Status,
+    /// Object waiting for the user to confirm its deletion
+    pending_delete: Option<String>,
+    quit: bool,
+}
+
+pub async fn execute(uri: Option<String>, client: LakeFSClient) -> Result<()> {
+    let mut browser = Browser::new(client);
+    // Fail before taking over the terminal if the server is unreachable
+    browser.start(uri.as_deref()).await?;
+
+    let mut terminal = ratatui::try_init()?;
+    let result = run(&mut terminal, &mut browser).await;
+    ratatui::try_restore()?;
+    result
+}
+
+async fn run(terminal: &mut DefaultTerminal, browser: &mut Browser) -> Result<()> {
+    while !browser.quit {
+        terminal.draw(|frame| draw(frame, browser))?;
+        if let Event::Key(key) = tokio::task::spawn_blocking(event::read).await?? {
+            if key.kind == KeyEventKind::Press {
+                browser.on_key(key).await;
+            }
+        }
+    }
+    Ok(())
+}
+
+impl Browser {
+    fn new(client: LakeFSClient) -> Self {
+        Self {
+            client,
+            focus: Pane::Repositories,
+            repositories: Selectable::default(),
+            branches: Selectable::default(),
+            commits: Selectable::default(),
+            objects: Selectable::default(),
+            location: None,
+            preview: Preview::default(),
+            status: Status::Info(String::new()),
+            pending_delete: None,
+            quit: false,
+        }
+    }
+
+    /// List repositories and open `uri`, which may name just a repository
+    /// (`lakefs://repo`) or a reference and directory in it.
+    async fn start(&mut self, uri: Option<&str>) -> Result<()> {
+        let params = PaginationParams { prefix: None, after: None, amount: Some(PAGE_SIZE), ..Default::default() };
+        let page = self.client.list_repositories_with(&params).await?;
+        self.repositories = Selectable::new(page.results);
+
+        let Some(uri) = uri else {
+            return Ok(());
+        };
+        let (repository, reference, prefix) = match parse_uri(uri) {
+            Ok(uri) => {
+                let uri = resolve_uri(&self.client, uri).await?;
+                (uri.repository, Some(uri.reference), directory_prefix(uri.path.as_deref()))
+            }
+            Err(_) => (parse_repository_uri(uri)?, None, String::new()),
+        };
+        self.repositories.select_where(|r| r.id == repository);
+        self.open_repository(&repository, reference, prefix).await?;
+        self.focus = Pane::Objects;
+        Ok(())
+    }
+
+    async fn on_key(&mut self, key: KeyEvent) {
+        self.status = Status::Info(String::new());
+        if let Err(e) = self.handle_key(key).await {
+            self.status = Status::Error(format!("{:#}", e));
+        }
+    }
+
+    async fn handle_key(&mut self, key: KeyEvent) -> Result<()> {
+        if let Some(path) = self.pending_delete.take() {
+            if matches!(key.code, KeyCode::Char('y') | KeyCode::Char('Y')) {
+                self.delete(&path).await?;
+            } else {
+                self.status = Status::Info("Delete cancelled".to_string());
+            }
+            return Ok(());
+        }
+
+        match key.code {
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => self.quit = true,
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            KeyCode::Tab | KeyCode::Right | KeyCode::Char('l') => self.focus = self.focus.cycle(1),
+            KeyCode::BackTab | KeyCode::Left | KeyCode::Char('h') => self.focus = self.focus.cycle(-1),
+            KeyCode::Down | KeyCode::Char('j') => self.move_by(1),
+            KeyCode::Up | KeyCode::Char('k') => self.move_by(-1),
+            KeyCode::PageDown => self.move_by(PAGE_STEP),
+            KeyCode::PageUp => self.move_by(-PAGE_STEP),
+            KeyCode::Home | KeyCode::Char('g') => self.move_by(isize::MIN / 2),
+            KeyCode::End | KeyCode::Char('G') => self.move_by(isize::MAX / 2),
+            KeyCode::Enter => self.activate().await?,
+            KeyCode::Backspace => self.go_up().await?,
+            KeyCode::Char('d') => self.download().await?,
+            KeyCode::Char('x') | KeyCode::Delete => self.ask_delete()?,
+            KeyCode::Char('c') => self.diff().await?,
+            KeyCode::Char('r') => self.refresh().await?,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn move_by(&mut self, delta: isize) {
+        match self.focus {
+            Pane::Repositories => self.repositories.move_by(delta),
+            Pane::Branches => self.branches.move_by(delta),
+            Pane::Commits => self.commits.move_by(delta),
+            Pane::Objects => self.objects.move_by(delta),
+            Pane::Preview => {
+                let scroll = (self.preview.scroll as isize + delta).clamp(0, u16::MAX as isize);
+                self.preview.scroll = scroll as u16;
+            }
+        }
+    }
+
+    async fn activate(&mut self) -> Result<()> {
+        match self.focus {
+            Pane::Repositories => {
+                if let Some(repository) = self.repositories.selected().map(|r| r.id.clone()) {
+                    self.open_repository(&repository, None, String::new()).await?;
+                    self.focus = Pane::Branches;
+                }
+            }
+            Pane::Branches => {
+                let selected = self.branches.selected().map(|b| b.id.clone());
+                if let (Some(branch), Some(repository)) = (selected, self.repository()) {
+                    self.open_reference(&repository, &branch, String::new()).await?;
+                    self.focus = Pane::Objects;
+                }
+            }
+            Pane::Commits => {
+                let selected = self.commits.selected().map(|c| c.id.clone());
+                if let (Some(commit), Some(repository)) = (selected, self.repository()) {
+                    self.browse(Location { repository, reference: commit, prefix: String::new(), on_branch: false }).await?;
+                    self.focus = Pane::Objects;
+                }
+            }
+            Pane::Objects => {
+                let Some(location) = self.location.clone() else {
+                    return Ok(());
+                };
+                match self.objects.selected() {
+                    Some(Entry::Parent) => self.go_up().await?,
+                    Some(Entry::Directory(name)) => {
+                        let prefix = format!("{}{}/", location.prefix, name);
+                        self.browse(Location { prefix, ..location }).await?;
+                    }
+                    Some(Entry::Object { stats, .. }) => {
+                        let stats = stats.clone();
+                        self.show_object(&location, &stats).await?;
+                    }
+                    None => {}
+                }
+            }
+            Pane::Preview => {}
+        }
+        Ok(())
+    }
+
+    fn repository(&self) -> Option<String> {
+        self.location.as_ref().map(|l| l.repository.clone())
+    }
+
+    /// Load a repository's branches and browse `reference`, by default the
+    /// repository's default branch.
+    async fn open_repository(&mut self, repository: &str, reference: Option<String>, prefix: String) -> Result<()> {
+        let params = PaginationParams { prefix: None, after: None, amount: Some(PAGE_SIZE), ..Default::default() };
+        let page = self.client.list_branches_with(repository, &params).await?;
+        self.branches = Selectable::new(page.results);
+
+        let reference = match reference {
+            Some(reference) => reference,
+            None => self.client.get_repository(repository).await?.default_branch,
+        };
+        self.branches.select_where(|b| b.id == reference);
+        self.open_reference(repository, &reference, prefix).await
+    }
+
+    /// Browse `reference`, listing its commits when it is a branch.
+    async fn open_reference(&mut self, repository: &str, reference: &str, prefix: String) -> Result<()> {
+        let on_branch = self.branches.items.iter().any(|b| b.id == reference);
+        self.commits = if on_branch {
+            let params = PaginationParams { prefix: None, after: None, amount: Some(COMMITS_SHOWN), ..Default::default() };
+            Selectable::new(self.client.log_commits_with(repository, reference, &params).await?.results)
+        } else {
+            Selectable::default()
+        };
+
+        let location = Location {
+            repository: repository.to_string(),
+            reference: reference.to_string(),
+            prefix,
+            on_branch,
+        };
+        self.browse(location).await
+    }
+
+    async fn browse(&mut self, location: Location) -> Result<()> {
+        let objects = list_directory(&self.client, &location.repository, &location.reference, &location.prefix).await?;
+        self.objects = Selectable::new(tree_entries(objects, &location.prefix));
+        self.location = Some(location);
+        Ok(())
+    }
+
+    async fn go_up(&mut self) -> Result<()> {
+        let Some(location) = self.location.clone() else {
+            return Ok(());
+        };
+        let Some(current) = location.prefix.strip_suffix('/') else {
+            return Ok(());
+        };
+        let (parent, name) = match current.rsplit_once('/') {
+            Some((parent, name)) => (format!("{}/", parent), name.to_string()),
+            None => (String::new(), current.to_string()),
+        };
+        self.browse(Location { prefix: parent, ..location }).await?;
+        self.objects.select_where(|entry| matches!(entry, Entry::Directory(n) if *n == name));
+        Ok(())
+    }
+
+    async fn refresh(&mut self) -> Result<()> {
+        let Some(location) = self.location.clone() else {
+            return Ok(());
+        };
+        let selected = self.objects.selected().map(Entry::label);
+        if location.on_branch {
+            self.open_reference(&location.repository, &location.reference, location.prefix).await?;
+        } else {
+            self.browse(location).await?;
+        }
+        if let Some(label) = selected {
+            self.objects.select_where(|entry| entry.label() == label);
+        }
+        self.status = Status::Info("Refreshed".to_string());
+        Ok(())
+    }
+
+    fn selected_object(&self) -> Option<(Location, ObjectStats)> {
+        match (&self.location, self.objects.selected()) {
+            (Some(location), Some(Entry::Object { stats, .. })) => Some((location.clone(), stats.clone())),
+            _ => None,
+        }
+    }
+
+    /// Show an object's details and the start of its content.
+    async fn show_object(&mut self, location: &Location, stats: &ObjectStats) -> Result<()> {
+        let stream = self.client
+            .download_object_stream(&location.repository, &location.reference, &stats.path)
+            .await?;
+        let mut stream = Box::pin(stream);
+        let mut content = Vec::new();
+        while let Some(chunk) = stream.next().await {
+            content.extend_from_slice(&chunk?);
+            if content.len() >= PREVIEW_BYTES {
+                break;
+            }
+        }
+        content.truncate(PREVIEW_BYTES);
+
+        let dim = Style::default().add_modifier(Modifier::DIM);
+        let mut lines = vec![
+            Line::styled(format!("size      {}", format_size(stats.size_bytes)), dim),
+            Line::styled(format!("modified  {}", stats.mtime.format("%Y-%m-%d %H:%M:%S")), dim),
+            Line::styled(format!("checksum  {}", stats.checksum), dim),
+        ];
+        if let Some(content_type) = &stats.content_type {
+            lines.push(Line::styled(format!("type      {}", content_type), dim));
+        }
+        for (key, value) in stats.metadata.iter().flatten() {
+            lines.push(Line::styled(format!("meta      {}={}", key, value), dim));
+        }
+        lines.push(Line::default());
+
+        if content.contains(&0) {
+            lines.push(Line::from("Binary content, not shown"));
+        } else {
+            let text = String::from_utf8_lossy(&content);
+            lines.extend(text.lines().map(|line| Line::from(line.to_string())));
+            if (content.len() as i64) < stats.size_bytes {
+                lines.push(Line::styled(format!("... first {} shown", format_size(content.len() as i64)), dim));
+            }
+        }
+
+        self.preview = Preview { title: stats.path.clone(), lines, scroll: 0 };
+        Ok(())
+    }
+
+    /// Download the selected object into the current directory.
+    async fn download(&mut self) -> Result<()> {
+        let Some((location, stats)) = self.selected_object() else {
+            self.status = Status::Info("Select an object to download".to_string());
+            return Ok(());
+        };
+        let name = stats.path.rsplit('/').next().unwrap_or(&stats.path).to_string();
+        let destination = Path::new(&name);
+        if destination.exists() {
+            anyhow::bail!("{} already exists in the current directory", name);
+        }
+
+        let reporter: Arc<dyn ProgressReporter> = Arc::new(NoopProgress);
+        let written = transfer::download(
+            &self.client,
+            &location.repository,
+            &location.reference,
+            &stats,
+            destination,
+            &stats.path,
+            None,
+            &reporter,
+        )
+        .await
+        .with_context(|| format!("Failed to download {}", location.uri(&stats.path)))?;
+        self.status = Status::Info(format!("Downloaded {} ({}) to ./{}", stats.path, format_size(written as i64), name));
+        Ok(())
+    }
+
+    fn ask_delete(&mut self) -> Result<()> {
+        let Some((location, stats)) = self.selected_object() else {
+            self.status = Status::Info("Select an object to delete".to_string());
+            return Ok(());
+        };
+        if !location.on_branch {
+            anyhow::bail!("Objects can only be deleted from a branch, not from {}", location.reference);
+        }
+        self.pending_delete = Some(stats.path);
+        Ok(())
+    }
+
+    async fn delete(&mut self, path: &str) -> Result<()> {
+        let Some(location) = self.location.clone() else {
+            return Ok(());
+        };
+        self.client.delete_object(&location.repository, &location.reference, path).await?;
+        self.browse(location.clone()).await?;
+        self.status = Status::Info(format!("Deleted {}", location.uri(path)));
+        Ok(())
+    }
+
+    /// Show the changes of the selected commit, or the uncommitted changes of
+    /// the selected (or browsed) branch.
+    async fn diff(&mut self) -> Result<()> {
+        let Some(repository) = self.repository() else {
+            return Ok(());
+        };
+        let (title, diffs) = match self.focus {
+            Pane::Commits => {
+                let Some(commit) = self.commits.selected() else {
+                    return Ok(());
+                };
+                let Some(parent) = commit.parents.first() else {
+                    self.status = Status::Info("The first commit has no parent to compare with".to_string());
+                    return Ok(());
+                };
+                let (id, parent) = (commit.id.clone(), parent.clone());
+                let diffs = collect_diffs(&self.client, &repository, &parent, &id, None).await?;
+                (format!("Changes in {}", short_id(&id)), diffs)
+            }
+            Pane::Branches | Pane::Objects | Pane::Preview | Pane::Repositories => {
+                let branch = match self.focus {
+                    Pane::Branches => self.branches.selected().map(|b| b.id.clone()),
+                    _ => self.location.as_ref().filter(|l| l.on_branch).map(|l| l.reference.clone()),
+                };
+                let Some(branch) = branch else {
+                    self.status = Status::Info("Select a branch or commit to diff".to_string());
+                    return Ok(());
+                };
+                let diffs = self.uncommitted(&repository, &branch).await?;
+                (format!("Uncommitted changes on {}", branch), diffs)
+            }
+        };
+
+        let mut lines: Vec<Line<'static>> = diffs.iter().map(diff_line).collect();
+        if lines.is_empty() {
+            lines.push(Line::from("No changes"));
+        }
+        self.preview = Preview { title, lines, scroll: 0 };
+        Ok(())
+    }
+
+    async fn uncommitted(&self, repository: &str, branch: &str) -> Result<Vec<Diff>> {
+        let mut diffs = Vec::new();
+        let mut params = PaginationParams::new();
+        loop {
+            let page = self.client.diff_uncommitted(repository, branch, &params).await?;
+            diffs.extend(page.results);
+
+            match page.pagination.next_offset {
+                Some(next) if page.pagination.has_more => params.after = Some(next),
+                _ => break,
+            }
+        }
+        Ok(diffs)
+    }
+}
+
+fn diff_line(diff: &Diff) -> Line<'static> {
+    let (marker, color) = match diff.diff_type {
+        DiffType::Added => ("+", Color::Green),
+        DiffType::Removed => ("-", Color::Red),
+        DiffType::Changed => ("~", Color::Yellow),
+    };
+    Line::from(vec![Span::styled(format!("{} ", marker), Style::default().fg(color)), Span::raw(diff.path.clone())])
+}
+
+fn pane_block(title: String, focused: bool) -> Block<'static> {
+    let border = if focused { Style::default().fg(Color::Cyan) } else { Style::default() };
+    Block::bordered().title(title).border_style(border)
+}
+
+fn draw_list<T>(frame: &mut Frame, area: Rect, block: Block<'static>, list: &mut Selectable<T>, item: impl Fn(&T) -> Line<'static>) {
+    let items: Vec<ListItem> = list.items.iter().map(|i| ListItem::new(item(i))).collect();
+    let widget = List::new(items)
+        .block(block)
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+        .highlight_symbol("> ");
+    frame.render_stateful_widget(widget, area, &mut list.state);
+}
+
+fn draw(frame: &mut Frame, browser: &mut Browser) {
+    let [top, bottom, status] =
+        Layout::vertical([Constraint::Percentage(35), Constraint::Min(5), Constraint::Length(1)]).areas(frame.area());
+    let [repositories, branches, commits] =
+        Layout::horizontal([Constraint::Percentage(20), Constraint::Percentage(25), Constraint::Percentage(55)]).areas(top);
+    let [objects, preview] = Layout::horizontal([Constraint::Percentage(45), Constraint::Percentage(55)]).areas(bottom);
+    let focus = browser.focus;
+
+    draw_list(frame, repositories, pane_block("Repositories".into(), focus == Pane::Repositories), &mut browser.repositories, |r| {
+        let read_only = if r.read_only == Some(true) { " (read-only)" } else { "" };
+        Line::from(format!("{}{}", r.id, read_only))
+    });
+    draw_list(frame, branches, pane_block("Branches".into(), focus == Pane::Branches), &mut browser.branches, |b| {
+        Line::from(vec![Span::raw(format!("{} ", b.id)), short_id(&b.commit_id).to_string().dim()])
+    });
+    draw_list(frame, commits, pane_block("Commits".into(), focus == Pane::Commits), &mut browser.commits, |c| {
+        Line::from(vec![
+            short_id(&c.id).to_string().yellow(),
+            format!(" {} ", c.creation_date.format("%Y-%m-%d %H:%M")).dim(),
+            Span::raw(c.message.lines().next().unwrap_or_default().to_string()),
+        ])
+    });
+
+    let title = browser.location.as_ref().map_or_else(|| "Objects".to_string(), |l| l.uri(&l.prefix));
+    draw_list(frame, objects, pane_block(title, focus == Pane::Objects), &mut browser.objects, |entry| match entry {
+        Entry::Object { name, stats } => Line::from(vec![Span::raw(format!("{} ", name)), format_size(stats.size_bytes).dim()]),
+        entry => Line::from(entry.label()).bold(),
+    });
+
+    let title = if browser.preview.title.is_empty() { "Preview".to_string() } else { browser.preview.title.clone() };
+    let paragraph = Paragraph::new(browser.preview.lines.clone())
+        .block(pane_block(title, focus == Pane::Preview))
+        .scroll((browser.preview.scroll, 0));
+    frame.render_widget(paragraph, preview);
+
+    let line = match (&browser.pending_delete, &browser.status) {
+        (Some(path), _) => {
+            let uri = browser.location.as_ref().map_or_else(|| path.clone(), |l| l.uri(path));
+            Line::from(format!("Delete {}? (y/N)", uri)).yellow()
+        }
+        (None, Status::Error(message)) => Line::from(message.clone()).red(),
+        (None, Status::Info(message)) if !message.is_empty() => Line::from(message.clone()),
+        (None, Status::Info(_)) => Line::from(KEY_HINTS).dim(),
+    };
+    frame.render_widget(line, status);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lakefs_test_utils::fixtures::test_object_stats;
+    use lakefs_test_utils::scenario::MockLakeFS;
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn stats(path: &str) -> ObjectStats {
+        ObjectStats { path: path.to_string(), ..test_object_stats() }
+    }
+
+    fn labels(browser: &Browser) -> Vec<String> {
+        browser.objects.items.iter().map(Entry::label).collect()
+    }
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    async fn server() -> MockServer {
+        let server = MockLakeFS::new()
+            .with_repo("repo")
+            .with_branch("main")
+            .with_object("README.md", "hello\n")
+            .with_object("data/a.csv", "a,b\n1,2\n")
+            .with_object("data/raw/b.csv", "c,d\n")
+            .start()
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repositories/repo/branches/main/commits"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "results": [{
+                    "id": "c2f1e0d9b8a7", "parents": ["a1b2c3d4e5f6"], "committer": "user",
+                    "message": "Add data\n\nDetails", "creation_date": 1704067200, "meta_range_id": ""
+                }],
+                "pagination": {"has_more": false, "max_per_page": 100, "results": 1, "next_offset": null}
+            })))
+            .mount(&server)
+            .await;
+        server
+    }
+
+    #[test]
+    fn test_tree_entries() {
+        let objects = vec![stats("data/z.csv"), stats("data/raw/b.csv"), stats("data/a.csv"), stats("data/raw/c.csv")];
+        let entries = tree_entries(objects, "data/");
+        let labels: Vec<String> = entries.iter().map(Entry::label).collect();
+        assert_eq!(labels, ["..", "raw/", "a.csv", "z.csv"]);
+
+        let prefix = ObjectStats { path_type: PathType::Directory, ..stats("logs/") };
+        let labels: Vec<String> = tree_entries(vec![prefix, stats("top.txt")], "").iter().map(Entry::label).collect();
+        assert_eq!(labels, ["logs/", "top.txt"]);
+    }
+
+    #[tokio::test]
+    async fn test_navigate_and_preview() {
+        let server = server().await;
+        let mut browser = Browser::new(LakeFSClient::new(server.uri(), "Bearer token"));
+        browser.start(Some("lakefs://repo")).await.unwrap();
+
+        assert_eq!(browser.focus, Pane::Objects);
+        assert_eq!(browser.branches.selected().map(|b| b.id.as_str()), Some("main"));
+        assert_eq!(browser.commits.items.len(), 1);
+        assert_eq!(labels(&browser), ["data/", "README.md"]);
+
+        browser.on_key(key(KeyCode::Enter)).await;
+        assert_eq!(labels(&browser), ["..", "raw/", "a.csv"]);
+        let requests = server.received_requests().await.unwrap();
+        let listings: Vec<_> = requests.iter()
+            .filter(|request| request.url.path() == "/repositories/repo/refs/main/objects")
+            .collect();
+        assert_eq!(listings.len(), 2);
+        assert!(listings.iter().all(|request| request.url.query_pairs().any(|(key, value)| key == "delimiter" && value == "/")));
+
+        browser.on_key(key(KeyCode::End)).await;
+        browser.on_key(key(KeyCode::Enter)).await;
+        assert_eq!(browser.preview.title, "data/a.csv");
+        assert!(browser.preview.lines.contains(&Line::from("a,b")));
+
+        browser.on_key(key(KeyCode::Backspace)).await;
+        assert_eq!(browser.location.as_ref().unwrap().prefix, "");
+        assert_eq!(browser.objects.selected().map(Entry::label).as_deref(), Some("data/"));
+
+        browser.on_key(key(KeyCode::Char('q'))).await;
+        assert!(browser.quit);
+    }
+
+    #[tokio::test]
+    async fn test_delete_asks_for_confirmation() {
+        let server = server().await;
+        Mock::given(method("DELETE"))
+            .and(path("/repositories/repo/branches/main/objects"))
+            .and(query_param("path", "README.md"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let mut browser = Browser::new(LakeFSClient::new(server.uri(), "Bearer token"));
+        browser.start(Some("lakefs://repo/main/")).await.unwrap();
+        browser.on_key(key(KeyCode::Down)).await;
+
+        browser.on_key(key(KeyCode::Char('x'))).await;
+        browser.on_key(key(KeyCode::Char('n'))).await;
+        assert!(matches!(&browser.status, Status::Info(message) if message == "Delete cancelled"));
+
+        browser.on_key(key(KeyCode::Char('x'))).await;
+        assert_eq!(browser.pending_delete.as_deref(), Some("README.md"));
+        browser.on_key(key(KeyCode::Char('y'))).await;
+        assert!(matches!(&browser.status, Status::Info(message) if message == "Deleted lakefs://repo/main/README.md"));
+    }
+
+    #[tokio::test]
+    async fn test_draw_shows_location_and_commits() {
+        let server = server().await;
+        let mut browser = Browser::new(LakeFSClient::new(server.uri(), "Bearer token"));
+        browser.start(Some("lakefs://repo/main/data")).await.unwrap();
+
+        let mut terminal = Terminal::new(TestBackend::new(120, 30)).unwrap();
+        terminal.draw(|frame| draw(frame, &mut browser)).unwrap();
+        let screen: String = terminal.backend().buffer().content().iter().map(|cell| cell.symbol()).collect();
+        assert!(screen.contains("lakefs://repo/main/data/"));
+        assert!(screen.contains("c2f1e0d9 2024-01-01 00:00 Add data"));
+        assert!(screen.contains("> .."));
+    }
+}
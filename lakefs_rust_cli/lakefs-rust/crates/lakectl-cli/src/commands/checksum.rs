@@ -0,0 +1,213 @@
+//! `fs checksum`: manifests of the checksums lakeFS keeps for objects (MD5
+//! or multipart ETags, not SHA-256), in the `<checksum>  <path>` format of
+//! `md5sum`, and checking a ref or a local directory against one.
+
+use super::fs::{directory_prefix, list_all_objects};
+use anyhow::{Context, Result};
+use lakefs_api::{Checksum, LakeFSClient, LakeFSUri};
+use lakefs_local::{paths, transfer};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::Path;
+use walkdir::WalkDir;
+
+/// Checksums by path, relative to the prefix or directory they were taken under.
+#[derive(Debug, Default, PartialEq)]
+pub(super) struct Manifest {
+    pub entries: BTreeMap<String, Checksum>,
+}
+
+impl Manifest {
+    /// The checksum of every object under the URI's path.
+    pub async fn of_objects(client: &LakeFSClient, uri: &LakeFSUri) -> Result<Self> {
+        let prefix = directory_prefix(uri.path.as_deref());
+        let objects = list_all_objects(client, &uri.repository, &uri.reference, &prefix).await?;
+        let entries = objects
+            .into_iter()
+            .map(|stats| (stats.path[prefix.len()..].to_string(), stats.checksum))
+            .collect();
+        Ok(Self { entries })
+    }
+
+    /// Read a manifest. Blank lines and `#` comments are skipped, and the
+    /// `*` that marks binary mode in `sha256sum` output is ignored.
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut entries = BTreeMap::new();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim_end();
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (checksum, path) = line
+                .split_once(char::is_whitespace)
+                .map(|(checksum, path)| (checksum, path.trim_start().trim_start_matches('*')))
+                .filter(|(_, path)| !path.is_empty())
+                .ok_or_else(|| anyhow::anyhow!("Line {} is not `<checksum>  <path>`: {}", number + 1, line))?;
+            entries.insert(path.to_string(), Checksum::parse(checksum));
+        }
+        Ok(Self { entries })
+    }
+
+    pub fn render(&self) -> String {
+        let mut text = String::new();
+        for (path, checksum) in &self.entries {
+            let _ = writeln!(text, "{}  {}", checksum, path);
+        }
+        text
+    }
+}
+
+/// How a ref or directory compares with a manifest, each list sorted by path.
+#[derive(Debug, Default, PartialEq)]
+pub(super) struct Verification {
+    pub matched: usize,
+    pub mismatched: Vec<String>,
+    pub missing: Vec<String>,
+    /// Present but not in the manifest
+    pub extra: Vec<String>,
+    /// Recorded with a checksum the content cannot be compared with, such
+    /// as the ETag of a multipart upload
+    pub unverifiable: Vec<String>,
+}
+
+impl Verification {
+    pub fn passed(&self) -> bool {
+        self.mismatched.is_empty() && self.missing.is_empty()
+    }
+
+    fn record(&mut self, path: &str, result: Option<bool>) {
+        match result {
+            Some(true) => self.matched += 1,
+            Some(false) => self.mismatched.push(path.to_string()),
+            None => self.unverifiable.push(path.to_string()),
+        }
+    }
+}
+
+/// Whether checksum `actual` shows the same content as `expected`; `None`
+/// when the two are of kinds that cannot be compared.
+fn compare(expected: &Checksum, actual: &Checksum) -> Option<bool> {
+    if expected == actual {
+        return Some(true);
+    }
+    match expected.matches(actual) {
+        Some(matches) => Some(matches),
+        None if std::mem::discriminant(expected) == std::mem::discriminant(actual) => Some(false),
+        None => None,
+    }
+}
+
+/// Compare the checksums of another listing, such as of a different ref,
+/// with `manifest`.
+pub(super) fn verify_objects(manifest: &Manifest, objects: &Manifest) -> Verification {
+    let mut verification = Verification::default();
+    for (path, expected) in &manifest.entries {
+        match objects.entries.get(path) {
+            Some(actual) => verification.record(path, compare(expected, actual)),
+            None => verification.missing.push(path.clone()),
+        }
+    }
+    verification.extra = objects
+        .entries
+        .keys()
+        .filter(|path| !manifest.entries.contains_key(*path))
+        .cloned()
+        .collect();
+    verification
+}
+
+/// Hash the files under `directory` and compare them with `manifest`. The
+/// `.lakectl` directory of a checkout is not looked at.
+pub(super) fn verify_directory(manifest: &Manifest, directory: &Path) -> Result<Verification> {
+    let mut verification = Verification::default();
+    for (path, expected) in &manifest.entries {
+        let file = paths::to_local(directory, path);
+        if !file.is_file() {
+            verification.missing.push(path.clone());
+            continue;
+        }
+        let digests = transfer::file_digests(&file).with_context(|| format!("Failed to read {}", file.display()))?;
+        verification.record(path, digests.iter().find_map(|digest| expected.matches(digest)));
+    }
+
+    for entry in WalkDir::new(directory) {
+        let entry = entry?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let key = paths::to_key(directory, entry.path())?;
+        if !key.starts_with(".lakectl/") && !manifest.entries.contains_key(&key) {
+            verification.extra.push(key);
+        }
+    }
+    verification.extra.sort();
+    Ok(verification)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lakefs_test_utils::scenario::MockLakeFS;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_manifest_round_trip() {
+        let text = "\
+# data/v1
+5d41402abc4b2a76b9719d911017c592  a.csv
+2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824 *dir/b.csv
+
+0123456789abcdef0123456789abcdef-3  big.parquet
+";
+        let manifest = Manifest::parse(text).unwrap();
+        assert_eq!(manifest.entries.len(), 3);
+        assert!(matches!(manifest.entries["dir/b.csv"], Checksum::Sha256(_)));
+        assert!(manifest.render().starts_with("5d41402abc4b2a76b9719d911017c592  a.csv\n"));
+        assert_eq!(Manifest::parse(&manifest.render()).unwrap(), manifest);
+
+        let error = Manifest::parse("5d41402abc4b2a76b9719d911017c592\n").unwrap_err();
+        assert!(error.to_string().contains("Line 1"));
+    }
+
+    #[tokio::test]
+    async fn test_verify_ref_and_directory() {
+        let server = MockLakeFS::new()
+            .with_repo("repo")
+            .with_branch("main")
+            .with_object("data/a.csv", "hello")
+            .with_object("data/b.csv", "b")
+            .with_branch("dev")
+            .with_object("data/a.csv", "changed")
+            .with_object("data/c.csv", "c")
+            .start()
+            .await;
+        let client = LakeFSClient::new(server.uri(), "Bearer test-token");
+        let manifest = Manifest::of_objects(&client, &LakeFSUri::new("repo", "main").with_path("data")).await.unwrap();
+        assert_eq!(manifest.entries.keys().collect::<Vec<_>>(), ["a.csv", "b.csv"]);
+
+        let dev = Manifest::of_objects(&client, &LakeFSUri::new("repo", "dev").with_path("data/")).await.unwrap();
+        let verification = verify_objects(&manifest, &dev);
+        assert_eq!(verification, Verification {
+            matched: 0,
+            mismatched: vec!["a.csv".into()],
+            missing: vec!["b.csv".into()],
+            extra: vec!["c.csv".into()],
+            unverifiable: vec![],
+        });
+        assert!(!verification.passed());
+        assert!(verify_objects(&manifest, &manifest).passed());
+
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("a.csv"), "hello").unwrap();
+        std::fs::write(temp_dir.path().join("b.csv"), "not b").unwrap();
+        std::fs::write(temp_dir.path().join("notes.txt"), "extra").unwrap();
+        std::fs::create_dir(temp_dir.path().join(".lakectl")).unwrap();
+        std::fs::write(temp_dir.path().join(".lakectl/index.json"), "{}").unwrap();
+        let verification = verify_directory(&manifest, temp_dir.path()).unwrap();
+        assert_eq!((verification.matched, verification.mismatched), (1, vec!["b.csv".to_string()]));
+        assert_eq!(verification.extra, ["notes.txt"]);
+
+        let multipart = Manifest::parse("0123456789abcdef0123456789abcdef-2  a.csv").unwrap();
+        assert_eq!(verify_directory(&multipart, temp_dir.path()).unwrap().unverifiable, ["a.csv"]);
+    }
+}
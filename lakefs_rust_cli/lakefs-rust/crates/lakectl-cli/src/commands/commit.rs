@@ -1,8 +1,35 @@
-use crate::utils::parse_uri;
+use crate::config::CommitConfig;
+use crate::utils::{parse_metadata, parse_uri, resolve_uri};
 use anyhow::Result;
-use lakefs_api::LakeFSClient;
+use lakefs_api::{walk_commits, Commit, LakeFSClient};
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::fmt::Write;
 use tabled::{Table, Tabled};
 
+/// How `lakectl log` prints the history.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LogFormat {
+    /// The branch's history as a table
+    Table,
+    /// Every parent of every commit, drawn as an ASCII graph
+    Graph,
+    /// The same graph in Graphviz DOT
+    Dot,
+}
+
+impl LogFormat {
+    pub fn from_flags(graph: bool, dot: bool) -> Self {
+        if dot {
+            LogFormat::Dot
+        } else if graph {
+            LogFormat::Graph
+        } else {
+            LogFormat::Table
+        }
+    }
+}
+
 #[derive(Tabled)]
 struct CommitRow {
     id: String,
@@ -15,14 +42,17 @@ pub async fn execute(
     branch: String,
     message: String,
     _allow_empty: bool,  // Currently unused
+    metadata: Vec<String>,
     client: LakeFSClient,
 ) -> Result<()> {
     let uri = parse_uri(&branch)?;
+    let metadata = parse_metadata(&metadata)?;
     
-    let commit = client.commit(
+    let commit = client.commit_with_metadata(
         &uri.repository,
         &uri.reference,
         &message,
+        &metadata,
     ).await?;
     
     println!("Created commit: {}", commit.id);
@@ -33,8 +63,106 @@ pub async fn execute(
     Ok(())
 }
 
-pub async fn log(branch: String, amount: usize, client: LakeFSClient) -> Result<()> {
+/// Replace the branch tip with a commit holding the same changes but a new
+/// message and/or metadata.
+pub async fn amend(
+    branch: String,
+    message: Option<String>,
+    metadata: Vec<String>,
+    expected_head: Option<String>,
+    client: LakeFSClient,
+) -> Result<()> {
     let uri = parse_uri(&branch)?;
+    let metadata = if metadata.is_empty() {
+        None
+    } else {
+        Some(parse_metadata(&metadata)?)
+    };
+    
+    let commit = client.amend_commit(
+        &uri.repository,
+        &uri.reference,
+        expected_head.as_deref(),
+        message.as_deref(),
+        metadata.as_ref(),
+    ).await?;
+    
+    println!("Amended commit: {}", commit.id);
+    println!("Message: {}", commit.message);
+    println!("Committer: {}", commit.committer);
+    println!("Date: {}", commit.creation_date.format("%Y-%m-%d %H:%M:%S"));
+    
+    Ok(())
+}
+
+/// Refuse `message` unless it ends with every trailer `rules` requires, each
+/// with a value. Keys match regardless of case, as in git.
+pub fn verify_message(message: &str, rules: &CommitConfig) -> Result<()> {
+    let trailers = trailers(message);
+    let missing: Vec<String> = rules
+        .required_trailers
+        .iter()
+        .map(|key| key.trim().trim_end_matches(':'))
+        .filter(|key| !trailers.iter().any(|(k, v)| k.eq_ignore_ascii_case(key) && !v.is_empty()))
+        .map(|key| format!("'{}: ...'", key))
+        .collect();
+    if !missing.is_empty() {
+        anyhow::bail!(
+            "Commit message is missing the required trailer{} {}; end the message with a paragraph of trailers, or pass --no-verify",
+            if missing.len() == 1 { "" } else { "s" },
+            missing.join(", "),
+        );
+    }
+    Ok(())
+}
+
+/// Check the `-m` message of `lakectl commit` against `rules`. Without one,
+/// a new commit would get an empty message, so it is refused when trailers
+/// are required; an amend keeps the message it had.
+pub fn verify_options(message: Option<&str>, amend: bool, rules: &CommitConfig) -> Result<()> {
+    match message {
+        Some(message) => verify_message(message, rules),
+        None if !amend && !rules.required_trailers.is_empty() => anyhow::bail!(
+            "Commit message is required, as it must end with the trailers {}; pass -m, or --no-verify",
+            rules.required_trailers.join(", "),
+        ),
+        None => Ok(()),
+    }
+}
+
+/// The `Key: value` trailers of `message`: its last paragraph, if every line
+/// of it is one and it is not the subject.
+fn trailers(message: &str) -> Vec<(&str, &str)> {
+    let lines: Vec<&str> = message.trim().lines().collect();
+    let Some(blank) = lines.iter().rposition(|line| line.trim().is_empty()) else {
+        return Vec::new();
+    };
+    lines[blank + 1..]
+        .iter()
+        .map(|line| {
+            let (key, value) = line.split_once(':')?;
+            let is_token = !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+            is_token.then(|| (key, value.trim()))
+        })
+        .collect::<Option<Vec<_>>>()
+        .unwrap_or_default()
+}
+
+pub async fn log(branch: String, amount: usize, format: LogFormat, client: LakeFSClient) -> Result<()> {
+    let uri = resolve_uri(&client, &branch).await?;
+    
+    if format != LogFormat::Table {
+        let commits = walk_commits(&client, &uri.repository, &uri.reference, amount).await?;
+        match format {
+            LogFormat::Dot => print!("{}", dot(&commits)),
+            _ => {
+                for line in graph_lines(&commits) {
+                    println!("{}", line);
+                }
+            }
+        }
+        return Ok(());
+    }
     
     let response = client.log_commits(&uri.repository, &uri.reference).await?;
     
@@ -54,4 +182,206 @@ pub async fn log(branch: String, amount: usize, client: LakeFSClient) -> Result<
     println!("{}", table);
     
     Ok(())
-}
\ No newline at end of file
+}
+pub(super) fn short_id(id: &str) -> &str {
+    &id[..id.len().min(8)]
+}
+
+pub(super) fn subject(commit: &Commit) -> &str {
+    commit.message.lines().next().unwrap_or("")
+}
+
+/// Draw `commits`, each listed before its parents, the way `git log --graph`
+/// does: a column per line of history, `*` on the commit, `\\` where a merge
+/// opens a column for another parent and `/` where a column joins another.
+fn graph_lines(commits: &[Commit]) -> Vec<String> {
+    let mut lines = Vec::new();
+    // Commit each column is waiting for
+    let mut columns: Vec<String> = Vec::new();
+    
+    for commit in commits {
+        let column = match columns.iter().position(|id| *id == commit.id) {
+            Some(column) => column,
+            None => {
+                columns.push(commit.id.clone());
+                columns.len() - 1
+            }
+        };
+        
+        let marks: Vec<&str> = (0..columns.len()).map(|c| if c == column { "*" } else { "|" }).collect();
+        lines.push(format!("{} {} {}", marks.join(" "), short_id(&commit.id), subject(commit)));
+        
+        // Parents already waited for elsewhere are joined; the rest take over
+        // this column and the ones opened right after it
+        let mut next = columns.clone();
+        let opened: Vec<&String> = commit.parents.iter().filter(|p| !columns.contains(p)).collect();
+        match opened.split_first() {
+            Some((first, rest)) => {
+                next[column] = first.to_string();
+                for (offset, parent) in rest.iter().enumerate() {
+                    next.insert(column + 1 + offset, parent.to_string());
+                }
+            }
+            None => {
+                next.remove(column);
+            }
+        }
+        
+        let position = |id: &String| next.iter().position(|n| n == id).expect("every column continues");
+        let mut edges: Vec<(usize, usize)> = Vec::new();
+        for (c, id) in columns.iter().enumerate() {
+            if c == column {
+                edges.extend(commit.parents.iter().map(|parent| (c, position(parent))));
+            } else {
+                edges.push((c, position(id)));
+            }
+        }
+        lines.extend(transition(&edges));
+        columns = next;
+    }
+    lines
+}
+
+/// Rows moving each `(from, to)` column edge one step at a time until every
+/// edge has reached its column.
+fn transition(edges: &[(usize, usize)]) -> Vec<String> {
+    let mut rows = Vec::new();
+    let mut at: Vec<usize> = edges.iter().map(|(from, _)| *from).collect();
+    while edges.iter().zip(&at).any(|((_, to), at)| at != to) {
+        let width = at.iter().chain(edges.iter().map(|(_, to)| to)).max().map_or(0, |max| 2 * max + 2);
+        let mut row = vec![' '; width];
+        for ((_, to), at) in edges.iter().zip(at.iter_mut()) {
+            match (*at).cmp(to) {
+                Ordering::Equal => row[2 * *at] = '|',
+                Ordering::Greater => {
+                    row[2 * *at - 1] = '/';
+                    *at -= 1;
+                }
+                Ordering::Less => {
+                    row[2 * *at + 1] = '\\';
+                    *at += 1;
+                }
+            }
+        }
+        rows.push(row.into_iter().collect::<String>().trim_end().to_string());
+    }
+    rows
+}
+
+/// `commits` as a Graphviz digraph, with edges from each commit to those of
+/// its parents that were walked.
+fn dot(commits: &[Commit]) -> String {
+    let escape = |text: &str| text.replace('\\', "\\\\").replace('"', "\\\"");
+    let walked: HashSet<&str> = commits.iter().map(|c| c.id.as_str()).collect();
+    
+    let mut out = String::from("digraph commits {\n    node [shape=box, fontname=monospace];\n");
+    for commit in commits {
+        let _ = writeln!(out, "    \"{}\" [label=\"{}\\n{}\"];", commit.id, short_id(&commit.id), escape(subject(commit)));
+    }
+    for commit in commits {
+        for parent in commit.parents.iter().filter(|p| walked.contains(p.as_str())) {
+            let _ = writeln!(out, "    \"{}\" -> \"{}\";", commit.id, parent);
+        }
+    }
+    out.push_str("}\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use std::collections::HashMap;
+    
+    fn commit(id: &str, parents: &[&str]) -> Commit {
+        Commit {
+            id: id.to_string(),
+            parents: parents.iter().map(|p| p.to_string()).collect(),
+            committer: "user".to_string(),
+            message: format!("Commit \"{}\"\n\nbody", id),
+            creation_date: Utc::now(),
+            meta_range_id: String::new(),
+            metadata: HashMap::new(),
+            generation: None,
+            version: None,
+        }
+    }
+    
+    /// main: a - b - c - m, with feature f branched from a and merged in m
+    fn history() -> Vec<Commit> {
+        vec![
+            commit("m", &["c", "f"]),
+            commit("c", &["b"]),
+            commit("f", &["a"]),
+            commit("b", &["a"]),
+            commit("a", &[]),
+        ]
+    }
+    
+    #[test]
+    fn test_graph_lines() {
+        assert_eq!(graph_lines(&history()), [
+            "* m Commit \"m\"",
+            "|\\",
+            "* | c Commit \"c\"",
+            "| * f Commit \"f\"",
+            "* | b Commit \"b\"",
+            "|/",
+            "* a Commit \"a\"",
+        ]);
+    }
+    
+    #[test]
+    fn test_graph_shifts_columns_right_of_a_merge() {
+        let commits = [commit("z", &["y"]), commit("w", &["v"]), commit("y", &["p", "q"])];
+        assert_eq!(graph_lines(&commits), [
+            "* z Commit \"z\"",
+            "| * w Commit \"w\"",
+            "* | y Commit \"y\"",
+            "|\\ \\",
+        ]);
+    }
+    
+    #[test]
+    fn test_dot_links_walked_parents() {
+        let dot = dot(&history()[..2]);
+        assert!(dot.starts_with("digraph commits {\n"));
+        assert!(dot.contains("    \"m\" [label=\"m\\nCommit \\\"m\\\"\"];\n"));
+        assert!(dot.contains("    \"m\" -> \"c\";\n"));
+        assert!(!dot.contains("\"f\""));
+        assert!(dot.ends_with("}\n"));
+    }
+    
+    #[test]
+    fn test_trailers_come_from_the_last_paragraph() {
+        let message = "Add data\n\nSee: the docs, twice\n\nJira: DATA-42\nSigned-off-by: a <a@b.c>\n";
+        assert_eq!(trailers(message), [("Jira", "DATA-42"), ("Signed-off-by", "a <a@b.c>")]);
+        assert!(trailers("Jira: DATA-42").is_empty());
+        assert!(trailers("Add data\n\nJira: DATA-42\nnot a trailer").is_empty());
+    }
+    
+    #[test]
+    fn test_verify_message_requires_trailers() {
+        let rules = CommitConfig {
+            required_trailers: vec!["Jira".to_string(), "Reviewed-by:".to_string()],
+            ..Default::default()
+        };
+        assert!(verify_message("Add data\n\njira: DATA-42\nReviewed-by: b", &rules).is_ok());
+        assert!(verify_message("Add data", &CommitConfig::default()).is_ok());
+        
+        let error = verify_message("Add data\n\nJira:\nReviewed-by: b", &rules).unwrap_err();
+        assert!(error.to_string().contains("missing the required trailer 'Jira: ...'"), "{}", error);
+        let error = verify_message("Add data", &rules).unwrap_err();
+        assert!(error.to_string().contains("trailers 'Jira: ...', 'Reviewed-by: ...'"), "{}", error);
+    }
+    
+    #[test]
+    fn test_verify_options_requires_a_message() {
+        let rules = CommitConfig { required_trailers: vec!["Jira".to_string()], ..Default::default() };
+        let error = verify_options(None, false, &rules).unwrap_err();
+        assert!(error.to_string().contains("Commit message is required"), "{}", error);
+        assert!(verify_options(None, true, &rules).is_ok());
+        assert!(verify_options(None, false, &CommitConfig::default()).is_ok());
+        assert!(verify_options(Some("Add data"), true, &rules).is_err());
+    }
+}
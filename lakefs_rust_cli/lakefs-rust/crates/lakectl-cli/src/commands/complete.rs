@@ -0,0 +1,82 @@
+use anyhow::Result;
+use lakefs_api::{LakeFSClient, PaginationParams};
+use std::io::{self, Write};
+use std::time::Duration;
+
+// Hidden plumbing commands backing shell completion and editor integrations.
+// They fetch a single page with a short timeout and print bare names, one per
+// line, so callers never wait long on a slow or unreachable server.
+
+/// Request timeout for completion commands.
+pub const TIMEOUT: Duration = Duration::from_secs(3);
+/// Names fetched per completion request; only the first page is listed.
+const PAGE_SIZE: usize = 1000;
+
+pub async fn list_repos(prefix: Option<String>, client: LakeFSClient) -> Result<()> {
+    print_names(repo_names(prefix, &client).await?)
+}
+
+pub async fn list_refs(repository: String, prefix: Option<String>, client: LakeFSClient) -> Result<()> {
+    print_names(ref_names(&repository, prefix, &client).await?)
+}
+
+async fn repo_names(prefix: Option<String>, client: &LakeFSClient) -> Result<Vec<String>> {
+    let params = PaginationParams { prefix, after: None, amount: Some(PAGE_SIZE), ..Default::default() };
+    let page = client.list_repositories_with(&params).await?;
+    Ok(page.results.into_iter().map(|r| r.id).collect())
+}
+
+async fn ref_names(repository: &str, prefix: Option<String>, client: &LakeFSClient) -> Result<Vec<String>> {
+    // Completion may hand us either a bare name or a partially typed URI
+    let repository = repository.strip_prefix("lakefs://").unwrap_or(repository);
+    let repository = repository.split('/').next().unwrap_or_default();
+
+    let params = PaginationParams { prefix, after: None, amount: Some(PAGE_SIZE), ..Default::default() };
+    let (branches, tags) = futures::try_join!(
+        client.list_branches_with(repository, &params),
+        client.list_tags_with(repository, &params),
+    )?;
+    let mut names: Vec<String> = branches.results.into_iter().map(|b| b.id)
+        .chain(tags.results.into_iter().map(|t| t.id))
+        .collect();
+    names.sort();
+    names.dedup();
+    Ok(names)
+}
+
+fn print_names(names: Vec<String>) -> Result<()> {
+    let mut stdout = io::stdout().lock();
+    for name in names {
+        writeln!(stdout, "{}", name)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lakefs_test_utils::scenario::MockLakeFS;
+
+    #[tokio::test]
+    async fn test_completion_names() {
+        let server = MockLakeFS::new()
+            .with_repo("analytics")
+            .with_branch("main")
+            .with_branch("dev")
+            .with_tag("v1")
+            .with_repo("archive")
+            .with_repo("images")
+            .start()
+            .await;
+        let client = LakeFSClient::new(server.uri(), "Bearer test-token");
+
+        let repos = repo_names(Some("a".to_string()), &client).await.unwrap();
+        assert_eq!(repos, vec!["analytics", "archive"]);
+
+        let refs = ref_names("lakefs://analytics/", None, &client).await.unwrap();
+        assert_eq!(refs, vec!["dev", "main", "v1"]);
+
+        let refs = ref_names("analytics", Some("v".to_string()), &client).await.unwrap();
+        assert_eq!(refs, vec!["v1"]);
+    }
+}
@@ -0,0 +1,85 @@
+use crate::cli::ConfigCommands;
+use crate::config::{
+    check_config, config_value, keyring_reference, load_config_from, masked_config, set_config_value, store_secret,
+    write_keyring_credentials, AppConfig,
+};
+use anyhow::{Context, Result};
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+pub fn execute(command: ConfigCommands, config_path: Option<PathBuf>) -> Result<()> {
+    match command {
+        ConfigCommands::SetCredentials { access_key_id, secret_access_key } => {
+            let path = config_path.context("Could not determine the config file location; pass --config")?;
+            let secret_access_key = match secret_access_key {
+                Some(secret) => secret,
+                None => prompt_secret()?,
+            };
+
+            store_secret(&access_key_id, &secret_access_key)?;
+            write_keyring_credentials(&path, &access_key_id)?;
+
+            println!("Stored secret for '{}' in the OS keyring", access_key_id);
+            println!("Updated {} to use {}", path.display(), keyring_reference(&access_key_id));
+        }
+        ConfigCommands::Validate => {
+            let source = match &config_path {
+                Some(path) if path.exists() => path.display().to_string(),
+                _ => "the environment (no config file found)".to_string(),
+            };
+            let report = check_config(config_path)?;
+            for warning in &report.warnings {
+                eprintln!("warning: {}", warning);
+            }
+            for error in &report.errors {
+                eprintln!("error: {}", error);
+            }
+            if !report.errors.is_empty() {
+                anyhow::bail!("{} has {} error(s)", source, report.errors.len());
+            }
+            println!("Configuration from {} is valid", source);
+        }
+        ConfigCommands::Show { repository } => {
+            let config = repository_config(config_path, repository)?;
+            print!("{}", serde_yaml::to_string(&masked_config(&config)?)?);
+        }
+        ConfigCommands::Get { key, repository } => {
+            let config = repository_config(config_path, repository)?;
+            match config_value(&config, &key)? {
+                Some(serde_yaml::Value::String(value)) => println!("{}", value),
+                Some(value) => print!("{}", serde_yaml::to_string(&value)?),
+                None => anyhow::bail!("{} is not set", key),
+            }
+        }
+        ConfigCommands::Set { key, value } => {
+            let path = config_path.context("Could not determine the config file location; pass --config")?;
+            set_config_value(&path, &key, &value)?;
+            println!("Set {} in {}", key, path.display());
+        }
+    }
+
+    Ok(())
+}
+
+/// The configuration in effect, for commands on `repository` if given.
+fn repository_config(config_path: Option<PathBuf>, repository: Option<String>) -> Result<AppConfig> {
+    let config = load_config_from(config_path)?;
+    match repository {
+        Some(repository) => config.for_repository(&repository),
+        None => Ok(config),
+    }
+}
+
+fn prompt_secret() -> Result<String> {
+    print!("Secret access key: ");
+    io::stdout().flush()?;
+
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+
+    let secret = input.trim();
+    if secret.is_empty() {
+        anyhow::bail!("Secret access key cannot be empty");
+    }
+    Ok(secret.to_string())
+}
@@ -0,0 +1,580 @@
+use crate::cli::DaemonCommands;
+use crate::config::OptionsConfig;
+use crate::utils::parse_duration;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use lakefs_api::{LakeFSClient, LakeFSUri};
+use lakefs_local::metrics::DURATION_BUCKETS;
+use lakefs_local::{LinkStrategy, LocalIndex, SyncConfig, SyncManager, SyncMetrics};
+use metrics_exporter_prometheus::PrometheusBuilder;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio_util::sync::CancellationToken;
+
+// The daemon listens on a Unix socket and speaks JSON-RPC 2.0, one request
+// and one response per line. Methods: `status`, `sync_now` (optional `path`
+// param), `metrics` (Prometheus text) and `shutdown`.
+
+/// Default control socket, in the user's runtime directory or else in
+/// `~/.lakectl`, never in a temporary directory other users can write to.
+pub fn default_socket_path() -> PathBuf {
+    dirs::runtime_dir()
+        .or_else(|| dirs::home_dir().map(|home| home.join(".lakectl")))
+        .unwrap_or_default()
+        .join("lakectl-daemon.sock")
+}
+
+fn socket_path(socket: Option<&str>) -> PathBuf {
+    socket.map(PathBuf::from).unwrap_or_else(default_socket_path)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Request {
+    jsonrpc: String,
+    id: Value,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Response {
+    jsonrpc: String,
+    id: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    result: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    error: Option<RpcError>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+const PARSE_ERROR: i64 = -32700;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+/// Outcome of a checkout's most recent sync.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct LastSync {
+    at: Option<DateTime<Utc>>,
+    uploaded: usize,
+    downloaded: usize,
+    removed: usize,
+    errors: usize,
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CheckoutStatus {
+    path: PathBuf,
+    remote: String,
+    last_sync: LastSync,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct DaemonStatus {
+    pid: u32,
+    started_at: DateTime<Utc>,
+    interval_secs: u64,
+    checkouts: Vec<CheckoutStatus>,
+}
+
+struct Checkout {
+    path: PathBuf,
+    remote: LakeFSUri,
+    /// Held for the whole sync so scheduled and requested syncs never overlap
+    running: Mutex<()>,
+    /// Replaced once a sync finishes, so status reads never wait on one
+    last_sync: RwLock<LastSync>,
+}
+
+struct Daemon {
+    client: LakeFSClient,
+    parallelism: usize,
+    object_cache: Option<PathBuf>,
+    link_strategy: LinkStrategy,
+    ignore_permissions: bool,
+    delta_threshold: Option<u64>,
+    interval: Duration,
+    started_at: DateTime<Utc>,
+    checkouts: Vec<Checkout>,
+    metrics: Arc<SyncMetrics>,
+    metrics_file: Option<PathBuf>,
+    shutdown: CancellationToken,
+}
+
+impl Daemon {
+    fn new(
+        client: LakeFSClient,
+        options: &OptionsConfig,
+        paths: &[String],
+        interval: Duration,
+        metrics: Arc<SyncMetrics>,
+        shutdown: CancellationToken,
+    ) -> Result<Self> {
+        let checkouts = paths
+            .iter()
+            .map(|path| {
+                let path = std::fs::canonicalize(path)
+                    .with_context(|| format!("Checkout {} does not exist", path))?;
+                let index = LocalIndex::load(&path)
+                    .with_context(|| format!("{} is not a lakectl checkout", path.display()))?;
+                Ok(Checkout {
+                    remote: LakeFSUri::new(&index.repository, &index.reference),
+                    path,
+                    running: Mutex::new(()),
+                    last_sync: RwLock::new(LastSync::default()),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            client,
+            parallelism: options.parallelism,
+            object_cache: options.object_cache.clone(),
+            link_strategy: options.link_strategy,
+            ignore_permissions: options.ignore_permissions,
+            delta_threshold: options.delta_threshold,
+            interval,
+            started_at: Utc::now(),
+            checkouts,
+            metrics,
+            metrics_file: None,
+            shutdown,
+        })
+    }
+
+    async fn sync_checkout(&self, checkout: &Checkout) -> LastSync {
+        let _running = checkout.running.lock().await;
+
+        let config = SyncConfig {
+            parallelism: self.parallelism,
+            object_cache: self.object_cache.clone(),
+            link_strategy: self.link_strategy,
+            ignore_permissions: self.ignore_permissions,
+            delta_threshold: self.delta_threshold,
+            ..Default::default()
+        };
+        let manager = SyncManager::new(Arc::new(self.client.clone()), config)
+            .with_cancellation(self.shutdown.child_token())
+            .with_metrics(self.metrics.clone());
+
+        let last_sync = match manager.sync(&checkout.path, &checkout.remote).await {
+            Ok(result) => LastSync {
+                at: Some(Utc::now()),
+                uploaded: result.uploaded,
+                downloaded: result.downloaded,
+                removed: result.removed,
+                errors: result.errors.len(),
+                error: None,
+            },
+            Err(e) => LastSync {
+                at: Some(Utc::now()),
+                error: Some(e.to_string()),
+                ..Default::default()
+            },
+        };
+        if let Some(error) = &last_sync.error {
+            tracing::warn!(path = %checkout.path.display(), %error, "scheduled sync failed");
+        }
+        *checkout.last_sync.write().unwrap() = last_sync.clone();
+        last_sync
+    }
+
+    /// Sync every checkout, or only the one at `path`.
+    async fn sync_now(&self, path: Option<&Path>) -> std::result::Result<Vec<CheckoutStatus>, String> {
+        let path = path
+            .map(|p| std::fs::canonicalize(p).map_err(|e| format!("{}: {}", p.display(), e)))
+            .transpose()?;
+        let selected: Vec<&Checkout> = self.checkouts
+            .iter()
+            .filter(|checkout| path.as_ref().is_none_or(|p| *p == checkout.path))
+            .collect();
+        if selected.is_empty() {
+            return Err("No registered checkout at that path".to_string());
+        }
+
+        let mut statuses = Vec::new();
+        for checkout in selected {
+            let last_sync = self.sync_checkout(checkout).await;
+            statuses.push(CheckoutStatus {
+                path: checkout.path.clone(),
+                remote: checkout.remote.to_string(),
+                last_sync,
+            });
+        }
+        self.write_metrics();
+        Ok(statuses)
+    }
+
+    /// Status of every checkout, reporting the previous run of one being
+    /// synced instead of waiting for it.
+    fn status(&self) -> DaemonStatus {
+        let checkouts = self.checkouts
+            .iter()
+            .map(|checkout| CheckoutStatus {
+                path: checkout.path.clone(),
+                remote: checkout.remote.to_string(),
+                last_sync: checkout.last_sync.read().unwrap().clone(),
+            })
+            .collect();
+
+        DaemonStatus {
+            pid: std::process::id(),
+            started_at: self.started_at,
+            interval_secs: self.interval.as_secs(),
+            checkouts,
+        }
+    }
+
+    fn write_metrics(&self) {
+        if let Some(path) = &self.metrics_file {
+            if let Err(e) = std::fs::write(path, self.metrics.render_prometheus()) {
+                tracing::warn!(path = %path.display(), error = %e, "failed to write metrics");
+            }
+        }
+    }
+
+    async fn handle(&self, line: &str) -> Response {
+        let request: Request = match serde_json::from_str(line) {
+            Ok(request) => request,
+            Err(e) => return Response::error(Value::Null, PARSE_ERROR, e.to_string()),
+        };
+        let id = request.id;
+
+        match request.method.as_str() {
+            "status" => Response::result(id, json!(self.status())),
+            "sync_now" => {
+                let path = match request.params.get("path") {
+                    None | Some(Value::Null) => None,
+                    Some(Value::String(path)) => Some(PathBuf::from(path)),
+                    Some(_) => return Response::error(id, INVALID_PARAMS, "path must be a string".to_string()),
+                };
+                match self.sync_now(path.as_deref()).await {
+                    Ok(statuses) => Response::result(id, json!(statuses)),
+                    Err(message) => Response::error(id, INVALID_PARAMS, message),
+                }
+            }
+            "metrics" => Response::result(id, json!(self.metrics.render_prometheus())),
+            "shutdown" => {
+                self.shutdown.cancel();
+                Response::result(id, json!(true))
+            }
+            method => Response::error(id, METHOD_NOT_FOUND, format!("Unknown method '{}'", method)),
+        }
+    }
+}
+
+impl Response {
+    fn result(id: Value, result: Value) -> Self {
+        Self { jsonrpc: "2.0".to_string(), id, result: Some(result), error: None }
+    }
+
+    fn error(id: Value, code: i64, message: String) -> Self {
+        Self { jsonrpc: "2.0".to_string(), id, result: None, error: Some(RpcError { code, message }) }
+    }
+}
+
+/// Run the daemon until it is stopped over the socket or `cancel` fires.
+/// With `metrics_listen`, the `metrics` facade is exported over HTTP.
+#[cfg(unix)]
+#[allow(clippy::too_many_arguments)]
+pub async fn start(
+    paths: Vec<String>,
+    interval: String,
+    socket: Option<String>,
+    client: LakeFSClient,
+    options: &OptionsConfig,
+    metrics: Arc<SyncMetrics>,
+    metrics_file: Option<PathBuf>,
+    metrics_listen: Option<SocketAddr>,
+    cancel: CancellationToken,
+) -> Result<()> {
+    let interval = parse_duration(&interval)?;
+    if interval.is_zero() {
+        anyhow::bail!("--interval must be greater than zero");
+    }
+    let socket = socket_path(socket.as_deref());
+
+    if let Some(addr) = metrics_listen {
+        serve_metrics(addr)?;
+    }
+    let mut daemon = Daemon::new(client, options, &paths, interval, metrics, cancel)?;
+    daemon.metrics_file = metrics_file;
+
+    let listener = unix::bind(&socket).await?;
+    println!(
+        "lakectl daemon (pid {}) syncing {} checkout(s) every {}s, control socket {}",
+        std::process::id(),
+        daemon.checkouts.len(),
+        interval.as_secs(),
+        socket.display()
+    );
+
+    let result = unix::serve(Arc::new(daemon), listener).await;
+    let _ = std::fs::remove_file(&socket);
+    result
+}
+
+#[cfg(not(unix))]
+#[allow(clippy::too_many_arguments)]
+pub async fn start(
+    _paths: Vec<String>,
+    _interval: String,
+    _socket: Option<String>,
+    _client: LakeFSClient,
+    _options: &OptionsConfig,
+    _metrics: Arc<SyncMetrics>,
+    _metrics_file: Option<PathBuf>,
+    _metrics_listen: Option<SocketAddr>,
+    _cancel: CancellationToken,
+) -> Result<()> {
+    anyhow::bail!("The daemon requires Unix domain sockets, which this platform does not support")
+}
+
+/// Install a Prometheus recorder for the `metrics` facade, answering scrapes
+/// on `addr` from the current runtime.
+#[cfg(unix)]
+fn serve_metrics(addr: SocketAddr) -> Result<()> {
+    PrometheusBuilder::new()
+        .with_http_listener(addr)
+        .set_buckets(&DURATION_BUCKETS)?
+        .install()
+        .with_context(|| format!("Failed to serve metrics on {}", addr))?;
+    SyncMetrics::describe();
+    Ok(())
+}
+
+/// Send a control command to a running daemon and print its reply.
+pub async fn control(command: &DaemonCommands) -> Result<()> {
+    match command {
+        DaemonCommands::Start { .. } => unreachable!("the daemon is started with a configured client"),
+        DaemonCommands::Status { socket } => {
+            let status: DaemonStatus = serde_json::from_value(call(socket.as_deref(), "status", json!({})).await?)?;
+            println!("Daemon running (pid {}) since {}", status.pid, status.started_at.format("%Y-%m-%d %H:%M:%S"));
+            println!("Sync interval: {}s", status.interval_secs);
+            for checkout in &status.checkouts {
+                print_checkout(checkout);
+            }
+        }
+        DaemonCommands::SyncNow { path, socket } => {
+            let params = json!({ "path": path });
+            let statuses: Vec<CheckoutStatus> = serde_json::from_value(call(socket.as_deref(), "sync_now", params).await?)?;
+            for checkout in &statuses {
+                print_checkout(checkout);
+            }
+        }
+        DaemonCommands::Stop { socket } => {
+            call(socket.as_deref(), "shutdown", json!({})).await?;
+            println!("Daemon stopped");
+        }
+    }
+
+    Ok(())
+}
+
+fn print_checkout(checkout: &CheckoutStatus) {
+    let last = &checkout.last_sync;
+    let summary = match (&last.at, &last.error) {
+        (None, _) => "not synced yet".to_string(),
+        (Some(at), Some(error)) => format!("failed at {}: {}", at.format("%Y-%m-%d %H:%M:%S"), error),
+        (Some(at), None) => format!(
+            "synced at {} (Downloaded: {}, Uploaded: {}, Removed: {}, Errors: {})",
+            at.format("%Y-%m-%d %H:%M:%S"),
+            last.downloaded,
+            last.uploaded,
+            last.removed,
+            last.errors
+        ),
+    };
+    println!("{} <-> {}: {}", checkout.path.display(), checkout.remote, summary);
+}
+
+#[cfg(unix)]
+async fn call(socket: Option<&str>, method: &str, params: Value) -> Result<Value> {
+    unix::call(&socket_path(socket), method, params).await
+}
+
+#[cfg(not(unix))]
+async fn call(_socket: Option<&str>, _method: &str, _params: Value) -> Result<Value> {
+    anyhow::bail!("The daemon requires Unix domain sockets, which this platform does not support")
+}
+
+#[cfg(unix)]
+mod unix {
+    use super::*;
+    use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::{UnixListener, UnixStream};
+
+    /// Bind the control socket, replacing a stale one left by a daemon that
+    /// did not shut down cleanly. Only the owner may connect to it.
+    pub(super) async fn bind(socket: &Path) -> Result<UnixListener> {
+        if socket.exists() {
+            if UnixStream::connect(socket).await.is_ok() {
+                anyhow::bail!("A daemon is already listening on {}", socket.display());
+            }
+            std::fs::remove_file(socket)?;
+        }
+        if let Some(parent) = socket.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            std::fs::DirBuilder::new().recursive(true).mode(0o700).create(parent)?;
+        }
+        let listener = UnixListener::bind(socket).with_context(|| format!("Failed to bind {}", socket.display()))?;
+        std::fs::set_permissions(socket, std::fs::Permissions::from_mode(0o600))?;
+        Ok(listener)
+    }
+
+    pub(super) async fn serve(daemon: Arc<Daemon>, listener: UnixListener) -> Result<()> {
+        let scheduler = {
+            let daemon = daemon.clone();
+            tokio::spawn(async move {
+                let mut ticks = tokio::time::interval(daemon.interval);
+                ticks.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+                loop {
+                    tokio::select! {
+                        _ = daemon.shutdown.cancelled() => break,
+                        _ = ticks.tick() => {
+                            let _ = daemon.sync_now(None).await;
+                        }
+                    }
+                }
+            })
+        };
+
+        loop {
+            tokio::select! {
+                _ = daemon.shutdown.cancelled() => break,
+                accepted = listener.accept() => {
+                    let (stream, _) = accepted?;
+                    tokio::spawn(handle_connection(daemon.clone(), stream));
+                }
+            }
+        }
+
+        scheduler.await?;
+        Ok(())
+    }
+
+    async fn handle_connection(daemon: Arc<Daemon>, stream: UnixStream) {
+        let (reader, mut writer) = stream.into_split();
+        let mut lines = BufReader::new(reader).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let response = daemon.handle(&line).await;
+            let Ok(mut reply) = serde_json::to_string(&response) else {
+                break;
+            };
+            reply.push('\n');
+            if writer.write_all(reply.as_bytes()).await.is_err() {
+                break;
+            }
+        }
+    }
+
+    pub(super) async fn call(socket: &Path, method: &str, params: Value) -> Result<Value> {
+        let stream = UnixStream::connect(socket)
+            .await
+            .with_context(|| format!("Could not reach the daemon at {}; is it running?", socket.display()))?;
+        let (reader, mut writer) = stream.into_split();
+
+        let request = Request {
+            jsonrpc: "2.0".to_string(),
+            id: json!(1),
+            method: method.to_string(),
+            params,
+        };
+        let mut line = serde_json::to_string(&request)?;
+        line.push('\n');
+        writer.write_all(line.as_bytes()).await?;
+
+        let reply = BufReader::new(reader)
+            .lines()
+            .next_line()
+            .await?
+            .context("The daemon closed the connection without replying")?;
+        let response: Response = serde_json::from_str(&reply)?;
+        match (response.result, response.error) {
+            (_, Some(error)) => anyhow::bail!("Daemon error: {}", error.message),
+            (Some(result), None) => Ok(result),
+            (None, None) => anyhow::bail!("Daemon reply has neither a result nor an error"),
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use lakefs_test_utils::scenario::MockLakeFS;
+    use std::os::unix::fs::PermissionsExt;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_daemon_control_api() {
+        let server = MockLakeFS::new()
+            .with_repo("repo")
+            .with_branch("main")
+            .with_object("data/a.csv", "a,b\n")
+            .start()
+            .await;
+        let client = LakeFSClient::new(server.uri(), "Bearer test-token");
+
+        let temp_dir = TempDir::new().unwrap();
+        let checkout = temp_dir.path().join("checkout");
+        std::fs::create_dir(&checkout).unwrap();
+        LocalIndex::new("repo", "main", "c0").save(&checkout).unwrap();
+        let socket = temp_dir.path().join("daemon.sock");
+        let metrics_addr = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+        serve_metrics(metrics_addr).unwrap();
+
+        let shutdown = CancellationToken::new();
+        let daemon = Daemon::new(
+            client,
+            &OptionsConfig::default(),
+            &[checkout.to_string_lossy().to_string()],
+            Duration::from_secs(3600),
+            Arc::new(SyncMetrics::new()),
+            shutdown.clone(),
+        ).unwrap();
+        let listener = unix::bind(&socket).await.unwrap();
+        assert_eq!(std::fs::metadata(&socket).unwrap().permissions().mode() & 0o777, 0o600);
+        let served = tokio::spawn(unix::serve(Arc::new(daemon), listener));
+
+        let synced: Vec<CheckoutStatus> = serde_json::from_value(
+            unix::call(&socket, "sync_now", json!({ "path": checkout })).await.unwrap()
+        ).unwrap();
+        assert_eq!(synced.len(), 1);
+        assert_eq!(synced[0].remote, "lakefs://repo/main");
+        assert!(synced[0].last_sync.error.is_none());
+        assert!(checkout.join("data/a.csv").exists());
+
+        let status: DaemonStatus = serde_json::from_value(
+            unix::call(&socket, "status", json!({})).await.unwrap()
+        ).unwrap();
+        assert_eq!(status.pid, std::process::id());
+        assert_eq!(status.checkouts.len(), 1);
+        assert!(status.checkouts[0].last_sync.at.is_some());
+
+        let metrics = unix::call(&socket, "metrics", json!({})).await.unwrap();
+        assert!(metrics.as_str().unwrap().contains("lakectl_sync_runs_total"));
+        let scraped = reqwest::get(format!("http://{}/metrics", metrics_addr)).await.unwrap().text().await.unwrap();
+        assert!(scraped.contains("lakectl_sync_objects_total{direction=\"download\"} 1"), "{}", scraped);
+
+        let unknown = unix::call(&socket, "reboot", json!({})).await.unwrap_err();
+        assert!(unknown.to_string().contains("Unknown method"));
+
+        unix::call(&socket, "shutdown", json!({})).await.unwrap();
+        served.await.unwrap().unwrap();
+        assert!(shutdown.is_cancelled());
+    }
+}
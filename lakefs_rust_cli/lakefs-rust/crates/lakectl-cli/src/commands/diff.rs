@@ -1,17 +1,48 @@
-use crate::utils::{parse_uri, format_diff_type};
+use crate::utils::{resolve_uri, format_diff_type, format_size};
 use anyhow::Result;
-use lakefs_api::LakeFSClient;
+use lakefs_api::models::{Diff, DiffType};
+use lakefs_api::{LakeFSClient, PaginationParams};
+use std::collections::HashMap;
+
+/// How `lakectl diff` prints the differences.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DiffFormat {
+    /// One `+`/`-`/`~` marker and path per line
+    Default,
+    /// Changed paths only
+    NameOnly,
+    /// Counts by change type and the total size change
+    Stat,
+    /// Left and right paths side by side
+    TwoColumn,
+}
+
+impl DiffFormat {
+    pub fn from_flags(name_only: bool, stat: bool, two_column: bool) -> Self {
+        if name_only {
+            DiffFormat::NameOnly
+        } else if stat {
+            DiffFormat::Stat
+        } else if two_column {
+            DiffFormat::TwoColumn
+        } else {
+            DiffFormat::Default
+        }
+    }
+}
 
 pub async fn execute(
     left: String,
     right: Option<String>,
+    prefix: Option<String>,
+    format: DiffFormat,
     client: LakeFSClient,
 ) -> Result<()> {
-    let left_uri = parse_uri(&left)?;
+    let left_uri = resolve_uri(&client, &left).await?;
     
     let (right_repo, right_ref) = match &right {
         Some(r) => {
-            let right_uri = parse_uri(r)?;
+            let right_uri = resolve_uri(&client, r).await?;
             (right_uri.repository, right_uri.reference)
         }
         None => {
@@ -24,25 +55,245 @@ pub async fn execute(
         anyhow::bail!("Cannot diff across different repositories");
     }
     
-    let diff_result = client.diff(
-        &left_uri.repository,
-        &left_uri.reference,
-        &right_ref,
-    ).await?;
+    let diffs = collect_diffs(&client, &left_uri.repository, &left_uri.reference, &right_ref, prefix).await?;
     
-    if diff_result.results.is_empty() {
-        println!("No differences found");
+    if diffs.is_empty() {
+        if format != DiffFormat::NameOnly {
+            println!("No differences found");
+        }
         return Ok(());
     }
     
     let right_str = right.as_deref().unwrap_or("working tree");
-    println!("Differences between {} and {}:", left, right_str);
-    println!();
+    match format {
+        DiffFormat::NameOnly => {
+            for diff in diffs {
+                println!("{}", diff.path);
+            }
+        }
+        DiffFormat::Stat => {
+            // The working tree is the branch itself, compared with its last commit
+            let (left_stat_ref, right_stat_ref) = match right {
+                Some(_) => (left_uri.reference.clone(), right_ref),
+                None => (format!("{}@", left_uri.reference), left_uri.reference.clone()),
+            };
+            let stat = DiffStat::collect(&client, &left_uri.repository, &left_stat_ref, &right_stat_ref, &diffs).await?;
+            println!("{}", stat.summary());
+        }
+        DiffFormat::TwoColumn => {
+            print!("{}", two_column(&left, right_str, &diffs));
+        }
+        DiffFormat::Default => {
+            println!("Differences between {} and {}:", left, right_str);
+            println!();
     
-    for diff in diff_result.results {
-        let diff_type = format_diff_type(&diff.diff_type.to_string());
-        println!("{} {}", diff_type, diff.path);
+            for diff in diffs {
+                let diff_type = format_diff_type(&diff.diff_type.to_string());
+                println!("{} {}", diff_type, diff.path);
+            }
+        }
     }
     
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Fetch every page of the diff between two refs. The prefix is applied by
+/// the server.
+pub async fn collect_diffs(
+    client: &LakeFSClient,
+    repository: &str,
+    left_ref: &str,
+    right_ref: &str,
+    prefix: Option<String>,
+) -> Result<Vec<Diff>> {
+    let mut diffs = Vec::new();
+    let mut params = PaginationParams { prefix, after: None, amount: None, ..Default::default() };
+    loop {
+        let page = client.diff_with(repository, left_ref, right_ref, &params).await?;
+        diffs.extend(page.results);
+        
+        match page.pagination.next_offset {
+            Some(next) if page.pagination.has_more => params.after = Some(next),
+            _ => break,
+        }
+    }
+    Ok(diffs)
+}
+
+/// Concurrent stat requests used to size changed objects.
+const STAT_CONCURRENCY: usize = 16;
+
+#[derive(Debug, Default, PartialEq)]
+struct DiffStat {
+    added: usize,
+    removed: usize,
+    changed: usize,
+    /// Bytes gained (positive) or freed (negative) going from left to right
+    size_delta: i64,
+}
+
+impl DiffStat {
+    async fn collect(
+        client: &LakeFSClient,
+        repository: &str,
+        left_ref: &str,
+        right_ref: &str,
+        diffs: &[Diff],
+    ) -> Result<Self> {
+        let mut stat = DiffStat::default();
+        let mut left_paths = Vec::new();
+        let mut right_paths = Vec::new();
+    
+        for diff in diffs {
+            match diff.diff_type {
+                DiffType::Added => {
+                    stat.added += 1;
+                    right_paths.push(diff.path.clone());
+                }
+                DiffType::Removed => {
+                    stat.removed += 1;
+                    left_paths.push(diff.path.clone());
+                }
+                DiffType::Changed => {
+                    stat.changed += 1;
+                    left_paths.push(diff.path.clone());
+                    right_paths.push(diff.path.clone());
+                }
+            }
+        }
+    
+        let left_sizes = sizes(client, repository, left_ref, &left_paths).await?;
+        let right_sizes = sizes(client, repository, right_ref, &right_paths).await?;
+        stat.size_delta = right_sizes.values().sum::<i64>() - left_sizes.values().sum::<i64>();
+    
+        Ok(stat)
+    }
+    
+    fn summary(&self) -> String {
+        let sign = if self.size_delta < 0 { "-" } else { "+" };
+        format!(
+            "{} added, {} removed, {} changed; size change {}{}",
+            self.added,
+            self.removed,
+            self.changed,
+            sign,
+            format_size(self.size_delta.abs())
+        )
+    }
+}
+
+async fn sizes(
+    client: &LakeFSClient,
+    repository: &str,
+    reference: &str,
+    paths: &[String],
+) -> Result<HashMap<String, i64>> {
+    let mut sizes = HashMap::new();
+    for (path, stats) in client.stat_objects(repository, reference, paths, STAT_CONCURRENCY).await {
+        sizes.insert(path, stats?.size_bytes);
+    }
+    Ok(sizes)
+}
+
+/// Render removed paths in the left column, added paths in the right and
+/// changed paths in both, aligned on the widest left entry.
+fn two_column(left_title: &str, right_title: &str, diffs: &[Diff]) -> String {
+    let rows: Vec<(&str, &str)> = diffs
+        .iter()
+        .map(|diff| match diff.diff_type {
+            DiffType::Added => ("", diff.path.as_str()),
+            DiffType::Removed => (diff.path.as_str(), ""),
+            DiffType::Changed => (diff.path.as_str(), diff.path.as_str()),
+        })
+        .collect();
+    
+    let width = rows
+        .iter()
+        .map(|(left, _)| left.chars().count())
+        .chain(std::iter::once(left_title.chars().count()))
+        .max()
+        .unwrap_or(0);
+    
+    let mut output = format!("{:<width$} | {}\n", left_title, right_title, width = width);
+    for (left, right) in rows {
+        let line = format!("{:<width$} | {}", left, right, width = width);
+        output.push_str(line.trim_end());
+        output.push('\n');
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lakefs_api::models::PathType;
+    use lakefs_test_utils::scenario::MockLakeFS;
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, ResponseTemplate};
+
+    fn diff(path: &str, diff_type: DiffType) -> Diff {
+        Diff {
+            path: path.to_string(),
+            path_type: PathType::Object,
+            diff_type,
+            size_bytes: None,
+        }
+    }
+
+    #[test]
+    fn test_two_column_alignment() {
+        let diffs = vec![
+            diff("data/new.csv", DiffType::Added),
+            diff("data/gone.csv", DiffType::Removed),
+            diff("readme.md", DiffType::Changed),
+        ];
+
+        let output = two_column("main", "dev", &diffs);
+        assert_eq!(output, "\
+main          | dev
+              | data/new.csv
+data/gone.csv |
+readme.md     | readme.md
+");
+    }
+
+    #[tokio::test]
+    async fn test_diff_stat_sizes() {
+        let server = MockLakeFS::new()
+            .with_repo("repo")
+            .with_branch("main")
+            .with_object("gone.csv", "12345")
+            .with_object("readme.md", "hi")
+            .with_branch("dev")
+            .with_object("new.csv", "1234567890")
+            .with_object("readme.md", "hello")
+            .start()
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repositories/repo/refs/main/diff/dev"))
+            .and(query_param("prefix", "data/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "results": [],
+                "pagination": { "has_more": false, "max_per_page": 1000, "next_offset": null, "results": 0 }
+            })))
+            .mount(&server)
+            .await;
+        let client = LakeFSClient::new(server.uri(), "Bearer test-token");
+
+        let diffs = vec![
+            diff("new.csv", DiffType::Added),
+            diff("gone.csv", DiffType::Removed),
+            diff("readme.md", DiffType::Changed),
+        ];
+        let stat = DiffStat::collect(&client, "repo", "main", "dev", &diffs).await.unwrap();
+        assert_eq!(stat, DiffStat { added: 1, removed: 1, changed: 1, size_delta: 10 - 5 + 5 - 2 });
+
+        execute(
+            "lakefs://repo/main".to_string(),
+            Some("lakefs://repo/dev".to_string()),
+            Some("data/".to_string()),
+            DiffFormat::Stat,
+            client,
+        ).await.unwrap();
+    }
+}
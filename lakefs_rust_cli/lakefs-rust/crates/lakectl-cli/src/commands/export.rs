@@ -0,0 +1,445 @@
+use super::fs::{directory_prefix, list_all_objects};
+use super::replicate::verify;
+use crate::utils::{format_size, resolve_uri};
+use anyhow::{Context, Result};
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use futures::{Stream, StreamExt};
+use lakefs_api::models::ObjectStats;
+use lakefs_api::{Checksum, LakeFSClient, PaginationParams};
+use lakefs_auth::ObjectStoreConfig;
+use lakefs_local::paths;
+use md5::{Digest, Md5};
+use serde::Serialize;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+/// Written at the root of the destination once every object is in place, so
+/// its presence marks a complete export.
+const MANIFEST_NAME: &str = "_lakefs_export.json";
+
+/// Validity of the URLs objects are PUT to
+const PUT_EXPIRY: Duration = Duration::from_secs(60 * 60);
+
+/// Largest object S3 stores from one PUT; larger ones go up in parts
+const MAX_PUT_SIZE: u64 = 5 * 1024 * 1024 * 1024;
+
+/// Smallest part of a multipart upload. Each part is read into memory
+/// before it is sent.
+const PART_SIZE: u64 = 64 * 1024 * 1024;
+
+/// Most parts a multipart upload may have
+const MAX_PARTS: u64 = 10_000;
+
+/// What was exported, and from which commit.
+#[derive(Debug, Serialize)]
+struct ExportManifest {
+    source: String,
+    commit: String,
+    exported_at: DateTime<Utc>,
+    objects: Vec<ExportedObject>,
+}
+
+#[derive(Debug, Serialize)]
+struct ExportedObject {
+    /// Relative to the destination
+    key: String,
+    size_bytes: i64,
+    checksum: Checksum,
+}
+
+/// Where an export is written.
+#[derive(Debug)]
+enum Target {
+    /// `file://path`
+    Directory(PathBuf),
+    /// `s3://bucket/prefix`, written through URLs signed with the configured
+    /// storage keys
+    Bucket { storage: ObjectStoreConfig, prefix: String },
+}
+
+impl Target {
+    fn parse(destination: &str, storage: Option<&ObjectStoreConfig>) -> Result<Self> {
+        if let Some(path) = destination.strip_prefix("file://") {
+            return Ok(Target::Directory(PathBuf::from(path)));
+        }
+        let Some((bucket, prefix)) = destination.strip_prefix("s3://").map(|rest| rest.split_once('/').unwrap_or((rest, ""))) else {
+            anyhow::bail!("Invalid export destination '{}': expected s3://bucket/prefix or file://path", destination);
+        };
+        if bucket.is_empty() {
+            anyhow::bail!("Invalid export destination '{}': missing bucket", destination);
+        }
+        Ok(Target::Bucket {
+            storage: storage.cloned().unwrap_or_default(),
+            prefix: format!("s3://{}/{}", bucket, directory_prefix(Some(prefix))),
+        })
+    }
+
+    /// Write `body`, of `size` bytes, as `key` under the target.
+    async fn write(
+        &self,
+        http: &reqwest::Client,
+        key: &str,
+        size: u64,
+        body: impl Stream<Item = std::io::Result<Bytes>> + Send + Sync + 'static,
+    ) -> Result<()> {
+        match self {
+            Target::Directory(root) => {
+                paths::check_key(key)?;
+                let path = paths::to_local(root, key);
+                if let Some(parent) = path.parent() {
+                    tokio::fs::create_dir_all(parent).await?;
+                }
+                let mut partial = path.clone().into_os_string();
+                partial.push(".part");
+
+                let mut file = tokio::fs::File::create(&partial).await?;
+                let mut body = std::pin::pin!(body);
+                while let Some(chunk) = body.next().await {
+                    file.write_all(&chunk?).await?;
+                }
+                file.flush().await?;
+                drop(file);
+                tokio::fs::rename(&partial, &path).await?;
+            }
+            Target::Bucket { storage, prefix } if size > MAX_PUT_SIZE => {
+                let part_size = PART_SIZE.max(size.div_ceil(MAX_PARTS));
+                write_multipart(http, storage, &format!("{}{}", prefix, key), part_size, body).await?;
+            }
+            Target::Bucket { storage, prefix } => {
+                let url = storage.presign("PUT", &format!("{}{}", prefix, key), PUT_EXPIRY).await?;
+                let response = http
+                    .put(url)
+                    .header(reqwest::header::CONTENT_LENGTH, size)
+                    .body(reqwest::Body::wrap_stream(body))
+                    .send()
+                    .await?;
+                if !response.status().is_success() {
+                    let status = response.status();
+                    anyhow::bail!("store refused the upload: {} {}", status, response.text().await.unwrap_or_default());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Delete `key` under the target, if it is there.
+    async fn remove(&self, http: &reqwest::Client, key: &str) -> Result<()> {
+        match self {
+            Target::Directory(root) => match tokio::fs::remove_file(paths::to_local(root, key)).await {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+                _ => {}
+            },
+            Target::Bucket { storage, prefix } => {
+                let url = storage.presign("DELETE", &format!("{}{}", prefix, key), PUT_EXPIRY).await?;
+                let response = http.delete(url).send().await?;
+                if !response.status().is_success() && response.status() != reqwest::StatusCode::NOT_FOUND {
+                    anyhow::bail!("store refused to delete {}: {}", key, response.status());
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Upload `body` to an `s3://bucket/key` address in parts of `part_size`
+/// bytes, aborting the upload if any part fails.
+async fn write_multipart(
+    http: &reqwest::Client,
+    storage: &ObjectStoreConfig,
+    address: &str,
+    part_size: u64,
+    body: impl Stream<Item = std::io::Result<Bytes>>,
+) -> Result<()> {
+    let upload_id = storage.create_multipart_upload(address).await?;
+    let uploaded = async {
+        let part_size = part_size as usize;
+        let mut body = std::pin::pin!(body);
+        let mut part = Vec::with_capacity(part_size);
+        let mut etags = Vec::new();
+        loop {
+            let chunk = body.next().await.transpose()?;
+            if let Some(chunk) = &chunk {
+                part.extend_from_slice(chunk);
+            }
+            while part.len() >= part_size || (chunk.is_none() && !part.is_empty()) {
+                let rest = part.split_off(part_size.min(part.len()));
+                let data = std::mem::replace(&mut part, rest);
+                let url = storage.presign_upload_part(address, &upload_id, etags.len() + 1, PUT_EXPIRY).await?;
+                let response = http.put(url).body(data).send().await?;
+                if !response.status().is_success() {
+                    let status = response.status();
+                    anyhow::bail!("store refused part {}: {} {}", etags.len() + 1, status, response.text().await.unwrap_or_default());
+                }
+                let etag = response
+                    .headers()
+                    .get(reqwest::header::ETAG)
+                    .and_then(|etag| etag.to_str().ok())
+                    .ok_or_else(|| anyhow::anyhow!("store sent no ETag for part {}", etags.len() + 1))?;
+                etags.push(etag.to_string());
+            }
+            if chunk.is_none() {
+                break;
+            }
+        }
+        storage.complete_multipart_upload(address, &upload_id, &etags).await?;
+        Ok(())
+    }.await;
+
+    if uploaded.is_err() {
+        if let Err(e) = storage.abort_multipart_upload(address, &upload_id).await {
+            eprintln!("Failed to abort the upload of {}: {}", address, e);
+        }
+    }
+    uploaded
+}
+
+/// Copy the objects of a reference to a plain, unversioned layout in a bucket
+/// or directory, keyed by their path under the source.
+///
+/// The reference is pinned to its current commit first, so uncommitted
+/// changes are not exported and commits made meanwhile do not mix in. lakeFS
+/// exports by running a Spark job outside its API, so objects are streamed
+/// through this client instead. Objects already at the destination are
+/// overwritten and others there are left alone.
+pub async fn execute(
+    source: String,
+    destination: String,
+    parallelism: usize,
+    storage: Option<&ObjectStoreConfig>,
+    client: LakeFSClient,
+) -> Result<()> {
+    let target = Arc::new(Target::parse(&destination, storage)?);
+    let source = resolve_uri(&client, &source).await?;
+    let params = PaginationParams { prefix: None, after: None, amount: Some(1), ..Default::default() };
+    let commit = client
+        .log_commits_with(&source.repository, &source.reference, &params)
+        .await?
+        .results
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("{} has no commits to export", source.reference))?;
+
+    let source_prefix = directory_prefix(source.path.as_deref());
+    let objects = list_all_objects(&client, &source.repository, &commit.id, &source_prefix).await?;
+
+    // A manifest left by an earlier export would vouch for a mix of both
+    let http = reqwest::Client::new();
+    target.remove(&http, MANIFEST_NAME).await?;
+    let semaphore = Arc::new(Semaphore::new(parallelism.max(1)));
+    let mut tasks = JoinSet::new();
+    for object in objects {
+        let key = object.path.strip_prefix(&source_prefix).unwrap_or(&object.path).to_string();
+        let (client, http, target, semaphore) = (client.clone(), http.clone(), target.clone(), semaphore.clone());
+        let (repository, commit_id) = (source.repository.clone(), commit.id.clone());
+        tasks.spawn(async move {
+            let _permit = semaphore.acquire().await?;
+            export_object(&client, &http, &repository, &commit_id, &target, object, key.clone())
+                .await
+                .with_context(|| format!("export {}", key))
+        });
+    }
+
+    let mut exported = Vec::new();
+    let mut failed = 0;
+    while let Some(joined) = tasks.join_next().await {
+        match joined? {
+            Ok(object) => exported.push(object),
+            Err(e) => {
+                eprintln!("Failed to {:#}", e);
+                failed += 1;
+            }
+        }
+    }
+    if failed > 0 {
+        anyhow::bail!("{} of {} objects failed to export; the destination has no manifest", failed, exported.len() + failed);
+    }
+
+    exported.sort_by(|a, b| a.key.cmp(&b.key));
+    let bytes: i64 = exported.iter().map(|o| o.size_bytes).sum();
+    let count = exported.len();
+    let manifest = ExportManifest {
+        source: source.to_string(),
+        commit: commit.id.clone(),
+        exported_at: Utc::now(),
+        objects: exported,
+    };
+    let body = Bytes::from(serde_json::to_vec_pretty(&manifest)?);
+    let size = body.len() as u64;
+    target.write(&http, MANIFEST_NAME, size, futures::stream::once(async { Ok(body) })).await?;
+
+    println!("Exported {} objects ({}) from commit {} to {}", count, format_size(bytes), commit.id, destination);
+    Ok(())
+}
+
+/// Stream one object to the target, checking what was read against its stats.
+async fn export_object(
+    client: &LakeFSClient,
+    http: &reqwest::Client,
+    repository: &str,
+    commit_id: &str,
+    target: &Target,
+    object: ObjectStats,
+    key: String,
+) -> Result<ExportedObject> {
+    let hasher = Arc::new(Mutex::new((Md5::new(), 0u64)));
+    let tap = hasher.clone();
+    let stream = client
+        .download_object_stream(repository, commit_id, &object.path)
+        .await?
+        .map(move |chunk| {
+            let chunk = chunk.map_err(std::io::Error::other)?;
+            let mut state = tap.lock().unwrap();
+            state.0.update(&chunk);
+            state.1 += chunk.len() as u64;
+            Ok(chunk)
+        });
+    target.write(http, &key, object.size_bytes as u64, stream).await?;
+
+    let (hasher, size) = std::mem::take(&mut *hasher.lock().unwrap());
+    verify(&object, &Checksum::Md5(format!("{:x}", hasher.finalize())), size)?;
+    Ok(ExportedObject { key, size_bytes: object.size_bytes, checksum: object.checksum })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lakefs_test_utils::scenario::MockLakeFS;
+    use tempfile::TempDir;
+    use wiremock::matchers::{body_string, body_string_contains, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[test]
+    fn test_target_parse() {
+        let target = Target::parse("s3://bucket/exports/daily", None).unwrap();
+        assert!(matches!(target, Target::Bucket { prefix, .. } if prefix == "s3://bucket/exports/daily/"));
+        let target = Target::parse("s3://bucket", None).unwrap();
+        assert!(matches!(target, Target::Bucket { prefix, .. } if prefix == "s3://bucket/"));
+        let target = Target::parse("file:///tmp/export", None).unwrap();
+        assert!(matches!(target, Target::Directory(path) if path == std::path::Path::new("/tmp/export")));
+
+        assert!(Target::parse("s3:///prefix", None).is_err());
+        assert!(Target::parse("gs://bucket", None).is_err());
+        assert!(Target::parse("./export", None).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_export_to_directory() {
+        let server = MockLakeFS::new()
+            .with_repo("repo")
+            .with_branch("main")
+            .with_object("data/a.csv", "a")
+            .with_object("data/sub/b.csv", "bb")
+            .with_object("other/c.csv", "c")
+            .start()
+            .await;
+        // The mock serves objects by branch name, so the head commit is named after it
+        Mock::given(method("GET"))
+            .and(path("/repositories/repo/branches/main/commits"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "results": [{
+                    "id": "main",
+                    "parents": [],
+                    "committer": "user",
+                    "message": "add data",
+                    "creation_date": 1_700_000_000,
+                    "meta_range_id": "",
+                    "metadata": {}
+                }],
+                "pagination": {"has_more": false, "max_per_page": 1, "results": 1}
+            })))
+            .mount(&server)
+            .await;
+        let client = LakeFSClient::new(server.uri(), "Bearer test-token");
+        let temp_dir = TempDir::new().unwrap();
+        let destination = format!("file://{}", temp_dir.path().join("out").display());
+
+        execute("lakefs://repo/main/data".to_string(), destination, 2, None, client).await.unwrap();
+
+        let out = temp_dir.path().join("out");
+        assert_eq!(std::fs::read_to_string(out.join("a.csv")).unwrap(), "a");
+        assert_eq!(std::fs::read_to_string(out.join("sub/b.csv")).unwrap(), "bb");
+        assert!(!out.join("other").exists());
+
+        let manifest: serde_json::Value = serde_json::from_slice(&std::fs::read(out.join(MANIFEST_NAME)).unwrap()).unwrap();
+        assert_eq!(manifest["commit"], "main");
+        let keys: Vec<&str> = manifest["objects"].as_array().unwrap().iter().map(|o| o["key"].as_str().unwrap()).collect();
+        assert_eq!(keys, ["a.csv", "sub/b.csv"]);
+    }
+
+    fn storage(store: &MockServer) -> ObjectStoreConfig {
+        ObjectStoreConfig {
+            endpoint: Some(store.uri()),
+            force_path_style: true,
+            access_key_id: Some("key".into()),
+            secret_access_key: Some("secret".into()),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_write_multipart() {
+        let store = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/bucket/big.bin"))
+            .and(query_param("uploads", ""))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                "<InitiateMultipartUploadResult><Bucket>bucket</Bucket><Key>big.bin</Key>\
+                 <UploadId>up/1</UploadId></InitiateMultipartUploadResult>",
+            ))
+            .expect(1)
+            .mount(&store)
+            .await;
+        for (number, data) in [("1", "abcd"), ("2", "efgh"), ("3", "ij")] {
+            Mock::given(method("PUT"))
+                .and(path("/bucket/big.bin"))
+                .and(query_param("partNumber", number))
+                .and(query_param("uploadId", "up/1"))
+                .and(body_string(data))
+                .respond_with(ResponseTemplate::new(200).insert_header("ETag", format!("\"etag{}\"", number).as_str()))
+                .expect(1)
+                .mount(&store)
+                .await;
+        }
+        Mock::given(method("POST"))
+            .and(path("/bucket/big.bin"))
+            .and(query_param("uploadId", "up/1"))
+            .and(body_string_contains("<Part><PartNumber>3</PartNumber><ETag>\"etag3\"</ETag></Part></CompleteMultipartUpload>"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("<CompleteMultipartUploadResult/>"))
+            .expect(1)
+            .mount(&store)
+            .await;
+
+        let body = futures::stream::iter(["abc", "defghi", "j"].map(|chunk| Ok(Bytes::from(chunk))));
+        write_multipart(&reqwest::Client::new(), &storage(&store), "s3://bucket/big.bin", 4, body).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_write_multipart_aborts_on_failure() {
+        let store = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(query_param("uploads", ""))
+            .respond_with(ResponseTemplate::new(200).set_body_string("<Result><UploadId>up</UploadId></Result>"))
+            .mount(&store)
+            .await;
+        Mock::given(method("PUT"))
+            .respond_with(ResponseTemplate::new(500))
+            .mount(&store)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path("/bucket/big.bin"))
+            .and(query_param("uploadId", "up"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&store)
+            .await;
+
+        let body = futures::stream::iter([Ok(Bytes::from("abcdef"))]);
+        let error = write_multipart(&reqwest::Client::new(), &storage(&store), "s3://bucket/big.bin", 4, body).await.unwrap_err();
+        assert!(error.to_string().contains("part 1"), "{}", error);
+    }
+}
@@ -1,13 +1,50 @@
+use super::archive;
+use super::checksum::{self, Manifest};
 use crate::cli::FsCommands;
 use crate::config::OptionsConfig;
-use crate::utils::{parse_uri, format_size};
-use anyhow::Result;
-use bytes::Bytes;
-use indicatif::{ProgressBar, ProgressStyle};
-use lakefs_api::{LakeFSClient, models::PathType};
-use std::path::Path;
+use crate::utils::{parse_uri, require_branch, resolve_uri, parse_duration, parse_metadata, parse_size, format_size};
+use anyhow::{Context, Result};
+use globset::{Glob, GlobMatcher};
+use futures::StreamExt;
+use lakefs_api::{download::download_objects, path_history, Checksum, DownloadEvent, DownloadOptions, DownloadRequest, LakeFSClient, LakeFSUri, ListEntry, ListObjectsOptions, PaginationParams, UploadOptions, models::{ObjectStats, PathType}};
+use lakefs_auth::ObjectStoreConfig;
+use lakefs_local::{content_type, paths, transfer};
+use lakefs_local::{Compression, MultiProgressReporter, NoopProgress, PathFilter, ProgressReporter};
+use std::collections::{BTreeMap, HashMap};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tabled::{Table, Tabled};
 use tokio::fs;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use walkdir::WalkDir;
+
+/// Local path standing for standard input as an `fs upload` source, and for
+/// standard output as an `fs download` destination.
+const STANDARD_STREAM: &str = "-";
+
+/// Lifetime of URLs `fs presign` signs locally when no `--expiry` is given.
+const DEFAULT_PRESIGN_EXPIRY: Duration = Duration::from_secs(60 * 60);
+/// Longest lifetime of an S3 SigV4 presigned URL.
+const MAX_PRESIGN_EXPIRY: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+#[derive(Tabled)]
+struct UsageRow {
+    size: String,
+    objects: u64,
+    path: String,
+}
+
+#[derive(Tabled)]
+struct ChangeRow {
+    change: String,
+    commit: String,
+    date: String,
+    committer: String,
+    message: String,
+}
 
 #[derive(Tabled)]
 struct ObjectRow {
@@ -22,27 +59,28 @@ pub async fn execute(
     command: FsCommands,
     client: LakeFSClient,
     options: &OptionsConfig,
+    storage: Option<&ObjectStoreConfig>,
 ) -> Result<()> {
     match command {
-        FsCommands::Ls { path, recursive: _ } => {
-            let uri = parse_uri(&path)?;
-            let response = client.list_objects(
-                &uri.repository,
-                &uri.reference,
-                uri.path.as_deref(),
-            ).await?;
+        FsCommands::Ls { path, recursive } => {
+            let uri = resolve_uri(&client, &path).await?;
+            let entries = list_entries(&client, &uri, recursive).await?;
             
-            let rows: Vec<ObjectRow> = response
-                .results
+            let rows: Vec<ObjectRow> = entries
                 .into_iter()
-                .map(|obj| ObjectRow {
-                    path_type: match obj.path_type {
-                        PathType::Directory => "dir".to_string(),
-                        PathType::Object => "file".to_string(),
+                .map(|entry| match entry {
+                    ListEntry::CommonPrefix(path) => ObjectRow {
+                        path_type: "dir".to_string(),
+                        path,
+                        size: String::new(),
+                        modified: String::new(),
+                    },
+                    ListEntry::Object(obj) => ObjectRow {
+                        path_type: "file".to_string(),
+                        path: obj.path,
+                        size: format_size(obj.size_bytes),
+                        modified: obj.mtime.format("%Y-%m-%d %H:%M:%S").to_string(),
                     },
-                    path: obj.path,
-                    size: format_size(obj.size_bytes),
-                    modified: obj.mtime.format("%Y-%m-%d %H:%M:%S").to_string(),
                 })
                 .collect();
             
@@ -50,13 +88,101 @@ pub async fn execute(
             println!("{}", table);
         }
         
+        FsCommands::Du { path, depth, bytes } => {
+            let uri = resolve_uri(&client, &path).await?;
+            let prefix = directory_prefix(uri.path.as_deref());
+            let objects = list_all_objects(&client, &uri.repository, &uri.reference, &prefix).await?;
+            
+            let size = |total: i64| if bytes { total.to_string() } else { format_size(total) };
+            let mut rows: Vec<UsageRow> = disk_usage(&objects, &prefix, depth)
+                .into_iter()
+                .map(|(directory, usage)| UsageRow {
+                    size: size(usage.bytes),
+                    objects: usage.objects,
+                    path: format!("{}{}/", prefix, directory),
+                })
+                .collect();
+            rows.push(UsageRow {
+                size: size(objects.iter().map(|o| o.size_bytes).sum()),
+                objects: objects.len() as u64,
+                path: format!("{} (total)", uri),
+            });
+            
+            println!("{}", Table::new(rows));
+        }
+        
+        FsCommands::Find {
+            path,
+            name,
+            min_size,
+            max_size,
+            metadata,
+            json,
+        } => {
+            let uri = resolve_uri(&client, &path).await?;
+            let query = FindQuery {
+                name: name
+                    .map(|pattern| Glob::new(&pattern).map(|glob| glob.compile_matcher()))
+                    .transpose()
+                    .context("Invalid --name pattern")?,
+                min_size: min_size.as_deref().map(parse_size).transpose()?,
+                max_size: max_size.as_deref().map(parse_size).transpose()?,
+                metadata: parse_metadata(&metadata)?,
+            };
+            let prefix = directory_prefix(uri.path.as_deref());
+            
+            let mut stdout = std::io::stdout().lock();
+            for_each_object(&client, &uri.repository, &uri.reference, &prefix, |object| {
+                if !query.matches(&object) {
+                    return Ok(());
+                }
+                if json {
+                    writeln!(stdout, "{}", serde_json::to_string(&object)?)?;
+                } else {
+                    writeln!(stdout, "lakefs://{}/{}/{}", uri.repository, uri.reference, object.path)?;
+                }
+                Ok(())
+            }).await?;
+        }
+        
         FsCommands::Download {
             source,
             destination,
-            recursive: _,
-            parallelism: _,
+            recursive,
+            parallelism,
+            filters,
         } => {
-            let uri = parse_uri(&source)?;
+            let uri = resolve_uri(&client, &source).await?;
+            if destination.as_deref() == Some(STANDARD_STREAM) {
+                if recursive {
+                    anyhow::bail!("--recursive cannot be used when downloading to standard output");
+                }
+                let path = uri.path.ok_or_else(|| {
+                    anyhow::anyhow!("Source URI must include a path")
+                })?;
+                let stats = client.get_object(&uri.repository, &uri.reference, &path).await?;
+                let mut stdout = tokio::io::stdout();
+                match transfer::download_to_writer(&client, &uri.repository, &uri.reference, &stats, &mut stdout).await {
+                    // The reader went away, as `head` does once it has enough
+                    Err(lakefs_local::Error::Io(e)) if e.kind() == std::io::ErrorKind::BrokenPipe => {}
+                    result => {
+                        result?;
+                    }
+                }
+                return Ok(());
+            }
+            if recursive {
+                let destination = PathBuf::from(destination.unwrap_or_else(|| ".".to_string()));
+                let reporter = progress_reporter(options);
+                let count = download_recursive(&client, &uri, &destination, parallelism, &filters.to_filter()?, &reporter).await?;
+                
+                reporter.finish(&format!("Downloaded {} objects to {}", count, destination.display()));
+                if !reporter.is_enabled() {
+                    println!("Downloaded {} objects to {}", count, destination.display());
+                }
+                return Ok(());
+            }
+            
             let path = uri.path.ok_or_else(|| {
                 anyhow::anyhow!("Source URI must include a path")
             })?;
@@ -67,32 +193,22 @@ pub async fn execute(
                     .unwrap_or_else(|| path.clone())
             });
             
-            // Download the object
-            let pb = if !options.no_progress {
-                let pb = ProgressBar::new(0);
-                pb.set_style(
-                    ProgressStyle::default_bar()
-                        .template("{spinner:.green} Downloading {msg}")
-                        .unwrap(),
-                );
-                pb.set_message(path.clone());
-                Some(pb)
-            } else {
-                None
-            };
+            let reporter = progress_reporter(options);
+            let stats = client.get_object(&uri.repository, &uri.reference, &path).await?;
             
-            let data = client.download_object(
+            transfer::download(
+                &client,
                 &uri.repository,
                 &uri.reference,
+                &stats,
+                Path::new(&destination),
                 &path,
+                None,
+                &reporter,
             ).await?;
             
-            // Write to file
-            fs::write(&destination, &data).await?;
-            
-            if let Some(pb) = pb {
-                pb.finish_with_message(format!("Downloaded {} to {}", path, destination));
-            } else {
+            reporter.finish(&format!("Downloaded {} to {}", path, destination));
+            if !reporter.is_enabled() {
                 println!("Downloaded {} to {}", path, destination);
             }
         }
@@ -101,9 +217,86 @@ pub async fn execute(
             source,
             destination,
             recursive,
-            parallelism: _,
+            parallelism,
+            compress,
+            size_hint,
+            content_type,
+            no_sniff,
+            expand_archive,
+            filters,
         } => {
             let uri = parse_uri(&destination)?;
+            require_branch(&client, &uri).await?;
+            let content_type = ContentType::new(content_type, no_sniff);
+            if expand_archive {
+                if source == STANDARD_STREAM {
+                    anyhow::bail!("--expand-archive needs an archive file, not standard input");
+                }
+                let reporter = progress_reporter(options);
+                let count = archive::expand_archive(
+                    &client,
+                    &uri,
+                    Path::new(&source),
+                    parallelism,
+                    &content_type,
+                    filters.to_filter()?,
+                    &reporter,
+                ).await?;
+                
+                reporter.finish(&format!("Uploaded {} files from {} to {}", count, source, uri));
+                if !reporter.is_enabled() {
+                    println!("Uploaded {} files from {} to {}", count, source, uri);
+                }
+                return Ok(());
+            }
+            if source == STANDARD_STREAM {
+                if recursive || compress.is_some() {
+                    anyhow::bail!("--recursive and --compress cannot be used when uploading from standard input");
+                }
+                let path = uri.path.ok_or_else(|| {
+                    anyhow::anyhow!("Destination URI must include a path")
+                })?;
+                let size_hint = size_hint.as_deref().map(parse_size).transpose()?;
+                let reporter = progress_reporter(options);
+                if let Some(size) = size_hint {
+                    reporter.set_total(1, size);
+                }
+                
+                let stats = transfer::upload_reader(
+                    &client,
+                    &uri.repository,
+                    &uri.reference,
+                    &path,
+                    tokio::io::stdin(),
+                    size_hint,
+                    "stdin",
+                    &content_type.upload_options(&path, None),
+                    None,
+                    &reporter,
+                ).await?;
+                
+                reporter.finish(&format!("Uploaded standard input to {}", path));
+                if !reporter.is_enabled() {
+                    println!("Uploaded standard input to {}", path);
+                }
+                println!("Size: {}", format_size(stats.size_bytes));
+                println!("Checksum: {}", stats.checksum);
+                return Ok(());
+            }
+            if size_hint.is_some() {
+                anyhow::bail!("--size-hint only applies when uploading from standard input (-)");
+            }
+            if recursive && fs::metadata(&source).await?.is_dir() {
+                let reporter = progress_reporter(options);
+                let count = upload_recursive(&client, &uri, Path::new(&source), parallelism, compress, &content_type, &filters.to_filter()?, &reporter).await?;
+                
+                reporter.finish(&format!("Uploaded {} files to {}", count, uri));
+                if !reporter.is_enabled() {
+                    println!("Uploaded {} files to {}", count, uri);
+                }
+                return Ok(());
+            }
+            
             let path = uri.path.ok_or_else(|| {
                 anyhow::anyhow!("Destination URI must include a path")
             })?;
@@ -115,33 +308,24 @@ pub async fn execute(
                 anyhow::bail!("Source is a directory. Use -r/--recursive to upload directories");
             }
             
-            // Read file content
-            let pb = if !options.no_progress {
-                let pb = ProgressBar::new(metadata.len());
-                pb.set_style(
-                    ProgressStyle::default_bar()
-                        .template("{spinner:.green} Uploading {msg}")
-                        .unwrap(),
-                );
-                pb.set_message(source.clone());
-                Some(pb)
-            } else {
-                None
-            };
-            
-            let data = fs::read(&source).await?;
+            let reporter = progress_reporter(options);
             
             // Upload the object
-            let stats = client.upload_object(
+            let stats = transfer::upload_file(
+                &client,
                 &uri.repository,
                 &uri.reference,
                 &path,
-                Bytes::from(data),
+                Path::new(&source),
+                &source,
+                &content_type.upload_options(&path, Some(Path::new(&source))),
+                compress,
+                None,
+                &reporter,
             ).await?;
             
-            if let Some(pb) = pb {
-                pb.finish_with_message(format!("Uploaded {} to {}", source, path));
-            } else {
+            reporter.finish(&format!("Uploaded {} to {}", source, path));
+            if !reporter.is_enabled() {
                 println!("Uploaded {} to {}", source, path);
             }
             
@@ -151,6 +335,7 @@ pub async fn execute(
         
         FsCommands::Rm { path, recursive: _ } => {
             let uri = parse_uri(&path)?;
+            require_branch(&client, &uri).await?;
             let object_path = uri.path.ok_or_else(|| {
                 anyhow::anyhow!("Path URI must include an object path")
             })?;
@@ -164,32 +349,983 @@ pub async fn execute(
             println!("Removed: {}", object_path);
         }
         
-        FsCommands::Stat { path } => {
-            let uri = parse_uri(&path)?;
+        FsCommands::Sync { source, destination, dry_run, no_delete, parallelism, filters } => {
+            let source = resolve_uri(&client, &source).await?;
+            let destination = parse_uri(&destination)?;
+            if source.repository != destination.repository {
+                anyhow::bail!("Cannot sync across different repositories");
+            }
+            require_branch(&client, &destination).await?;
+            
+            let source_prefix = directory_prefix(source.path.as_deref());
+            let dest_prefix = directory_prefix(destination.path.as_deref());
+            let source_objects = list_all_objects(&client, &source.repository, &source.reference, &source_prefix).await?;
+            let dest_objects = list_all_objects(&client, &destination.repository, &destination.reference, &dest_prefix).await?;
+            
+            let mut plan = plan_sync(&source_objects, &source_prefix, &dest_objects, &dest_prefix, &filters.to_filter()?);
+            if no_delete {
+                plan.retain(|action| matches!(action, SyncAction::Copy(_)));
+            }
+            
+            if plan.is_empty() {
+                println!("Destination is up to date");
+                return Ok(());
+            }
+            if dry_run {
+                for action in &plan {
+                    match action {
+                        SyncAction::Copy(relative) => println!("copy   {}{} -> {}{}", source_prefix, relative, dest_prefix, relative),
+                        SyncAction::Delete(relative) => println!("delete {}{}", dest_prefix, relative),
+                    }
+                }
+                return Ok(());
+            }
+            
+            let (copied, deleted) = apply_sync(&client, &source, &destination, plan, parallelism).await?;
+            println!("Copied {} and deleted {} objects on {}", copied, deleted, destination);
+        }
+        
+        FsCommands::Stat { paths, parallelism, json } => {
+            let paths = read_path_list(paths, std::io::stdin().lock())?;
+            if paths.is_empty() {
+                anyhow::bail!("No paths to stat");
+            }
+            
+            // A single path fails as before; with several, the others are
+            // still shown and the failures reported at the end
+            let single = paths.len() == 1;
+            let mut objects = Vec::new();
+            let mut failed = 0;
+            for (path, result) in stat_paths(&client, &paths, parallelism).await {
+                match result {
+                    Ok(stats) => objects.push(stats),
+                    Err(e) if single => return Err(e),
+                    Err(e) => {
+                        eprintln!("{}: {:#}", path, e);
+                        failed += 1;
+                    }
+                }
+            }
+            
+            if json {
+                println!("{}", serde_json::to_string_pretty(&objects)?);
+            } else {
+                for (i, stats) in objects.into_iter().enumerate() {
+                    if i > 0 {
+                        println!();
+                    }
+                    println!("Path: {}", stats.path);
+                    println!("Type: {:?}", stats.path_type);
+                    println!("Size: {}", format_size(stats.size_bytes));
+                    println!("Modified: {}", stats.mtime.format("%Y-%m-%d %H:%M:%S"));
+                    println!("Checksum: {}", stats.checksum);
+                    
+                    if let Some(metadata) = stats.metadata {
+                        println!("Metadata:");
+                        for (key, value) in metadata {
+                            println!("  {}: {}", key, value);
+                        }
+                    }
+                }
+            }
+            if failed > 0 {
+                anyhow::bail!("Could not stat {} of {} paths", failed, paths.len());
+            }
+        }
+        
+        FsCommands::Blame { path, history } => {
+            let uri = resolve_uri(&client, &path).await?;
+            let object_path = uri.path.ok_or_else(|| {
+                anyhow::anyhow!("Path URI must include an object path")
+            })?;
+            
+            let limit = if history { None } else { Some(1) };
+            let changes = path_history(&client, &uri.repository, &uri.reference, &object_path, limit).await?;
+            if changes.is_empty() {
+                anyhow::bail!("No commit in the history of {} changed {}", uri.reference, object_path);
+            }
+            
+            let rows: Vec<ChangeRow> = changes
+                .into_iter()
+                .map(|c| ChangeRow {
+                    change: c.change.to_string(),
+                    commit: c.commit.id.chars().take(8).collect(),
+                    date: c.commit.creation_date.format("%Y-%m-%d %H:%M:%S").to_string(),
+                    committer: c.commit.committer,
+                    message: c.commit.message.lines().next().unwrap_or("").to_string(),
+                })
+                .collect();
+            println!("{}", Table::new(rows));
+        }
+        
+        FsCommands::Archive { path, format, output, metadata } => {
+            let uri = resolve_uri(&client, &path).await?;
+            let (objects, bytes) = archive::write_archive(&client, &uri, format, &output, metadata).await?;
+            // Standard output holds the archive itself
+            eprintln!("Archived {} objects ({}) from {}", objects, format_size(bytes), uri);
+        }
+        
+        FsCommands::Checksum { target, output, verify: None } => {
+            let uri = resolve_uri(&client, &target).await?;
+            let manifest = Manifest::of_objects(&client, &uri).await?;
+            match output {
+                Some(output) => {
+                    std::fs::write(&output, manifest.render()).with_context(|| format!("Failed to write {}", output))?;
+                    println!("Wrote checksums of {} objects under {} to {}", manifest.entries.len(), uri, output);
+                }
+                None => print!("{}", manifest.render()),
+            }
+        }
+        
+        FsCommands::Checksum { target, verify: Some(manifest_path), .. } => {
+            let text = std::fs::read_to_string(&manifest_path)
+                .with_context(|| format!("Failed to read {}", manifest_path))?;
+            let manifest = Manifest::parse(&text).with_context(|| format!("Invalid manifest {}", manifest_path))?;
+            
+            let verification = if target.starts_with("lakefs://") {
+                let uri = resolve_uri(&client, &target).await?;
+                checksum::verify_objects(&manifest, &Manifest::of_objects(&client, &uri).await?)
+            } else {
+                let directory = PathBuf::from(&target);
+                tokio::task::spawn_blocking(move || checksum::verify_directory(&manifest, &directory)).await??
+            };
+            
+            for (label, paths) in [
+                ("MISMATCH", &verification.mismatched),
+                ("MISSING", &verification.missing),
+                ("UNVERIFIABLE", &verification.unverifiable),
+                ("EXTRA", &verification.extra),
+            ] {
+                for path in paths {
+                    println!("{:<12}  {}", label, path);
+                }
+            }
+            println!(
+                "{} matched, {} mismatched, {} missing, {} unverifiable, {} not in the manifest",
+                verification.matched,
+                verification.mismatched.len(),
+                verification.missing.len(),
+                verification.unverifiable.len(),
+                verification.extra.len(),
+            );
+            if !verification.passed() {
+                anyhow::bail!("{} does not match {}", target, manifest_path);
+            }
+        }
+        
+        FsCommands::Presign { path, expiry, write } => {
+            let uri = resolve_uri(&client, &path).await?;
+            if write {
+                require_branch(&client, &uri).await?;
+            }
             let object_path = uri.path.ok_or_else(|| {
                 anyhow::anyhow!("Path URI must include an object path")
             })?;
+            let expiry = expiry.as_deref().map(parse_duration).transpose()?;
+            if let Some(expiry) = expiry.filter(|e| e.is_zero() || *e > MAX_PRESIGN_EXPIRY) {
+                anyhow::bail!("--expiry must be more than 0s and at most 7d, not {}s", expiry.as_secs());
+            }
+            
+            // With a storage section the URLs are signed here, for the
+            // endpoint and addressing style this client reaches the store by
+            if let Some(storage) = storage {
+                let expiry = expiry.unwrap_or(DEFAULT_PRESIGN_EXPIRY);
+                let stats = client.get_object(&uri.repository, &uri.reference, &object_path).await?;
+                println!("Read URL: {}", storage.presign("GET", &stats.physical_address, expiry).await?);
+                
+                if write {
+                    let location = client.get_presigned_upload_location(
+                        &uri.repository,
+                        &uri.reference,
+                        &object_path,
+                    ).await?;
+                    let address = location.physical_address.ok_or_else(|| {
+                        anyhow::anyhow!("Server did not return a physical address to upload to")
+                    })?;
+                    println!("Write URL: {}", storage.presign("PUT", &address, expiry).await?);
+                }
+                return Ok(());
+            }
             
-            let stats = client.get_object(
+            let stats = client.get_object_presigned(
                 &uri.repository,
                 &uri.reference,
                 &object_path,
             ).await?;
+            check_server_expiry(stats.physical_address_expiry, expiry, now())?;
             
-            println!("Path: {}", stats.path);
-            println!("Type: {:?}", stats.path_type);
-            println!("Size: {}", format_size(stats.size_bytes));
-            println!("Modified: {}", stats.mtime.format("%Y-%m-%d %H:%M:%S"));
-            println!("Checksum: {}", stats.checksum);
+            let write_url = match write {
+                true => {
+                    let location = client.get_presigned_upload_location(
+                        &uri.repository,
+                        &uri.reference,
+                        &object_path,
+                    ).await?;
+                    
+                    let url = location.presigned_url.ok_or_else(|| {
+                        anyhow::anyhow!("Server did not return a presigned upload URL; is presigning enabled for this storage?")
+                    })?;
+                    check_server_expiry(location.presigned_url_expiry, expiry, now())?;
+                    Some(url)
+                }
+                false => None,
+            };
             
-            if let Some(metadata) = stats.metadata {
-                println!("Metadata:");
-                for (key, value) in metadata {
-                    println!("  {}: {}", key, value);
+            println!("Read URL: {}", stats.physical_address);
+            if let Some(url) = write_url {
+                println!("Write URL: {}", url);
+            }
+        }
+    }
+    
+    Ok(())
+}
+
+/// Every entry under the URI's path, following all pages. Unless
+/// `recursive`, directories are listed as common prefixes instead of the
+/// objects beneath them.
+async fn list_entries(client: &LakeFSClient, uri: &LakeFSUri, recursive: bool) -> Result<Vec<ListEntry>> {
+    let mut options = ListObjectsOptions {
+        prefix: uri.path.clone(),
+        delimiter: (!recursive).then(|| "/".to_string()),
+        ..Default::default()
+    };
+    let mut entries = Vec::new();
+    loop {
+        let page = client.list_objects(&uri.repository, &uri.reference, &options).await?;
+        entries.extend(page.results.into_iter().map(ListEntry::from));
+        match page.pagination.next_offset {
+            Some(next) if page.pagination.has_more => options.after = Some(next),
+            _ => return Ok(entries),
+        }
+    }
+}
+
+/// Object prefix for a recursive transfer, ending in `/` so `data` does not
+/// also match `data2/`.
+pub(super) fn directory_prefix(path: Option<&str>) -> String {
+    match path.map(|p| p.trim_end_matches('/')) {
+        Some(p) if !p.is_empty() => format!("{}/", p),
+        _ => String::new(),
+    }
+}
+
+/// Download every object under the source prefix that passes `filter` into
+/// `destination`, keeping paths relative to the prefix. Returns the number
+/// of objects downloaded.
+async fn download_recursive(
+    client: &LakeFSClient,
+    uri: &LakeFSUri,
+    destination: &Path,
+    parallelism: usize,
+    filter: &PathFilter,
+    reporter: &Arc<dyn ProgressReporter>,
+) -> Result<usize> {
+    let prefix = directory_prefix(uri.path.as_deref());
+    let options = ListObjectsOptions {
+        prefix: Some(prefix.clone()).filter(|p| !p.is_empty()),
+        ..Default::default()
+    };
+    let listing = client.list_objects(&uri.repository, &uri.reference, &options).await?;
+    
+    let objects: Vec<ObjectStats> = listing
+        .results
+        .into_iter()
+        .filter(|stats| stats.path_type == PathType::Object)
+        .filter(|stats| stats.path.strip_prefix(&prefix).is_some_and(|relative| filter.is_included(relative)))
+        .collect();
+    // Compressed objects are decompressed once downloaded
+    let mut encodings = HashMap::new();
+    for stats in &objects {
+        if let Some(compression) = Compression::of(stats)? {
+            encodings.insert(stats.path.clone(), compression);
+        }
+    }
+    let objects: Vec<DownloadRequest> = objects
+        .into_iter()
+        .map(|stats| {
+            let target = destination.join(&stats.path[prefix.len()..]);
+            DownloadRequest { checksum: Some(stats.checksum), ..(stats.path, target).into() }
+        })
+        .collect();
+    
+    let options = DownloadOptions { concurrency: parallelism, ..Default::default() };
+    let events = download_objects(client, &uri.repository, &uri.reference, objects, options);
+    let mut events = std::pin::pin!(events);
+    let mut completed = 0;
+    let mut failed = 0;
+    while let Some(event) = events.next().await {
+        match event {
+            DownloadEvent::Started { path, size } => reporter.file_started(&path, size.unwrap_or_default()),
+            DownloadEvent::Progress { path, bytes } => reporter.bytes_transferred(&path, bytes),
+            DownloadEvent::Retrying { .. } | DownloadEvent::Unchanged { .. } => {}
+            DownloadEvent::Finished { path, destination, .. } => {
+                reporter.file_finished(&path);
+                let decoded = match encodings.remove(&path) {
+                    Some(compression) => tokio::task::spawn_blocking(move || compression.decode_in_place(&destination)).await?,
+                    None => Ok(0),
+                };
+                match decoded {
+                    Ok(_) => completed += 1,
+                    Err(e) => {
+                        eprintln!("Failed to download {}: {}", path, e);
+                        failed += 1;
+                    }
+                }
+            }
+            DownloadEvent::Failed { path, error } => {
+                eprintln!("Failed to download {}: {}", path, error);
+                failed += 1;
+            }
+        }
+    }
+    
+    if failed > 0 {
+        anyhow::bail!("{} of {} files failed to download", failed, completed + failed);
+    }
+    Ok(completed)
+}
+
+/// Where uploaded objects get their content type from.
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum ContentType {
+    /// The same type for every object
+    Fixed(String),
+    /// Guessed from each object's extension, then from its first bytes
+    Sniffed,
+    /// None, leaving the server's default
+    Unset,
+}
+
+impl ContentType {
+    fn new(content_type: Option<String>, no_sniff: bool) -> Self {
+        match content_type {
+            Some(content_type) => ContentType::Fixed(content_type),
+            None if no_sniff => ContentType::Unset,
+            None => ContentType::Sniffed,
+        }
+    }
+    
+    /// Options uploading `source`, if it is a file, to the object at `path`.
+    pub(super) fn upload_options(&self, path: &str, source: Option<&Path>) -> UploadOptions {
+        let content_type = match self {
+            ContentType::Fixed(content_type) => Some(content_type.clone()),
+            ContentType::Sniffed => content_type::sniff(path, source),
+            ContentType::Unset => None,
+        };
+        UploadOptions { content_type, ..Default::default() }
+    }
+}
+
+/// Upload every file under `source` that passes `filter` to the destination
+/// prefix, keeping paths relative to `source`. Returns the number of files
+/// uploaded.
+#[allow(clippy::too_many_arguments)]
+pub(super) async fn upload_recursive(
+    client: &LakeFSClient,
+    uri: &LakeFSUri,
+    source: &Path,
+    parallelism: usize,
+    compression: Option<Compression>,
+    content_type: &ContentType,
+    filter: &PathFilter,
+    reporter: &Arc<dyn ProgressReporter>,
+) -> Result<usize> {
+    let prefix = directory_prefix(uri.path.as_deref());
+    
+    let semaphore = Arc::new(Semaphore::new(parallelism.max(1)));
+    let mut tasks = JoinSet::new();
+    for entry in WalkDir::new(source) {
+        let entry = entry?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let relative = paths::to_key(source, entry.path())?;
+        if !filter.is_included(&relative) {
+            continue;
+        }
+        
+        let client = client.clone();
+        let uri = uri.clone();
+        let local_path = entry.into_path();
+        let remote_path = format!("{}{}", prefix, relative);
+        let semaphore = semaphore.clone();
+        let reporter = reporter.clone();
+        let content_type = content_type.clone();
+        tasks.spawn(async move {
+            let _permit = semaphore.acquire().await?;
+            let label = local_path.to_string_lossy();
+            let options = content_type.upload_options(&remote_path, Some(&local_path));
+            transfer::upload_file(&client, &uri.repository, &uri.reference, &remote_path, &local_path, &label, &options, compression, None, &reporter).await?;
+            anyhow::Ok(())
+        });
+    }
+    
+    join_transfers(tasks, "upload").await
+}
+
+/// Every object under `prefix`, across all listing pages.
+pub(super) async fn list_all_objects(
+    client: &LakeFSClient,
+    repository: &str,
+    reference: &str,
+    prefix: &str,
+) -> Result<Vec<ObjectStats>> {
+    let mut objects = Vec::new();
+    for_each_object(client, repository, reference, prefix, |object| {
+        objects.push(object);
+        Ok(())
+    }).await?;
+    Ok(objects)
+}
+
+/// The objects directly under `prefix`, with each subdirectory listed once
+/// as a common prefix instead of the objects beneath it.
+pub(super) async fn list_directory(
+    client: &LakeFSClient,
+    repository: &str,
+    reference: &str,
+    prefix: &str,
+) -> Result<Vec<ObjectStats>> {
+    let mut options = ListObjectsOptions {
+        prefix: Some(prefix.to_string()).filter(|p| !p.is_empty()),
+        delimiter: Some("/".to_string()),
+        ..Default::default()
+    };
+    let mut entries = Vec::new();
+    loop {
+        let page = client.list_objects(repository, reference, &options).await?;
+        entries.extend(page.results);
+        match page.pagination.next_offset {
+            Some(next) if page.pagination.has_more => options.after = Some(next),
+            _ => return Ok(entries),
+        }
+    }
+}
+
+/// Call `f` on every object under `prefix` as each listing page arrives.
+async fn for_each_object(
+    client: &LakeFSClient,
+    repository: &str,
+    reference: &str,
+    prefix: &str,
+    mut f: impl FnMut(ObjectStats) -> Result<()>,
+) -> Result<()> {
+    let mut params = PaginationParams {
+        prefix: Some(prefix.to_string()).filter(|p| !p.is_empty()),
+        after: None,
+        amount: None,
+        ..Default::default()
+    };
+    loop {
+        let page = client.list_objects_with(repository, reference, &params).await?;
+        for object in page.results.into_iter().filter(|o| o.path_type == PathType::Object) {
+            f(object)?;
+        }
+        
+        match page.pagination.next_offset {
+            Some(next) if page.pagination.has_more => params.after = Some(next),
+            _ => break,
+        }
+    }
+    Ok(())
+}
+
+/// Predicates `fs find` applies to listed objects; unset ones match anything.
+#[derive(Debug, Default)]
+struct FindQuery {
+    name: Option<GlobMatcher>,
+    min_size: Option<u64>,
+    max_size: Option<u64>,
+    metadata: HashMap<String, String>,
+}
+
+impl FindQuery {
+    fn matches(&self, object: &ObjectStats) -> bool {
+        let file_name = object.path.rsplit('/').next().unwrap_or(&object.path);
+        let size = object.size_bytes.max(0) as u64;
+        let user_metadata = object.metadata.as_ref();
+        
+        self.name.as_ref().is_none_or(|name| name.is_match(file_name))
+            && self.min_size.is_none_or(|min| size >= min)
+            && self.max_size.is_none_or(|max| size <= max)
+            && self.metadata.iter().all(|(key, value)| {
+                user_metadata.and_then(|m| m.get(key)) == Some(value)
+            })
+    }
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub(super) struct Usage {
+    pub(super) objects: u64,
+    pub(super) bytes: i64,
+}
+
+/// Object count and size of every directory up to `depth` levels below
+/// `prefix`, keyed by path relative to it. A directory includes everything
+/// below it; objects directly under the prefix only count towards the total.
+pub(super) fn disk_usage(objects: &[ObjectStats], prefix: &str, depth: usize) -> BTreeMap<String, Usage> {
+    let mut usage: BTreeMap<String, Usage> = BTreeMap::new();
+    for object in objects {
+        let relative = object.path.strip_prefix(prefix).unwrap_or(&object.path);
+        let directories = relative.match_indices('/').map(|(end, _)| &relative[..end]);
+        for directory in directories.take(depth) {
+            let entry = usage.entry(directory.to_string()).or_default();
+            entry.objects += 1;
+            entry.bytes += object.size_bytes;
+        }
+    }
+    usage
+}
+
+/// A change `fs sync` makes on the destination, by path relative to the
+/// destination prefix.
+#[derive(Debug, PartialEq)]
+enum SyncAction {
+    Copy(String),
+    Delete(String),
+}
+
+/// Copy source objects that are missing or differ (by checksum) on the
+/// destination, and delete destination objects the source does not have.
+/// Paths the filter excludes are left alone on both sides.
+fn plan_sync(
+    source: &[ObjectStats],
+    source_prefix: &str,
+    destination: &[ObjectStats],
+    dest_prefix: &str,
+    filter: &PathFilter,
+) -> Vec<SyncAction> {
+    let relative = |objects: &[ObjectStats], prefix: &str| -> BTreeMap<String, Checksum> {
+        objects
+            .iter()
+            .filter_map(|o| Some((o.path.strip_prefix(prefix)?.to_string(), o.checksum.clone())))
+            .filter(|(path, _)| filter.is_included(path))
+            .collect()
+    };
+    let source = relative(source, source_prefix);
+    let destination = relative(destination, dest_prefix);
+    
+    let copies = source
+        .iter()
+        .filter(|(path, checksum)| destination.get(*path) != Some(*checksum))
+        .map(|(path, _)| SyncAction::Copy(path.clone()));
+    let deletes = destination
+        .keys()
+        .filter(|path| !source.contains_key(*path))
+        .map(|path| SyncAction::Delete(path.clone()));
+    copies.chain(deletes).collect()
+}
+
+/// Apply `plan` with at most `parallelism` requests in flight. Returns the
+/// number of objects copied and deleted.
+async fn apply_sync(
+    client: &LakeFSClient,
+    source: &LakeFSUri,
+    destination: &LakeFSUri,
+    plan: Vec<SyncAction>,
+    parallelism: usize,
+) -> Result<(usize, usize)> {
+    let source_prefix = directory_prefix(source.path.as_deref());
+    let dest_prefix = directory_prefix(destination.path.as_deref());
+    let deletes = plan.iter().filter(|action| matches!(action, SyncAction::Delete(_))).count();
+    
+    let semaphore = Arc::new(Semaphore::new(parallelism.max(1)));
+    let mut tasks = JoinSet::new();
+    for action in plan {
+        let client = client.clone();
+        let source = source.clone();
+        let destination = destination.clone();
+        let (source_prefix, dest_prefix) = (source_prefix.clone(), dest_prefix.clone());
+        let semaphore = semaphore.clone();
+        tasks.spawn(async move {
+            let _permit = semaphore.acquire().await?;
+            match action {
+                SyncAction::Copy(relative) => {
+                    client.copy_object(
+                        &destination.repository,
+                        &destination.reference,
+                        &format!("{}{}", dest_prefix, relative),
+                        &source.reference,
+                        &format!("{}{}", source_prefix, relative),
+                    ).await.with_context(|| format!("copy {}", relative))?;
                 }
+                SyncAction::Delete(relative) => {
+                    client.delete_object(
+                        &destination.repository,
+                        &destination.reference,
+                        &format!("{}{}", dest_prefix, relative),
+                    ).await.with_context(|| format!("delete {}", relative))?;
+                }
+            }
+            anyhow::Ok(())
+        });
+    }
+    
+    let applied = join_transfers(tasks, "sync").await?;
+    Ok((applied - deletes, deletes))
+}
+
+/// Wait for every transfer, reporting each failure, and fail if any did.
+pub(super) async fn join_transfers(mut tasks: JoinSet<Result<()>>, verb: &str) -> Result<usize> {
+    let mut completed = 0;
+    let mut failed = 0;
+    while let Some(joined) = tasks.join_next().await {
+        match joined? {
+            Ok(()) => completed += 1,
+            Err(e) => {
+                eprintln!("Failed to {}: {}", verb, e);
+                failed += 1;
             }
         }
     }
     
+    if failed > 0 {
+        anyhow::bail!("{} of {} files failed to {}", failed, completed + failed, verb);
+    }
+    Ok(completed)
+}
+
+pub(super) fn progress_reporter(options: &OptionsConfig) -> Arc<dyn ProgressReporter> {
+    if options.no_progress {
+        Arc::new(NoopProgress)
+    } else {
+        Arc::new(MultiProgressReporter::new())
+    }
+}
+
+/// lakeFS decides presigned URL lifetime server-side, so a requested expiry
+/// can only be checked against it: fail when the URL, expiring at Unix time
+/// `server_expiry`, lasts less than `requested` from `now`.
+fn check_server_expiry(server_expiry: Option<i64>, requested: Option<Duration>, now: i64) -> Result<()> {
+    let (Some(server_expiry), Some(requested)) = (server_expiry, requested) else {
+        return Ok(());
+    };
+    
+    let remaining = server_expiry - now;
+    if remaining < requested.as_secs() as i64 {
+        anyhow::bail!(
+            "The server limits presigned URLs to {}s, shorter than the requested {}s; \
+             ask for less, or add a storage section to sign URLs locally",
+            remaining.max(0),
+            requested.as_secs()
+        );
+    }
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Seconds since the Unix epoch.
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64
+}
+
+/// `paths`, with `-` replaced by the URIs listed in `stdin`, one per line.
+/// Blank lines are skipped.
+fn read_path_list(paths: Vec<String>, stdin: impl std::io::BufRead) -> Result<Vec<String>> {
+    let mut listed = Vec::with_capacity(paths.len());
+    let mut stdin = Some(stdin);
+    for path in paths {
+        if path != "-" {
+            listed.push(path);
+            continue;
+        }
+        // Standard input can only be read once
+        let Some(stdin) = stdin.take() else { continue };
+        for line in stdin.lines() {
+            let line = line.context("Failed to read paths from standard input")?;
+            let line = line.trim();
+            if !line.is_empty() {
+                listed.push(line.to_string());
+            }
+        }
+    }
+    Ok(listed)
+}
+
+/// Stat every path URI, running at most `parallelism` requests at once.
+/// Results are in the order of `paths`; one failing does not stop the others.
+async fn stat_paths(
+    client: &LakeFSClient,
+    paths: &[String],
+    parallelism: usize,
+) -> Vec<(String, Result<ObjectStats>)> {
+    let requests: Vec<_> = paths
+        .iter()
+        .map(|path| async move {
+            let result = async {
+                let uri = resolve_uri(client, path).await?;
+                let object_path = uri.path.ok_or_else(|| {
+                    anyhow::anyhow!("Path URI must include an object path")
+                })?;
+                Ok(client.get_object(&uri.repository, &uri.reference, &object_path).await?)
+            };
+            (path.clone(), result.await)
+        })
+        .collect();
+    
+    futures::stream::iter(requests)
+        .buffered(parallelism.max(1))
+        .collect()
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lakefs_test_utils::scenario::MockLakeFS;
+    use tempfile::TempDir;
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, ResponseTemplate};
+
+    #[test]
+    fn test_check_server_expiry() {
+        let hour = Duration::from_secs(3600);
+        assert!(check_server_expiry(Some(1000 + 3600), Some(hour), 1000).is_ok());
+        assert!(check_server_expiry(None, Some(hour), 1000).is_ok());
+        assert!(check_server_expiry(Some(1000 + 900), None, 1000).is_ok());
+        
+        let error = check_server_expiry(Some(1000 + 900), Some(hour), 1000).unwrap_err();
+        assert!(error.to_string().contains("limits presigned URLs to 900s, shorter than the requested 3600s"));
+    }
+
+    #[test]
+    fn test_directory_prefix() {
+        assert_eq!(directory_prefix(None), "");
+        assert_eq!(directory_prefix(Some("")), "");
+        assert_eq!(directory_prefix(Some("data")), "data/");
+        assert_eq!(directory_prefix(Some("data/")), "data/");
+    }
+
+    #[tokio::test]
+    async fn test_list_entries_rolls_up_directories() {
+        let server = MockLakeFS::new()
+            .page_size(1)
+            .with_repo("repo")
+            .with_branch("main")
+            .with_object("data/a.csv", "a")
+            .with_object("data/2024/01.csv", "1")
+            .with_object("data/2024/02.csv", "2")
+            .with_object("data/tmp/b.csv", "b")
+            .start()
+            .await;
+        let client = LakeFSClient::new(server.uri(), "Bearer test-token");
+        let uri = parse_uri("lakefs://repo/main/data/").unwrap();
+        
+        let entries = list_entries(&client, &uri, false).await.unwrap();
+        let paths: Vec<_> = entries.iter().map(ListEntry::path).collect();
+        assert_eq!(paths, ["data/2024/", "data/a.csv", "data/tmp/"]);
+        assert!(matches!(entries[0], ListEntry::CommonPrefix(_)));
+        
+        let entries = list_entries(&client, &uri, true).await.unwrap();
+        assert_eq!(entries.len(), 4);
+        assert!(entries.iter().all(|entry| matches!(entry, ListEntry::Object(_))));
+    }
+
+    #[test]
+    fn test_content_type_choice() {
+        let sniffed = ContentType::new(None, false);
+        assert_eq!(sniffed.upload_options("data/a.json", None).content_type.as_deref(), Some("application/json"));
+        assert_eq!(sniffed.upload_options("data/a", None).content_type, None);
+        
+        let fixed = ContentType::new(Some("text/csv".into()), false);
+        assert_eq!(fixed.upload_options("data/a.json", None).content_type.as_deref(), Some("text/csv"));
+        assert_eq!(ContentType::new(None, true).upload_options("data/a.json", None), UploadOptions::default());
+    }
+
+    #[test]
+    fn test_read_path_list() {
+        let stdin = "lakefs://repo/main/b.csv\n\n  lakefs://repo/main/c.csv  \n".as_bytes();
+        let paths = read_path_list(vec!["lakefs://repo/main/a.csv".into(), "-".into(), "-".into()], stdin).unwrap();
+        assert_eq!(paths, ["lakefs://repo/main/a.csv", "lakefs://repo/main/b.csv", "lakefs://repo/main/c.csv"]);
+    }
+
+    #[tokio::test]
+    async fn test_stat_paths_keeps_order() {
+        let server = MockLakeFS::new()
+            .with_repo("repo")
+            .with_branch("main")
+            .with_object("a.csv", "a")
+            .with_object("b.csv", "bb")
+            .start()
+            .await;
+        let client = LakeFSClient::new(server.uri(), "Bearer test-token");
+        
+        let paths = ["lakefs://repo/main/b.csv", "lakefs://repo/main/missing.csv", "lakefs://repo/main/a.csv", "lakefs://repo/main"]
+            .map(String::from);
+        let results = stat_paths(&client, &paths, 2).await;
+        
+        let sizes: Vec<_> = results.iter().map(|(_, result)| result.as_ref().ok().map(|stats| stats.size_bytes)).collect();
+        assert_eq!(sizes, [Some(2), None, Some(1), None]);
+        assert_eq!(results[1].0, "lakefs://repo/main/missing.csv");
+    }
+
+    #[tokio::test]
+    async fn test_download_recursive_filtered() {
+        let server = MockLakeFS::new()
+            .with_repo("repo")
+            .with_branch("main")
+            .with_object("data/a.csv", "a")
+            .with_object("data/tmp/b.csv", "b")
+            .with_object("data/c.json", "c")
+            .with_object("data2/d.csv", "d")
+            .start()
+            .await;
+        let client = LakeFSClient::new(server.uri(), "Bearer test-token");
+        let temp_dir = TempDir::new().unwrap();
+        
+        let filter = PathFilter::new()
+            .exclude("tmp").unwrap()
+            .include("*.csv").unwrap()
+            .exclude("*").unwrap();
+        let uri = parse_uri("lakefs://repo/main/data").unwrap();
+        let reporter: Arc<dyn ProgressReporter> = Arc::new(NoopProgress);
+        let count = download_recursive(&client, &uri, temp_dir.path(), 4, &filter, &reporter).await.unwrap();
+        
+        assert_eq!(count, 1);
+        assert_eq!(std::fs::read_to_string(temp_dir.path().join("a.csv")).unwrap(), "a");
+        assert!(!temp_dir.path().join("tmp/b.csv").exists());
+        assert!(!temp_dir.path().join("c.json").exists());
+    }
+    
+    #[tokio::test]
+    async fn test_sync_between_refs() {
+        let server = MockLakeFS::new()
+            .with_repo("repo")
+            .with_branch("main")
+            .with_object("raw/same.csv", "same")
+            .with_object("raw/changed.csv", "new")
+            .with_object("raw/added.csv", "added")
+            .with_object("raw/skip.tmp", "skip")
+            .with_branch("prod")
+            .with_object("data/same.csv", "same")
+            .with_object("data/changed.csv", "old")
+            .with_object("data/stale.csv", "stale")
+            .with_object("data/keep.tmp", "keep")
+            .start()
+            .await;
+        let client = LakeFSClient::new(server.uri(), "Bearer test-token");
+        
+        let source = parse_uri("lakefs://repo/main/raw").unwrap();
+        let destination = parse_uri("lakefs://repo/prod/data").unwrap();
+        let source_objects = list_all_objects(&client, "repo", "main", "raw/").await.unwrap();
+        let dest_objects = list_all_objects(&client, "repo", "prod", "data/").await.unwrap();
+        let filter = PathFilter::new().exclude("*.tmp").unwrap();
+        let plan = plan_sync(&source_objects, "raw/", &dest_objects, "data/", &filter);
+        assert_eq!(plan, vec![
+            SyncAction::Copy("added.csv".to_string()),
+            SyncAction::Copy("changed.csv".to_string()),
+            SyncAction::Delete("stale.csv".to_string()),
+        ]);
+        
+        for dest in ["data/added.csv", "data/changed.csv"] {
+            Mock::given(method("POST"))
+                .and(path("/repositories/repo/branches/prod/objects/copy"))
+                .and(query_param("dest_path", dest))
+                .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!({
+                    "path": dest,
+                    "path_type": "object",
+                    "physical_address": "s3://bucket/x",
+                    "checksum": "abc",
+                    "size_bytes": 1,
+                    "mtime": "2024-01-01T00:00:00Z"
+                })))
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+        Mock::given(method("DELETE"))
+            .and(path("/repositories/repo/branches/prod/objects"))
+            .and(query_param("path", "data/stale.csv"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+        
+        let (copied, deleted) = apply_sync(&client, &source, &destination, plan, 2).await.unwrap();
+        assert_eq!((copied, deleted), (2, 1));
+    }
+    
+    #[test]
+    fn test_disk_usage_depth() {
+        let object = |path: &str, size_bytes: i64| -> ObjectStats {
+            serde_json::from_value(serde_json::json!({
+                "path": path,
+                "path_type": "object",
+                "physical_address": "s3://bucket/x",
+                "checksum": "abc",
+                "size_bytes": size_bytes,
+                "mtime": 0
+            })).unwrap()
+        };
+        let objects = vec![
+            object("data/readme.md", 1),
+            object("data/raw/2024/a.csv", 10),
+            object("data/raw/2024/b.csv", 20),
+            object("data/raw/c.csv", 5),
+            object("data/clean/d.csv", 100),
+        ];
+        
+        let top = disk_usage(&objects, "data/", 1);
+        assert_eq!(top.keys().collect::<Vec<_>>(), ["clean", "raw"]);
+        assert_eq!(top["raw"], Usage { objects: 3, bytes: 35 });
+        
+        let nested = disk_usage(&objects, "data/", 2);
+        assert_eq!(nested["raw/2024"], Usage { objects: 2, bytes: 30 });
+        assert_eq!(nested.len(), 3);
+        
+        assert!(disk_usage(&objects, "data/", 0).is_empty());
+    }
+    
+    #[test]
+    fn test_find_query_matches() {
+        let object: ObjectStats = serde_json::from_value(serde_json::json!({
+            "path": "reports/2024/sales.csv",
+            "path_type": "object",
+            "physical_address": "s3://bucket/x",
+            "checksum": "abc",
+            "size_bytes": 2048,
+            "mtime": 0,
+            "metadata": {"team": "analytics"}
+        })).unwrap();
+        let query = |name: Option<&str>, min_size: Option<u64>, metadata: &[(&str, &str)]| FindQuery {
+            name: name.map(|n| Glob::new(n).unwrap().compile_matcher()),
+            min_size,
+            max_size: None,
+            metadata: metadata.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+        };
+        
+        assert!(FindQuery::default().matches(&object));
+        assert!(query(Some("*.csv"), Some(1024), &[("team", "analytics")]).matches(&object));
+        assert!(!query(Some("*.parquet"), None, &[]).matches(&object));
+        // The pattern is matched against the file name only
+        assert!(!query(Some("reports/*"), None, &[]).matches(&object));
+        assert!(!query(None, Some(4096), &[]).matches(&object));
+        assert!(!query(None, None, &[("team", "ml")]).matches(&object));
+        assert!(!query(None, None, &[("owner", "analytics")]).matches(&object));
+    }
+    
+    #[tokio::test]
+    async fn test_for_each_object_pages() {
+        let server = MockLakeFS::new()
+            .with_repo("repo")
+            .with_branch("main")
+            .with_object("data/a.csv", "a")
+            .with_object("data/b.csv", "b")
+            .with_object("data/c.csv", "c")
+            .with_object("other/d.csv", "d")
+            .page_size(2)
+            .start()
+            .await;
+        let client = LakeFSClient::new(server.uri(), "Bearer test-token");
+        
+        let mut seen = Vec::new();
+        for_each_object(&client, "repo", "main", "data/", |object| {
+            seen.push(object.path);
+            Ok(())
+        }).await.unwrap();
+        assert_eq!(seen, ["data/a.csv", "data/b.csv", "data/c.csv"]);
+    }
+}
@@ -0,0 +1,388 @@
+use super::fs::{directory_prefix, progress_reporter};
+use crate::cli::IngestMode;
+use crate::config::OptionsConfig;
+use crate::utils::{format_size, parse_uri};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use futures::TryStreamExt;
+use lakefs_api::models::{ObjectStage, ObjectStats};
+use lakefs_api::{Checksum, LakeFSClient, LakeFSUri, UploadOptions};
+use lakefs_auth::{ObjectStoreConfig, StoreObject};
+use lakefs_local::{paths, ProgressReporter};
+use md5::{Digest, Md5};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use walkdir::WalkDir;
+
+/// Validity of the URLs bucket objects are read through when copying
+const GET_EXPIRY: Duration = Duration::from_secs(60 * 60);
+
+/// What was ingested, written to `--manifest`.
+#[derive(Debug, Serialize)]
+struct IngestManifest {
+    source: String,
+    destination: String,
+    mode: &'static str,
+    ingested_at: DateTime<Utc>,
+    objects: Vec<IngestedObject>,
+}
+
+#[derive(Debug, Serialize)]
+struct IngestedObject {
+    /// File path or `s3://` address it came from
+    source: String,
+    /// Path on the branch
+    path: String,
+    size_bytes: i64,
+    checksum: Checksum,
+}
+
+/// Where objects are ingested from.
+enum Source {
+    Directory(PathBuf),
+    /// `s3://bucket/prefix/`, listed and read with the configured storage keys
+    Bucket { storage: ObjectStoreConfig, address: String },
+}
+
+/// One object found at the source, with its key relative to it.
+enum Found {
+    File { key: String, path: PathBuf },
+    Stored { key: String, object: StoreObject },
+}
+
+impl Source {
+    fn parse(from: &str, storage: Option<&ObjectStoreConfig>) -> Self {
+        match from.strip_prefix("s3://") {
+            Some(rest) => Source::Bucket {
+                storage: storage.cloned().unwrap_or_default(),
+                address: format!("s3://{}", directory_prefix(Some(rest))),
+            },
+            None => Source::Directory(PathBuf::from(from.strip_prefix("file://").unwrap_or(from))),
+        }
+    }
+
+    /// Objects can only be staged in place when they are in a bucket lakeFS
+    /// can read; files have to be copied.
+    fn default_mode(&self) -> IngestMode {
+        match self {
+            Source::Directory(_) => IngestMode::Copy,
+            Source::Bucket { .. } => IngestMode::Stage,
+        }
+    }
+
+    async fn list(&self) -> Result<Vec<Found>> {
+        match self {
+            Source::Directory(root) => {
+                if !root.is_dir() {
+                    anyhow::bail!("{} is not a directory", root.display());
+                }
+                let mut found = Vec::new();
+                for entry in WalkDir::new(root).sort_by_file_name() {
+                    let entry = entry?;
+                    if entry.file_type().is_file() {
+                        let key = paths::to_key(root, entry.path())?;
+                        found.push(Found::File { key, path: entry.into_path() });
+                    }
+                }
+                Ok(found)
+            }
+            Source::Bucket { storage, address } => {
+                let prefix_len = address.splitn(4, '/').nth(3).unwrap_or_default().len();
+                let objects = storage.list(address).await?;
+                Ok(objects
+                    .into_iter()
+                    .filter(|object| !object.key.ends_with('/'))
+                    .map(|object| Found::Stored { key: object.key[prefix_len..].to_string(), object })
+                    .collect())
+            }
+        }
+    }
+}
+
+/// Bring a directory or bucket prefix onto a branch in bulk, then optionally
+/// commit with a summary of what was ingested in the commit metadata.
+///
+/// Staging links bucket objects into the branch where they are, without
+/// reading them, so lakeFS must be able to read that bucket; copying streams
+/// every object through this client into the repository's own storage.
+#[allow(clippy::too_many_arguments)]
+pub async fn execute(
+    from: String,
+    to: String,
+    mode: Option<IngestMode>,
+    manifest: Option<String>,
+    commit: Option<String>,
+    parallelism: usize,
+    options: &OptionsConfig,
+    storage: Option<&ObjectStoreConfig>,
+    client: LakeFSClient,
+) -> Result<()> {
+    let destination = parse_uri(&to)?;
+    let source = Arc::new(Source::parse(&from, storage));
+    let mode = mode.unwrap_or_else(|| source.default_mode());
+    if mode == IngestMode::Stage && matches!(*source, Source::Directory(_)) {
+        anyhow::bail!("Local files cannot be staged in place; use --mode copy");
+    }
+
+    let found = source.list().await?;
+    if found.is_empty() {
+        anyhow::bail!("Nothing to ingest from {}", from);
+    }
+    let dest_prefix = directory_prefix(destination.path.as_deref());
+    let reporter = progress_reporter(options);
+
+    let destination = Arc::new(destination);
+    let http = reqwest::Client::new();
+    let semaphore = Arc::new(Semaphore::new(parallelism.max(1)));
+    let mut tasks = JoinSet::new();
+    for item in found {
+        let (client, http, source, destination) = (client.clone(), http.clone(), source.clone(), destination.clone());
+        let (semaphore, reporter) = (semaphore.clone(), reporter.clone());
+        let dest_prefix = dest_prefix.clone();
+        tasks.spawn(async move {
+            let _permit = semaphore.acquire().await?;
+            let label = match &item {
+                Found::File { path, .. } => path.display().to_string(),
+                Found::Stored { object, .. } => object.address.clone(),
+            };
+            ingest_object(&client, &http, &source, &destination, &dest_prefix, mode, item, &reporter)
+                .await
+                .with_context(|| format!("ingest {}", label))
+        });
+    }
+
+    let mut ingested = Vec::new();
+    let mut failed = 0;
+    while let Some(joined) = tasks.join_next().await {
+        match joined? {
+            Ok(object) => ingested.push(object),
+            Err(e) => {
+                eprintln!("Failed to {:#}", e);
+                failed += 1;
+            }
+        }
+    }
+    ingested.sort_by(|a, b| a.path.cmp(&b.path));
+    let count = ingested.len();
+    let bytes: i64 = ingested.iter().map(|o| o.size_bytes).sum();
+    reporter.finish(&format!("Ingested {} objects", count));
+
+    let mut metadata = HashMap::from([
+        ("ingest_source".to_string(), from.clone()),
+        ("ingest_objects".to_string(), count.to_string()),
+        ("ingest_bytes".to_string(), bytes.to_string()),
+    ]);
+    if let Some(path) = &manifest {
+        let record = IngestManifest {
+            source: from.clone(),
+            destination: destination.to_string(),
+            mode: mode.name(),
+            ingested_at: Utc::now(),
+            objects: ingested,
+        };
+        let content = serde_json::to_vec_pretty(&record)?;
+        std::fs::write(path, &content).with_context(|| format!("Failed to write manifest {}", path))?;
+        let name = Path::new(path).file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        metadata.insert("ingest_manifest".to_string(), name);
+        metadata.insert("ingest_manifest_md5".to_string(), format!("{:x}", Md5::digest(&content)));
+    }
+
+    if failed > 0 {
+        anyhow::bail!("{} of {} objects failed to ingest; nothing was committed", failed, count + failed);
+    }
+    println!("Ingested {} objects ({}) into {}", count, format_size(bytes), destination);
+    if let Some(message) = commit {
+        let commit = client
+            .commit_with_metadata(&destination.repository, &destination.reference, &message, &metadata)
+            .await?;
+        println!("Committed: {}", commit.id);
+    }
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn ingest_object(
+    client: &LakeFSClient,
+    http: &reqwest::Client,
+    source: &Source,
+    destination: &LakeFSUri,
+    dest_prefix: &str,
+    mode: IngestMode,
+    item: Found,
+    reporter: &Arc<dyn ProgressReporter>,
+) -> Result<IngestedObject> {
+    let (repository, branch) = (&destination.repository, &destination.reference);
+    let (origin, stats): (String, ObjectStats) = match (item, source) {
+        (Found::File { key, path }, _) => {
+            let dest_path = format!("{}{}", dest_prefix, key);
+            let label = path.display().to_string();
+            let stats = lakefs_local::transfer::upload_file(
+                client,
+                repository,
+                branch,
+                &dest_path,
+                &path,
+                &label,
+                &UploadOptions::default(),
+                None,
+                None,
+                reporter,
+            ).await?;
+            (label, stats)
+        }
+        (Found::Stored { key, object }, _) if mode == IngestMode::Stage => {
+            let stage = ObjectStage {
+                physical_address: object.address.clone(),
+                checksum: object.etag.clone().into(),
+                size_bytes: object.size as i64,
+                mtime: object.last_modified.map(|t| t.timestamp()),
+                metadata: None,
+                content_type: None,
+            };
+            let stats = client.stage_object(repository, branch, &format!("{}{}", dest_prefix, key), &stage).await?;
+            (object.address, stats)
+        }
+        (Found::Stored { key, object }, Source::Bucket { storage, .. }) => {
+            let url = storage.presign("GET", &object.address, GET_EXPIRY).await?;
+            let response = http.get(url).send().await?.error_for_status()?;
+            let stream = response.bytes_stream().map_err(std::io::Error::other);
+            let stats = client.upload_object_stream(repository, branch, &format!("{}{}", dest_prefix, key), stream).await?;
+            (object.address, stats)
+        }
+        (Found::Stored { .. }, Source::Directory(_)) => unreachable!("directories only list files"),
+    };
+
+    Ok(IngestedObject {
+        source: origin,
+        path: stats.path,
+        size_bytes: stats.size_bytes,
+        checksum: stats.checksum,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lakefs_test_utils::scenario::MockLakeFS;
+    use tempfile::TempDir;
+    use wiremock::matchers::{body_json, method, path, query_param};
+    use wiremock::{Mock, ResponseTemplate};
+
+    fn stats(path: &str, size: i64) -> serde_json::Value {
+        serde_json::json!({
+            "path": path,
+            "path_type": "object",
+            "physical_address": "s3://bucket/x",
+            "checksum": "0cc175b9c0f1b6a831c399e269772661",
+            "size_bytes": size,
+            "mtime": 1_700_000_000
+        })
+    }
+
+    #[test]
+    fn test_source_parse() {
+        let source = Source::parse("s3://bucket/raw", None);
+        assert!(matches!(&source, Source::Bucket { address, .. } if address == "s3://bucket/raw/"));
+        assert_eq!(source.default_mode(), IngestMode::Stage);
+
+        let source = Source::parse("file:///data/raw", None);
+        assert!(matches!(&source, Source::Directory(path) if path == Path::new("/data/raw")));
+        assert_eq!(source.default_mode(), IngestMode::Copy);
+        assert!(matches!(Source::parse("./raw", None), Source::Directory(_)));
+    }
+
+    #[tokio::test]
+    async fn test_ingest_directory_with_manifest_and_commit() {
+        let server = MockLakeFS::new().with_repo("repo").with_branch("main").start().await;
+        for (dest, size) in [("raw/a.csv", 1), ("raw/sub/b.csv", 2)] {
+            Mock::given(method("PUT"))
+                .and(path("/repositories/repo/branches/main/objects"))
+                .and(query_param("path", dest))
+                .respond_with(ResponseTemplate::new(201).set_body_json(stats(dest, size)))
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+        Mock::given(method("POST"))
+            .and(path("/repositories/repo/branches/main/commits"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!({
+                "id": "c1",
+                "parents": [],
+                "committer": "user",
+                "message": "ingest",
+                "creation_date": 1_700_000_000,
+                "meta_range_id": "",
+                "metadata": {}
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let client = LakeFSClient::new(server.uri(), "Bearer test-token");
+
+        let temp_dir = TempDir::new().unwrap();
+        let raw = temp_dir.path().join("raw");
+        std::fs::create_dir_all(raw.join("sub")).unwrap();
+        std::fs::write(raw.join("a.csv"), "a").unwrap();
+        std::fs::write(raw.join("sub/b.csv"), "bb").unwrap();
+        let manifest = temp_dir.path().join("manifest.json");
+        let options = OptionsConfig { no_progress: true, ..Default::default() };
+
+        execute(
+            raw.display().to_string(),
+            "lakefs://repo/main/raw/".to_string(),
+            None,
+            Some(manifest.display().to_string()),
+            Some("ingest".to_string()),
+            2,
+            &options,
+            None,
+            client,
+        ).await.unwrap();
+
+        let record: serde_json::Value = serde_json::from_slice(&std::fs::read(&manifest).unwrap()).unwrap();
+        assert_eq!(record["mode"], "copy");
+        let paths: Vec<&str> = record["objects"].as_array().unwrap().iter().map(|o| o["path"].as_str().unwrap()).collect();
+        assert_eq!(paths, ["raw/a.csv", "raw/sub/b.csv"]);
+    }
+
+    #[tokio::test]
+    async fn test_stage_bucket_objects() {
+        let server = MockLakeFS::new().with_repo("repo").with_branch("main").start().await;
+        Mock::given(method("PUT"))
+            .and(path("/repositories/repo/branches/main/objects"))
+            .and(query_param("path", "raw/a.csv"))
+            .and(body_json(serde_json::json!({
+                "physical_address": "s3://bucket/in/a.csv",
+                "checksum": "0cc175b9c0f1b6a831c399e269772661",
+                "size_bytes": 1,
+                "mtime": 1_714_557_600
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(stats("raw/a.csv", 1)))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let client = LakeFSClient::new(server.uri(), "Bearer test-token");
+        let destination: LakeFSUri = "lakefs://repo/main/raw/".parse().unwrap();
+        let object = StoreObject {
+            address: "s3://bucket/in/a.csv".to_string(),
+            key: "in/a.csv".to_string(),
+            size: 1,
+            etag: "0cc175b9c0f1b6a831c399e269772661".to_string(),
+            last_modified: DateTime::from_timestamp(1_714_557_600, 0),
+        };
+        let source = Source::parse("s3://bucket/in/", None);
+        let reporter: Arc<dyn ProgressReporter> = Arc::new(lakefs_local::NoopProgress);
+
+        let item = Found::Stored { key: "a.csv".to_string(), object };
+        let ingested = ingest_object(&client, &reqwest::Client::new(), &source, &destination, "raw/", IngestMode::Stage, item, &reporter)
+            .await
+            .unwrap();
+        assert_eq!(ingested.source, "s3://bucket/in/a.csv");
+        assert_eq!(ingested.path, "raw/a.csv");
+    }
+}
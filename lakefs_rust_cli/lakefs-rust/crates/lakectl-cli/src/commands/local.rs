@@ -1,19 +1,40 @@
-use crate::cli::LocalCommands;
-use crate::config::OptionsConfig;
-use anyhow::Result;
-use lakefs_api::{LakeFSClient, LakeFSUri};
-use lakefs_local::{SyncManager, SyncConfig, LocalIndex};
+use crate::cli::{ErrorPolicyArgs, FilterArgs, LocalCommands, StashCommands};
+use crate::config::{CommitConfig, OptionsConfig};
+use super::actions::{self, PendingCommit};
+use super::commit::verify_message;
+use crate::utils::{format_size, parse_metadata, parse_rate, resolve_uri};
+use crate::editor;
+use anyhow::{Context, Result};
+use futures::{Stream, StreamExt};
+use lakefs_api::{LakeFSClient, LakeFSUri, PaginationParams};
+use lakefs_local::{ChangeType, MultiProgressReporter, SyncManager, SyncConfig, SyncEvent, SyncMetrics, SyncResult, LocalIndex};
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
-use std::str::FromStr;
+use std::sync::Arc;
+use tabled::{Table, Tabled};
+use tokio_util::sync::CancellationToken;
+
+/// Where the commit message is edited, next to the checkout's index
+const COMMIT_MESSAGE_FILE: &str = ".lakectl/COMMIT_EDITMSG";
+
+/// Metadata key of the whole message of a commit made with a message of
+/// several lines
+const FULL_MESSAGE_KEY: &str = "full_message";
+
+/// Staged changes listed in the commit message template
+const TEMPLATE_CHANGES: usize = 100;
 
 pub async fn execute(
     command: LocalCommands,
     client: LakeFSClient,
     options: &OptionsConfig,
+    rules: &CommitConfig,
+    metrics: Option<Arc<SyncMetrics>>,
+    cancel: CancellationToken,
 ) -> Result<()> {
     match command {
         LocalCommands::Init { remote, path } => {
-            let uri = LakeFSUri::from_str(&remote)?;
+            let uri = resolve_uri(&client, &remote).await?;
             let path = Path::new(&path);
             
             // Check if already initialized
@@ -35,8 +56,8 @@ pub async fn execute(
             println!("Initialized local directory at {}", path.display());
         }
         
-        LocalCommands::Clone { remote, path } => {
-            let uri = LakeFSUri::from_str(&remote)?;
+        LocalCommands::Clone { remote, path, snapshot, limit_rate, summary_json, error_policy } => {
+            let uri = resolve_uri(&client, &remote).await?;
             let path = path.map(PathBuf::from).unwrap_or_else(|| {
                 PathBuf::from(&uri.repository)
             });
@@ -50,28 +71,48 @@ pub async fn execute(
             std::fs::create_dir_all(&path)?;
             
             // Initialize index
-            let branch = client.get_branch(&uri.repository, &uri.reference).await?;
-            let index = LocalIndex::new(
-                &uri.repository,
-                &uri.reference,
-                &branch.commit_id,
-            );
+            let (index, uri) = if snapshot {
+                let commit = client.get_commit(&uri.repository, &uri.reference).await?;
+                let index = LocalIndex::snapshot(&uri.repository, &commit.id);
+                (index, LakeFSUri::new(&uri.repository, &commit.id))
+            } else {
+                let branch = client.get_branch(&uri.repository, &uri.reference).await?;
+                let index = LocalIndex::new(
+                    &uri.repository,
+                    &uri.reference,
+                    &branch.commit_id,
+                );
+                (index, uri)
+            };
             
             index.save(&path)?;
             
             // Perform initial sync
+            let limit_rate = limit_rate.as_deref().map(parse_rate).transpose()?;
             let config = SyncConfig {
                 parallelism: options.parallelism,
-                show_progress: !options.no_progress,
+                max_upload_bandwidth: limit_rate,
+                max_download_bandwidth: limit_rate,
+                object_cache: options.object_cache.clone(),
+                link_strategy: options.link_strategy,
+                max_errors: error_policy.max_errors(),
+                compression: options.compression,
+                delta_threshold: options.delta_threshold,
+                ignore_permissions: options.ignore_permissions,
                 ..Default::default()
             };
             
-            let sync_manager = SyncManager::new(client, config);
-            let result = sync_manager.sync(&path, &uri).await?;
+            let sync_manager = sync_manager(&client, config, metrics, cancel);
+            let result = if snapshot {
+                watch(sync_manager.pull_with_events(&path, &uri), options).await?
+            } else {
+                watch(sync_manager.sync_with_events(&path, &uri), options).await?
+            };
+            write_summary(summary_json.as_deref(), &result)?;
             
             println!(
                 "Cloned {} to {}",
-                uri.to_string(),
+                uri,
                 path.display()
             );
             println!(
@@ -79,6 +120,7 @@ pub async fn execute(
                 result.downloaded,
                 result.errors.len()
             );
+            check_errors(&result)?;
         }
         
         LocalCommands::Status { path } => {
@@ -86,26 +128,77 @@ pub async fn execute(
             let index = LocalIndex::load(path)?;
             
             println!("Repository: {}", index.repository);
-            println!("Branch: {}", index.reference);
+            if index.snapshot {
+                println!("Snapshot of commit: {}", index.reference);
+            } else {
+                println!("Branch: {}", index.reference);
+            }
             println!("Head commit: {}", index.head_commit);
             println!("Last sync: {}", index.last_sync.format("%Y-%m-%d %H:%M:%S"));
             println!("Tracked files: {}", index.entries.len());
+            
+            let report = lakefs_local::status(&client, path).await?;
+            let source = if report.cached { "cached listing" } else { "listed" };
+            println!("Remote head: {} ({})", report.commit, source);
+            print_changes("Local changes", &report.local);
+            print_changes("Remote changes", &report.remote);
         }
         
-        LocalCommands::Pull { path, force: _ } => {
+        LocalCommands::Clean { path, dry_run } => {
+            let path = Path::new(&path);
+            let report = lakefs_local::clean(&client, path, options.parallelism, dry_run).await?;
+            
+            let verb = if dry_run { "Would remove" } else { "Removed" };
+            for file in &report.temp_files {
+                println!("{} {}", verb, file.display());
+            }
+            for entry in &report.pruned_entries {
+                println!("{} index entry {}", verb, entry);
+            }
+            println!(
+                "{} {} temp files ({}) and {} index entries",
+                verb,
+                report.temp_files.len(),
+                format_size(report.reclaimed_bytes as i64),
+                report.pruned_entries.len()
+            );
+        }
+        
+        LocalCommands::Stash { command } => stash(command, &client).await?,
+        
+        LocalCommands::Pull { path, force: _, prune, no_prune: _, verify_remote, limit_rate, summary_json, error_policy, filters } => {
             let path = Path::new(&path);
             let mut index = LocalIndex::load(path)?;
+            if index.snapshot {
+                anyhow::bail!(
+                    "{} is a snapshot of commit {}; use `lakectl local pin` to switch commits",
+                    path.display(),
+                    index.reference
+                );
+            }
             
             let uri = LakeFSUri::new(&index.repository, &index.reference);
             
+            let limit_rate = limit_rate.as_deref().map(parse_rate).transpose()?;
             let config = SyncConfig {
                 parallelism: options.parallelism,
-                show_progress: !options.no_progress,
+                verify_remote,
+                max_upload_bandwidth: limit_rate,
+                max_download_bandwidth: limit_rate,
+                filter: filters.to_filter()?,
+                delete_extraneous: prune,
+                object_cache: options.object_cache.clone(),
+                link_strategy: options.link_strategy,
+                max_errors: error_policy.max_errors(),
+                compression: options.compression,
+                delta_threshold: options.delta_threshold,
+                ignore_permissions: options.ignore_permissions,
                 ..Default::default()
             };
             
-            let sync_manager = SyncManager::new(client.clone(), config);
-            let result = sync_manager.sync(path, &uri).await?;
+            let sync_manager = sync_manager(&client, config, metrics, cancel);
+            let result = watch(sync_manager.sync_with_events(path, &uri), options).await?;
+            write_summary(summary_json.as_deref(), &result)?;
             
             // Update index with new head
             let branch = client.get_branch(&uri.repository, &uri.reference).await?;
@@ -114,55 +207,214 @@ pub async fn execute(
             
             println!(
                 "Pulled changes from {}",
-                uri.to_string()
+                uri
             );
             println!(
-                "Downloaded: {}, Uploaded: {}, Removed: {}, Errors: {}",
+                "Downloaded: {}, Uploaded: {}, Removed: {}, Unchanged: {}, Errors: {}",
                 result.downloaded,
                 result.uploaded,
                 result.removed,
+                result.unchanged,
                 result.errors.len()
             );
+            check_errors(&result)?;
         }
         
-        LocalCommands::Commit { path, message } => {
-            if message.trim().is_empty() {
-                anyhow::bail!("Commit message cannot be empty");
-            }
-            
+        LocalCommands::Pin { commit, path, limit_rate } => {
             let path = Path::new(&path);
-            let mut index = LocalIndex::load(path)?;
-            
-            let uri = LakeFSUri::new(&index.repository, &index.reference);
+            let index = LocalIndex::load(path)?;
+            if !index.snapshot {
+                anyhow::bail!(
+                    "{} follows branch {}; only snapshot clones can be pinned",
+                    path.display(),
+                    index.reference
+                );
+            }
             
-            // First sync to upload changes
+            let commit = client.get_commit(&index.repository, &commit).await?;
+            let uri = LakeFSUri::new(&index.repository, &commit.id);
             let config = SyncConfig {
                 parallelism: options.parallelism,
-                show_progress: !options.no_progress,
+                max_download_bandwidth: limit_rate.as_deref().map(parse_rate).transpose()?,
+                delete_extraneous: true,
+                object_cache: options.object_cache.clone(),
+                link_strategy: options.link_strategy,
+                ignore_permissions: options.ignore_permissions,
                 ..Default::default()
             };
             
-            let sync_manager = SyncManager::new(client.clone(), config);
-            let result = sync_manager.sync(path, &uri).await?;
+            let sync_manager = sync_manager(&client, config, metrics, cancel);
+            let result = watch(sync_manager.pull_with_events(path, &uri), options).await?;
             
-            if result.uploaded == 0 && result.removed == 0 {
-                println!("No changes to commit");
-                return Ok(());
+            println!("Pinned {} to commit {}", path.display(), commit.id);
+            println!(
+                "Downloaded: {}, Removed: {}, Errors: {}",
+                result.downloaded,
+                result.removed,
+                result.errors.len()
+            );
+            check_errors(&result)?;
+        }
+        
+        LocalCommands::Push { path, verify_remote, limit_rate, summary_json, error_policy, filters } => {
+            let path = Path::new(&path);
+            let (uri, result) = push(path, &client, options, None, verify_remote, limit_rate, summary_json, &error_policy, &filters, metrics, cancel).await?;
+            
+            println!("Pushed local changes to {}", uri);
+            println!(
+                "Uploaded: {}, Removed: {}, Unchanged: {}, Errors: {}",
+                result.uploaded,
+                result.removed,
+                result.unchanged,
+                result.errors.len()
+            );
+            check_errors(&result)?;
+        }
+        
+        LocalCommands::Commit { path, message, file, metadata, pre_check, no_verify, allow_partial, transactional, via_branch, verify_remote, limit_rate, summary_json, error_policy, filters } => {
+            if message.as_deref().is_some_and(|m| m.trim().is_empty()) {
+                anyhow::bail!("Commit message cannot be empty");
+            }
+            let mut metadata = parse_metadata(&metadata)?;
+            // Read before uploading, so a missing file fails fast
+            let message = match file {
+                Some(file) => Some(read_message_file(&file)?),
+                None => message,
+            };
+            let verify = |message: &str| if no_verify { Ok(()) } else { verify_message(message, rules) };
+            if let Some(message) = &message {
+                verify(message)?;
             }
             
-            // Create commit
-            let commit = client.commit(
-                &uri.repository,
-                &uri.reference,
-                &message,
-            ).await?;
+            let path = Path::new(&path);
+            // What the index held before, for reverting this push's changes
+            let before = LocalIndex::load(path)?;
+            let scratch = match via_branch {
+                true => Some(scratch_branch(&client, &before).await?),
+                false => None,
+            };
+            let staged = async {
+                let (uri, result) = push(path, &client, options, scratch.as_deref(), verify_remote, limit_rate, summary_json, &error_policy, &filters, metrics, cancel).await?;
+                // Committing a partial upload would record an inconsistent tree
+                if allow_partial {
+                    if let Some(failure) = report_errors(&result) {
+                        eprintln!("Warning: {}; committing the changes that were uploaded", failure);
+                    }
+                } else {
+                    check_errors(&result).map_err(|e| {
+                        e.context("No commit was created; run it again to retry the failed files, or pass --allow-partial")
+                    })?;
+                }
+                
+                // Earlier pushes may have staged changes even if this one had none
+                if result.uploaded == 0 && result.removed == 0 {
+                    let params = PaginationParams { prefix: None, after: None, amount: Some(1), ..Default::default() };
+                    let staged = client.diff_uncommitted(&uri.repository, &uri.reference, &params).await?;
+                    if staged.results.is_empty() {
+                        println!("No changes to commit");
+                        return Ok(None);
+                    }
+                }
+                
+                let message = match message {
+                    Some(message) => message,
+                    None => {
+                        let preset = match &rules.template {
+                            Some(file) => std::fs::read_to_string(file)
+                                .with_context(|| format!("Failed to read commit template {}", file.display()))?,
+                            None => String::new(),
+                        };
+                        let template = commit_template(&client, &uri, &preset).await?;
+                        let file = path.join(COMMIT_MESSAGE_FILE);
+                        let message = editor::edit(&editor::editor(), &file, &template)?;
+                        // A scratch branch is deleted on the way out, taking the uploads with it
+                        let left = match &scratch {
+                            Some(_) => format!("nothing was pushed to {}", before.reference),
+                            None => format!("the changes stay uploaded to {}", uri),
+                        };
+                        if message.is_empty() || message == editor::strip_comments(&preset) {
+                            anyhow::bail!("Aborting commit due to empty commit message; {}", left);
+                        }
+                        verify(&message).with_context(|| {
+                            format!("The message is kept in {}; {}", file.display(), left)
+                        })?;
+                        message
+                    }
+                };
+                let message = commit_subject(&message, &mut metadata)?;
+                
+                if pre_check {
+                    let pending = PendingCommit {
+                        repository: &uri.repository,
+                        branch: &uri.reference,
+                        message: &message,
+                        metadata: &metadata,
+                    };
+                    actions::pre_check(&client, &pending).await?;
+                }
+                
+                if transactional {
+                    verify_staged(path, &client, &uri, &result, &before, options).await?;
+                }
+                
+                // Create commit
+                let commit = client.commit_with_metadata(
+                    &uri.repository,
+                    &uri.reference,
+                    &message,
+                    &metadata,
+                ).await?;
+                Ok(Some((commit, result)))
+            }.await;
+            let (commit, result) = match staged {
+                Ok(Some(staged)) => staged,
+                outcome => {
+                    if let Some(scratch) = &scratch {
+                        // Nothing was committed: the scratch branch and what
+                        // the index says was staged on it both go
+                        before.save(path)?;
+                        let deleted = client.delete_branch(&before.repository, scratch).await;
+                        outcome?;
+                        deleted?;
+                    } else {
+                        outcome?;
+                    }
+                    return Ok(());
+                }
+            };
+            println!("Created commit: {}", commit.id);
+            println!("Message: {}", commit.message);
+            
+            let head = match &scratch {
+                Some(scratch) => {
+                    let merged = match client.merge(&before.repository, scratch, &before.reference).await {
+                        Ok(merged) => merged,
+                        Err(e) => {
+                            before.save(path)?;
+                            return Err(anyhow::Error::new(e).context(format!(
+                                "Failed to merge {} into {}; commit {} stays on {}",
+                                scratch, before.reference, commit.id, scratch
+                            )));
+                        }
+                    };
+                    println!("Merged into {}: {}", before.reference, merged.id);
+                    merged.id
+                }
+                None => commit.id,
+            };
             
             // Update index with new head
-            index.update_head(&commit.id);
+            let mut index = LocalIndex::load(path)?;
+            index.update_head(&head);
             index.save(path)?;
             
-            println!("Created commit: {}", commit.id);
-            println!("Message: {}", commit.message);
+            // The merge landed, so a leftover scratch branch is only clutter
+            if let Some(scratch) = &scratch {
+                if let Err(e) = client.delete_branch(&before.repository, scratch).await {
+                    eprintln!("Warning: failed to delete scratch branch {}: {}", scratch, e);
+                }
+            }
+            
             println!(
                 "Uploaded: {}, Removed: {}",
                 result.uploaded,
@@ -172,4 +424,681 @@ pub async fn execute(
     }
     
     Ok(())
-}
\ No newline at end of file
+}
+
+/// `changes` under `title`, one per line as `git status` lists them.
+fn print_changes(title: &str, changes: &[(String, ChangeType)]) {
+    if changes.is_empty() {
+        println!("{}: none", title);
+        return;
+    }
+    println!("{}:", title);
+    for (path, change_type) in changes {
+        let label = match change_type {
+            ChangeType::Added => "added:",
+            ChangeType::Modified => "modified:",
+            ChangeType::Removed => "removed:",
+        };
+        println!("  {:<10}{}", label, path);
+    }
+}
+
+/// Read a commit message from `file`, or from stdin for `-`.
+fn read_message_file(file: &str) -> Result<String> {
+    let message = if file == "-" {
+        std::io::read_to_string(std::io::stdin())?
+    } else {
+        std::fs::read_to_string(file).with_context(|| format!("Failed to read commit message from {}", file))?
+    };
+    Ok(message)
+}
+
+/// The first line of `message`, which becomes the commit's message. A
+/// message of several lines is also kept whole, trailers included, under
+/// `FULL_MESSAGE_KEY` in `metadata`.
+fn commit_subject(message: &str, metadata: &mut HashMap<String, String>) -> Result<String> {
+    let message = message.trim();
+    let subject = message.lines().next().unwrap_or_default().trim_end();
+    if subject.is_empty() {
+        anyhow::bail!("Commit message cannot be empty");
+    }
+    if message.contains('\n') {
+        metadata.insert(FULL_MESSAGE_KEY.to_string(), message.to_string());
+    }
+    Ok(subject.to_string())
+}
+
+/// What the editor opens with: `preset`, the configured template, or else an
+/// empty first line, followed by the changes staged on the branch as comments.
+async fn commit_template(client: &LakeFSClient, uri: &LakeFSUri, preset: &str) -> Result<String> {
+    let params = PaginationParams::new().with_amount(TEMPLATE_CHANGES);
+    let staged = client.diff_uncommitted(&uri.repository, &uri.reference, &params).await?;
+    
+    let mut template = if preset.is_empty() {
+        String::from("\n")
+    } else {
+        format!("{}\n", preset.trim_end())
+    };
+    template.push_str(
+        "\
+         # Write the commit message for these changes. Lines starting with '#' are\n\
+         # ignored and an empty message aborts the commit. The first line becomes\n\
+         # the commit message; a longer message is kept whole in the commit's\n\
+         # metadata.\n\
+         #\n",
+    );
+    template.push_str(&format!("# Changes to be committed to {}:\n", uri));
+    for diff in &staged.results {
+        template.push_str(&format!("#\t{:<9}{}\n", format!("{}:", diff.diff_type), diff.path));
+    }
+    if staged.pagination.has_more {
+        template.push_str("#\t...\n");
+    }
+    Ok(template)
+}
+
+/// Upload the local changes under `path` to its tracked branch, or to
+/// `branch`, without committing them.
+#[allow(clippy::too_many_arguments)]
+async fn push(
+    path: &Path,
+    client: &LakeFSClient,
+    options: &OptionsConfig,
+    branch: Option<&str>,
+    verify_remote: bool,
+    limit_rate: Option<String>,
+    summary_json: Option<String>,
+    error_policy: &ErrorPolicyArgs,
+    filters: &FilterArgs,
+    metrics: Option<Arc<SyncMetrics>>,
+    cancel: CancellationToken,
+) -> Result<(LakeFSUri, SyncResult)> {
+    let index = LocalIndex::load(path)?;
+    if index.snapshot {
+        anyhow::bail!("{} is a read-only snapshot of commit {}", path.display(), index.reference);
+    }
+    let uri = LakeFSUri::new(&index.repository, branch.unwrap_or(&index.reference));
+    
+    let config = SyncConfig {
+        parallelism: options.parallelism,
+        verify_remote,
+        max_upload_bandwidth: limit_rate.as_deref().map(parse_rate).transpose()?,
+        filter: filters.to_filter()?,
+        max_errors: error_policy.max_errors(),
+        compression: options.compression,
+        delta_threshold: options.delta_threshold,
+        ignore_permissions: options.ignore_permissions,
+        ..Default::default()
+    };
+    
+    let sync_manager = sync_manager(client, config, metrics, cancel);
+    let result = watch(sync_manager.push_with_events(path, &uri), options).await?;
+    write_summary(summary_json.as_deref(), &result)?;
+    
+    Ok((uri, result))
+}
+
+/// A new branch from the head commit of the checkout `index` describes,
+/// for `local commit --via-branch` to commit on and merge.
+async fn scratch_branch(client: &LakeFSClient, index: &LocalIndex) -> Result<String> {
+    let branch = format!("lakectl-commit-{:08x}", fastrand::u32(..));
+    client.create_branch(&index.repository, &branch, &index.head_commit)
+        .await
+        .with_context(|| format!("Failed to create branch {} from {}", branch, index.head_commit))?;
+    Ok(branch)
+}
+
+/// Check that the uncommitted changes on `uri`'s branch are the ones the
+/// checkout at `path` staged, and revert those `result` pushed if another
+/// writer's show up.
+async fn verify_staged(
+    path: &Path,
+    client: &LakeFSClient,
+    uri: &LakeFSUri,
+    result: &SyncResult,
+    before: &LocalIndex,
+    options: &OptionsConfig,
+) -> Result<()> {
+    let config = SyncConfig {
+        parallelism: options.parallelism,
+        ..Default::default()
+    };
+    let manager = SyncManager::new(Arc::new(client.clone()), config);
+    let mismatches = manager.verify_staged(path, uri, result).await?;
+    if mismatches.is_empty() {
+        return Ok(());
+    }
+    
+    for mismatch in &mismatches {
+        eprintln!("  {}", mismatch);
+    }
+    let reverted = manager.revert_staged(path, uri, result, before, &mismatches).await?;
+    anyhow::bail!(
+        "{} paths on {} changed while committing; reverted the {} changes this push staged instead",
+        mismatches.len(),
+        uri,
+        reverted
+    )
+}
+
+#[derive(Tabled)]
+struct StashRow {
+    id: String,
+    created: String,
+    branch: String,
+    files: usize,
+    deleted: usize,
+    message: String,
+}
+
+async fn stash(command: StashCommands, client: &LakeFSClient) -> Result<()> {
+    match command {
+        StashCommands::Push { path, message } => {
+            match lakefs_local::stash::push(client, Path::new(&path), message).await? {
+                Some(stash) => println!(
+                    "Stashed {} changed and {} deleted files as {}",
+                    stash.files.len(),
+                    stash.deleted.len(),
+                    stash.id
+                ),
+                None => println!("No local changes to stash"),
+            }
+        }
+        
+        StashCommands::Pop { path, id } => {
+            let stash = lakefs_local::stash::pop(Path::new(&path), id.as_deref())?;
+            println!(
+                "Restored {} changed and {} deleted files from {}",
+                stash.files.len(),
+                stash.deleted.len(),
+                stash.id
+            );
+        }
+        
+        StashCommands::List { path } => {
+            let stashes = lakefs_local::stash::list(Path::new(&path))?;
+            if stashes.is_empty() {
+                println!("No stashes");
+                return Ok(());
+            }
+            let rows = stashes.into_iter().map(|stash| StashRow {
+                id: stash.id,
+                created: stash.created.format("%Y-%m-%d %H:%M:%S").to_string(),
+                branch: stash.reference,
+                files: stash.files.len(),
+                deleted: stash.deleted.len(),
+                message: stash.message.unwrap_or_default(),
+            });
+            println!("{}", Table::new(rows));
+        }
+    }
+    
+    Ok(())
+}
+
+fn sync_manager(
+    client: &LakeFSClient,
+    config: SyncConfig,
+    metrics: Option<Arc<SyncMetrics>>,
+    cancel: CancellationToken,
+) -> SyncManager {
+    let manager = SyncManager::new(Arc::new(client.clone()), config).with_cancellation(cancel);
+    match metrics {
+        Some(metrics) => manager.with_metrics(metrics),
+        None => manager,
+    }
+}
+
+/// Drive a sync to its end through its `events`, drawn as progress bars
+/// unless progress is turned off.
+async fn watch(events: impl Stream<Item = SyncEvent>, options: &OptionsConfig) -> Result<SyncResult> {
+    let display = (!options.no_progress).then(MultiProgressReporter::new);
+    let mut events = std::pin::pin!(events);
+    while let Some(event) = events.next().await {
+        if let Some(display) = &display {
+            display.handle(&event);
+        }
+        if let SyncEvent::Finished(result) = event {
+            return Ok(result?);
+        }
+    }
+    anyhow::bail!("sync ended without a result")
+}
+
+#[derive(Tabled)]
+struct ErrorRow {
+    path: String,
+    error: String,
+}
+
+/// List the files a sync failed on and fail the command if there were any.
+pub(super) fn check_errors(result: &SyncResult) -> Result<()> {
+    match report_errors(result) {
+        Some(failure) => Err(anyhow::anyhow!(failure)),
+        None => Ok(()),
+    }
+}
+
+/// Print the failed files of `result` as a table to stderr, returning a
+/// summary of the failure if there was one.
+fn report_errors(result: &SyncResult) -> Option<String> {
+    if result.errors.is_empty() {
+        return None;
+    }
+    
+    let rows = result.errors.iter().map(|(path, error)| ErrorRow {
+        path: path.clone(),
+        error: error.to_string(),
+    });
+    eprintln!("{}", Table::new(rows));
+    if result.skipped > 0 {
+        return Some(format!(
+            "{} files failed to sync; stopped with {} changes not applied",
+            result.errors.len(),
+            result.skipped
+        ));
+    }
+    Some(format!("{} files failed to sync", result.errors.len()))
+}
+
+fn write_summary(path: Option<&str>, result: &SyncResult) -> Result<()> {
+    if let Some(path) = path {
+        let report = serde_json::to_string_pretty(&result.summary_json())?;
+        std::fs::write(path, report)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lakefs_local::{TransferDirection, TransferRecord};
+    use lakefs_test_utils::scenario::MockLakeFS;
+    use std::time::Duration;
+    use tempfile::TempDir;
+    use wiremock::matchers::{body_partial_json, method, path, path_regex, query_param};
+    use wiremock::{Mock, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_commit_without_changes() {
+        let server = MockLakeFS::new()
+            .with_repo("repo")
+            .with_branch("main")
+            .start()
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repositories/repo/branches/main/diff"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "results": [],
+                "pagination": { "has_more": false, "max_per_page": 1, "next_offset": null, "results": 0 }
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/repositories/repo/branches/main/commits"))
+            .respond_with(ResponseTemplate::new(500))
+            .expect(0)
+            .mount(&server)
+            .await;
+        let client = LakeFSClient::new(server.uri(), "Bearer test-token");
+        
+        let temp_dir = TempDir::new().unwrap();
+        LocalIndex::new("repo", "main", "c0").save(temp_dir.path()).unwrap();
+        
+        let options = OptionsConfig { no_progress: true, ..Default::default() };
+        let command = LocalCommands::Commit {
+            path: temp_dir.path().to_string_lossy().to_string(),
+            message: Some("nothing".to_string()),
+            file: None,
+            metadata: Vec::new(),
+            pre_check: false,
+            no_verify: false,
+            allow_partial: false,
+            transactional: false,
+            via_branch: false,
+            verify_remote: false,
+            limit_rate: None,
+            summary_json: None,
+            error_policy: ErrorPolicyArgs::default(),
+            filters: FilterArgs::default(),
+        };
+        execute(command, client, &options, &CommitConfig::default(), None, CancellationToken::new()).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_commit_message_from_file() {
+        let server = MockLakeFS::new()
+            .with_repo("repo")
+            .with_branch("main")
+            .start()
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repositories/repo/branches/main/diff"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "results": [{"path": "a.csv", "path_type": "object", "type": "added"}],
+                "pagination": { "has_more": false, "max_per_page": 1, "next_offset": null, "results": 1 }
+            })))
+            .mount(&server)
+            .await;
+        let full_message = "Add data\n\nFrom the nightly export.\n\nSigned-off-by: alice <alice@example.com>";
+        Mock::given(method("POST"))
+            .and(path("/repositories/repo/branches/main/commits"))
+            .and(body_partial_json(serde_json::json!({
+                "message": "Add data",
+                "metadata": {"full_message": full_message, "team": "data"}
+            })))
+            .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!({
+                "id": "c1",
+                "parents": ["c0"],
+                "committer": "alice",
+                "message": "Add data",
+                "creation_date": 1_700_000_000,
+                "meta_range_id": "",
+                "metadata": {}
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let client = LakeFSClient::new(server.uri(), "Bearer test-token");
+        
+        let temp_dir = TempDir::new().unwrap();
+        let checkout = temp_dir.path().join("checkout");
+        LocalIndex::new("repo", "main", "c0").save(&checkout).unwrap();
+        let message_file = temp_dir.path().join("message.txt");
+        std::fs::write(&message_file, format!("{}\n\n", full_message)).unwrap();
+        
+        let options = OptionsConfig { no_progress: true, ..Default::default() };
+        let command = LocalCommands::Commit {
+            path: checkout.to_string_lossy().to_string(),
+            message: None,
+            file: Some(message_file.to_string_lossy().to_string()),
+            metadata: vec!["team=data".to_string()],
+            pre_check: false,
+            no_verify: false,
+            allow_partial: false,
+            transactional: false,
+            via_branch: false,
+            verify_remote: false,
+            limit_rate: None,
+            summary_json: None,
+            error_policy: ErrorPolicyArgs::default(),
+            filters: FilterArgs::default(),
+        };
+        execute(command, client, &options, &CommitConfig::default(), None, CancellationToken::new()).await.unwrap();
+        assert_eq!(LocalIndex::load(&checkout).unwrap().head_commit, "c1");
+    }
+
+    #[tokio::test]
+    async fn test_commit_refuses_partial_push() {
+        let server = MockLakeFS::new()
+            .with_repo("repo")
+            .with_branch("main")
+            .start()
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/repositories/repo/branches/main/objects"))
+            .and(query_param("path", "good.csv"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(lakefs_test_utils::fixtures::test_object_stats()))
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/repositories/repo/branches/main/objects"))
+            .and(query_param("path", "bad.csv"))
+            .respond_with(ResponseTemplate::new(400))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repositories/repo/branches/main/diff"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "results": [{"path": "good.csv", "path_type": "object", "type": "added"}],
+                "pagination": { "has_more": false, "max_per_page": 1, "next_offset": null, "results": 1 }
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/repositories/repo/branches/main/commits"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!({
+                "id": "c1",
+                "parents": ["c0"],
+                "committer": "alice",
+                "message": "Add data",
+                "creation_date": 1_700_000_000,
+                "meta_range_id": "",
+                "metadata": {}
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        
+        let temp_dir = TempDir::new().unwrap();
+        LocalIndex::new("repo", "main", "c0").save(temp_dir.path()).unwrap();
+        std::fs::write(temp_dir.path().join("good.csv"), "good").unwrap();
+        std::fs::write(temp_dir.path().join("bad.csv"), "bad").unwrap();
+        
+        let options = OptionsConfig { no_progress: true, ..Default::default() };
+        let command = |allow_partial| LocalCommands::Commit {
+            path: temp_dir.path().to_string_lossy().to_string(),
+            message: Some("Add data".to_string()),
+            file: None,
+            metadata: Vec::new(),
+            pre_check: false,
+            no_verify: false,
+            allow_partial,
+            transactional: false,
+            via_branch: false,
+            verify_remote: false,
+            limit_rate: None,
+            summary_json: None,
+            error_policy: ErrorPolicyArgs::default(),
+            filters: FilterArgs::default(),
+        };
+        let client = || LakeFSClient::new(server.uri(), "Bearer test-token");
+        let error = execute(command(false), client(), &options, &CommitConfig::default(), None, CancellationToken::new()).await.unwrap_err();
+        assert!(error.to_string().contains("--allow-partial"));
+        assert_eq!(LocalIndex::load(temp_dir.path()).unwrap().head_commit, "c0");
+        
+        execute(command(true), client(), &options, &CommitConfig::default(), None, CancellationToken::new()).await.unwrap();
+        assert_eq!(LocalIndex::load(temp_dir.path()).unwrap().head_commit, "c1");
+    }
+
+    #[tokio::test]
+    async fn test_commit_via_branch() {
+        let server = MockLakeFS::new()
+            .with_repo("repo")
+            .with_branch("main")
+            .start()
+            .await;
+        let scratch = "lakectl-commit-[0-9a-f]{8}";
+        Mock::given(method("POST"))
+            .and(path("/repositories/repo/branches"))
+            .and(body_partial_json(serde_json::json!({"source": "c0"})))
+            .respond_with(ResponseTemplate::new(201).set_body_json(lakefs_test_utils::fixtures::test_branch()))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path_regex(format!("^/repositories/repo/refs/{}/objects$", scratch)))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "results": [],
+                "pagination": { "has_more": false, "max_per_page": 1000, "next_offset": null, "results": 0 }
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path_regex(format!("^/repositories/repo/branches/{}/objects$", scratch)))
+            .and(query_param("path", "a.csv"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(lakefs_test_utils::fixtures::test_object_stats()))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path_regex(format!("^/repositories/repo/branches/{}/commits$", scratch)))
+            .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!({
+                "id": "c1",
+                "parents": ["c0"],
+                "committer": "alice",
+                "message": "Add data",
+                "creation_date": 1_700_000_000,
+                "meta_range_id": "",
+                "metadata": {}
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path_regex(format!("^/repositories/repo/refs/{}/merge/main$", scratch)))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({"reference": "m1"})))
+            .expect(1)
+            .mount(&server)
+            .await;
+        // Failing to clean up after the merge must not lose the new head
+        Mock::given(method("DELETE"))
+            .and(path_regex(format!("^/repositories/repo/branches/{}$", scratch)))
+            .respond_with(ResponseTemplate::new(500))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let client = LakeFSClient::new(server.uri(), "Bearer test-token");
+        
+        let temp_dir = TempDir::new().unwrap();
+        LocalIndex::new("repo", "main", "c0").save(temp_dir.path()).unwrap();
+        std::fs::write(temp_dir.path().join("a.csv"), "data").unwrap();
+        
+        let options = OptionsConfig { no_progress: true, ..Default::default() };
+        let command = LocalCommands::Commit {
+            path: temp_dir.path().to_string_lossy().to_string(),
+            message: Some("Add data".to_string()),
+            file: None,
+            metadata: Vec::new(),
+            pre_check: false,
+            no_verify: false,
+            allow_partial: false,
+            transactional: false,
+            via_branch: true,
+            verify_remote: false,
+            limit_rate: None,
+            summary_json: None,
+            error_policy: ErrorPolicyArgs::default(),
+            filters: FilterArgs::default(),
+        };
+        execute(command, client, &options, &CommitConfig::default(), None, CancellationToken::new()).await.unwrap();
+        
+        let index = LocalIndex::load(temp_dir.path()).unwrap();
+        assert_eq!(index.head_commit, "m1");
+        assert_eq!(index.reference, "main");
+        assert!(index.entries.contains_key("a.csv"));
+    }
+
+    #[tokio::test]
+    async fn test_commit_requires_trailers() {
+        let temp_dir = TempDir::new().unwrap();
+        LocalIndex::new("repo", "main", "c0").save(temp_dir.path()).unwrap();
+        
+        // Nothing is listening: the message must be refused before uploading
+        let client = LakeFSClient::new("http://127.0.0.1:9", "Bearer test-token");
+        let options = OptionsConfig { no_progress: true, ..Default::default() };
+        let rules = CommitConfig { required_trailers: vec!["Jira".to_string()], ..Default::default() };
+        let command = LocalCommands::Commit {
+            path: temp_dir.path().to_string_lossy().to_string(),
+            message: Some("Add data".to_string()),
+            file: None,
+            metadata: Vec::new(),
+            pre_check: false,
+            no_verify: false,
+            allow_partial: false,
+            transactional: false,
+            via_branch: false,
+            verify_remote: false,
+            limit_rate: None,
+            summary_json: None,
+            error_policy: ErrorPolicyArgs::default(),
+            filters: FilterArgs::default(),
+        };
+        let error = execute(command, client, &options, &rules, None, CancellationToken::new()).await.unwrap_err();
+        assert!(error.to_string().contains("missing the required trailer 'Jira: ...'"), "{}", error);
+    }
+
+    #[test]
+    fn test_commit_subject() {
+        let mut metadata = HashMap::new();
+        assert_eq!(commit_subject("  Fix typo \n", &mut metadata).unwrap(), "Fix typo");
+        assert!(metadata.is_empty());
+        assert!(commit_subject(" \n ", &mut metadata).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_refuses_commit() {
+        let temp_dir = TempDir::new().unwrap();
+        LocalIndex::snapshot("repo", "c0").save(temp_dir.path()).unwrap();
+        
+        // Nothing is listening: the command must fail before any request
+        let client = LakeFSClient::new("http://127.0.0.1:9", "Bearer test-token");
+        let options = OptionsConfig { no_progress: true, ..Default::default() };
+        let command = LocalCommands::Commit {
+            path: temp_dir.path().to_string_lossy().to_string(),
+            message: Some("change".to_string()),
+            file: None,
+            metadata: Vec::new(),
+            pre_check: false,
+            no_verify: false,
+            allow_partial: false,
+            transactional: false,
+            via_branch: false,
+            verify_remote: false,
+            limit_rate: None,
+            summary_json: None,
+            error_policy: ErrorPolicyArgs::default(),
+            filters: FilterArgs::default(),
+        };
+        let error = execute(command, client, &options, &CommitConfig::default(), None, CancellationToken::new()).await.unwrap_err();
+        assert!(error.to_string().contains("read-only snapshot of commit c0"));
+    }
+
+    #[test]
+    fn test_write_summary() {
+        let temp_dir = TempDir::new().unwrap();
+        let report_path = temp_dir.path().join("summary.json");
+        let result = SyncResult {
+            uploaded: 0,
+            downloaded: 1,
+            removed: 0,
+            unchanged: 0,
+            skipped: 0,
+            errors: Vec::new(),
+            files: vec![TransferRecord {
+                path: "data/file.csv".into(),
+                direction: TransferDirection::Download,
+                bytes: 128,
+                duration: Duration::from_millis(7),
+                error: None,
+            }],
+        };
+        
+        write_summary(Some(report_path.to_str().unwrap()), &result).unwrap();
+        
+        let report: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&report_path).unwrap()).unwrap();
+        assert_eq!(report["downloaded"], 1);
+        assert_eq!(report["bytes_downloaded"], 128);
+        assert_eq!(report["files"][0]["path"], "data/file.csv");
+    }
+
+    #[test]
+    fn test_check_errors() {
+        let mut result = SyncResult {
+            uploaded: 1,
+            downloaded: 0,
+            removed: 0,
+            unchanged: 0,
+            skipped: 0,
+            errors: Vec::new(),
+            files: Vec::new(),
+        };
+        assert!(check_errors(&result).is_ok());
+        
+        result.errors.push(("data/a.csv".into(), lakefs_local::Error::Sync("boom".into())));
+        result.skipped = 4;
+        let error = check_errors(&result).unwrap_err();
+        assert_eq!(error.to_string(), "1 files failed to sync; stopped with 4 changes not applied");
+    }
+}
@@ -1,28 +1,171 @@
-use crate::utils::parse_uri;
+use crate::commands::diff::collect_diffs;
+use crate::utils::{format_diff_type, parse_uri, resolve_uri};
 use anyhow::Result;
-use lakefs_api::LakeFSClient;
+use lakefs_api::models::{Diff, DiffType};
+use lakefs_api::{LakeFSClient, MergeOptions};
+use std::collections::HashMap;
+
+/// Commit metadata key recording which ref a squash merge came from.
+const SQUASH_SOURCE_KEY: &str = "squash_source";
+
+pub struct MergeArgs {
+    pub strategy: Option<String>,
+    pub dry_run: bool,
+    pub squash: bool,
+    pub message: Option<String>,
+}
 
 pub async fn execute(
     source: String,
     destination: String,
-    _strategy: Option<String>,  // Currently unused
+    args: MergeArgs,
     client: LakeFSClient,
 ) -> Result<()> {
-    let source_uri = parse_uri(&source)?;
+    let source_uri = resolve_uri(&client, &source).await?;
     let dest_uri = parse_uri(&destination)?;
     
     if source_uri.repository != dest_uri.repository {
         anyhow::bail!("Cannot merge across different repositories");
     }
     
-    let merge_result = client.merge(
+    if args.dry_run {
+        // lakeFS diffs refs from their merge base by default, which is
+        // exactly what a merge would apply to the destination
+        let diffs = collect_diffs(
+            &client,
+            &source_uri.repository,
+            &dest_uri.reference,
+            &source_uri.reference,
+            None,
+        ).await?;
+        print!("{}", dry_run_summary(&source, &destination, &diffs));
+        return Ok(());
+    }
+    
+    let mut options = MergeOptions {
+        message: args.message,
+        strategy: args.strategy,
+        squash_merge: args.squash,
+        ..Default::default()
+    };
+    if args.squash {
+        options.message.get_or_insert_with(|| format!("Squash merge {} into {}", source, destination));
+        options.metadata = Some(HashMap::from([
+            (SQUASH_SOURCE_KEY.to_string(), source_uri.reference.clone()),
+        ]));
+    }
+    
+    let merge_result = client.merge_with(
         &source_uri.repository,
         &source_uri.reference,
         &dest_uri.reference,
+        &options,
     ).await?;
     
-    println!("Merged {} into {}", source, destination);
+    let verb = if args.squash { "Squash merged" } else { "Merged" };
+    println!("{} {} into {}", verb, source, destination);
     println!("Merge commit: {}", merge_result.id);
     
     Ok(())
-}
\ No newline at end of file
+}
+
+fn dry_run_summary(source: &str, destination: &str, diffs: &[Diff]) -> String {
+    if diffs.is_empty() {
+        return format!("Nothing to merge: {} already contains {}\n", destination, source);
+    }
+    
+    let count = |diff_type: fn(&DiffType) -> bool| diffs.iter().filter(|d| diff_type(&d.diff_type)).count();
+    let mut summary = format!(
+        "Merging {} into {} would apply {} changes ({} added, {} removed, {} changed):\n",
+        source,
+        destination,
+        diffs.len(),
+        count(|t| matches!(t, DiffType::Added)),
+        count(|t| matches!(t, DiffType::Removed)),
+        count(|t| matches!(t, DiffType::Changed)),
+    );
+    for diff in diffs {
+        summary.push_str(&format!("{} {}\n", format_diff_type(&diff.diff_type.to_string()), diff.path));
+    }
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{body_json, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_merge_dry_run_does_not_merge() {
+        let mock_server = MockServer::start().await;
+        
+        Mock::given(method("GET"))
+            .and(path("/repositories/repo/refs/main/diff/feature"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "results": [
+                    { "path": "a.csv", "path_type": "object", "type": "added" },
+                    { "path": "b.csv", "path_type": "object", "type": "changed" }
+                ],
+                "pagination": { "has_more": false, "max_per_page": 1000, "next_offset": null, "results": 2 }
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
+        
+        let client = LakeFSClient::new(mock_server.uri(), "Bearer test-token");
+        let args = MergeArgs { strategy: None, dry_run: true, squash: false, message: None };
+        execute(
+            "lakefs://repo/feature".to_string(),
+            "lakefs://repo/main".to_string(),
+            args,
+            client,
+        ).await.unwrap();
+    }
+    
+    #[tokio::test]
+    async fn test_squash_merge_records_source() {
+        let mock_server = MockServer::start().await;
+        
+        Mock::given(method("POST"))
+            .and(path("/repositories/repo/refs/feature/merge/main"))
+            .and(body_json(serde_json::json!({
+                "message": "Squash merge lakefs://repo/feature into lakefs://repo/main",
+                "metadata": { "squash_source": "feature" },
+                "squash_merge": true
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "id": "abc" })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        
+        let client = LakeFSClient::new(mock_server.uri(), "Bearer test-token");
+        let args = MergeArgs { strategy: None, dry_run: false, squash: true, message: None };
+        execute(
+            "lakefs://repo/feature".to_string(),
+            "lakefs://repo/main".to_string(),
+            args,
+            client,
+        ).await.unwrap();
+    }
+    
+    #[test]
+    fn test_dry_run_summary_counts() {
+        let diffs: Vec<Diff> = serde_json::from_value(serde_json::json!([
+            { "path": "a.csv", "path_type": "object", "type": "added" },
+            { "path": "b.csv", "path_type": "object", "type": "removed" },
+            { "path": "c.csv", "path_type": "object", "type": "added" }
+        ])).unwrap();
+        
+        let summary = dry_run_summary("feature", "main", &diffs);
+        assert!(summary.starts_with("Merging feature into main would apply 3 changes (2 added, 1 removed, 0 changed):"));
+        assert!(summary.lines().any(|line| line.ends_with(" b.csv")));
+        
+        assert_eq!(dry_run_summary("feature", "main", &[]), "Nothing to merge: main already contains feature\n");
+    }
+}
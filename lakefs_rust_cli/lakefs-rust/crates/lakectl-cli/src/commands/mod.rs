@@ -1,41 +1,232 @@
 mod repo;
+mod abuse;
+mod archive;
+mod actions;
+mod bench;
 mod branch;
+mod checksum;
 mod commit;
 mod fs;
 mod diff;
 mod merge;
 mod local;
+mod metadata;
+mod refs;
+mod complete;
+mod replicate;
+mod export;
+mod ingest;
+mod audit;
+mod stats;
+mod browse;
+pub mod pick;
+pub mod config;
+pub mod daemon;
 
-use crate::cli::{Cli, Commands};
-use crate::config::AppConfig;
+use crate::cli::{Cli, Commands, DaemonCommands};
+use crate::config::{load_config_file, AppConfig};
+use crate::hints;
+use crate::telemetry::TraceContextMiddleware;
 use anyhow::Result;
-use lakefs_api::LakeFSClient;
-use lakefs_auth::create_auth_provider;
+use lakefs_api::{default_user_agent, Cassette, ClientConfig, LakeFSClient, ResponseCache};
+use lakefs_auth::{create_auth_provider, AuthProvider};
+use lakefs_local::{LocalIndex, SyncMetrics};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
 
-pub async fn execute(cli: Cli, config: AppConfig) -> Result<()> {
-    // Create auth provider
-    let auth_provider = create_auth_provider(
-        config.credentials.clone(),
-        &config.server.endpoint_url,
-    ).await?;
+/// Memory for responses read at commit IDs, which never change
+const RESPONSE_CACHE_BYTES: u64 = 64 * 1024 * 1024;
+
+pub async fn execute(cli: Cli, config: AppConfig, cancel: CancellationToken) -> Result<()> {
+    // Settings for the target repository take over from here
+    let (repository, reference) = (cli.command.repository(), cli.command.reference());
+    let checkout = cli.command.checkout().map(PathBuf::from);
+    let config = match &repository {
+        Some(repository) => config.for_repository(repository)?,
+        None => config,
+    };
+    let endpoint = config.server.endpoint_url.clone();
+    
+    let (client, metrics) = match build_client(&cli, &config).await {
+        Ok(built) => built,
+        Err(e) => return Err(hints::with_hint(e, &endpoint, None).await),
+    };
+    match run(cli, config, client.clone(), metrics, cancel).await {
+        Ok(()) => Ok(()),
+        Err(e) => {
+            // Local commands work on the repository and branch their checkout tracks
+            let (repository, reference) = match checkout.map(|path| LocalIndex::load(&path)) {
+                Some(Ok(index)) => (Some(index.repository), reference.or(Some(index.reference))),
+                _ => (repository, reference),
+            };
+            let target = repository.as_deref().map(|repository| hints::Target {
+                client: &client,
+                repository,
+                reference: reference.as_deref(),
+            });
+            Err(hints::with_hint(e, &endpoint, target).await)
+        }
+    }
+}
+
+/// Client for the command, and the sync metrics it records into when
+/// `--metrics-file` is given or it runs the daemon.
+async fn build_client(cli: &Cli, config: &AppConfig) -> Result<(LakeFSClient, Option<Arc<SyncMetrics>>)> {
+    let mut user_agent = config.server.user_agent
+        .clone()
+        .unwrap_or_else(default_user_agent);
+    if config.options.usage_telemetry {
+        user_agent.push_str(&format!(" command/{}", cli.command.name()));
+    }
     
-    // Create client
-    let auth_header = auth_provider.get_auth_header().await?;
-    let client = LakeFSClient::new(&config.server.endpoint_url, auth_header);
+    // Load tests time the server, so reads must not be answered from memory
+    let response_cache = match cli.command {
+        Commands::Abuse { .. } => None,
+        _ => Some(Arc::new(ResponseCache::in_memory(RESPONSE_CACHE_BYTES))),
+    };
+    let mut client_config = ClientConfig {
+        user_agent: Some(user_agent),
+        response_cache,
+        cassette: cassette_from_env()?.map(Arc::new),
+        ..Default::default()
+    };
+    let runs_daemon = matches!(cli.command, Commands::Daemon { .. });
+    if cli.metrics_file.is_some() && !runs_daemon && !matches!(cli.command, Commands::Local { .. }) {
+        anyhow::bail!("--metrics-file only applies to local commands and daemon start");
+    }
+    let metrics = (cli.metrics_file.is_some() || runs_daemon).then(|| Arc::new(SyncMetrics::new()));
+    if let Some(metrics) = &metrics {
+        client_config.middleware.push(metrics.clone());
+    }
+    if matches!(cli.command, Commands::ListRepos { .. } | Commands::ListRefs { .. }) {
+        client_config.timeout = Some(complete::TIMEOUT);
+    }
+    if config.telemetry.is_some() {
+        client_config.middleware.push(Arc::new(TraceContextMiddleware));
+    }
     
-    // Execute command
+    let provider = auth_provider(config).await?;
+    let client = LakeFSClient::from_provider(&config.server.endpoint_url, provider, client_config)?;
+    Ok((client, metrics))
+}
+
+/// Provider of the credentials in `config`, which every clone of a client
+/// asks per request so tokens are refreshed as they expire. It is asked once
+/// here so bad credentials fail before the command starts; providers cache
+/// what they fetch, so the first request reuses it.
+async fn auth_provider(config: &AppConfig) -> Result<Arc<dyn AuthProvider>> {
+    let provider: Arc<dyn AuthProvider> = create_auth_provider(
+        config.credentials.clone(),
+        &config.server.endpoint_url,
+    ).await?.into();
+    provider.get_auth_header().await?;
+    Ok(provider)
+}
+
+async fn run(
+    cli: Cli,
+    config: AppConfig,
+    client: LakeFSClient,
+    metrics: Option<Arc<SyncMetrics>>,
+    cancel: CancellationToken,
+) -> Result<()> {
     match cli.command {
-        Commands::Repo { command } => repo::execute(command, client).await,
+        Commands::Repo { command } => repo::execute(command, client, &config.options).await,
         Commands::Branch { command } => branch::execute(command, client).await,
-        Commands::Commit { branch, message, allow_empty } => {
-            commit::execute(branch, message, allow_empty, client).await
+        Commands::Commit { branch, message, allow_empty, metadata, amend, expected_head, no_verify } => {
+            if !no_verify {
+                commit::verify_options(message.as_deref(), amend, &config.commit)?;
+            }
+            if amend {
+                commit::amend(branch, message, metadata, expected_head, client).await
+            } else {
+                let message = message.unwrap_or_default();
+                commit::execute(branch, message, allow_empty, metadata, client).await
+            }
+        }
+        Commands::Log { branch, amount, graph, dot } => {
+            commit::log(branch, amount, commit::LogFormat::from_flags(graph, dot), client).await
+        }
+        Commands::Fs { command } => fs::execute(command, client, &config.options, config.storage.as_ref()).await,
+        Commands::Diff { left, right, prefix, name_only, stat, two_column } => {
+            let format = diff::DiffFormat::from_flags(name_only, stat, two_column);
+            diff::execute(left, right, prefix, format, client).await
+        }
+        Commands::Merge { source, destination, strategy, dry_run, squash, message } => {
+            let args = merge::MergeArgs { strategy, dry_run, squash, message };
+            merge::execute(source, destination, args, client).await
+        }
+        Commands::Replicate { source, destination, prefix, source_config, manifest, parallelism } => {
+            let source_client = match source_config {
+                Some(path) => profile_client(Path::new(&path)).await?,
+                None => client.clone(),
+            };
+            let parallelism = parallelism.unwrap_or(config.options.parallelism);
+            replicate::execute(source, destination, prefix, Path::new(&manifest), parallelism, source_client, client).await
+        }
+        Commands::Export { source, destination, parallelism } => {
+            let parallelism = parallelism.unwrap_or(config.options.parallelism);
+            export::execute(source, destination, parallelism, config.storage.as_ref(), client).await
+        }
+        Commands::Ingest { from, to, mode, manifest, commit, parallelism } => {
+            let parallelism = parallelism.unwrap_or(config.options.parallelism);
+            let storage = config.storage.as_ref();
+            ingest::execute(from, to, mode, manifest, commit, parallelism, &config.options, storage, client).await
+        }
+        Commands::Audit { repository, since, committer, changes, json } => {
+            audit::execute(repository, since, committer, changes, json, client).await
+        }
+        Commands::Local { command } => {
+            let result = local::execute(command, client, &config.options, &config.commit, metrics.clone(), cancel).await;
+            if let (Some(path), Some(metrics)) = (&cli.metrics_file, &metrics) {
+                std::fs::write(path, metrics.render_prometheus())?;
+            }
+            result
         }
-        Commands::Log { branch, amount } => commit::log(branch, amount, client).await,
-        Commands::Fs { command } => fs::execute(command, client, &config.options).await,
-        Commands::Diff { left, right } => diff::execute(left, right, client).await,
-        Commands::Merge { source, destination, strategy } => {
-            merge::execute(source, destination, strategy, client).await
+        Commands::Browse { uri } => browse::execute(uri, client).await,
+        Commands::Refs { command } => refs::execute(command, client).await,
+        Commands::Metadata { command } => metadata::execute(command, client).await,
+        Commands::Abuse { command } => abuse::execute(command, client).await,
+        Commands::Bench { command } => bench::execute(command, client, &config.options, cancel).await,
+        Commands::Config { .. } => unreachable!("config commands run before the configuration is loaded"),
+        Commands::Daemon { command: DaemonCommands::Start { paths, interval, socket, metrics_listen } } => {
+            let metrics_file = cli.metrics_file.map(PathBuf::from);
+            let metrics = metrics.unwrap_or_default();
+            daemon::start(paths, interval, socket, client, &config.options, metrics, metrics_file, metrics_listen, cancel).await
         }
-        Commands::Local { command } => local::execute(command, client, &config.options).await,
+        Commands::Daemon { .. } => unreachable!("daemon control commands run before the configuration is loaded"),
+        Commands::ListRepos { prefix } => complete::list_repos(prefix, client).await,
+        Commands::ListRefs { repository, prefix } => complete::list_refs(repository, prefix, client).await,
+    }
+}
+
+/// Client for the server described by another config file.
+async fn profile_client(path: &Path) -> Result<LakeFSClient> {
+    config_client(load_config_file(path)?).await
+}
+
+/// Plain client for the server in `config`, without the response cache and
+/// middleware commands run with.
+async fn config_client(config: AppConfig) -> Result<LakeFSClient> {
+    let client_config = ClientConfig {
+        user_agent: config.server.user_agent.clone(),
+        ..Default::default()
+    };
+    let provider = auth_provider(&config).await?;
+    Ok(LakeFSClient::from_provider(&config.server.endpoint_url, provider, client_config)?)
+}
+
+/// `LAKECTL_RECORD=<file>` records API traffic, `LAKECTL_REPLAY=<file>`
+/// answers requests from a previous recording without contacting the server.
+fn cassette_from_env() -> Result<Option<Cassette>> {
+    let record = std::env::var_os("LAKECTL_RECORD");
+    let replay = std::env::var_os("LAKECTL_REPLAY");
+    
+    match (record, replay) {
+        (Some(_), Some(_)) => anyhow::bail!("LAKECTL_RECORD and LAKECTL_REPLAY cannot be used together"),
+        (Some(path), None) => Ok(Some(Cassette::record(path))),
+        (None, Some(path)) => Ok(Some(Cassette::replay(path)?)),
+        (None, None) => Ok(None),
     }
 }
@@ -0,0 +1,250 @@
+use super::browse::{tree_entries, Entry};
+use super::commit::{short_id, subject};
+use super::config_client;
+use super::fs::list_all_objects;
+use crate::cli::Cli;
+use crate::config::{config_path, load_config_from};
+use anyhow::Result;
+use clap::error::{ContextKind, ContextValue, ErrorKind};
+use clap::{CommandFactory, Parser};
+use dialoguer::theme::ColorfulTheme;
+use dialoguer::FuzzySelect;
+use lakefs_api::{LakeFSClient, PaginationParams};
+use std::ffi::OsString;
+use std::io::IsTerminal;
+
+// When a command is run from a terminal without one of its lakefs:// URI
+// arguments, ask for the repository, reference and path with fuzzy search
+// over what the server lists, then parse the command line again with the
+// picked URIs appended.
+
+/// Repositories, branches and tags offered; only the first page is listed.
+const PAGE_SIZE: usize = 1000;
+/// Commits of the default branch offered when any reference will do.
+const RECENT_COMMITS: usize = 20;
+
+/// How much of a URI an argument needs, judged by its value name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum UriKind {
+    /// `lakefs://repo`
+    Repository,
+    /// `lakefs://repo/branch`
+    Branch,
+    /// `lakefs://repo/ref`
+    Reference,
+    /// `lakefs://repo/ref/path`
+    Path,
+}
+
+impl UriKind {
+    fn of(value_name: &str) -> Option<Self> {
+        match value_name {
+            "REPOSITORY_URI" => Some(UriKind::Repository),
+            "BRANCH_URI" | "DEST_BRANCH_URI" | "DEST_BRANCH" => Some(UriKind::Branch),
+            "COMMIT_URI" => Some(UriKind::Reference),
+            name if name.ends_with("_REF") => Some(UriKind::Reference),
+            name if name == "URI" || name.ends_with("_URI") => Some(UriKind::Path),
+            _ => None,
+        }
+    }
+}
+
+/// URIs missing from the command line, when clap failed only for want of
+/// positional URI arguments.
+fn missing_uris(error: &clap::Error) -> Option<Vec<(String, UriKind)>> {
+    if error.kind() != ErrorKind::MissingRequiredArgument {
+        return None;
+    }
+    let Some(ContextValue::Strings(missing)) = error.get(ContextKind::InvalidArg) else {
+        return None;
+    };
+    // Positionals are rendered `<NAME>`, options `--name <NAME>`
+    missing
+        .iter()
+        .map(|arg| {
+            let name = arg.strip_prefix('<')?.strip_suffix('>')?;
+            Some((name.to_string(), UriKind::of(name)?))
+        })
+        .collect()
+}
+
+/// Parse the command line, asking for missing URIs when run interactively.
+/// Any other error, or one that cannot be fixed by asking, exits as clap
+/// would.
+pub async fn parse_or_prompt() -> Result<Cli> {
+    let error = match Cli::try_parse() {
+        Ok(cli) => return Ok(cli),
+        Err(error) => error,
+    };
+    let interactive = std::io::stdin().is_terminal() && std::io::stderr().is_terminal();
+    let missing = match missing_uris(&error) {
+        Some(missing) if interactive => missing,
+        _ => error.exit(),
+    };
+
+    let mut args: Vec<OsString> = std::env::args_os().collect();
+    // The parse failed, but `--config` can still be read
+    let matches = Cli::command().ignore_errors(true).try_get_matches_from(&args)?;
+    let config = load_config_from(config_path(matches.get_one::<String>("config").map(String::as_str)))?;
+    let client = config_client(config).await?;
+
+    for (name, kind) in missing {
+        let uri = pick(&client, &name, kind).await?;
+        eprintln!("{}: {}", name, uri);
+        args.push(uri.into());
+    }
+    Ok(Cli::try_parse_from(args).unwrap_or_else(|error| error.exit()))
+}
+
+async fn pick(client: &LakeFSClient, name: &str, kind: UriKind) -> Result<String> {
+    let params = PaginationParams { prefix: None, after: None, amount: Some(PAGE_SIZE), ..Default::default() };
+    let repositories = client.list_repositories_with(&params).await?.results;
+    if repositories.is_empty() {
+        anyhow::bail!("No repositories to pick {} from", name);
+    }
+    let names: Vec<String> = repositories.iter().map(|r| r.id.clone()).collect();
+    let index = choose(format!("{}: repository", name), names, 0).await?;
+    let repository = &repositories[index];
+    if kind == UriKind::Repository {
+        return Ok(format!("lakefs://{}", repository.id));
+    }
+
+    let branches = client.list_branches_with(&repository.id, &params).await?.results;
+    if branches.is_empty() {
+        anyhow::bail!("Repository {} has no branches", repository.id);
+    }
+    let default = branches.iter().position(|b| b.id == repository.default_branch).unwrap_or_default();
+    let mut choices: Vec<(String, String)> = branches.into_iter().map(|b| (b.id.clone(), b.id)).collect();
+    let prompt = match kind {
+        UriKind::Branch => "branch",
+        _ => {
+            choices.extend(tags_and_commits(client, &repository.id, &repository.default_branch).await?);
+            "reference"
+        }
+    };
+    let labels = choices.iter().map(|(label, _)| label.clone()).collect();
+    let reference = &choices[choose(format!("{}: {}", name, prompt), labels, default).await?].1;
+    if kind != UriKind::Path {
+        return Ok(format!("lakefs://{}/{}", repository.id, reference));
+    }
+
+    let path = pick_path(client, name, &repository.id, reference).await?;
+    Ok(format!("lakefs://{}/{}/{}", repository.id, reference, path))
+}
+
+/// Choices besides the branches for arguments taking any reference, as
+/// (label, reference): the tags, then the latest commits on the default
+/// branch.
+async fn tags_and_commits(client: &LakeFSClient, repository: &str, default_branch: &str) -> Result<Vec<(String, String)>> {
+    let params = PaginationParams { amount: Some(PAGE_SIZE), ..Default::default() };
+    let tags = client.list_tags_with(repository, &params).await?.results;
+    let params = PaginationParams { amount: Some(RECENT_COMMITS), ..Default::default() };
+    let commits = client.log_commits_with(repository, default_branch, &params).await?.results;
+
+    let tags = tags.into_iter().map(|tag| (format!("{} (tag)", tag.id), tag.id));
+    let commits = commits.into_iter().map(|commit| (format!("{} {}", short_id(&commit.id), subject(&commit)), commit.id));
+    Ok(tags.chain(commits).collect())
+}
+
+/// Walk the object tree from the root until an object, or the directory
+/// being shown, is picked.
+async fn pick_path(client: &LakeFSClient, name: &str, repository: &str, reference: &str) -> Result<String> {
+    let mut prefix = String::new();
+    loop {
+        let objects = list_all_objects(client, repository, reference, &prefix).await?;
+        let entries = tree_entries(objects, &prefix);
+        let here = if prefix.is_empty() { "(repository root)".to_string() } else { format!("(this directory: {})", prefix) };
+        let labels = std::iter::once(here).chain(entries.iter().map(Entry::label)).collect();
+
+        let index = choose(format!("{}: path", name), labels, 0).await?;
+        match index.checked_sub(1).map(|i| &entries[i]) {
+            None => return Ok(prefix),
+            Some(Entry::Parent) => {
+                let trimmed = prefix.trim_end_matches('/');
+                prefix = trimmed.rsplit_once('/').map_or_else(String::new, |(parent, _)| format!("{}/", parent));
+            }
+            Some(Entry::Directory(directory)) => prefix = format!("{}{}/", prefix, directory),
+            Some(Entry::Object { stats, .. }) => return Ok(stats.path.clone()),
+        }
+    }
+}
+
+/// Index of the item picked by fuzzy search. Escape cancels the command.
+async fn choose(prompt: String, items: Vec<String>, default: usize) -> Result<usize> {
+    let picked = tokio::task::spawn_blocking(move || {
+        FuzzySelect::with_theme(&ColorfulTheme::default())
+            .with_prompt(prompt)
+            .items(&items)
+            .default(default)
+            .interact_opt()
+    })
+    .await??;
+    picked.ok_or_else(|| anyhow::anyhow!("Cancelled"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lakefs_test_utils::scenario::MockLakeFS;
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, ResponseTemplate};
+
+    fn missing(args: &[&str]) -> Option<Vec<(String, UriKind)>> {
+        missing_uris(&Cli::try_parse_from(args).err().unwrap())
+    }
+
+    #[test]
+    fn test_missing_uris() {
+        assert_eq!(missing(&["lakectl", "log"]), Some(vec![("BRANCH_URI".to_string(), UriKind::Branch)]));
+        assert_eq!(
+            missing(&["lakectl", "merge"]),
+            Some(vec![
+                ("SOURCE_REF".to_string(), UriKind::Reference),
+                ("DEST_BRANCH".to_string(), UriKind::Branch),
+            ])
+        );
+        assert_eq!(missing(&["lakectl", "fs", "ls"]), Some(vec![("PATH_URI".to_string(), UriKind::Path)]));
+
+        // Only the URI arguments can be asked for
+        assert_eq!(missing(&["lakectl", "repo", "create"]), None);
+        assert_eq!(missing(&["lakectl", "audit"]), None);
+        assert_eq!(missing(&["lakectl", "bogus"]), None);
+    }
+
+    #[tokio::test]
+    async fn test_tags_and_commits() {
+        let server = MockLakeFS::new()
+            .with_repo("repo")
+            .with_branch("main")
+            .with_tag("v1")
+            .start()
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repositories/repo/branches/main/commits"))
+            .and(query_param("amount", "20"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "results": [{
+                    "id": "c2f1e0d9b8a7", "parents": [], "committer": "user",
+                    "message": "Add data\n\nDetails", "creation_date": 1704067200, "meta_range_id": ""
+                }],
+                "pagination": {"has_more": false, "max_per_page": 100, "results": 1, "next_offset": null}
+            })))
+            .mount(&server)
+            .await;
+        let client = LakeFSClient::new(server.uri(), "Bearer token");
+
+        let choices = tags_and_commits(&client, "repo", "main").await.unwrap();
+        assert_eq!(choices, [
+            ("v1 (tag)".to_string(), "v1".to_string()),
+            ("c2f1e0d9 Add data".to_string(), "c2f1e0d9b8a7".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn test_uri_kind_of_value_names() {
+        assert_eq!(UriKind::of("REPOSITORY_URI"), Some(UriKind::Repository));
+        assert_eq!(UriKind::of("LEFT_REF"), Some(UriKind::Reference));
+        assert_eq!(UriKind::of("SOURCE_URI"), Some(UriKind::Path));
+        assert_eq!(UriKind::of("LOCAL_PATH"), None);
+    }
+}
@@ -0,0 +1,94 @@
+use crate::cli::RefsCommands;
+use crate::utils::parse_repository_uri;
+use anyhow::{Context, Result};
+use lakefs_api::{LakeFSClient, RefsDump};
+use std::io::Read;
+
+pub async fn execute(command: RefsCommands, client: LakeFSClient) -> Result<()> {
+    match command {
+        RefsCommands::Dump { repository, output } => {
+            let repository = parse_repository_uri(&repository)?;
+            let manifest = client.dump_refs(&repository).await?;
+            let json = serde_json::to_string_pretty(&manifest)?;
+            
+            match output {
+                Some(path) => {
+                    std::fs::write(&path, json + "\n")
+                        .with_context(|| format!("Failed to write manifest to {}", path))?;
+                    eprintln!("Dumped refs of '{}' to {}", repository, path);
+                }
+                None => println!("{}", json),
+            }
+        }
+        
+        RefsCommands::Restore { repository, manifest } => {
+            let repository = parse_repository_uri(&repository)?;
+            let manifest = read_manifest(&manifest)?;
+            client.restore_refs(&repository, &manifest).await?;
+            
+            println!("Restored refs of repository '{}'", repository);
+        }
+    }
+    
+    Ok(())
+}
+
+fn read_manifest(path: &str) -> Result<RefsDump> {
+    let content = if path == "-" {
+        let mut content = String::new();
+        std::io::stdin().read_to_string(&mut content)?;
+        content
+    } else {
+        std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read manifest {}", path))?
+    };
+    
+    serde_json::from_str(&content).with_context(|| format!("Invalid refs manifest {}", path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+    use wiremock::matchers::{body_json, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_dump_then_restore() {
+        let mock_server = MockServer::start().await;
+        let manifest = serde_json::json!({
+            "commits_meta_range_id": "c1",
+            "tags_meta_range_id": "t1",
+            "branches_meta_range_id": "b1"
+        });
+        
+        Mock::given(method("PUT"))
+            .and(path("/repositories/source/refs/dump"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(&manifest))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/repositories/target/refs/restore"))
+            .and(body_json(&manifest))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let manifest_path = temp_dir.path().join("refs.json").to_string_lossy().to_string();
+        let client = LakeFSClient::new(mock_server.uri(), "Bearer test-token");
+        
+        let dump = RefsCommands::Dump {
+            repository: "lakefs://source".to_string(),
+            output: Some(manifest_path.clone()),
+        };
+        execute(dump, client.clone()).await.unwrap();
+        
+        let restore = RefsCommands::Restore {
+            repository: "lakefs://target".to_string(),
+            manifest: manifest_path,
+        };
+        execute(restore, client).await.unwrap();
+    }
+}
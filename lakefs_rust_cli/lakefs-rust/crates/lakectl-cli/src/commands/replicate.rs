@@ -0,0 +1,306 @@
+use super::fs::{directory_prefix, list_all_objects};
+use crate::utils::{format_size, parse_uri, resolve_uri};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use futures::StreamExt;
+use lakefs_api::models::ObjectStats;
+use lakefs_api::{Checksum, LakeFSClient, LakeFSUri};
+use md5::{Digest, Md5};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+/// First line of a manifest, naming the replication it belongs to.
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct ManifestHeader {
+    source: String,
+    destination: String,
+}
+
+/// A replicated object, one JSON line per object.
+#[derive(Debug, Serialize, Deserialize)]
+struct ManifestEntry {
+    path: String,
+    /// Source checksum, so objects changed since the entry are copied again
+    checksum: Checksum,
+    size_bytes: i64,
+    destination_checksum: Checksum,
+    replicated_at: DateTime<Utc>,
+}
+
+/// Append-only record of the objects a replication has finished. Each object
+/// is written as it completes, so an interrupted run loses nothing it copied.
+struct Manifest {
+    file: File,
+    done: HashMap<String, Checksum>,
+}
+
+impl Manifest {
+    fn open(path: &Path, header: &ManifestHeader) -> Result<Self> {
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        };
+
+        let mut lines = content.lines();
+        let mut done = HashMap::new();
+        if let Some(first) = lines.next() {
+            let existing: ManifestHeader = serde_json::from_str(first)
+                .with_context(|| format!("{} is not a replication manifest", path.display()))?;
+            if existing != *header {
+                anyhow::bail!(
+                    "{} belongs to the replication of {} to {}; use another --manifest",
+                    path.display(),
+                    existing.source,
+                    existing.destination
+                );
+            }
+            // The last line may have been cut short by an interrupted run
+            for entry in lines.filter_map(|line| serde_json::from_str::<ManifestEntry>(line).ok()) {
+                done.insert(entry.path, entry.checksum);
+            }
+        }
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Failed to open {}", path.display()))?;
+        if content.is_empty() {
+            writeln!(file, "{}", serde_json::to_string(header)?)?;
+        } else if !content.ends_with('\n') {
+            writeln!(file)?;
+        }
+
+        Ok(Self { file, done })
+    }
+
+    fn is_done(&self, object: &ObjectStats) -> bool {
+        self.done.get(&object.path) == Some(&object.checksum)
+    }
+
+    fn record(&mut self, entry: &ManifestEntry) -> Result<()> {
+        writeln!(self.file, "{}", serde_json::to_string(entry)?)?;
+        Ok(self.file.flush()?)
+    }
+}
+
+/// Copy every object under the source (and `prefix` below it) to the
+/// destination branch, streaming each one through without touching disk.
+/// Objects already recorded in the manifest with an unchanged checksum are
+/// skipped, so rerunning after a failure resumes where it stopped.
+pub async fn execute(
+    source: String,
+    destination: String,
+    prefix: Option<String>,
+    manifest: &Path,
+    parallelism: usize,
+    source_client: LakeFSClient,
+    client: LakeFSClient,
+) -> Result<()> {
+    let source = resolve_uri(&source_client, &source).await?;
+    let destination = parse_uri(&destination)?;
+    let source_prefix = directory_prefix(source.path.as_deref());
+    let dest_prefix = directory_prefix(destination.path.as_deref());
+
+    let listing_prefix = format!("{}{}", source_prefix, prefix.unwrap_or_default());
+    let objects = list_all_objects(&source_client, &source.repository, &source.reference, &listing_prefix).await?;
+
+    let header = ManifestHeader {
+        source: source.to_string(),
+        destination: destination.to_string(),
+    };
+    let mut manifest = Manifest::open(manifest, &header)?;
+    let total = objects.len();
+    let pending: Vec<ObjectStats> = objects.into_iter().filter(|o| !manifest.is_done(o)).collect();
+    let skipped = total - pending.len();
+
+    let source = Arc::new(source);
+    let destination = Arc::new(destination);
+    let semaphore = Arc::new(Semaphore::new(parallelism.max(1)));
+    let mut tasks = JoinSet::new();
+    for object in pending {
+        let dest_path = format!("{}{}", dest_prefix, object.path.strip_prefix(&source_prefix).unwrap_or(&object.path));
+        let (source_client, client) = (source_client.clone(), client.clone());
+        let (source, destination) = (source.clone(), destination.clone());
+        let semaphore = semaphore.clone();
+        tasks.spawn(async move {
+            let _permit = semaphore.acquire().await?;
+            let path = object.path.clone();
+            replicate_object(&source_client, &source, &client, &destination, object, &dest_path)
+                .await
+                .with_context(|| format!("replicate {}", path))
+        });
+    }
+
+    let mut replicated = 0;
+    let mut bytes = 0;
+    let mut failed = 0;
+    while let Some(joined) = tasks.join_next().await {
+        match joined? {
+            Ok(entry) => {
+                manifest.record(&entry)?;
+                replicated += 1;
+                bytes += entry.size_bytes;
+            }
+            Err(e) => {
+                eprintln!("Failed to {:#}", e);
+                failed += 1;
+            }
+        }
+    }
+
+    if failed > 0 {
+        anyhow::bail!("{} of {} objects failed to replicate; rerun to resume", failed, replicated + failed);
+    }
+    println!(
+        "Replicated {} objects ({}) to {}, {} already up to date",
+        replicated,
+        format_size(bytes),
+        destination,
+        skipped
+    );
+    Ok(())
+}
+
+/// Stream one object from the source to the destination, hashing it on the
+/// way, and check what was read against the source's stats.
+async fn replicate_object(
+    source_client: &LakeFSClient,
+    source: &LakeFSUri,
+    client: &LakeFSClient,
+    destination: &LakeFSUri,
+    object: ObjectStats,
+    dest_path: &str,
+) -> Result<ManifestEntry> {
+    let hasher = Arc::new(Mutex::new((Md5::new(), 0u64)));
+    let tap = hasher.clone();
+    let stream = source_client
+        .download_object_stream(&source.repository, &source.reference, &object.path)
+        .await?
+        .map(move |chunk| {
+            let chunk = chunk.map_err(std::io::Error::other)?;
+            let mut state = tap.lock().unwrap();
+            state.0.update(&chunk);
+            state.1 += chunk.len() as u64;
+            Ok(chunk)
+        });
+
+    let stats = client
+        .upload_object_stream(&destination.repository, &destination.reference, dest_path, stream)
+        .await?;
+
+    let (hasher, size) = std::mem::take(&mut *hasher.lock().unwrap());
+    verify(&object, &Checksum::Md5(format!("{:x}", hasher.finalize())), size)?;
+
+    Ok(ManifestEntry {
+        path: object.path,
+        checksum: object.checksum,
+        size_bytes: object.size_bytes,
+        destination_checksum: stats.checksum,
+        replicated_at: Utc::now(),
+    })
+}
+
+/// lakeFS checksums are the storage ETag, which is the content MD5 unless
+/// the object was uploaded in parts; those can only be checked by size.
+pub(super) fn verify(object: &ObjectStats, digest: &Checksum, size: u64) -> Result<()> {
+    if size != object.size_bytes as u64 {
+        anyhow::bail!("read {} bytes, expected {}", size, object.size_bytes);
+    }
+
+    if object.checksum.matches(digest) == Some(false) {
+        anyhow::bail!("content MD5 {} does not match source checksum {}", digest, object.checksum);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lakefs_test_utils::scenario::MockLakeFS;
+    use tempfile::TempDir;
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, ResponseTemplate};
+
+    fn object(checksum: &str, size_bytes: i64) -> ObjectStats {
+        serde_json::from_value(serde_json::json!({
+            "path": "a.csv",
+            "path_type": "object",
+            "physical_address": "s3://bucket/a",
+            "checksum": checksum,
+            "size_bytes": size_bytes,
+            "mtime": "2024-01-01T00:00:00Z"
+        })).unwrap()
+    }
+
+    #[test]
+    fn test_verify() {
+        let digest = Checksum::Md5(format!("{:x}", Md5::digest(b"hello")));
+        assert!(verify(&object(&digest.to_string(), 5), &digest, 5).is_ok());
+        assert!(verify(&object(&digest.to_string(), 6), &digest, 5).is_err());
+        assert!(verify(&object("0123456789abcdef0123456789abcdef", 5), &digest, 5).is_err());
+        // Multipart ETags are not content hashes
+        assert!(verify(&object("0123456789abcdef0123456789abcdef-2", 5), &digest, 5).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_replicate_resumes_from_manifest() {
+        let server = MockLakeFS::new()
+            .with_repo("source")
+            .with_branch("main")
+            .with_object("data/a.csv", "a")
+            .with_object("data/b.csv", "bb")
+            .with_object("other/c.csv", "c")
+            .with_repo("target")
+            .with_branch("main")
+            .start()
+            .await;
+        for dest in ["backup/data/a.csv", "backup/data/b.csv"] {
+            Mock::given(method("PUT"))
+                .and(path("/repositories/target/branches/main/objects"))
+                .and(query_param("path", dest))
+                .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!({
+                    "path": dest,
+                    "path_type": "object",
+                    "physical_address": "s3://bucket/x",
+                    "checksum": "abc",
+                    "size_bytes": 1,
+                    "mtime": "2024-01-01T00:00:00Z"
+                })))
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+        let client = LakeFSClient::new(server.uri(), "Bearer test-token");
+        let temp_dir = TempDir::new().unwrap();
+        let manifest = temp_dir.path().join("manifest.jsonl");
+
+        for _ in 0..2 {
+            execute(
+                "lakefs://source/main".to_string(),
+                "lakefs://target/main/backup".to_string(),
+                Some("data/".to_string()),
+                &manifest,
+                4,
+                client.clone(),
+                client.clone(),
+            ).await.unwrap();
+        }
+
+        let content = std::fs::read_to_string(&manifest).unwrap();
+        assert_eq!(content.lines().count(), 3);
+
+        let other = ManifestHeader {
+            source: "lakefs://source/dev".to_string(),
+            destination: "lakefs://target/main".to_string(),
+        };
+        assert!(Manifest::open(&manifest, &other).is_err());
+    }
+}
@@ -1,6 +1,15 @@
-use crate::cli::RepoCommands;
-use anyhow::Result;
-use lakefs_api::LakeFSClient;
+use super::fs::{upload_recursive, ContentType};
+use super::stats;
+use crate::cli::{RepoCommands, RepoSort};
+use crate::config::OptionsConfig;
+use crate::utils::confirm;
+use anyhow::{Context, Result};
+use lakefs_api::{LakeFSClient, LakeFSUri, PaginationParams, Repository, StorageConfig};
+use lakefs_local::{FilterAction, NoopProgress, PathFilter, ProgressReporter};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use regex::Regex;
+use tempfile::TempDir;
 use tabled::{Table, Tabled};
 
 #[derive(Tabled)]
@@ -11,24 +20,53 @@ struct RepoRow {
     created: String,
 }
 
-pub async fn execute(command: RepoCommands, client: LakeFSClient) -> Result<()> {
+pub async fn execute(command: RepoCommands, client: LakeFSClient, options: &OptionsConfig) -> Result<()> {
     match command {
         RepoCommands::Create {
             name,
             storage_namespace,
-            default_branch: _,  // Currently unused
+            default_branch,
+            dry_run,
         } => {
-            let repo = client.create_repository(&name, &storage_namespace).await?;
+            let storage_namespace = checked_namespace(&client, &name, storage_namespace, dry_run).await?;
+            
+            if dry_run {
+                println!("Storage namespace '{}' is valid", storage_namespace);
+                return Ok(());
+            }
+            
+            let repo = client.create_repository_with_branch(&name, &storage_namespace, &default_branch).await?;
             println!("Created repository: {}", repo.id);
             println!("Storage namespace: {}", repo.storage_namespace);
             println!("Default branch: {}", repo.default_branch);
         }
         
-        RepoCommands::List { amount: _, after: _ } => {
-            let response = client.list_repositories().await?;
+        RepoCommands::Bootstrap { name, storage_namespace, template, message } => {
+            let template = TemplateSource::parse(&template);
+            let storage_namespace = checked_namespace(&client, &name, storage_namespace, false).await?;
+            let template_dir = template.fetch().await?;
+            
+            let (repo, files, commit_id) = bootstrap(&client, &name, &storage_namespace, template_dir.path(), &message, options).await?;
             
-            let rows: Vec<RepoRow> = response
-                .results
+            println!("Created repository: {}", repo.id);
+            println!("Storage namespace: {}", repo.storage_namespace);
+            println!("Uploaded {} template files to {}", files, repo.default_branch);
+            println!("Initial commit: {}", commit_id);
+        }
+        
+        RepoCommands::List { amount, after, prefix, sort } => {
+            let params = PaginationParams { prefix, after, amount, ..Default::default() };
+            let (repositories, next_offset) = match sort {
+                Some(sort) => (sorted_repositories(&client, params, sort).await?, None),
+                None => {
+                    let response = client.list_repositories_with(&params).await?;
+                    let next_offset = response.pagination.has_more
+                        .then(|| response.pagination.next_offset.unwrap_or_default());
+                    (response.results, next_offset)
+                }
+            };
+            
+            let rows: Vec<RepoRow> = repositories
                 .into_iter()
                 .map(|r| RepoRow {
                     name: r.id.clone(),
@@ -41,33 +79,474 @@ pub async fn execute(command: RepoCommands, client: LakeFSClient) -> Result<()>
             let table = Table::new(rows);
             println!("{}", table);
             
-            if response.pagination.has_more {
-                println!(
-                    "\nMore results available. Use --after {} to see next page",
-                    response.pagination.next_offset.unwrap_or_default()
-                );
+            if let Some(next_offset) = next_offset {
+                println!("\nMore results available. Use --after {} to see next page", next_offset);
             }
         }
         
         RepoCommands::Delete { name, yes } => {
-            if !yes {
-                print!("Are you sure you want to delete repository '{}'? [y/N] ", name);
-                use std::io::{self, Write};
-                io::stdout().flush()?;
-                
-                let mut input = String::new();
-                io::stdin().read_line(&mut input)?;
-                
-                if input.trim().to_lowercase() != "y" {
-                    println!("Deletion cancelled");
-                    return Ok(());
-                }
+            if !yes && !confirm(&format!("Are you sure you want to delete repository '{}'?", name))? {
+                println!("Deletion cancelled");
+                return Ok(());
             }
             
             client.delete_repository(&name).await?;
             println!("Deleted repository: {}", name);
         }
+        
+        RepoCommands::Stats { repository, since, depth, top, parallelism, json } => {
+            let parallelism = parallelism.unwrap_or(options.parallelism);
+            return stats::execute(repository, since, depth, top, parallelism, json, client).await;
+        }
     }
     
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Validate `storage_namespace` for a new repository `name`. When it overlaps
+/// another repository's, offer a free one instead (or, on a dry run, fail
+/// naming it).
+async fn checked_namespace(client: &LakeFSClient, name: &str, storage_namespace: String, dry_run: bool) -> Result<String> {
+    let storage_config = client.get_storage_config().await?;
+    validate_storage_namespace(&storage_namespace, &storage_config)?;
+    
+    let existing = list_all_repositories(client, PaginationParams::new()).await?;
+    let Some(owner) = namespace_owner(&storage_namespace, &existing) else {
+        return Ok(storage_namespace);
+    };
+    let message = format!(
+        "Storage namespace '{}' overlaps with repository '{}' ({})",
+        storage_namespace, owner.id, owner.storage_namespace
+    );
+    let suggestion = match suggest_namespace(&owner.storage_namespace, name, &existing) {
+        Some(suggestion) if !dry_run => suggestion,
+        Some(suggestion) => anyhow::bail!("{}. Try '{}'", message, suggestion),
+        None => anyhow::bail!(message),
+    };
+    eprintln!("{}", message);
+    if !confirm(&format!("Use '{}' instead?", suggestion))? {
+        anyhow::bail!("Repository not created");
+    }
+    Ok(suggestion)
+}
+
+/// Where a repository template comes from.
+#[derive(Debug, PartialEq)]
+enum TemplateSource {
+    Directory(PathBuf),
+    Git(String),
+}
+
+/// A template checked out on disk. A cloned one lives in a temporary
+/// directory that is removed when this is dropped.
+enum TemplateDir {
+    Local(PathBuf),
+    Cloned(TempDir),
+}
+
+impl TemplateDir {
+    fn path(&self) -> &Path {
+        match self {
+            TemplateDir::Local(path) => path,
+            TemplateDir::Cloned(dir) => dir.path(),
+        }
+    }
+}
+
+impl TemplateSource {
+    /// An existing directory is used as is; anything that looks like a git
+    /// remote is cloned.
+    fn parse(template: &str) -> Self {
+        let is_remote = ["https://", "http://", "ssh://", "git://", "git@"]
+            .iter()
+            .any(|prefix| template.starts_with(prefix));
+        if is_remote || (template.ends_with(".git") && !Path::new(template).is_dir()) {
+            TemplateSource::Git(template.to_string())
+        } else {
+            TemplateSource::Directory(PathBuf::from(template))
+        }
+    }
+    
+    async fn fetch(&self) -> Result<TemplateDir> {
+        match self {
+            TemplateSource::Directory(path) => {
+                if !path.is_dir() {
+                    anyhow::bail!("Template directory {} does not exist", path.display());
+                }
+                Ok(TemplateDir::Local(path.clone()))
+            }
+            TemplateSource::Git(url) => {
+                let dir = tempfile::Builder::new()
+                    .prefix("lakectl-template-")
+                    .tempdir()
+                    .context("Failed to create a directory to clone the template into")?;
+                let status = tokio::process::Command::new("git")
+                    .args(["clone", "--quiet", "--depth", "1", url])
+                    .arg(dir.path())
+                    .status()
+                    .await
+                    .context("Failed to run git")?;
+                if !status.success() {
+                    anyhow::bail!("git clone {} failed ({})", url, status);
+                }
+                Ok(TemplateDir::Cloned(dir))
+            }
+        }
+    }
+}
+
+/// Create the repository, upload the template to its default branch and
+/// commit it. Returns the repository, the number of files and the commit ID.
+async fn bootstrap(
+    client: &LakeFSClient,
+    name: &str,
+    storage_namespace: &str,
+    template: &Path,
+    message: &str,
+    options: &OptionsConfig,
+) -> Result<(Repository, usize, String)> {
+    let repo = client.create_repository(name, storage_namespace).await?;
+    
+    let mut filter = PathFilter::new();
+    filter.add(FilterAction::Exclude, ".git")?;
+    let uri = LakeFSUri::new(&repo.id, &repo.default_branch);
+    let reporter: Arc<dyn ProgressReporter> = Arc::new(NoopProgress);
+    let files = upload_recursive(client, &uri, template, options.parallelism, None, &ContentType::Sniffed, &filter, &reporter).await?;
+    if files == 0 {
+        anyhow::bail!("Template {} has no files; repository {} was created empty", template.display(), repo.id);
+    }
+    
+    let commit = client.commit(&repo.id, &repo.default_branch, message).await?;
+    Ok((repo, files, commit.id))
+}
+
+/// Reject namespaces the server would refuse, or that are probably typos.
+fn validate_storage_namespace(namespace: &str, config: &StorageConfig) -> Result<()> {
+    if namespace.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        anyhow::bail!("Storage namespace '{}' contains whitespace", namespace);
+    }
+    if namespace.contains(['?', '#']) {
+        anyhow::bail!("Storage namespace '{}' must not contain a query or fragment", namespace);
+    }
+    if namespace.ends_with('/') {
+        anyhow::bail!("Storage namespace '{}' must not end with '/'", namespace);
+    }
+    
+    let expected_scheme = config.blockstore_namespace_example.split_once("://").map(|(scheme, _)| scheme);
+    let scheme = namespace.split_once("://").map(|(scheme, _)| scheme);
+    if let (Some(expected), Some(actual)) = (expected_scheme, scheme) {
+        if !expected.eq_ignore_ascii_case(actual) {
+            anyhow::bail!(
+                "Storage namespace scheme '{}' is not supported by the {} blockstore (e.g. {})",
+                actual, config.blockstore_type, config.blockstore_namespace_example
+            );
+        }
+    }
+    
+    if !config.blockstore_namespace_validity_regex.is_empty() {
+        let regex = Regex::new(&config.blockstore_namespace_validity_regex)?;
+        if !regex.is_match(namespace) {
+            anyhow::bail!(
+                "Storage namespace '{}' is not valid for the {} blockstore (e.g. {})",
+                namespace, config.blockstore_type, config.blockstore_namespace_example
+            );
+        }
+    }
+    
+    Ok(())
+}
+
+async fn list_all_repositories(client: &LakeFSClient, mut params: PaginationParams) -> Result<Vec<Repository>> {
+    let mut repositories = Vec::new();
+    
+    loop {
+        let page = client.list_repositories_with(&params).await?;
+        repositories.extend(page.results);
+        
+        match page.pagination.next_offset {
+            Some(next) if page.pagination.has_more => params.after = Some(next),
+            _ => break,
+        }
+    }
+    
+    Ok(repositories)
+}
+
+/// Every repository matching `params`, sorted, then the first `amount` of
+/// them. Sorting one page would only order that page, so this reads them all.
+async fn sorted_repositories(client: &LakeFSClient, params: PaginationParams, sort: RepoSort) -> Result<Vec<Repository>> {
+    let amount = params.amount;
+    let mut repositories = list_all_repositories(client, params).await?;
+    match sort {
+        RepoSort::Created => repositories.sort_by_key(|r| r.creation_date),
+        RepoSort::Name => repositories.sort_by(|a, b| a.id.cmp(&b.id)),
+    }
+    repositories.truncate(amount.unwrap_or(usize::MAX));
+    Ok(repositories)
+}
+
+/// Two namespaces overlap when one equals or contains the other.
+fn namespaces_overlap(a: &str, b: &str) -> bool {
+    let a = a.trim_end_matches('/');
+    let b = b.trim_end_matches('/');
+    a == b || a.starts_with(&format!("{}/", b)) || b.starts_with(&format!("{}/", a))
+}
+
+fn namespace_owner<'a>(namespace: &str, repositories: &'a [Repository]) -> Option<&'a Repository> {
+    repositories.iter().find(|repo| namespaces_overlap(namespace, &repo.storage_namespace))
+}
+
+/// A namespace next to `taken`, named after the new repository, that no
+/// existing repository overlaps with.
+fn suggest_namespace(taken: &str, name: &str, repositories: &[Repository]) -> Option<String> {
+    let taken = taken.trim_end_matches('/');
+    let parent = match taken.rsplit_once('/') {
+        Some((parent, _)) if !parent.ends_with(':') && !parent.ends_with(":/") => parent,
+        _ => taken,
+    };
+    
+    std::iter::once(format!("{}/{}", parent, name))
+        .chain((1..100).map(|i| format!("{}/{}-{}", parent, name, i)))
+        .find(|candidate| namespace_owner(candidate, repositories).is_none())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lakefs_test_utils::fixtures::test_repository;
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{MockServer, Mock, ResponseTemplate};
+
+    fn s3_config() -> StorageConfig {
+        StorageConfig {
+            blockstore_type: "s3".to_string(),
+            blockstore_namespace_example: "s3://example-bucket/".to_string(),
+            blockstore_namespace_validity_regex: "^s3://".to_string(),
+            default_namespace_prefix: None,
+            pre_sign_support: true,
+            import_support: true,
+            pre_sign_support_ui: false,
+            import_validity_regex: None,
+            pre_sign_multipart_upload: None,
+            blockstore_id: None,
+            blockstore_description: None,
+        }
+    }
+
+    fn repo(id: &str, storage_namespace: &str) -> Repository {
+        Repository {
+            id: id.to_string(),
+            storage_namespace: storage_namespace.to_string(),
+            ..test_repository()
+        }
+    }
+
+    #[test]
+    fn test_validate_storage_namespace() {
+        let config = s3_config();
+        assert!(validate_storage_namespace("s3://bucket/repo", &config).is_ok());
+        assert!(validate_storage_namespace("gs://bucket/repo", &config).is_err());
+        assert!(validate_storage_namespace("s3://bucket/repo/", &config).is_err());
+        assert!(validate_storage_namespace("s3://bucket/repo ", &config).is_err());
+        assert!(validate_storage_namespace("s3://bucket/repo?x=1", &config).is_err());
+        assert!(validate_storage_namespace("bucket/repo", &config).is_err());
+    }
+
+    #[test]
+    fn test_namespace_conflicts_and_suggestion() {
+        let existing = vec![repo("a", "s3://bucket/data"), repo("b", "s3://bucket/new")];
+        
+        assert_eq!(namespace_owner("s3://bucket/data", &existing).unwrap().id, "a");
+        assert_eq!(namespace_owner("s3://bucket/data/sub", &existing).unwrap().id, "a");
+        assert!(namespace_owner("s3://bucket/database", &existing).is_none());
+        
+        assert_eq!(suggest_namespace("s3://bucket/data", "new", &existing).unwrap(), "s3://bucket/new-1");
+        assert_eq!(suggest_namespace("s3://bucket/data", "repo", &existing).unwrap(), "s3://bucket/repo");
+        
+        // Nothing fits next to a repository that owns the whole bucket
+        let whole_bucket = vec![repo("a", "s3://bucket")];
+        assert!(suggest_namespace("s3://bucket", "repo", &whole_bucket).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_create_dry_run_does_not_create() {
+        let mock_server = MockServer::start().await;
+        
+        Mock::given(method("GET"))
+            .and(path("/config/storage"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(s3_config()))
+            .mount(&mock_server)
+            .await;
+        
+        Mock::given(method("GET"))
+            .and(path("/repositories"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "results": [repo("other", "s3://bucket/other")],
+                "pagination": {"has_more": false, "max_per_page": 100, "results": 1, "next_offset": null}
+            })))
+            .mount(&mock_server)
+            .await;
+        
+        Mock::given(method("POST"))
+            .and(path("/repositories"))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
+        
+        let client = LakeFSClient::new(mock_server.uri(), "Bearer test-token");
+        let command = RepoCommands::Create {
+            name: "new".to_string(),
+            storage_namespace: "s3://bucket/new".to_string(),
+            default_branch: "main".to_string(),
+            dry_run: true,
+        };
+        assert!(execute(command, client.clone(), &OptionsConfig::default()).await.is_ok());
+        
+        let command = RepoCommands::Create {
+            name: "new".to_string(),
+            storage_namespace: "s3://bucket/other/new".to_string(),
+            default_branch: "main".to_string(),
+            dry_run: true,
+        };
+        assert!(execute(command, client, &OptionsConfig::default()).await.is_err());
+    }
+
+    #[test]
+    fn test_template_source() {
+        for url in ["https://github.com/org/template", "git@github.com:org/template.git", "ssh://host/template"] {
+            assert_eq!(TemplateSource::parse(url), TemplateSource::Git(url.to_string()));
+        }
+        assert_eq!(TemplateSource::parse("templates/data"), TemplateSource::Directory(PathBuf::from("templates/data")));
+        assert_eq!(TemplateSource::parse("../template.git"), TemplateSource::Git("../template.git".to_string()));
+    }
+    
+    #[tokio::test]
+    async fn test_bootstrap_uploads_template_and_commits() {
+        let mock_server = MockServer::start().await;
+        let template = tempfile::TempDir::new().unwrap();
+        std::fs::write(template.path().join("README.md"), "# Data").unwrap();
+        std::fs::write(template.path().join(".lakefsignore"), "*.tmp").unwrap();
+        std::fs::create_dir_all(template.path().join("_lakefs_actions")).unwrap();
+        std::fs::write(template.path().join("_lakefs_actions/checks.yaml"), "name: checks").unwrap();
+        std::fs::create_dir_all(template.path().join(".git")).unwrap();
+        std::fs::write(template.path().join(".git/HEAD"), "ref: refs/heads/main").unwrap();
+        
+        Mock::given(method("POST"))
+            .and(path("/repositories"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(repo("new", "s3://bucket/new")))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        for object in ["README.md", ".lakefsignore", "_lakefs_actions/checks.yaml"] {
+            Mock::given(method("PUT"))
+                .and(path("/repositories/new/branches/main/objects"))
+                .and(query_param("path", object))
+                .respond_with(ResponseTemplate::new(201).set_body_json(lakefs_test_utils::fixtures::test_object_stats()))
+                .expect(1)
+                .mount(&mock_server)
+                .await;
+        }
+        Mock::given(method("POST"))
+            .and(path("/repositories/new/branches/main/commits"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(lakefs_test_utils::fixtures::test_commit()))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        
+        let client = LakeFSClient::new(mock_server.uri(), "Bearer test-token");
+        let (repo, files, _) = bootstrap(&client, "new", "s3://bucket/new", template.path(), "Bootstrap", &OptionsConfig::default())
+            .await
+            .unwrap();
+        assert_eq!(repo.id, "new");
+        assert_eq!(files, 3);
+    }
+    
+    #[tokio::test]
+    async fn test_list_repositories_passes_pagination()  {
+        let mock_server = MockServer::start().await;
+        
+        Mock::given(method("GET"))
+            .and(path("/repositories"))
+            .and(query_param("prefix", "data"))
+            .and(query_param("amount", "10"))
+            .respond_with(ResponseTemplate::new(200)
+                .set_body_json(serde_json::json!({
+                    "results": [
+                        {
+                            "id": "data-b",
+                            "storage_namespace": "s3://bucket/b",
+                            "default_branch": "main",
+                            "creation_date": "2024-01-02T00:00:00Z"
+                        },
+                        {
+                            "id": "data-a",
+                            "storage_namespace": "s3://bucket/a",
+                            "default_branch": "main",
+                            "creation_date": "2024-01-01T00:00:00Z"
+                        }
+                    ],
+                    "pagination": {
+                        "has_more": false,
+                        "max_per_page": 10,
+                        "results": 2,
+                        "next_offset": null
+                    }
+                })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = LakeFSClient::new(mock_server.uri(), "Bearer test-token");
+        
+        let command = RepoCommands::List {
+            amount: Some(10),
+            after: None,
+            prefix: Some("data".to_string()),
+            sort: Some(RepoSort::Name),
+        };
+        
+        let result = execute(command, client, &OptionsConfig::default()).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_sort_reads_every_page()  {
+        let mock_server = MockServer::start().await;
+        let repo = |id: &str, created: &str| serde_json::json!({
+            "id": id,
+            "storage_namespace": format!("s3://bucket/{}", id),
+            "default_branch": "main",
+            "creation_date": created
+        });
+        
+        Mock::given(method("GET"))
+            .and(path("/repositories"))
+            .and(query_param("after", "b"))
+            .respond_with(ResponseTemplate::new(200)
+                .set_body_json(serde_json::json!({
+                    "results": [repo("c", "2024-01-01T00:00:00Z")],
+                    "pagination": {"has_more": false, "max_per_page": 2, "results": 1, "next_offset": null}
+                })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        
+        Mock::given(method("GET"))
+            .and(path("/repositories"))
+            .respond_with(ResponseTemplate::new(200)
+                .set_body_json(serde_json::json!({
+                    "results": [repo("a", "2024-01-03T00:00:00Z"), repo("b", "2024-01-02T00:00:00Z")],
+                    "pagination": {"has_more": true, "max_per_page": 2, "results": 2, "next_offset": "b"}
+                })))
+            .mount(&mock_server)
+            .await;
+
+        let client = LakeFSClient::new(mock_server.uri(), "Bearer test-token");
+        let params = PaginationParams { amount: Some(2), ..Default::default() };
+        let repositories = sorted_repositories(&client, params, RepoSort::Created).await.unwrap();
+        
+        let ids: Vec<_> = repositories.iter().map(|r| r.id.as_str()).collect();
+        assert_eq!(ids, ["c", "b"]);
+    }
+}
@@ -0,0 +1,288 @@
+use super::audit::commits_since;
+use super::fs::{disk_usage, list_all_objects};
+use crate::utils::{format_size, parse_duration, parse_repository_uri};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use futures::{StreamExt, TryStreamExt};
+use lakefs_api::models::Commit;
+use lakefs_api::{LakeFSClient, PaginationParams};
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use tabled::{Table, Tabled};
+
+/// Size and activity of a repository.
+#[derive(Debug, Serialize)]
+struct RepoStats {
+    repository: String,
+    /// Start of the window commits are counted over
+    since: DateTime<Utc>,
+    branches: Vec<BranchStats>,
+    /// Commits in the window on any branch, each counted once
+    commits: usize,
+    /// The most active committers in the window
+    committers: Vec<CommitterStats>,
+}
+
+#[derive(Debug, Serialize)]
+struct BranchStats {
+    branch: String,
+    objects: u64,
+    /// Logical size: the sum of object sizes, however much storage they share
+    /// with other branches
+    bytes: i64,
+    /// Commits in the window in the branch's history
+    commits: usize,
+    prefixes: Vec<PrefixStats>,
+}
+
+#[derive(Debug, Serialize)]
+struct PrefixStats {
+    prefix: String,
+    objects: u64,
+    bytes: i64,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+struct CommitterStats {
+    committer: String,
+    commits: usize,
+    last_commit: DateTime<Utc>,
+}
+
+#[derive(Tabled)]
+struct BranchRow {
+    branch: String,
+    objects: u64,
+    size: String,
+    commits: usize,
+}
+
+#[derive(Tabled)]
+struct PrefixRow {
+    branch: String,
+    prefix: String,
+    objects: u64,
+    size: String,
+}
+
+#[derive(Tabled)]
+struct CommitterRow {
+    committer: String,
+    commits: usize,
+    #[tabled(rename = "last commit")]
+    last_commit: String,
+}
+
+/// Report object counts and logical size per branch and top-level prefixes,
+/// and who committed how much recently.
+///
+/// Every object on every branch is listed, so this reads as much as listing
+/// the whole repository once per branch.
+pub async fn execute(
+    repository: String,
+    since: String,
+    depth: usize,
+    top: usize,
+    parallelism: usize,
+    json: bool,
+    client: LakeFSClient,
+) -> Result<()> {
+    let repository = parse_repository_uri(&repository)?;
+    let cutoff = Utc::now() - chrono::Duration::from_std(parse_duration(&since)?)?;
+    let stats = collect_stats(&client, &repository, cutoff, depth, top, parallelism).await?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&stats)?);
+        return Ok(());
+    }
+
+    let rows: Vec<BranchRow> = stats
+        .branches
+        .iter()
+        .map(|b| BranchRow {
+            branch: b.branch.clone(),
+            objects: b.objects,
+            size: format_size(b.bytes),
+            commits: b.commits,
+        })
+        .collect();
+    println!("{}", Table::new(rows));
+
+    let rows: Vec<PrefixRow> = stats
+        .branches
+        .iter()
+        .flat_map(|b| {
+            b.prefixes.iter().map(|p| PrefixRow {
+                branch: b.branch.clone(),
+                prefix: p.prefix.clone(),
+                objects: p.objects,
+                size: format_size(p.bytes),
+            })
+        })
+        .collect();
+    if !rows.is_empty() {
+        println!("\n{}", Table::new(rows));
+    }
+
+    println!("\n{} commits since {}", stats.commits, stats.since.format("%Y-%m-%d %H:%M:%S"));
+    if !stats.committers.is_empty() {
+        let rows: Vec<CommitterRow> = stats
+            .committers
+            .iter()
+            .map(|c| CommitterRow {
+                committer: c.committer.clone(),
+                commits: c.commits,
+                last_commit: c.last_commit.format("%Y-%m-%d %H:%M:%S").to_string(),
+            })
+            .collect();
+        println!("{}", Table::new(rows));
+    }
+    Ok(())
+}
+
+/// List every branch, up to `parallelism` at a time, and tally its objects
+/// and its commits made after `cutoff`.
+async fn collect_stats(
+    client: &LakeFSClient,
+    repository: &str,
+    cutoff: DateTime<Utc>,
+    depth: usize,
+    top: usize,
+    parallelism: usize,
+) -> Result<RepoStats> {
+    let mut branches = Vec::new();
+    let mut params = PaginationParams::default();
+    loop {
+        let page = client.list_branches_with(repository, &params).await?;
+        branches.extend(page.results.into_iter().map(|b| b.id));
+
+        match page.pagination.next_offset {
+            Some(next) if page.pagination.has_more => params.after = Some(next),
+            _ => break,
+        }
+    }
+
+    let per_branch: Vec<(BranchStats, Vec<Commit>)> = futures::stream::iter(branches)
+        .map(|branch| async move {
+            let objects = list_all_objects(client, repository, &branch, "").await?;
+            let commits = commits_since(client, repository, &branch, cutoff).await?;
+            let prefixes = disk_usage(&objects, "", depth)
+                .into_iter()
+                .map(|(directory, usage)| PrefixStats {
+                    prefix: format!("{}/", directory),
+                    objects: usage.objects,
+                    bytes: usage.bytes,
+                })
+                .collect();
+            let stats = BranchStats {
+                objects: objects.len() as u64,
+                bytes: objects.iter().map(|o| o.size_bytes).sum(),
+                commits: commits.len(),
+                prefixes,
+                branch,
+            };
+            Ok::<_, anyhow::Error>((stats, commits))
+        })
+        .buffered(parallelism.max(1))
+        .try_collect()
+        .await?;
+
+    let mut seen = HashSet::new();
+    let mut commits = Vec::new();
+    let mut branches = Vec::new();
+    for (stats, branch_commits) in per_branch {
+        commits.extend(branch_commits.into_iter().filter(|c| seen.insert(c.id.clone())));
+        branches.push(stats);
+    }
+
+    let mut committers = committer_stats(&commits);
+    committers.truncate(top);
+    Ok(RepoStats {
+        repository: repository.to_string(),
+        since: cutoff,
+        branches,
+        commits: commits.len(),
+        committers,
+    })
+}
+
+/// Commits per committer, most active first; ties go to whoever committed
+/// last.
+fn committer_stats(commits: &[Commit]) -> Vec<CommitterStats> {
+    let mut by_committer: HashMap<&str, CommitterStats> = HashMap::new();
+    for commit in commits {
+        let stats = by_committer.entry(&commit.committer).or_insert_with(|| CommitterStats {
+            committer: commit.committer.clone(),
+            commits: 0,
+            last_commit: commit.creation_date,
+        });
+        stats.commits += 1;
+        stats.last_commit = stats.last_commit.max(commit.creation_date);
+    }
+
+    let mut committers: Vec<CommitterStats> = by_committer.into_values().collect();
+    committers.sort_by_key(|c| (Reverse(c.commits), Reverse(c.last_commit), c.committer.clone()));
+    committers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lakefs_test_utils::scenario::MockLakeFS;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, ResponseTemplate};
+
+    fn commit(id: &str, committer: &str, age_days: i64) -> serde_json::Value {
+        serde_json::json!({
+            "id": id,
+            "parents": [],
+            "committer": committer,
+            "message": format!("change {}", id),
+            "creation_date": (Utc::now() - chrono::Duration::days(age_days)).timestamp(),
+            "meta_range_id": "",
+            "metadata": {}
+        })
+    }
+
+    #[tokio::test]
+    async fn test_collect_stats_per_branch() {
+        let server = MockLakeFS::new()
+            .with_repo("repo")
+            .with_branch("main")
+            .with_object("raw/a.csv", "aaaa")
+            .with_object("raw/b.csv", "bb")
+            .with_object("README", "r")
+            .with_branch("dev")
+            .with_object("raw/a.csv", "aaaaaaaa")
+            .start()
+            .await;
+        let logs = [
+            ("main", vec![commit("m2", "alice", 1), commit("m1", "bob", 2), commit("m0", "admin", 60)]),
+            ("dev", vec![commit("d1", "bob", 0), commit("m1", "bob", 2), commit("m0", "admin", 60)]),
+        ];
+        for (branch, commits) in logs {
+            Mock::given(method("GET"))
+                .and(path(format!("/repositories/repo/branches/{}/commits", branch)))
+                .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                    "results": commits,
+                    "pagination": {"has_more": false, "max_per_page": 100, "results": 3}
+                })))
+                .mount(&server)
+                .await;
+        }
+        let client = LakeFSClient::new(server.uri(), "Bearer test-token");
+
+        let cutoff = Utc::now() - chrono::Duration::days(30);
+        let stats = collect_stats(&client, "repo", cutoff, 1, 10, 2).await.unwrap();
+        let branches: Vec<_> = stats.branches.iter().map(|b| (b.branch.as_str(), b.objects, b.bytes, b.commits)).collect();
+        assert_eq!(branches, [("dev", 1, 8, 2), ("main", 3, 7, 2)]);
+        let prefixes: Vec<_> = stats.branches[1].prefixes.iter().map(|p| (p.prefix.as_str(), p.objects, p.bytes)).collect();
+        assert_eq!(prefixes, [("raw/", 2, 6)]);
+
+        // m1 is on both branches but counted once
+        assert_eq!(stats.commits, 3);
+        let committers: Vec<_> = stats.committers.iter().map(|c| (c.committer.as_str(), c.commits)).collect();
+        assert_eq!(committers, [("bob", 2), ("alice", 1)]);
+    }
+}
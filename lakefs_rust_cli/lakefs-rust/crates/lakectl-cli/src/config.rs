@@ -1,9 +1,85 @@
 use crate::cli::Cli;
+use crate::utils::edit_distance;
 use anyhow::{Context, Result};
-use config::{Config, Environment, File};
-use lakefs_auth::AuthConfig;
+use config::{builder::DefaultState, Config, ConfigBuilder, File};
+use lakefs_auth::{AuthConfig, ObjectStoreConfig};
+use lakefs_local::{Compression, LinkStrategy};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Keyring service under which lakectl stores secrets.
+const KEYRING_SERVICE: &str = "lakectl";
+/// Prefix marking a config value as a reference to a keyring entry.
+const KEYRING_PREFIX: &str = "keyring:";
+
+/// Config keys that can be set from the environment. As in Go lakectl, the
+/// variable is `LAKECTL_` plus the upper-cased key path with `.` replaced by
+/// `_` (`server.endpoint_url` is `LAKECTL_SERVER_ENDPOINT_URL`). Key names
+/// contain underscores themselves, so variables are looked up from this list
+/// rather than split apart.
+const ENV_KEYS: &[&str] = &[
+    "server.endpoint_url",
+    "server.user_agent",
+    "credentials.type",
+    "credentials.access_key_id",
+    "credentials.secret_access_key",
+    "credentials.region",
+    "credentials.base_uri",
+    "credentials.client_id",
+    "credentials.resource",
+    "credentials.audience",
+    "credentials.command",
+    "credentials.args",
+    "options.parallelism",
+    "options.no_progress",
+    "options.usage_telemetry",
+    "options.object_cache",
+    "options.link_strategy",
+    "options.compression",
+    "options.delta_threshold",
+    "options.ignore_permissions",
+    "storage.endpoint",
+    "storage.region",
+    "storage.force_path_style",
+    "storage.access_key_id",
+    "storage.secret_access_key",
+    "telemetry.endpoint",
+    "telemetry.service_name",
+    "commit.template",
+];
+
+/// Keys in `ENV_KEYS` holding a list, whose variable is split on whitespace.
+const LIST_ENV_KEYS: &[&str] = &["credentials.args"];
+
+/// Keys that can only be set in the config file.
+const FILE_ONLY_KEYS: &[&str] = &[
+    "credentials.providers",
+    "storage.session_token",
+    "telemetry.headers",
+    "commit.required_trailers",
+];
+
+/// `credentials.type` values, which are case-sensitive.
+const CREDENTIAL_TYPES: &[&str] = &[
+    "Basic", "Login", "AwsIam", "AzureIdentity", "GcpIdentity", "Exec", "Env", "Prompt", "Chain",
+];
+
+/// Other exact spellings of a credential type that `AuthConfig` accepts, and
+/// the type each stands for. No other case is.
+const CREDENTIAL_TYPE_ALIASES: &[(&str, &str)] = &[("exec", "Exec")];
+
+/// More concurrent transfers than this is a typo rather than a tuning choice.
+const MAX_PARALLELISM: u64 = 1024;
+
+/// Sections a `repositories.<name>` entry can override. Telemetry is set up
+/// before the command, and so its repository, is known.
+const REPOSITORY_SECTIONS: &[&str] = &["server", "credentials", "options", "storage", "commit"];
+
+/// Keys whose values `config show` and `config get` mask, wherever they appear.
+const SECRET_KEYS: &[&str] = &["secret_access_key", "session_token", "headers"];
+/// Shown in place of a secret
+const MASK: &str = "********";
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AppConfig {
@@ -11,17 +87,123 @@ pub struct AppConfig {
     pub credentials: AuthConfig,
     #[serde(default)]
     pub options: OptionsConfig,
+    /// Direct access to the object store, for signing URLs on the client
+    #[serde(default)]
+    pub storage: Option<ObjectStoreConfig>,
+    /// OpenTelemetry trace export; off unless this section is present
+    #[serde(default)]
+    pub telemetry: Option<TelemetryConfig>,
+    /// Rules commit messages must follow
+    #[serde(default)]
+    pub commit: CommitConfig,
+    /// Settings for single repositories, keyed by name, laid out like the
+    /// sections above. Applied by [`AppConfig::for_repository`].
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub repositories: HashMap<String, serde_json::Value>,
+}
+
+impl AppConfig {
+    /// The configuration for a command on `repository`: its entry under
+    /// `repositories` merged over the rest. Sections merge key by key, except
+    /// `credentials`, which is replaced as a whole so settings of one type
+    /// never mix with another's.
+    pub fn for_repository(mut self, repository: &str) -> Result<AppConfig> {
+        let Some(overrides) = self.repositories.remove(repository) else {
+            return Ok(self);
+        };
+        self.repositories.clear();
+        
+        let mut merged = serde_json::to_value(&self)?;
+        if let (Some(merged), Some(overrides)) = (merged.as_object_mut(), overrides.as_object()) {
+            for (section, value) in overrides {
+                match merged.get_mut(section) {
+                    Some(existing) if section != "credentials" => merge_values(existing, value.clone()),
+                    _ => {
+                        merged.insert(section.clone(), value.clone());
+                    }
+                }
+            }
+        }
+        let config: AppConfig = serde_json::from_value(merged)
+            .with_context(|| format!("Invalid settings under repositories.{}", repository))?;
+        resolve_secrets(config)
+    }
+}
+
+fn merge_values(base: &mut serde_json::Value, overrides: serde_json::Value) {
+    match (base, overrides) {
+        (serde_json::Value::Object(base), serde_json::Value::Object(overrides)) => {
+            for (key, value) in overrides {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_values(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overrides) => *base = overrides,
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TelemetryConfig {
+    /// OTLP/HTTP traces endpoint, such as `http://collector:4318/v1/traces`.
+    /// Defaults to `OTEL_EXPORTER_OTLP_ENDPOINT`, then a local collector.
+    #[serde(default)]
+    pub endpoint: Option<String>,
+    /// `service.name` of the exported spans
+    #[serde(default = "default_service_name")]
+    pub service_name: String,
+    /// Headers sent with each export, e.g. collector credentials
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+}
+
+/// What `lakectl commit` and `lakectl local commit` expect of a message,
+/// unless run with `--no-verify`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CommitConfig {
+    /// File whose text `local commit` opens the editor with
+    pub template: Option<PathBuf>,
+    /// Trailer keys, such as `Jira`, every message must end with
+    pub required_trailers: Vec<String>,
+}
+
+fn default_service_name() -> String {
+    "lakectl".to_string()
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ServerConfig {
     pub endpoint_url: String,
+    /// Replaces the default `lakectl-rust/<version> (<os>; <arch>)` User-Agent
+    #[serde(default)]
+    pub user_agent: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+#[serde(default)]
 pub struct OptionsConfig {
     pub parallelism: usize,
     pub no_progress: bool,
+    /// Opt in to reporting the invoked command in the User-Agent
+    #[serde(default)]
+    pub usage_telemetry: bool,
+    /// Object cache shared by local checkouts
+    pub object_cache: Option<PathBuf>,
+    /// How local checkouts materialize files from `object_cache`: `copy`,
+    /// `hardlink` or `reflink`
+    pub link_strategy: LinkStrategy,
+    /// Compress files local checkouts upload: `gzip` or `zstd`
+    pub compression: Option<Compression>,
+    /// Size in bytes from which local checkouts record block checksums
+    pub delta_threshold: Option<u64>,
+    /// Leave file modes and modification times out of local checkouts. When
+    /// off they are kept in the user metadata of uploaded objects and
+    /// restored on download
+    pub ignore_permissions: bool,
 }
 
 impl Default for OptionsConfig {
@@ -29,46 +211,679 @@ impl Default for OptionsConfig {
         Self {
             parallelism: 10,
             no_progress: false,
+            usage_telemetry: false,
+            object_cache: None,
+            link_strategy: LinkStrategy::default(),
+            compression: None,
+            delta_threshold: None,
+            ignore_permissions: true,
         }
     }
 }
 
-pub fn load_config(cli: &Cli) -> Result<AppConfig> {
-    let mut builder = Config::builder();
-    
+/// Config file given with `--config`, or the default location.
+pub fn config_file_path(cli: &Cli) -> Option<PathBuf> {
+    config_path(cli.config.as_deref())
+}
+
+/// `explicit`, or the default config file location.
+pub fn config_path(explicit: Option<&str>) -> Option<PathBuf> {
     // Default config file location
     let default_config = dirs::config_dir()
         .map(|p| p.join("lakectl").join("config.yaml"))
         .or_else(|| dirs::home_dir().map(|p| p.join(".lakectl.yaml")));
     
-    // Load from config file
-    let config_file = cli.config
-        .as_ref()
+    explicit
         .map(PathBuf::from)
-        .or(default_config);
+        .or(default_config)
+}
+
+pub fn load_config(cli: &Cli) -> Result<AppConfig> {
+    load_config_from(config_file_path(cli))
+}
+
+/// Configuration from the file at `path`, when it exists, and the environment.
+pub fn load_config_from(path: Option<PathBuf>) -> Result<AppConfig> {
+    build_config(config_builder(path)?)
+}
+
+/// Sources of the configuration: the file at `path`, when it exists, and the
+/// environment.
+fn config_builder(path: Option<PathBuf>) -> Result<ConfigBuilder<DefaultState>> {
+    let mut builder = Config::builder();
     
-    if let Some(path) = config_file {
+    // Load from config file
+    if let Some(path) = path {
         if path.exists() {
             builder = builder.add_source(File::from(path));
         }
     }
     
     // Override with environment variables
-    builder = builder.add_source(
-        Environment::with_prefix("LAKECTL")
-            .separator("_")
-            .try_parsing(true),
-    );
+    apply_env_overrides(builder, |name| std::env::var(name).ok())
+}
+
+/// A problem with one configuration key.
+#[derive(Debug, PartialEq)]
+pub struct ConfigIssue {
+    pub key: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for ConfigIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.key, self.message)
+    }
+}
+
+/// What `lakectl config validate` found. Errors stop lakectl from starting;
+/// warnings are keys it ignores, most likely typos.
+#[derive(Debug, Default)]
+pub struct ConfigReport {
+    pub errors: Vec<ConfigIssue>,
+    pub warnings: Vec<ConfigIssue>,
+}
+
+impl ConfigReport {
+    fn error(&mut self, key: &str, message: String) {
+        self.errors.push(ConfigIssue { key: key.to_string(), message });
+    }
+    
+    fn warning(&mut self, key: &str, message: String) {
+        self.warnings.push(ConfigIssue { key: key.to_string(), message });
+    }
+}
+
+/// Check the configuration that would be loaded from `path` and the
+/// environment, including whatever only shows when it is deserialized, such
+/// as a keyring entry that cannot be read.
+pub fn check_config(path: Option<PathBuf>) -> Result<ConfigReport> {
+    let config = config_builder(path)?
+        .build()
+        .context("Failed to build configuration")?;
+    
+    let mut report = validate(&config);
+    if report.errors.is_empty() {
+        if let Err(e) = into_app_config(config) {
+            report.error("config", format!("{:#}", e));
+        }
+    }
+    Ok(report)
+}
+
+/// Check the keys serde would reject with a vague message, or accept although
+/// they cannot work.
+fn validate(config: &Config) -> ConfigReport {
+    let mut report = ConfigReport::default();
+    let raw: serde_json::Value = match config.clone().try_deserialize() {
+        Ok(raw) => raw,
+        Err(e) => {
+            report.error("config", e.to_string());
+            return report;
+        }
+    };
     
+    check_unknown_keys(&raw, &mut report);
+    
+    match raw.pointer("/server/endpoint_url").and_then(as_text) {
+        Some(url) if !url.trim().is_empty() => {}
+        _ => report.error(
+            "server.endpoint_url",
+            "is not set; add it to the config file or set LAKECTL_SERVER_ENDPOINT_URL, e.g. http://localhost:8000/api/v1".to_string(),
+        ),
+    }
+    if raw.get("credentials").is_none() {
+        report.error("credentials", "is not set".to_string());
+    }
+    check_settings("", &raw, &mut report);
+    
+    if let Some(repositories) = raw.get("repositories").and_then(|r| r.as_object()) {
+        for (name, settings) in repositories {
+            check_settings(&format!("repositories.{}.", name), settings, &mut report);
+        }
+    }
+    report
+}
+
+/// Check the values present in `settings`, a whole configuration or a
+/// repository's entry, reporting them under `prefix`.
+fn check_settings(prefix: &str, settings: &serde_json::Value, report: &mut ConfigReport) {
+    for key in ["server.endpoint_url", "storage.endpoint", "telemetry.endpoint"] {
+        let pointer = format!("/{}", key.replace('.', "/"));
+        match settings.pointer(&pointer).and_then(as_text) {
+            Some(url) if !url.trim().is_empty() => check_url(&format!("{}{}", prefix, key), &url, report),
+            _ => {}
+        }
+    }
+    
+    if let Some(credentials) = settings.get("credentials") {
+        check_credentials(&format!("{}credentials", prefix), credentials, report);
+    }
+    
+    if let Some(value) = settings.pointer("/options/parallelism") {
+        let key = format!("{}options.parallelism", prefix);
+        match as_text(value).and_then(|v| v.trim().parse::<u64>().ok()) {
+            Some(0) => report.error(&key, "must be at least 1".to_string()),
+            Some(n) if n > MAX_PARALLELISM => report.error(
+                &key,
+                format!("{} concurrent transfers is more than a server can usefully take; use at most {}", n, MAX_PARALLELISM),
+            ),
+            Some(_) => {}
+            None => report.error(&key, format!("must be a whole number, not {}", value)),
+        }
+    }
+}
+
+/// Warn about keys lakectl does not read, suggesting the key meant where one
+/// is close. Sections of their own, such as Go lakectl's `metastore`, are
+/// only flagged when they look like a misspelt section.
+fn check_unknown_keys(raw: &serde_json::Value, report: &mut ConfigReport) {
+    let known: Vec<&str> = ENV_KEYS.iter().chain(FILE_ONLY_KEYS).copied().collect();
+    let mut sections: Vec<&str> = known.iter().filter_map(|k| k.split_once('.')).map(|(s, _)| s).collect();
+    sections.sort();
+    sections.dedup();
+    let Some(raw) = raw.as_object() else {
+        return;
+    };
+    
+    for (section, value) in raw {
+        if section == "repositories" {
+            for (name, settings) in value.as_object().into_iter().flatten() {
+                for (section, value) in settings.as_object().into_iter().flatten() {
+                    let key = format!("repositories.{}.{}", name, section);
+                    if REPOSITORY_SECTIONS.contains(&section.as_str()) {
+                        check_section_keys(&key, section, value, &known, report);
+                    } else {
+                        let message = match closest(section, REPOSITORY_SECTIONS.iter().copied()) {
+                            Some(suggestion) if suggestion != section => format!("ignored; did you mean '{}'?", suggestion),
+                            _ => format!("ignored; only {} can be set per repository", REPOSITORY_SECTIONS.join(", ")),
+                        };
+                        report.warning(&key, message);
+                    }
+                }
+            }
+        } else if sections.contains(&section.as_str()) {
+            check_section_keys(section, section, value, &known, report);
+        } else if let Some(suggestion) = closest(section, sections.iter().copied().chain(["repositories"])) {
+            report.warning(section, format!("unknown section, ignored; did you mean '{}'?", suggestion));
+        }
+    }
+}
+
+/// Warn about keys in `value`, the `section` found at `key`, that lakectl
+/// does not read.
+fn check_section_keys(key: &str, section: &str, value: &serde_json::Value, known: &[&str], report: &mut ConfigReport) {
+    let Some(keys) = value.as_object() else {
+        return;
+    };
+    let names: Vec<&str> = known.iter().filter_map(|k| k.strip_prefix(section)?.strip_prefix('.')).collect();
+    for name in keys.keys() {
+        if names.contains(&name.as_str()) || (section == "credentials" && name == "type") {
+            continue;
+        }
+        let message = match closest(name, names.iter().copied()) {
+            Some(suggestion) => format!("unknown key, ignored; did you mean '{}.{}'?", key, suggestion),
+            None => "unknown key, ignored".to_string(),
+        };
+        report.warning(&format!("{}.{}", key, name), message);
+    }
+}
+
+fn check_credentials(key: &str, credentials: &serde_json::Value, report: &mut ConfigReport) {
+    let type_key = format!("{}.type", key);
+    let Some(kind) = credentials.get("type").and_then(as_text) else {
+        report.error(&type_key, format!("is not set; expected one of {}", CREDENTIAL_TYPES.join(", ")));
+        return;
+    };
+    let canonical = CREDENTIAL_TYPE_ALIASES
+        .iter()
+        .find(|(alias, _)| *alias == kind)
+        .map_or(kind.as_str(), |(_, canonical)| canonical);
+    let required: &[&str] = match canonical {
+        "Basic" | "Login" => &["access_key_id", "secret_access_key"],
+        "AwsIam" => &["region"],
+        "Exec" => &["command"],
+        "Chain" => &["providers"],
+        "AzureIdentity" | "GcpIdentity" | "Env" | "Prompt" => &[],
+        _ => {
+            let hint = match closest(&kind, CREDENTIAL_TYPES.iter().copied()) {
+                Some(suggestion) => format!("did you mean '{}'? ", suggestion),
+                None => String::new(),
+            };
+            report.error(&type_key, format!("'{}' is not a credential type; {}expected one of {}", kind, hint, CREDENTIAL_TYPES.join(", ")));
+            return;
+        }
+    };
+    
+    for field in required {
+        if credentials.get(field).is_some_and(|v| !v.is_null()) {
+            continue;
+        }
+        let env = ENV_KEYS
+            .iter()
+            .find(|k| **k == format!("credentials.{}", field))
+            .map(|k| format!(" or set {}", env_var_name(k)))
+            .filter(|_| key == "credentials")
+            .unwrap_or_default();
+        report.error(&format!("{}.{}", key, field), format!("is required for {} credentials; add it to the config file{}", kind, env));
+    }
+    
+    if kind == "Chain" {
+        match credentials.get("providers").map(|p| p.as_array()) {
+            Some(Some(providers)) if providers.is_empty() => {
+                report.error(&format!("{}.providers", key), "must list at least one provider".to_string());
+            }
+            Some(Some(providers)) => {
+                for (i, provider) in providers.iter().enumerate() {
+                    check_credentials(&format!("{}.providers[{}]", key, i), provider, report);
+                }
+            }
+            Some(None) => report.error(&format!("{}.providers", key), "must be a list of credential settings".to_string()),
+            None => {}
+        }
+    }
+}
+
+fn check_url(key: &str, value: &str, report: &mut ConfigReport) {
+    match reqwest::Url::parse(value) {
+        Ok(url) if matches!(url.scheme(), "http" | "https") && url.has_host() => {}
+        // `localhost:8000` parses, with `localhost` as the scheme
+        _ if !value.contains("://") => {
+            report.error(key, format!("'{}' is missing a scheme; did you mean 'http://{}'?", value, value));
+        }
+        Ok(url) => report.error(key, format!("'{}' must be an http:// or https:// URL, not {}://", value, url.scheme())),
+        Err(e) => report.error(key, format!("'{}' is not a valid URL: {}", value, e)),
+    }
+}
+
+/// A value as text; environment overrides arrive as strings whatever their
+/// type.
+fn as_text(value: &serde_json::Value) -> Option<String> {
+    match value {
+        serde_json::Value::String(s) => Some(s.clone()),
+        serde_json::Value::Number(n) => Some(n.to_string()),
+        serde_json::Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+/// The candidate `word` is most likely a misspelling of, if any: one that
+/// differs only in case, or by at most two edits.
+fn closest<'a>(word: &str, candidates: impl Iterator<Item = &'a str>) -> Option<&'a str> {
+    let word = word.to_lowercase();
+    candidates
+        .map(|candidate| (edit_distance(&word, &candidate.to_lowercase()), candidate))
+        .filter(|(distance, _)| *distance <= 2)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// Load a second server's configuration, such as the source of `replicate`.
+/// Only the file is read: `LAKECTL_*` variables describe the main server.
+pub fn load_config_file(path: &Path) -> Result<AppConfig> {
+    if !path.exists() {
+        anyhow::bail!("Config file {} does not exist", path.display());
+    }
+    let builder = Config::builder()
+        .add_source(File::from(path))
+        .set_default("credentials.type", "Basic")?;
+    build_config(builder)
+}
+
+fn build_config(builder: ConfigBuilder<DefaultState>) -> Result<AppConfig> {
     // Build config
     let config = builder
         .build()
         .context("Failed to build configuration")?;
     
+    let report = validate(&config);
+    if !report.errors.is_empty() {
+        let errors: Vec<String> = report.errors.iter().map(|e| format!("  {}", e)).collect();
+        anyhow::bail!("Invalid configuration:\n{}\nRun `lakectl config validate` for details", errors.join("\n"));
+    }
+    into_app_config(config)
+}
+
+fn into_app_config(config: Config) -> Result<AppConfig> {
     // Parse into our structure
-    config
+    let config: AppConfig = config
         .try_deserialize()
-        .context("Failed to deserialize configuration")
+        .context("Failed to deserialize configuration")?;
+    resolve_secrets(config)
+}
+
+/// Replace keyring references in `config` with the secrets they name.
+fn resolve_secrets(mut config: AppConfig) -> Result<AppConfig> {
+    config.credentials = resolve_credentials(config.credentials)?;
+    if let Some(storage) = config.storage.as_mut() {
+        storage.secret_access_key = storage.secret_access_key.take().map(resolve_secret).transpose()?;
+    }
+    Ok(config)
+}
+
+/// Name of the environment variable that overrides `key`.
+fn env_var_name(key: &str) -> String {
+    format!("LAKECTL_{}", key.replace('.', "_").to_uppercase())
+}
+
+fn apply_env_overrides(
+    mut builder: ConfigBuilder<DefaultState>,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Result<ConfigBuilder<DefaultState>> {
+    for key in ENV_KEYS {
+        let Some(value) = lookup(&env_var_name(key)) else {
+            continue;
+        };
+        builder = match LIST_ENV_KEYS.contains(key) {
+            true => builder.set_override(*key, value.split_whitespace().map(str::to_string).collect::<Vec<_>>())?,
+            false => builder.set_override(*key, value)?,
+        };
+    }
+    
+    // Go lakectl only has key pairs, so its variables alone must be enough
+    Ok(builder.set_default("credentials.type", "Basic")?)
+}
+
+/// Config value referring to the keyring entry for `account`.
+pub fn keyring_reference(account: &str) -> String {
+    format!("{}{}", KEYRING_PREFIX, account)
+}
+
+/// Save `secret` in the OS keyring under `account`.
+pub fn store_secret(account: &str, secret: &str) -> Result<()> {
+    keyring::Entry::new(KEYRING_SERVICE, account)
+        .and_then(|entry| entry.set_password(secret))
+        .with_context(|| format!("Failed to store secret for '{}' in the OS keyring", account))
+}
+
+/// Replace a keyring reference in the credentials with the stored secret.
+fn resolve_credentials(credentials: AuthConfig) -> Result<AuthConfig> {
+    match credentials {
+        AuthConfig::Basic { access_key_id, secret_access_key } => {
+            let secret_access_key = resolve_secret(secret_access_key)?;
+            Ok(AuthConfig::Basic { access_key_id, secret_access_key })
+        }
+        AuthConfig::Login { access_key_id, secret_access_key, base_uri } => {
+            let secret_access_key = resolve_secret(secret_access_key)?;
+            Ok(AuthConfig::Login { access_key_id, secret_access_key, base_uri })
+        }
+        AuthConfig::Chain { providers } => {
+            let providers = providers.into_iter().map(resolve_credentials).collect::<Result<_>>()?;
+            Ok(AuthConfig::Chain { providers })
+        }
+        other => Ok(other),
+    }
+}
+
+fn resolve_secret(secret_access_key: String) -> Result<String> {
+    match secret_access_key.strip_prefix(KEYRING_PREFIX) {
+        Some(account) => keyring::Entry::new(KEYRING_SERVICE, account)
+            .and_then(|entry| entry.get_password())
+            .with_context(|| format!("Failed to read secret for '{}' from the OS keyring", account)),
+        None => Ok(secret_access_key),
+    }
+}
+
+/// Point the Basic credentials in the config file at `path` to the keyring
+/// entry for `access_key_id`, keeping every other setting.
+pub fn write_keyring_credentials(path: &Path, access_key_id: &str) -> Result<()> {
+    let mut document = read_document(path)?;
+    let mapping = document
+        .as_mapping_mut()
+        .with_context(|| format!("{} is not a YAML mapping", path.display()))?;
+    let credentials = AuthConfig::Basic {
+        access_key_id: access_key_id.to_string(),
+        secret_access_key: keyring_reference(access_key_id),
+    };
+    mapping.insert("credentials".into(), serde_yaml::to_value(credentials)?);
+    
+    write_document(path, &document)
+}
+
+/// Set `key`, such as `server.endpoint_url`, to `value` in the config file at
+/// `path`, creating the file if needed. The value is read as YAML, so numbers
+/// and booleans keep their type. Nothing is written if the new value is
+/// invalid for the key.
+pub fn set_config_value(path: &Path, key: &str, value: &str) -> Result<()> {
+    let known: Vec<&str> = ENV_KEYS.iter().chain(FILE_ONLY_KEYS).copied().collect();
+    let setting = match key.strip_prefix("repositories.").and_then(|rest| rest.split_once('.')) {
+        Some((_, setting)) if !REPOSITORY_SECTIONS.iter().any(|s| setting.starts_with(&format!("{}.", s))) => {
+            anyhow::bail!("Only {} can be set per repository", REPOSITORY_SECTIONS.join(", "));
+        }
+        Some((_, setting)) => setting,
+        None => key,
+    };
+    if !known.contains(&setting) {
+        let hint = match closest(setting, known.iter().copied()) {
+            Some(suggestion) => format!("; did you mean '{}'?", suggestion),
+            None => String::new(),
+        };
+        anyhow::bail!("Unknown config key '{}'{}", key, hint);
+    }
+    
+    let text = read_text(path)?;
+    let mut document: serde_yaml::Value = serde_yaml::from_str(&text)
+        .with_context(|| format!("Failed to parse {}", path.display()))?;
+    if document.is_null() {
+        document = serde_yaml::Value::Mapping(Default::default());
+    }
+    let value: serde_yaml::Value = serde_yaml::from_str(value).unwrap_or_else(|_| value.into());
+    let (parents, name) = key.rsplit_once('.').map_or((None, key), |(p, n)| (Some(p), n));
+    let mut mapping = document
+        .as_mapping_mut()
+        .with_context(|| format!("{} is not a YAML mapping", path.display()))?;
+    for part in parents.into_iter().flat_map(|p| p.split('.')) {
+        let entry = mapping
+            .entry(part.into())
+            .or_insert_with(|| serde_yaml::Value::Mapping(Default::default()));
+        if entry.is_null() {
+            *entry = serde_yaml::Value::Mapping(Default::default());
+        }
+        mapping = entry
+            .as_mapping_mut()
+            .with_context(|| format!("'{}' in {} is not a section", part, path.display()))?;
+    }
+    mapping.insert(name.into(), value.clone());
+    
+    // Only this key's problems: the rest of the file may be set next
+    let content = serde_yaml::to_string(&document)?;
+    let config = Config::builder()
+        .add_source(File::from_str(&content, config::FileFormat::Yaml))
+        .set_default("credentials.type", "Basic")?
+        .build()
+        .context("Failed to build configuration")?;
+    if let Some(issue) = validate(&config).errors.into_iter().find(|issue| issue.key == key) {
+        anyhow::bail!("Not saved: {}", issue);
+    }
+    
+    // Editing the text keeps comments; it is used only if it reads back as
+    // the document
+    let edited = edit_in_place(&text, key, &value)
+        .filter(|edited| serde_yaml::from_str::<serde_yaml::Value>(edited).ok().as_ref() == Some(&document));
+    match edited {
+        Some(edited) => write_text(path, &edited),
+        None => {
+            if text.contains('#') {
+                eprintln!("Warning: {} was rewritten in full, so its comments were not kept", path.display());
+            }
+            write_document(path, &document)
+        }
+    }
+}
+
+/// Set `key` to `value` in the YAML `text` by changing or adding only the
+/// lines involved, keeping comments and the rest of the layout. `None` when
+/// the text is not block-style YAML this can follow, such as a flow-style
+/// section or a value spanning lines.
+fn edit_in_place(text: &str, key: &str, value: &serde_yaml::Value) -> Option<String> {
+    let rendered = inline_yaml(value)?;
+    let mut lines: Vec<String> = text.lines().map(str::to_string).collect();
+    if lines.iter().any(|line| line.starts_with("---") || line.starts_with("...")) {
+        return None;
+    }
+    
+    let parts: Vec<&str> = key.split('.').collect();
+    // The mapping searched for each part, and the indent of its keys
+    let (mut start, mut end, mut indent) = (0, lines.len(), 0);
+    for (i, part) in parts.iter().enumerate() {
+        if let Some(first) = (start..end).find_map(|n| content_indent(&lines[n])) {
+            indent = first;
+        }
+        let mut found = None;
+        for (n, line) in lines.iter().enumerate().take(end).skip(start) {
+            if content_indent(line) == Some(indent) && mapping_entry(line)?.0 == *part {
+                found = Some(n);
+                break;
+            }
+        }
+        
+        let Some(n) = found else {
+            // Add the rest of the key after the mapping's last entry
+            let at = (start..end).rev().find(|&n| content_indent(&lines[n]).is_some()).map_or(start, |n| n + 1);
+            let added = parts[i..].iter().enumerate().map(|(depth, part)| {
+                let pad = " ".repeat(indent + 2 * depth);
+                match i + depth + 1 == parts.len() {
+                    true => format!("{}{}: {}", pad, part, rendered),
+                    false => format!("{}{}:", pad, part),
+                }
+            });
+            lines.splice(at..at, added);
+            return Some(lines.join("\n") + "\n");
+        };
+        
+        let rest = mapping_entry(&lines[n])?.1;
+        let comment = rest
+            .find(" #")
+            .filter(|_| !rest.contains(['"', '\'']))
+            .map(|at| &rest[rest[..at].trim_end().len()..]);
+        let current = comment.map_or(rest, |comment| &rest[..rest.len() - comment.len()]).trim();
+        // The entry's value runs up to the next line as far left as its key
+        let value_end = (n + 1..end)
+            .find(|&m| content_indent(&lines[m]).is_some_and(|at| at <= indent))
+            .unwrap_or(end);
+        let nested = (n + 1..value_end).any(|m| content_indent(&lines[m]).is_some());
+        
+        if i + 1 == parts.len() {
+            if nested {
+                return None;
+            }
+            lines[n] = format!("{}{}: {}{}", " ".repeat(indent), part, rendered, comment.unwrap_or(""));
+            return Some(lines.join("\n") + "\n");
+        }
+        if !current.is_empty() {
+            return None;
+        }
+        (start, end, indent) = (n + 1, value_end, indent + 2);
+    }
+    None
+}
+
+/// Indent of a line holding YAML; `None` for blank lines and comments.
+fn content_indent(line: &str) -> Option<usize> {
+    let trimmed = line.trim_start();
+    (!trimmed.is_empty() && !trimmed.starts_with('#')).then(|| line.len() - trimmed.len())
+}
+
+/// Key of a `key: value` line, and what follows its colon.
+fn mapping_entry(line: &str) -> Option<(&str, &str)> {
+    let trimmed = line.trim_start();
+    if trimmed.starts_with(['-', '{', '[', '"', '\'', '?', '&', '*', '!', '|', '>']) {
+        return None;
+    }
+    let (name, rest) = trimmed.split_once(':')?;
+    (rest.is_empty() || rest.starts_with(' ')).then_some((name.trim_end(), rest))
+}
+
+/// `value` written on one line: scalars as serde_yaml writes them and
+/// sequences of them in flow style.
+fn inline_yaml(value: &serde_yaml::Value) -> Option<String> {
+    match value {
+        serde_yaml::Value::Sequence(items) => {
+            let items = items.iter().map(inline_yaml).collect::<Option<Vec<_>>>()?;
+            Some(format!("[{}]", items.join(", ")))
+        }
+        serde_yaml::Value::Mapping(_) | serde_yaml::Value::Tagged(_) => None,
+        scalar => {
+            let text = serde_yaml::to_string(scalar).ok()?;
+            let text = text.trim_end();
+            (!text.contains('\n')).then(|| text.to_string())
+        }
+    }
+}
+
+/// The configuration as YAML, with secrets masked.
+pub fn masked_config(config: &AppConfig) -> Result<serde_yaml::Value> {
+    let mut value = serde_yaml::to_value(config)?;
+    mask_secrets(&mut value);
+    Ok(value)
+}
+
+/// The value of `key` in the masked configuration, if it is set.
+pub fn config_value(config: &AppConfig, key: &str) -> Result<Option<serde_yaml::Value>> {
+    let mut value = masked_config(config)?;
+    for part in key.split('.') {
+        match value.get_mut(part) {
+            Some(child) => value = std::mem::take(child),
+            None => return Ok(None),
+        }
+    }
+    Ok((!value.is_null()).then_some(value))
+}
+
+fn mask_secrets(value: &mut serde_yaml::Value) {
+    match value {
+        serde_yaml::Value::Mapping(mapping) => {
+            for (key, child) in mapping.iter_mut() {
+                if key.as_str().is_some_and(|k| SECRET_KEYS.contains(&k)) {
+                    mask(child);
+                } else {
+                    mask_secrets(child);
+                }
+            }
+        }
+        serde_yaml::Value::Sequence(items) => items.iter_mut().for_each(mask_secrets),
+        _ => {}
+    }
+}
+
+/// Replace every string in `value` with [`MASK`], keeping the structure.
+fn mask(value: &mut serde_yaml::Value) {
+    match value {
+        serde_yaml::Value::String(_) => *value = MASK.into(),
+        serde_yaml::Value::Mapping(mapping) => mapping.values_mut().for_each(mask),
+        serde_yaml::Value::Sequence(items) => items.iter_mut().for_each(mask),
+        _ => {}
+    }
+}
+
+/// The config file at `path`, or an empty mapping if there is none yet.
+fn read_document(path: &Path) -> Result<serde_yaml::Value> {
+    if !path.exists() {
+        return Ok(serde_yaml::Value::Mapping(Default::default()));
+    }
+    serde_yaml::from_str(&read_text(path)?)
+        .with_context(|| format!("Failed to parse {}", path.display()))
+}
+
+/// The file's text, empty if there is no file.
+fn read_text(path: &Path) -> Result<String> {
+    if !path.exists() {
+        return Ok(String::new());
+    }
+    std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))
+}
+
+fn write_document(path: &Path, document: &serde_yaml::Value) -> Result<()> {
+    write_text(path, &serde_yaml::to_string(document)?)
+}
+
+fn write_text(path: &Path, text: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, text)
+        .with_context(|| format!("Failed to write {}", path.display()))
 }
 
 #[cfg(test)]
@@ -85,10 +900,19 @@ mod tests {
         assert!(!options.no_progress);
     }
 
+    #[test]
+    fn test_object_cache_options()  {
+        let options: OptionsConfig = serde_yaml::from_str("object_cache: /data/lakectl-cache\nlink_strategy: hardlink\n").unwrap();
+        assert_eq!(options.object_cache, Some(PathBuf::from("/data/lakectl-cache")));
+        assert_eq!(options.link_strategy, LinkStrategy::Hardlink);
+        assert!(serde_yaml::from_str::<OptionsConfig>("link_strategy: symlink\n").is_err());
+    }
+
     #[test]
     fn test_server_config()  {
         let server = ServerConfig {
             endpoint_url: "http://localhost:8000".to_string(),
+            user_agent: None,
         };
         assert_eq!(server.endpoint_url, "http://localhost:8000");
     }
@@ -98,12 +922,17 @@ mod tests {
         let config = AppConfig {
             server: ServerConfig {
                 endpoint_url: "http://test.lakefs.io".to_string(),
+                user_agent: None,
             },
             credentials: AuthConfig::Basic {
                 access_key_id: "test-key".to_string(),
                 secret_access_key: "test-secret".to_string(),
             },
             options: OptionsConfig::default(),
+            storage: None,
+            telemetry: None,
+            commit: CommitConfig::default(),
+            repositories: HashMap::new(),
         };
         
         let yaml = serde_yaml::to_string(&config).unwrap();
@@ -114,6 +943,23 @@ mod tests {
         assert_eq!(deserialized.server.endpoint_url, "http://test.lakefs.io");
     }
 
+    #[test]
+    fn test_user_agent_serialization()  {
+        let server = ServerConfig {
+            endpoint_url: "http://test.lakefs.io".to_string(),
+            user_agent: Some("my-pipeline/1.0".to_string()),
+        };
+        
+        let yaml = serde_yaml::to_string(&server).unwrap();
+        assert!(yaml.contains("user_agent: my-pipeline/1.0"));
+        
+        let deserialized: ServerConfig = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(deserialized.user_agent.as_deref(), Some("my-pipeline/1.0"));
+        
+        let server: ServerConfig = serde_yaml::from_str("endpoint_url: http://test.lakefs.io\n").unwrap();
+        assert_eq!(server.user_agent, None);
+    }
+
     #[test]
     fn test_load_config_from_file()  {
         let temp_dir = TempDir::new().unwrap();
@@ -137,14 +983,407 @@ options:
             command: Commands::Repo { 
                 command: RepoCommands::List { 
                     amount: None, 
-                    after: None 
+                    after: None,
+                    prefix: None,
+                    sort: None,
                 } 
             },
             config: Some(config_path.to_string_lossy().to_string()),
             verbose: false,
             no_color: false,
+            metrics_file: None,
+        };
+        
+        let _config = load_config(&cli).unwrap();
+    }
+    
+    #[test]
+    fn test_load_config_file_reads_options()  {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("config.yaml");
+        fs::write(&config_path, r#"
+server:
+  endpoint_url: http://test.lakefs.io
+credentials:
+  type: Basic
+  access_key_id: test_key
+  secret_access_key: test_secret
+options:
+  parallelism: 20
+  no_progress: true
+"#).unwrap();
+        
+        let config = load_config_file(&config_path).unwrap();
+        assert_eq!(config.server.endpoint_url, "http://test.lakefs.io");
+        assert_eq!(config.options.parallelism, 20);
+        assert!(config.options.no_progress);
+    }
+    
+    #[test]
+    fn test_telemetry_config()  {
+        let yaml = "server:\n  endpoint_url: http://test.lakefs.io\ncredentials:\n  type: Basic\n  access_key_id: k\n  secret_access_key: s\n";
+        let config: AppConfig = serde_yaml::from_str(yaml).unwrap();
+        assert!(config.telemetry.is_none());
+        
+        let yaml = format!("{}telemetry:\n  endpoint: http://collector:4318/v1/traces\n", yaml);
+        let config: AppConfig = serde_yaml::from_str(&yaml).unwrap();
+        let telemetry = config.telemetry.unwrap();
+        assert_eq!(telemetry.endpoint.as_deref(), Some("http://collector:4318/v1/traces"));
+        assert_eq!(telemetry.service_name, "lakectl");
+    }
+    
+    #[test]
+    fn test_load_config_file_ignores_env()  {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("source.yaml");
+        fs::write(&config_path, r#"
+server:
+  endpoint_url: http://source.lakefs.io
+credentials:
+  access_key_id: source_key
+  secret_access_key: source_secret
+"#).unwrap();
+        
+        let config = load_config_file(&config_path).unwrap();
+        assert_eq!(config.server.endpoint_url, "http://source.lakefs.io");
+        assert!(matches!(config.credentials, AuthConfig::Basic { .. }));
+        assert!(load_config_file(&temp_dir.path().join("missing.yaml")).is_err());
+    }
+    
+    #[test]
+    fn test_write_keyring_credentials_keeps_other_settings()  {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("config.yaml");
+        fs::write(&config_path, r#"
+server:
+  endpoint_url: http://test.lakefs.io
+credentials:
+  type: Basic
+  access_key_id: old_key
+  secret_access_key: plaintext_secret
+"#).unwrap();
+        
+        write_keyring_credentials(&config_path, "new_key").unwrap();
+        
+        let content = fs::read_to_string(&config_path).unwrap();
+        assert!(!content.contains("plaintext_secret"));
+        let config: AppConfig = serde_yaml::from_str(&content).unwrap();
+        assert_eq!(config.server.endpoint_url, "http://test.lakefs.io");
+        match config.credentials {
+            AuthConfig::Basic { access_key_id, secret_access_key } => {
+                assert_eq!(access_key_id, "new_key");
+                assert_eq!(secret_access_key, "keyring:new_key");
+            }
+            _ => panic!("Expected Basic credentials"),
+        }
+    }
+    
+    #[test]
+    fn test_resolve_credentials_without_reference()  {
+        let credentials = AuthConfig::Basic {
+            access_key_id: "key".to_string(),
+            secret_access_key: "secret".to_string(),
         };
+        match resolve_credentials(credentials).unwrap() {
+            AuthConfig::Basic { secret_access_key, .. } => assert_eq!(secret_access_key, "secret"),
+            _ => panic!("Expected Basic credentials"),
+        }
+    }
+    
+    #[test]
+    fn test_go_lakectl_env_vars()  {
+        let env: std::collections::HashMap<&str, &str> = [
+            ("LAKECTL_SERVER_ENDPOINT_URL", "http://env.lakefs.io"),
+            ("LAKECTL_CREDENTIALS_ACCESS_KEY_ID", "env_key"),
+            ("LAKECTL_CREDENTIALS_SECRET_ACCESS_KEY", "env_secret"),
+            ("LAKECTL_OPTIONS_PARALLELISM", "4"),
+        ].into_iter().collect();
+        
+        let builder = apply_env_overrides(Config::builder(), |name| env.get(name).map(|v| v.to_string())).unwrap();
+        let config: AppConfig = builder.build().unwrap().try_deserialize().unwrap();
+        
+        assert_eq!(config.server.endpoint_url, "http://env.lakefs.io");
+        assert_eq!(config.options.parallelism, 4);
+        match config.credentials {
+            AuthConfig::Basic { access_key_id, secret_access_key } => {
+                assert_eq!(access_key_id, "env_key");
+                assert_eq!(secret_access_key, "env_secret");
+            }
+            _ => panic!("Expected Basic credentials"),
+        }
+        assert!(config.storage.is_none());
+    }
+    
+    #[test]
+    fn test_storage_env_vars()  {
+        let env: std::collections::HashMap<&str, &str> = [
+            ("LAKECTL_SERVER_ENDPOINT_URL", "http://env.lakefs.io"),
+            ("LAKECTL_CREDENTIALS_ACCESS_KEY_ID", "env_key"),
+            ("LAKECTL_CREDENTIALS_SECRET_ACCESS_KEY", "env_secret"),
+            ("LAKECTL_STORAGE_ENDPOINT", "http://minio:9000"),
+            ("LAKECTL_STORAGE_FORCE_PATH_STYLE", "true"),
+        ].into_iter().collect();
+        
+        let builder = apply_env_overrides(Config::builder(), |name| env.get(name).map(|v| v.to_string())).unwrap();
+        let config = build_config(builder).unwrap();
+        
+        let storage = config.storage.unwrap();
+        assert_eq!(storage.endpoint.as_deref(), Some("http://minio:9000"));
+        assert!(storage.force_path_style);
+        assert_eq!(storage.region, "us-east-1");
+    }
+    
+    #[test]
+    fn test_exec_args_env_var()  {
+        let env: std::collections::HashMap<&str, &str> = [
+            ("LAKECTL_SERVER_ENDPOINT_URL", "http://env.lakefs.io"),
+            ("LAKECTL_CREDENTIALS_TYPE", "Exec"),
+            ("LAKECTL_CREDENTIALS_COMMAND", "/usr/bin/get-lakefs-creds"),
+            ("LAKECTL_CREDENTIALS_ARGS", "--profile  prod"),
+        ].into_iter().collect();
+        
+        let builder = apply_env_overrides(Config::builder(), |name| env.get(name).map(|v| v.to_string())).unwrap();
+        let config = build_config(builder).unwrap();
+        
+        match config.credentials {
+            AuthConfig::Exec { command, args } => {
+                assert_eq!(command, "/usr/bin/get-lakefs-creds");
+                assert_eq!(args, ["--profile", "prod"]);
+            }
+            _ => panic!("Expected Exec credentials"),
+        }
+    }
+    
+    fn validate_yaml(yaml: &str) -> ConfigReport {
+        let builder = Config::builder()
+            .add_source(File::from_str(yaml, config::FileFormat::Yaml))
+            .set_default("credentials.type", "Basic")
+            .unwrap();
+        validate(&builder.build().unwrap())
+    }
+    
+    fn keys(issues: &[ConfigIssue]) -> Vec<&str> {
+        issues.iter().map(|i| i.key.as_str()).collect()
+    }
+    
+    #[test]
+    fn test_validate_reports_each_field()  {
+        let report = validate_yaml(r#"
+server:
+  endpoint_url: localhost:8000
+credentials:
+  type: basic
+  access_key_id: key
+options:
+  parallelism: 0
+"#);
+        assert_eq!(keys(&report.errors), ["server.endpoint_url", "credentials.type", "options.parallelism"]);
+        assert!(report.errors[0].message.contains("did you mean 'http://localhost:8000'"));
+        assert!(report.errors[1].message.contains("did you mean 'Basic'"));
+        assert!(report.errors[2].message.contains("at least 1"));
+        
+        let report = validate_yaml("credentials:\n  type: Basic\n  access_key_id: key\noptions:\n  parallelism: many\n");
+        assert_eq!(keys(&report.errors), ["server.endpoint_url", "credentials.secret_access_key", "options.parallelism"]);
+        assert!(report.errors[0].message.contains("LAKECTL_SERVER_ENDPOINT_URL"));
+        assert!(report.errors[1].message.contains("LAKECTL_CREDENTIALS_SECRET_ACCESS_KEY"));
+    }
+    
+    #[test]
+    fn test_validate_checks_chained_providers()  {
+        let report = validate_yaml(r#"
+server:
+  endpoint_url: ftp://lakefs.example.com
+credentials:
+  type: Chain
+  providers:
+    - type: Env
+    - type: AwsIam
+    - type: Kerberos
+"#);
+        assert_eq!(keys(&report.errors), ["server.endpoint_url", "credentials.providers[1].region", "credentials.providers[2].type"]);
+        assert!(report.errors[0].message.contains("not ftp://"));
+        assert!(report.errors[2].message.contains("expected one of Basic, Login"));
+    }
+    
+    #[test]
+    fn test_validate_credential_type_case()  {
+        let server = "server:\n  endpoint_url: https://lakefs.example.com\n";
+        let report = validate_yaml(&format!("{}credentials:\n  type: exec\n", server));
+        assert_eq!(keys(&report.errors), ["credentials.command"]);
+        
+        let report = validate_yaml(&format!("{}credentials:\n  type: EXEC\n  command: helper\n", server));
+        assert_eq!(keys(&report.errors), ["credentials.type"]);
+        assert!(report.errors[0].message.contains("did you mean 'Exec'"));
+    }
+    
+    #[test]
+    fn test_validate_warns_about_misspelt_keys()  {
+        let report = validate_yaml(r#"
+server:
+  endpoint_url: https://lakefs.example.com/api/v1
+credentials:
+  type: Basic
+  access_key_id: key
+  secret_access_key: secret
+options:
+  paralelism: 4
+  retries: 3
+metastore:
+  hive: {}
+"#);
+        assert!(report.errors.is_empty());
+        assert_eq!(keys(&report.warnings), ["options.paralelism", "options.retries"]);
+        assert!(report.warnings[0].message.contains("did you mean 'options.parallelism'"));
+        assert_eq!(report.warnings[1].message, "unknown key, ignored");
+    }
+    
+    #[test]
+    fn test_set_config_value()  {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("config.yaml");
+        fs::write(&config_path, "# lakectl\ncredentials:\n  access_key_id: key\n  secret_access_key: secret\n").unwrap();
+        
+        set_config_value(&config_path, "server.endpoint_url", "https://lakefs.example.com/api/v1").unwrap();
+        set_config_value(&config_path, "options.parallelism", "32").unwrap();
+        let config = load_config_file(&config_path).unwrap();
+        assert_eq!(config.server.endpoint_url, "https://lakefs.example.com/api/v1");
+        assert_eq!(config.options.parallelism, 32);
+        
+        let error = set_config_value(&config_path, "options.parallelism", "0").unwrap_err().to_string();
+        assert!(error.contains("must be at least 1"), "{}", error);
+        let error = set_config_value(&config_path, "options.paralelism", "4").unwrap_err().to_string();
+        assert!(error.contains("did you mean 'options.parallelism'"), "{}", error);
+        assert_eq!(load_config_file(&config_path).unwrap().options.parallelism, 32);
+    }
+    
+    #[test]
+    fn test_set_config_value_keeps_comments()  {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("config.yaml");
+        fs::write(&config_path, "\
+# lakectl
+server:
+  endpoint_url: http://old.example.com  # staging
+
+# Keys
+credentials:
+  access_key_id: key
+  secret_access_key: secret
+").unwrap();
+        
+        set_config_value(&config_path, "server.endpoint_url", "https://lakefs.example.com").unwrap();
+        set_config_value(&config_path, "credentials.type", "Basic").unwrap();
+        set_config_value(&config_path, "options.parallelism", "32").unwrap();
+        set_config_value(&config_path, "commit.required_trailers", "[Jira, Reviewed-by]").unwrap();
+        assert_eq!(fs::read_to_string(&config_path).unwrap(), "\
+# lakectl
+server:
+  endpoint_url: https://lakefs.example.com  # staging
+
+# Keys
+credentials:
+  access_key_id: key
+  secret_access_key: secret
+  type: Basic
+options:
+  parallelism: 32
+commit:
+  required_trailers: [Jira, Reviewed-by]
+");
+        
+        // Flow style is beyond editing in place, so the file is rewritten
+        fs::write(&config_path, "# flow\noptions: {parallelism: 4}\n").unwrap();
+        set_config_value(&config_path, "options.parallelism", "8").unwrap();
+        assert_eq!(fs::read_to_string(&config_path).unwrap(), "options:\n  parallelism: 8\n");
+    }
+    
+    #[test]
+    fn test_config_value_masks_secrets()  {
+        let yaml = "server:\n  endpoint_url: http://test.lakefs.io\ncredentials:\n  type: Basic\n  access_key_id: k\n  secret_access_key: s\ntelemetry:\n  headers:\n    authorization: Bearer t\n";
+        let config: AppConfig = serde_yaml::from_str(yaml).unwrap();
+        
+        let value = |key| config_value(&config, key).unwrap();
+        assert_eq!(value("credentials.access_key_id"), Some("k".into()));
+        assert_eq!(value("credentials.secret_access_key"), Some(MASK.into()));
+        assert_eq!(value("options.parallelism"), Some(10.into()));
+        assert_eq!(value("server.user_agent"), None);
+        assert_eq!(value("nonsense"), None);
+        
+        let shown = serde_yaml::to_string(&masked_config(&config).unwrap()).unwrap();
+        assert!(!shown.contains("Bearer"));
+    }
+    
+    #[test]
+    fn test_repository_overrides()  {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("config.yaml");
+        fs::write(&config_path, r#"
+server:
+  endpoint_url: http://test.lakefs.io
+credentials:
+  type: Basic
+  access_key_id: key
+  secret_access_key: secret
+options:
+  parallelism: 8
+  no_progress: true
+repositories:
+  analytics:
+    options:
+      parallelism: 64
+    storage:
+      endpoint: http://minio:9000
+  archive:
+    server:
+      endpoint_url: https://archive.lakefs.io
+    credentials:
+      type: Env
+"#).unwrap();
+        let config = || load_config_file(&config_path).unwrap();
+        
+        let analytics = config().for_repository("analytics").unwrap();
+        assert_eq!(analytics.options.parallelism, 64);
+        assert!(analytics.options.no_progress);
+        assert_eq!(analytics.storage.unwrap().endpoint.as_deref(), Some("http://minio:9000"));
+        assert!(matches!(analytics.credentials, AuthConfig::Basic { .. }));
+        
+        let archive = config().for_repository("archive").unwrap();
+        assert_eq!(archive.server.endpoint_url, "https://archive.lakefs.io");
+        assert!(matches!(archive.credentials, AuthConfig::Env));
+        assert_eq!(archive.options.parallelism, 8);
+        
+        let other = config().for_repository("other").unwrap();
+        assert_eq!(other.server.endpoint_url, "http://test.lakefs.io");
+    }
+    
+    #[test]
+    fn test_validate_repository_overrides()  {
+        let report = validate_yaml(r#"
+server:
+  endpoint_url: http://test.lakefs.io
+credentials:
+  access_key_id: key
+  secret_access_key: secret
+repositories:
+  analytics:
+    options:
+      parallelism: 0
+    credentials:
+      type: AwsIam
+    telemetry:
+      endpoint: http://collector:4318
+"#);
+        assert_eq!(keys(&report.errors), ["repositories.analytics.credentials.region", "repositories.analytics.options.parallelism"]);
+        assert_eq!(keys(&report.warnings), ["repositories.analytics.telemetry"]);
+    }
+    
+    #[test]
+    fn test_load_config_fails_with_field_errors()  {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("config.yaml");
+        fs::write(&config_path, "server:\n  endpoint_url: http://\ncredentials:\n  access_key_id: k\n  secret_access_key: s\n").unwrap();
         
-        let config = load_config(&cli).unwrap();
+        let error = load_config_file(&config_path).unwrap_err().to_string();
+        assert!(error.contains("server.endpoint_url: 'http://' is not a valid URL"), "{}", error);
     }
 }
\ No newline at end of file
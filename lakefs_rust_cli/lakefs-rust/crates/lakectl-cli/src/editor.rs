@@ -0,0 +1,88 @@
+//! Messages written in the user's editor, as `git commit` does without `-m`.
+
+use anyhow::{Context, Result};
+use std::path::Path;
+use std::process::Command;
+
+/// Editor used when neither `$VISUAL` nor `$EDITOR` is set
+#[cfg(windows)]
+const DEFAULT_EDITOR: &str = "notepad";
+#[cfg(not(windows))]
+const DEFAULT_EDITOR: &str = "vi";
+
+/// The user's editor: `$VISUAL`, then `$EDITOR`, then the platform default.
+pub fn editor() -> String {
+    ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|value| !value.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_EDITOR.to_string())
+}
+
+/// Write `template` to `file`, open it in `editor` and return what was saved
+/// with `#` comment lines removed. The editor is run through the shell, so
+/// it may carry arguments, as in `code --wait`.
+pub fn edit(editor: &str, file: &Path, template: &str) -> Result<String> {
+    std::fs::write(file, template).with_context(|| format!("Failed to write {}", file.display()))?;
+
+    let status = shell(editor, file)
+        .status()
+        .with_context(|| format!("Failed to start editor '{}'", editor))?;
+    if !status.success() {
+        anyhow::bail!("Editor '{}' exited with {}", editor, status);
+    }
+
+    let text = std::fs::read_to_string(file).with_context(|| format!("Failed to read {}", file.display()))?;
+    Ok(strip_comments(&text))
+}
+
+#[cfg(not(windows))]
+fn shell(editor: &str, file: &Path) -> Command {
+    let mut command = Command::new("sh");
+    command.arg("-c").arg(format!("{} \"$@\"", editor)).arg(editor).arg(file);
+    command
+}
+
+#[cfg(windows)]
+fn shell(editor: &str, file: &Path) -> Command {
+    let mut command = Command::new("cmd");
+    command.arg("/C").arg(editor).arg(file);
+    command
+}
+
+/// `text` without lines starting with `#`, and without trailing whitespace
+/// or leading and trailing blank lines.
+pub fn strip_comments(text: &str) -> String {
+    let lines: Vec<&str> = text
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .map(str::trim_end)
+        .collect();
+    lines.join("\n").trim_matches('\n').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_strip_comments() {
+        let text = "\nAdd data  \n\nLonger body\n# a comment\nSigned-off-by: a <a@b.c>\n\n# Changes:\n#\tadded: x\n";
+        assert_eq!(strip_comments(text), "Add data\n\nLonger body\nSigned-off-by: a <a@b.c>");
+        assert_eq!(strip_comments("# only comments\n\n"), "");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_edit_returns_saved_message() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("COMMIT_EDITMSG");
+
+        // An "editor" that appends a message below the template
+        let message = edit("printf 'Add data\\n\\nBody\\n' >>", &file, "\n# template\n").unwrap();
+        assert_eq!(message, "Add data\n\nBody");
+
+        assert!(edit("false", &file, "").is_err());
+    }
+}
@@ -0,0 +1,179 @@
+//! Advice printed under common errors, such as rejected credentials or an
+//! unreachable server, saying what to check rather than only what failed.
+
+use crate::suggest;
+use lakefs_api::LakeFSClient;
+use std::fmt;
+
+/// An error, and what to do about it.
+#[derive(Debug)]
+pub struct Hinted {
+    error: anyhow::Error,
+    hint: String,
+}
+
+impl Hinted {
+    pub fn hint(&self) -> &str {
+        &self.hint
+    }
+}
+
+impl fmt::Display for Hinted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.error, f)
+    }
+}
+
+impl std::error::Error for Hinted {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.error.source()
+    }
+}
+
+/// Print `error` with its causes, and its hint if it has one.
+pub fn render(error: &anyhow::Error) {
+    eprintln!("Error: {:?}", error);
+    if let Some(hinted) = error.downcast_ref::<Hinted>() {
+        eprintln!("\nHint: {}", hinted.hint());
+    }
+}
+
+/// What a command was working on, checked when something was not found.
+pub struct Target<'a> {
+    pub client: &'a LakeFSClient,
+    pub repository: &'a str,
+    pub reference: Option<&'a str>,
+}
+
+/// `error`, with a hint attached when it is one of the failures there is
+/// advice for. `endpoint` is the server the command talked to; with a
+/// `target`, whatever was not found is checked for being its repository or
+/// reference, and existing names close to it are suggested.
+pub async fn with_hint(error: anyhow::Error, endpoint: &str, target: Option<Target<'_>>) -> anyhow::Error {
+    let hint = match classify(&error) {
+        Some(Failure::Unauthorized) => Some(
+            "the server rejected the credentials; check them with `lakectl config show`, \
+             or store new ones with `lakectl config set-credentials`"
+                .to_string(),
+        ),
+        Some(Failure::Forbidden) => Some(
+            "the credentials are valid but not allowed to do this; ask an administrator to check the user's policies"
+                .to_string(),
+        ),
+        Some(Failure::Unreachable) => Some(format!(
+            "is the endpoint correct, and the server running? (current: {}) \
+             Change it with `lakectl config set server.endpoint_url <url>`",
+            endpoint
+        )),
+        Some(Failure::NotFound) => match target {
+            Some(Target { client, repository, reference }) => match suggest::missing_repository(client, repository).await {
+                Some(hint) => Some(hint),
+                None => match reference {
+                    Some(reference) => suggest::missing_reference(client, repository, reference).await,
+                    None => None,
+                },
+            },
+            None => None,
+        },
+        None => None,
+    };
+    match hint {
+        Some(hint) => Hinted { error, hint }.into(),
+        None => error,
+    }
+}
+
+/// The failures there are hints for.
+#[derive(Debug, PartialEq)]
+enum Failure {
+    Unauthorized,
+    Forbidden,
+    Unreachable,
+    NotFound,
+}
+
+fn classify(error: &anyhow::Error) -> Option<Failure> {
+    error.chain().find_map(|cause| {
+        if let Some(error) = cause.downcast_ref::<lakefs_api::Error>() {
+            return match error {
+                lakefs_api::Error::Auth(_) => Some(Failure::Unauthorized),
+                lakefs_api::Error::Api { status: 403, .. } => Some(Failure::Forbidden),
+                lakefs_api::Error::NotFound(_) => Some(Failure::NotFound),
+                _ => None,
+            };
+        }
+        if let Some(lakefs_auth::Error::InvalidCredentials | lakefs_auth::Error::Login(_)) =
+            cause.downcast_ref::<lakefs_auth::Error>()
+        {
+            return Some(Failure::Unauthorized);
+        }
+        match cause.downcast_ref::<reqwest::Error>() {
+            Some(error) if error.is_connect() || error.is_timeout() => Some(Failure::Unreachable),
+            _ => None,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+    use lakefs_test_utils::scenario::MockLakeFS;
+
+    #[tokio::test]
+    async fn test_hint_for_rejected_credentials() {
+        let error = anyhow::Error::from(lakefs_api::Error::Auth("unauthorized".to_string())).context("Failed to list repositories");
+        let error = with_hint(error, "http://localhost:8000/api/v1", None).await;
+        let hinted = error.downcast_ref::<Hinted>().unwrap();
+        assert!(hinted.hint().contains("lakectl config set-credentials"));
+        assert_eq!(error.to_string(), "Failed to list repositories");
+    }
+
+    #[tokio::test]
+    async fn test_hint_for_unreachable_server() {
+        // Bound and released, so nothing is listening there
+        let port = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let endpoint = format!("http://127.0.0.1:{}/api/v1", port);
+        let client = LakeFSClient::new(endpoint.clone(), "Bearer test-token");
+        let error = client.list_repositories().await.context("Failed to list repositories").unwrap_err();
+
+        let error = with_hint(error, &endpoint, None).await;
+        let hint = error.downcast_ref::<Hinted>().unwrap().hint();
+        assert!(hint.contains(&format!("(current: {})", endpoint)), "{}", hint);
+    }
+
+    fn target<'a>(client: &'a LakeFSClient, repository: &'a str, reference: Option<&'a str>) -> Option<Target<'a>> {
+        Some(Target { client, repository, reference })
+    }
+
+    #[tokio::test]
+    async fn test_hint_suggests_close_names() {
+        let server = MockLakeFS::new()
+            .with_repo("analytics")
+            .with_branch("main")
+            .with_repo("analytics-archive")
+            .with_branch("main")
+            .with_repo("images")
+            .with_branch("main")
+            .start()
+            .await;
+        let client = LakeFSClient::new(server.uri(), "Bearer test-token");
+        let not_found = || anyhow::Error::from(lakefs_api::Error::NotFound("not found".to_string()));
+
+        let error = with_hint(not_found(), &server.uri(), target(&client, "analytcs", Some("main"))).await;
+        let hint = error.downcast_ref::<Hinted>().unwrap().hint();
+        assert_eq!(hint, "repository 'analytcs' does not exist; did you mean 'analytics'?");
+
+        let error = with_hint(not_found(), &server.uri(), target(&client, "analytic", None)).await;
+        let hint = error.downcast_ref::<Hinted>().unwrap().hint();
+        assert_eq!(hint, "repository 'analytic' does not exist; did you mean 'analytics' or 'analytics-archive'?");
+
+        let error = with_hint(not_found(), &server.uri(), target(&client, "images", Some("mian"))).await;
+        let hint = error.downcast_ref::<Hinted>().unwrap().hint();
+        assert_eq!(hint, "'mian' is not a branch of images; did you mean 'main'?");
+
+        // The repository and branch are there, so something else was missing
+        let error = with_hint(not_found(), &server.uri(), target(&client, "images", Some("main"))).await;
+        assert!(error.downcast_ref::<Hinted>().is_none());
+    }
+}
@@ -0,0 +1,130 @@
+//! Latency percentiles of repeated requests, for load tests.
+
+use serde::Serialize;
+use std::time::Duration;
+
+/// Latencies of the requests made so far, and how many failed.
+#[derive(Debug, Default)]
+pub struct Latencies {
+    samples: Vec<Duration>,
+    errors: usize,
+    first_error: Option<String>,
+}
+
+/// What a run of requests took. Failed requests are counted but their
+/// latencies are left out, so timeouts do not masquerade as slow successes.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct LatencySummary {
+    pub requests: usize,
+    pub errors: usize,
+    pub first_error: Option<String>,
+    pub requests_per_second: f64,
+    pub min_ms: f64,
+    pub mean_ms: f64,
+    pub p50_ms: f64,
+    pub p90_ms: f64,
+    pub p95_ms: f64,
+    pub p99_ms: f64,
+    pub max_ms: f64,
+}
+
+impl Latencies {
+    pub fn record(&mut self, latency: Duration) {
+        self.samples.push(latency);
+    }
+
+    pub fn record_error(&mut self, error: impl std::fmt::Display) {
+        self.errors += 1;
+        self.first_error.get_or_insert_with(|| error.to_string());
+    }
+
+    /// Summary of everything recorded, over a run that took `elapsed`.
+    pub fn summary(mut self, elapsed: Duration) -> LatencySummary {
+        self.samples.sort();
+        let requests = self.samples.len() + self.errors;
+        let total: Duration = self.samples.iter().sum();
+        let mean = match self.samples.len() {
+            0 => Duration::ZERO,
+            n => total / n as u32,
+        };
+        let seconds = elapsed.as_secs_f64();
+        LatencySummary {
+            requests,
+            errors: self.errors,
+            first_error: self.first_error,
+            requests_per_second: if seconds > 0.0 { requests as f64 / seconds } else { 0.0 },
+            min_ms: millis(self.samples.first().copied().unwrap_or_default()),
+            mean_ms: millis(mean),
+            p50_ms: millis(percentile(&self.samples, 50.0)),
+            p90_ms: millis(percentile(&self.samples, 90.0)),
+            p95_ms: millis(percentile(&self.samples, 95.0)),
+            p99_ms: millis(percentile(&self.samples, 99.0)),
+            max_ms: millis(self.samples.last().copied().unwrap_or_default()),
+        }
+    }
+}
+
+impl std::fmt::Display for LatencySummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "requests: {} ({:.1}/s)", self.requests, self.requests_per_second)?;
+        match &self.first_error {
+            Some(error) => writeln!(f, "errors:   {} (first: {})", self.errors, error)?,
+            None => writeln!(f, "errors:   0")?,
+        }
+        writeln!(f, "latency (ms):")?;
+        writeln!(f, "  min  {:>10.2}", self.min_ms)?;
+        writeln!(f, "  mean {:>10.2}", self.mean_ms)?;
+        writeln!(f, "  p50  {:>10.2}", self.p50_ms)?;
+        writeln!(f, "  p90  {:>10.2}", self.p90_ms)?;
+        writeln!(f, "  p95  {:>10.2}", self.p95_ms)?;
+        writeln!(f, "  p99  {:>10.2}", self.p99_ms)?;
+        write!(f, "  max  {:>10.2}", self.max_ms)
+    }
+}
+
+/// Nearest-rank percentile of `sorted`.
+fn percentile(sorted: &[Duration], percent: f64) -> Duration {
+    if sorted.is_empty() {
+        return Duration::ZERO;
+    }
+    let rank = (percent / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_nanos() as f64 / 1e6
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summary_percentiles() {
+        let mut latencies = Latencies::default();
+        for ms in (1..=100).rev() {
+            latencies.record(Duration::from_millis(ms));
+        }
+        latencies.record_error("503 Service Unavailable");
+        latencies.record_error("timed out");
+
+        let summary = latencies.summary(Duration::from_secs(2));
+        assert_eq!(summary.requests, 102);
+        assert_eq!(summary.errors, 2);
+        assert_eq!(summary.first_error.as_deref(), Some("503 Service Unavailable"));
+        assert_eq!(summary.requests_per_second, 51.0);
+        assert_eq!((summary.min_ms, summary.max_ms), (1.0, 100.0));
+        assert_eq!(summary.mean_ms, 50.5);
+        assert_eq!((summary.p50_ms, summary.p90_ms, summary.p99_ms), (50.0, 90.0, 99.0));
+    }
+
+    #[test]
+    fn test_summary_without_successes() {
+        let mut latencies = Latencies::default();
+        latencies.record_error("connection refused");
+        let summary = latencies.summary(Duration::ZERO);
+        assert_eq!(summary.requests, 1);
+        assert_eq!(summary.p99_ms, 0.0);
+        assert_eq!(summary.requests_per_second, 0.0);
+    }
+}
@@ -2,26 +2,71 @@ mod cli;
 mod config;
 mod commands;
 mod utils;
+mod telemetry;
+mod editor;
+mod hints;
+mod latency;
+mod suggest;
 
 use anyhow::Result;
-use clap::Parser;
-use crate::cli::Cli;  // Changed from lakectl_cli::cli::Cli
+use crate::cli::{Commands, DaemonCommands};  // Changed from lakectl_cli::cli::Cli
+use tokio_util::sync::CancellationToken;
+use tracing::Instrument;
+
+/// Exit code used when a command is interrupted with Ctrl-C.
+const EXIT_INTERRUPTED: i32 = 130;
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    // Initialize logging
-    tracing_subscriber::fmt()
-        .with_max_level(tracing::Level::INFO)
-        .init();
+    // Parse CLI arguments, asking for lakefs:// URIs left off in a terminal
+    let cli = commands::pick::parse_or_prompt().await?;
+    
+    // Config commands edit the config file, so they must work without a valid one
+    let config_path = config::config_file_path(&cli);
+    if let Commands::Config { command } = cli.command {
+        telemetry::init(None)?;
+        return commands::config::execute(command, config_path);
+    }
     
-    // Parse CLI arguments
-    let cli = Cli::parse();
+    // Daemon control commands only talk to the control socket
+    if let Commands::Daemon { command } = &cli.command {
+        if !matches!(command, DaemonCommands::Start { .. }) {
+            telemetry::init(None)?;
+            return commands::daemon::control(command).await;
+        }
+    }
     
     // Load configuration
     let config = config::load_config(&cli)?;
     
+    // Initialize logging, and trace export if configured
+    let telemetry = telemetry::init(config.telemetry.as_ref())?;
+    
+    // First Ctrl-C cancels in-flight work, a second one exits immediately
+    let cancel = CancellationToken::new();
+    let signal_cancel = cancel.clone();
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            eprintln!("Interrupted, finishing up (press Ctrl-C again to force exit)");
+            signal_cancel.cancel();
+            if tokio::signal::ctrl_c().await.is_ok() {
+                std::process::exit(EXIT_INTERRUPTED);
+            }
+        }
+    });
+    
     // Execute command
-    commands::execute(cli, config).await?;
+    let span = tracing::info_span!("lakectl", command = cli.command.name());
+    let result = commands::execute(cli, config, cancel).instrument(span).await;
+    telemetry.shutdown();
+    if let Err(e) = result {
+        if matches!(e.downcast_ref::<lakefs_local::Error>(), Some(lakefs_local::Error::Cancelled)) {
+            eprintln!("Cancelled; completed transfers were saved to the local index");
+            std::process::exit(EXIT_INTERRUPTED);
+        }
+        hints::render(&e);
+        std::process::exit(1);
+    }
     
     Ok(())
 }
\ No newline at end of file
@@ -0,0 +1,119 @@
+//! "Did you mean" suggestions for repositories and branches that do not
+//! exist, from the names that do.
+
+use crate::utils::edit_distance;
+use lakefs_api::{LakeFSClient, PaginationParams};
+
+/// Most names suggested at once
+const MAX_SUGGESTIONS: usize = 3;
+
+/// Names from `candidates` close enough to `name` to be what was meant,
+/// closest first: a couple of typos away, or one containing the other.
+pub fn closest_names(name: &str, candidates: impl IntoIterator<Item = String>) -> Vec<String> {
+    let mut close: Vec<(usize, String)> = candidates
+        .into_iter()
+        .map(|candidate| (edit_distance(name, &candidate), candidate))
+        .filter(|(distance, candidate)| *distance <= 2 || candidate.contains(name) || name.contains(candidate.as_str()))
+        .collect();
+    close.sort();
+    close.into_iter().take(MAX_SUGGESTIONS).map(|(_, candidate)| candidate).collect()
+}
+
+/// Suggestion for `repository` if it does not exist.
+pub async fn missing_repository(client: &LakeFSClient, repository: &str) -> Option<String> {
+    match client.get_repository(repository).await {
+        Err(lakefs_api::Error::NotFound(_)) => {}
+        _ => return None,
+    }
+    let names = repository_names(client).await.unwrap_or_default();
+    Some(match closest_names(repository, names).as_slice() {
+        [] => format!("repository '{}' does not exist; `lakectl repo list` shows the ones that do", repository),
+        close => format!("repository '{}' does not exist; did you mean {}?", repository, quoted(close)),
+    })
+}
+
+/// Suggestion for `reference` if it names no branch, tag or commit in
+/// `repository` but is close to a branch that does exist.
+pub async fn missing_reference(client: &LakeFSClient, repository: &str, reference: &str) -> Option<String> {
+    // `main~2`, `main@` and `main@<time>` stand or fall with `main`
+    let base = reference.split(['~', '^', '@']).next().unwrap_or(reference);
+    if base.is_empty() || client.get_branch(repository, base).await.is_ok() {
+        return None;
+    }
+    match client.get_commit(repository, base).await {
+        Err(lakefs_api::Error::NotFound(_)) => {}
+        _ => return None,
+    }
+    let names = branch_names(client, repository).await.ok()?;
+    match closest_names(base, names).as_slice() {
+        [] => None,
+        close => Some(format!("'{}' is not a branch of {}; did you mean {}?", base, repository, quoted(close))),
+    }
+}
+
+fn quoted(names: &[String]) -> String {
+    let names: Vec<String> = names.iter().map(|name| format!("'{}'", name)).collect();
+    names.join(" or ")
+}
+
+async fn repository_names(client: &LakeFSClient) -> lakefs_api::Result<Vec<String>> {
+    let mut names = Vec::new();
+    let mut params = PaginationParams::default();
+    loop {
+        let page = client.list_repositories_with(&params).await?;
+        names.extend(page.results.into_iter().map(|r| r.id));
+
+        match page.pagination.next_offset {
+            Some(next) if page.pagination.has_more => params.after = Some(next),
+            _ => return Ok(names),
+        }
+    }
+}
+
+async fn branch_names(client: &LakeFSClient, repository: &str) -> lakefs_api::Result<Vec<String>> {
+    let mut names = Vec::new();
+    let mut params = PaginationParams::default();
+    loop {
+        let page = client.list_branches_with(repository, &params).await?;
+        names.extend(page.results.into_iter().map(|b| b.id));
+
+        match page.pagination.next_offset {
+            Some(next) if page.pagination.has_more => params.after = Some(next),
+            _ => return Ok(names),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lakefs_test_utils::scenario::MockLakeFS;
+
+    #[test]
+    fn test_closest_names() {
+        let names = || ["main", "dev", "develop", "feature-login"].map(String::from);
+        assert_eq!(closest_names("mian", names()), ["main"]);
+        assert_eq!(closest_names("devel", names()), ["dev", "develop"]);
+        assert_eq!(closest_names("login", names()), ["feature-login"]);
+        assert!(closest_names("release", names()).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_missing_reference() {
+        let server = MockLakeFS::new()
+            .with_repo("repo")
+            .with_branch("main")
+            .with_branch("develop")
+            .start()
+            .await;
+        let client = LakeFSClient::new(server.uri(), "Bearer test-token");
+
+        let hint = missing_reference(&client, "repo", "mian").await;
+        assert_eq!(hint.as_deref(), Some("'mian' is not a branch of repo; did you mean 'main'?"));
+        let hint = missing_reference(&client, "repo", "devlop@2024-05-01").await;
+        assert_eq!(hint.as_deref(), Some("'devlop' is not a branch of repo; did you mean 'develop'?"));
+
+        assert_eq!(missing_reference(&client, "repo", "main~1").await, None);
+        assert_eq!(missing_reference(&client, "repo", "a1b2c3d4").await, None);
+    }
+}
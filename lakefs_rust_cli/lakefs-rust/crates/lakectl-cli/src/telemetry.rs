@@ -0,0 +1,114 @@
+//! Logging and, when configured, OpenTelemetry trace export.
+
+use crate::config::TelemetryConfig;
+use anyhow::{Context, Result};
+use lakefs_api::ClientMiddleware;
+use opentelemetry::propagation::Injector;
+use opentelemetry::trace::TracerProvider;
+use opentelemetry_otlp::{SpanExporter, WithExportConfig, WithHttpConfig};
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry_sdk::trace::SdkTracerProvider;
+use opentelemetry_sdk::Resource;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use tracing::level_filters::LevelFilter;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::Layer;
+
+/// Keeps the trace exporter alive; `shutdown` flushes spans not yet sent.
+pub struct Telemetry {
+    provider: Option<SdkTracerProvider>,
+}
+
+impl Telemetry {
+    pub fn shutdown(self) {
+        if let Some(provider) = self.provider {
+            if let Err(e) = provider.shutdown() {
+                eprintln!("Failed to export traces: {}", e);
+            }
+        }
+    }
+}
+
+/// Install the global subscriber: log events at INFO and above and, with a
+/// `telemetry` section, export spans over OTLP/HTTP.
+pub fn init(config: Option<&TelemetryConfig>) -> Result<Telemetry> {
+    // Logs go to stderr, keeping stdout for output such as `fs download -`
+    let fmt = tracing_subscriber::fmt::layer()
+        .with_writer(std::io::stderr)
+        .with_filter(LevelFilter::INFO);
+    let Some(config) = config else {
+        tracing_subscriber::registry().with(fmt).init();
+        return Ok(Telemetry { provider: None });
+    };
+
+    let mut exporter = SpanExporter::builder()
+        .with_http()
+        .with_headers(config.headers.clone());
+    if let Some(endpoint) = &config.endpoint {
+        exporter = exporter.with_endpoint(endpoint);
+    }
+    let exporter = exporter.build().context("Failed to set up trace export")?;
+    let provider = SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(Resource::builder().with_service_name(config.service_name.clone()).build())
+        .build();
+    opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
+
+    let otel = tracing_opentelemetry::layer()
+        .with_tracer(provider.tracer("lakectl"))
+        .with_filter(LevelFilter::INFO);
+    tracing_subscriber::registry().with(fmt).with(otel).init();
+    Ok(Telemetry { provider: Some(provider) })
+}
+
+/// Adds a W3C `traceparent` header for the current span to every API
+/// request, so server-side traces join the caller's.
+pub struct TraceContextMiddleware;
+
+impl ClientMiddleware for TraceContextMiddleware {
+    fn on_request(&self, request: &mut reqwest::Request) -> lakefs_api::Result<()> {
+        let context = tracing::Span::current().context();
+        opentelemetry::global::get_text_map_propagator(|propagator| {
+            propagator.inject_context(&context, &mut HeaderInjector(request.headers_mut()));
+        });
+        Ok(())
+    }
+}
+
+struct HeaderInjector<'a>(&'a mut HeaderMap);
+
+impl Injector for HeaderInjector<'_> {
+    fn set(&mut self, key: &str, value: String) {
+        if let (Ok(name), Ok(value)) = (HeaderName::from_bytes(key.as_bytes()), HeaderValue::from_str(&value)) {
+            self.0.insert(name, value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trace_context_middleware_sets_traceparent() {
+        let provider = SdkTracerProvider::builder().build();
+        opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
+        let subscriber = tracing_subscriber::registry()
+            .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("test")));
+
+        let mut request = reqwest::Client::new()
+            .get("http://localhost/api/v1/repositories")
+            .build()
+            .unwrap();
+        tracing::subscriber::with_default(subscriber, || {
+            let _span = tracing::info_span!("lakectl").entered();
+            TraceContextMiddleware.on_request(&mut request).unwrap();
+        });
+
+        let traceparent = request.headers()["traceparent"].to_str().unwrap();
+        assert!(traceparent.starts_with("00-"), "{}", traceparent);
+        assert_eq!(traceparent.split('-').count(), 4);
+    }
+}
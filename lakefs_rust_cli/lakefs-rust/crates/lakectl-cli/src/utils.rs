@@ -1,6 +1,6 @@
 use colored::Colorize;
 use human_bytes::human_bytes;
-use lakefs_api::LakeFSUri;
+use lakefs_api::{LakeFSApi, LakeFSUri, RefKind};
 use std::str::FromStr;
 
 pub fn parse_uri(uri: &str) -> anyhow::Result<LakeFSUri> {
@@ -8,6 +8,97 @@ pub fn parse_uri(uri: &str) -> anyhow::Result<LakeFSUri> {
         .map_err(|e| anyhow::anyhow!("Invalid URI '{}': {}", uri, e))
 }
 
+/// Parse a URI and resolve a `ref@time` reference in it to the commit it
+/// names, so every command accepts `lakefs://repo/main@2024-05-01T00:00:00Z/path`.
+pub async fn resolve_uri(client: &dyn LakeFSApi, uri: &str) -> anyhow::Result<LakeFSUri> {
+    let parsed = parse_uri(uri)?;
+    lakefs_api::resolve_uri(client, parsed)
+        .await
+        .map_err(|e| anyhow::anyhow!("Cannot resolve '{}': {}", uri, e))
+}
+
+/// Refuse to write to `uri` unless its reference is a branch, saying what
+/// it is instead. Tags and commits cannot change.
+pub async fn require_branch(client: &dyn LakeFSApi, uri: &LakeFSUri) -> anyhow::Result<()> {
+    let resolved = lakefs_api::resolve_ref(client, &uri.repository, &uri.reference).await?;
+    match resolved.kind {
+        RefKind::Branch => Ok(()),
+        RefKind::Tag => anyhow::bail!(
+            "Cannot write to {}: '{}' is a tag of commit {}, not a branch",
+            uri, uri.reference, resolved.commit_id
+        ),
+        RefKind::Commit => anyhow::bail!(
+            "Cannot write to {}: '{}' is commit {}, not a branch",
+            uri, uri.reference, resolved.commit_id
+        ),
+    }
+}
+
+/// Parse a repository URI (`lakefs://repo`), ignoring any trailing ref or path.
+pub fn parse_repository_uri(uri: &str) -> anyhow::Result<String> {
+    let repository = uri
+        .strip_prefix("lakefs://")
+        .and_then(|rest| rest.split('/').next())
+        .filter(|repo| !repo.is_empty())
+        .ok_or_else(|| anyhow::anyhow!("Invalid repository URI '{}': expected lakefs://<repository>", uri))?;
+    Ok(repository.to_string())
+}
+
+/// Parse a duration like `90s`, `30m`, `1h` or `7d`. A bare number is seconds.
+pub fn parse_duration(input: &str) -> anyhow::Result<std::time::Duration> {
+    let input = input.trim();
+    let split = input.find(|c: char| !c.is_ascii_digit()).unwrap_or(input.len());
+    let (value, unit) = input.split_at(split);
+    
+    let value: u64 = value
+        .parse()
+        .map_err(|_| anyhow::anyhow!("Invalid duration '{}'", input))?;
+    let multiplier = match unit {
+        "" | "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        _ => anyhow::bail!("Invalid duration unit in '{}': expected s, m, h or d", input),
+    };
+    
+    let seconds = value
+        .checked_mul(multiplier)
+        .ok_or_else(|| anyhow::anyhow!("Invalid duration '{}': too long", input))?;
+    Ok(std::time::Duration::from_secs(seconds))
+}
+
+/// Parse a byte size like `512`, `500K`, `10MB` or `1.5G` (binary multiples).
+pub fn parse_size(input: &str) -> anyhow::Result<u64> {
+    let input = input.trim();
+    let split = input
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(input.len());
+    let (value, unit) = input.split_at(split);
+    
+    let value: f64 = value
+        .parse()
+        .map_err(|_| anyhow::anyhow!("Invalid size '{}'", input))?;
+    let multiplier: u64 = match unit.trim().to_ascii_uppercase().as_str() {
+        "" | "B" => 1,
+        "K" | "KB" | "KIB" => 1 << 10,
+        "M" | "MB" | "MIB" => 1 << 20,
+        "G" | "GB" | "GIB" => 1 << 30,
+        "T" | "TB" | "TIB" => 1 << 40,
+        _ => anyhow::bail!("Invalid size unit in '{}': expected B, K, M, G or T", input),
+    };
+    
+    Ok((value * multiplier as f64) as u64)
+}
+
+/// Parse a `--limit-rate` in bytes per second, which must be at least one
+/// byte: a smaller limit would stall the transfer rather than slow it.
+pub fn parse_rate(input: &str) -> anyhow::Result<u64> {
+    match parse_size(input)? {
+        0 => anyhow::bail!("Invalid rate '{}': must be at least 1 byte per second", input),
+        rate => Ok(rate),
+    }
+}
+
 pub fn format_size(bytes: i64) -> String {
     human_bytes(bytes as f64)
 }
@@ -21,6 +112,20 @@ pub fn format_diff_type(diff_type: &str) -> String {
     }
 }
 
+/// Parse `KEY=VALUE` pairs, as given to repeatable `--meta` flags.
+pub fn parse_metadata(entries: &[String]) -> anyhow::Result<std::collections::HashMap<String, String>> {
+    entries
+        .iter()
+        .map(|entry| {
+            let (key, value) = entry
+                .split_once('=')
+                .filter(|(key, _)| !key.is_empty())
+                .ok_or_else(|| anyhow::anyhow!("Invalid metadata '{}': expected KEY=VALUE", entry))?;
+            Ok((key.to_string(), value.to_string()))
+        })
+        .collect()
+}
+
 pub fn confirm(prompt: &str) -> anyhow::Result<bool> {
     use std::io::{self, Write};
     
@@ -33,6 +138,22 @@ pub fn confirm(prompt: &str) -> anyhow::Result<bool> {
     Ok(input.trim().to_lowercase() == "y")
 }
 
+/// Levenshtein distance between `a` and `b`.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substituted = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substituted.min(row[j] + 1).min(row[j + 1] + 1);
+        }
+    }
+    row[b.len()]
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -51,6 +172,52 @@ mod tests {
         assert!(parse_uri("lakefs://").is_err());
     }
 
+    #[test]
+    fn test_parse_repository_uri() {
+        assert_eq!(parse_repository_uri("lakefs://repo").unwrap(), "repo");
+        assert_eq!(parse_repository_uri("lakefs://repo/main/path").unwrap(), "repo");
+        assert!(parse_repository_uri("lakefs://").is_err());
+        assert!(parse_repository_uri("s3://bucket").is_err());
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("90").unwrap().as_secs(), 90);
+        assert_eq!(parse_duration("30m").unwrap().as_secs(), 1800);
+        assert_eq!(parse_duration("1h").unwrap().as_secs(), 3600);
+        assert_eq!(parse_duration("7d").unwrap().as_secs(), 604800);
+        assert!(parse_duration("1w").is_err());
+        assert!(parse_duration("h").is_err());
+        assert!(parse_duration("999999999999999d").unwrap_err().to_string().starts_with("Invalid duration"));
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("512").unwrap(), 512);
+        assert_eq!(parse_size("500K").unwrap(), 500 * 1024);
+        assert_eq!(parse_size("10MB").unwrap(), 10 * 1024 * 1024);
+        assert_eq!(parse_size("1.5g").unwrap(), 1536 * 1024 * 1024);
+        assert!(parse_size("10XB").is_err());
+        assert!(parse_size("MB").is_err());
+    }
+
+    #[test]
+    fn test_parse_rate() {
+        assert_eq!(parse_rate("500K").unwrap(), 500 * 1024);
+        assert_eq!(parse_rate("1").unwrap(), 1);
+        assert!(parse_rate("0").is_err());
+        assert!(parse_rate("0.0001K").is_err());
+    }
+
+    #[test]
+    fn test_parse_metadata() {
+        let metadata = parse_metadata(&["owner=data-team".to_string(), "query=a=b".to_string()]).unwrap();
+        assert_eq!(metadata["owner"], "data-team");
+        assert_eq!(metadata["query"], "a=b");
+        assert!(parse_metadata(&["novalue".to_string()]).is_err());
+        assert!(parse_metadata(&["=value".to_string()]).is_err());
+    }
+
     #[test]
     fn test_format_size() {
         // The human_bytes function returns strings with specific formatting
@@ -69,4 +236,36 @@ mod tests {
         let result = format_diff_type("added");
         assert!(!result.is_empty());
     }
+
+    #[tokio::test]
+    async fn test_require_branch() {
+        use lakefs_test_utils::scenario::MockLakeFS;
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, ResponseTemplate};
+
+        let server = MockLakeFS::new()
+            .with_repo("repo")
+            .with_branch("main")
+            .start()
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repositories/repo/tags/v1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({"id": "v1", "commit_id": "c1"})))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repositories/repo/commits/c1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(lakefs_test_utils::fixtures::test_commit()))
+            .mount(&server)
+            .await;
+        let client = lakefs_api::LakeFSClient::new(server.uri(), "Bearer test-token");
+
+        require_branch(&client, &parse_uri("lakefs://repo/main/a.csv").unwrap()).await.unwrap();
+        let error = require_branch(&client, &parse_uri("lakefs://repo/v1/a.csv").unwrap()).await.unwrap_err();
+        assert_eq!(error.to_string(), "Cannot write to lakefs://repo/v1/a.csv: 'v1' is a tag of commit c1, not a branch");
+        let error = require_branch(&client, &parse_uri("lakefs://repo/c1/a.csv").unwrap()).await.unwrap_err();
+        assert!(error.to_string().contains("'c1' is commit abc123, not a branch"));
+        let error = require_branch(&client, &parse_uri("lakefs://repo/nope/a.csv").unwrap()).await.unwrap_err();
+        assert!(error.to_string().contains("no branch, tag or commit named 'nope'"));
+    }
 }
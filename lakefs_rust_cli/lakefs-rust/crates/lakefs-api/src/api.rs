@@ -0,0 +1,413 @@
+use crate::{
+    client::LakeFSClient,
+    download::ObjectRange,
+    error::Result,
+    models::*,
+};
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::Stream;
+use std::collections::HashMap;
+use std::pin::Pin;
+
+/// Streaming request body accepted by `LakeFSApi::upload_object_stream`.
+pub type UploadStream = Pin<Box<dyn Stream<Item = std::io::Result<Bytes>> + Send + Sync>>;
+
+/// Streaming response body returned by `LakeFSApi::download_object_stream`.
+pub type DownloadStream = Pin<Box<dyn Stream<Item = Result<Bytes>> + Send>>;
+
+/// The lakeFS operations offered by `LakeFSClient`, as an object-safe trait.
+///
+/// Code that takes `Arc<dyn LakeFSApi>` instead of a concrete client can be
+/// unit tested against a hand-written or `mockall` mock, without an HTTP
+/// server. The `mock` feature exports such a mock as `MockLakeFSApi`.
+#[cfg_attr(any(test, feature = "mock"), mockall::automock)]
+#[async_trait]
+pub trait LakeFSApi: Send + Sync {
+    // Config operations
+    async fn get_storage_config(&self) -> Result<StorageConfig>;
+
+    // Repository operations
+    async fn create_repository(&self, name: &str, storage_namespace: &str) -> Result<Repository>;
+    async fn list_repositories_with(&self, params: &PaginationParams) -> Result<Pagination<Repository>>;
+    async fn get_repository(&self, repository: &str) -> Result<Repository>;
+    async fn delete_repository(&self, repository: &str) -> Result<()>;
+
+    // Branch operations
+    async fn create_branch(&self, repository: &str, branch: &str, source: &str) -> Result<Branch>;
+    async fn list_branches_with(&self, repository: &str, params: &PaginationParams) -> Result<Pagination<Branch>>;
+    async fn get_branch(&self, repository: &str, branch: &str) -> Result<Branch>;
+    async fn get_tag(&self, repository: &str, tag: &str) -> Result<Tag>;
+    async fn delete_branch(&self, repository: &str, branch: &str) -> Result<()>;
+    async fn diff_uncommitted(&self, repository: &str, branch: &str, params: &PaginationParams) -> Result<Pagination<Diff>>;
+    async fn reset_uncommitted(&self, repository: &str, branch: &str) -> Result<()>;
+    async fn reset_object(&self, repository: &str, branch: &str, path: &str) -> Result<()>;
+    async fn hard_reset_branch(&self, repository: &str, branch: &str, reference: &str) -> Result<()>;
+
+    // Commit operations
+    async fn commit(&self, repository: &str, branch: &str, message: &str) -> Result<Commit>;
+    async fn commit_with_metadata(
+        &self,
+        repository: &str,
+        branch: &str,
+        message: &str,
+        metadata: &HashMap<String, String>,
+    ) -> Result<Commit>;
+    async fn amend_commit<'a>(
+        &self,
+        repository: &str,
+        branch: &str,
+        expected_head: Option<&'a str>,
+        message: Option<&'a str>,
+        metadata: Option<&'a HashMap<String, String>>,
+    ) -> Result<Commit>;
+    async fn get_commit(&self, repository: &str, commit_id: &str) -> Result<Commit>;
+    async fn log_commits_with(&self, repository: &str, branch: &str, params: &PaginationParams) -> Result<Pagination<Commit>>;
+
+    // Object operations
+    async fn list_objects(&self, repository: &str, reference: &str, options: &ListObjectsOptions) -> Result<Pagination<ObjectStats>>;
+    async fn list_objects_with(&self, repository: &str, reference: &str, params: &PaginationParams) -> Result<Pagination<ObjectStats>>;
+    async fn get_object(&self, repository: &str, reference: &str, path: &str) -> Result<ObjectStats>;
+    async fn stat_objects(
+        &self,
+        repository: &str,
+        reference: &str,
+        paths: &[String],
+        concurrency: usize,
+    ) -> Vec<(String, Result<ObjectStats>)>;
+    async fn get_object_presigned(&self, repository: &str, reference: &str, path: &str) -> Result<ObjectStats>;
+    async fn get_presigned_upload_location(&self, repository: &str, branch: &str, path: &str) -> Result<StagingLocation>;
+    async fn upload_object(&self, repository: &str, branch: &str, path: &str, content: Bytes) -> Result<ObjectStats>;
+    async fn upload_object_stream(&self, repository: &str, branch: &str, path: &str, content: UploadStream) -> Result<ObjectStats>;
+    async fn upload_object_with(
+        &self,
+        repository: &str,
+        branch: &str,
+        path: &str,
+        content: Bytes,
+        options: &UploadOptions,
+    ) -> Result<ObjectStats>;
+    async fn upload_object_stream_with(
+        &self,
+        repository: &str,
+        branch: &str,
+        path: &str,
+        content: UploadStream,
+        options: &UploadOptions,
+    ) -> Result<ObjectStats>;
+    async fn download_object_stream(&self, repository: &str, reference: &str, path: &str) -> Result<DownloadStream>;
+    async fn download_object_stream_if_none_match(
+        &self,
+        repository: &str,
+        reference: &str,
+        path: &str,
+        etag: &Checksum,
+    ) -> Result<Option<DownloadStream>>;
+    async fn download_object(&self, repository: &str, reference: &str, path: &str) -> Result<Bytes>;
+    async fn get_object_range<'a>(
+        &self,
+        repository: &str,
+        reference: &str,
+        path: &str,
+        offset: u64,
+        if_none_match: Option<&'a Checksum>,
+    ) -> Result<Option<ObjectRange>>;
+    async fn stage_object(&self, repository: &str, branch: &str, path: &str, object: &ObjectStage) -> Result<ObjectStats>;
+    async fn copy_object(&self, repository: &str, branch: &str, dest_path: &str, src_ref: &str, src_path: &str) -> Result<ObjectStats>;
+    async fn delete_object(&self, repository: &str, branch: &str, path: &str) -> Result<()>;
+    async fn delete_object_if<'a>(&self, repository: &str, branch: &str, path: &str, precondition: Option<&'a Precondition>) -> Result<()>;
+
+    // Diff and merge operations
+    async fn diff(&self, repository: &str, left_ref: &str, right_ref: &str) -> Result<DiffResult>;
+    async fn diff_with(
+        &self,
+        repository: &str,
+        left_ref: &str,
+        right_ref: &str,
+        params: &PaginationParams,
+    ) -> Result<Pagination<Diff>>;
+    async fn merge(&self, repository: &str, source_ref: &str, destination_branch: &str) -> Result<MergeResult>;
+    async fn merge_with(
+        &self,
+        repository: &str,
+        source_ref: &str,
+        destination_branch: &str,
+        options: &MergeOptions,
+    ) -> Result<MergeResult>;
+
+    // Refs operations
+    async fn dump_refs(&self, repository: &str) -> Result<RefsDump>;
+    async fn restore_refs(&self, repository: &str, manifest: &RefsDump) -> Result<()>;
+
+    // Metadata operations
+    async fn get_meta_range(&self, repository: &str, meta_range_id: &str) -> Result<StorageUri>;
+    async fn get_range(&self, repository: &str, range_id: &str) -> Result<StorageUri>;
+}
+
+#[async_trait]
+impl LakeFSApi for LakeFSClient {
+    async fn get_storage_config(&self) -> Result<StorageConfig> {
+        LakeFSClient::get_storage_config(self).await
+    }
+
+    async fn create_repository(&self, name: &str, storage_namespace: &str) -> Result<Repository> {
+        LakeFSClient::create_repository(self, name, storage_namespace).await
+    }
+
+    async fn list_repositories_with(&self, params: &PaginationParams) -> Result<Pagination<Repository>> {
+        LakeFSClient::list_repositories_with(self, params).await
+    }
+
+    async fn get_repository(&self, repository: &str) -> Result<Repository> {
+        LakeFSClient::get_repository(self, repository).await
+    }
+
+    async fn delete_repository(&self, repository: &str) -> Result<()> {
+        LakeFSClient::delete_repository(self, repository).await
+    }
+
+    async fn create_branch(&self, repository: &str, branch: &str, source: &str) -> Result<Branch> {
+        LakeFSClient::create_branch(self, repository, branch, source).await
+    }
+
+    async fn list_branches_with(&self, repository: &str, params: &PaginationParams) -> Result<Pagination<Branch>> {
+        LakeFSClient::list_branches_with(self, repository, params).await
+    }
+
+    async fn get_branch(&self, repository: &str, branch: &str) -> Result<Branch> {
+        LakeFSClient::get_branch(self, repository, branch).await
+    }
+
+    async fn get_tag(&self, repository: &str, tag: &str) -> Result<Tag> {
+        LakeFSClient::get_tag(self, repository, tag).await
+    }
+
+    async fn delete_branch(&self, repository: &str, branch: &str) -> Result<()> {
+        LakeFSClient::delete_branch(self, repository, branch).await
+    }
+
+    async fn diff_uncommitted(&self, repository: &str, branch: &str, params: &PaginationParams) -> Result<Pagination<Diff>> {
+        LakeFSClient::diff_uncommitted(self, repository, branch, params).await
+    }
+
+    async fn reset_uncommitted(&self, repository: &str, branch: &str) -> Result<()> {
+        LakeFSClient::reset_uncommitted(self, repository, branch).await
+    }
+
+    async fn reset_object(&self, repository: &str, branch: &str, path: &str) -> Result<()> {
+        LakeFSClient::reset_object(self, repository, branch, path).await
+    }
+
+    async fn hard_reset_branch(&self, repository: &str, branch: &str, reference: &str) -> Result<()> {
+        LakeFSClient::hard_reset_branch(self, repository, branch, reference).await
+    }
+
+    async fn commit(&self, repository: &str, branch: &str, message: &str) -> Result<Commit> {
+        LakeFSClient::commit(self, repository, branch, message).await
+    }
+
+    async fn commit_with_metadata(
+        &self,
+        repository: &str,
+        branch: &str,
+        message: &str,
+        metadata: &HashMap<String, String>,
+    ) -> Result<Commit> {
+        LakeFSClient::commit_with_metadata(self, repository, branch, message, metadata).await
+    }
+
+    async fn amend_commit<'a>(
+        &self,
+        repository: &str,
+        branch: &str,
+        expected_head: Option<&'a str>,
+        message: Option<&'a str>,
+        metadata: Option<&'a HashMap<String, String>>,
+    ) -> Result<Commit> {
+        LakeFSClient::amend_commit(self, repository, branch, expected_head, message, metadata).await
+    }
+
+    async fn get_commit(&self, repository: &str, commit_id: &str) -> Result<Commit> {
+        LakeFSClient::get_commit(self, repository, commit_id).await
+    }
+
+    async fn log_commits_with(&self, repository: &str, branch: &str, params: &PaginationParams) -> Result<Pagination<Commit>> {
+        LakeFSClient::log_commits_with(self, repository, branch, params).await
+    }
+
+    async fn list_objects(&self, repository: &str, reference: &str, options: &ListObjectsOptions) -> Result<Pagination<ObjectStats>> {
+        LakeFSClient::list_objects(self, repository, reference, options).await
+    }
+
+    async fn list_objects_with(&self, repository: &str, reference: &str, params: &PaginationParams) -> Result<Pagination<ObjectStats>> {
+        LakeFSClient::list_objects_with(self, repository, reference, params).await
+    }
+
+    async fn get_object(&self, repository: &str, reference: &str, path: &str) -> Result<ObjectStats> {
+        LakeFSClient::get_object(self, repository, reference, path).await
+    }
+
+    async fn stat_objects(
+        &self,
+        repository: &str,
+        reference: &str,
+        paths: &[String],
+        concurrency: usize,
+    ) -> Vec<(String, Result<ObjectStats>)> {
+        LakeFSClient::stat_objects(self, repository, reference, paths, concurrency).await
+    }
+
+    async fn get_object_presigned(&self, repository: &str, reference: &str, path: &str) -> Result<ObjectStats> {
+        LakeFSClient::get_object_presigned(self, repository, reference, path).await
+    }
+
+    async fn get_presigned_upload_location(&self, repository: &str, branch: &str, path: &str) -> Result<StagingLocation> {
+        LakeFSClient::get_presigned_upload_location(self, repository, branch, path).await
+    }
+
+    async fn upload_object(&self, repository: &str, branch: &str, path: &str, content: Bytes) -> Result<ObjectStats> {
+        LakeFSClient::upload_object(self, repository, branch, path, content).await
+    }
+
+    async fn upload_object_stream(&self, repository: &str, branch: &str, path: &str, content: UploadStream) -> Result<ObjectStats> {
+        LakeFSClient::upload_object_stream(self, repository, branch, path, content).await
+    }
+
+    async fn upload_object_with(
+        &self,
+        repository: &str,
+        branch: &str,
+        path: &str,
+        content: Bytes,
+        options: &UploadOptions,
+    ) -> Result<ObjectStats> {
+        LakeFSClient::upload_object_with(self, repository, branch, path, content, options).await
+    }
+
+    async fn upload_object_stream_with(
+        &self,
+        repository: &str,
+        branch: &str,
+        path: &str,
+        content: UploadStream,
+        options: &UploadOptions,
+    ) -> Result<ObjectStats> {
+        LakeFSClient::upload_object_stream_with(self, repository, branch, path, content, options).await
+    }
+
+    async fn download_object_stream(&self, repository: &str, reference: &str, path: &str) -> Result<DownloadStream> {
+        let stream = LakeFSClient::download_object_stream(self, repository, reference, path).await?;
+        Ok(Box::pin(stream))
+    }
+
+    async fn download_object_stream_if_none_match(
+        &self,
+        repository: &str,
+        reference: &str,
+        path: &str,
+        etag: &Checksum,
+    ) -> Result<Option<DownloadStream>> {
+        let stream = LakeFSClient::download_object_stream_if_none_match(self, repository, reference, path, etag).await?;
+        Ok(stream.map(|stream| Box::pin(stream) as DownloadStream))
+    }
+
+    async fn download_object(&self, repository: &str, reference: &str, path: &str) -> Result<Bytes> {
+        LakeFSClient::download_object(self, repository, reference, path).await
+    }
+
+    async fn get_object_range<'a>(
+        &self,
+        repository: &str,
+        reference: &str,
+        path: &str,
+        offset: u64,
+        if_none_match: Option<&'a Checksum>,
+    ) -> Result<Option<ObjectRange>> {
+        LakeFSClient::get_object_range(self, repository, reference, path, offset, if_none_match).await
+    }
+
+    async fn stage_object(&self, repository: &str, branch: &str, path: &str, object: &ObjectStage) -> Result<ObjectStats> {
+        LakeFSClient::stage_object(self, repository, branch, path, object).await
+    }
+
+    async fn copy_object(&self, repository: &str, branch: &str, dest_path: &str, src_ref: &str, src_path: &str) -> Result<ObjectStats> {
+        LakeFSClient::copy_object(self, repository, branch, dest_path, src_ref, src_path).await
+    }
+
+    async fn delete_object(&self, repository: &str, branch: &str, path: &str) -> Result<()> {
+        LakeFSClient::delete_object(self, repository, branch, path).await
+    }
+
+    async fn delete_object_if<'a>(&self, repository: &str, branch: &str, path: &str, precondition: Option<&'a Precondition>) -> Result<()> {
+        LakeFSClient::delete_object_if(self, repository, branch, path, precondition).await
+    }
+
+    async fn diff(&self, repository: &str, left_ref: &str, right_ref: &str) -> Result<DiffResult> {
+        LakeFSClient::diff(self, repository, left_ref, right_ref).await
+    }
+
+    async fn diff_with(
+        &self,
+        repository: &str,
+        left_ref: &str,
+        right_ref: &str,
+        params: &PaginationParams,
+    ) -> Result<Pagination<Diff>> {
+        LakeFSClient::diff_with(self, repository, left_ref, right_ref, params).await
+    }
+
+    async fn merge(&self, repository: &str, source_ref: &str, destination_branch: &str) -> Result<MergeResult> {
+        LakeFSClient::merge(self, repository, source_ref, destination_branch).await
+    }
+
+    async fn merge_with(
+        &self,
+        repository: &str,
+        source_ref: &str,
+        destination_branch: &str,
+        options: &MergeOptions,
+    ) -> Result<MergeResult> {
+        LakeFSClient::merge_with(self, repository, source_ref, destination_branch, options).await
+    }
+
+    async fn dump_refs(&self, repository: &str) -> Result<RefsDump> {
+        LakeFSClient::dump_refs(self, repository).await
+    }
+
+    async fn restore_refs(&self, repository: &str, manifest: &RefsDump) -> Result<()> {
+        LakeFSClient::restore_refs(self, repository, manifest).await
+    }
+
+    async fn get_meta_range(&self, repository: &str, meta_range_id: &str) -> Result<StorageUri> {
+        LakeFSClient::get_meta_range(self, repository, meta_range_id).await
+    }
+
+    async fn get_range(&self, repository: &str, range_id: &str) -> Result<StorageUri> {
+        LakeFSClient::get_range(self, repository, range_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+    use std::sync::Arc;
+
+    async fn head_commit(api: &dyn LakeFSApi, repository: &str, branch: &str) -> Result<String> {
+        Ok(api.get_branch(repository, branch).await?.commit_id)
+    }
+
+    #[tokio::test]
+    async fn test_mock_api() {
+        let mut mock = MockLakeFSApi::new();
+        mock.expect_get_branch()
+            .withf(|repository, branch| repository == "test-repo" && branch == "main")
+            .returning(|_, branch| Ok(Branch { id: branch.to_string(), commit_id: "abc123".to_string() }));
+        mock.expect_get_branch()
+            .returning(|_, branch| Err(Error::NotFound(branch.to_string())));
+
+        let api: Arc<dyn LakeFSApi> = Arc::new(mock);
+        assert_eq!(head_commit(api.as_ref(), "test-repo", "main").await.unwrap(), "abc123");
+        assert!(matches!(head_commit(api.as_ref(), "test-repo", "dev").await, Err(Error::NotFound(_))));
+    }
+}
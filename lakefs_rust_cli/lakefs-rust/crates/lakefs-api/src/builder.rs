@@ -0,0 +1,287 @@
+use crate::{
+    cassette::Cassette,
+    response_cache::ResponseCache,
+    client::{default_user_agent, LakeFSClient},
+    error::{Error, Result},
+    middleware::ClientMiddleware,
+    retry::RetryPolicy,
+};
+use lakefs_auth::AuthProvider;
+use reqwest::{header::HeaderValue, Client};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Settings for [`LakeFSClient::from_provider`], everything about a client
+/// other than its server and credentials.
+#[derive(Clone, Default)]
+pub struct ClientConfig {
+    /// Replaces the default `lakectl-rust/<version> (<os>; <arch>)` User-Agent
+    pub user_agent: Option<String>,
+    pub timeout: Option<Duration>,
+    pub connect_timeout: Option<Duration>,
+    pub retry_policy: RetryPolicy,
+    /// Run in order on every request
+    pub middleware: Vec<Arc<dyn ClientMiddleware>>,
+    pub cassette: Option<Arc<Cassette>>,
+    pub response_cache: Option<Arc<ResponseCache>>,
+}
+
+/// Fluent constructor for `LakeFSClient`.
+///
+/// ```no_run
+/// # use lakefs_api::{LakeFSClient, RetryPolicy};
+/// # use std::time::Duration;
+/// let client = LakeFSClient::builder()
+///     .base_url("http://localhost:8000/api/v1")
+///     .auth_header("Basic ...")
+///     .timeout(Duration::from_secs(30))
+///     .retry_policy(RetryPolicy::exponential(3))
+///     .build()?;
+/// # Ok::<(), lakefs_api::Error>(())
+/// ```
+#[derive(Default)]
+pub struct LakeFSClientBuilder {
+    base_url: Option<String>,
+    auth_header: Option<String>,
+    auth_provider: Option<Arc<dyn AuthProvider>>,
+    timeout: Option<Duration>,
+    connect_timeout: Option<Duration>,
+    retry_policy: RetryPolicy,
+    user_agent: Option<String>,
+    middleware: Vec<Arc<dyn ClientMiddleware>>,
+    client: Option<Client>,
+    cassette: Option<Arc<Cassette>>,
+    response_cache: Option<Arc<ResponseCache>>,
+}
+
+impl LakeFSClientBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = Some(base_url.into());
+        self
+    }
+
+    /// Use a fixed `Authorization` header value for every request.
+    pub fn auth_header(mut self, auth_header: impl Into<String>) -> Self {
+        self.auth_header = Some(auth_header.into());
+        self
+    }
+
+    /// Resolve the `Authorization` header from a provider on every request,
+    /// so providers that refresh tokens keep working for long-lived clients.
+    pub fn auth_provider(mut self, provider: Arc<dyn AuthProvider>) -> Self {
+        self.auth_provider = Some(provider);
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = policy;
+        self
+    }
+
+    /// Override the default `lakectl-rust/<version> (<os>; <arch>)` User-Agent.
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// Register a hook that can modify outgoing requests and observe
+    /// responses. See `HeaderMiddleware` for adding fixed headers.
+    pub fn middleware(mut self, middleware: Arc<dyn ClientMiddleware>) -> Self {
+        self.middleware.push(middleware);
+        self
+    }
+
+    /// Use a preconfigured reqwest client. Timeouts and the user agent must
+    /// then be configured on that client instead.
+    pub fn reqwest_client(mut self, client: Client) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    /// Record interactions to, or replay them from, a cassette file.
+    pub fn cassette(mut self, cassette: Arc<Cassette>) -> Self {
+        self.cassette = Some(cassette);
+        self
+    }
+
+    /// Serve stats, listings and downloads at commit IDs from `cache` once
+    /// fetched. Reads at branches and tags always go to the server.
+    pub fn response_cache(mut self, cache: Arc<ResponseCache>) -> Self {
+        self.response_cache = Some(cache);
+        self
+    }
+
+    /// Apply everything in `config`.
+    pub fn config(mut self, config: ClientConfig) -> Self {
+        self.user_agent = config.user_agent.or(self.user_agent);
+        self.timeout = config.timeout.or(self.timeout);
+        self.connect_timeout = config.connect_timeout.or(self.connect_timeout);
+        self.retry_policy = config.retry_policy;
+        self.middleware.extend(config.middleware);
+        self.cassette = config.cassette.or(self.cassette);
+        self.response_cache = config.response_cache.or(self.response_cache);
+        self
+    }
+
+    pub fn build(self) -> Result<LakeFSClient> {
+        let base_url = self.base_url
+            .ok_or_else(|| Error::Config("base_url is required".into()))?;
+        let base_url = base_url.trim_end_matches('/').to_string();
+
+        let parsed = url::Url::parse(&base_url)
+            .map_err(|e| Error::Config(format!("Invalid base_url '{}': {}", base_url, e)))?;
+        if parsed.scheme() != "http" && parsed.scheme() != "https" {
+            return Err(Error::Config(format!(
+                "Unsupported base_url scheme '{}': expected http or https",
+                parsed.scheme()
+            )));
+        }
+
+        if self.auth_header.is_some() && self.auth_provider.is_some() {
+            return Err(Error::Config(
+                "auth_header and auth_provider are mutually exclusive".into(),
+            ));
+        }
+
+        if let Some(header) = &self.auth_header {
+            HeaderValue::from_str(header)
+                .map_err(|_| Error::Config("auth_header is not a valid header value".into()))?;
+        }
+
+        for timeout in [self.timeout, self.connect_timeout].into_iter().flatten() {
+            if timeout.is_zero() {
+                return Err(Error::Config("timeouts must be greater than zero".into()));
+            }
+        }
+
+        let client = match self.client {
+            Some(client) => {
+                if self.timeout.is_some() || self.connect_timeout.is_some() || self.user_agent.is_some() {
+                    return Err(Error::Config(
+                        "timeout and user_agent cannot be combined with reqwest_client".into(),
+                    ));
+                }
+                client
+            }
+            None => {
+                let mut builder = Client::builder();
+                if let Some(timeout) = self.timeout {
+                    builder = builder.timeout(timeout);
+                }
+                if let Some(timeout) = self.connect_timeout {
+                    builder = builder.connect_timeout(timeout);
+                }
+                let user_agent = self.user_agent.unwrap_or_else(default_user_agent);
+                let value = HeaderValue::from_str(&user_agent)
+                    .map_err(|_| Error::Config("user_agent is not a valid header value".into()))?;
+                builder = builder.user_agent(value);
+                builder.build()?
+            }
+        };
+
+        Ok(LakeFSClient::from_parts(
+            client,
+            base_url,
+            self.auth_header.unwrap_or_default(),
+            self.auth_provider,
+            self.retry_policy,
+            self.middleware,
+            self.cassette,
+        ).with_response_cache(self.response_cache))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_requires_base_url() {
+        let result = LakeFSClientBuilder::new().auth_header("Bearer token").build();
+        assert!(matches!(result, Err(Error::Config(_))));
+    }
+
+    #[test]
+    fn test_build_rejects_invalid_url() {
+        let result = LakeFSClient::builder().base_url("not a url").build();
+        assert!(matches!(result, Err(Error::Config(_))));
+
+        let result = LakeFSClient::builder().base_url("ftp://lakefs/api/v1").build();
+        assert!(matches!(result, Err(Error::Config(_))));
+    }
+
+    #[test]
+    fn test_build_rejects_zero_timeout() {
+        let result = LakeFSClient::builder()
+            .base_url("http://localhost:8000")
+            .timeout(Duration::ZERO)
+            .build();
+        assert!(matches!(result, Err(Error::Config(_))));
+    }
+
+    #[test]
+    fn test_build_rejects_conflicting_auth() {
+        let provider = Arc::new(lakefs_auth::basic::BasicAuth::new("key".into(), "secret".into()));
+        let result = LakeFSClient::builder()
+            .base_url("http://localhost:8000")
+            .auth_header("Bearer token")
+            .auth_provider(provider)
+            .build();
+        assert!(matches!(result, Err(Error::Config(_))));
+    }
+
+    #[tokio::test]
+    async fn test_from_provider_asks_provider_per_request() {
+        use wiremock::matchers::{header, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repositories/repo"))
+            .and(header("authorization", "Basic a2V5OnNlY3JldA=="))
+            .and(header("user-agent", "pipeline/1.0"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "repo",
+                "creation_date": 1_700_000_000,
+                "default_branch": "main",
+                "storage_namespace": "s3://bucket/repo"
+            })))
+            .expect(2)
+            .mount(&server)
+            .await;
+        let provider = Arc::new(lakefs_auth::basic::BasicAuth::new("key".into(), "secret".into()));
+        let config = ClientConfig { user_agent: Some("pipeline/1.0".to_string()), ..Default::default() };
+
+        let client = LakeFSClient::from_provider(server.uri(), provider, config).unwrap();
+        client.get_repository("repo").await.unwrap();
+        client.clone().get_repository("repo").await.unwrap();
+    }
+
+    #[test]
+    fn test_build_trims_trailing_slash() {
+        let client = LakeFSClient::builder()
+            .base_url("http://localhost:8000/api/v1/")
+            .auth_header("Bearer token")
+            .timeout(Duration::from_secs(5))
+            .user_agent("test-agent/1.0")
+            .retry_policy(RetryPolicy::exponential(2))
+            .build()
+            .unwrap();
+
+        assert_eq!(client.base_url(), "http://localhost:8000/api/v1");
+    }
+}
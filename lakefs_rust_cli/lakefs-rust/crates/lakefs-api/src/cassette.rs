@@ -0,0 +1,237 @@
+use crate::error::{Error, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use bytes::Bytes;
+use reqwest::{Client, Request, Response};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Records API interactions to a JSON file, or plays them back without a
+/// server, VCR-style.
+///
+/// Interactions are matched on method and URL (relative to the client's base
+/// URL) in recording order, so repeated identical requests replay their
+/// responses in sequence. Request headers, including `Authorization`, are
+/// never written to the cassette.
+pub struct Cassette {
+    mode: Mode,
+    interactions: Mutex<Vec<Interaction>>,
+}
+
+enum Mode {
+    Record(PathBuf),
+    Replay { used: Mutex<Vec<bool>> },
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CassetteFile {
+    interactions: Vec<Interaction>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Interaction {
+    request: RecordedRequest,
+    response: RecordedResponse,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RecordedRequest {
+    method: String,
+    url: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RecordedResponse {
+    status: u16,
+    headers: BTreeMap<String, String>,
+    body: String,
+    /// Set when `body` holds base64 because the payload is not UTF-8
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    base64: bool,
+}
+
+impl Cassette {
+    /// Record every interaction to `path`, which is rewritten after each one.
+    pub fn record(path: impl Into<PathBuf>) -> Self {
+        Self {
+            mode: Mode::Record(path.into()),
+            interactions: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Answer requests from the interactions recorded in `path`.
+    pub fn replay(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)?;
+        let file: CassetteFile = serde_json::from_str(&content)
+            .map_err(|e| Error::Cassette(format!("Failed to parse {}: {}", path.display(), e)))?;
+
+        Ok(Self {
+            mode: Mode::Replay { used: Mutex::new(vec![false; file.interactions.len()]) },
+            interactions: Mutex::new(file.interactions),
+        })
+    }
+
+    /// Send `request` (record mode) or look up its recorded response (replay
+    /// mode). Transport errors are passed through untouched so the retry
+    /// policy can still act on them.
+    pub(crate) async fn execute(
+        &self,
+        client: &Client,
+        request: Request,
+        base_url: &str,
+    ) -> Result<reqwest::Result<Response>> {
+        let recorded = RecordedRequest {
+            method: request.method().to_string(),
+            url: relative_url(request.url().as_str(), base_url),
+        };
+
+        match &self.mode {
+            Mode::Replay { used } => self.replay_response(used, &recorded).map(Ok),
+            Mode::Record(path) => {
+                let response = match client.execute(request).await {
+                    Ok(response) => response,
+                    Err(e) => return Ok(Err(e)),
+                };
+                let status = response.status().as_u16();
+                let headers = response.headers()
+                    .iter()
+                    .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+                    .collect();
+                let body = match response.bytes().await {
+                    Ok(body) => body,
+                    Err(e) => return Ok(Err(e)),
+                };
+
+                let (text, base64) = match std::str::from_utf8(&body) {
+                    Ok(text) => (text.to_string(), false),
+                    Err(_) => (STANDARD.encode(&body), true),
+                };
+                let interaction = Interaction {
+                    request: recorded,
+                    response: RecordedResponse { status, headers, body: text, base64 },
+                };
+                let replayed = build_response(&interaction.response, body)?;
+                self.save(path, interaction)?;
+                Ok(Ok(replayed))
+            }
+        }
+    }
+
+    fn replay_response(&self, used: &Mutex<Vec<bool>>, request: &RecordedRequest) -> Result<Response> {
+        let interactions = self.interactions.lock().unwrap();
+        let mut used = used.lock().unwrap();
+
+        let index = interactions
+            .iter()
+            .enumerate()
+            .position(|(i, interaction)| {
+                !used[i]
+                    && interaction.request.method == request.method
+                    && interaction.request.url == request.url
+            })
+            .ok_or_else(|| Error::Cassette(format!(
+                "No recorded interaction for {} {}",
+                request.method, request.url
+            )))?;
+        used[index] = true;
+
+        let response = &interactions[index].response;
+        let body = if response.base64 {
+            STANDARD.decode(&response.body)
+                .map_err(|e| Error::Cassette(format!("Invalid base64 body: {}", e)))?
+        } else {
+            response.body.clone().into_bytes()
+        };
+        build_response(response, Bytes::from(body))
+    }
+
+    fn save(&self, path: &Path, interaction: Interaction) -> Result<()> {
+        let mut interactions = self.interactions.lock().unwrap();
+        interactions.push(interaction);
+
+        let file = CassetteFile { interactions: interactions.clone() };
+        let content = serde_json::to_string_pretty(&file)
+            .map_err(|e| Error::Cassette(format!("Failed to serialize cassette: {}", e)))?;
+        std::fs::write(path, content)?;
+        Ok(())
+    }
+}
+
+fn relative_url(url: &str, base_url: &str) -> String {
+    url.strip_prefix(base_url).unwrap_or(url).to_string()
+}
+
+fn build_response(recorded: &RecordedResponse, body: Bytes) -> Result<Response> {
+    let mut builder = http::Response::builder().status(recorded.status);
+    for (name, value) in &recorded.headers {
+        builder = builder.header(name.as_str(), value.as_str());
+    }
+    let response = builder
+        .body(body)
+        .map_err(|e| Error::Cassette(format!("Invalid recorded response: {}", e)))?;
+    Ok(Response::from(response))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LakeFSClient;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_record_then_replay() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repositories/test-repo"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "test-repo",
+                "storage_namespace": "s3://bucket",
+                "default_branch": "main",
+                "creation_date": "2024-01-01T00:00:00Z"
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repositories/test-repo/refs/main/objects"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(vec![0xff, 0x00, 0x7f]))
+            .mount(&mock_server)
+            .await;
+
+        let temp_dir = TempDir::new().unwrap();
+        let cassette_path = temp_dir.path().join("cassette.json");
+
+        let recording = LakeFSClient::builder()
+            .base_url(mock_server.uri())
+            .auth_header("Bearer secret-token")
+            .cassette(Arc::new(Cassette::record(&cassette_path)))
+            .build()
+            .unwrap();
+        recording.get_repository("test-repo").await.unwrap();
+        recording.download_object("test-repo", "main", "bin").await.unwrap();
+
+        let recorded = std::fs::read_to_string(&cassette_path).unwrap();
+        assert!(!recorded.contains("secret-token"));
+
+        // Replay with the server gone
+        let replaying = LakeFSClient::builder()
+            .base_url(mock_server.uri())
+            .auth_header("Bearer secret-token")
+            .cassette(Arc::new(Cassette::replay(&cassette_path).unwrap()))
+            .build()
+            .unwrap();
+        drop(mock_server);
+
+        let repo = replaying.get_repository("test-repo").await.unwrap();
+        assert_eq!(repo.default_branch, "main");
+        let data = replaying.download_object("test-repo", "main", "bin").await.unwrap();
+        assert_eq!(data.as_ref(), &[0xff, 0x00, 0x7f]);
+
+        let missing = replaying.get_repository("test-repo").await;
+        assert!(matches!(missing, Err(Error::Cassette(_))));
+    }
+}
@@ -0,0 +1,158 @@
+//! Object checksums as lakeFS reports them.
+//!
+//! The `checksum` of an object is whatever its blockstore produced: usually
+//! the ETag, which is the content MD5 for single-part S3 and MinIO uploads,
+//! but the MD5 of the part digests for multipart uploads, and an opaque value
+//! on some backends. Local and in-memory stores may report a SHA-256 instead.
+//! [`Checksum`] keeps track of which of these a value is, so content is only
+//! verified against digests it can actually be compared with.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Checksum {
+    /// Hex MD5 of the content
+    Md5(String),
+    /// Hex SHA-256 of the content, written `sha256:<hex>` or as bare hex
+    Sha256(String),
+    /// ETag of a multipart upload: the MD5 of the part MD5s and the part
+    /// count. Not a digest of the content, so it cannot be recomputed.
+    Multipart { digest: String, parts: u32 },
+    /// Anything else; only comparable with other checksums for equality.
+    Other(String),
+}
+
+fn is_hex(value: &str, len: usize) -> bool {
+    value.len() == len && value.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+impl Checksum {
+    /// Interpret a checksum string, ignoring the quotes ETags come in.
+    pub fn parse(value: &str) -> Self {
+        let trimmed = value.trim().trim_matches('"');
+        let lower = trimmed.to_ascii_lowercase();
+
+        if let Some(hex) = lower.strip_prefix("sha256:") {
+            if is_hex(hex, 64) {
+                return Checksum::Sha256(hex.to_string());
+            }
+        } else if is_hex(&lower, 32) {
+            return Checksum::Md5(lower);
+        } else if is_hex(&lower, 64) {
+            return Checksum::Sha256(lower);
+        } else if let Some((digest, parts)) = lower.split_once('-') {
+            if let (true, Ok(parts)) = (is_hex(digest, 32), parts.parse()) {
+                return Checksum::Multipart { digest: digest.to_string(), parts };
+            }
+        }
+        Checksum::Other(trimmed.to_string())
+    }
+
+    /// Whether the content can be checked against this checksum.
+    pub fn is_content_digest(&self) -> bool {
+        matches!(self, Checksum::Md5(_) | Checksum::Sha256(_))
+    }
+
+    /// Compare with a digest computed from the content. `None` when the two
+    /// are not the same kind of digest, so nothing can be concluded.
+    pub fn matches(&self, computed: &Checksum) -> Option<bool> {
+        match (self, computed) {
+            (Checksum::Md5(a), Checksum::Md5(b)) | (Checksum::Sha256(a), Checksum::Sha256(b)) => Some(a == b),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Checksum {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Checksum::Md5(hex) | Checksum::Sha256(hex) => f.write_str(hex),
+            Checksum::Multipart { digest, parts } => write!(f, "{}-{}", digest, parts),
+            Checksum::Other(value) => f.write_str(value),
+        }
+    }
+}
+
+impl FromStr for Checksum {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Checksum::parse(s))
+    }
+}
+
+impl From<&str> for Checksum {
+    fn from(value: &str) -> Self {
+        Checksum::parse(value)
+    }
+}
+
+impl From<String> for Checksum {
+    fn from(value: String) -> Self {
+        Checksum::parse(&value)
+    }
+}
+
+impl Default for Checksum {
+    fn default() -> Self {
+        Checksum::Other(String::new())
+    }
+}
+
+impl Serialize for Checksum {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Checksum {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Checksum::parse(&String::deserialize(deserializer)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MD5_HELLO: &str = "5d41402abc4b2a76b9719d911017c592";
+    const SHA256_HELLO: &str = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Checksum::parse(&format!("\"{}\"", MD5_HELLO.to_uppercase())), Checksum::Md5(MD5_HELLO.into()));
+        assert_eq!(Checksum::parse(&format!("sha256:{}", SHA256_HELLO)), Checksum::Sha256(SHA256_HELLO.into()));
+        assert_eq!(Checksum::parse(SHA256_HELLO), Checksum::Sha256(SHA256_HELLO.into()));
+        assert_eq!(
+            Checksum::parse(&format!("\"{}-12\"", MD5_HELLO)),
+            Checksum::Multipart { digest: MD5_HELLO.into(), parts: 12 }
+        );
+        assert_eq!(Checksum::parse("sha256:123abc"), Checksum::Other("sha256:123abc".into()));
+        assert_eq!(Checksum::parse("AAAAhQ=="), Checksum::Other("AAAAhQ==".into()));
+    }
+
+    #[test]
+    fn test_matches() {
+        let md5 = Checksum::Md5(MD5_HELLO.into());
+        let sha256 = Checksum::Sha256(SHA256_HELLO.into());
+
+        assert_eq!(Checksum::parse(MD5_HELLO).matches(&md5), Some(true));
+        assert_eq!(Checksum::parse("00000000000000000000000000000000").matches(&md5), Some(false));
+        // A SHA-256 is never evidence about an MD5, and vice versa
+        assert_eq!(Checksum::parse(MD5_HELLO).matches(&sha256), None);
+        assert_eq!(Checksum::parse(&format!("{}-3", MD5_HELLO)).matches(&md5), None);
+        assert!(!Checksum::parse("opaque").is_content_digest());
+    }
+
+    #[test]
+    fn test_serde_round_trip() {
+        let json = format!("[\"\\\"{}\\\"\",\"{}-2\",\"opaque\"]", MD5_HELLO, MD5_HELLO);
+        let checksums: Vec<Checksum> = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            serde_json::to_string(&checksums).unwrap(),
+            format!("[\"{}\",\"{}-2\",\"opaque\"]", MD5_HELLO, MD5_HELLO)
+        );
+    }
+}
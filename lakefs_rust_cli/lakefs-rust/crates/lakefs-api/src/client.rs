@@ -1,40 +1,217 @@
-use crate::{error::{Error, Result}, models::*};
+use crate::{
+    builder::{ClientConfig, LakeFSClientBuilder},
+    cassette::Cassette,
+    download::ObjectRange,
+    response_cache::ResponseCache,
+    error::{Error, Result},
+    middleware::ClientMiddleware,
+    models::*,
+    retry::RetryPolicy,
+};
 use bytes::Bytes;
-use reqwest::{Client, Response, StatusCode};
+use futures::stream::{self, Stream, StreamExt, TryStreamExt};
+use lakefs_auth::AuthProvider;
+use reqwest::{header::AUTHORIZATION, Client, Request, RequestBuilder, Response, StatusCode};
 use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::Instrument;
+
+/// Prefix of the request headers carrying an uploaded object's user metadata.
+pub const USER_METADATA_HEADER_PREFIX: &str = "X-Lakefs-Meta-";
 
 #[derive(Clone)]
 pub struct LakeFSClient {
     client: Client,
     base_url: String,
     auth_header: String,
+    auth_provider: Option<Arc<dyn AuthProvider>>,
+    retry_policy: RetryPolicy,
+    middleware: Vec<Arc<dyn ClientMiddleware>>,
+    cassette: Option<Arc<Cassette>>,
+    response_cache: Option<Arc<ResponseCache>>,
+}
+
+/// The User-Agent sent by default: `lakectl-rust/<version> (<os>; <arch>)`.
+pub fn default_user_agent() -> String {
+    format!(
+        "lakectl-rust/{} ({}; {})",
+        env!("CARGO_PKG_VERSION"),
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+    )
 }
 
 impl LakeFSClient {
     pub fn new(base_url: impl Into<String>, auth_header: impl Into<String>) -> Self {
-        Self {
-            client: Client::new(),
-            base_url: base_url.into(),
-            auth_header: auth_header.into(),
-        }
+        let client = Client::builder()
+            .user_agent(default_user_agent())
+            .build()
+            .unwrap_or_default();
+        Self::with_client(client, base_url, auth_header)
     }
     
     pub fn with_client(client: Client, base_url: impl Into<String>, auth_header: impl Into<String>) -> Self {
+        Self::from_parts(
+            client,
+            base_url.into(),
+            auth_header.into(),
+            None,
+            RetryPolicy::none(),
+            Vec::new(),
+            None,
+        )
+    }
+    
+    pub fn builder() -> LakeFSClientBuilder {
+        LakeFSClientBuilder::new()
+    }
+    
+    /// Client for the server at `endpoint` that asks `provider` for the
+    /// `Authorization` header of every request, so tokens are refreshed as
+    /// they expire however long the client, or any clone of it, is used.
+    pub fn from_provider(
+        endpoint: impl Into<String>,
+        provider: Arc<dyn AuthProvider>,
+        config: ClientConfig,
+    ) -> Result<Self> {
+        Self::builder()
+            .base_url(endpoint)
+            .auth_provider(provider)
+            .config(config)
+            .build()
+    }
+    
+    pub(crate) fn from_parts(
+        client: Client,
+        base_url: String,
+        auth_header: String,
+        auth_provider: Option<Arc<dyn AuthProvider>>,
+        retry_policy: RetryPolicy,
+        middleware: Vec<Arc<dyn ClientMiddleware>>,
+        cassette: Option<Arc<Cassette>>,
+    ) -> Self {
         Self {
             client,
-            base_url: base_url.into(), 
-            auth_header: auth_header.into(),
+            base_url,
+            auth_header,
+            auth_provider,
+            retry_policy,
+            middleware,
+            cassette,
+            response_cache: None,
         }
     }
     
-    async fn request<T: DeserializeOwned>(&self, method: reqwest::Method, path: &str) -> Result<T> {
-        let url = format!("{}{}", self.base_url, path);
-        let response = self.client
-            .request(method, &url)
-            .header("Authorization", &self.auth_header)
-            .send()
-            .await?;
+    pub(crate) fn with_response_cache(mut self, cache: Option<Arc<ResponseCache>>) -> Self {
+        self.response_cache = cache;
+        self
+    }
+    
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+    
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+    
+    async fn authorization(&self) -> Result<String> {
+        match &self.auth_provider {
+            Some(provider) => Ok(provider.get_auth_header().await?),
+            None => Ok(self.auth_header.clone()),
+        }
+    }
+    
+    /// Send a request with authentication, middleware and the retry policy
+    /// applied, in a span covering all its attempts.
+    async fn send(&self, builder: RequestBuilder) -> Result<Response> {
+        let authorization = self.authorization().await?;
+        let request = builder.header(AUTHORIZATION, authorization).build()?;
+        
+        let span = tracing::info_span!(
+            "lakefs_api",
+            http.request.method = %request.method(),
+            url.path = %request.url().path(),
+            http.response.status_code = tracing::field::Empty,
+        );
+        self.execute(request).instrument(span).await
+    }
+    
+    async fn execute(&self, mut request: Request) -> Result<Response> {
+        for middleware in &self.middleware {
+            middleware.on_request(&mut request)?;
+        }
+        
+        let mut attempt = 0;
+        loop {
+            let retry = if attempt < self.retry_policy.max_retries {
+                request.try_clone()
+            } else {
+                None
+            };
+            
+            let outcome = match &self.cassette {
+                Some(cassette) => cassette.execute(&self.client, request, &self.base_url).await?,
+                None => self.client.execute(request).await,
+            };
+            if let Ok(response) = &outcome {
+                for middleware in &self.middleware {
+                    middleware.on_response(response);
+                }
+            }
+            let next = match (outcome, retry) {
+                (Ok(response), Some(next)) if RetryPolicy::is_retryable_status(response.status()) => next,
+                (Err(e), Some(next)) if RetryPolicy::is_retryable_error(&e) => next,
+                (outcome, _) => {
+                    if let Ok(response) = &outcome {
+                        tracing::Span::current().record("http.response.status_code", response.status().as_u16());
+                    }
+                    return Ok(outcome?);
+                }
+            };
             
+            tokio::time::sleep(self.retry_policy.backoff(attempt)).await;
+            attempt += 1;
+            for middleware in &self.middleware {
+                middleware.on_retry(attempt);
+            }
+            request = next;
+        }
+    }
+    
+    async fn request<T: DeserializeOwned>(&self, method: reqwest::Method, path: &str) -> Result<T> {
+        let response = self.send(self.client.request(method, self.url(path))).await?;
+        self.handle_response(response).await
+    }
+    
+    /// The result of `fetch`, served from and stored in the response cache
+    /// when `reference` is a commit ID.
+    async fn cached_json<T: serde::Serialize + DeserializeOwned>(
+        &self,
+        reference: &str,
+        key: String,
+        fetch: impl std::future::Future<Output = Result<T>>,
+    ) -> Result<T> {
+        let Some(cache) = self.response_cache.as_ref().filter(|_| ResponseCache::is_immutable(reference)) else {
+            return fetch.await;
+        };
+        if let Some(value) = cache.get(&key).and_then(|hit| serde_json::from_slice(&hit).ok()) {
+            return Ok(value);
+        }
+        let value = fetch.await?;
+        if let Ok(encoded) = serde_json::to_vec(&value) {
+            cache.put(&key, encoded.into());
+        }
+        Ok(value)
+    }
+    
+    async fn request_with_query<Q: serde::Serialize + ?Sized, T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &Q,
+    ) -> Result<T> {
+        let response = self.send(self.client.get(self.url(path)).query(query)).await?;
         self.handle_response(response).await
     }
     
@@ -44,27 +221,26 @@ impl LakeFSClient {
         path: &str,
         body: &B,
     ) -> Result<T> {
-        let url = format!("{}{}", self.base_url, path);
-        let response = self.client
-            .request(method, &url)
-            .header("Authorization", &self.auth_header)
-            .json(body)
-            .send()
-            .await?;
-            
+        let response = self.send(self.client.request(method, self.url(path)).json(body)).await?;
         self.handle_response(response).await
     }
     
     async fn handle_response<T: DeserializeOwned>(&self, response: Response) -> Result<T> {
+        let response = Self::check_status(response).await?;
+        Ok(response.json().await?)
+    }
+    
+    async fn check_status(response: Response) -> Result<Response> {
         let status = response.status();
         
         if status.is_success() {
-            Ok(response.json().await?)
+            Ok(response)
         } else {
             let message = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
             match status {
                 StatusCode::NOT_FOUND => Err(Error::NotFound(message)),
                 StatusCode::UNAUTHORIZED => Err(Error::Auth(message)),
+                StatusCode::PRECONDITION_FAILED => Err(Error::PreconditionFailed(message)),
                 _ => Err(Error::Api {
                     status: status.as_u16(),
                     message,
@@ -73,6 +249,11 @@ impl LakeFSClient {
         }
     }
     
+    // Config operations
+    pub async fn get_storage_config(&self) -> Result<StorageConfig> {
+        self.request(reqwest::Method::GET, "/config/storage").await
+    }
+    
     // Repository operations
     pub async fn create_repository(&self, name: &str, storage_namespace: &str) -> Result<Repository> {
         let body = serde_json::json!({
@@ -83,8 +264,24 @@ impl LakeFSClient {
         self.request_with_body(reqwest::Method::POST, "/repositories", &body).await
     }
     
+    /// Create a repository whose first branch is `default_branch` instead of
+    /// the server's default.
+    pub async fn create_repository_with_branch(&self, name: &str, storage_namespace: &str, default_branch: &str) -> Result<Repository> {
+        let body = serde_json::json!({
+            "name": name,
+            "storage_namespace": storage_namespace,
+            "default_branch": default_branch,
+        });
+        
+        self.request_with_body(reqwest::Method::POST, "/repositories", &body).await
+    }
+    
     pub async fn list_repositories(&self) -> Result<Pagination<Repository>> {
-        self.request(reqwest::Method::GET, "/repositories").await
+        self.list_repositories_with(&PaginationParams::default()).await
+    }
+    
+    pub async fn list_repositories_with(&self, params: &PaginationParams) -> Result<Pagination<Repository>> {
+        self.request_with_query("/repositories", params).await
     }
     
     pub async fn get_repository(&self, repository: &str) -> Result<Repository> {
@@ -110,8 +307,12 @@ impl LakeFSClient {
     }
     
     pub async fn list_branches(&self, repository: &str) -> Result<Pagination<Branch>> {
+        self.list_branches_with(repository, &PaginationParams::default()).await
+    }
+    
+    pub async fn list_branches_with(&self, repository: &str, params: &PaginationParams) -> Result<Pagination<Branch>> {
         let path = format!("/repositories/{}/branches", repository);
-        self.request(reqwest::Method::GET, &path).await
+        self.request_with_query(&path, params).await
     }
     
     pub async fn get_branch(&self, repository: &str, branch: &str) -> Result<Branch> {
@@ -120,8 +321,53 @@ impl LakeFSClient {
     }
     
     pub async fn delete_branch(&self, repository: &str, branch: &str) -> Result<()> {
-        let path = format!("/repositories/{}/branches/{}", repository, branch);
-        let _: serde_json::Value = self.request(reqwest::Method::DELETE, &path).await?;
+        // Answered with 204 and no body
+        let url = self.url(&format!("/repositories/{}/branches/{}", repository, branch));
+        let response = self.send(self.client.delete(url)).await?;
+        Self::check_status(response).await?;
+        Ok(())
+    }
+    
+    pub async fn get_tag(&self, repository: &str, tag: &str) -> Result<Tag> {
+        let path = format!("/repositories/{}/tags/{}", repository, tag);
+        self.request(reqwest::Method::GET, &path).await
+    }
+    
+    pub async fn list_tags_with(&self, repository: &str, params: &PaginationParams) -> Result<Pagination<Tag>> {
+        let path = format!("/repositories/{}/tags", repository);
+        self.request_with_query(&path, params).await
+    }
+    
+    /// Uncommitted changes on `branch`.
+    pub async fn diff_uncommitted(&self, repository: &str, branch: &str, params: &PaginationParams) -> Result<Pagination<Diff>> {
+        let path = format!("/repositories/{}/branches/{}/diff", repository, branch);
+        self.request_with_query(&path, params).await
+    }
+    
+    /// Discard every uncommitted change on `branch`.
+    pub async fn reset_uncommitted(&self, repository: &str, branch: &str) -> Result<()> {
+        let url = self.url(&format!("/repositories/{}/branches/{}", repository, branch));
+        let body = serde_json::json!({ "type": "reset" });
+        let response = self.send(self.client.put(url).json(&body)).await?;
+        Self::check_status(response).await?;
+        Ok(())
+    }
+    
+    /// Discard the uncommitted change to the object at `path` on `branch`,
+    /// leaving it as the branch's head commit has it.
+    pub async fn reset_object(&self, repository: &str, branch: &str, path: &str) -> Result<()> {
+        let url = self.url(&format!("/repositories/{}/branches/{}", repository, branch));
+        let body = serde_json::json!({ "type": "object", "path": path });
+        let response = self.send(self.client.put(url).json(&body)).await?;
+        Self::check_status(response).await?;
+        Ok(())
+    }
+    
+    /// Point `branch` at `reference`. The branch must have no uncommitted changes.
+    pub async fn hard_reset_branch(&self, repository: &str, branch: &str, reference: &str) -> Result<()> {
+        let url = self.url(&format!("/repositories/{}/branches/{}/hard_reset", repository, branch));
+        let response = self.send(self.client.put(url).query(&[("ref", reference)])).await?;
+        Self::check_status(response).await?;
         Ok(())
     }
     
@@ -135,30 +381,212 @@ impl LakeFSClient {
         self.request_with_body(reqwest::Method::POST, &path, &body).await
     }
     
+    pub async fn commit_with_metadata(
+        &self,
+        repository: &str,
+        branch: &str,
+        message: &str,
+        metadata: &HashMap<String, String>,
+    ) -> Result<Commit> {
+        let path = format!("/repositories/{}/branches/{}/commits", repository, branch);
+        let body = serde_json::json!({
+            "message": message,
+            "metadata": metadata,
+        });
+        
+        self.request_with_body(reqwest::Method::POST, &path, &body).await
+    }
+    
+    /// Replace the commit at the tip of `branch` with one holding the same
+    /// changes, like `git commit --amend`. `None` keeps the original message
+    /// or metadata.
+    ///
+    /// lakeFS cannot rewrite commits, so the branch is reset to the parent and
+    /// the tip's changes are staged again and recommitted. This refuses to run
+    /// on a merge or initial commit, on a branch with uncommitted changes, or
+    /// when the head is not `expected_head` or moves before the reset. If
+    /// restaging or committing fails, the branch is put back on the original tip.
+    pub async fn amend_commit<'a>(
+        &self,
+        repository: &str,
+        branch: &str,
+        expected_head: Option<&'a str>,
+        message: Option<&'a str>,
+        metadata: Option<&'a HashMap<String, String>>,
+    ) -> Result<Commit> {
+        let head = self.get_branch(repository, branch).await?.commit_id;
+        if let Some(expected) = expected_head {
+            if expected != head {
+                return Err(Error::Conflict(format!(
+                    "branch '{}' is at {}, expected {}", branch, head, expected
+                )));
+            }
+        }
+        
+        let tip = self.get_commit(repository, &head).await?;
+        let parent = match tip.parents.as_slice() {
+            [parent] => parent.clone(),
+            [] => return Err(Error::InvalidArgument("cannot amend the initial commit".into())),
+            _ => return Err(Error::InvalidArgument("cannot amend a merge commit".into())),
+        };
+        
+        let uncommitted = self.diff_uncommitted(repository, branch, &PaginationParams::new().with_amount(1)).await?;
+        if !uncommitted.results.is_empty() {
+            return Err(Error::Conflict(format!("branch '{}' has uncommitted changes", branch)));
+        }
+        
+        // Capture the tip's changes before the branch moves away from it
+        let mut changes = Vec::new();
+        let mut params = PaginationParams::default();
+        loop {
+            let page = self.diff_with(repository, &parent, &head, &params).await?;
+            for change in page.results {
+                let stage = match change.diff_type {
+                    DiffType::Removed => None,
+                    DiffType::Added | DiffType::Changed => {
+                        let stats = self.get_object(repository, &head, &change.path).await?;
+                        Some(ObjectStage::from(&stats))
+                    }
+                };
+                changes.push((change.path, stage));
+            }
+            
+            match page.pagination.next_offset {
+                Some(next) if page.pagination.has_more => params.after = Some(next),
+                _ => break,
+            }
+        }
+        
+        let current = self.get_branch(repository, branch).await?.commit_id;
+        if current != head {
+            return Err(Error::Conflict(format!(
+                "branch '{}' moved from {} to {} while amending", branch, head, current
+            )));
+        }
+        
+        self.hard_reset_branch(repository, branch, &parent).await?;
+        let message = message.unwrap_or(&tip.message);
+        let metadata = metadata.unwrap_or(&tip.metadata);
+        let amended = match self.restage(repository, branch, &changes).await {
+            Ok(()) => self.commit_with_metadata(repository, branch, message, metadata).await,
+            Err(e) => Err(e),
+        };
+        
+        if amended.is_err() {
+            // Best effort; the original error is the one worth reporting
+            let _ = self.reset_uncommitted(repository, branch).await;
+            let _ = self.hard_reset_branch(repository, branch, &head).await;
+        }
+        amended
+    }
+    
+    async fn restage(&self, repository: &str, branch: &str, changes: &[(String, Option<ObjectStage>)]) -> Result<()> {
+        for (path, stage) in changes {
+            match stage {
+                Some(stage) => {
+                    self.stage_object(repository, branch, path, stage).await?;
+                }
+                None => self.delete_object(repository, branch, path).await?,
+            }
+        }
+        Ok(())
+    }
+    
     pub async fn get_commit(&self, repository: &str, commit_id: &str) -> Result<Commit> {
         let path = format!("/repositories/{}/commits/{}", repository, commit_id);
         self.request(reqwest::Method::GET, &path).await
     }
     
     pub async fn log_commits(&self, repository: &str, branch: &str) -> Result<Pagination<Commit>> {
+        self.log_commits_with(repository, branch, &PaginationParams::default()).await
+    }
+    
+    pub async fn log_commits_with(&self, repository: &str, branch: &str, params: &PaginationParams) -> Result<Pagination<Commit>> {
         let path = format!("/repositories/{}/branches/{}/commits", repository, branch);
-        self.request(reqwest::Method::GET, &path).await
+        self.request_with_query(&path, params).await
     }
     
     // Object operations
-    pub async fn list_objects(&self, repository: &str, reference: &str, path: Option<&str>) -> Result<Pagination<ObjectStats>> {
-        let base_path = format!("/repositories/{}/refs/{}/objects", repository, reference);
-        let path = match path {
-            Some(p) => format!("{}?prefix={}", base_path, p),
-            None => base_path,
-        };
-        
-        self.request(reqwest::Method::GET, &path).await
+    /// One page of the objects at `reference`. With a delimiter, common
+    /// prefixes come back as [`PathType::Directory`] entries; convert them
+    /// with [`ListEntry::from`].
+    pub async fn list_objects(&self, repository: &str, reference: &str, options: &ListObjectsOptions) -> Result<Pagination<ObjectStats>> {
+        let key = format!(
+            "list\0{}\0{}\0{:?}\0{:?}\0{:?}\0{:?}",
+            repository, reference, options.prefix, options.after, options.amount, options.delimiter
+        );
+        self.cached_json(reference, key, async {
+            let path = format!("/repositories/{}/refs/{}/objects", repository, reference);
+            self.request_with_query(&path, options).await
+        }).await
+    }
+    
+    pub async fn list_objects_with(&self, repository: &str, reference: &str, params: &PaginationParams) -> Result<Pagination<ObjectStats>> {
+        let key = format!(
+            "list\0{}\0{}\0{:?}\0{:?}\0{:?}\0{:?}",
+            repository, reference, params.prefix, params.after, params.amount, params.user_metadata
+        );
+        self.cached_json(reference, key, async {
+            let path = format!("/repositories/{}/refs/{}/objects", repository, reference);
+            self.request_with_query(&path, params).await
+        }).await
     }
     
     pub async fn get_object(&self, repository: &str, reference: &str, path: &str) -> Result<ObjectStats> {
-        let path = format!("/repositories/{}/refs/{}/objects/stat?path={}", repository, reference, path);
-        self.request(reqwest::Method::GET, &path).await
+        let key = format!("stat\0{}\0{}\0{}", repository, reference, path);
+        self.cached_json(reference, key, async {
+            let url = self.url(&format!("/repositories/{}/refs/{}/objects/stat", repository, reference));
+            let response = self.send(self.client.get(url).query(&[("path", path)])).await?;
+            self.handle_response(response).await
+        }).await
+    }
+    
+    /// Stat many objects concurrently, running at most `concurrency` requests at once.
+    ///
+    /// Results are returned in the same order as `paths`; a failure for one path
+    /// does not abort the others.
+    pub async fn stat_objects(
+        &self,
+        repository: &str,
+        reference: &str,
+        paths: &[String],
+        concurrency: usize,
+    ) -> Vec<(String, Result<ObjectStats>)> {
+        // Build the futures up front; mapping lazily over borrowed paths trips
+        // higher-ranked lifetime inference when this future must be `Send`.
+        let requests: Vec<_> = paths
+            .iter()
+            .map(|path| async move {
+                (path.clone(), self.get_object(repository, reference, path).await)
+            })
+            .collect();
+        
+        stream::iter(requests)
+            .buffered(concurrency.max(1))
+            .collect()
+            .await
+    }
+    
+    /// Stat an object, asking the server to replace `physical_address` with a presigned read URL.
+    pub async fn get_object_presigned(&self, repository: &str, reference: &str, path: &str) -> Result<ObjectStats> {
+        let url = self.url(&format!("/repositories/{}/refs/{}/objects/stat", repository, reference));
+        let builder = self.client
+            .get(url)
+            .query(&[("path", path), ("presign", "true")]);
+            
+        let response = self.send(builder).await?;
+        self.handle_response(response).await
+    }
+    
+    /// Ask the server for a presigned URL that uploads directly to the branch's storage.
+    pub async fn get_presigned_upload_location(&self, repository: &str, branch: &str, path: &str) -> Result<StagingLocation> {
+        let url = self.url(&format!("/repositories/{}/branches/{}/staging/backing", repository, branch));
+        let builder = self.client
+            .get(url)
+            .query(&[("path", path), ("presign", "true")]);
+            
+        let response = self.send(builder).await?;
+        self.handle_response(response).await
     }
     
     pub async fn upload_object(
@@ -168,63 +596,215 @@ impl LakeFSClient {
         path: &str,
         content: Bytes,
     ) -> Result<ObjectStats> {
-        let url = format!("{}/repositories/{}/branches/{}/objects?path={}", 
-                         self.base_url, repository, branch, path);
-        
-        let response = self.client
-            .put(&url)
-            .header("Authorization", &self.auth_header)
-            .body(content)
-            .send()
-            .await?;
-            
+        self.upload_object_with(repository, branch, path, content, &UploadOptions::default()).await
+    }
+    
+    /// Upload an object with a precondition or user metadata.
+    pub async fn upload_object_with(
+        &self,
+        repository: &str,
+        branch: &str,
+        path: &str,
+        content: Bytes,
+        options: &UploadOptions,
+    ) -> Result<ObjectStats> {
+        let builder = self.upload_request(repository, branch, path, options).body(content);
+        let response = self.send(builder).await?;
+        self.handle_response(response).await
+    }
+    
+    /// User metadata is sent as `X-Lakefs-Meta-<key>` headers.
+    fn upload_request(
+        &self,
+        repository: &str,
+        branch: &str,
+        path: &str,
+        options: &UploadOptions,
+    ) -> RequestBuilder {
+        let url = self.url(&format!("/repositories/{}/branches/{}/objects", repository, branch));
+        let mut builder = self.client.put(url).query(&[("path", path)]);
+        for (key, value) in &options.metadata {
+            builder = builder.header(format!("{}{}", USER_METADATA_HEADER_PREFIX, key), value);
+        }
+        if let Some(content_type) = &options.content_type {
+            builder = builder.header(reqwest::header::CONTENT_TYPE, content_type);
+        }
+        Self::with_precondition(builder, options.precondition.as_ref())
+    }
+    
+    fn with_precondition(builder: RequestBuilder, precondition: Option<&Precondition>) -> RequestBuilder {
+        match precondition {
+            Some(precondition) => {
+                let (name, value) = precondition.header();
+                builder.header(name, value)
+            }
+            None => builder,
+        }
+    }
+    
+    /// Upload an object from a stream of chunks. Streaming bodies cannot be
+    /// replayed, so these requests are never retried.
+    pub async fn upload_object_stream<S>(
+        &self,
+        repository: &str,
+        branch: &str,
+        path: &str,
+        content: S,
+    ) -> Result<ObjectStats>
+    where
+        S: Stream<Item = std::io::Result<Bytes>> + Send + Sync + 'static,
+    {
+        self.upload_object_stream_with(repository, branch, path, content, &UploadOptions::default()).await
+    }
+    
+    /// Streaming counterpart of [`upload_object_with`](Self::upload_object_with).
+    pub async fn upload_object_stream_with<S>(
+        &self,
+        repository: &str,
+        branch: &str,
+        path: &str,
+        content: S,
+        options: &UploadOptions,
+    ) -> Result<ObjectStats>
+    where
+        S: Stream<Item = std::io::Result<Bytes>> + Send + Sync + 'static,
+    {
+        let builder = self
+            .upload_request(repository, branch, path, options)
+            .body(reqwest::Body::wrap_stream(content));
+        let response = self.send(builder).await?;
         self.handle_response(response).await
     }
     
+    /// Download an object as a stream of chunks instead of buffering it.
+    pub async fn download_object_stream(
+        &self,
+        repository: &str,
+        reference: &str,
+        path: &str,
+    ) -> Result<impl Stream<Item = Result<Bytes>>> {
+        let url = self.url(&format!("/repositories/{}/refs/{}/objects", repository, reference));
+        let response = self.send(self.client.get(url).query(&[("path", path)])).await?;
+        let response = Self::check_status(response).await?;
+        Ok(response.bytes_stream().map_err(Error::from))
+    }
+    
+    /// Request an object's content from byte `offset` on. Servers that honour
+    /// the range answer 206 Partial Content; others send all of it. When
+    /// `if_none_match` is still the object's checksum, the server answers
+    /// 304 Not Modified instead and `None` is returned.
+    pub async fn get_object_range(
+        &self,
+        repository: &str,
+        reference: &str,
+        path: &str,
+        offset: u64,
+        if_none_match: Option<&Checksum>,
+    ) -> Result<Option<ObjectRange>> {
+        let url = self.url(&format!("/repositories/{}/refs/{}/objects", repository, reference));
+        let mut builder = self.client.get(url).query(&[("path", path)]);
+        if offset > 0 {
+            builder = builder.header(reqwest::header::RANGE, format!("bytes={}-", offset));
+        }
+        if let Some(etag) = if_none_match {
+            builder = builder.header(reqwest::header::IF_NONE_MATCH, format!("\"{}\"", etag));
+        }
+        let response = self.send(builder).await?;
+        if if_none_match.is_some() && response.status() == StatusCode::NOT_MODIFIED {
+            return Ok(None);
+        }
+        let response = Self::check_status(response).await?;
+        Ok(Some(ObjectRange {
+            partial: response.status() == StatusCode::PARTIAL_CONTENT,
+            etag: response
+                .headers()
+                .get(reqwest::header::ETAG)
+                .and_then(|etag| etag.to_str().ok())
+                .map(Checksum::parse),
+            size: response.content_length(),
+            content: Box::pin(response.bytes_stream().map_err(Error::from)),
+        }))
+    }
+    
+    /// Download an object unless its checksum is still `etag`, in which case
+    /// the server answers 304 Not Modified and `None` is returned.
+    pub async fn download_object_stream_if_none_match(
+        &self,
+        repository: &str,
+        reference: &str,
+        path: &str,
+        etag: &Checksum,
+    ) -> Result<Option<impl Stream<Item = Result<Bytes>>>> {
+        let url = self.url(&format!("/repositories/{}/refs/{}/objects", repository, reference));
+        let builder = self.client
+            .get(url)
+            .query(&[("path", path)])
+            .header(reqwest::header::IF_NONE_MATCH, format!("\"{}\"", etag));
+        let response = self.send(builder).await?;
+        if response.status() == StatusCode::NOT_MODIFIED {
+            return Ok(None);
+        }
+        let response = Self::check_status(response).await?;
+        Ok(Some(response.bytes_stream().map_err(Error::from)))
+    }
+    
     pub async fn download_object(&self, repository: &str, reference: &str, path: &str) -> Result<Bytes> {
-        let url = format!("{}/repositories/{}/refs/{}/objects?path={}", 
-                         self.base_url, repository, reference, path);
-        
-        let response = self.client
-            .get(&url)
-            .header("Authorization", &self.auth_header)
-            .send()
-            .await?;
-        
-        let status = response.status();  // Capture status before consuming response
+        let cache = self.response_cache.as_ref().filter(|_| ResponseCache::is_immutable(reference));
+        let key = format!("object\0{}\0{}\0{}", repository, reference, path);
+        if let Some(content) = cache.and_then(|cache| cache.get(&key)) {
+            return Ok(content);
+        }
         
-        if status.is_success() {
-            Ok(response.bytes().await?)
-        } else {
-            let message = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-            Err(Error::Api {
-                status: status.as_u16(),
-                message,
-            })
+        let url = self.url(&format!("/repositories/{}/refs/{}/objects", repository, reference));
+        let response = self.send(self.client.get(url).query(&[("path", path)])).await?;
+        let response = Self::check_status(response).await?;
+        let content = response.bytes().await?;
+        if let Some(cache) = cache {
+            cache.put(&key, content.clone());
         }
+        Ok(content)
+    }
+    
+    /// Link an object that already exists in the underlying storage into `branch`.
+    pub async fn stage_object(&self, repository: &str, branch: &str, path: &str, object: &ObjectStage) -> Result<ObjectStats> {
+        let url = self.url(&format!("/repositories/{}/branches/{}/objects", repository, branch));
+        let response = self.send(self.client.put(url).query(&[("path", path)]).json(object)).await?;
+        self.handle_response(response).await
+    }
+    
+    /// Copy `src_path` at `src_ref` to `dest_path` on `branch` without
+    /// transferring the data through the client.
+    pub async fn copy_object(
+        &self,
+        repository: &str,
+        branch: &str,
+        dest_path: &str,
+        src_ref: &str,
+        src_path: &str,
+    ) -> Result<ObjectStats> {
+        let url = self.url(&format!("/repositories/{}/branches/{}/objects/copy", repository, branch));
+        let body = serde_json::json!({ "src_path": src_path, "src_ref": src_ref });
+        let response = self.send(self.client.post(url).query(&[("dest_path", dest_path)]).json(&body)).await?;
+        self.handle_response(response).await
     }
     
     pub async fn delete_object(&self, repository: &str, branch: &str, path: &str) -> Result<()> {
-        let url = format!("{}/repositories/{}/branches/{}/objects?path={}", 
-                         self.base_url, repository, branch, path);
-        
-        let response = self.client
-            .delete(&url)
-            .header("Authorization", &self.auth_header)
-            .send()
-            .await?;
-        
-        let status = response.status();  // Capture status before consuming response
-            
-        if status.is_success() {
-            Ok(())
-        } else {
-            let message = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-            Err(Error::Api {
-                status: status.as_u16(),
-                message,
-            })
-        }
+        self.delete_object_if(repository, branch, path, None).await
+    }
+    
+    /// Delete an object only if `precondition` holds for it.
+    pub async fn delete_object_if(
+        &self,
+        repository: &str,
+        branch: &str,
+        path: &str,
+        precondition: Option<&Precondition>,
+    ) -> Result<()> {
+        let url = self.url(&format!("/repositories/{}/branches/{}/objects", repository, branch));
+        let builder = Self::with_precondition(self.client.delete(url).query(&[("path", path)]), precondition);
+        let response = self.send(builder).await?;
+        Self::check_status(response).await?;
+        Ok(())
     }
     
     // Diff operations
@@ -233,6 +813,17 @@ impl LakeFSClient {
         self.request(reqwest::Method::GET, &path).await
     }
     
+    pub async fn diff_with(
+        &self,
+        repository: &str,
+        left_ref: &str,
+        right_ref: &str,
+        params: &PaginationParams,
+    ) -> Result<Pagination<Diff>> {
+        let path = format!("/repositories/{}/refs/{}/diff/{}", repository, left_ref, right_ref);
+        self.request_with_query(&path, params).await
+    }
+    
     // Merge operations
     pub async fn merge(&self, repository: &str, source_ref: &str, destination_branch: &str) -> Result<MergeResult> {
         let path = format!("/repositories/{}/refs/{}/merge/{}", repository, source_ref, destination_branch);
@@ -240,10 +831,59 @@ impl LakeFSClient {
         
         self.request_with_body(reqwest::Method::POST, &path, &body).await
     }
+    
+    pub async fn merge_with(
+        &self,
+        repository: &str,
+        source_ref: &str,
+        destination_branch: &str,
+        options: &MergeOptions,
+    ) -> Result<MergeResult> {
+        let path = format!("/repositories/{}/refs/{}/merge/{}", repository, source_ref, destination_branch);
+        self.request_with_body(reqwest::Method::POST, &path, options).await
+    }
+    
+    /// The commits `source_ref` and `destination_branch` point at and the
+    /// best common ancestor of the two, without merging.
+    pub async fn find_merge_base(&self, repository: &str, source_ref: &str, destination_branch: &str) -> Result<MergeBase> {
+        let path = format!("/repositories/{}/refs/{}/merge/{}", repository, source_ref, destination_branch);
+        self.request(reqwest::Method::GET, &path).await
+    }
+    
+    // Refs operations
+    /// Write the repository's branches, tags and commits to metaranges in its
+    /// storage namespace, returning the manifest needed to restore them.
+    pub async fn dump_refs(&self, repository: &str) -> Result<RefsDump> {
+        let path = format!("/repositories/{}/refs/dump", repository);
+        self.request(reqwest::Method::PUT, &path).await
+    }
+    
+    /// Load refs from a `dump_refs` manifest into an empty repository.
+    pub async fn restore_refs(&self, repository: &str, manifest: &RefsDump) -> Result<()> {
+        let url = self.url(&format!("/repositories/{}/refs/restore", repository));
+        let response = self.send(self.client.put(url).json(manifest)).await?;
+        Self::check_status(response).await?;
+        Ok(())
+    }
+    
+    // Metadata operations
+    pub async fn get_meta_range(&self, repository: &str, meta_range_id: &str) -> Result<StorageUri> {
+        let path = format!("/repositories/{}/metadata/meta_range/{}", repository, meta_range_id);
+        self.request(reqwest::Method::GET, &path).await
+    }
+    
+    pub async fn get_range(&self, repository: &str, range_id: &str) -> Result<StorageUri> {
+        let path = format!("/repositories/{}/metadata/range/{}", repository, range_id);
+        self.request(reqwest::Method::GET, &path).await
+    }
 }
 
-    use wiremock::matchers::{method, path, header};
-    use wiremock::MockServer;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use wiremock::matchers::{body_json, body_partial_json, header, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
 
     #[tokio::test]
     async fn test_client_creation() {
@@ -252,6 +892,104 @@ impl LakeFSClient {
         assert_eq!(client.auth_header, "Bearer test-token");
     }
 
+    #[tokio::test]
+    async fn test_response_cache_only_serves_commit_ids() {
+        let mock_server = MockServer::start().await;
+        let commit = "c0ffee".repeat(10) + "abcd";
+        let stats = serde_json::json!({
+            "path": "data/a.csv",
+            "path_type": "object",
+            "physical_address": "s3://bucket/a",
+            "checksum": "d41d8cd98f00b204e9800998ecf8427e",
+            "size_bytes": 3,
+            "mtime": 1700000000
+        });
+        
+        Mock::given(method("GET"))
+            .and(path(format!("/repositories/repo/refs/{}/objects/stat", commit)))
+            .respond_with(ResponseTemplate::new(200).set_body_json(&stats))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(format!("/repositories/repo/refs/{}/objects", commit)))
+            .respond_with(ResponseTemplate::new(200).set_body_string("abc"))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repositories/repo/refs/main/objects/stat"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(&stats))
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+        
+        let client = LakeFSClient::builder()
+            .base_url(mock_server.uri())
+            .auth_header("Bearer test-token")
+            .response_cache(Arc::new(ResponseCache::in_memory(1 << 20)))
+            .build()
+            .unwrap();
+        for _ in 0..2 {
+            assert_eq!(client.get_object("repo", &commit, "data/a.csv").await.unwrap().size_bytes, 3);
+            assert_eq!(client.download_object("repo", &commit, "data/a.csv").await.unwrap(), "abc");
+            client.get_object("repo", "main", "data/a.csv").await.unwrap();
+        }
+    }
+
+    #[test]
+    fn test_default_user_agent() {
+        let user_agent = default_user_agent();
+        assert!(user_agent.starts_with(&format!("lakectl-rust/{} (", env!("CARGO_PKG_VERSION"))));
+        assert!(user_agent.contains(std::env::consts::OS));
+        assert!(user_agent.contains(std::env::consts::ARCH));
+    }
+
+    #[tokio::test]
+    async fn test_user_agent_header() {
+        let mock_server = MockServer::start().await;
+        
+        Mock::given(method("GET"))
+            .and(path("/repositories"))
+            .and(header("User-Agent", default_user_agent().as_str()))
+            .respond_with(ResponseTemplate::new(200)
+                .set_body_json(serde_json::json!({
+                    "results": [],
+                    "pagination": {
+                        "has_more": false,
+                        "max_per_page": 100,
+                        "results": 0
+                    }
+                })))
+            .mount(&mock_server)
+            .await;
+        
+        Mock::given(method("GET"))
+            .and(path("/repositories"))
+            .and(header("User-Agent", "custom-agent/2.0"))
+            .respond_with(ResponseTemplate::new(200)
+                .set_body_json(serde_json::json!({
+                    "results": [],
+                    "pagination": {
+                        "has_more": false,
+                        "max_per_page": 100,
+                        "results": 0
+                    }
+                })))
+            .mount(&mock_server)
+            .await;
+
+        let client = LakeFSClient::new(mock_server.uri(), "Bearer test-token");
+        assert!(client.list_repositories().await.is_ok());
+        
+        let client = LakeFSClient::builder()
+            .base_url(mock_server.uri())
+            .user_agent("custom-agent/2.0")
+            .build()
+            .unwrap();
+        assert!(client.list_repositories().await.is_ok());
+    }
+
     #[tokio::test]
     async fn test_create_repository() {
         let mock_server = MockServer::start().await;
@@ -277,6 +1015,29 @@ impl LakeFSClient {
         assert_eq!(repo.default_branch, "main");
     }
 
+    #[tokio::test]
+    async fn test_create_repository_with_branch() {
+        let mock_server = MockServer::start().await;
+        
+        Mock::given(method("POST"))
+            .and(path("/repositories"))
+            .and(body_partial_json(serde_json::json!({"name": "test-repo", "default_branch": "trunk"})))
+            .respond_with(ResponseTemplate::new(200)
+                .set_body_json(serde_json::json!({
+                    "id": "test-repo",
+                    "storage_namespace": "s3://bucket",
+                    "default_branch": "trunk",
+                    "creation_date": "2024-01-01T00:00:00Z"
+                })))
+            .mount(&mock_server)
+            .await;
+
+        let client = LakeFSClient::new(mock_server.uri(), "Bearer test-token");
+        let repo = client.create_repository_with_branch("test-repo", "s3://bucket", "trunk").await.unwrap();
+
+        assert_eq!(repo.default_branch, "trunk");
+    }
+
     #[tokio::test]
     async fn test_list_repositories() {
         let mock_server = MockServer::start().await;
@@ -309,6 +1070,158 @@ impl LakeFSClient {
         assert!(!repos.pagination.has_more);
     }
 
+    #[tokio::test]
+    async fn test_list_repositories_with_params() {
+        let mock_server = MockServer::start().await;
+        
+        Mock::given(method("GET"))
+            .and(path("/repositories"))
+            .and(query_param("prefix", "analytics-"))
+            .and(query_param("after", "analytics-a"))
+            .and(query_param("amount", "2"))
+            .respond_with(ResponseTemplate::new(200)
+                .set_body_json(serde_json::json!({
+                    "results": [{
+                        "id": "analytics-b",
+                        "storage_namespace": "s3://bucket/b",
+                        "default_branch": "main",
+                        "creation_date": "2024-01-01T00:00:00Z"
+                    }],
+                    "pagination": {
+                        "has_more": true,
+                        "max_per_page": 2,
+                        "results": 1,
+                        "next_offset": "analytics-b"
+                    }
+                })))
+            .mount(&mock_server)
+            .await;
+
+        let client = LakeFSClient::new(mock_server.uri(), "Bearer test-token");
+        let params = PaginationParams::new()
+            .with_prefix("analytics-")
+            .with_after("analytics-a")
+            .with_amount(2);
+        let repos = client.list_repositories_with(&params).await.unwrap();
+
+        assert_eq!(repos.results[0].id, "analytics-b");
+        assert_eq!(repos.pagination.next_offset.as_deref(), Some("analytics-b"));
+    }
+
+    #[tokio::test]
+    async fn test_list_objects_with_user_metadata() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/repositories/repo/refs/main/objects"))
+            .and(query_param("user_metadata", "true"))
+            .respond_with(ResponseTemplate::new(200)
+                .set_body_json(serde_json::json!({
+                    "results": [{
+                        "path": "run.sh",
+                        "path_type": "object",
+                        "physical_address": "s3://bucket/run",
+                        "checksum": "aaa",
+                        "size_bytes": 1,
+                        "mtime": "2024-01-01T00:00:00Z",
+                        "metadata": {"lakectl-mode": "755"}
+                    }],
+                    "pagination": {
+                        "has_more": false,
+                        "max_per_page": 1000,
+                        "results": 1
+                    }
+                })))
+            .mount(&mock_server)
+            .await;
+
+        let client = LakeFSClient::new(mock_server.uri(), "Bearer test-token");
+        let params = PaginationParams::new().with_user_metadata(true);
+        let objects = client.list_objects_with("repo", "main", &params).await.unwrap();
+        let metadata = objects.results[0].metadata.as_ref().unwrap();
+        assert_eq!(metadata["lakectl-mode"], "755");
+    }
+
+    #[tokio::test]
+    async fn test_stat_objects_batch() {
+        let mock_server = MockServer::start().await;
+        
+        Mock::given(method("GET"))
+            .and(path("/repositories/test-repo/refs/main/objects/stat"))
+            .and(query_param("path", "a.txt"))
+            .respond_with(ResponseTemplate::new(200)
+                .set_body_json(serde_json::json!({
+                    "path": "a.txt",
+                    "path_type": "object",
+                    "physical_address": "s3://bucket/a",
+                    "checksum": "aaa",
+                    "size_bytes": 1,
+                    "mtime": "2024-01-01T00:00:00Z"
+                })))
+            .mount(&mock_server)
+            .await;
+        
+        Mock::given(method("GET"))
+            .and(path("/repositories/test-repo/refs/main/objects/stat"))
+            .and(query_param("path", "missing.txt"))
+            .respond_with(ResponseTemplate::new(404).set_body_string("not found"))
+            .mount(&mock_server)
+            .await;
+
+        let client = LakeFSClient::new(mock_server.uri(), "Bearer test-token");
+        let paths = vec!["a.txt".to_string(), "missing.txt".to_string()];
+        let results = client.stat_objects("test-repo", "main", &paths, 4).await;
+        
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].0, "a.txt");
+        assert_eq!(results[0].1.as_ref().unwrap().checksum.to_string(), "aaa");
+        assert!(matches!(results[1].1, Err(Error::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_presigned_urls() {
+        let mock_server = MockServer::start().await;
+        
+        Mock::given(method("GET"))
+            .and(path("/repositories/test-repo/refs/main/objects/stat"))
+            .and(query_param("path", "data.csv"))
+            .and(query_param("presign", "true"))
+            .respond_with(ResponseTemplate::new(200)
+                .set_body_json(serde_json::json!({
+                    "path": "data.csv",
+                    "path_type": "object",
+                    "physical_address": "https://bucket.s3.amazonaws.com/data?X-Amz-Signature=abc",
+                    "physical_address_expiry": 1704070800,
+                    "checksum": "checksum123",
+                    "size_bytes": 100,
+                    "mtime": "2024-01-01T00:00:00Z"
+                })))
+            .mount(&mock_server)
+            .await;
+        
+        Mock::given(method("GET"))
+            .and(path("/repositories/test-repo/branches/main/staging/backing"))
+            .and(query_param("path", "data.csv"))
+            .and(query_param("presign", "true"))
+            .respond_with(ResponseTemplate::new(200)
+                .set_body_json(serde_json::json!({
+                    "physical_address": "s3://bucket/data/abc",
+                    "presigned_url": "https://bucket.s3.amazonaws.com/data/abc?X-Amz-Signature=def",
+                    "presigned_url_expiry": 1704070800
+                })))
+            .mount(&mock_server)
+            .await;
+
+        let client = LakeFSClient::new(mock_server.uri(), "Bearer test-token");
+        
+        let stats = client.get_object_presigned("test-repo", "main", "data.csv").await.unwrap();
+        assert!(stats.physical_address.starts_with("https://"));
+        assert_eq!(stats.physical_address_expiry, Some(1704070800));
+        
+        let location = client.get_presigned_upload_location("test-repo", "main", "data.csv").await.unwrap();
+        assert!(location.presigned_url.unwrap().contains("X-Amz-Signature"));
+    }
+
     #[tokio::test]
     async fn test_error_handling() {
         let mock_server = MockServer::start().await;
@@ -372,6 +1285,239 @@ impl LakeFSClient {
         assert_eq!(branch.commit_id, "abc123");
     }
 
+    #[tokio::test]
+    async fn test_squash_merge_options() {
+        let mock_server = MockServer::start().await;
+        
+        Mock::given(method("POST"))
+            .and(path("/repositories/repo/refs/feature/merge/main"))
+            .and(body_json(serde_json::json!({
+                "message": "Squash feature",
+                "squash_merge": true
+            })))
+            .respond_with(ResponseTemplate::new(200)
+                .set_body_json(serde_json::json!({ "id": "squashed" })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = LakeFSClient::new(mock_server.uri(), "Bearer test-token");
+        let options = MergeOptions {
+            message: Some("Squash feature".to_string()),
+            squash_merge: true,
+            ..Default::default()
+        };
+        let result = client.merge_with("repo", "feature", "main", &options).await.unwrap();
+        assert_eq!(result.id, "squashed");
+    }
+
+    #[tokio::test]
+    async fn test_dump_and_restore_refs() {
+        let mock_server = MockServer::start().await;
+        let manifest = serde_json::json!({
+            "commits_meta_range_id": "c1",
+            "tags_meta_range_id": "t1",
+            "branches_meta_range_id": "b1"
+        });
+        
+        Mock::given(method("PUT"))
+            .and(path("/repositories/test-repo/refs/dump"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(&manifest))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/repositories/restored/refs/restore"))
+            .and(body_json(&manifest))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = LakeFSClient::new(mock_server.uri(), "Bearer test-token");
+        let dump = client.dump_refs("test-repo").await.unwrap();
+        assert_eq!(dump.branches_meta_range_id, "b1");
+        client.restore_refs("restored", &dump).await.unwrap();
+    }
+
+    fn empty_page() -> serde_json::Value {
+        serde_json::json!({
+            "results": [],
+            "pagination": { "has_more": false, "max_per_page": 1000, "next_offset": null, "results": 0 }
+        })
+    }
+
+    #[tokio::test]
+    async fn test_amend_commit() {
+        let mock_server = MockServer::start().await;
+        
+        Mock::given(method("GET"))
+            .and(path("/repositories/repo/branches/main"))
+            .respond_with(ResponseTemplate::new(200)
+                .set_body_json(serde_json::json!({ "id": "main", "commit_id": "tip" })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repositories/repo/commits/tip"))
+            .respond_with(ResponseTemplate::new(200)
+                .set_body_json(serde_json::json!({
+                    "id": "tip",
+                    "parents": ["base"],
+                    "committer": "admin",
+                    "message": "tpyo",
+                    "creation_date": "2024-01-01T00:00:00Z",
+                    "meta_range_id": "mr",
+                    "metadata": { "owner": "data-team" }
+                })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repositories/repo/branches/main/diff"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(empty_page()))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repositories/repo/refs/base/diff/tip"))
+            .respond_with(ResponseTemplate::new(200)
+                .set_body_json(serde_json::json!({
+                    "results": [
+                        { "path": "new.csv", "path_type": "object", "type": "added" },
+                        { "path": "old.csv", "path_type": "object", "type": "removed" }
+                    ],
+                    "pagination": { "has_more": false, "max_per_page": 1000, "next_offset": null, "results": 2 }
+                })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repositories/repo/refs/tip/objects/stat"))
+            .and(query_param("path", "new.csv"))
+            .respond_with(ResponseTemplate::new(200)
+                .set_body_json(serde_json::json!({
+                    "path": "new.csv",
+                    "path_type": "object",
+                    "physical_address": "s3://bucket/data/abc",
+                    "checksum": "abc",
+                    "size_bytes": 3,
+                    "mtime": "2024-01-01T00:00:00Z"
+                })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/repositories/repo/branches/main/hard_reset"))
+            .and(query_param("ref", "base"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/repositories/repo/branches/main/objects"))
+            .and(query_param("path", "new.csv"))
+            .and(body_json(serde_json::json!({
+                "physical_address": "s3://bucket/data/abc",
+                "checksum": "abc",
+                "size_bytes": 3,
+                "mtime": 1704067200
+            })))
+            .respond_with(ResponseTemplate::new(201)
+                .set_body_json(serde_json::json!({
+                    "path": "new.csv",
+                    "path_type": "object",
+                    "physical_address": "s3://bucket/data/abc",
+                    "checksum": "abc",
+                    "size_bytes": 3,
+                    "mtime": "2024-01-01T00:00:00Z"
+                })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path("/repositories/repo/branches/main/objects"))
+            .and(query_param("path", "old.csv"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/repositories/repo/branches/main/commits"))
+            .and(body_json(serde_json::json!({
+                "message": "typo",
+                "metadata": { "owner": "data-team" }
+            })))
+            .respond_with(ResponseTemplate::new(201)
+                .set_body_json(serde_json::json!({
+                    "id": "amended",
+                    "parents": ["base"],
+                    "committer": "admin",
+                    "message": "typo",
+                    "creation_date": "2024-01-01T00:00:00Z",
+                    "meta_range_id": "mr2",
+                    "metadata": { "owner": "data-team" }
+                })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = LakeFSClient::new(mock_server.uri(), "Bearer test-token");
+        let commit = client.amend_commit("repo", "main", Some("tip"), Some("typo"), None).await.unwrap();
+        assert_eq!(commit.id, "amended");
+
+        let moved = client.amend_commit("repo", "main", Some("older"), Some("typo"), None).await;
+        assert!(matches!(moved, Err(Error::Conflict(_))));
+    }
+
+    #[tokio::test]
+    async fn test_meta_range_and_range_locations() {
+        let mock_server = MockServer::start().await;
+        
+        Mock::given(method("GET"))
+            .and(path("/repositories/test-repo/metadata/meta_range/mr1"))
+            .respond_with(ResponseTemplate::new(200)
+                .set_body_json(serde_json::json!({
+                    "location": "s3://bucket/repo/_lakefs/mr1"
+                })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repositories/test-repo/metadata/range/r1"))
+            .respond_with(ResponseTemplate::new(200)
+                .set_body_json(serde_json::json!({
+                    "location": "s3://bucket/repo/_lakefs/r1"
+                })))
+            .mount(&mock_server)
+            .await;
+
+        let client = LakeFSClient::new(mock_server.uri(), "Bearer test-token");
+        let meta_range = client.get_meta_range("test-repo", "mr1").await.unwrap();
+        assert_eq!(meta_range.location, "s3://bucket/repo/_lakefs/mr1");
+        let range = client.get_range("test-repo", "r1").await.unwrap();
+        assert_eq!(range.location, "s3://bucket/repo/_lakefs/r1");
+    }
+
+    #[tokio::test]
+    async fn test_copy_object() {
+        let mock_server = MockServer::start().await;
+        
+        Mock::given(method("POST"))
+            .and(path("/repositories/test-repo/branches/dev/objects/copy"))
+            .and(query_param("dest_path", "promoted/a.csv"))
+            .and(body_json(serde_json::json!({ "src_path": "staging/a.csv", "src_ref": "main" })))
+            .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!({
+                "path": "promoted/a.csv",
+                "path_type": "object",
+                "physical_address": "s3://bucket/data/abc",
+                "checksum": "abc",
+                "size_bytes": 3,
+                "mtime": "2024-01-01T00:00:00Z"
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = LakeFSClient::new(mock_server.uri(), "Bearer test-token");
+        let stats = client.copy_object("test-repo", "dev", "promoted/a.csv", "main", "staging/a.csv").await.unwrap();
+        assert_eq!(stats.path, "promoted/a.csv");
+        assert_eq!(stats.checksum.to_string(), "abc");
+    }
+
     #[tokio::test]
     async fn test_upload_download_object() {
         let mock_server = MockServer::start().await;
@@ -415,4 +1561,243 @@ impl LakeFSClient {
         // Test download
         let data = client.download_object("test-repo", "main", "test.txt").await.unwrap();
         assert_eq!(data, Bytes::from("test content"));
-    }
\ No newline at end of file
+    }
+
+    #[tokio::test]
+    async fn test_upload_download_object_stream() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .and(path("/repositories/test-repo/branches/main/objects"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "path": "test.txt",
+                "path_type": "object",
+                "physical_address": "s3://bucket/object",
+                "checksum": "checksum123",
+                "size_bytes": 12,
+                "mtime": "2024-01-01T00:00:00Z"
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repositories/test-repo/refs/main/objects"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(b"test content"))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        let client = LakeFSClient::new(mock_server.uri(), "Bearer test-token");
+
+        let chunks = stream::iter(vec![Ok(Bytes::from("test ")), Ok(Bytes::from("content"))]);
+        let stats = client.upload_object_stream("test-repo", "main", "test.txt", chunks).await.unwrap();
+        assert_eq!(stats.path, "test.txt");
+        let requests = mock_server.received_requests().await.unwrap();
+        assert_eq!(requests[0].body, b"test content".to_vec());
+
+        let downloaded: Vec<Bytes> = client.download_object_stream("test-repo", "main", "test.txt")
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(downloaded.concat(), b"test content".to_vec());
+    }
+
+    #[tokio::test]
+    async fn test_upload_sends_user_metadata() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .and(path("/repositories/test-repo/branches/main/objects"))
+            .and(header("X-Lakefs-Meta-content-encoding", "zstd"))
+            .and(header("content-type", "text/csv"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!({
+                "path": "a.csv",
+                "path_type": "object",
+                "physical_address": "s3://bucket/object",
+                "checksum": "abc",
+                "size_bytes": 3,
+                "mtime": "2024-01-01T00:00:00Z"
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        let client = LakeFSClient::new(mock_server.uri(), "Bearer test-token");
+
+        let options = UploadOptions {
+            metadata: HashMap::from([("content-encoding".to_string(), "zstd".to_string())]),
+            content_type: Some("text/csv".to_string()),
+            ..Default::default()
+        };
+        let stats = client.upload_object_with("test-repo", "main", "a.csv", Bytes::from("abc"), &options).await.unwrap();
+        assert_eq!(stats.path, "a.csv");
+    }
+
+    #[tokio::test]
+    async fn test_conditional_requests() {
+        let mock_server = MockServer::start().await;
+        let current = Checksum::parse("0123456789abcdef0123456789abcdef");
+        
+        Mock::given(method("GET"))
+            .and(path("/repositories/test-repo/refs/main/objects"))
+            .and(header("if-none-match", "\"0123456789abcdef0123456789abcdef\""))
+            .respond_with(ResponseTemplate::new(304))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repositories/test-repo/refs/main/objects"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(b"new content"))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/repositories/test-repo/branches/main/objects"))
+            .and(header("if-match", "\"ffffffffffffffffffffffffffffffff\""))
+            .respond_with(ResponseTemplate::new(412).set_body_string("checksum mismatch"))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path("/repositories/test-repo/branches/main/objects"))
+            .and(header("if-match", "\"0123456789abcdef0123456789abcdef\""))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = LakeFSClient::new(mock_server.uri(), "Bearer test-token");
+        
+        let unchanged = client
+            .download_object_stream_if_none_match("test-repo", "main", "a.txt", &current)
+            .await
+            .unwrap();
+        assert!(unchanged.is_none());
+        let changed: Vec<Bytes> = client
+            .download_object_stream_if_none_match("test-repo", "main", "a.txt", &Checksum::Sha256("0".repeat(64)))
+            .await
+            .unwrap()
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(changed.concat(), b"new content".to_vec());
+        
+        let stale = UploadOptions {
+            precondition: Some(Precondition::IfMatch(Checksum::parse("ffffffffffffffffffffffffffffffff"))),
+            ..Default::default()
+        };
+        let result = client.upload_object_with("test-repo", "main", "a.txt", Bytes::from("x"), &stale).await;
+        assert!(matches!(result, Err(Error::PreconditionFailed(message)) if message == "checksum mismatch"));
+        
+        client
+            .delete_object_if("test-repo", "main", "a.txt", Some(&Precondition::IfMatch(current)))
+            .await
+            .unwrap();
+        assert_eq!(Precondition::IfAbsent.header(), (reqwest::header::IF_NONE_MATCH, "*".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_builder_retries_transient_errors() {
+        let mock_server = MockServer::start().await;
+        
+        Mock::given(method("GET"))
+            .and(path("/repositories/test-repo"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(2)
+            .mount(&mock_server)
+            .await;
+        
+        Mock::given(method("GET"))
+            .and(path("/repositories/test-repo"))
+            .respond_with(ResponseTemplate::new(200)
+                .set_body_json(serde_json::json!({
+                    "id": "test-repo",
+                    "storage_namespace": "s3://bucket",
+                    "default_branch": "main",
+                    "creation_date": "2024-01-01T00:00:00Z"
+                })))
+            .mount(&mock_server)
+            .await;
+
+        #[derive(Default)]
+        struct RetryRecorder(std::sync::Mutex<Vec<u32>>);
+        
+        impl ClientMiddleware for RetryRecorder {
+            fn on_request(&self, _request: &mut reqwest::Request) -> Result<()> {
+                Ok(())
+            }
+            
+            fn on_retry(&self, attempt: u32) {
+                self.0.lock().unwrap().push(attempt);
+            }
+        }
+        
+        let retries = Arc::new(RetryRecorder::default());
+        let client = LakeFSClient::builder()
+            .base_url(mock_server.uri())
+            .auth_header("Bearer test-token")
+            .retry_policy(RetryPolicy::exponential(3)
+                .with_backoff(Duration::from_millis(1), Duration::from_millis(5)))
+            .middleware(retries.clone())
+            .build()
+            .unwrap();
+        
+        let repo = client.get_repository("test-repo").await.unwrap();
+        assert_eq!(repo.id, "test-repo");
+        assert_eq!(*retries.0.lock().unwrap(), vec![1, 2]);
+    }
+
+    #[tokio::test]
+    async fn test_builder_auth_provider_and_middleware() {
+        struct TenantHeader;
+        
+        impl ClientMiddleware for TenantHeader {
+            fn on_request(&self, request: &mut reqwest::Request) -> Result<()> {
+                request.headers_mut().insert("X-Tenant-Id", "acme".parse().unwrap());
+                Ok(())
+            }
+        }
+        
+        #[derive(Default)]
+        struct StatusRecorder(std::sync::Mutex<Vec<u16>>);
+        
+        impl ClientMiddleware for StatusRecorder {
+            fn on_request(&self, _request: &mut reqwest::Request) -> Result<()> {
+                Ok(())
+            }
+            
+            fn on_response(&self, response: &reqwest::Response) {
+                self.0.lock().unwrap().push(response.status().as_u16());
+            }
+        }
+        
+        let mock_server = MockServer::start().await;
+        
+        Mock::given(method("GET"))
+            .and(path("/repositories/test-repo/refs/main/objects/stat"))
+            .and(query_param("path", "data/a b.csv"))
+            .and(header("Authorization", "Basic dXNlcjpwYXNz"))
+            .and(header("X-Tenant-Id", "acme"))
+            .respond_with(ResponseTemplate::new(200)
+                .set_body_json(serde_json::json!({
+                    "path": "data/a b.csv",
+                    "path_type": "object",
+                    "physical_address": "s3://bucket/object",
+                    "checksum": "checksum123",
+                    "size_bytes": 10,
+                    "mtime": "2024-01-01T00:00:00Z"
+                })))
+            .mount(&mock_server)
+            .await;
+
+        let provider = Arc::new(lakefs_auth::basic::BasicAuth::new("user".into(), "pass".into()));
+        let recorder = Arc::new(StatusRecorder::default());
+        let client = LakeFSClient::builder()
+            .base_url(mock_server.uri())
+            .auth_provider(provider)
+            .middleware(Arc::new(TenantHeader))
+            .middleware(recorder.clone())
+            .build()
+            .unwrap();
+        
+        let stats = client.get_object("test-repo", "main", "data/a b.csv").await.unwrap();
+        assert_eq!(stats.size_bytes, 10);
+        assert_eq!(*recorder.0.lock().unwrap(), vec![200]);
+    }
+}
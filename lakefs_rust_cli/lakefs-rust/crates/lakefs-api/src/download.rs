@@ -0,0 +1,437 @@
+//! Downloading objects into local files, resuming transfers that break off:
+//! `fs download -r`, local checkouts and anything else that fetches a batch
+//! of objects.
+
+use crate::api::{DownloadStream, LakeFSApi};
+use crate::{Checksum, Error, Result, RetryPolicy};
+use futures::channel::mpsc;
+use futures::stream::{self, Stream, StreamExt};
+use futures::SinkExt;
+use md5::Md5;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use tokio::fs;
+use tokio::io::{AsyncSeekExt, AsyncWriteExt};
+
+/// Suffix of the temporary file a download is written to before it is
+/// renamed into place.
+pub const TEMP_SUFFIX: &str = ".lakectl-tmp";
+
+/// Where a download to `destination` is staged.
+pub fn temp_path(destination: &Path) -> PathBuf {
+    let mut name = destination.file_name().unwrap_or_default().to_os_string();
+    name.push(TEMP_SUFFIX);
+    destination.with_file_name(name)
+}
+
+/// Settings for [`download_objects`].
+#[derive(Debug, Clone)]
+pub struct DownloadOptions {
+    /// Objects transferred at the same time
+    pub concurrency: usize,
+    /// How often a transfer that broke off is resumed, and how long to wait
+    /// before each attempt. Failed requests are retried by the client's own
+    /// policy.
+    pub retry_policy: RetryPolicy,
+}
+
+impl Default for DownloadOptions {
+    fn default() -> Self {
+        Self {
+            concurrency: 8,
+            retry_policy: RetryPolicy::exponential(3),
+        }
+    }
+}
+
+/// One object for [`download_objects`] to fetch.
+#[derive(Debug, Clone)]
+pub struct DownloadRequest {
+    pub path: String,
+    pub destination: PathBuf,
+    /// What to check the content against, instead of the ETag the server
+    /// sends with it
+    pub checksum: Option<Checksum>,
+    /// Checksum of what `destination` holds already: the object is only
+    /// sent if its own differs, and reported `Unchanged` otherwise
+    pub if_none_match: Option<Checksum>,
+}
+
+impl From<(String, PathBuf)> for DownloadRequest {
+    fn from((path, destination): (String, PathBuf)) -> Self {
+        Self { path, destination, checksum: None, if_none_match: None }
+    }
+}
+
+/// An object's content from some offset on, as [`LakeFSApi::get_object_range`]
+/// returns it.
+pub struct ObjectRange {
+    /// Whether the server honoured the offset (206 Partial Content) rather
+    /// than sending the whole object
+    pub partial: bool,
+    pub etag: Option<Checksum>,
+    /// Bytes the server is sending, when it said
+    pub size: Option<u64>,
+    pub content: DownloadStream,
+}
+
+/// Progress of one object in a [`download_objects`] batch. Events for
+/// different objects interleave; every object ends with `Finished`,
+/// `Unchanged` or `Failed`.
+#[derive(Debug)]
+pub enum DownloadEvent {
+    /// The server started sending the object, once per object however
+    /// often it is resumed; `size` is its length, when known
+    Started { path: String, size: Option<u64> },
+    /// A chunk of `bytes` was written
+    Progress { path: String, bytes: u64 },
+    /// The transfer broke off and is resumed for the `attempt`th time
+    Retrying { path: String, attempt: u32, error: Error },
+    /// The object was written to its destination in full
+    Finished { path: String, destination: PathBuf, bytes: u64 },
+    /// The object still has the request's `if_none_match` checksum, so its
+    /// destination was left alone
+    Unchanged { path: String },
+    Failed { path: String, error: Error },
+}
+
+/// Events waiting for the consumer before transfers pause.
+const EVENT_BUFFER: usize = 64;
+
+/// A download written so far, kept across resumed attempts.
+struct Partial {
+    file: fs::File,
+    written: u64,
+    md5: Md5,
+    sha256: Sha256,
+    expected: Option<Checksum>,
+    started: bool,
+}
+
+impl Partial {
+    async fn restart(&mut self) -> Result<()> {
+        self.file.set_len(0).await?;
+        self.file.rewind().await?;
+        self.written = 0;
+        self.md5 = Md5::new();
+        self.sha256 = Sha256::new();
+        Ok(())
+    }
+
+    /// Compare the content with the expected checksum when it is an MD5 or
+    /// SHA-256 digest. Multipart ETags cannot be recomputed and are
+    /// accepted as-is.
+    fn verify(self, path: &str) -> Result<()> {
+        let Some(expected) = self.expected else {
+            return Ok(());
+        };
+        let digests = [
+            Checksum::Md5(format!("{:x}", self.md5.finalize())),
+            Checksum::Sha256(format!("{:x}", self.sha256.finalize())),
+        ];
+        for actual in digests {
+            if expected.matches(&actual) == Some(false) {
+                return Err(Error::ChecksumMismatch {
+                    path: path.to_string(),
+                    expected: expected.to_string(),
+                    actual: actual.to_string(),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Download each of `objects` at `reference` through `client`, at most
+/// `options.concurrency` at a time, reporting progress as a stream of
+/// events.
+///
+/// Parent directories are created as needed. Each object is written to
+/// [`temp_path`] of its destination, checked against its checksum and only
+/// then renamed into place, so a failed download never looks complete.
+/// Nothing is transferred until the stream is polled, and dropping it
+/// cancels the transfers still running.
+///
+/// ```no_run
+/// # async fn example(client: lakefs_api::LakeFSClient) {
+/// use futures::StreamExt;
+/// use lakefs_api::download::{download_objects, DownloadEvent, DownloadOptions};
+///
+/// let objects = vec![("data/a.csv".to_string(), "out/a.csv".into()).into()];
+/// let mut events = std::pin::pin!(download_objects(&client, "repo", "main", objects, DownloadOptions::default()));
+/// while let Some(event) = events.next().await {
+///     if let DownloadEvent::Failed { path, error } = event {
+///         eprintln!("{}: {}", path, error);
+///     }
+/// }
+/// # }
+/// ```
+pub fn download_objects<'a>(
+    client: &'a dyn LakeFSApi,
+    repository: &'a str,
+    reference: &'a str,
+    objects: Vec<DownloadRequest>,
+    options: DownloadOptions,
+) -> impl Stream<Item = DownloadEvent> + Send + 'a {
+    let (sender, receiver) = mpsc::channel(EVENT_BUFFER);
+    let concurrency = options.concurrency.max(1);
+    let transfers = stream::iter(objects).for_each_concurrent(concurrency, move |request| {
+        let mut events = sender.clone();
+        let retry_policy = options.retry_policy.clone();
+        async move {
+            let result = download_to(client, repository, reference, &request, &retry_policy, &mut events).await;
+            let DownloadRequest { path, destination, .. } = request;
+            let event = match result {
+                Ok(Some(bytes)) => DownloadEvent::Finished { path, destination, bytes },
+                Ok(None) => DownloadEvent::Unchanged { path },
+                Err(error) => DownloadEvent::Failed { path, error },
+            };
+            let _ = events.send(event).await;
+        }
+    });
+    // Drive the transfers while handing out their events; the receiver
+    // ends once the last transfer has dropped its sender
+    let driver = stream::once(transfers).filter_map(|_| async { None });
+    stream::select(receiver, driver)
+}
+
+/// Fetch `request` into its destination, returning the bytes written, or
+/// `None` when it is unchanged.
+async fn download_to(
+    client: &dyn LakeFSApi,
+    repository: &str,
+    reference: &str,
+    request: &DownloadRequest,
+    retry_policy: &RetryPolicy,
+    events: &mut mpsc::Sender<DownloadEvent>,
+) -> Result<Option<u64>> {
+    let (path, destination) = (request.path.as_str(), request.destination.as_path());
+    if let Some(parent) = destination.parent() {
+        fs::create_dir_all(parent).await?;
+    }
+    let temp = temp_path(destination);
+    let mut partial = Partial {
+        file: fs::File::create(&temp).await?,
+        written: 0,
+        md5: Md5::new(),
+        sha256: Sha256::new(),
+        expected: request.checksum.clone(),
+        started: false,
+    };
+
+    let mut attempt = 0;
+    let result = loop {
+        match fetch(client, repository, reference, request, &mut partial, events).await {
+            // Only transfers cut off by the network are worth resuming
+            Err(Error::Http(error)) if attempt < retry_policy.max_retries => {
+                tokio::time::sleep(retry_policy.backoff(attempt)).await;
+                attempt += 1;
+                let _ = events
+                    .send(DownloadEvent::Retrying { path: path.to_string(), attempt, error: Error::Http(error) })
+                    .await;
+            }
+            result => break result,
+        }
+    };
+
+    let finished = async {
+        if !result? {
+            drop(partial);
+            fs::remove_file(&temp).await?;
+            return Ok(None);
+        }
+        partial.file.sync_all().await?;
+        let written = partial.written;
+        partial.verify(path)?;
+        fs::rename(&temp, destination).await?;
+        Ok(Some(written))
+    };
+    let outcome = finished.await;
+    if outcome.is_err() {
+        let _ = fs::remove_file(&temp).await;
+    }
+    outcome
+}
+
+/// Append the rest of the object to `partial`, resuming where an earlier
+/// attempt stopped. Returns `false` when the server says the destination
+/// is current.
+async fn fetch(
+    client: &dyn LakeFSApi,
+    repository: &str,
+    reference: &str,
+    request: &DownloadRequest,
+    partial: &mut Partial,
+    events: &mut mpsc::Sender<DownloadEvent>,
+) -> Result<bool> {
+    let path = request.path.as_str();
+    // Once the object started arriving, it is wanted whatever it holds
+    let if_none_match = request.if_none_match.as_ref().filter(|_| !partial.started);
+    let Some(range) = client.get_object_range(repository, reference, path, partial.written, if_none_match).await? else {
+        return Ok(false);
+    };
+    if partial.written > 0 && !range.partial {
+        partial.restart().await?;
+    }
+    if partial.expected.is_none() {
+        partial.expected = range.etag;
+    }
+    if !partial.started {
+        partial.started = true;
+        let _ = events.send(DownloadEvent::Started { path: path.to_string(), size: range.size }).await;
+    }
+
+    let mut content = range.content;
+    while let Some(chunk) = content.next().await {
+        let chunk = chunk?;
+        partial.file.write_all(&chunk).await?;
+        partial.md5.update(&chunk);
+        partial.sha256.update(&chunk);
+        partial.written += chunk.len() as u64;
+        let _ = events.send(DownloadEvent::Progress { path: path.to_string(), bytes: chunk.len() as u64 }).await;
+    }
+    partial.file.flush().await?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::MockLakeFSApi;
+    use crate::LakeFSClient;
+    use bytes::Bytes;
+    use lakefs_test_utils::scenario::MockLakeFS;
+    use tempfile::TempDir;
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    async fn collect(client: &LakeFSClient, objects: Vec<DownloadRequest>) -> Vec<DownloadEvent> {
+        let options = DownloadOptions { concurrency: 2, ..Default::default() };
+        download_objects(client, "repo", "main", objects, options).collect().await
+    }
+
+    #[tokio::test]
+    async fn test_download_objects_reports_each_object() {
+        let server = MockLakeFS::new()
+            .with_repo("repo")
+            .with_branch("main")
+            .with_object("data/a.csv", "a,b\n1,2\n")
+            .with_object("data/b.csv", "c,d\n")
+            .start()
+            .await;
+        let client = LakeFSClient::new(server.uri(), "Bearer test-token");
+        let dir = TempDir::new().unwrap();
+
+        let objects = ["data/a.csv", "data/b.csv", "data/missing.csv"]
+            .iter()
+            .map(|object| (object.to_string(), dir.path().join("out").join(object)).into())
+            .collect();
+        let events = collect(&client, objects).await;
+
+        let mut finished: Vec<(&str, u64)> = events
+            .iter()
+            .filter_map(|event| match event {
+                DownloadEvent::Finished { path, bytes, .. } => Some((path.as_str(), *bytes)),
+                _ => None,
+            })
+            .collect();
+        finished.sort();
+        assert_eq!(finished, vec![("data/a.csv", 8), ("data/b.csv", 4)]);
+        assert!(events.iter().any(|event| matches!(
+            event,
+            DownloadEvent::Failed { path, error: Error::NotFound(_) } if path == "data/missing.csv"
+        )));
+        let progress: u64 = events
+            .iter()
+            .filter_map(|event| match event {
+                DownloadEvent::Progress { bytes, .. } => Some(*bytes),
+                _ => None,
+            })
+            .sum();
+        assert_eq!(progress, 12);
+
+        assert_eq!(std::fs::read_to_string(dir.path().join("out/data/a.csv")).unwrap(), "a,b\n1,2\n");
+        assert!(!dir.path().join("out/data/missing.csv").exists());
+        assert!(!temp_path(&dir.path().join("out/data/missing.csv")).exists());
+    }
+
+    fn range(partial: bool, chunks: Vec<Result<Bytes>>) -> ObjectRange {
+        ObjectRange { partial, etag: None, size: None, content: Box::pin(stream::iter(chunks)) }
+    }
+
+    #[tokio::test]
+    async fn test_download_objects_resumes_where_it_broke_off() {
+        let mut client = MockLakeFSApi::new();
+        client
+            .expect_get_object_range()
+            .withf(|_, _, _, offset, _| *offset == 0)
+            .times(1)
+            .returning(|_, _, _, _, _| {
+                let cut = reqwest::Client::new().get("not a url").build().unwrap_err();
+                Ok(Some(range(false, vec![Ok(Bytes::from("abcd")), Err(Error::Http(cut))])))
+            });
+        client
+            .expect_get_object_range()
+            .withf(|_, _, _, offset, if_none_match| *offset == 4 && if_none_match.is_none())
+            .times(1)
+            .returning(|_, _, _, _, _| Ok(Some(range(true, vec![Ok(Bytes::from("efgh"))]))));
+        let dir = TempDir::new().unwrap();
+
+        let request = DownloadRequest {
+            checksum: Some(Checksum::parse("e8dc4081b13434b45189a720b77b6818")),
+            if_none_match: Some(Checksum::parse("00000000000000000000000000000000")),
+            ..("a.txt".to_string(), dir.path().join("a.txt")).into()
+        };
+        let options = DownloadOptions {
+            retry_policy: RetryPolicy::exponential(1).with_backoff(std::time::Duration::ZERO, std::time::Duration::ZERO),
+            ..Default::default()
+        };
+        let events: Vec<DownloadEvent> = download_objects(&client, "repo", "main", vec![request], options).collect().await;
+
+        let started = events.iter().filter(|event| matches!(event, DownloadEvent::Started { .. })).count();
+        assert_eq!(started, 1);
+        assert!(events.iter().any(|event| matches!(event, DownloadEvent::Retrying { attempt: 1, .. })));
+        assert!(matches!(events.last(), Some(DownloadEvent::Finished { bytes: 8, .. })), "{:?}", events);
+        assert_eq!(std::fs::read_to_string(dir.path().join("a.txt")).unwrap(), "abcdefgh");
+    }
+
+    #[tokio::test]
+    async fn test_download_objects_leaves_current_destination_alone() {
+        let mut client = MockLakeFSApi::new();
+        client
+            .expect_get_object_range()
+            .withf(|_, _, _, _, if_none_match| if_none_match.is_some())
+            .times(1)
+            .returning(|_, _, _, _, _| Ok(None));
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("a.txt"), "current").unwrap();
+
+        let request = DownloadRequest {
+            if_none_match: Some(Checksum::parse("e8dc4081b13434b45189a720b77b6818")),
+            ..("a.txt".to_string(), dir.path().join("a.txt")).into()
+        };
+        let events: Vec<DownloadEvent> = download_objects(&client, "repo", "main", vec![request], DownloadOptions::default()).collect().await;
+        assert!(matches!(events.as_slice(), [DownloadEvent::Unchanged { .. }]), "{:?}", events);
+        assert_eq!(std::fs::read_to_string(dir.path().join("a.txt")).unwrap(), "current");
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_download_objects_rejects_content_not_matching_etag() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repositories/repo/refs/main/objects"))
+            .and(query_param("path", "a.txt"))
+            .respond_with(ResponseTemplate::new(200)
+                .insert_header("ETag", "\"5d41402abc4b2a76b9719d911017c592\"")
+                .set_body_bytes(b"not hello"))
+            .mount(&mock_server)
+            .await;
+        let client = LakeFSClient::new(mock_server.uri(), "Bearer test-token");
+        let dir = TempDir::new().unwrap();
+
+        let events = collect(&client, vec![("a.txt".to_string(), dir.path().join("a.txt")).into()]).await;
+        assert!(matches!(events.last(), Some(DownloadEvent::Failed { error: Error::ChecksumMismatch { .. }, .. })));
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+}
@@ -20,8 +20,33 @@ pub enum Error {
     #[error("Invalid argument: {0}")]
     InvalidArgument(String),
     
+    #[error("Conflict: {0}")]
+    Conflict(String),
+    
+    #[error("Precondition failed: {0}")]
+    PreconditionFailed(String),
+    
+    #[error("Checksum mismatch for {path}: expected {expected}, got {actual}")]
+    ChecksumMismatch {
+        path: String,
+        expected: String,
+        actual: String,
+    },
+    
     #[error("I/O error: {0}")]
     Io(#[from] std::io::Error),
+    
+    #[error("Invalid configuration: {0}")]
+    Config(String),
+    
+    #[error("Cassette error: {0}")]
+    Cassette(String),
+}
+
+impl From<lakefs_auth::Error> for Error {
+    fn from(err: lakefs_auth::Error) -> Self {
+        Error::Auth(err.to_string())
+    }
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
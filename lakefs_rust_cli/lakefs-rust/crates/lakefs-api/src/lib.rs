@@ -1,14 +1,39 @@
+pub mod api;
+pub mod builder;
+pub mod cassette;
+pub mod checksum;
 pub mod client;
+pub mod download;
 pub mod error;
+pub mod graph;
+pub mod middleware;
 pub mod models;
+pub mod provenance;
+pub mod resolve;
+pub mod response_cache;
+pub mod retry;
 pub mod uri;
 
-pub use client::LakeFSClient;
+pub use api::{DownloadStream, LakeFSApi, UploadStream};
+#[cfg(feature = "mock")]
+pub use api::MockLakeFSApi;
+pub use builder::{ClientConfig, LakeFSClientBuilder};
+pub use cassette::Cassette;
+pub use checksum::Checksum;
+pub use client::{default_user_agent, LakeFSClient};
+pub use download::{DownloadEvent, DownloadOptions, DownloadRequest};
 pub use error::{Error, Result};
+pub use graph::walk_commits;
+pub use middleware::{ClientMiddleware, HeaderMiddleware};
+pub use provenance::{path_history, PathChange};
+pub use resolve::{resolve_ref, resolve_reference, resolve_uri, RefKind, ResolvedRef};
+pub use response_cache::ResponseCache;
+pub use retry::RetryPolicy;
 pub use uri::LakeFSUri;
 
 // Re-export common types
 pub use models::{
-    Repository, Branch, Commit, ObjectStats,
-    DiffResult, MergeResult,
+    Repository, Branch, Tag, Commit, ObjectStats, ListEntry, ListObjectsOptions,
+    DiffResult, MergeBase, MergeResult, PaginationParams, StagingLocation, StorageConfig, StorageUri,
+    RefsDump, ObjectStage, MergeOptions, Precondition, UploadOptions,
 };
@@ -0,0 +1,76 @@
+use crate::error::{Error, Result};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+
+/// Hook invoked for every outgoing request made by `LakeFSClient`.
+///
+/// Middleware runs after the `Authorization` header has been applied, in the
+/// order it was registered on the builder.
+pub trait ClientMiddleware: Send + Sync {
+    fn on_request(&self, request: &mut reqwest::Request) -> Result<()>;
+
+    /// Observe a response before its status is checked. Called once per
+    /// attempt, so retried requests are seen more than once.
+    fn on_response(&self, _response: &reqwest::Response) {}
+
+    /// Called before retry number `attempt` (starting at 1) is sent.
+    fn on_retry(&self, _attempt: u32) {}
+}
+
+/// Middleware that adds a fixed set of headers (tenant IDs, tracing context)
+/// to every request. Headers already present on the request are replaced.
+#[derive(Debug, Clone, Default)]
+pub struct HeaderMiddleware {
+    headers: HeaderMap,
+}
+
+impl HeaderMiddleware {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn header(mut self, name: &str, value: &str) -> Result<Self> {
+        let name = HeaderName::from_bytes(name.as_bytes())
+            .map_err(|_| Error::Config(format!("Invalid header name '{}'", name)))?;
+        let value = HeaderValue::from_str(value)
+            .map_err(|_| Error::Config(format!("Invalid value for header '{}'", name)))?;
+        self.headers.insert(name, value);
+        Ok(self)
+    }
+}
+
+impl ClientMiddleware for HeaderMiddleware {
+    fn on_request(&self, request: &mut reqwest::Request) -> Result<()> {
+        for (name, value) in &self.headers {
+            request.headers_mut().insert(name.clone(), value.clone());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_header_middleware_sets_headers() {
+        let middleware = HeaderMiddleware::new()
+            .header("X-Tenant-Id", "acme").unwrap()
+            .header("X-Request-Source", "etl").unwrap();
+
+        let mut request = reqwest::Client::new()
+            .get("http://localhost/api/v1/repositories")
+            .header("X-Tenant-Id", "other")
+            .build()
+            .unwrap();
+        middleware.on_request(&mut request).unwrap();
+
+        assert_eq!(request.headers()["X-Tenant-Id"], "acme");
+        assert_eq!(request.headers()["X-Request-Source"], "etl");
+    }
+
+    #[test]
+    fn test_header_middleware_rejects_invalid_header() {
+        assert!(matches!(HeaderMiddleware::new().header("bad header", "v"), Err(Error::Config(_))));
+        assert!(matches!(HeaderMiddleware::new().header("X-Ok", "bad\nvalue"), Err(Error::Config(_))));
+    }
+}
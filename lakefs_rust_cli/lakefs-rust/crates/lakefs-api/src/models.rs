@@ -1,61 +1,212 @@
+//! Request and response bodies of the lakeFS API.
+//!
+//! Response models ignore fields they do not know, so newer servers keep
+//! working. Build with the `strict-models` feature to reject unknown fields
+//! instead, which shows where the models have fallen behind a server.
+
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
 use std::collections::HashMap;
 
+pub use crate::checksum::Checksum;
+
+/// lakeFS sends timestamps as Unix seconds. RFC 3339 strings are accepted
+/// too, since earlier versions of these models wrote them.
+mod unix_timestamp {
+    use chrono::{DateTime, Utc};
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Raw {
+        Seconds(i64),
+        Text(String),
+    }
+
+    pub fn serialize<S: Serializer>(value: &DateTime<Utc>, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i64(value.timestamp())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<DateTime<Utc>, D::Error> {
+        match Raw::deserialize(deserializer)? {
+            Raw::Seconds(seconds) => DateTime::from_timestamp(seconds, 0)
+                .ok_or_else(|| D::Error::custom(format!("timestamp {} is out of range", seconds))),
+            Raw::Text(text) => DateTime::parse_from_rfc3339(&text)
+                .map(|time| time.with_timezone(&Utc))
+                .map_err(D::Error::custom),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "strict-models", serde(deny_unknown_fields))]
 pub struct Repository {
     pub id: String,
     pub storage_namespace: String,
     pub default_branch: String,
+    #[serde(with = "unix_timestamp")]
     pub creation_date: DateTime<Utc>,
+    /// Read-only repositories reject writes to every branch
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub read_only: Option<bool>,
+    /// Storage the repository lives on, for servers with several blockstores
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub storage_id: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "strict-models", serde(deny_unknown_fields))]
 pub struct Branch {
     pub id: String,
     pub commit_id: String,
 }
 
+/// A name fixed to one commit.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "strict-models", serde(deny_unknown_fields))]
+pub struct Tag {
+    pub id: String,
+    pub commit_id: String,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "strict-models", serde(deny_unknown_fields))]
 pub struct Commit {
     pub id: String,
     pub parents: Vec<String>,
     pub committer: String,
     pub message: String,
+    #[serde(with = "unix_timestamp")]
     pub creation_date: DateTime<Utc>,
     pub meta_range_id: String,
+    #[serde(default)]
     pub metadata: HashMap<String, String>,
+    /// Distance from the repository's first commit along the longest parent chain
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub generation: Option<i64>,
+    /// Commit format version
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<i32>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "strict-models", serde(deny_unknown_fields))]
 pub struct ObjectStats {
     pub path: String,
     pub path_type: PathType,
     pub physical_address: String,
-    pub checksum: String,
+    pub checksum: Checksum,
+    /// Not sent for common prefixes
+    #[serde(default)]
     pub size_bytes: i64,
+    #[serde(with = "unix_timestamp")]
     pub mtime: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<HashMap<String, String>>,
+    /// Unix timestamp after which a presigned `physical_address` stops working
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub physical_address_expiry: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+}
+
+/// One entry of an object listing. Delimited listings send the directories
+/// under the prefix as common prefixes, which only carry their path.
+#[derive(Debug, Clone)]
+pub enum ListEntry {
+    Object(ObjectStats),
+    /// Path of the directory, ending with the delimiter
+    CommonPrefix(String),
+}
+
+impl From<ObjectStats> for ListEntry {
+    fn from(stats: ObjectStats) -> Self {
+        match stats.path_type {
+            PathType::Object => ListEntry::Object(stats),
+            PathType::Directory => ListEntry::CommonPrefix(stats.path),
+        }
+    }
+}
+
+impl ListEntry {
+    pub fn path(&self) -> &str {
+        match self {
+            ListEntry::Object(stats) => &stats.path,
+            ListEntry::CommonPrefix(path) => path,
+        }
+    }
+}
+
+/// An object already in the underlying storage, to be linked into a branch.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ObjectStage {
+    pub physical_address: String,
+    pub checksum: Checksum,
+    pub size_bytes: i64,
+    /// Unix timestamp
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mtime: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub metadata: Option<HashMap<String, String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+}
+
+impl From<&ObjectStats> for ObjectStage {
+    fn from(stats: &ObjectStats) -> Self {
+        Self {
+            physical_address: stats.physical_address.clone(),
+            checksum: stats.checksum.clone(),
+            size_bytes: stats.size_bytes,
+            mtime: Some(stats.mtime.timestamp()),
+            metadata: stats.metadata.clone(),
+            content_type: stats.content_type.clone(),
+        }
+    }
+}
+
+/// Where to write an object directly to the underlying storage.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "strict-models", serde(deny_unknown_fields))]
+pub struct StagingLocation {
+    pub physical_address: Option<String>,
+    #[serde(default)]
+    pub presigned_url: Option<String>,
+    /// Unix timestamp after which `presigned_url` stops working
+    #[serde(default)]
+    pub presigned_url_expiry: Option<i64>,
+    #[serde(default)]
+    pub token: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum PathType {
     Object,
+    /// Sent by lakeFS as `common_prefix` in delimited listings
+    #[serde(alias = "common_prefix")]
     Directory,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "strict-models", serde(deny_unknown_fields))]
 pub struct DiffResult {
     pub results: Vec<Diff>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pagination: Option<PaginationInfo>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "strict-models", serde(deny_unknown_fields))]
 pub struct Diff {
     pub path: String,
     pub path_type: PathType,
     #[serde(rename = "type")]
     pub diff_type: DiffType,
+    /// Size of the object on the side where it exists
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size_bytes: Option<i64>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -76,18 +227,208 @@ impl std::fmt::Display for DiffType {
     }
 }
 
+/// Options for `merge_with`; the defaults match a plain merge.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct MergeOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<HashMap<String, String>>,
+    /// Conflict resolution: `dest-wins` or `source-wins`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub strategy: Option<String>,
+    /// Create a single-parent commit holding the combined changes instead of
+    /// a merge commit
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub squash_merge: bool,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "strict-models", serde(deny_unknown_fields))]
 pub struct MergeResult {
+    /// ID of the merge commit
+    #[serde(rename = "reference", alias = "id")]
     pub id: String,
 }
 
+/// Commits found by `find_merge_base`: the two sides and their merge base.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "strict-models", serde(deny_unknown_fields))]
+pub struct MergeBase {
+    pub source_commit_id: String,
+    pub destination_commit_id: String,
+    pub base_commit_id: String,
+}
+
+/// Storage capabilities reported by `GET /config/storage`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "strict-models", serde(deny_unknown_fields))]
+pub struct StorageConfig {
+    pub blockstore_type: String,
+    pub blockstore_namespace_example: String,
+    /// Regex every storage namespace must match
+    #[serde(rename = "blockstore_namespace_ValidityRegex")]
+    pub blockstore_namespace_validity_regex: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_namespace_prefix: Option<String>,
+    #[serde(default)]
+    pub pre_sign_support: bool,
+    #[serde(default)]
+    pub import_support: bool,
+    #[serde(default)]
+    pub pre_sign_support_ui: bool,
+    /// Regex every import source must match
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub import_validity_regex: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pre_sign_multipart_upload: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blockstore_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blockstore_description: Option<String>,
+}
+
+/// Physical location of a metarange or range file.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "strict-models", serde(deny_unknown_fields))]
+pub struct StorageUri {
+    pub location: String,
+}
+
+/// Manifest produced by a refs dump, naming the metaranges that hold the
+/// repository's commits, tags and branches.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "strict-models", serde(deny_unknown_fields))]
+pub struct RefsDump {
+    pub commits_meta_range_id: String,
+    pub tags_meta_range_id: String,
+    pub branches_meta_range_id: String,
+}
+
+/// Condition a write or delete is made on, so a change made by someone else
+/// in the meantime is not overwritten. A request whose condition does not
+/// hold fails with [`Error::PreconditionFailed`](crate::Error::PreconditionFailed).
+#[derive(Debug, Clone, PartialEq)]
+pub enum Precondition {
+    /// The object's checksum (its ETag) is still this one
+    IfMatch(Checksum),
+    /// There is no object at the path yet
+    IfAbsent,
+}
+
+impl Precondition {
+    /// The conditional request header expressing this precondition.
+    pub fn header(&self) -> (reqwest::header::HeaderName, String) {
+        match self {
+            Precondition::IfMatch(checksum) => (reqwest::header::IF_MATCH, format!("\"{}\"", checksum)),
+            Precondition::IfAbsent => (reqwest::header::IF_NONE_MATCH, "*".to_string()),
+        }
+    }
+}
+
+/// Options for `upload_object_with`; the defaults match a plain upload.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UploadOptions {
+    /// Fail instead of replacing an object that does not satisfy this
+    pub precondition: Option<Precondition>,
+    /// User metadata stored with the object
+    pub metadata: HashMap<String, String>,
+    /// Sent as the `Content-Type` of the upload, which lakeFS keeps as the
+    /// object's content type
+    pub content_type: Option<String>,
+}
+
+/// Query parameters accepted by the paginated listing endpoints.
+#[derive(Debug, Serialize, Clone, Default, PartialEq)]
+pub struct PaginationParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prefix: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub after: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amount: Option<usize>,
+    /// Object listings only: whether each object carries its user metadata
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_metadata: Option<bool>,
+}
+
+impl PaginationParams {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    pub fn with_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = Some(prefix.into());
+        self
+    }
+    
+    pub fn with_after(mut self, after: impl Into<String>) -> Self {
+        self.after = Some(after.into());
+        self
+    }
+    
+    pub fn with_amount(mut self, amount: usize) -> Self {
+        self.amount = Some(amount);
+        self
+    }
+    
+    pub fn with_user_metadata(mut self, user_metadata: bool) -> Self {
+        self.user_metadata = Some(user_metadata);
+        self
+    }
+}
+
+/// Query parameters of an object listing.
+#[derive(Debug, Serialize, Clone, Default, PartialEq)]
+pub struct ListObjectsOptions {
+    /// Only list paths starting with this
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prefix: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub after: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amount: Option<usize>,
+    /// Roll up paths that contain this after the prefix into one common
+    /// prefix each, ending with it, as a directory listing does with `/`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delimiter: Option<String>,
+}
+
+impl ListObjectsOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    pub fn with_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = Some(prefix.into());
+        self
+    }
+    
+    pub fn with_after(mut self, after: impl Into<String>) -> Self {
+        self.after = Some(after.into());
+        self
+    }
+    
+    pub fn with_amount(mut self, amount: usize) -> Self {
+        self.amount = Some(amount);
+        self
+    }
+    
+    pub fn with_delimiter(mut self, delimiter: impl Into<String>) -> Self {
+        self.delimiter = Some(delimiter.into());
+        self
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "strict-models", serde(deny_unknown_fields))]
 pub struct Pagination<T> {
     pub results: Vec<T>,
     pub pagination: PaginationInfo,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "strict-models", serde(deny_unknown_fields))]
 pub struct PaginationInfo {
     pub has_more: bool,
     pub max_per_page: i32,
@@ -107,6 +448,8 @@ mod tests {
             storage_namespace: "s3://bucket/path".to_string(),
             default_branch: "main".to_string(),
             creation_date: Utc::now(),
+            read_only: None,
+            storage_id: None,
         };
 
         let json = serde_json::to_string(&repo).unwrap();
@@ -117,6 +460,152 @@ mod tests {
         assert_eq!(repo.default_branch, deserialized.default_branch);
     }
 
+    /// Deserialize a lakeFS response body and check that serializing it
+    /// again gives back exactly the same JSON.
+    fn assert_round_trip<T: Serialize + serde::de::DeserializeOwned>(body: &str) -> T {
+        let original: serde_json::Value = serde_json::from_str(body).unwrap();
+        let model: T = serde_json::from_value(original.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&model).unwrap(), original);
+        model
+    }
+
+    #[test]
+    fn test_repository_response() {
+        let repo: Repository = assert_round_trip(r#"{
+            "id": "example",
+            "creation_date": 1718010000,
+            "default_branch": "main",
+            "storage_namespace": "s3://bucket/example",
+            "read_only": true,
+            "storage_id": "primary"
+        }"#);
+        assert_eq!(repo.creation_date.timestamp(), 1718010000);
+        assert_eq!(repo.read_only, Some(true));
+    }
+
+    #[test]
+    fn test_commit_response() {
+        let commit: Commit = assert_round_trip(r#"{
+            "id": "c7a632d74f46c3ba6ab6fd15bbeb3b1d3cd8f3a1f2bb5db5f5e8e5a95ac2c7a1",
+            "parents": ["1e7e5b4c3a8a0e0f1b1a7a6a2e3b0d0a6f6f2c8f4b7d3e2a1c0b9a8f7e6d5c4b"],
+            "committer": "admin",
+            "message": "Add January data",
+            "creation_date": 1718010123,
+            "meta_range_id": "a4b1c0d8e6f73a2b",
+            "metadata": {"source": "etl"},
+            "generation": 42,
+            "version": 1
+        }"#);
+        assert_eq!(commit.generation, Some(42));
+        assert_eq!(commit.metadata["source"], "etl");
+    }
+
+    #[test]
+    fn test_object_stats_response() {
+        let stats: ObjectStats = assert_round_trip(r#"{
+            "path": "data/2024/01.parquet",
+            "path_type": "object",
+            "physical_address": "s3://bucket/example/data/gbc0kb0lr6ok4hse9olg/cp4d1lslr6ok4hse9olg",
+            "checksum": "5d41402abc4b2a76b9719d911017c592",
+            "size_bytes": 4096,
+            "mtime": 1718010456,
+            "metadata": {"owner": "etl"},
+            "content_type": "application/octet-stream"
+        }"#);
+        assert_eq!(stats.content_type.as_deref(), Some("application/octet-stream"));
+        assert_eq!(stats.mtime.timestamp(), 1718010456);
+    }
+
+    #[test]
+    fn test_common_prefix_listing_entry() {
+        let entry: ObjectStats = serde_json::from_str(r#"{
+            "path": "data/2024/",
+            "path_type": "common_prefix",
+            "physical_address": "",
+            "checksum": "",
+            "mtime": 0
+        }"#).unwrap();
+        assert_eq!(entry.path_type, PathType::Directory);
+        assert_eq!(entry.size_bytes, 0);
+        
+        let entry = ListEntry::from(entry);
+        assert!(matches!(&entry, ListEntry::CommonPrefix(path) if path == "data/2024/"));
+        assert_eq!(entry.path(), "data/2024/");
+    }
+
+    #[test]
+    fn test_diff_and_merge_responses() {
+        let diffs: DiffResult = assert_round_trip(r#"{
+            "pagination": {"has_more": false, "next_offset": "", "results": 1, "max_per_page": 1000},
+            "results": [{"type": "added", "path": "data/new.csv", "path_type": "object", "size_bytes": 12}]
+        }"#);
+        assert_eq!(diffs.results[0].size_bytes, Some(12));
+
+        let merge: MergeResult = assert_round_trip(r#"{"reference": "d3b2f1"}"#);
+        assert_eq!(merge.id, "d3b2f1");
+    }
+
+    #[test]
+    fn test_merge_base_response() {
+        let base: MergeBase = assert_round_trip(r#"{
+            "source_commit_id": "c3",
+            "destination_commit_id": "c9",
+            "base_commit_id": "c1"
+        }"#);
+        assert_eq!(base.base_commit_id, "c1");
+    }
+
+    #[test]
+    fn test_storage_config_response() {
+        let config: StorageConfig = assert_round_trip(r#"{
+            "blockstore_type": "s3",
+            "blockstore_namespace_example": "s3://example-bucket/",
+            "blockstore_namespace_ValidityRegex": "^s3://",
+            "default_namespace_prefix": "s3://my-bucket/",
+            "pre_sign_support": true,
+            "pre_sign_support_ui": true,
+            "import_support": true,
+            "import_validity_regex": "^s3://",
+            "pre_sign_multipart_upload": true
+        }"#);
+        assert_eq!(config.pre_sign_multipart_upload, Some(true));
+    }
+
+    const BRANCH_WITH_NEW_FIELD: &str = r#"{"id": "main", "commit_id": "d3b2f1", "protected": true}"#;
+
+    #[cfg(not(feature = "strict-models"))]
+    #[test]
+    fn test_unknown_fields_ignored() {
+        let branch: Branch = serde_json::from_str(BRANCH_WITH_NEW_FIELD).unwrap();
+        assert_eq!(branch.commit_id, "d3b2f1");
+    }
+
+    #[cfg(feature = "strict-models")]
+    #[test]
+    fn test_unknown_fields_rejected() {
+        let error = serde_json::from_str::<Branch>(BRANCH_WITH_NEW_FIELD).unwrap_err();
+        assert!(error.to_string().contains("protected"));
+    }
+
+    #[test]
+    fn test_rfc3339_timestamps_still_accepted() {
+        let repo: Repository = serde_json::from_str(r#"{
+            "id": "example",
+            "creation_date": "2024-01-01T00:00:00Z",
+            "default_branch": "main",
+            "storage_namespace": "s3://bucket/example"
+        }"#).unwrap();
+        assert_eq!(repo.creation_date.timestamp(), 1704067200);
+    }
+
+    #[test]
+    fn test_pagination_params_skip_unset() {
+        let params = PaginationParams::new().with_prefix("data").with_amount(5);
+        let json = serde_json::to_value(&params).unwrap();
+        
+        assert_eq!(json, serde_json::json!({"prefix": "data", "amount": 5}));
+    }
+
     #[test]
     fn test_diff_type_display() {
         assert_eq!(DiffType::Added.to_string(), "added");
@@ -0,0 +1,174 @@
+//! Which commits changed a path.
+//!
+//! lakeFS keeps no per-object history, so each commit in a branch's log is
+//! diffed against its parents with the path as the diff prefix. A path
+//! ending in `/` stands for everything under it.
+
+use crate::api::LakeFSApi;
+use crate::error::Result;
+use crate::models::{Commit, Diff, DiffType, PaginationParams};
+use futures::{StreamExt, TryStreamExt};
+
+/// Commits diffed at the same time
+const CONCURRENCY: usize = 8;
+
+/// A commit that changed the path, and how.
+#[derive(Debug, Clone)]
+pub struct PathChange {
+    pub commit: Commit,
+    pub change: DiffType,
+}
+
+/// Commits in the log of `branch` that changed `path`, newest first, stopping
+/// after `limit` of them.
+///
+/// A merge only counts when the path differs from every parent, so a change
+/// is credited to the commit that made it rather than to the merges that
+/// brought it in.
+pub async fn path_history(
+    client: &dyn LakeFSApi,
+    repository: &str,
+    branch: &str,
+    path: &str,
+    limit: Option<usize>,
+) -> Result<Vec<PathChange>> {
+    let mut changes = Vec::new();
+    let mut params = PaginationParams::new();
+    loop {
+        let page = client.log_commits_with(repository, branch, &params).await?;
+        let found: Vec<Option<PathChange>> = futures::stream::iter(page.results)
+            .map(|commit| change_in(client, repository, path, commit))
+            .buffered(CONCURRENCY)
+            .try_collect()
+            .await?;
+        for change in found.into_iter().flatten() {
+            changes.push(change);
+            if limit.is_some_and(|limit| changes.len() >= limit) {
+                return Ok(changes);
+            }
+        }
+
+        match page.pagination.next_offset {
+            Some(next) if page.pagination.has_more => params.after = Some(next),
+            _ => return Ok(changes),
+        }
+    }
+}
+
+/// How `commit` changed `path`, if it did.
+async fn change_in(client: &dyn LakeFSApi, repository: &str, path: &str, commit: Commit) -> Result<Option<PathChange>> {
+    let params = PaginationParams { prefix: Some(path.to_string()), after: None, amount: Some(1), ..Default::default() };
+
+    // The first commit changed whatever it contains
+    if commit.parents.is_empty() {
+        let listing = client.list_objects_with(repository, &commit.id, &params).await?;
+        let added = listing.results.first().is_some_and(|object| covers(path, &object.path));
+        return Ok(added.then_some(PathChange { commit, change: DiffType::Added }));
+    }
+
+    let mut change = None;
+    for parent in &commit.parents {
+        // Paths under the prefix sort after the prefix itself, so an exact
+        // match is always first
+        let diff = client.diff_with(repository, parent, &commit.id, &params).await?;
+        match diff.results.into_iter().next() {
+            Some(Diff { path: changed, diff_type, .. }) if covers(path, &changed) => {
+                change.get_or_insert(diff_type);
+            }
+            _ => return Ok(None),
+        }
+    }
+    Ok(change.map(|change| PathChange { commit, change }))
+}
+
+/// Whether a change to `changed` is a change to `path`.
+fn covers(path: &str, changed: &str) -> bool {
+    changed == path || path.ends_with('/') && changed.starts_with(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::MockLakeFSApi;
+    use crate::models::{ObjectStats, Pagination, PaginationInfo, PathType};
+    use chrono::Utc;
+    use std::collections::HashMap;
+
+    fn commit(id: &str, parents: &[&str]) -> Commit {
+        Commit {
+            id: id.to_string(),
+            parents: parents.iter().map(|p| p.to_string()).collect(),
+            committer: "user".to_string(),
+            message: format!("commit {}", id),
+            creation_date: Utc::now(),
+            meta_range_id: String::new(),
+            metadata: HashMap::new(),
+            generation: None,
+            version: None,
+        }
+    }
+
+    fn page<T>(results: Vec<T>) -> Pagination<T> {
+        let info = PaginationInfo { has_more: false, max_per_page: 100, next_offset: None, results: results.len() as i32 };
+        Pagination { results, pagination: info }
+    }
+
+    fn diff(path: &str, diff_type: DiffType) -> Diff {
+        Diff { path: path.to_string(), path_type: PathType::Object, diff_type, size_bytes: None }
+    }
+
+    /// Log of main: m merges feature commit f (which changed data/a.csv) into
+    /// c (which changed data/a.csv.bak); a added data/a.csv
+    fn history() -> MockLakeFSApi {
+        let mut mock = MockLakeFSApi::new();
+        mock.expect_log_commits_with().returning(|_, _, _| {
+            Ok(page(vec![commit("m", &["c", "f"]), commit("c", &["a"]), commit("f", &["a"]), commit("a", &[])]))
+        });
+        mock.expect_diff_with().returning(|_, left, right, params| {
+            assert_eq!(params.amount, Some(1));
+            let results = match (left, right) {
+                // Against c, the merge brings in f's change; against f, nothing
+                ("c", "m") => vec![diff("data/a.csv", DiffType::Changed)],
+                ("a", "c") => vec![diff("data/a.csv.bak", DiffType::Added)],
+                ("a", "f") => vec![diff("data/a.csv", DiffType::Changed)],
+                _ => vec![],
+            };
+            Ok(page(results))
+        });
+        mock.expect_list_objects_with().returning(|_, _, _| {
+            Ok(page(vec![ObjectStats {
+                path: "data/a.csv".to_string(),
+                path_type: PathType::Object,
+                physical_address: "s3://bucket/a".to_string(),
+                checksum: "d41d8cd98f00b204e9800998ecf8427e".to_string().into(),
+                size_bytes: 0,
+                mtime: Utc::now(),
+                metadata: None,
+                physical_address_expiry: None,
+                content_type: None,
+            }]))
+        });
+        mock
+    }
+
+    fn ids(changes: &[PathChange]) -> Vec<(&str, String)> {
+        changes.iter().map(|c| (c.commit.id.as_str(), c.change.to_string())).collect()
+    }
+
+    #[tokio::test]
+    async fn test_path_history_credits_the_changing_commit() {
+        let mock = history();
+        let changes = path_history(&mock, "repo", "main", "data/a.csv", None).await.unwrap();
+        assert_eq!(ids(&changes), [("f", "changed".to_string()), ("a", "added".to_string())]);
+
+        let last = path_history(&mock, "repo", "main", "data/a.csv", Some(1)).await.unwrap();
+        assert_eq!(ids(&last), [("f", "changed".to_string())]);
+    }
+
+    #[tokio::test]
+    async fn test_path_history_of_a_directory() {
+        let mock = history();
+        let changes = path_history(&mock, "repo", "main", "data/", None).await.unwrap();
+        assert_eq!(ids(&changes), [("c", "added".to_string()), ("f", "changed".to_string()), ("a", "added".to_string())]);
+    }
+}
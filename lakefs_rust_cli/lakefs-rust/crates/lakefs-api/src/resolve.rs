@@ -0,0 +1,222 @@
+//! References pinned to a point in time, and what kind of reference a
+//! name is.
+//!
+//! `main@2024-05-01T00:00:00Z` names the latest commit on `main` made at or
+//! before that time. lakeFS has no such syntax, so these references are
+//! turned into commit IDs here before they reach the server; any other
+//! reference, including lakeFS's own `main@`, passes through unchanged.
+
+use crate::api::LakeFSApi;
+use crate::error::{Error, Result};
+use crate::models::{Commit, PaginationParams};
+use crate::uri::LakeFSUri;
+use chrono::{DateTime, NaiveDate, Utc};
+
+/// Commits fetched per log page while searching
+const PAGE_SIZE: usize = 1000;
+
+/// The reference and time of `base@time`, when `reference` is one. The time is
+/// RFC 3339, or a date for midnight UTC.
+pub fn split_timestamp(reference: &str) -> Option<(&str, DateTime<Utc>)> {
+    let (base, time) = reference.split_once('@')?;
+    if base.is_empty() {
+        return None;
+    }
+    let at = match DateTime::parse_from_rfc3339(time) {
+        Ok(at) => at.with_timezone(&Utc),
+        Err(_) => NaiveDate::parse_from_str(time, "%Y-%m-%d").ok()?.and_hms_opt(0, 0, 0)?.and_utc(),
+    };
+    Some((base, at))
+}
+
+/// Latest commit in the log of `reference` made at or before `at`.
+///
+/// Each page of the log is binary searched on creation date, which relies on
+/// the log being newest first; a page is only fetched when everything before
+/// it is newer than `at`.
+pub async fn commit_at(client: &dyn LakeFSApi, repository: &str, reference: &str, at: DateTime<Utc>) -> Result<Commit> {
+    let mut params = PaginationParams { prefix: None, after: None, amount: Some(PAGE_SIZE), ..Default::default() };
+    loop {
+        let mut page = client.log_commits_with(repository, reference, &params).await?;
+        let index = page.results.partition_point(|commit| commit.creation_date > at);
+        if index < page.results.len() {
+            return Ok(page.results.swap_remove(index));
+        }
+
+        match page.pagination.next_offset {
+            Some(next) if page.pagination.has_more => params.after = Some(next),
+            _ => return Err(Error::NotFound(format!("no commit on {} at or before {}", reference, at.to_rfc3339()))),
+        }
+    }
+}
+
+/// `reference`, with a time in it replaced by the commit it names.
+pub async fn resolve_reference(client: &dyn LakeFSApi, repository: &str, reference: &str) -> Result<String> {
+    match split_timestamp(reference) {
+        Some((base, at)) => Ok(commit_at(client, repository, base, at).await?.id),
+        None => Ok(reference.to_string()),
+    }
+}
+
+/// Which kind of reference a name is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RefKind {
+    Branch,
+    Tag,
+    /// A commit ID, or an expression such as `main~2` or `main@` that
+    /// names a commit
+    Commit,
+}
+
+impl std::fmt::Display for RefKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            RefKind::Branch => "branch",
+            RefKind::Tag => "tag",
+            RefKind::Commit => "commit",
+        })
+    }
+}
+
+/// A reference, what kind it is and the commit it points at.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedRef {
+    pub kind: RefKind,
+    pub commit_id: String,
+}
+
+/// What `reference` names in `repository`. Branches win over tags of the
+/// same name, as they do on the server, and anything else the server can
+/// resolve to a commit, including `ref@time`, is a commit.
+pub async fn resolve_ref(client: &dyn LakeFSApi, repository: &str, reference: &str) -> Result<ResolvedRef> {
+    if let Some((base, at)) = split_timestamp(reference) {
+        let commit = commit_at(client, repository, base, at).await?;
+        return Ok(ResolvedRef { kind: RefKind::Commit, commit_id: commit.id });
+    }
+
+    match client.get_branch(repository, reference).await {
+        Ok(branch) => return Ok(ResolvedRef { kind: RefKind::Branch, commit_id: branch.commit_id }),
+        Err(Error::NotFound(_)) => {}
+        Err(e) => return Err(e),
+    }
+    match client.get_tag(repository, reference).await {
+        Ok(tag) => return Ok(ResolvedRef { kind: RefKind::Tag, commit_id: tag.commit_id }),
+        Err(Error::NotFound(_)) => {}
+        Err(e) => return Err(e),
+    }
+    match client.get_commit(repository, reference).await {
+        Ok(commit) => Ok(ResolvedRef { kind: RefKind::Commit, commit_id: commit.id }),
+        Err(Error::NotFound(_)) => Err(Error::NotFound(format!(
+            "no branch, tag or commit named '{}' in {}",
+            reference, repository
+        ))),
+        Err(e) => Err(e),
+    }
+}
+
+/// `uri`, with its reference resolved by [`resolve_reference`].
+pub async fn resolve_uri(client: &dyn LakeFSApi, uri: LakeFSUri) -> Result<LakeFSUri> {
+    let reference = resolve_reference(client, &uri.repository, &uri.reference).await?;
+    Ok(LakeFSUri { reference, ..uri })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::MockLakeFSApi;
+    use crate::models::{Branch, Pagination, PaginationInfo, Tag};
+    use chrono::TimeZone;
+    use std::collections::HashMap;
+
+    fn commit(id: &str, day: u32) -> Commit {
+        Commit {
+            id: id.to_string(),
+            parents: vec![],
+            committer: "user".to_string(),
+            message: format!("commit {}", id),
+            creation_date: Utc.with_ymd_and_hms(2024, 5, day, 12, 0, 0).unwrap(),
+            meta_range_id: String::new(),
+            metadata: HashMap::new(),
+            generation: None,
+            version: None,
+        }
+    }
+
+    /// Log of main, newest first, two commits to a page
+    fn log() -> MockLakeFSApi {
+        let mut mock = MockLakeFSApi::new();
+        mock.expect_log_commits_with().returning(|_, reference, params| {
+            assert_eq!(reference, "main");
+            let (results, next) = match params.after.as_deref() {
+                None => (vec![commit("e", 9), commit("d", 7)], Some("d")),
+                Some("d") => (vec![commit("c", 5), commit("b", 3)], Some("b")),
+                _ => (vec![commit("a", 1)], None),
+            };
+            let pagination = PaginationInfo {
+                has_more: next.is_some(),
+                max_per_page: 2,
+                next_offset: next.map(String::from),
+                results: results.len() as i32,
+            };
+            Ok(Pagination { results, pagination })
+        });
+        mock
+    }
+
+    #[test]
+    fn test_split_timestamp() {
+        let (base, at) = split_timestamp("main@2024-05-01T02:00:00+02:00").unwrap();
+        assert_eq!(base, "main");
+        assert_eq!(at, Utc.with_ymd_and_hms(2024, 5, 1, 0, 0, 0).unwrap());
+        assert_eq!(split_timestamp("main@2024-05-01"), Some(("main", at)));
+
+        // lakeFS's own `main@` and plain references are left alone
+        assert_eq!(split_timestamp("main@"), None);
+        assert_eq!(split_timestamp("main"), None);
+        assert_eq!(split_timestamp("main@yesterday"), None);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_reference_finds_latest_commit_before() {
+        let mock = log();
+        let at = |reference: &'static str| resolve_reference(&mock, "repo", reference);
+        assert_eq!(at("main@2024-05-09T12:00:00Z").await.unwrap(), "e");
+        assert_eq!(at("main@2024-05-06").await.unwrap(), "c");
+        assert_eq!(at("main@2024-05-02").await.unwrap(), "a");
+        assert_eq!(at("main").await.unwrap(), "main");
+        assert!(matches!(at("main@2024-04-30").await, Err(Error::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_resolve_ref_kinds() {
+        let mut mock = log();
+        mock.expect_get_branch().returning(|_, branch| match branch {
+            "main" => Ok(Branch { id: "main".to_string(), commit_id: "e".to_string() }),
+            _ => Err(Error::NotFound(String::new())),
+        });
+        mock.expect_get_tag().returning(|_, tag| match tag {
+            "v1" => Ok(Tag { id: "v1".to_string(), commit_id: "c".to_string() }),
+            _ => Err(Error::NotFound(String::new())),
+        });
+        mock.expect_get_commit().returning(|_, reference| match reference {
+            "main~1" => Ok(commit("d", 7)),
+            _ => Err(Error::NotFound(String::new())),
+        });
+
+        let resolve = |reference: &'static str| resolve_ref(&mock, "repo", reference);
+        assert_eq!(resolve("main").await.unwrap(), ResolvedRef { kind: RefKind::Branch, commit_id: "e".into() });
+        assert_eq!(resolve("v1").await.unwrap(), ResolvedRef { kind: RefKind::Tag, commit_id: "c".into() });
+        assert_eq!(resolve("main~1").await.unwrap().kind, RefKind::Commit);
+        assert_eq!(resolve("main@2024-05-04").await.unwrap(), ResolvedRef { kind: RefKind::Commit, commit_id: "b".into() });
+        let error = resolve("nope").await.unwrap_err();
+        assert!(error.to_string().contains("no branch, tag or commit named 'nope'"));
+    }
+
+    #[tokio::test]
+    async fn test_resolve_uri_keeps_path() {
+        let mock = log();
+        let uri = "lakefs://repo/main@2024-05-08/data/".parse().unwrap();
+        let resolved = resolve_uri(&mock, uri).await.unwrap();
+        assert_eq!(resolved.to_string(), "lakefs://repo/d/data/");
+    }
+}
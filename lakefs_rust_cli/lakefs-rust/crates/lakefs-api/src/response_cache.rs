@@ -0,0 +1,206 @@
+use bytes::Bytes;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+/// Cache for responses that can never change: object stats, listings and
+/// object contents read at a commit ID rather than a branch or tag.
+///
+/// Entries are kept in memory up to a byte limit, least recently used first
+/// out, and optionally in a directory with its own limit that outlives the
+/// process. Responses larger than a limit are not stored at that level.
+///
+/// ```no_run
+/// # use lakefs_api::{LakeFSClient, ResponseCache};
+/// # use std::sync::Arc;
+/// let cache = ResponseCache::in_memory(64 << 20).with_disk("/var/cache/lakectl", 1 << 30);
+/// let client = LakeFSClient::builder()
+///     .base_url("http://localhost:8000/api/v1")
+///     .auth_header("Basic ...")
+///     .response_cache(Arc::new(cache))
+///     .build()?;
+/// # Ok::<(), lakefs_api::Error>(())
+/// ```
+pub struct ResponseCache {
+    memory: Mutex<Memory>,
+    max_memory_bytes: u64,
+    disk: Option<Disk>,
+}
+
+#[derive(Default)]
+struct Memory {
+    entries: HashMap<String, (Bytes, u64)>,
+    size: u64,
+    /// Incremented on every access, to order entries by last use
+    clock: u64,
+}
+
+struct Disk {
+    dir: PathBuf,
+    max_bytes: u64,
+}
+
+impl ResponseCache {
+    pub fn in_memory(max_bytes: u64) -> Self {
+        Self {
+            memory: Mutex::new(Memory::default()),
+            max_memory_bytes: max_bytes,
+            disk: None,
+        }
+    }
+
+    /// Also keep entries as files in `dir`, evicting the least recently
+    /// used once they take up more than `max_bytes`.
+    pub fn with_disk(mut self, dir: impl Into<PathBuf>, max_bytes: u64) -> Self {
+        self.disk = Some(Disk { dir: dir.into(), max_bytes });
+        self
+    }
+
+    /// Whether responses for `reference` are immutable: lakeFS commit IDs
+    /// are 64 hex digits, while branches and tags can move.
+    pub fn is_immutable(reference: &str) -> bool {
+        reference.len() == 64 && reference.bytes().all(|b| b.is_ascii_hexdigit())
+    }
+
+    pub(crate) fn get(&self, key: &str) -> Option<Bytes> {
+        {
+            let mut memory = self.memory.lock().unwrap();
+            memory.clock += 1;
+            let clock = memory.clock;
+            if let Some((value, last_used)) = memory.entries.get_mut(key) {
+                *last_used = clock;
+                return Some(value.clone());
+            }
+        }
+
+        let disk = self.disk.as_ref()?;
+        let path = disk.path(key);
+        let value = Bytes::from(fs::read(&path).ok()?);
+        // Touch the file so eviction sees it as recently used
+        if let Ok(file) = fs::File::options().append(true).open(&path) {
+            let _ = file.set_modified(SystemTime::now());
+        }
+        self.put_memory(key, value.clone());
+        Some(value)
+    }
+
+    /// Store `value`. Failing to write the disk copy only loses the entry.
+    pub(crate) fn put(&self, key: &str, value: Bytes) {
+        if let Some(disk) = &self.disk {
+            let _ = disk.put(key, &value);
+        }
+        self.put_memory(key, value);
+    }
+
+    fn put_memory(&self, key: &str, value: Bytes) {
+        let size = value.len() as u64;
+        if size > self.max_memory_bytes {
+            return;
+        }
+        let mut memory = self.memory.lock().unwrap();
+        memory.clock += 1;
+        let clock = memory.clock;
+        if let Some((old, _)) = memory.entries.insert(key.to_string(), (value, clock)) {
+            memory.size -= old.len() as u64;
+        }
+        memory.size += size;
+        while memory.size > self.max_memory_bytes {
+            let Some(oldest) = memory.entries.iter().min_by_key(|(_, (_, used))| *used).map(|(k, _)| k.clone()) else {
+                break;
+            };
+            if let Some((evicted, _)) = memory.entries.remove(&oldest) {
+                memory.size -= evicted.len() as u64;
+            }
+        }
+    }
+}
+
+impl Disk {
+    fn path(&self, key: &str) -> PathBuf {
+        let digest = Sha256::digest(key.as_bytes());
+        let name: String = digest.iter().map(|b| format!("{:02x}", b)).collect();
+        self.dir.join(name)
+    }
+
+    fn put(&self, key: &str, value: &[u8]) -> std::io::Result<()> {
+        if value.len() as u64 > self.max_bytes {
+            return Ok(());
+        }
+        fs::create_dir_all(&self.dir)?;
+        let path = self.path(key);
+        let temp = path.with_extension("tmp");
+        fs::write(&temp, value)?;
+        fs::rename(&temp, &path)?;
+        self.evict()
+    }
+
+    /// Remove the least recently used files until the rest fit.
+    fn evict(&self) -> std::io::Result<()> {
+        let mut files = Vec::new();
+        let mut total = 0;
+        for entry in fs::read_dir(&self.dir)? {
+            let entry = entry?;
+            let metadata = entry.metadata()?;
+            if metadata.is_file() {
+                total += metadata.len();
+                files.push((metadata.modified()?, metadata.len(), entry.path()));
+            }
+        }
+        files.sort();
+        for (_, len, path) in files {
+            if total <= self.max_bytes {
+                break;
+            }
+            fs::remove_file(path)?;
+            total -= len;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_is_immutable() {
+        assert!(ResponseCache::is_immutable(&"a1".repeat(32)));
+        assert!(!ResponseCache::is_immutable("main"));
+        assert!(!ResponseCache::is_immutable(&"a1".repeat(20)));
+        assert!(!ResponseCache::is_immutable(&"z1".repeat(32)));
+    }
+
+    #[test]
+    fn test_memory_evicts_least_recently_used() {
+        let cache = ResponseCache::in_memory(10);
+        cache.put("a", Bytes::from_static(b"aaaa"));
+        cache.put("b", Bytes::from_static(b"bbbb"));
+        assert!(cache.get("a").is_some());
+        cache.put("c", Bytes::from_static(b"cccc"));
+
+        assert!(cache.get("a").is_some());
+        assert!(cache.get("b").is_none());
+        assert!(cache.get("c").is_some());
+
+        cache.put("big", Bytes::from_static(b"more than ten bytes"));
+        assert!(cache.get("big").is_none());
+    }
+
+    #[test]
+    fn test_disk_outlives_memory() {
+        let dir = TempDir::new().unwrap();
+        let cache = ResponseCache::in_memory(0).with_disk(dir.path(), 10);
+        cache.put("a", Bytes::from_static(b"aaaa"));
+        cache.put("b", Bytes::from_static(b"bbbb"));
+        cache.put("c", Bytes::from_static(b"cccc"));
+
+        let reopened = ResponseCache::in_memory(0).with_disk(dir.path(), 10);
+        let kept = ["a", "b", "c"].iter().filter_map(|key| reopened.get(key)).count();
+        assert_eq!(kept, 2);
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 2);
+    }
+}
@@ -0,0 +1,90 @@
+use reqwest::StatusCode;
+use std::time::Duration;
+
+/// Controls how failed requests are retried.
+///
+/// Only transient failures are retried: connection errors, timeouts and
+/// `429`/`502`/`503`/`504` responses. The default policy never retries.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl RetryPolicy {
+    pub fn none() -> Self {
+        Self {
+            max_retries: 0,
+            initial_backoff: Duration::ZERO,
+            max_backoff: Duration::ZERO,
+        }
+    }
+    
+    /// Exponential backoff starting at 100ms and capped at 5s.
+    pub fn exponential(max_retries: u32) -> Self {
+        Self {
+            max_retries,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(5),
+        }
+    }
+    
+    pub fn with_backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max;
+        self
+    }
+    
+    /// Delay before retry number `attempt` (zero-based).
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt);
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+    
+    pub fn is_retryable_status(status: StatusCode) -> bool {
+        matches!(
+            status,
+            StatusCode::TOO_MANY_REQUESTS
+                | StatusCode::BAD_GATEWAY
+                | StatusCode::SERVICE_UNAVAILABLE
+                | StatusCode::GATEWAY_TIMEOUT
+        )
+    }
+    
+    pub fn is_retryable_error(error: &reqwest::Error) -> bool {
+        error.is_connect() || error.is_timeout()
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_is_capped() {
+        let policy = RetryPolicy::exponential(5)
+            .with_backoff(Duration::from_millis(100), Duration::from_millis(350));
+        
+        assert_eq!(policy.backoff(0), Duration::from_millis(100));
+        assert_eq!(policy.backoff(1), Duration::from_millis(200));
+        assert_eq!(policy.backoff(2), Duration::from_millis(350));
+        assert_eq!(policy.backoff(30), Duration::from_millis(350));
+    }
+
+    #[test]
+    fn test_retryable_statuses() {
+        assert!(RetryPolicy::is_retryable_status(StatusCode::SERVICE_UNAVAILABLE));
+        assert!(RetryPolicy::is_retryable_status(StatusCode::TOO_MANY_REQUESTS));
+        assert!(!RetryPolicy::is_retryable_status(StatusCode::NOT_FOUND));
+        assert!(!RetryPolicy::is_retryable_status(StatusCode::INTERNAL_SERVER_ERROR));
+    }
+}
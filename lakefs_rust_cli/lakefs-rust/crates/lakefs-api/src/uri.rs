@@ -22,10 +22,13 @@ impl LakeFSUri {
         self
     }
     
-    pub fn to_string(&self) -> String {
+}
+
+impl std::fmt::Display for LakeFSUri {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match &self.path {
-            Some(path) => format!("lakefs://{}/{}/{}", self.repository, self.reference, path),
-            None => format!("lakefs://{}/{}", self.repository, self.reference),
+            Some(path) => write!(f, "lakefs://{}/{}/{}", self.repository, self.reference, path),
+            None => write!(f, "lakefs://{}/{}", self.repository, self.reference),
         }
     }
 }
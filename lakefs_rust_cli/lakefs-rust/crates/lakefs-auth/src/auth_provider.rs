@@ -1,4 +1,13 @@
-use crate::{error::Result, basic::BasicAuth, aws_iam::AwsIamAuth};
+use crate::{
+    error::Result,
+    basic::BasicAuth,
+    aws_iam::AwsIamAuth,
+    azure_identity::AzureIdentityAuth,
+    gcp_identity::GcpIdentityAuth,
+    exec::ExecAuth,
+    login::LoginAuth,
+    chain::ChainAuthProvider,
+};
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 
@@ -14,11 +23,68 @@ pub enum AuthConfig {
         access_key_id: String,
         secret_access_key: String,
     },
+    /// Access key pair exchanged for a session token via `/auth/login`,
+    /// instead of being sent with every request.
+    Login {
+        access_key_id: String,
+        secret_access_key: String,
+        #[serde(default)]
+        base_uri: Option<String>,
+    },
     AwsIam {
         region: String,
         #[serde(default)]
         base_uri: Option<String>,
     },
+    /// Azure Managed Identity. `client_id` selects a user-assigned identity.
+    AzureIdentity {
+        #[serde(default)]
+        client_id: Option<String>,
+        #[serde(default)]
+        resource: Option<String>,
+        #[serde(default)]
+        base_uri: Option<String>,
+    },
+    /// GCP service-account identity token, issued for `audience`.
+    GcpIdentity {
+        #[serde(default)]
+        audience: Option<String>,
+        #[serde(default)]
+        base_uri: Option<String>,
+    },
+    /// External credential helper whose JSON output supplies the credentials.
+    #[serde(alias = "exec")]
+    Exec {
+        command: String,
+        #[serde(default)]
+        args: Vec<String>,
+    },
+    /// Access key pair from `LAKECTL_CREDENTIALS_ACCESS_KEY_ID` and
+    /// `LAKECTL_CREDENTIALS_SECRET_ACCESS_KEY`.
+    Env,
+    /// Access key pair typed in on the terminal.
+    Prompt,
+    /// The first of `providers`, in order, that yields credentials.
+    Chain {
+        providers: Vec<AuthConfig>,
+    },
+}
+
+impl AuthConfig {
+    /// Short name of the provider type, for messages.
+    pub fn name(&self) -> &'static str {
+        match self {
+            AuthConfig::Basic { .. } => "basic",
+            AuthConfig::Login { .. } => "login",
+            AuthConfig::AwsIam { .. } => "aws_iam",
+            AuthConfig::AzureIdentity { .. } => "azure_identity",
+            AuthConfig::GcpIdentity { .. } => "gcp_identity",
+            AuthConfig::Exec { .. } => "exec",
+            AuthConfig::Env => "env",
+            AuthConfig::Prompt => "prompt",
+            AuthConfig::Chain { .. } => "chain",
+        }
+    }
 }
 
 pub async fn create_auth_provider(
@@ -29,17 +95,33 @@ pub async fn create_auth_provider(
         AuthConfig::Basic { access_key_id, secret_access_key } => {
             Ok(Box::new(BasicAuth::new(access_key_id, secret_access_key)))
         }
+        AuthConfig::Login { access_key_id, secret_access_key, base_uri } => {
+            Ok(Box::new(LoginAuth::new(endpoint, access_key_id, secret_access_key, base_uri)))
+        }
         AuthConfig::AwsIam { region, base_uri } => {
             let provider = AwsIamAuth::new(region, endpoint, base_uri).await?;
             Ok(Box::new(provider))
         }
+        AuthConfig::AzureIdentity { client_id, resource, base_uri } => {
+            Ok(Box::new(AzureIdentityAuth::new(endpoint, client_id, resource, base_uri)))
+        }
+        AuthConfig::GcpIdentity { audience, base_uri } => {
+            Ok(Box::new(GcpIdentityAuth::new(endpoint, audience, base_uri)))
+        }
+        AuthConfig::Exec { command, args } => {
+            Ok(Box::new(ExecAuth::new(command, args)))
+        }
+        AuthConfig::Env => Ok(Box::new(BasicAuth::from_env()?)),
+        AuthConfig::Prompt => Ok(Box::new(BasicAuth::prompt().await?)),
+        AuthConfig::Chain { providers } => {
+            Ok(Box::new(ChainAuthProvider::new(providers, endpoint)))
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use super::*;
 
     #[tokio::test]
     async fn test_create_basic_auth_provider()  {
@@ -69,6 +151,61 @@ mod tests {
             base_uri: Some("http://custom".to_string()),
         };
         
+        let _json = serde_json::to_string(&aws_config).unwrap();
+    }
+
+    #[test]
+    fn test_aws_iam_config_serialization()  {
+        let aws_config = AuthConfig::AwsIam {
+            region: "us-east-1".to_string(),
+            base_uri: None,
+        };
+        
         let json = serde_json::to_string(&aws_config).unwrap();
+        assert!(json.contains("\"type\":\"AwsIam\""));
+        assert!(json.contains("us-east-1"));
+    }
+
+    #[test]
+    fn test_identity_config_deserialization() {
+        let azure: AuthConfig = serde_json::from_str(r#"{"type":"AzureIdentity","client_id":"abc"}"#).unwrap();
+        assert!(matches!(azure, AuthConfig::AzureIdentity { client_id: Some(_), resource: None, base_uri: None }));
+        
+        let gcp: AuthConfig = serde_json::from_str(r#"{"type":"GcpIdentity"}"#).unwrap();
+        assert!(matches!(gcp, AuthConfig::GcpIdentity { audience: None, base_uri: None }));
+    }
+
+    #[test]
+    fn test_login_config_deserialization() {
+        let login: AuthConfig = serde_json::from_str(
+            r#"{"type":"Login","access_key_id":"key","secret_access_key":"secret"}"#,
+        ).unwrap();
+        assert!(matches!(login, AuthConfig::Login { base_uri: None, .. }));
+    }
+
+    #[test]
+    fn test_chain_config_deserialization() {
+        let chain: AuthConfig = serde_json::from_str(
+            r#"{"type":"Chain","providers":[{"type":"Env"},{"type":"AwsIam","region":"us-east-1"},{"type":"Prompt"}]}"#,
+        ).unwrap();
+        match chain {
+            AuthConfig::Chain { providers } => {
+                let names: Vec<_> = providers.iter().map(AuthConfig::name).collect();
+                assert_eq!(names, ["env", "aws_iam", "prompt"]);
+            }
+            _ => panic!("Expected a credential chain"),
+        }
+    }
+
+    #[test]
+    fn test_exec_config_deserialization() {
+        let exec: AuthConfig = serde_json::from_str(r#"{"type":"exec","command":"/usr/bin/get-lakefs-creds"}"#).unwrap();
+        match exec {
+            AuthConfig::Exec { command, args } => {
+                assert_eq!(command, "/usr/bin/get-lakefs-creds");
+                assert!(args.is_empty());
+            }
+            _ => panic!("Expected exec credentials"),
+        }
     }
 }
@@ -1,39 +1,137 @@
-use crate::{auth_provider::AuthProvider, error::{Error, Result}};
+use crate::{
+    auth_provider::AuthProvider,
+    error::{Error, Result},
+    external::{login_url, AuthenticationToken, TokenCache},
+};
 use async_trait::async_trait;
 use aws_config::{meta::region::RegionProviderChain, BehaviorVersion};
 use aws_credential_types::provider::{SharedCredentialsProvider, ProvideCredentials};
-use aws_sigv4::http_request::{sign, SignableBody, SignableRequest, SigningSettings};
+use aws_credential_types::Credentials;
+use aws_sigv4::http_request::{sign, SignableBody, SignableRequest, SignatureLocation, SigningSettings};
 use aws_sigv4::sign::v4;
 use aws_types::region::Region;
-use chrono::Utc;
-use http::{Method, Uri};
+use base64::Engine;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
-use std::time::SystemTime;
+use std::time::{Duration, SystemTime};
 
-#[derive(Debug, Serialize, Deserialize)]
-struct AwsAuthRequest {
+const LOGIN_PATH: &str = "/auth/external/principal/login";
+const SERVICE: &str = "sts";
+const ACTION: &str = "GetCallerIdentity";
+const VERSION: &str = "2011-06-15";
+/// Header binding the presigned request to one lakeFS server, so it cannot be
+/// replayed against another.
+const SERVER_ID_HEADER: &str = "x-lakefs-server-id";
+/// How long the presigned request stays valid; lakeFS uses it immediately.
+const PRESIGN_TTL: Duration = Duration::from_secs(60);
+/// Lifetime requested for the lakeFS session token.
+const TOKEN_TTL_SECS: i64 = 3600;
+
+/// A presigned STS GetCallerIdentity request, taken apart into the fields
+/// lakeFS reassembles and sends to STS to learn the caller's IAM identity.
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct IdentityTokenInfo {
     method: String,
     host: String,
     region: String,
-    service: String,
+    action: String,
     date: String,
-    expires_in: i64,
-    body: String,
-    headers: std::collections::HashMap<String, String>,
+    expiration_duration: String,
+    access_key_id: String,
+    signature: String,
+    signed_headers: Vec<String>,
+    version: String,
+    algorithm: String,
+    security_token: String,
+}
+
+impl IdentityTokenInfo {
+    /// The identity token lakeFS expects: the request as base64-encoded JSON.
+    fn encode(&self) -> Result<String> {
+        let json = serde_json::to_vec(self).map_err(|e| Error::Aws(e.to_string()))?;
+        Ok(base64::engine::general_purpose::STANDARD.encode(json))
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct LakeFSAuthResponse {
-    token: String,
+#[derive(Debug, Serialize)]
+struct IdentityRequest<'a> {
+    identity_token: &'a str,
+}
+
+#[derive(Debug, Serialize)]
+struct ExternalLoginInformation<'a> {
+    identity_request: IdentityRequest<'a>,
+    token_expiration_duration: i64,
+}
+
+/// Presign a GetCallerIdentity request for `region`'s STS endpoint, signing
+/// the host and lakeFS server ID headers and carrying the signature in the
+/// query string.
+fn presign_identity_request(
+    credentials: &Credentials,
+    region: &str,
+    server_id: &str,
+    time: SystemTime,
+) -> Result<IdentityTokenInfo> {
+    let host = format!("sts.{}.amazonaws.com", region);
+    let url = format!("https://{}/?Action={}&Version={}", host, ACTION, VERSION);
+    let headers = [("host", host.as_str()), (SERVER_ID_HEADER, server_id)];
+    let signable_request = SignableRequest::new("POST", url, headers.into_iter(), SignableBody::Bytes(&[]))
+        .map_err(|e| Error::Aws(format!("Failed to create signable request: {}", e)))?;
+
+    let mut settings = SigningSettings::default();
+    settings.signature_location = SignatureLocation::QueryParams;
+    settings.expires_in = Some(PRESIGN_TTL);
+
+    let identity = credentials.clone().into();
+    let signing_params = v4::SigningParams::builder()
+        .identity(&identity)
+        .region(region)
+        .name(SERVICE)
+        .time(time)
+        .settings(settings)
+        .build()
+        .map_err(|e| Error::Aws(format!("Failed to build signing params: {}", e)))?
+        .into();
+
+    let (instructions, _signature) = sign(signable_request, &signing_params)
+        .map_err(|e| Error::Aws(e.to_string()))?
+        .into_parts();
+    let param = |name: &str| {
+        instructions
+            .params()
+            .iter()
+            .find(|(key, _)| *key == name)
+            .map(|(_, value)| value.to_string())
+            .unwrap_or_default()
+    };
+
+    Ok(IdentityTokenInfo {
+        method: "POST".to_string(),
+        host,
+        region: region.to_string(),
+        action: ACTION.to_string(),
+        date: param("X-Amz-Date"),
+        expiration_duration: param("X-Amz-Expires"),
+        access_key_id: credentials.access_key_id().to_string(),
+        signature: param("X-Amz-Signature"),
+        signed_headers: param("X-Amz-SignedHeaders").split(';').map(str::to_string).collect(),
+        version: VERSION.to_string(),
+        algorithm: param("X-Amz-Algorithm"),
+        security_token: param("X-Amz-Security-Token"),
+    })
 }
 
+/// Logs in to lakeFS with the AWS identity of the environment (instance
+/// profile, IRSA, environment variables, ...) through lakeFS's external
+/// principal login.
 pub struct AwsIamAuth {
     region: Region,
-    endpoint: String,
-    base_uri: Option<String>,
+    server_id: String,
+    login_url: String,
     credentials_provider: SharedCredentialsProvider,
     client: Client,
+    cache: TokenCache,
 }
 
 impl AwsIamAuth {
@@ -44,148 +142,35 @@ impl AwsIamAuth {
     ) -> Result<Self> {
         let region_provider = RegionProviderChain::default_provider()
             .or_else(Region::new(region.clone()));
-        
+
         let config = aws_config::defaults(BehaviorVersion::latest())
             .region(region_provider)
             .load()
             .await;
-        
+
         Ok(Self {
             region: Region::new(region),
-            endpoint: endpoint.to_string(),
-            base_uri,
+            server_id: server_id(endpoint)?,
+            login_url: login_url(endpoint, base_uri.as_deref(), LOGIN_PATH),
             credentials_provider: config.credentials_provider()
                 .ok_or_else(|| Error::Config("No AWS credentials provider found".into()))?,
             client: Client::new(),
+            cache: TokenCache::default(),
         })
     }
-    
-    async fn create_sts_request(&self) -> Result<AwsAuthRequest> {
-        let credentials = self.credentials_provider
-            .provide_credentials()
-            .await
-            .map_err(|e| Error::Aws(e.to_string()))?;
-        
-        // Create STS GetCallerIdentity request
-        let method = Method::POST;
-        let service = "sts";
-        let host = format!("sts.{}.amazonaws.com", self.region.as_ref());
-        let uri = Uri::builder()
-            .scheme("https")
-            .authority(host.as_str())
-            .path_and_query("/?Action=GetCallerIdentity&Version=2011-06-15")
-            .build()
-            .map_err(|e| Error::Config(e.to_string()))?;
-        
-        let now = SystemTime::now();
-        let signing_settings = SigningSettings::default();
-        
-        let body = "";
-        let signable_body = SignableBody::Bytes(body.as_bytes());
-        
-        let mut headers = http::HeaderMap::new();
-        headers.insert("Host", host.parse().unwrap());
-        
-        // Convert headers to the format expected by SignableRequest
-        let header_vec: Vec<(&str, &str)> = headers
-            .iter()
-            .map(|(k, v)| (k.as_str(), v.to_str().unwrap()))
-            .collect();
-        
-        let mut request = http::Request::builder()
-            .method(method.clone())
-            .uri(&uri)
-            .body(body.as_bytes().to_vec())
-            .map_err(|e| Error::Config(e.to_string()))?;
-        
-        *request.headers_mut() = headers.clone();
-        
-        // Create a signable request
-        let signable_request = SignableRequest::new(
-            method.as_str(),
-            uri.to_string(),
-            header_vec.into_iter(),
-            signable_body,
-        ).map_err(|e| Error::Aws(format!("Failed to create signable request: {}", e)))?;
-        
-        // Create signing parameters
-        let identity = aws_credential_types::Credentials::new(
-            credentials.access_key_id(),
-            credentials.secret_access_key(),
-            credentials.session_token().map(|s| s.to_string()),
-            None,
-            "manual",
-        ).into();
-        
-        let signing_params = v4::SigningParams::builder()
-            .identity(&identity)
-            .region(self.region.as_ref())
-            .name(service)
-            .time(now)
-            .settings(signing_settings)
-            .build()
-            .map_err(|e| Error::Aws(format!("Failed to build signing params: {}", e)))?
-            .into();
-        
-        // Sign the request
-        let output = sign(signable_request, &signing_params)
-            .map_err(|e| Error::Aws(e.to_string()))?;
-        
-        // Extract signed headers directly from the output
-        let mut header_map = std::collections::HashMap::new();
-        
-        // The output contains the signature - let's extract it properly
-        let (_signing_instructions, signature) = output.into_parts();
-        
-        // Add the Authorization header with the signature
-        header_map.insert("Authorization".to_string(), signature);
-        
-        // Add the X-Amz-Date header
-        let date_time = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
-        header_map.insert("X-Amz-Date".to_string(), date_time);
-        
-        // Add security token if present
-        if let Some(token) = credentials.session_token() {
-            header_map.insert("X-Amz-Security-Token".to_string(), token.to_string());
-        }
-        
-        // Add other required headers
-        for (name, value) in headers.iter() {
-            if !header_map.contains_key(name.as_str()) {
-                header_map.insert(
-                    name.to_string(),
-                    value.to_str().map_err(|_| Error::Config("Invalid header value".into()))?.to_string(),
-                );
-            }
-        }
-        
-        Ok(AwsAuthRequest {
-            method: method.to_string(),
-            host: host.clone(),
-            region: self.region.to_string(),
-            service: service.to_string(),
-            date: Utc::now().format("%Y%m%dT%H%M%SZ").to_string(),
-            expires_in: 900,
-            body: body.to_string(),
-            headers: header_map,
-        })
-    }
-    
-    async fn get_lakefs_token(&self, auth_request: AwsAuthRequest) -> Result<String> {
-        let url = match &self.base_uri {
-            Some(base) => format!("{}/external/auth/external_principal_login", base),
-            None => format!("{}/api/v1/external/auth/external_principal_login", self.endpoint),
-        };
-        
+
+    async fn login(&self, identity_token: &str) -> Result<AuthenticationToken> {
         let response = self.client
-            .post(&url)
-            .json(&auth_request)
+            .post(&self.login_url)
+            .json(&ExternalLoginInformation {
+                identity_request: IdentityRequest { identity_token },
+                token_expiration_duration: TOKEN_TTL_SECS,
+            })
             .send()
             .await?;
-        
+
         if response.status().is_success() {
-            let auth_response: LakeFSAuthResponse = response.json().await?;
-            Ok(auth_response.token)
+            Ok(response.json().await?)
         } else {
             let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
             Err(Error::Aws(format!("Authentication failed: {}", error_text)))
@@ -193,11 +178,90 @@ impl AwsIamAuth {
     }
 }
 
+/// Host (and port, if any) of the lakeFS endpoint, which lakeFS compares
+/// with the signed server ID header.
+fn server_id(endpoint: &str) -> Result<String> {
+    let url = url::Url::parse(endpoint).map_err(|e| Error::Config(format!("Invalid endpoint '{}': {}", endpoint, e)))?;
+    let host = url.host_str().ok_or_else(|| Error::Config(format!("Endpoint '{}' has no host", endpoint)))?;
+    Ok(match url.port() {
+        Some(port) => format!("{}:{}", host, port),
+        None => host.to_string(),
+    })
+}
+
 #[async_trait]
 impl AuthProvider for AwsIamAuth {
     async fn get_auth_header(&self) -> Result<String> {
-        let sts_request = self.create_sts_request().await?;
-        let token = self.get_lakefs_token(sts_request).await?;
-        Ok(format!("Bearer {}", token))
+        if let Some(token) = self.cache.get() {
+            return Ok(format!("Bearer {}", token));
+        }
+
+        let credentials = self.credentials_provider
+            .provide_credentials()
+            .await
+            .map_err(|e| Error::Aws(e.to_string()))?;
+        let request = presign_identity_request(&credentials, self.region.as_ref(), &self.server_id, SystemTime::now())?;
+        let auth = self.login(&request.encode()?).await?;
+        self.cache.set(auth.token.clone(), auth.token_expiration);
+        Ok(format!("Bearer {}", auth.token))
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Example credentials from the AWS Signature Version 4 documentation
+    fn credentials(session_token: Option<&str>) -> Credentials {
+        Credentials::new(
+            "AKIDEXAMPLE",
+            "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY",
+            session_token.map(str::to_string),
+            None,
+            "test",
+        )
+    }
+
+    fn fixture_time() -> SystemTime {
+        // 2015-08-30T12:36:00Z
+        SystemTime::UNIX_EPOCH + Duration::from_secs(1_440_938_160)
+    }
+
+    #[test]
+    fn test_presign_identity_request() {
+        let info = presign_identity_request(&credentials(None), "us-east-1", "lakefs.example.com", fixture_time()).unwrap();
+        assert_eq!(info, IdentityTokenInfo {
+            method: "POST".into(),
+            host: "sts.us-east-1.amazonaws.com".into(),
+            region: "us-east-1".into(),
+            action: "GetCallerIdentity".into(),
+            date: "20150830T123600Z".into(),
+            expiration_duration: "60".into(),
+            access_key_id: "AKIDEXAMPLE".into(),
+            signature: "40d5cebb967d545ca19ae662e264ceb0fb32fce16350da6f1cd5c7d8ca6f3672".into(),
+            signed_headers: vec!["host".into(), "x-lakefs-server-id".into()],
+            version: "2011-06-15".into(),
+            algorithm: "AWS4-HMAC-SHA256".into(),
+            security_token: String::new(),
+        });
+    }
+
+    #[test]
+    fn test_presign_identity_request_with_session_token() {
+        let info = presign_identity_request(&credentials(Some("session/token+1")), "eu-west-1", "lakefs:8000", fixture_time()).unwrap();
+        assert_eq!(info.host, "sts.eu-west-1.amazonaws.com");
+        assert_eq!(info.security_token, "session/token+1");
+        assert_eq!(info.signature, "29e838acb6284db6d4e6a07614b53bb2dc30d87ae11afe264ac88a1f3e78b17e");
+
+        let decoded = base64::engine::general_purpose::STANDARD.decode(info.encode().unwrap()).unwrap();
+        let round_trip: IdentityTokenInfo = serde_json::from_slice(&decoded).unwrap();
+        assert_eq!(round_trip, info);
+    }
+
+    #[test]
+    fn test_server_id() {
+        assert_eq!(server_id("https://lakefs.example.com/api/v1").unwrap(), "lakefs.example.com");
+        assert_eq!(server_id("http://localhost:8000").unwrap(), "localhost:8000");
+        assert!(server_id("not a url").is_err());
+    }
+}
@@ -0,0 +1,135 @@
+use crate::{
+    auth_provider::AuthProvider,
+    error::{Error, Result},
+    external::{login_url, login_with_identity_token, TokenCache},
+};
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::Deserialize;
+
+const IMDS_TOKEN_URL: &str = "http://169.254.169.254/metadata/identity/oauth2/token";
+const DEFAULT_RESOURCE: &str = "https://management.azure.com/";
+const LOGIN_PATH: &str = "/auth/external/azure/login";
+
+#[derive(Debug, Deserialize)]
+struct ImdsToken {
+    access_token: String,
+}
+
+/// Logs in to lakeFS with an Azure Managed Identity token from the instance
+/// metadata service.
+pub struct AzureIdentityAuth {
+    login_url: String,
+    metadata_url: String,
+    client_id: Option<String>,
+    resource: String,
+    client: Client,
+    cache: TokenCache,
+}
+
+impl AzureIdentityAuth {
+    /// `client_id` selects a user-assigned identity; the system-assigned
+    /// identity is used when it is `None`.
+    pub fn new(
+        endpoint: &str,
+        client_id: Option<String>,
+        resource: Option<String>,
+        base_uri: Option<String>,
+    ) -> Self {
+        Self {
+            login_url: login_url(endpoint, base_uri.as_deref(), LOGIN_PATH),
+            metadata_url: IMDS_TOKEN_URL.to_string(),
+            client_id,
+            resource: resource.unwrap_or_else(|| DEFAULT_RESOURCE.to_string()),
+            client: Client::new(),
+            cache: TokenCache::default(),
+        }
+    }
+
+    /// Fetch identity tokens from `url` instead of the instance metadata service.
+    pub fn with_metadata_url(mut self, url: impl Into<String>) -> Self {
+        self.metadata_url = url.into();
+        self
+    }
+
+    async fn identity_token(&self) -> Result<String> {
+        let mut query = vec![
+            ("api-version", "2018-02-01"),
+            ("resource", self.resource.as_str()),
+        ];
+        if let Some(client_id) = &self.client_id {
+            query.push(("client_id", client_id));
+        }
+
+        let response = self.client
+            .get(&self.metadata_url)
+            .header("Metadata", "true")
+            .query(&query)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+            return Err(Error::Identity(format!("Azure managed identity token request failed: {}", error_text)));
+        }
+
+        let token: ImdsToken = response.json().await?;
+        Ok(token.access_token)
+    }
+}
+
+#[async_trait]
+impl AuthProvider for AzureIdentityAuth {
+    async fn get_auth_header(&self) -> Result<String> {
+        if let Some(token) = self.cache.get() {
+            return Ok(format!("Bearer {}", token));
+        }
+
+        let identity_token = self.identity_token().await?;
+        let (token, expires_at) = login_with_identity_token(&self.client, &self.login_url, &identity_token).await?;
+        self.cache.set(token.clone(), expires_at);
+        Ok(format!("Bearer {}", token))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{body_json, header, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_azure_identity_login() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/metadata/identity/oauth2/token"))
+            .and(header("Metadata", "true"))
+            .and(query_param("client_id", "user-assigned"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "access_token": "azure-jwt",
+                "expires_in": "3599"
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("POST"))
+            .and(path("/api/v1/auth/external/azure/login"))
+            .and(body_json(serde_json::json!({"identity_token": "azure-jwt"})))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "token": "lakefs-token",
+                "token_expiration": chrono::Utc::now().timestamp() + 3600
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let auth = AzureIdentityAuth::new(&mock_server.uri(), Some("user-assigned".into()), None, None)
+            .with_metadata_url(format!("{}/metadata/identity/oauth2/token", mock_server.uri()));
+
+        assert_eq!(auth.get_auth_header().await.unwrap(), "Bearer lakefs-token");
+        // Served from the cache
+        assert_eq!(auth.get_auth_header().await.unwrap(), "Bearer lakefs-token");
+    }
+}
@@ -1,6 +1,10 @@
-use crate::{auth_provider::AuthProvider, error::Result};
+use crate::{auth_provider::AuthProvider, error::{Error, Result}};
 use async_trait::async_trait;
 use base64::Engine;
+use std::io::{self, BufRead, IsTerminal, Write};
+
+const ACCESS_KEY_ID_VAR: &str = "LAKECTL_CREDENTIALS_ACCESS_KEY_ID";
+const SECRET_ACCESS_KEY_VAR: &str = "LAKECTL_CREDENTIALS_SECRET_ACCESS_KEY";
 
 pub struct BasicAuth {
     access_key_id: String,
@@ -14,6 +18,46 @@ impl BasicAuth {
             secret_access_key,
         }
     }
+
+    /// Read the key pair from the environment variables the Go lakectl uses.
+    pub fn from_env() -> Result<Self> {
+        let var = |name: &str| {
+            std::env::var(name)
+                .ok()
+                .filter(|value| !value.is_empty())
+                .ok_or_else(|| Error::Config(format!("{} is not set", name)))
+        };
+        Ok(Self::new(var(ACCESS_KEY_ID_VAR)?, var(SECRET_ACCESS_KEY_VAR)?))
+    }
+
+    /// Ask for the key pair on the terminal. Fails without one, so
+    /// unattended runs do not block on input.
+    pub async fn prompt() -> Result<Self> {
+        tokio::task::spawn_blocking(|| {
+            if !io::stdin().is_terminal() {
+                return Err(Error::Config("no terminal to prompt for credentials".to_string()));
+            }
+            let access_key_id = read_line("Access key ID: ")?;
+            let secret_access_key = read_line("Secret access key: ")?;
+            Ok(Self::new(access_key_id, secret_access_key))
+        })
+        .await
+        .map_err(|e| Error::Config(e.to_string()))?
+    }
+}
+
+fn read_line(prompt: &str) -> Result<String> {
+    let io_error = |e: io::Error| Error::Config(format!("failed to read credentials: {}", e));
+    eprint!("{}", prompt);
+    io::stderr().flush().map_err(io_error)?;
+
+    let mut input = String::new();
+    io::stdin().lock().read_line(&mut input).map_err(io_error)?;
+    let value = input.trim();
+    if value.is_empty() {
+        return Err(Error::InvalidCredentials);
+    }
+    Ok(value.to_string())
 }
 
 #[async_trait]
@@ -0,0 +1,99 @@
+use crate::{
+    auth_provider::{create_auth_provider, AuthConfig, AuthProvider},
+    error::{Error, Result},
+};
+use async_trait::async_trait;
+use tokio::sync::Mutex;
+
+/// Tries each configured provider in order and keeps using the first one
+/// that produces credentials, so one configuration can cover a laptop
+/// (environment, prompt) and CI (IAM) alike.
+pub struct ChainAuthProvider {
+    providers: Vec<AuthConfig>,
+    endpoint: String,
+    selected: Mutex<Option<Box<dyn AuthProvider>>>,
+}
+
+impl ChainAuthProvider {
+    pub fn new(providers: Vec<AuthConfig>, endpoint: &str) -> Self {
+        Self {
+            providers,
+            endpoint: endpoint.to_string(),
+            selected: Mutex::new(None),
+        }
+    }
+}
+
+#[async_trait]
+impl AuthProvider for ChainAuthProvider {
+    async fn get_auth_header(&self) -> Result<String> {
+        let mut selected = self.selected.lock().await;
+        if let Some(provider) = selected.as_ref() {
+            return provider.get_auth_header().await;
+        }
+
+        let mut failures = Vec::new();
+        for config in &self.providers {
+            let name = config.name();
+            let attempt = async {
+                let provider = create_auth_provider(config.clone(), &self.endpoint).await?;
+                let header = provider.get_auth_header().await?;
+                Ok::<_, Error>((provider, header))
+            };
+            match attempt.await {
+                Ok((provider, header)) => {
+                    *selected = Some(provider);
+                    return Ok(header);
+                }
+                Err(e) => failures.push(format!("{}: {}", name, e)),
+            }
+        }
+
+        if failures.is_empty() {
+            return Err(Error::Config("credential chain has no providers".to_string()));
+        }
+        Err(Error::Config(format!("no credential provider succeeded ({})", failures.join("; "))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exec(script: &str) -> AuthConfig {
+        AuthConfig::Exec {
+            command: "sh".to_string(),
+            args: vec!["-c".to_string(), script.to_string()],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_chain_falls_back_and_keeps_winner() {
+        let temp_dir = std::env::temp_dir().join(format!("lakefs-chain-{}", std::process::id()));
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let attempts = temp_dir.join("attempts");
+        let chain = ChainAuthProvider::new(
+            vec![
+                exec(&format!("echo x >> {}; exit 1", attempts.display())),
+                AuthConfig::Basic {
+                    access_key_id: "user".to_string(),
+                    secret_access_key: "pass".to_string(),
+                },
+            ],
+            "http://localhost",
+        );
+
+        assert_eq!(chain.get_auth_header().await.unwrap(), "Basic dXNlcjpwYXNz");
+        assert_eq!(chain.get_auth_header().await.unwrap(), "Basic dXNlcjpwYXNz");
+        // The failing helper is not run again once a provider has succeeded
+        assert_eq!(std::fs::read_to_string(&attempts).unwrap().lines().count(), 1);
+        std::fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_chain_reports_every_failure() {
+        let chain = ChainAuthProvider::new(vec![exec("exit 1"), exec("echo '{}'")], "http://localhost");
+        let err = chain.get_auth_header().await.unwrap_err().to_string();
+        assert_eq!(err.matches("exec:").count(), 2, "{}", err);
+    }
+}
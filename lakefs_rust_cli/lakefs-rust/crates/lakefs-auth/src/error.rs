@@ -13,6 +13,15 @@ pub enum Error {
     
     #[error("Configuration error: {0}")]
     Config(String),
+    
+    #[error("Workload identity error: {0}")]
+    Identity(String),
+    
+    #[error("Login failed: {0}")]
+    Login(String),
+    
+    #[error("Credential helper error: {0}")]
+    CredentialHelper(String),
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,155 @@
+use crate::{
+    auth_provider::AuthProvider,
+    error::{Error, Result},
+};
+use async_trait::async_trait;
+use base64::Engine;
+use chrono::{DateTime, Duration, Utc};
+use serde::Deserialize;
+use std::process::Stdio;
+use std::sync::Mutex;
+use tokio::process::Command;
+
+/// Run the helper again this many seconds before its credentials expire.
+const EXPIRY_MARGIN_SECS: i64 = 60;
+
+/// Credentials printed to stdout by a credential helper, either a key pair or
+/// a bearer token. Expiry is given as `expiration` (RFC 3339) or `expires_in`
+/// (seconds); without either, the credentials are used for the lifetime of
+/// the process.
+#[derive(Debug, Deserialize)]
+struct ExecCredentials {
+    #[serde(default)]
+    access_key_id: Option<String>,
+    #[serde(default)]
+    secret_access_key: Option<String>,
+    #[serde(default)]
+    token: Option<String>,
+    #[serde(default)]
+    expiration: Option<DateTime<Utc>>,
+    #[serde(default)]
+    expires_in: Option<i64>,
+}
+
+impl ExecCredentials {
+    fn auth_header(&self) -> Result<String> {
+        match (&self.token, &self.access_key_id, &self.secret_access_key) {
+            (Some(token), _, _) => Ok(format!("Bearer {}", token)),
+            (None, Some(key), Some(secret)) => {
+                let credentials = format!("{}:{}", key, secret);
+                let encoded = base64::engine::general_purpose::STANDARD.encode(credentials.as_bytes());
+                Ok(format!("Basic {}", encoded))
+            }
+            _ => Err(Error::CredentialHelper(
+                "output must contain either `token` or `access_key_id` and `secret_access_key`".to_string(),
+            )),
+        }
+    }
+
+    fn expires_at(&self) -> Option<DateTime<Utc>> {
+        self.expiration
+            .or_else(|| self.expires_in.map(|secs| Utc::now() + Duration::seconds(secs)))
+    }
+}
+
+/// Obtains credentials by running an external program, like kubeconfig
+/// credential plugins.
+pub struct ExecAuth {
+    command: String,
+    args: Vec<String>,
+    cached: Mutex<Option<(String, Option<DateTime<Utc>>)>>,
+}
+
+impl ExecAuth {
+    pub fn new(command: String, args: Vec<String>) -> Self {
+        Self {
+            command,
+            args,
+            cached: Mutex::new(None),
+        }
+    }
+
+    fn cached_header(&self) -> Option<String> {
+        let cached = self.cached.lock().unwrap();
+        match cached.as_ref() {
+            Some((header, None)) => Some(header.clone()),
+            Some((header, Some(expires_at))) if Utc::now() + Duration::seconds(EXPIRY_MARGIN_SECS) < *expires_at => {
+                Some(header.clone())
+            }
+            _ => None,
+        }
+    }
+
+    async fn run(&self) -> Result<ExecCredentials> {
+        let output = Command::new(&self.command)
+            .args(&self.args)
+            .stdin(Stdio::null())
+            .output()
+            .await
+            .map_err(|e| Error::CredentialHelper(format!("failed to run {}: {}", self.command, e)))?;
+
+        if !output.status.success() {
+            return Err(Error::CredentialHelper(format!(
+                "{} exited with {}: {}",
+                self.command,
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+
+        serde_json::from_slice(&output.stdout)
+            .map_err(|e| Error::CredentialHelper(format!("invalid output from {}: {}", self.command, e)))
+    }
+}
+
+#[async_trait]
+impl AuthProvider for ExecAuth {
+    async fn get_auth_header(&self) -> Result<String> {
+        if let Some(header) = self.cached_header() {
+            return Ok(header);
+        }
+
+        let credentials = self.run().await?;
+        let header = credentials.auth_header()?;
+        *self.cached.lock().unwrap() = Some((header.clone(), credentials.expires_at()));
+        Ok(header)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shell(script: &str) -> ExecAuth {
+        ExecAuth::new("sh".to_string(), vec!["-c".to_string(), script.to_string()])
+    }
+
+    #[tokio::test]
+    async fn test_exec_key_pair() {
+        let auth = shell(r#"echo '{"access_key_id": "user", "secret_access_key": "pass"}'"#);
+        assert_eq!(auth.get_auth_header().await.unwrap(), "Basic dXNlcjpwYXNz");
+    }
+
+    #[tokio::test]
+    async fn test_exec_token_cached_until_expiry() {
+        // Each run prints a token containing the shell's pid
+        let auth = shell(r#"echo "{\"token\": \"t-$$\", \"expires_in\": 3600}""#);
+        let first = auth.get_auth_header().await.unwrap();
+        assert!(first.starts_with("Bearer t-"));
+        assert_eq!(auth.get_auth_header().await.unwrap(), first);
+
+        let auth = shell(r#"echo "{\"token\": \"t-$$\", \"expires_in\": 10}""#);
+        let first = auth.get_auth_header().await.unwrap();
+        assert_ne!(auth.get_auth_header().await.unwrap(), first);
+    }
+
+    #[tokio::test]
+    async fn test_exec_failure() {
+        let auth = shell("echo 'no credentials' >&2; exit 3");
+        let err = auth.get_auth_header().await.unwrap_err();
+        assert!(err.to_string().contains("no credentials"));
+
+        let auth = shell(r#"echo '{"expires_in": 60}'"#);
+        assert!(matches!(auth.get_auth_header().await, Err(Error::CredentialHelper(_))));
+    }
+}
@@ -0,0 +1,104 @@
+use crate::error::{Error, Result};
+use chrono::Utc;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+
+/// Refresh cached tokens this many seconds before the server says they expire.
+const EXPIRY_MARGIN_SECS: i64 = 60;
+
+#[derive(Debug, Serialize)]
+struct IdentityTokenLogin<'a> {
+    identity_token: &'a str,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct AuthenticationToken {
+    pub(crate) token: String,
+    #[serde(default)]
+    pub(crate) token_expiration: Option<i64>,
+}
+
+/// URL of a lakeFS login endpoint, relative to `base_uri` when set.
+pub(crate) fn login_url(endpoint: &str, base_uri: Option<&str>, path: &str) -> String {
+    match base_uri {
+        Some(base) => format!("{}{}", base.trim_end_matches('/'), path),
+        None => format!("{}/api/v1{}", endpoint.trim_end_matches('/'), path),
+    }
+}
+
+/// Exchange a cloud identity token for a lakeFS session token.
+pub(crate) async fn login_with_identity_token(
+    client: &Client,
+    url: &str,
+    identity_token: &str,
+) -> Result<(String, Option<i64>)> {
+    let response = client
+        .post(url)
+        .json(&IdentityTokenLogin { identity_token })
+        .send()
+        .await?;
+
+    if response.status().is_success() {
+        let auth: AuthenticationToken = response.json().await?;
+        Ok((auth.token, auth.token_expiration))
+    } else {
+        let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+        Err(Error::Identity(format!("lakeFS external login failed: {}", error_text)))
+    }
+}
+
+/// lakeFS session token reused until shortly before it expires.
+#[derive(Default)]
+pub(crate) struct TokenCache {
+    cached: Mutex<Option<(String, Option<i64>)>>,
+}
+
+impl TokenCache {
+    pub(crate) fn get(&self) -> Option<String> {
+        let cached = self.cached.lock().unwrap();
+        match cached.as_ref() {
+            Some((token, Some(expires_at))) if Utc::now().timestamp() + EXPIRY_MARGIN_SECS < *expires_at => {
+                Some(token.clone())
+            }
+            _ => None,
+        }
+    }
+
+    pub(crate) fn set(&self, token: String, expires_at: Option<i64>) {
+        *self.cached.lock().unwrap() = Some((token, expires_at));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_login_url() {
+        assert_eq!(
+            login_url("http://lakefs:8000", None, "/auth/external/gcp/login"),
+            "http://lakefs:8000/api/v1/auth/external/gcp/login"
+        );
+        assert_eq!(
+            login_url("http://lakefs:8000", Some("http://lakefs:8000/custom/"), "/auth/external/gcp/login"),
+            "http://lakefs:8000/custom/auth/external/gcp/login"
+        );
+    }
+
+    #[test]
+    fn test_token_cache_expiry() {
+        let cache = TokenCache::default();
+        assert!(cache.get().is_none());
+
+        cache.set("fresh".into(), Some(Utc::now().timestamp() + 3600));
+        assert_eq!(cache.get().as_deref(), Some("fresh"));
+
+        cache.set("stale".into(), Some(Utc::now().timestamp() + 10));
+        assert!(cache.get().is_none());
+
+        // Tokens without an expiry are not cached
+        cache.set("unknown".into(), None);
+        assert!(cache.get().is_none());
+    }
+}
@@ -0,0 +1,127 @@
+use crate::{
+    auth_provider::AuthProvider,
+    error::{Error, Result},
+    external::{login_url, login_with_identity_token, TokenCache},
+};
+use async_trait::async_trait;
+use reqwest::Client;
+
+const METADATA_IDENTITY_URL: &str =
+    "http://metadata.google.internal/computeMetadata/v1/instance/service-accounts/default/identity";
+const LOGIN_PATH: &str = "/auth/external/gcp/login";
+
+/// Logs in to lakeFS with a GCP service-account identity token from the
+/// metadata server.
+pub struct GcpIdentityAuth {
+    login_url: String,
+    metadata_url: String,
+    audience: String,
+    client: Client,
+    cache: TokenCache,
+}
+
+impl GcpIdentityAuth {
+    /// The identity token is issued for `audience`, defaulting to the lakeFS
+    /// endpoint.
+    pub fn new(endpoint: &str, audience: Option<String>, base_uri: Option<String>) -> Self {
+        Self {
+            login_url: login_url(endpoint, base_uri.as_deref(), LOGIN_PATH),
+            metadata_url: METADATA_IDENTITY_URL.to_string(),
+            audience: audience.unwrap_or_else(|| endpoint.to_string()),
+            client: Client::new(),
+            cache: TokenCache::default(),
+        }
+    }
+
+    /// Fetch identity tokens from `url` instead of the metadata server.
+    pub fn with_metadata_url(mut self, url: impl Into<String>) -> Self {
+        self.metadata_url = url.into();
+        self
+    }
+
+    async fn identity_token(&self) -> Result<String> {
+        let response = self.client
+            .get(&self.metadata_url)
+            .header("Metadata-Flavor", "Google")
+            .query(&[("audience", self.audience.as_str()), ("format", "full")])
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+            return Err(Error::Identity(format!("GCP identity token request failed: {}", error_text)));
+        }
+
+        Ok(response.text().await?.trim().to_string())
+    }
+}
+
+#[async_trait]
+impl AuthProvider for GcpIdentityAuth {
+    async fn get_auth_header(&self) -> Result<String> {
+        if let Some(token) = self.cache.get() {
+            return Ok(format!("Bearer {}", token));
+        }
+
+        let identity_token = self.identity_token().await?;
+        let (token, expires_at) = login_with_identity_token(&self.client, &self.login_url, &identity_token).await?;
+        self.cache.set(token.clone(), expires_at);
+        Ok(format!("Bearer {}", token))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{body_json, header, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_gcp_identity_login() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/identity"))
+            .and(header("Metadata-Flavor", "Google"))
+            .and(query_param("audience", "lakefs"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("gcp-jwt\n"))
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("POST"))
+            .and(path("/api/v1/auth/external/gcp/login"))
+            .and(body_json(serde_json::json!({"identity_token": "gcp-jwt"})))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "token": "lakefs-token"
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let auth = GcpIdentityAuth::new(&mock_server.uri(), Some("lakefs".into()), None)
+            .with_metadata_url(format!("{}/identity", mock_server.uri()));
+
+        assert_eq!(auth.get_auth_header().await.unwrap(), "Bearer lakefs-token");
+    }
+
+    #[tokio::test]
+    async fn test_gcp_identity_login_rejected() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/identity"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("gcp-jwt"))
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("POST"))
+            .and(path("/api/v1/auth/external/gcp/login"))
+            .respond_with(ResponseTemplate::new(401).set_body_string("unknown principal"))
+            .mount(&mock_server)
+            .await;
+
+        let auth = GcpIdentityAuth::new(&mock_server.uri(), None, None)
+            .with_metadata_url(format!("{}/identity", mock_server.uri()));
+
+        assert!(matches!(auth.get_auth_header().await, Err(Error::Identity(_))));
+    }
+}
@@ -1,7 +1,15 @@
 pub mod auth_provider;
 pub mod basic;
 pub mod aws_iam;
+pub mod azure_identity;
+pub mod gcp_identity;
 pub mod error;
+pub mod exec;
+pub mod login;
+pub mod chain;
+pub mod storage;
+mod external;
 
 pub use auth_provider::{AuthProvider, AuthConfig, create_auth_provider};
-pub use error::{Error, Result};
\ No newline at end of file
+pub use error::{Error, Result};
+pub use storage::{ObjectStoreConfig, StoreObject};
\ No newline at end of file
@@ -0,0 +1,114 @@
+use crate::{
+    auth_provider::AuthProvider,
+    error::{Error, Result},
+    external::{login_url, AuthenticationToken, TokenCache},
+};
+use async_trait::async_trait;
+use reqwest::{Client, StatusCode};
+use serde::Serialize;
+
+const LOGIN_PATH: &str = "/auth/login";
+
+#[derive(Debug, Serialize)]
+struct LoginRequest<'a> {
+    access_key_id: &'a str,
+    secret_access_key: &'a str,
+}
+
+/// Exchanges an access key pair for a lakeFS session token through
+/// `/auth/login`, as lakectl does, so the server verifies the secret once per
+/// session rather than on every request.
+pub struct LoginAuth {
+    login_url: String,
+    access_key_id: String,
+    secret_access_key: String,
+    client: Client,
+    cache: TokenCache,
+}
+
+impl LoginAuth {
+    pub fn new(endpoint: &str, access_key_id: String, secret_access_key: String, base_uri: Option<String>) -> Self {
+        Self {
+            login_url: login_url(endpoint, base_uri.as_deref(), LOGIN_PATH),
+            access_key_id,
+            secret_access_key,
+            client: Client::new(),
+            cache: TokenCache::default(),
+        }
+    }
+
+    async fn login(&self) -> Result<AuthenticationToken> {
+        let response = self.client
+            .post(&self.login_url)
+            .json(&LoginRequest {
+                access_key_id: &self.access_key_id,
+                secret_access_key: &self.secret_access_key,
+            })
+            .send()
+            .await?;
+
+        match response.status() {
+            status if status.is_success() => Ok(response.json().await?),
+            StatusCode::UNAUTHORIZED => Err(Error::InvalidCredentials),
+            _ => {
+                let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+                Err(Error::Login(error_text))
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl AuthProvider for LoginAuth {
+    async fn get_auth_header(&self) -> Result<String> {
+        if let Some(token) = self.cache.get() {
+            return Ok(format!("Bearer {}", token));
+        }
+
+        let auth = self.login().await?;
+        self.cache.set(auth.token.clone(), auth.token_expiration);
+        Ok(format!("Bearer {}", auth.token))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use wiremock::matchers::{body_json, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_login_session_reused() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/api/v1/auth/login"))
+            .and(body_json(serde_json::json!({"access_key_id": "key", "secret_access_key": "secret"})))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "token": "session-jwt",
+                "token_expiration": Utc::now().timestamp() + 3600
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let auth = LoginAuth::new(&mock_server.uri(), "key".into(), "secret".into(), None);
+        assert_eq!(auth.get_auth_header().await.unwrap(), "Bearer session-jwt");
+        assert_eq!(auth.get_auth_header().await.unwrap(), "Bearer session-jwt");
+    }
+
+    #[tokio::test]
+    async fn test_login_rejected() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/api/v1/auth/login"))
+            .respond_with(ResponseTemplate::new(401).set_body_string("error authenticating request"))
+            .mount(&mock_server)
+            .await;
+
+        let auth = LoginAuth::new(&mock_server.uri(), "key".into(), "wrong".into(), None);
+        assert!(matches!(auth.get_auth_header().await, Err(Error::InvalidCredentials)));
+    }
+}
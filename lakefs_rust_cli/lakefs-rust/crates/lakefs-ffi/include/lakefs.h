@@ -0,0 +1,86 @@
+/*
+ * C interface to the lakeFS client, implemented by liblakefs.
+ *
+ * Every call blocks until it is done. Functions returning int give
+ * LAKEFS_OK on success and LAKEFS_ERROR on failure; lakefs_client_new
+ * gives NULL. Either way lakefs_last_error() then describes what went
+ * wrong on the calling thread. Strings are UTF-8 and NUL-terminated, and
+ * are never retained past the call.
+ */
+#ifndef LAKEFS_H
+#define LAKEFS_H
+
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define LAKEFS_OK 0
+#define LAKEFS_ERROR (-1)
+
+typedef struct LakeFSHandle LakeFSHandle;
+
+/* What lakefs_sync_directory did */
+typedef struct LakeFSSyncStats {
+    uint64_t uploaded;
+    uint64_t downloaded;
+    uint64_t removed;
+    uint64_t unchanged;
+    uint64_t failed;
+} LakeFSSyncStats;
+
+/*
+ * Message for the last call on this thread that failed, or NULL. Valid
+ * until the next failing call on the same thread; copy it to keep it.
+ */
+const char *lakefs_last_error(void);
+
+/*
+ * Connect to the lakeFS API at endpoint (such as
+ * "http://localhost:8000/api/v1"). With NULL keys, credentials are read
+ * from LAKECTL_CREDENTIALS_ACCESS_KEY_ID and
+ * LAKECTL_CREDENTIALS_SECRET_ACCESS_KEY. Free with lakefs_client_free.
+ */
+LakeFSHandle *lakefs_client_new(const char *endpoint,
+                                const char *access_key_id,
+                                const char *secret_access_key);
+
+/* Release a handle once no call is using it. NULL is ignored. */
+void lakefs_client_free(LakeFSHandle *handle);
+
+/* Upload the file at local_path to path on branch */
+int lakefs_upload_file(const LakeFSHandle *handle,
+                       const char *repository,
+                       const char *branch,
+                       const char *path,
+                       const char *local_path);
+
+/*
+ * Download path at reference into local_path. The file only appears once
+ * it is complete and matches the object's checksum.
+ */
+int lakefs_download_file(const LakeFSHandle *handle,
+                         const char *repository,
+                         const char *reference,
+                         const char *path,
+                         const char *local_path);
+
+/*
+ * Sync the directory at local_path with uri
+ * ("lakefs://repository/branch[/prefix]") both ways, as `lakectl local
+ * sync` does, using at most parallelism transfers at a time (0 for the
+ * default). Counts are written to stats if it is not NULL; transfers that
+ * fail are counted in stats->failed without failing the call.
+ */
+int lakefs_sync_directory(const LakeFSHandle *handle,
+                          const char *local_path,
+                          const char *uri,
+                          uint32_t parallelism,
+                          LakeFSSyncStats *stats);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* LAKEFS_H */
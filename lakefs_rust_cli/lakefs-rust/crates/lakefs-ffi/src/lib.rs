@@ -0,0 +1,313 @@
+//! C interface for embedding the client in other languages, declared in
+//! `include/lakefs.h`.
+//!
+//! Every call blocks until it is done. Functions returning `int` give
+//! `LAKEFS_OK` (0) on success and `LAKEFS_ERROR` (-1) on failure; functions
+//! returning a handle give NULL. Either way [`lakefs_last_error`] then
+//! describes what went wrong on the calling thread. Strings are UTF-8 and
+//! NUL-terminated, and are never retained past the call.
+
+use lakefs_api::{ClientConfig, LakeFSClient, LakeFSUri, UploadOptions};
+use lakefs_auth::basic::BasicAuth;
+use lakefs_local::{transfer, NoopProgress, ProgressReporter, SyncConfig, SyncManager};
+use std::cell::RefCell;
+use std::ffi::{c_char, c_int, CStr, CString};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use std::ptr;
+use std::sync::Arc;
+use tokio::runtime::Runtime;
+
+pub const LAKEFS_OK: c_int = 0;
+pub const LAKEFS_ERROR: c_int = -1;
+
+/// Opaque handle created by [`lakefs_client_new`].
+pub struct LakeFSHandle {
+    client: LakeFSClient,
+    runtime: Runtime,
+}
+
+/// What [`lakefs_sync_directory`] did.
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct LakeFSSyncStats {
+    pub uploaded: u64,
+    pub downloaded: u64,
+    pub removed: u64,
+    pub unchanged: u64,
+    pub failed: u64,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: impl std::fmt::Display) {
+    // Messages with an interior NUL are cut short rather than lost
+    let message = message.to_string();
+    let end = message.find('\0').unwrap_or(message.len());
+    let message = CString::new(&message[..end]).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+}
+
+/// Run `f`, turning an error or a panic into `None` and the last error.
+/// Unwinding out of an `extern "C"` function aborts the process.
+fn guard<T>(f: impl FnOnce() -> Result<T, String>) -> Option<T> {
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(value)) => Some(value),
+        Ok(Err(message)) => {
+            set_last_error(message);
+            None
+        }
+        Err(_) => {
+            set_last_error("internal error: the call panicked");
+            None
+        }
+    }
+}
+
+fn status(result: Option<()>) -> c_int {
+    match result {
+        Some(()) => LAKEFS_OK,
+        None => LAKEFS_ERROR,
+    }
+}
+
+/// # Safety
+/// `value` must be NULL or a valid NUL-terminated string.
+unsafe fn string<'a>(value: *const c_char, name: &str) -> Result<&'a str, String> {
+    if value.is_null() {
+        return Err(format!("{} is NULL", name));
+    }
+    CStr::from_ptr(value).to_str().map_err(|_| format!("{} is not valid UTF-8", name))
+}
+
+/// # Safety
+/// `handle` must be NULL or a handle from `lakefs_client_new` not yet freed.
+unsafe fn handle<'a>(handle: *const LakeFSHandle) -> Result<&'a LakeFSHandle, String> {
+    handle.as_ref().ok_or_else(|| "client handle is NULL".to_string())
+}
+
+/// Message for the last call on this thread that failed, or NULL. Valid
+/// until the next failing call on the same thread; copy it to keep it.
+#[no_mangle]
+pub extern "C" fn lakefs_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| last.borrow().as_ref().map_or(ptr::null(), |message| message.as_ptr()))
+}
+
+/// Connect to the lakeFS API at `endpoint` (such as
+/// `http://localhost:8000/api/v1`). With NULL keys, credentials are read
+/// from `LAKECTL_CREDENTIALS_ACCESS_KEY_ID` and
+/// `LAKECTL_CREDENTIALS_SECRET_ACCESS_KEY`. Free the handle with
+/// `lakefs_client_free`.
+///
+/// # Safety
+/// Every argument must be NULL or a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn lakefs_client_new(
+    endpoint: *const c_char,
+    access_key_id: *const c_char,
+    secret_access_key: *const c_char,
+) -> *mut LakeFSHandle {
+    let created = guard(|| {
+        let endpoint = string(endpoint, "endpoint")?;
+        let auth = match (access_key_id.is_null(), secret_access_key.is_null()) {
+            (true, true) => BasicAuth::from_env().map_err(|e| e.to_string())?,
+            _ => BasicAuth::new(
+                string(access_key_id, "access_key_id")?.to_string(),
+                string(secret_access_key, "secret_access_key")?.to_string(),
+            ),
+        };
+        let client = LakeFSClient::from_provider(endpoint, Arc::new(auth), ClientConfig::default()).map_err(|e| e.to_string())?;
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| format!("failed to start the runtime: {}", e))?;
+        Ok(Box::new(LakeFSHandle { client, runtime }))
+    });
+    created.map_or(ptr::null_mut(), Box::into_raw)
+}
+
+/// Release a handle from `lakefs_client_new`. NULL is ignored.
+///
+/// # Safety
+/// `handle` must be NULL or a handle not yet freed, with no call using it
+/// still running.
+#[no_mangle]
+pub unsafe extern "C" fn lakefs_client_free(handle: *mut LakeFSHandle) {
+    if !handle.is_null() {
+        drop(Box::from_raw(handle));
+    }
+}
+
+/// Upload the file at `local_path` to `path` on `branch`, streaming large
+/// files from disk.
+///
+/// # Safety
+/// `handle` must be a live handle and the strings valid NUL-terminated
+/// strings.
+#[no_mangle]
+pub unsafe extern "C" fn lakefs_upload_file(
+    handle: *const LakeFSHandle,
+    repository: *const c_char,
+    branch: *const c_char,
+    path: *const c_char,
+    local_path: *const c_char,
+) -> c_int {
+    status(guard(|| {
+        let handle = self::handle(handle)?;
+        let (repository, branch) = (string(repository, "repository")?, string(branch, "branch")?);
+        let (path, local_path) = (string(path, "path")?, string(local_path, "local_path")?);
+        let reporter: Arc<dyn ProgressReporter> = Arc::new(NoopProgress);
+        handle.runtime.block_on(transfer::upload_file(
+            &handle.client,
+            repository,
+            branch,
+            path,
+            Path::new(local_path),
+            path,
+            &UploadOptions::default(),
+            None,
+            None,
+            &reporter,
+        ))
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    }))
+}
+
+/// Download `path` at `reference` into `local_path`. The file only appears
+/// once it is complete and matches the object's checksum.
+///
+/// # Safety
+/// `handle` must be a live handle and the strings valid NUL-terminated
+/// strings.
+#[no_mangle]
+pub unsafe extern "C" fn lakefs_download_file(
+    handle: *const LakeFSHandle,
+    repository: *const c_char,
+    reference: *const c_char,
+    path: *const c_char,
+    local_path: *const c_char,
+) -> c_int {
+    status(guard(|| {
+        let handle = self::handle(handle)?;
+        let (repository, reference) = (string(repository, "repository")?, string(reference, "reference")?);
+        let (path, local_path) = (string(path, "path")?, string(local_path, "local_path")?);
+        let reporter: Arc<dyn ProgressReporter> = Arc::new(NoopProgress);
+        handle.runtime.block_on(async {
+            let stats = handle.client.get_object(repository, reference, path).await?;
+            transfer::download(&handle.client, repository, reference, &stats, Path::new(local_path), path, None, &reporter).await
+        })
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    }))
+}
+
+/// Sync the directory at `local_path` with `uri`
+/// (`lakefs://repository/branch[/prefix]`) both ways, as `lakectl local
+/// sync` does, using at most `parallelism` transfers at a time (0 for the
+/// default). Counts are written to `stats` if it is not NULL. Transfers
+/// that fail are counted in `stats->failed` without failing the call.
+///
+/// # Safety
+/// `handle` must be a live handle, the strings valid NUL-terminated strings
+/// and `stats` NULL or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn lakefs_sync_directory(
+    handle: *const LakeFSHandle,
+    local_path: *const c_char,
+    uri: *const c_char,
+    parallelism: u32,
+    stats: *mut LakeFSSyncStats,
+) -> c_int {
+    status(guard(|| {
+        let handle = self::handle(handle)?;
+        let local_path = string(local_path, "local_path")?;
+        let uri: LakeFSUri = string(uri, "uri")?.parse().map_err(|e: lakefs_api::Error| e.to_string())?;
+        let mut config = SyncConfig::default();
+        if parallelism > 0 {
+            config.parallelism = parallelism as usize;
+        }
+        let manager = SyncManager::new(Arc::new(handle.client.clone()), config);
+        let result = handle.runtime
+            .block_on(manager.sync(Path::new(local_path), &uri))
+            .map_err(|e| e.to_string())?;
+        if let Some(stats) = stats.as_mut() {
+            *stats = LakeFSSyncStats {
+                uploaded: result.uploaded as u64,
+                downloaded: result.downloaded as u64,
+                removed: result.removed as u64,
+                unchanged: result.unchanged as u64,
+                failed: result.errors.len() as u64,
+            };
+        }
+        Ok(())
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lakefs_test_utils::scenario::MockLakeFS;
+    use tempfile::TempDir;
+
+    fn c(value: &str) -> CString {
+        CString::new(value).unwrap()
+    }
+
+    fn last_error() -> String {
+        unsafe { CStr::from_ptr(lakefs_last_error()) }.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn test_download_and_sync() {
+        let server_runtime = Runtime::new().unwrap();
+        let server = server_runtime.block_on(
+            MockLakeFS::new()
+                .with_repo("repo")
+                .with_branch("main")
+                .with_object("data/a.csv", "a,b\n1,2\n")
+                .start(),
+        );
+        let dir = TempDir::new().unwrap();
+
+        unsafe {
+            let endpoint = c(&server.uri());
+            let handle = lakefs_client_new(endpoint.as_ptr(), c("key").as_ptr(), c("secret").as_ptr());
+            assert!(!handle.is_null());
+
+            let target = c(dir.path().join("a.csv").to_str().unwrap());
+            let (repo, main, object) = (c("repo"), c("main"), c("data/a.csv"));
+            let code = lakefs_download_file(handle, repo.as_ptr(), main.as_ptr(), object.as_ptr(), target.as_ptr());
+            assert_eq!(code, LAKEFS_OK);
+            assert_eq!(std::fs::read_to_string(dir.path().join("a.csv")).unwrap(), "a,b\n1,2\n");
+
+            let missing = c("data/missing.csv");
+            let code = lakefs_download_file(handle, repo.as_ptr(), main.as_ptr(), missing.as_ptr(), target.as_ptr());
+            assert_eq!(code, LAKEFS_ERROR);
+            assert!(last_error().contains("not found"), "{}", last_error());
+
+            let checkout = c(dir.path().join("checkout").to_str().unwrap());
+            let mut stats = LakeFSSyncStats::default();
+            let code = lakefs_sync_directory(handle, checkout.as_ptr(), c("lakefs://repo/main").as_ptr(), 2, &mut stats);
+            assert_eq!(code, LAKEFS_OK, "{}", last_error());
+            assert_eq!(stats, LakeFSSyncStats { downloaded: 1, ..Default::default() });
+
+            lakefs_client_free(handle);
+        }
+    }
+
+    #[test]
+    fn test_invalid_arguments_set_last_error() {
+        unsafe {
+            assert!(lakefs_client_new(ptr::null(), ptr::null(), ptr::null()).is_null());
+            assert_eq!(last_error(), "endpoint is NULL");
+
+            let code = lakefs_upload_file(ptr::null(), ptr::null(), ptr::null(), ptr::null(), ptr::null());
+            assert_eq!(code, LAKEFS_ERROR);
+            assert_eq!(last_error(), "client handle is NULL");
+            lakefs_client_free(ptr::null_mut());
+        }
+    }
+}
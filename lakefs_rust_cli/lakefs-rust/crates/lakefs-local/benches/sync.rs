@@ -0,0 +1,99 @@
+//! Benchmarks of the sync engine against synthetic trees.
+//!
+//! Run with `cargo bench -p lakefs-local`. Transfers go to a local mock
+//! server, so they measure the engine's own overhead rather than a network;
+//! `lakectl bench sync` measures a real server.
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use lakefs_api::{LakeFSClient, LakeFSUri};
+use lakefs_local::{delta, ChangeDetector, LocalIndex, SyncConfig, SyncManager};
+use lakefs_test_utils::scenario::MockLakeFS;
+use std::path::Path;
+use std::sync::Arc;
+use tempfile::TempDir;
+
+const FILES: usize = 1000;
+const FILE_SIZE: usize = 4096;
+/// Files per directory of a synthetic tree
+const FAN_OUT: usize = 100;
+
+fn content(i: usize, size: usize) -> Vec<u8> {
+    (0..size).map(|j| (i * 31 + j) as u8).collect()
+}
+
+fn tree_path(i: usize) -> String {
+    format!("d{:03}/f{:05}.bin", i / FAN_OUT, i)
+}
+
+/// `files` files of `size` bytes each under `root`.
+fn write_tree(root: &Path, files: usize, size: usize) {
+    for i in 0..files {
+        let path = root.join(tree_path(i));
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content(i, size)).unwrap();
+    }
+}
+
+fn bench_detect_changes(c: &mut Criterion) {
+    let dir = TempDir::new().unwrap();
+    write_tree(dir.path(), FILES, FILE_SIZE);
+    let index = LocalIndex::new("repo", "main", "c0");
+    let detector = ChangeDetector::new(dir.path().to_path_buf());
+
+    let mut group = c.benchmark_group("detect_changes");
+    group.throughput(Throughput::Elements(FILES as u64));
+    group.bench_function("new_files", |b| {
+        b.iter(|| detector.detect_changes(&index, Vec::new()).unwrap())
+    });
+    group.finish();
+}
+
+fn bench_block_checksums(c: &mut Criterion) {
+    const SIZE: usize = 16 * 1024 * 1024;
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("large.bin");
+    std::fs::write(&path, content(0, SIZE)).unwrap();
+
+    let mut group = c.benchmark_group("block_checksums");
+    group.throughput(Throughput::Bytes(SIZE as u64));
+    group.bench_function("16MiB", |b| b.iter(|| delta::block_checksums(&path).unwrap()));
+    group.finish();
+}
+
+fn bench_pull(c: &mut Criterion) {
+    const OBJECTS: usize = 200;
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let server = runtime.block_on(async {
+        let mut mock = MockLakeFS::new().with_repo("repo").with_branch("main");
+        for i in 0..OBJECTS {
+            mock = mock.with_object(&tree_path(i), content(i, FILE_SIZE));
+        }
+        mock.start().await
+    });
+    let client = Arc::new(LakeFSClient::new(server.uri(), "Bearer test-token"));
+    let remote = LakeFSUri::new("repo", "main");
+
+    let mut group = c.benchmark_group("pull");
+    group.throughput(Throughput::Bytes((OBJECTS * FILE_SIZE) as u64));
+    for parallelism in [1, 10] {
+        let manager = SyncManager::new(
+            client.clone(),
+            SyncConfig { parallelism, ..Default::default() },
+        );
+        group.bench_function(format!("parallelism_{}", parallelism), |b| {
+            b.iter_batched(
+                || TempDir::new().unwrap(),
+                |dir| runtime.block_on(manager.pull(dir.path(), &remote)).unwrap(),
+                BatchSize::PerIteration,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().sample_size(10);
+    targets = bench_detect_changes, bench_block_checksums, bench_pull
+}
+criterion_main!(benches);
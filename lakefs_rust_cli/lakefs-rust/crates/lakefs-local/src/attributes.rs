@@ -0,0 +1,106 @@
+//! File modes and modification times carried in objects' user metadata, so
+//! a download can give a file back what it had where it was uploaded from.
+
+use crate::error::Result;
+use chrono::{DateTime, Utc};
+use lakefs_api::models::ObjectStats;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// User metadata key of a file's permission bits, in octal
+pub const MODE_METADATA_KEY: &str = "lakectl-mode";
+/// User metadata key of a file's modification time, in RFC 3339
+pub const MTIME_METADATA_KEY: &str = "lakectl-mtime";
+
+/// Record the mode and modification time of the file at `path` in
+/// `metadata`. Modes are only recorded on Unix.
+pub fn record(path: &Path, metadata: &mut HashMap<String, String>) -> Result<()> {
+    let file = fs::metadata(path)?;
+    let mtime: DateTime<Utc> = file.modified()?.into();
+    metadata.insert(MTIME_METADATA_KEY.to_string(), mtime.to_rfc3339());
+    if let Some(mode) = mode(path)? {
+        metadata.insert(MODE_METADATA_KEY.to_string(), format!("{:o}", mode));
+    }
+    Ok(())
+}
+
+/// Permission bits of the file at `path`; always `None` off Unix.
+pub fn mode(path: &Path) -> Result<Option<u32>> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        Ok(Some(fs::metadata(path)?.permissions().mode() & 0o7777))
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = path;
+        Ok(None)
+    }
+}
+
+/// Give the file at `path` the modification time and mode `stats` records,
+/// where it records them, returning the modification time set. Values that
+/// do not parse are ignored, as objects may come from other clients.
+pub fn restore(path: &Path, stats: &ObjectStats) -> Result<Option<DateTime<Utc>>> {
+    let Some(metadata) = &stats.metadata else {
+        return Ok(None);
+    };
+
+    // Before the mode, which may make the file read-only
+    let mtime = metadata
+        .get(MTIME_METADATA_KEY)
+        .and_then(|mtime| DateTime::parse_from_rfc3339(mtime).ok())
+        .map(|mtime| mtime.with_timezone(&Utc));
+    if let Some(mtime) = mtime {
+        fs::File::options().write(true).open(path)?.set_modified(mtime.into())?;
+    }
+
+    #[cfg(unix)]
+    if let Some(mode) = metadata.get(MODE_METADATA_KEY).and_then(|mode| u32::from_str_radix(mode, 8).ok()) {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(mode & 0o7777))?;
+    }
+    Ok(mtime)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lakefs_test_utils::fixtures::test_object_stats;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_record_and_restore() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = temp_dir.path().join("source.sh");
+        std::fs::write(&source, "#!/bin/sh").unwrap();
+        let mtime = DateTime::parse_from_rfc3339("2024-05-01T12:00:00Z").unwrap().with_timezone(&Utc);
+        std::fs::File::options().write(true).open(&source).unwrap().set_modified(mtime.into()).unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&source, std::fs::Permissions::from_mode(0o750)).unwrap();
+        }
+
+        let mut stats = test_object_stats();
+        let mut metadata = HashMap::new();
+        record(&source, &mut metadata).unwrap();
+        stats.metadata = Some(metadata);
+
+        let copy = temp_dir.path().join("copy.sh");
+        std::fs::write(&copy, "#!/bin/sh").unwrap();
+        assert_eq!(restore(&copy, &stats).unwrap(), Some(mtime));
+        let restored: DateTime<Utc> = std::fs::metadata(&copy).unwrap().modified().unwrap().into();
+        assert_eq!(restored, mtime);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(std::fs::metadata(&copy).unwrap().permissions().mode() & 0o7777, 0o750);
+            assert_eq!(mode(&copy).unwrap(), Some(0o750));
+        }
+
+        stats.metadata = Some(HashMap::from([(MTIME_METADATA_KEY.to_string(), "yesterday".to_string())]));
+        assert_eq!(restore(&copy, &stats).unwrap(), None);
+    }
+}
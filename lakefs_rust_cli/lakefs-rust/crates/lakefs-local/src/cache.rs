@@ -0,0 +1,223 @@
+use crate::error::Result;
+use crate::progress::ProgressReporter;
+use crate::throttle::BandwidthLimiter;
+use crate::transfer;
+use lakefs_api::{Checksum, LakeFSApi, models::ObjectStats};
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::fs;
+
+/// How files are materialized from the object cache into a checkout.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LinkStrategy {
+    /// Independent copy; uses the most disk but edits stay in the checkout
+    #[default]
+    Copy,
+    /// Hard link to the cached file. Writing to the file in place changes
+    /// the cache and every checkout linked to it, so use this for data that
+    /// is only read.
+    Hardlink,
+    /// Copy-on-write clone (Btrfs, XFS, APFS). Shares blocks until either
+    /// side is written; falls back to a copy where it is not supported.
+    Reflink,
+}
+
+/// Content-addressed store of downloaded objects, keyed by checksum, that
+/// several checkouts can share. An object already in the cache is linked or
+/// copied into place instead of being downloaded again.
+#[derive(Debug, Clone)]
+pub struct ObjectCache {
+    root: PathBuf,
+    strategy: LinkStrategy,
+}
+
+impl ObjectCache {
+    pub fn new(root: impl Into<PathBuf>, strategy: LinkStrategy) -> Self {
+        Self { root: root.into(), strategy }
+    }
+
+    /// Cache location for an object, or `None` when its checksum cannot be
+    /// used as a file name.
+    pub fn path_for(&self, checksum: &Checksum) -> Option<PathBuf> {
+        let key = checksum.to_string();
+        if key.len() < 3 || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+            return None;
+        }
+        Some(self.root.join(&key[..2]).join(&key))
+    }
+
+    /// Put the object described by `stats` at `destination`. A cached copy
+    /// is linked into place; otherwise the object is downloaded and added to
+    /// the cache. Returns the number of bytes downloaded, zero on a hit.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) async fn fetch(
+        &self,
+        client: &dyn LakeFSApi,
+        repository: &str,
+        reference: &str,
+        stats: &ObjectStats,
+        destination: &Path,
+        label: &str,
+        limiter: Option<Arc<BandwidthLimiter>>,
+        reporter: &Arc<dyn ProgressReporter>,
+    ) -> Result<u64> {
+        let cached = self.path_for(&stats.checksum);
+        if let Some(cached) = &cached {
+            if fs::metadata(cached).await.is_ok() {
+                let temp = transfer::temp_path(destination);
+                self.place(cached, &temp).await?;
+                fs::rename(&temp, destination).await?;
+                return Ok(0);
+            }
+        }
+
+        let downloaded = transfer::download(client, repository, reference, stats, destination, label, limiter, reporter).await?;
+        if let Some(cached) = &cached {
+            if let Err(error) = self.insert(destination, cached).await {
+                tracing::warn!(path = %stats.path, %error, "could not add object to cache");
+            }
+        }
+        Ok(downloaded)
+    }
+
+    /// Add a downloaded file to the cache. Concurrent inserts of the same
+    /// object stage under distinct names and the last rename wins.
+    async fn insert(&self, downloaded: &Path, cached: &Path) -> io::Result<()> {
+        static SEQUENCE: AtomicU64 = AtomicU64::new(0);
+
+        if let Some(parent) = cached.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+        let mut name = cached.file_name().unwrap_or_default().to_os_string();
+        name.push(format!(".{}-{}{}", std::process::id(), SEQUENCE.fetch_add(1, Ordering::Relaxed), transfer::TEMP_SUFFIX));
+        let staging = cached.with_file_name(name);
+
+        self.place(downloaded, &staging).await?;
+        fs::rename(&staging, cached).await
+    }
+
+    /// Create `target` from `source` with the configured strategy, copying
+    /// when a link cannot be made (links fail across filesystems).
+    async fn place(&self, source: &Path, target: &Path) -> io::Result<()> {
+        let _ = fs::remove_file(target).await;
+
+        let linked = match self.strategy {
+            LinkStrategy::Copy => false,
+            LinkStrategy::Hardlink => fs::hard_link(source, target).await.is_ok(),
+            LinkStrategy::Reflink => {
+                let (source, target) = (source.to_path_buf(), target.to_path_buf());
+                tokio::task::spawn_blocking(move || reflink(&source, &target))
+                    .await
+                    .map_err(io::Error::other)?
+                    .is_ok()
+            }
+        };
+        if !linked {
+            let _ = fs::remove_file(target).await;
+            fs::copy(source, target).await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn reflink(source: &Path, target: &Path) -> io::Result<()> {
+    use std::os::fd::AsRawFd;
+
+    let source = std::fs::File::open(source)?;
+    let target = std::fs::File::create(target)?;
+    // SAFETY: both descriptors are open for the duration of the call
+    let result = unsafe { libc::ioctl(target.as_raw_fd(), libc::FICLONE, source.as_raw_fd()) };
+    if result == -1 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn reflink(source: &Path, target: &Path) -> io::Result<()> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let source = CString::new(source.as_os_str().as_bytes())?;
+    let target = CString::new(target.as_os_str().as_bytes())?;
+    // SAFETY: both paths are valid NUL-terminated strings
+    let result = unsafe { libc::clonefile(source.as_ptr(), target.as_ptr(), 0) };
+    if result == -1 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn reflink(_source: &Path, _target: &Path) -> io::Result<()> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "reflinks are not supported on this platform"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::progress::NoopProgress;
+    use lakefs_api::LakeFSClient;
+    use lakefs_test_utils::scenario::MockLakeFS;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_path_for() {
+        let cache = ObjectCache::new("/cache", LinkStrategy::Copy);
+        assert_eq!(
+            cache.path_for(&"\"5d41402abc4b2a76b9719d911017c592\"".into()),
+            Some(PathBuf::from("/cache/5d/5d41402abc4b2a76b9719d911017c592"))
+        );
+        assert_eq!(cache.path_for(&"../etc".into()), None);
+        assert_eq!(cache.path_for(&"".into()), None);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_hardlinks_share_one_download() {
+        use std::os::unix::fs::MetadataExt;
+
+        let server = MockLakeFS::new()
+            .with_repo("repo")
+            .with_branch("main")
+            .with_object("a.csv", "shared")
+            .start()
+            .await;
+        let client = LakeFSClient::new(server.uri(), "Bearer test-token");
+        let stats = client.get_object("repo", "main", "a.csv").await.unwrap();
+        let reporter: Arc<dyn ProgressReporter> = Arc::new(NoopProgress);
+
+        let temp_dir = TempDir::new().unwrap();
+        let cache = ObjectCache::new(temp_dir.path().join("cache"), LinkStrategy::Hardlink);
+        let first = temp_dir.path().join("first.csv");
+        let second = temp_dir.path().join("second.csv");
+
+        let downloaded = cache.fetch(&client, "repo", "main", &stats, &first, "a.csv", None, &reporter).await.unwrap();
+        assert_eq!(downloaded, 6);
+        let downloaded = cache.fetch(&client, "repo", "main", &stats, &second, "a.csv", None, &reporter).await.unwrap();
+        assert_eq!(downloaded, 0);
+
+        assert_eq!(std::fs::read_to_string(&second).unwrap(), "shared");
+        let cached = cache.path_for(&stats.checksum).unwrap();
+        assert_eq!(std::fs::metadata(&first).unwrap().ino(), std::fs::metadata(&cached).unwrap().ino());
+        assert_eq!(std::fs::metadata(&cached).unwrap().nlink(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_reflink_falls_back_to_copy() {
+        let temp_dir = TempDir::new().unwrap();
+        let cached = temp_dir.path().join("cached");
+        std::fs::write(&cached, "data").unwrap();
+        let destination = temp_dir.path().join("out");
+
+        // tmpfs and ext4 have no reflinks, so this exercises the fallback there
+        let cache = ObjectCache::new(temp_dir.path(), LinkStrategy::Reflink);
+        cache.place(&cached, &destination).await.unwrap();
+        assert_eq!(std::fs::read_to_string(&destination).unwrap(), "data");
+    }
+}
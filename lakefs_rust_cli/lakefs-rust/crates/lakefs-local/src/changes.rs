@@ -1,7 +1,11 @@
 use crate::error::{Error, Result};
 use crate::index::{LocalIndex, IndexEntry};
+use lakefs_api::Checksum;
 use lakefs_api::models::ObjectStats;
-use sha2::{Sha256, Digest};
+use crate::transfer::{Hasher, TEMP_SUFFIX};
+use crate::filter::PathFilter;
+use crate::delta;
+use crate::paths;
 use std::path::{Path, PathBuf};
 use std::fs;
 use walkdir::WalkDir;
@@ -23,9 +27,21 @@ pub struct Change {
     pub remote_stats: Option<ObjectStats>,
 }
 
+/// Changes found by [`ChangeDetector::detect_changes`], along with the local
+/// entries that could not be read and so were left out.
+#[derive(Debug, Default)]
+pub struct ChangeReport {
+    pub changes: Vec<Change>,
+    /// Path of each unreadable entry, relative to the checkout when possible
+    pub errors: Vec<(String, Error)>,
+}
+
 pub struct ChangeDetector {
     local_path: PathBuf,
     gitignore: Gitignore,
+    filter: PathFilter,
+    max_depth: Option<usize>,
+    follow_links: bool,
 }
 
 impl ChangeDetector {
@@ -44,32 +60,79 @@ impl ChangeDetector {
         Self {
             local_path,
             gitignore,
+            filter: PathFilter::default(),
+            max_depth: None,
+            follow_links: false,
         }
     }
     
+    /// Only report changes for paths `filter` includes.
+    pub fn with_filter(mut self, filter: PathFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+    
+    /// Don't look at files more than `depth` directories below the checkout.
+    pub fn with_max_depth(mut self, depth: Option<usize>) -> Self {
+        self.max_depth = depth;
+        self
+    }
+    
+    /// Descend into symlinked directories (and junctions on Windows). Links
+    /// that loop back to an ancestor are reported as errors, not followed.
+    pub fn follow_links(mut self, follow: bool) -> Self {
+        self.follow_links = follow;
+        self
+    }
+    
     pub fn detect_changes(
         &self,
         index: &LocalIndex,
         remote_objects: Vec<ObjectStats>,
-    ) -> Result<Vec<Change>> {
+    ) -> Result<ChangeReport> {
         let mut changes = Vec::new();
+        let mut errors = Vec::new();
         let mut remote_map: std::collections::HashMap<String, ObjectStats> = remote_objects
             .into_iter()
             .map(|obj| (obj.path.clone(), obj))
             .collect();
         
-        // Check local files against index
-        for entry in WalkDir::new(&self.local_path)
+        // Check local files against index. Paths are walked in their
+        // extended-length form so trees deeper than MAX_PATH work on Windows.
+        let root = paths::extended_length(&self.local_path);
+        let mut walk = WalkDir::new(&root).follow_links(self.follow_links);
+        if let Some(depth) = self.max_depth {
+            walk = walk.max_depth(depth + 1);
+        }
+        for entry in walk
             .into_iter()
-            .filter_entry(|e| !self.is_ignored(e.path()))
-            .filter_map(|res| res.ok())  // Fixed: directly handle walkdir::Error
+            .filter_entry(|e| e.depth() == 0 || !self.is_ignored(e.path()))
         {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(e) => {
+                    let path = e.path()
+                        .and_then(|p| self.get_relative_path(p).ok())
+                        .unwrap_or_default();
+                    errors.push((path, Error::Walk(e)));
+                    continue;
+                }
+            };
             if !entry.file_type().is_file() {
                 continue;
             }
             
             let relative_path = self.get_relative_path(entry.path())?;
-            let metadata = fs::metadata(entry.path())?;
+            if !self.filter.is_included(&relative_path) {
+                continue;
+            }
+            let metadata = match fs::metadata(entry.path()) {
+                Ok(metadata) => metadata,
+                Err(e) => {
+                    errors.push((relative_path, e.into()));
+                    continue;
+                }
+            };
             
             match index.get_entry(&relative_path) {
                 Some(index_entry) => {
@@ -96,8 +159,11 @@ impl ChangeDetector {
         }
         
         // Check for removed files (in index but not on disk)
-        for (path, _) in &index.entries {
-            let local_path = self.local_path.join(path);
+        for path in index.entries.keys() {
+            if !self.filter.is_included(path) {
+                continue;
+            }
+            let local_path = paths::to_local(&self.local_path, path);
             if !local_path.exists() {
                 changes.push(Change {
                     path: path.clone(),
@@ -110,6 +176,9 @@ impl ChangeDetector {
         
         // Check for remote changes
         for (path, stats) in remote_map {
+            if !self.filter.is_included(&path) {
+                continue;
+            }
             match index.get_entry(&path) {
                 Some(index_entry) => {
                     if stats.checksum != index_entry.checksum {
@@ -132,20 +201,20 @@ impl ChangeDetector {
             }
         }
         
-        Ok(changes)
+        Ok(ChangeReport { changes, errors })
     }
     
     fn is_ignored(&self, path: &Path) -> bool {
-        if path.file_name().map(|n| n.to_str().unwrap_or("")).unwrap_or("").starts_with('.') {
+        let name = path.file_name().map(|n| n.to_str().unwrap_or("")).unwrap_or("");
+        if name.starts_with('.') || name.ends_with(TEMP_SUFFIX) {
             return true;
         }
         self.gitignore.matched(path, path.is_dir()).is_ignore()
     }
     
     fn get_relative_path(&self, path: &Path) -> Result<String> {
-        path.strip_prefix(&self.local_path)
-            .map_err(|_| Error::InvalidPath(format!("Path not within local directory: {:?}", path)))
-            .map(|p| p.to_string_lossy().to_string())
+        paths::to_key(&self.local_path, path)
+            .or_else(|_| paths::to_key(&paths::extended_length(&self.local_path), path))
     }
     
     fn has_changed(
@@ -162,19 +231,25 @@ impl ChangeDetector {
         // Check mtime (may not be reliable)
         let mtime: DateTime<Utc> = metadata.modified()?.into();
         if mtime > index_entry.mtime {
-            // Verify with checksum
-            let checksum = self.calculate_checksum(path)?;
-            return Ok(checksum != index_entry.checksum);
+            // Verify with checksum; one that can't be recomputed (such as a
+            // multipart ETag) proves nothing, so the file counts as changed
+            let digests = self.calculate_checksums(path)?;
+            let unchanged = digests.iter().find_map(|digest| index_entry.checksum.matches(digest));
+            // Block checksums are recorded for large files, which are the
+            // ones most often uploaded in parts
+            if unchanged.is_none() && !index_entry.blocks.is_empty() {
+                return Ok(delta::block_checksums(path)? != index_entry.blocks);
+            }
+            return Ok(unchanged != Some(true));
         }
         
         Ok(false)
     }
     
-    fn calculate_checksum(&self, path: &Path) -> Result<String> {
-        let data = fs::read(path)?;
-        let mut hasher = Sha256::new();
-        hasher.update(&data);
-        Ok(format!("{:x}", hasher.finalize()))
+    /// The MD5 and SHA-256 of a file, the digests lakeFS checksums can be
+    /// compared with.
+    fn calculate_checksums(&self, path: &Path) -> Result<[Checksum; 2]> {
+        Ok(Hasher::from_file(path)?.finish())
     }
 }
 
@@ -244,7 +319,7 @@ mod tests {
     }
 
     #[test]
-    fn test_calculate_checksum()  {
+    fn test_calculate_checksums()  {
         let temp_dir = TempDir::new().unwrap();
         let detector = ChangeDetector::new(temp_dir.path().to_path_buf());
         
@@ -252,10 +327,48 @@ mod tests {
         let file_path = temp_dir.path().join("test.txt");
         fs::write(&file_path, b"Hello, world!").unwrap();
         
-        let checksum = detector.calculate_checksum(&file_path).unwrap();
+        let [md5, sha256] = detector.calculate_checksums(&file_path).unwrap();
+        
+        // MD5 and SHA256 of "Hello, world!"
+        assert_eq!(md5, Checksum::Md5("6cd3556deb0da54bca060b4c39479839".into()));
+        assert_eq!(sha256, Checksum::Sha256("315f5bdb76d078c43b8ac0064e4a0164612b1fce77c869345bfc94c75894edd3".into()));
+    }
+
+    #[test]
+    fn test_detect_changes_max_depth()  {
+        let temp_dir = TempDir::new().unwrap();
+        fs::create_dir_all(temp_dir.path().join("a/b")).unwrap();
+        fs::write(temp_dir.path().join("top.txt"), b"1").unwrap();
+        fs::write(temp_dir.path().join("a/mid.txt"), b"2").unwrap();
+        fs::write(temp_dir.path().join("a/b/deep.txt"), b"3").unwrap();
+        let index = LocalIndex::new("test", "main", "commit1");
+        
+        let detector = ChangeDetector::new(temp_dir.path().to_path_buf()).with_max_depth(Some(1));
+        let mut paths: Vec<_> = detector.detect_changes(&index, vec![]).unwrap()
+            .changes
+            .into_iter()
+            .map(|c| c.path)
+            .collect();
+        paths.sort();
+        assert_eq!(paths, ["a/mid.txt", "top.txt"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_detect_changes_reports_link_loops()  {
+        let temp_dir = TempDir::new().unwrap();
+        fs::create_dir(temp_dir.path().join("a")).unwrap();
+        fs::write(temp_dir.path().join("a/file.txt"), b"content").unwrap();
+        std::os::unix::fs::symlink(temp_dir.path(), temp_dir.path().join("a/loop")).unwrap();
+        let index = LocalIndex::new("test", "main", "commit1");
         
-        // SHA256 of "Hello, world!"
-        assert_eq!(checksum, "315f5bdb76d078c43b8ac0064e4a0164612b1fce77c869345bfc94c75894edd3");
+        let detector = ChangeDetector::new(temp_dir.path().to_path_buf()).follow_links(true);
+        let report = detector.detect_changes(&index, vec![]).unwrap();
+        
+        assert_eq!(report.changes.len(), 1);
+        assert_eq!(report.errors.len(), 1);
+        assert_eq!(report.errors[0].0, "a/loop");
+        assert!(matches!(report.errors[0].1, Error::Walk(_)));
     }
 
     #[test]
@@ -270,7 +383,7 @@ mod tests {
         // Empty index
         let index = LocalIndex::new("test", "main", "commit1");
         
-        let changes = detector.detect_changes(&index, vec![]).unwrap();
+        let changes = detector.detect_changes(&index, vec![]).unwrap().changes;
         
         assert_eq!(changes.len(), 1);
         assert_eq!(changes[0].path, "new.txt");
@@ -278,6 +391,34 @@ mod tests {
         assert!(changes[0].local_path.is_some());
     }
 
+    #[test]
+    fn test_detect_changes_filtered()  {
+        let temp_dir = TempDir::new().unwrap();
+        fs::create_dir_all(temp_dir.path().join("tmp")).unwrap();
+        fs::write(temp_dir.path().join("keep.csv"), b"a").unwrap();
+        fs::write(temp_dir.path().join("tmp/skip.csv"), b"b").unwrap();
+        
+        let filter = PathFilter::new().exclude("tmp").unwrap();
+        let detector = ChangeDetector::new(temp_dir.path().to_path_buf()).with_filter(filter);
+        let index = LocalIndex::new("test", "main", "commit1");
+        let remote_objects = vec![ObjectStats {
+            path: "tmp/remote.csv".to_string(),
+            path_type: PathType::Object,
+            physical_address: "s3://bucket/object".to_string(),
+            checksum: "remote123".into(),
+            size_bytes: 200,
+            mtime: Utc::now(),
+            metadata: None,
+            physical_address_expiry: None,
+            content_type: None,
+        }];
+        
+        let changes = detector.detect_changes(&index, remote_objects).unwrap().changes;
+        
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].path, "keep.csv");
+    }
+
     #[test]
     fn test_detect_changes_removed_file()  {
         let temp_dir = TempDir::new().unwrap();
@@ -287,13 +428,14 @@ mod tests {
         let mut index = LocalIndex::new("test", "main", "commit1");
         index.add_entry("removed.txt".to_string(), IndexEntry {
             path: "removed.txt".to_string(),
-            checksum: "abc123".to_string(),
+            checksum: "abc123".into(),
             size: 100,
             mtime: Utc::now(),
             permissions: None,
+            blocks: Vec::new(),
         });
         
-        let changes = detector.detect_changes(&index, vec![]).unwrap();
+        let changes = detector.detect_changes(&index, vec![]).unwrap().changes;
         
         assert_eq!(changes.len(), 1);
         assert_eq!(changes[0].path, "removed.txt");
@@ -314,19 +456,48 @@ mod tests {
         let mut index = LocalIndex::new("test", "main", "commit1");
         index.add_entry("modified.txt".to_string(), IndexEntry {
             path: "modified.txt".to_string(),
-            checksum: "different".to_string(),
+            checksum: "different".into(),
             size: 50, // Different size than actual
             mtime: Utc::now() - chrono::Duration::days(1),
             permissions: None,
+            blocks: Vec::new(),
         });
         
-        let changes = detector.detect_changes(&index, vec![]).unwrap();
+        let changes = detector.detect_changes(&index, vec![]).unwrap().changes;
         
         assert_eq!(changes.len(), 1);
         assert_eq!(changes[0].path, "modified.txt");
         assert_eq!(changes[0].change_type, ChangeType::Modified);
     }
 
+    #[test]
+    fn test_touched_multipart_file_compared_by_blocks() {
+        let temp_dir = TempDir::new().unwrap();
+        let detector = ChangeDetector::new(temp_dir.path().to_path_buf());
+        let file_path = temp_dir.path().join("large.bin");
+        let mut content = vec![b'a'; delta::BLOCK_SIZE as usize * 3];
+        fs::write(&file_path, &content).unwrap();
+        
+        // Older than the file, so only its content can tell
+        let mut index = LocalIndex::new("test", "main", "commit1");
+        index.add_entry("large.bin".to_string(), IndexEntry {
+            path: "large.bin".to_string(),
+            checksum: "5d41402abc4b2a76b9719d911017c592-2".into(),
+            size: content.len() as u64,
+            mtime: Utc::now() - chrono::Duration::days(1),
+            permissions: None,
+            blocks: delta::block_checksums(&file_path).unwrap(),
+        });
+        assert!(detector.detect_changes(&index, vec![]).unwrap().changes.is_empty());
+        
+        // One byte in the middle block
+        content[delta::BLOCK_SIZE as usize + 1] = b'b';
+        fs::write(&file_path, &content).unwrap();
+        let changes = detector.detect_changes(&index, vec![]).unwrap().changes;
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].change_type, ChangeType::Modified);
+    }
+
     #[test]
     fn test_detect_remote_changes()  {
         let temp_dir = TempDir::new().unwrap();
@@ -339,13 +510,43 @@ mod tests {
             path: "remote-only.txt".to_string(),
             path_type: PathType::Object,
             physical_address: "s3://bucket/object".to_string(),
-            checksum: "remote123".to_string(),
+            checksum: "remote123".into(),
             size_bytes: 200,
             mtime: Utc::now(),
             metadata: None,
+            physical_address_expiry: None,
+            content_type: None,
         }];
         
-        let changes = detector.detect_changes(&index, remote_objects).unwrap();
+        let _changes = detector.detect_changes(&index, remote_objects).unwrap().changes;
+    }
+
+    #[test]
+    fn test_remote_only_object_is_added()  {
+        let temp_dir = TempDir::new().unwrap();
+        let detector = ChangeDetector::new(temp_dir.path().to_path_buf());
+        
+        let index = LocalIndex::new("test", "main", "commit1");
+        
+        // Remote object that doesn't exist locally
+        let remote_objects = vec![ObjectStats {
+            path: "remote-only.txt".to_string(),
+            path_type: PathType::Object,
+            physical_address: "s3://bucket/object".to_string(),
+            checksum: "remote123".into(),
+            size_bytes: 200,
+            mtime: Utc::now(),
+            metadata: None,
+            physical_address_expiry: None,
+            content_type: None,
+        }];
+        
+        let changes = detector.detect_changes(&index, remote_objects).unwrap().changes;
         
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].path, "remote-only.txt");
+        assert_eq!(changes[0].change_type, ChangeType::Added);
+        assert!(changes[0].local_path.is_none());
+        assert!(changes[0].remote_stats.is_some());
     }
 }
\ No newline at end of file
@@ -0,0 +1,118 @@
+use crate::error::Result;
+use crate::index::LocalIndex;
+use crate::paths;
+use crate::transfer::TEMP_SUFFIX;
+use lakefs_api::LakeFSApi;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// What [`clean`] removed, or would remove on a dry run.
+#[derive(Debug, Default)]
+pub struct CleanReport {
+    /// Partial downloads left behind by interrupted syncs
+    pub temp_files: Vec<PathBuf>,
+    /// Bytes the temp files took up
+    pub reclaimed_bytes: u64,
+    /// Index entries whose file and remote object are both gone
+    pub pruned_entries: Vec<String>,
+}
+
+/// Housekeeping for a long-lived checkout: delete leftover download temp
+/// files and forget tracked paths that no longer exist on either side.
+/// With `dry_run` nothing is changed and the report lists what would be.
+pub async fn clean(client: &dyn LakeFSApi, local_path: &Path, parallelism: usize, dry_run: bool) -> Result<CleanReport> {
+    let mut index = LocalIndex::load(local_path)?;
+    let mut report = CleanReport::default();
+
+    for entry in WalkDir::new(local_path).into_iter().filter_map(|entry| entry.ok()) {
+        let is_temp = entry.file_type().is_file()
+            && entry.file_name().to_string_lossy().ends_with(TEMP_SUFFIX);
+        if !is_temp {
+            continue;
+        }
+        report.reclaimed_bytes += entry.metadata().map(|m| m.len()).unwrap_or(0);
+        if !dry_run {
+            std::fs::remove_file(entry.path())?;
+        }
+        report.temp_files.push(entry.into_path());
+    }
+
+    let missing: Vec<String> = index
+        .entries
+        .keys()
+        .filter(|path| !paths::to_local(local_path, path).exists())
+        .cloned()
+        .collect();
+    let results = client.stat_objects(&index.repository, &index.reference, &missing, parallelism).await;
+    for (path, result) in results {
+        match result {
+            Ok(_) => {}
+            Err(lakefs_api::Error::NotFound(_)) => report.pruned_entries.push(path),
+            Err(e) => return Err(e.into()),
+        }
+    }
+    report.pruned_entries.sort();
+
+    if !dry_run && !report.pruned_entries.is_empty() {
+        for path in &report.pruned_entries {
+            index.remove_entry(path);
+        }
+        index.save(local_path)?;
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::IndexEntry;
+    use chrono::Utc;
+    use lakefs_api::LakeFSClient;
+    use lakefs_test_utils::scenario::MockLakeFS;
+    use tempfile::TempDir;
+
+    fn entry(path: &str) -> IndexEntry {
+        IndexEntry {
+            path: path.to_string(),
+            checksum: "old".into(),
+            size: 1,
+            mtime: Utc::now(),
+            permissions: None,
+            blocks: Vec::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_clean_removes_temp_files_and_vanished_entries() {
+        let server = MockLakeFS::new()
+            .with_repo("repo")
+            .with_branch("main")
+            .with_object("remote-only.csv", "still here")
+            .start()
+            .await;
+        let client = LakeFSClient::new(server.uri(), "Bearer test-token");
+
+        let temp_dir = TempDir::new().unwrap();
+        let mut index = LocalIndex::new("repo", "main", "c0");
+        for path in ["local.csv", "remote-only.csv", "gone.csv"] {
+            index.add_entry(path.to_string(), entry(path));
+        }
+        index.save(temp_dir.path()).unwrap();
+        std::fs::write(temp_dir.path().join("local.csv"), "kept").unwrap();
+        std::fs::create_dir(temp_dir.path().join("data")).unwrap();
+        std::fs::write(temp_dir.path().join("data/big.csv.lakectl-tmp"), "partial").unwrap();
+
+        let report = clean(&client, temp_dir.path(), 4, true).await.unwrap();
+        assert_eq!(report.temp_files.len(), 1);
+        assert_eq!(report.reclaimed_bytes, 7);
+        assert_eq!(report.pruned_entries, ["gone.csv"]);
+        assert!(temp_dir.path().join("data/big.csv.lakectl-tmp").exists());
+
+        clean(&client, temp_dir.path(), 4, false).await.unwrap();
+        assert!(!temp_dir.path().join("data/big.csv.lakectl-tmp").exists());
+        let index = LocalIndex::load(temp_dir.path()).unwrap();
+        let mut tracked: Vec<_> = index.entries.keys().cloned().collect();
+        tracked.sort();
+        assert_eq!(tracked, ["local.csv", "remote-only.csv"]);
+    }
+}
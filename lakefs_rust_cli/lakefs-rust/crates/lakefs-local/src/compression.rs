@@ -0,0 +1,155 @@
+//! Compressing object content on upload and undoing it on download.
+//!
+//! A compressed object keeps its path; the encoding is recorded in its
+//! user metadata under [`ENCODING_METADATA_KEY`], so downloads know to
+//! decompress it and clients that do not are at least told what it is.
+
+use crate::error::{Error, Result};
+use crate::transfer::temp_path;
+use lakefs_api::models::ObjectStats;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::str::FromStr;
+use tempfile::NamedTempFile;
+
+/// User metadata key naming the encoding of a compressed object.
+pub const ENCODING_METADATA_KEY: &str = "content-encoding";
+
+/// Level zstd compresses at; its default, fast enough not to slow uploads.
+const ZSTD_LEVEL: i32 = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Compression {
+    Gzip,
+    Zstd,
+}
+
+impl Compression {
+    /// The encoding `stats` records, if its object is compressed.
+    pub fn of(stats: &ObjectStats) -> Result<Option<Self>> {
+        let Some(encoding) = stats.metadata.as_ref().and_then(|metadata| metadata.get(ENCODING_METADATA_KEY)) else {
+            return Ok(None);
+        };
+        match encoding.as_str() {
+            "" | "identity" => Ok(None),
+            encoding => encoding.parse().map(Some),
+        }
+    }
+
+    /// Compress the file at `source` into a temporary file, deleted when the
+    /// result is dropped. Blocks, so async code should run it with
+    /// `spawn_blocking`.
+    pub fn encode_file(self, source: &Path) -> Result<NamedTempFile> {
+        let mut input = BufReader::new(File::open(source)?);
+        let mut encoded = NamedTempFile::new()?;
+        let output = BufWriter::new(encoded.as_file_mut());
+        match self {
+            Compression::Gzip => {
+                let mut encoder = flate2::write::GzEncoder::new(output, flate2::Compression::default());
+                io::copy(&mut input, &mut encoder)?;
+                encoder.finish()?.flush()?;
+            }
+            Compression::Zstd => {
+                let mut encoder = zstd::Encoder::new(output, ZSTD_LEVEL)?;
+                io::copy(&mut input, &mut encoder)?;
+                encoder.finish()?.flush()?;
+            }
+        }
+        Ok(encoded)
+    }
+
+    /// Decompress the file at `source` into `destination`, returning the
+    /// decompressed size. Blocks like `encode_file`.
+    pub fn decode_file(self, source: &Path, destination: &Path) -> Result<u64> {
+        let input = BufReader::new(File::open(source)?);
+        let mut output = BufWriter::new(File::create(destination)?);
+        let written = match self {
+            Compression::Gzip => io::copy(&mut flate2::read::MultiGzDecoder::new(input), &mut output),
+            Compression::Zstd => io::copy(&mut zstd::Decoder::with_buffer(input)?, &mut output),
+        }
+        .map_err(|e| Error::Compression(format!("Cannot decompress {} content: {}", self, e)))?;
+        output.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+        Ok(written)
+    }
+
+    /// Replace the compressed file at `path` with its decompressed content.
+    /// Blocks like `encode_file`.
+    pub fn decode_in_place(self, path: &Path) -> Result<u64> {
+        let decoded = temp_path(path);
+        let result = self.decode_file(path, &decoded).and_then(|written| {
+            std::fs::rename(&decoded, path)?;
+            Ok(written)
+        });
+        if result.is_err() {
+            let _ = std::fs::remove_file(&decoded);
+        }
+        result
+    }
+}
+
+impl fmt::Display for Compression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Compression::Gzip => "gzip",
+            Compression::Zstd => "zstd",
+        })
+    }
+}
+
+impl FromStr for Compression {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "gzip" => Ok(Compression::Gzip),
+            "zstd" => Ok(Compression::Zstd),
+            other => Err(Error::Compression(format!("Unsupported content encoding '{}'; expected gzip or zstd", other))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lakefs_test_utils::fixtures::test_object_stats;
+    use std::collections::HashMap;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let source = dir.path().join("data.csv");
+        let content = "id,value\n".to_string() + &"1,abc\n".repeat(1000);
+        std::fs::write(&source, &content).unwrap();
+
+        for compression in [Compression::Gzip, Compression::Zstd] {
+            let encoded = compression.encode_file(&source).unwrap();
+            assert!(encoded.as_file().metadata().unwrap().len() < content.len() as u64 / 10);
+
+            let decoded = dir.path().join(format!("decoded.{}", compression));
+            let written = compression.decode_file(encoded.path(), &decoded).unwrap();
+            assert_eq!(written, content.len() as u64);
+            assert_eq!(std::fs::read_to_string(&decoded).unwrap(), content);
+
+            std::fs::copy(encoded.path(), &decoded).unwrap();
+            compression.decode_in_place(&decoded).unwrap();
+            assert_eq!(std::fs::read_to_string(&decoded).unwrap(), content);
+        }
+    }
+
+    #[test]
+    fn test_of_reads_metadata() {
+        let mut stats = test_object_stats();
+        assert_eq!(Compression::of(&stats).unwrap(), None);
+
+        stats.metadata = Some(HashMap::from([(ENCODING_METADATA_KEY.to_string(), "ZSTD".to_string())]));
+        assert_eq!(Compression::of(&stats).unwrap(), Some(Compression::Zstd));
+
+        stats.metadata = Some(HashMap::from([(ENCODING_METADATA_KEY.to_string(), "br".to_string())]));
+        assert!(Compression::of(&stats).is_err());
+    }
+}
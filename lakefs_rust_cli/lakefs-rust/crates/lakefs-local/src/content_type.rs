@@ -0,0 +1,54 @@
+//! Guessing the content type of uploads, so objects are served, and
+//! presigned downloads answered, with a `Content-Type` that fits them.
+
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+/// Bytes read from the start of a file to look for a signature in
+const SNIFF_LENGTH: u64 = 8192;
+
+/// The content type that the extension of `name` implies, if it is a known one.
+pub fn from_name(name: &str) -> Option<String> {
+    mime_guess::from_path(name).first().map(|mime| mime.essence_str().to_string())
+}
+
+/// The content type of an object at `name`: from its extension, and
+/// otherwise from a signature in the first bytes of `source`, the file it is
+/// uploaded from. `None` when neither says, or `source` cannot be read; the
+/// upload then reports the error.
+pub fn sniff(name: &str, source: Option<&Path>) -> Option<String> {
+    if let Some(content_type) = from_name(name) {
+        return Some(content_type);
+    }
+
+    let mut head = Vec::new();
+    File::open(source?).ok()?.take(SNIFF_LENGTH).read_to_end(&mut head).ok()?;
+    infer::get(&head).map(|kind| kind.mime_type().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_sniff() {
+        assert_eq!(sniff("data/a.csv", None).as_deref(), Some("text/csv"));
+        assert_eq!(sniff("report.PDF", None).as_deref(), Some("application/pdf"));
+        assert_eq!(sniff("no-extension", None), None);
+
+        let temp_dir = TempDir::new().unwrap();
+        let png = temp_dir.path().join("image");
+        std::fs::write(&png, b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR").unwrap();
+        assert_eq!(sniff("image", Some(&png)).as_deref(), Some("image/png"));
+
+        // The extension wins over the content
+        assert_eq!(sniff("image.txt", Some(&png)).as_deref(), Some("text/plain"));
+
+        let text = temp_dir.path().join("notes");
+        std::fs::write(&text, "plain words").unwrap();
+        assert_eq!(sniff("notes", Some(&text)), None);
+        assert_eq!(sniff("missing", Some(&temp_dir.path().join("missing"))), None);
+    }
+}
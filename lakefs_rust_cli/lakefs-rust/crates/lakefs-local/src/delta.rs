@@ -0,0 +1,71 @@
+//! Block checksums of large files.
+//!
+//! Files of at least [`SyncConfig::delta_threshold`](crate::sync::SyncConfig)
+//! bytes have a checksum of each [`BLOCK_SIZE`] block recorded in the index.
+//! Objects uploaded in parts carry a multipart ETag rather than a checksum of
+//! their content, so for them the blocks are what tells a file that was only
+//! touched from one that changed. lakeFS replaces objects whole, so a changed
+//! file is uploaded in full.
+
+use crate::error::Result;
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io::{ErrorKind, Read};
+use std::path::Path;
+
+/// Size of the blocks files are compared in.
+pub const BLOCK_SIZE: u64 = 4 << 20;
+
+/// SHA-256 of each `BLOCK_SIZE` block of the file at `path`, the last one
+/// possibly shorter. Blocks, so async code should run it with
+/// `spawn_blocking`.
+pub fn block_checksums(path: &Path) -> Result<Vec<String>> {
+    let mut file = File::open(path)?;
+    let mut buffer = vec![0; BLOCK_SIZE as usize];
+    let mut checksums = Vec::new();
+    loop {
+        let mut filled = 0;
+        while filled < buffer.len() {
+            match file.read(&mut buffer[filled..]) {
+                Ok(0) => break,
+                Ok(read) => filled += read,
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+        if filled == 0 {
+            return Ok(checksums);
+        }
+        checksums.push(format!("{:x}", Sha256::digest(&buffer[..filled])));
+        if filled < buffer.len() {
+            return Ok(checksums);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_block_checksums() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("data.bin");
+        let mut content = vec![1u8; BLOCK_SIZE as usize * 2 + 10];
+        std::fs::write(&path, &content).unwrap();
+        let before = block_checksums(&path).unwrap();
+        assert_eq!(before.len(), 3);
+        assert_eq!(before[0], before[1]);
+
+        content[BLOCK_SIZE as usize + 5] = 2;
+        std::fs::write(&path, &content).unwrap();
+        let after = block_checksums(&path).unwrap();
+        assert_eq!(after[0], before[0]);
+        assert_ne!(after[1], before[1]);
+        assert_eq!(after[2], before[2]);
+
+        std::fs::write(&path, b"").unwrap();
+        assert!(block_checksums(&path).unwrap().is_empty());
+    }
+}
@@ -1,3 +1,5 @@
+use lakefs_api::RetryPolicy;
+use reqwest::StatusCode;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -16,6 +18,60 @@ pub enum Error {
     
     #[error("Invalid path: {0}")]
     InvalidPath(String),
+    
+    #[error("Invalid filter pattern {0}")]
+    Filter(String),
+    
+    #[error("Checksum mismatch for {path}: expected {expected}, got {actual}")]
+    ChecksumMismatch {
+        path: String,
+        expected: String,
+        actual: String,
+    },
+    
+    #[error("Cannot read local files: {0}")]
+    Walk(#[from] walkdir::Error),
+    
+    #[error("Stash error: {0}")]
+    Stash(String),
+    
+    #[error("Compression error: {0}")]
+    Compression(String),
+    
+    #[error("{0} changed on the remote during the sync")]
+    ConcurrentModification(String),
+    
+    #[error("Operation cancelled")]
+    Cancelled,
+    
+    #[error("Paths differ only in case, which this filesystem cannot tell apart: {}", format_collisions(.0))]
+    CaseCollision(Vec<(String, String)>),
+}
+
+impl Error {
+    /// Whether the failure may not happen again, such as a dropped
+    /// connection or a server asking to back off, so the transfer is worth
+    /// another attempt. The same statuses as `RetryPolicy` count, and so
+    /// does a connection lost while a body was being sent.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Error::Api(lakefs_api::Error::Http(error)) => {
+                RetryPolicy::is_retryable_error(error) || error.is_request() || error.is_body()
+            }
+            Error::Api(lakefs_api::Error::Api { status, .. }) => {
+                StatusCode::from_u16(*status).is_ok_and(RetryPolicy::is_retryable_status)
+            }
+            _ => false,
+        }
+    }
+}
+
+fn format_collisions(collisions: &[(String, String)]) -> String {
+    collisions
+        .iter()
+        .map(|(first, second)| format!("'{}' and '{}'", first, second))
+        .collect::<Vec<_>>()
+        .join(", ")
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
\ No newline at end of file
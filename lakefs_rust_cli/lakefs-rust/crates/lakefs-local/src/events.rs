@@ -0,0 +1,66 @@
+//! Progress of a sync as a stream of events, for callers that draw their own
+//! progress instead of the terminal bars.
+
+use crate::error::{Error, Result};
+use crate::progress::ProgressReporter;
+use crate::sync::SyncResult;
+use futures::channel::mpsc::UnboundedSender;
+
+/// Something that happened during a sync. Transfers run in parallel, so
+/// events for different paths interleave.
+#[derive(Debug)]
+pub enum SyncEvent {
+    /// Changes were detected: this many files to transfer or delete, moving
+    /// this many bytes
+    Scanned { files: u64, bytes: u64 },
+    /// A transfer of `size` bytes started
+    Started { path: String, size: u64 },
+    /// `bytes` more of `path` were transferred since its previous event
+    Progress { path: String, bytes: u64 },
+    /// A transfer finished
+    Completed { path: String },
+    /// A change could not be applied
+    Failed { path: String, error: String },
+    /// The sync ended; always the last event
+    Finished(Result<SyncResult>),
+}
+
+/// Reporter that forwards every update into a channel as a [`SyncEvent`].
+pub(crate) struct EventReporter {
+    sender: UnboundedSender<SyncEvent>,
+}
+
+impl EventReporter {
+    pub(crate) fn new(sender: UnboundedSender<SyncEvent>) -> Self {
+        Self { sender }
+    }
+
+    fn send(&self, event: SyncEvent) {
+        // A caller that stopped listening still gets its sync finished
+        let _ = self.sender.unbounded_send(event);
+    }
+}
+
+impl ProgressReporter for EventReporter {
+    fn set_total(&self, files: u64, bytes: u64) {
+        self.send(SyncEvent::Scanned { files, bytes });
+    }
+
+    fn file_started(&self, path: &str, size: u64) {
+        self.send(SyncEvent::Started { path: path.to_string(), size });
+    }
+
+    fn bytes_transferred(&self, path: &str, bytes: u64) {
+        self.send(SyncEvent::Progress { path: path.to_string(), bytes });
+    }
+
+    fn file_finished(&self, path: &str) {
+        self.send(SyncEvent::Completed { path: path.to_string() });
+    }
+
+    fn file_failed(&self, path: &str, error: &Error) {
+        self.send(SyncEvent::Failed { path: path.to_string(), error: error.to_string() });
+    }
+
+    fn finish(&self, _message: &str) {}
+}
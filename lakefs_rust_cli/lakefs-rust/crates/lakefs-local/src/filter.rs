@@ -0,0 +1,140 @@
+use crate::error::{Error, Result};
+use globset::{GlobBuilder, GlobMatcher};
+
+/// What a filter rule does with the paths it matches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterAction {
+    Include,
+    Exclude,
+}
+
+#[derive(Debug, Clone)]
+struct FilterRule {
+    action: FilterAction,
+    matcher: GlobMatcher,
+    /// Pattern has no `/` and is matched against single path components
+    component: bool,
+}
+
+impl FilterRule {
+    /// A rule matches a path when it matches the path itself or one of its
+    /// parent directories, so excluding `logs` also excludes `logs/a.txt`.
+    fn matches(&self, path: &str) -> bool {
+        if self.component {
+            path.split('/').any(|part| self.matcher.is_match(part))
+        } else {
+            path.match_indices('/')
+                .map(|(end, _)| &path[..end])
+                .chain(std::iter::once(path))
+                .any(|prefix| self.matcher.is_match(prefix))
+        }
+    }
+}
+
+/// Ordered `--include`/`--exclude` glob rules for relative object paths.
+///
+/// As in rsync, the first rule matching a path decides whether it is kept and
+/// paths no rule matches are included. `*` does not cross `/`, `**` does. A
+/// pattern without `/` matches a file or directory name at any depth; one with
+/// `/` matches from the root of the transfer.
+#[derive(Debug, Clone, Default)]
+pub struct PathFilter {
+    rules: Vec<FilterRule>,
+}
+
+impl PathFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a rule, after all rules added so far.
+    pub fn add(&mut self, action: FilterAction, pattern: &str) -> Result<()> {
+        let trimmed = pattern.trim_start_matches('/').trim_end_matches('/');
+        let matcher = GlobBuilder::new(trimmed)
+            .literal_separator(true)
+            .build()
+            .map_err(|e| Error::Filter(format!("'{}': {}", pattern, e)))?
+            .compile_matcher();
+
+        self.rules.push(FilterRule {
+            action,
+            matcher,
+            component: !pattern.trim_end_matches('/').contains('/'),
+        });
+        Ok(())
+    }
+
+    pub fn include(mut self, pattern: &str) -> Result<Self> {
+        self.add(FilterAction::Include, pattern)?;
+        Ok(self)
+    }
+
+    pub fn exclude(mut self, pattern: &str) -> Result<Self> {
+        self.add(FilterAction::Exclude, pattern)?;
+        Ok(self)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Whether `path`, relative to the root of the transfer, passes the filter.
+    pub fn is_included(&self, path: &str) -> bool {
+        self.rules
+            .iter()
+            .find(|rule| rule.matches(path))
+            .is_none_or(|rule| rule.action == FilterAction::Include)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_filter_includes_everything() {
+        let filter = PathFilter::new();
+        assert!(filter.is_included("a/b/c.txt"));
+    }
+
+    #[test]
+    fn test_first_match_wins() {
+        let filter = PathFilter::new()
+            .include("*.csv").unwrap()
+            .exclude("*").unwrap();
+        assert!(filter.is_included("data/2024/a.csv"));
+        assert!(!filter.is_included("data/readme.md"));
+
+        let filter = PathFilter::new()
+            .exclude("*").unwrap()
+            .include("*.csv").unwrap();
+        assert!(!filter.is_included("a.csv"));
+    }
+
+    #[test]
+    fn test_directory_patterns() {
+        let filter = PathFilter::new()
+            .exclude("tmp/").unwrap()
+            .exclude("/data/raw").unwrap();
+        assert!(!filter.is_included("tmp/x.txt"));
+        assert!(!filter.is_included("nested/tmp/x.txt"));
+        assert!(!filter.is_included("data/raw/2024/x.bin"));
+        assert!(filter.is_included("other/data/raw/x.bin"));
+        assert!(filter.is_included("data/clean/x.bin"));
+    }
+
+    #[test]
+    fn test_star_does_not_cross_separator() {
+        let filter = PathFilter::new()
+            .exclude("data/*.log").unwrap()
+            .exclude("logs/**/*.gz").unwrap();
+        assert!(!filter.is_included("data/app.log"));
+        assert!(filter.is_included("data/old/app.log"));
+        assert!(!filter.is_included("logs/2024/01/app.gz"));
+    }
+
+    #[test]
+    fn test_invalid_pattern() {
+        assert!(matches!(PathFilter::new().include("a[").unwrap_err(), Error::Filter(_)));
+    }
+}
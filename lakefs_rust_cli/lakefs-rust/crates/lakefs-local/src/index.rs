@@ -1,5 +1,7 @@
 use crate::error::{Error, Result};
+use crate::paths;
 use chrono::{DateTime, Utc};
+use lakefs_api::Checksum;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::Path;
@@ -8,10 +10,14 @@ use std::fs;
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct IndexEntry {
     pub path: String,
-    pub checksum: String,
+    pub checksum: Checksum,
     pub size: u64,
     pub mtime: DateTime<Utc>,
     pub permissions: Option<u32>,
+    /// Checksum of each `delta::BLOCK_SIZE` block, kept for files at least
+    /// `SyncConfig::delta_threshold` bytes large
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub blocks: Vec<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -22,6 +28,14 @@ pub struct LocalIndex {
     pub head_commit: String,
     pub entries: HashMap<String, IndexEntry>,
     pub last_sync: DateTime<Utc>,
+    /// Pinned to the commit in `reference` instead of following a branch.
+    /// Snapshot checkouts are only ever pulled, never pushed.
+    #[serde(default)]
+    pub snapshot: bool,
+    /// Commit whose objects the last complete, unfiltered pull fetched. Later
+    /// pulls ask the server for the changes since instead of listing it all.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pulled_commit: Option<String>,
 }
 
 impl LocalIndex {
@@ -36,6 +50,16 @@ impl LocalIndex {
             head_commit: head_commit.to_string(),
             entries: HashMap::new(),
             last_sync: Utc::now(),
+            snapshot: false,
+            pulled_commit: None,
+        }
+    }
+    
+    /// Index for a read-only checkout of `commit_id`.
+    pub fn snapshot(repository: &str, commit_id: &str) -> Self {
+        Self {
+            snapshot: true,
+            ..Self::new(repository, commit_id, commit_id)
         }
     }
     
@@ -44,7 +68,7 @@ impl LocalIndex {
         let data = fs::read_to_string(&index_path)
             .map_err(|e| Error::Index(format!("Failed to read index: {}", e)))?;
         
-        let index: Self = serde_json::from_str(&data)
+        let mut index: Self = serde_json::from_str(&data)
             .map_err(|e| Error::Index(format!("Failed to parse index: {}", e)))?;
             
         if index.version != Self::VERSION {
@@ -54,6 +78,14 @@ impl LocalIndex {
             )));
         }
         
+        // Indexes written on Windows may hold `\`-separated paths
+        index.entries = std::mem::take(&mut index.entries)
+            .into_values()
+            .map(|mut entry| {
+                entry.path = paths::normalize(&entry.path);
+                (entry.path.clone(), entry)
+            })
+            .collect();
         Ok(index)
     }
     
@@ -73,15 +105,18 @@ impl LocalIndex {
     }
     
     pub fn get_entry(&self, path: &str) -> Option<&IndexEntry> {
-        self.entries.get(path)
+        self.entries.get(&paths::normalize(path))
     }
     
-    pub fn add_entry(&mut self, path: String, entry: IndexEntry) {
+    /// Track `entry`, keyed by the canonical form of `path`.
+    pub fn add_entry(&mut self, path: String, mut entry: IndexEntry) {
+        let path = paths::normalize(&path);
+        entry.path = path.clone();
         self.entries.insert(path, entry);
     }
     
     pub fn remove_entry(&mut self, path: &str) -> Option<IndexEntry> {
-        self.entries.remove(path)
+        self.entries.remove(&paths::normalize(path))
     }
     
     pub fn update_head(&mut self, commit_id: &str) {
@@ -112,10 +147,11 @@ mod tests {
         
         let entry = IndexEntry {
             path: "test.txt".to_string(),
-            checksum: "abc123".to_string(),
+            checksum: "abc123".into(),
             size: 1024,
             mtime: Utc::now(),
             permissions: Some(0o644),
+            blocks: Vec::new(),
         };
         
         // Add entry
@@ -124,12 +160,12 @@ mod tests {
         
         // Get entry
         let retrieved = index.get_entry("test.txt").unwrap();
-        assert_eq!(retrieved.checksum, "abc123");
+        assert_eq!(retrieved.checksum.to_string(), "abc123");
         assert_eq!(retrieved.size, 1024);
         
         // Remove entry
         let removed = index.remove_entry("test.txt").unwrap();
-        assert_eq!(removed.checksum, "abc123");
+        assert_eq!(removed.checksum.to_string(), "abc123");
         assert!(index.entries.is_empty());
     }
 
@@ -142,10 +178,11 @@ mod tests {
         let mut index = LocalIndex::new("test-repo", "main", "commit123");
         index.add_entry("file1.txt".to_string(), IndexEntry {
             path: "file1.txt".to_string(),
-            checksum: "checksum1".to_string(),
+            checksum: "checksum1".into(),
             size: 100,
             mtime: Utc::now(),
             permissions: None,
+            blocks: Vec::new(),
         });
         
         index.save(path).unwrap();
@@ -157,6 +194,23 @@ mod tests {
         assert_eq!(loaded.reference, "main");
         assert_eq!(loaded.head_commit, "commit123");
         assert_eq!(loaded.entries.len(), 1);
-        assert_eq!(loaded.get_entry("file1.txt").unwrap().checksum, "checksum1");
+        assert_eq!(loaded.get_entry("file1.txt").unwrap().checksum.to_string(), "checksum1");
+    }
+
+    #[test]
+    fn test_index_paths_are_canonical() {
+        let mut index = LocalIndex::new("test-repo", "main", "commit123");
+        index.add_entry("./data//a.csv".to_string(), IndexEntry {
+            path: "./data//a.csv".to_string(),
+            checksum: "abc123".into(),
+            size: 1,
+            mtime: Utc::now(),
+            permissions: None,
+            blocks: Vec::new(),
+        });
+        
+        assert_eq!(index.entries.keys().collect::<Vec<_>>(), ["data/a.csv"]);
+        assert_eq!(index.get_entry("data/a.csv").unwrap().path, "data/a.csv");
+        assert!(index.remove_entry("./data/a.csv").is_some());
     }
 }
@@ -1,9 +1,40 @@
 pub mod sync;
+pub mod attributes;
 pub mod index;
 pub mod changes;
 pub mod error;
+pub mod events;
+pub mod throttle;
+pub mod progress;
+pub mod transfer;
+pub mod filter;
+pub mod metrics;
+pub mod cache;
+pub mod clean;
+pub mod listings;
+pub mod status;
+pub mod compression;
+pub mod content_type;
+pub mod delta;
+pub mod paths;
+pub mod stash;
+#[cfg(feature = "cli")]
+pub mod terminal;
 
-pub use sync::{SyncManager, SyncConfig};
+pub use sync::{SyncManager, SyncConfig, SyncResult, StagedMismatch, TransferRecord, TransferDirection};
 pub use index::{LocalIndex, IndexEntry};
-pub use changes::{Change, ChangeType, ChangeDetector};
+pub use changes::{Change, ChangeDetector, ChangeReport, ChangeType};
 pub use error::{Error, Result};
+pub use events::SyncEvent;
+pub use throttle::BandwidthLimiter;
+pub use filter::{FilterAction, PathFilter};
+pub use metrics::SyncMetrics;
+pub use cache::{LinkStrategy, ObjectCache};
+pub use clean::{clean, CleanReport};
+pub use listings::ListingCache;
+pub use status::{status, StatusReport};
+pub use compression::Compression;
+pub use stash::Stash;
+pub use progress::{ProgressReporter, NoopProgress};
+#[cfg(feature = "cli")]
+pub use terminal::MultiProgressReporter;
@@ -0,0 +1,143 @@
+//! Remote listings saved in the checkout, keyed by the commit they were
+//! taken at, so commands comparing against an unchanged head do not list the
+//! branch again.
+
+use crate::error::{Error, Result};
+use crate::transfer;
+use lakefs_api::models::ObjectStats;
+use lakefs_api::{LakeFSApi, PaginationParams};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Listings of one checkout, under `.lakectl/cache/listings/<commit>.json`.
+/// Only the listing of the latest commit is kept: storing one drops the
+/// others, so a moved branch head invalidates what was cached for the old one.
+pub struct ListingCache {
+    dir: PathBuf,
+}
+
+impl ListingCache {
+    const DIR: &'static str = ".lakectl/cache/listings";
+
+    pub fn new(local_path: &Path) -> Self {
+        Self { dir: local_path.join(Self::DIR) }
+    }
+
+    fn file(&self, commit: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", commit))
+    }
+
+    /// The listing taken at `commit`, if one is cached. A file that cannot be
+    /// read is treated as missing and listed again.
+    pub fn load(&self, commit: &str) -> Option<Vec<ObjectStats>> {
+        let data = fs::read(self.file(commit)).ok()?;
+        serde_json::from_slice(&data).ok()
+    }
+
+    /// Cache `objects` as the listing at `commit`, replacing any other.
+    pub fn store(&self, commit: &str, objects: &[ObjectStats]) -> Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let destination = self.file(commit);
+        let temp = transfer::temp_path(&destination);
+        let data = serde_json::to_vec(objects).map_err(|e| Error::Index(e.to_string()))?;
+        fs::write(&temp, data)?;
+        fs::rename(&temp, &destination)?;
+
+        for entry in fs::read_dir(&self.dir)?.filter_map(|entry| entry.ok()) {
+            if entry.path() != destination {
+                let _ = fs::remove_file(entry.path());
+            }
+        }
+        Ok(())
+    }
+
+    /// Forget every cached listing, as after uploading to the branch, which
+    /// changes it without moving its head.
+    pub fn clear(&self) -> Result<()> {
+        match fs::remove_dir_all(&self.dir) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Every object on `reference` of `repository`, whose head is `commit`:
+    /// from the cache when it has a listing at `commit`, and listed and
+    /// cached otherwise. The second value says whether the cache answered.
+    pub async fn list(
+        &self,
+        client: &dyn LakeFSApi,
+        repository: &str,
+        reference: &str,
+        commit: &str,
+    ) -> Result<(Vec<ObjectStats>, bool)> {
+        if let Some(objects) = self.load(commit) {
+            return Ok((objects, true));
+        }
+
+        let mut objects = Vec::new();
+        let mut params = PaginationParams::new();
+        loop {
+            let response = client.list_objects_with(repository, reference, &params).await?;
+            objects.extend(response.results);
+            match response.pagination.next_offset {
+                Some(next) if response.pagination.has_more => params.after = Some(next),
+                _ => break,
+            }
+        }
+        self.store(commit, &objects)?;
+        Ok((objects, false))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lakefs_test_utils::fixtures::test_object_stats;
+    use lakefs_test_utils::scenario::MockLakeFS;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_store_replaces_other_commits() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache = ListingCache::new(temp_dir.path());
+        assert!(cache.load("c1").is_none());
+
+        cache.store("c1", &[test_object_stats()]).unwrap();
+        assert_eq!(cache.load("c1").unwrap()[0].path, "test/file.txt");
+
+        cache.store("c2", &[]).unwrap();
+        assert!(cache.load("c1").is_none());
+        assert!(cache.load("c2").unwrap().is_empty());
+
+        cache.clear().unwrap();
+        assert!(cache.load("c2").is_none());
+        cache.clear().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_list_caches_by_commit() {
+        let server = MockLakeFS::new()
+            .page_size(1)
+            .with_repo("repo")
+            .with_branch("main")
+            .with_object("a.csv", "a")
+            .with_object("b.csv", "b")
+            .start()
+            .await;
+        let client = lakefs_api::LakeFSClient::new(server.uri(), "Bearer test-token");
+        let temp_dir = TempDir::new().unwrap();
+        let cache = ListingCache::new(temp_dir.path());
+
+        let (objects, cached) = cache.list(&client, "repo", "main", "c1").await.unwrap();
+        assert_eq!((objects.len(), cached), (2, false));
+        let requests = server.received_requests().await.unwrap().len();
+
+        let (objects, cached) = cache.list(&client, "repo", "main", "c1").await.unwrap();
+        assert_eq!((objects.len(), cached), (2, true));
+        assert_eq!(server.received_requests().await.unwrap().len(), requests);
+
+        // A new head is listed again
+        let (_, cached) = cache.list(&client, "repo", "main", "c2").await.unwrap();
+        assert!(!cached);
+    }
+}
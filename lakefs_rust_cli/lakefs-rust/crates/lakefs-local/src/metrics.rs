@@ -0,0 +1,262 @@
+use crate::sync::{TransferDirection, TransferRecord};
+use lakefs_api::ClientMiddleware;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Upper bounds, in seconds, of the duration histogram buckets. Exporters
+/// of the `metrics` facade should be configured with the same.
+pub const DURATION_BUCKETS: [f64; 10] = [0.01, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 60.0];
+
+const DIRECTIONS: [(TransferDirection, &str); 4] = [
+    (TransferDirection::Upload, "upload"),
+    (TransferDirection::Download, "download"),
+    (TransferDirection::DeleteLocal, "delete_local"),
+    (TransferDirection::DeleteRemote, "delete_remote"),
+];
+
+#[derive(Default)]
+struct Histogram {
+    buckets: [AtomicU64; DURATION_BUCKETS.len()],
+    count: AtomicU64,
+    sum_micros: AtomicU64,
+}
+
+impl Histogram {
+    fn observe(&self, duration: Duration) {
+        let seconds = duration.as_secs_f64();
+        for (bucket, bound) in self.buckets.iter().zip(DURATION_BUCKETS) {
+            if seconds <= bound {
+                bucket.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.sum_micros.fetch_add(duration.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    fn render(&self, out: &mut String, name: &str, labels: &str) {
+        let separator = if labels.is_empty() { "" } else { "," };
+        for (bucket, bound) in self.buckets.iter().zip(DURATION_BUCKETS) {
+            let _ = writeln!(out, "{}_bucket{{{}{}le=\"{}\"}} {}", name, labels, separator, bound, bucket.load(Ordering::Relaxed));
+        }
+        let count = self.count.load(Ordering::Relaxed);
+        let _ = writeln!(out, "{}_bucket{{{}{}le=\"+Inf\"}} {}", name, labels, separator, count);
+        let sum = self.sum_micros.load(Ordering::Relaxed) as f64 / 1e6;
+        let labels = if labels.is_empty() { String::new() } else { format!("{{{}}}", labels) };
+        let _ = writeln!(out, "{}_sum{} {}", name, labels, sum);
+        let _ = writeln!(out, "{}_count{} {}", name, labels, count);
+    }
+}
+
+/// Counters and histograms describing sync activity.
+///
+/// One instance can be shared by several `SyncManager`s (see
+/// `SyncManager::with_metrics`) so a long-running process reports totals
+/// across syncs. Registered as client middleware it also counts request
+/// retries. Everything recorded is also emitted through the `metrics`
+/// facade under the same names, for whichever recorder the process installs.
+#[derive(Default)]
+pub struct SyncMetrics {
+    syncs: AtomicU64,
+    objects: [AtomicU64; DIRECTIONS.len()],
+    bytes: [AtomicU64; DIRECTIONS.len()],
+    errors: AtomicU64,
+    retries: AtomicU64,
+    transfer_duration: [Histogram; DIRECTIONS.len()],
+    sync_duration: Histogram,
+}
+
+impl SyncMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register the help text of each metric with the installed recorder.
+    pub fn describe() {
+        metrics::describe_counter!("lakectl_sync_runs_total", "Completed syncs.");
+        metrics::describe_counter!("lakectl_sync_objects_total", "Objects transferred or deleted.");
+        metrics::describe_counter!("lakectl_sync_bytes_total", metrics::Unit::Bytes, "Bytes transferred.");
+        metrics::describe_counter!("lakectl_sync_errors_total", "Transfers that failed.");
+        metrics::describe_counter!("lakectl_sync_retries_total", "API requests retried after a transient failure.");
+        metrics::describe_histogram!("lakectl_sync_transfer_duration_seconds", metrics::Unit::Seconds, "Time spent on each file.");
+        metrics::describe_histogram!("lakectl_sync_duration_seconds", metrics::Unit::Seconds, "Time taken by each sync.");
+    }
+
+    pub(crate) fn record_transfer(&self, record: &TransferRecord) {
+        let slot = Self::slot(record.direction);
+        if record.error.is_some() {
+            self.errors.fetch_add(1, Ordering::Relaxed);
+            metrics::counter!("lakectl_sync_errors_total").increment(1);
+            return;
+        }
+        self.objects[slot].fetch_add(1, Ordering::Relaxed);
+        self.bytes[slot].fetch_add(record.bytes, Ordering::Relaxed);
+        self.transfer_duration[slot].observe(record.duration);
+        
+        let label = DIRECTIONS[slot].1;
+        metrics::counter!("lakectl_sync_objects_total", "direction" => label).increment(1);
+        if slot < 2 {
+            metrics::counter!("lakectl_sync_bytes_total", "direction" => label).increment(record.bytes);
+        }
+        metrics::histogram!("lakectl_sync_transfer_duration_seconds", "direction" => label).record(record.duration);
+    }
+
+    pub(crate) fn record_sync(&self, duration: Duration) {
+        self.syncs.fetch_add(1, Ordering::Relaxed);
+        self.sync_duration.observe(duration);
+        metrics::counter!("lakectl_sync_runs_total").increment(1);
+        metrics::histogram!("lakectl_sync_duration_seconds").record(duration);
+    }
+
+    /// Objects successfully transferred or deleted in `direction`.
+    pub fn objects(&self, direction: TransferDirection) -> u64 {
+        self.objects[Self::slot(direction)].load(Ordering::Relaxed)
+    }
+
+    pub fn bytes(&self, direction: TransferDirection) -> u64 {
+        self.bytes[Self::slot(direction)].load(Ordering::Relaxed)
+    }
+
+    pub fn errors(&self) -> u64 {
+        self.errors.load(Ordering::Relaxed)
+    }
+
+    pub fn retries(&self) -> u64 {
+        self.retries.load(Ordering::Relaxed)
+    }
+
+    pub fn syncs(&self) -> u64 {
+        self.syncs.load(Ordering::Relaxed)
+    }
+
+    /// Render every metric in the Prometheus text exposition format.
+    pub fn render_prometheus(&self) -> String {
+        let mut out = String::new();
+
+        out.push_str("# HELP lakectl_sync_runs_total Completed syncs.\n");
+        out.push_str("# TYPE lakectl_sync_runs_total counter\n");
+        let _ = writeln!(out, "lakectl_sync_runs_total {}", self.syncs());
+
+        out.push_str("# HELP lakectl_sync_objects_total Objects transferred or deleted.\n");
+        out.push_str("# TYPE lakectl_sync_objects_total counter\n");
+        for (direction, label) in DIRECTIONS {
+            let _ = writeln!(out, "lakectl_sync_objects_total{{direction=\"{}\"}} {}", label, self.objects(direction));
+        }
+
+        out.push_str("# HELP lakectl_sync_bytes_total Bytes transferred.\n");
+        out.push_str("# TYPE lakectl_sync_bytes_total counter\n");
+        for (direction, label) in &DIRECTIONS[..2] {
+            let _ = writeln!(out, "lakectl_sync_bytes_total{{direction=\"{}\"}} {}", label, self.bytes(*direction));
+        }
+
+        out.push_str("# HELP lakectl_sync_errors_total Transfers that failed.\n");
+        out.push_str("# TYPE lakectl_sync_errors_total counter\n");
+        let _ = writeln!(out, "lakectl_sync_errors_total {}", self.errors());
+
+        out.push_str("# HELP lakectl_sync_retries_total API requests retried after a transient failure.\n");
+        out.push_str("# TYPE lakectl_sync_retries_total counter\n");
+        let _ = writeln!(out, "lakectl_sync_retries_total {}", self.retries());
+
+        out.push_str("# HELP lakectl_sync_transfer_duration_seconds Time spent on each file.\n");
+        out.push_str("# TYPE lakectl_sync_transfer_duration_seconds histogram\n");
+        for ((_, label), histogram) in DIRECTIONS.iter().zip(&self.transfer_duration) {
+            let labels = format!("direction=\"{}\"", label);
+            histogram.render(&mut out, "lakectl_sync_transfer_duration_seconds", &labels);
+        }
+
+        out.push_str("# HELP lakectl_sync_duration_seconds Time taken by each sync.\n");
+        out.push_str("# TYPE lakectl_sync_duration_seconds histogram\n");
+        self.sync_duration.render(&mut out, "lakectl_sync_duration_seconds", "");
+
+        out
+    }
+
+    fn slot(direction: TransferDirection) -> usize {
+        DIRECTIONS.iter().position(|(d, _)| *d == direction).unwrap_or_default()
+    }
+}
+
+impl ClientMiddleware for SyncMetrics {
+    fn on_request(&self, _request: &mut reqwest::Request) -> lakefs_api::Result<()> {
+        Ok(())
+    }
+
+    fn on_retry(&self, _attempt: u32) {
+        self.retries.fetch_add(1, Ordering::Relaxed);
+        metrics::counter!("lakectl_sync_retries_total").increment(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+
+    fn record(direction: TransferDirection, bytes: u64, millis: u64, error: Option<&str>) -> TransferRecord {
+        TransferRecord {
+            path: "a.csv".into(),
+            direction,
+            bytes,
+            duration: Duration::from_millis(millis),
+            error: error.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_record_and_render() {
+        let metrics = SyncMetrics::new();
+        metrics.record_transfer(&record(TransferDirection::Upload, 100, 20, None));
+        metrics.record_transfer(&record(TransferDirection::Upload, 50, 200, None));
+        metrics.record_transfer(&record(TransferDirection::Download, 0, 5, Some("boom")));
+        metrics.record_sync(Duration::from_millis(300));
+        metrics.on_retry(1);
+
+        assert_eq!(metrics.objects(TransferDirection::Upload), 2);
+        assert_eq!(metrics.bytes(TransferDirection::Upload), 150);
+        assert_eq!(metrics.objects(TransferDirection::Download), 0);
+        assert_eq!(metrics.errors(), 1);
+
+        let text = metrics.render_prometheus();
+        assert!(text.contains("lakectl_sync_runs_total 1\n"));
+        assert!(text.contains("lakectl_sync_objects_total{direction=\"upload\"} 2\n"));
+        assert!(text.contains("lakectl_sync_bytes_total{direction=\"upload\"} 150\n"));
+        assert!(text.contains("lakectl_sync_retries_total 1\n"));
+        assert!(text.contains("lakectl_sync_transfer_duration_seconds_bucket{direction=\"upload\",le=\"0.05\"} 1\n"));
+        assert!(text.contains("lakectl_sync_transfer_duration_seconds_bucket{direction=\"upload\",le=\"0.25\"} 2\n"));
+        assert!(text.contains("lakectl_sync_duration_seconds_bucket{le=\"+Inf\"} 1\n"));
+        assert!(text.contains("lakectl_sync_duration_seconds_count 1\n"));
+    }
+
+    #[test]
+    fn test_emits_through_metrics_facade() {
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+        metrics::with_local_recorder(&recorder, || {
+            let metrics = SyncMetrics::new();
+            metrics.record_transfer(&record(TransferDirection::Download, 100, 20, None));
+            metrics.record_transfer(&record(TransferDirection::DeleteLocal, 0, 1, None));
+            metrics.record_sync(Duration::from_millis(300));
+        });
+        
+        let values: Vec<(String, Vec<String>, DebugValue)> = snapshotter
+            .snapshot()
+            .into_vec()
+            .into_iter()
+            .map(|(key, _, _, value)| {
+                let key = key.key();
+                let labels = key.labels().map(|label| format!("{}={}", label.key(), label.value())).collect();
+                (key.name().to_string(), labels, value)
+            })
+            .collect();
+        let value = |name: &str, labels: &[&str]| values
+            .iter()
+            .find(|(n, l, _)| n == name && l == labels)
+            .map(|(_, _, value)| value);
+        assert_eq!(value("lakectl_sync_objects_total", &["direction=download"]), Some(&DebugValue::Counter(1)));
+        assert_eq!(value("lakectl_sync_objects_total", &["direction=delete_local"]), Some(&DebugValue::Counter(1)));
+        assert_eq!(value("lakectl_sync_bytes_total", &["direction=download"]), Some(&DebugValue::Counter(100)));
+        assert_eq!(value("lakectl_sync_bytes_total", &["direction=delete_local"]), None);
+        assert_eq!(value("lakectl_sync_runs_total", &[]), Some(&DebugValue::Counter(1)));
+        assert!(matches!(value("lakectl_sync_duration_seconds", &[]), Some(DebugValue::Histogram(h)) if h.len() == 1));
+    }
+}
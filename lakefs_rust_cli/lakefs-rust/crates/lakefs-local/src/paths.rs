@@ -0,0 +1,192 @@
+//! Mapping between object keys, which always use `/`, and local paths,
+//! which use the platform's separator.
+
+use crate::error::{Error, Result};
+use path_slash::PathExt;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+/// Characters Windows does not allow in file names.
+const WINDOWS_RESERVED: [char; 8] = ['<', '>', ':', '"', '|', '?', '*', '\\'];
+
+/// Canonical form of a path relative to a checkout, as object paths are
+/// written: `/` separators and no empty or `.` segments.
+pub fn normalize(path: &str) -> String {
+    let path = Path::new(path).to_slash_lossy();
+    path.split('/')
+        .filter(|segment| !segment.is_empty() && *segment != ".")
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Key of the file at `path` inside the checkout at `base`.
+pub fn to_key(base: &Path, path: &Path) -> Result<String> {
+    let relative = path
+        .strip_prefix(base)
+        .map_err(|_| Error::InvalidPath(format!("Path not within local directory: {:?}", path)))?;
+    Ok(relative
+        .components()
+        .filter_map(|component| match component {
+            Component::Normal(segment) => Some(segment.to_string_lossy()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("/"))
+}
+
+/// Local path of the file for `key` in the checkout at `base`, joined
+/// segment by segment so it uses native separators.
+pub fn to_local(base: &Path, key: &str) -> PathBuf {
+    let mut path = extended_length(base);
+    path.extend(key.split('/').filter(|segment| !segment.is_empty() && *segment != "."));
+    path
+}
+
+/// `path` in the extended-length form (`\\?\C:\...`) Windows needs for
+/// paths longer than 260 characters; other platforms have no such limit and
+/// get `path` back unchanged, as do relative paths.
+pub fn extended_length(path: &Path) -> PathBuf {
+    if cfg!(windows) && path.is_absolute() {
+        // The prefix turns off all parsing, including of `/` separators
+        let raw = path.as_os_str().to_string_lossy().replace('/', "\\");
+        if raw.starts_with(r"\\?\") {
+            return path.to_path_buf();
+        }
+        if let Some(share) = raw.strip_prefix(r"\\") {
+            return PathBuf::from(format!(r"\\?\UNC\{}", share));
+        }
+        return PathBuf::from(format!(r"\\?\{}", raw));
+    }
+    path.to_path_buf()
+}
+
+/// Object key for `key` under the remote's optional path prefix.
+pub fn to_remote(prefix: Option<&str>, key: &str) -> String {
+    match prefix.map(|p| p.trim_end_matches('/')).filter(|p| !p.is_empty()) {
+        Some(prefix) => format!("{}/{}", prefix, key),
+        None => key.to_string(),
+    }
+}
+
+/// Key relative to the remote's optional path prefix of the object at
+/// `remote_key`, or `None` when it lies outside the prefix.
+pub fn from_remote<'a>(prefix: Option<&str>, remote_key: &'a str) -> Option<&'a str> {
+    match prefix.map(|p| p.trim_end_matches('/')).filter(|p| !p.is_empty()) {
+        Some(prefix) => remote_key.strip_prefix(prefix)?.strip_prefix('/'),
+        None => Some(remote_key),
+    }
+}
+
+/// Reject object keys that cannot be written as a file in the checkout:
+/// ones that would climb out of it, and on Windows ones with characters or
+/// trailing dots and spaces the filesystem does not allow.
+pub fn check_key(key: &str) -> Result<()> {
+    let invalid = key.split('/').any(|segment| {
+        segment == ".."
+            || (cfg!(windows) && (segment.contains(WINDOWS_RESERVED) || segment.ends_with(['.', ' '])))
+    });
+    if invalid {
+        return Err(Error::InvalidPath(format!("'{}' cannot be checked out as a local file", key)));
+    }
+    Ok(())
+}
+
+/// Pairs of paths that name the same file on a case-insensitive filesystem,
+/// such as `Data/a.csv` and `data/a.csv`, or `Data/a.csv` and `data/b.csv`
+/// whose directories would be merged. Each clash is reported once.
+pub fn case_collisions<'a>(paths: impl IntoIterator<Item = &'a str>) -> Vec<(String, String)> {
+    let mut seen: HashMap<String, &str> = HashMap::new();
+    let mut collisions = Vec::new();
+    for path in paths {
+        let mut end = 0;
+        for segment in path.split('/') {
+            end += segment.len();
+            let prefix = &path[..end];
+            end += 1;
+
+            match seen.get(&prefix.to_lowercase()) {
+                Some(existing) if *existing == prefix => {}
+                Some(existing) => {
+                    collisions.push((existing.to_string(), prefix.to_string()));
+                    break;
+                }
+                None => {
+                    seen.insert(prefix.to_lowercase(), prefix);
+                }
+            }
+        }
+    }
+    collisions.sort();
+    collisions.dedup();
+    collisions
+}
+
+/// Whether `dir` is on a filesystem that ignores case in file names, as is
+/// the default on macOS and Windows. Creates `dir` if it does not exist.
+pub fn is_case_insensitive(dir: &Path) -> io::Result<bool> {
+    fs::create_dir_all(dir)?;
+    let probe = dir.join(format!(".lakectl-CaseProbe-{}", std::process::id()));
+    fs::write(&probe, b"")?;
+    let folded = dir.join(format!(".lakectl-caseprobe-{}", std::process::id()));
+    let insensitive = folded.exists();
+    fs::remove_file(&probe)?;
+    Ok(insensitive)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize("./data//a.csv"), "data/a.csv");
+        assert_eq!(normalize("/data/a.csv/"), "data/a.csv");
+        #[cfg(windows)]
+        assert_eq!(normalize("data\\sub\\a.csv"), "data/sub/a.csv");
+    }
+
+    #[test]
+    fn test_key_mapping() {
+        let base = Path::new("checkout");
+        let local = to_local(base, "data/sub/a.csv");
+        assert_eq!(local, base.join("data").join("sub").join("a.csv"));
+        assert_eq!(to_key(base, &local).unwrap(), "data/sub/a.csv");
+        assert!(to_key(base, Path::new("elsewhere/a.csv")).is_err());
+
+        assert_eq!(to_remote(Some("prefix/"), "data/a.csv"), "prefix/data/a.csv");
+        assert_eq!(to_remote(Some(""), "data/a.csv"), "data/a.csv");
+        assert_eq!(to_remote(None, "data/a.csv"), "data/a.csv");
+    }
+
+    #[test]
+    fn test_check_key() {
+        assert!(check_key("data/a.csv").is_ok());
+        assert!(check_key("data/../../etc/passwd").is_err());
+        #[cfg(windows)]
+        {
+            assert!(check_key("data/a:b.csv").is_err());
+            assert!(check_key("data/trailing./a.csv").is_err());
+        }
+    }
+
+    #[test]
+    fn test_case_collisions() {
+        let paths = ["Data/a.csv", "data/a.csv", "data/b.csv", "Data/A.csv", "other/x", "other/y"];
+        assert_eq!(case_collisions(paths), vec![
+            ("Data".to_string(), "data".to_string()),
+            ("Data/a.csv".to_string(), "Data/A.csv".to_string()),
+        ]);
+        assert!(case_collisions(["a/b", "a/c", "b"]).is_empty());
+    }
+
+    #[test]
+    fn test_is_case_insensitive_cleans_up() {
+        let temp_dir = TempDir::new().unwrap();
+        let checkout = temp_dir.path().join("checkout");
+        is_case_insensitive(&checkout).unwrap();
+        assert_eq!(fs::read_dir(&checkout).unwrap().count(), 0);
+    }
+}
@@ -0,0 +1,42 @@
+use crate::error::Error;
+
+/// Receives byte-level progress for a batch of transfers.
+///
+/// Transfers may run in parallel, so every per-file call carries the path
+/// it refers to.
+pub trait ProgressReporter: Send + Sync {
+    /// Announce the size of the whole batch before any transfer starts.
+    fn set_total(&self, files: u64, bytes: u64);
+
+    fn file_started(&self, path: &str, size: u64);
+
+    fn bytes_transferred(&self, path: &str, bytes: u64);
+
+    fn file_finished(&self, path: &str);
+
+    /// A change failed. Its transfer may have started and will not finish.
+    fn file_failed(&self, _path: &str, _error: &Error) {}
+
+    fn finish(&self, message: &str);
+
+    /// Whether per-chunk updates are wanted. Transfers fall back to
+    /// whole-body requests when nobody is watching.
+    fn is_enabled(&self) -> bool {
+        true
+    }
+}
+
+/// Reporter that discards all updates.
+pub struct NoopProgress;
+
+impl ProgressReporter for NoopProgress {
+    fn set_total(&self, _files: u64, _bytes: u64) {}
+    fn file_started(&self, _path: &str, _size: u64) {}
+    fn bytes_transferred(&self, _path: &str, _bytes: u64) {}
+    fn file_finished(&self, _path: &str) {}
+    fn finish(&self, _message: &str) {}
+
+    fn is_enabled(&self) -> bool {
+        false
+    }
+}
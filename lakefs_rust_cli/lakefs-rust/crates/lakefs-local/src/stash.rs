@@ -0,0 +1,275 @@
+//! Setting local changes aside: `push` archives the files that differ from
+//! the index and reverts them, `pop` puts them back.
+
+use crate::changes::{ChangeDetector, ChangeType};
+use crate::error::{Error, Result};
+use crate::index::LocalIndex;
+use crate::paths;
+use crate::progress::{NoopProgress, ProgressReporter};
+use crate::transfer;
+use chrono::{DateTime, Utc};
+use lakefs_api::LakeFSApi;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+const STASH_DIR: &str = ".lakectl/stash";
+
+/// A set of stashed changes. The files are in `<id>.tar.zst` and this
+/// description next to them in `<id>.json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Stash {
+    pub id: String,
+    pub created: DateTime<Utc>,
+    pub message: Option<String>,
+    /// Branch or commit the checkout was at
+    pub reference: String,
+    pub head_commit: String,
+    /// Added and modified files, stored in the archive
+    pub files: Vec<String>,
+    /// Tracked files that had been deleted
+    pub deleted: Vec<String>,
+}
+
+impl Stash {
+    fn archive(&self, local_path: &Path) -> PathBuf {
+        local_path.join(STASH_DIR).join(format!("{}.tar.zst", self.id))
+    }
+
+    fn manifest(&self, local_path: &Path) -> PathBuf {
+        local_path.join(STASH_DIR).join(format!("{}.json", self.id))
+    }
+}
+
+/// Archive the local changes in the checkout at `local_path`, then revert
+/// them: added files are removed and modified or deleted ones are
+/// downloaded again at the head commit. Returns `None` when there is
+/// nothing to stash.
+pub async fn push(client: &dyn LakeFSApi, local_path: &Path, message: Option<String>) -> Result<Option<Stash>> {
+    let mut index = LocalIndex::load(local_path)?;
+    let report = ChangeDetector::new(local_path.to_path_buf()).detect_changes(&index, Vec::new())?;
+    if let Some((path, e)) = report.errors.into_iter().next() {
+        return Err(Error::Stash(format!("cannot read {}: {}", path, e)));
+    }
+
+    let mut files = Vec::new();
+    let mut deleted = Vec::new();
+    for change in report.changes {
+        match change.change_type {
+            ChangeType::Added | ChangeType::Modified => files.push(change.path),
+            ChangeType::Removed => deleted.push(change.path),
+        }
+    }
+    if files.is_empty() && deleted.is_empty() {
+        return Ok(None);
+    }
+    files.sort();
+    deleted.sort();
+
+    let stash = Stash {
+        id: next_id(local_path)?,
+        created: Utc::now(),
+        message,
+        reference: index.reference.clone(),
+        head_commit: index.head_commit.clone(),
+        files,
+        deleted,
+    };
+    write_archive(&stash, local_path)?;
+    let manifest = serde_json::to_vec_pretty(&stash).map_err(|e| Error::Stash(e.to_string()))?;
+    fs::write(stash.manifest(local_path), manifest)?;
+
+    // Only now that the changes are safely archived
+    let reporter: Arc<dyn ProgressReporter> = Arc::new(NoopProgress);
+    for path in stash.files.iter().chain(&stash.deleted) {
+        let local = paths::to_local(local_path, path);
+        let Some(entry) = index.entries.get_mut(path) else {
+            fs::remove_file(&local)?;
+            continue;
+        };
+        let stats = client.get_object(&index.repository, &index.head_commit, path).await?;
+        if let Some(parent) = local.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        transfer::download(client, &index.repository, &index.head_commit, &stats, &local, path, None, &reporter).await?;
+        // The content is what was synced, so don't make the next status hash it
+        entry.mtime = fs::metadata(&local)?.modified()?.into();
+    }
+    index.save(local_path)?;
+    Ok(Some(stash))
+}
+
+/// Stashes of the checkout at `local_path`, newest first.
+pub fn list(local_path: &Path) -> Result<Vec<Stash>> {
+    let dir = local_path.join(STASH_DIR);
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut stashes = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|ext| ext == "json") {
+            let stash = serde_json::from_slice::<Stash>(&fs::read(&path)?)
+                .map_err(|e| Error::Stash(format!("{}: {}", path.display(), e)))?;
+            stashes.push(stash);
+        }
+    }
+    stashes.sort_by(|a, b| b.created.cmp(&a.created).then_with(|| b.id.cmp(&a.id)));
+    Ok(stashes)
+}
+
+/// Restore the stash `id`, or the newest one, and delete it. Refuses to
+/// overwrite files that have changed since the stash was made.
+pub fn pop(local_path: &Path, id: Option<&str>) -> Result<Stash> {
+    let stashes = list(local_path)?;
+    let stash = match id {
+        Some(id) => stashes.into_iter().find(|stash| stash.id == id),
+        None => stashes.into_iter().next(),
+    }
+    .ok_or_else(|| Error::Stash(match id {
+        Some(id) => format!("no stash '{}'", id),
+        None => "no stashes".to_string(),
+    }))?;
+
+    let index = LocalIndex::load(local_path)?;
+    let report = ChangeDetector::new(local_path.to_path_buf()).detect_changes(&index, Vec::new())?;
+    let stashed: HashSet<&String> = stash.files.iter().chain(&stash.deleted).collect();
+    let mut conflicts: Vec<&str> = report
+        .changes
+        .iter()
+        .filter(|change| stashed.contains(&change.path))
+        .map(|change| change.path.as_str())
+        .collect();
+    if !conflicts.is_empty() {
+        conflicts.sort();
+        return Err(Error::Stash(format!(
+            "local changes to {} would be overwritten; push or stash them first",
+            conflicts.join(", ")
+        )));
+    }
+
+    let archive = fs::File::open(stash.archive(local_path))?;
+    let mut archive = tar::Archive::new(zstd::Decoder::new(archive)?);
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let key = paths::normalize(&entry.path()?.to_string_lossy());
+        paths::check_key(&key)?;
+        let local = paths::to_local(local_path, &key);
+        if let Some(parent) = local.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        entry.unpack(&local)?;
+    }
+    for path in &stash.deleted {
+        let local = paths::to_local(local_path, path);
+        if local.exists() {
+            fs::remove_file(local)?;
+        }
+    }
+
+    fs::remove_file(stash.archive(local_path))?;
+    fs::remove_file(stash.manifest(local_path))?;
+    Ok(stash)
+}
+
+/// A timestamp, suffixed if a stash was already made in the same second.
+fn next_id(local_path: &Path) -> Result<String> {
+    let dir = local_path.join(STASH_DIR);
+    fs::create_dir_all(&dir)?;
+    let base = Utc::now().format("%Y%m%d-%H%M%S").to_string();
+    let mut id = base.clone();
+    let mut n = 1;
+    while dir.join(format!("{}.json", id)).exists() {
+        n += 1;
+        id = format!("{}-{}", base, n);
+    }
+    Ok(id)
+}
+
+/// Write the stashed files to a temporary archive, renamed into place once
+/// complete.
+fn write_archive(stash: &Stash, local_path: &Path) -> Result<()> {
+    let destination = stash.archive(local_path);
+    let temp = transfer::temp_path(&destination);
+    let result = (|| -> Result<()> {
+        let encoder = zstd::Encoder::new(fs::File::create(&temp)?, 0)?;
+        let mut builder = tar::Builder::new(encoder);
+        for path in &stash.files {
+            builder.append_path_with_name(paths::to_local(local_path, path), path)?;
+        }
+        builder.into_inner()?.finish()?.sync_all()?;
+        Ok(())
+    })();
+    match result {
+        Ok(()) => Ok(fs::rename(&temp, &destination)?),
+        Err(e) => {
+            let _ = fs::remove_file(&temp);
+            Err(e)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::IndexEntry;
+    use lakefs_api::LakeFSClient;
+    use lakefs_test_utils::scenario::MockLakeFS;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_stash_push_and_pop() {
+        let server = MockLakeFS::new()
+            .with_repo("repo")
+            .with_branch("main")
+            .with_object("data/a.csv", "a,synced")
+            .with_object("data/b.csv", "b,synced")
+            .start()
+            .await;
+        let client = LakeFSClient::new(server.uri(), "Bearer test-token");
+
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let mut index = LocalIndex::new("repo", "main", "main");
+        fs::create_dir(root.join("data")).unwrap();
+        for (path, content) in [("data/a.csv", "a,synced"), ("data/b.csv", "b,synced")] {
+            fs::write(root.join(path), content).unwrap();
+            let stats = client.get_object("repo", "main", path).await.unwrap();
+            index.add_entry(path.to_string(), IndexEntry {
+                path: path.to_string(),
+                checksum: stats.checksum,
+                size: content.len() as u64,
+                mtime: Utc::now(),
+                permissions: None,
+                blocks: Vec::new(),
+            });
+        }
+        index.save(root).unwrap();
+
+        assert!(push(&client, root, None).await.unwrap().is_none());
+
+        fs::write(root.join("data/a.csv"), "a,edited locally").unwrap();
+        fs::remove_file(root.join("data/b.csv")).unwrap();
+        fs::write(root.join("new.csv"), "new").unwrap();
+        let stash = push(&client, root, Some("wip".to_string())).await.unwrap().unwrap();
+        assert_eq!(stash.files, ["data/a.csv", "new.csv"]);
+        assert_eq!(stash.deleted, ["data/b.csv"]);
+        assert_eq!(fs::read_to_string(root.join("data/a.csv")).unwrap(), "a,synced");
+        assert_eq!(fs::read_to_string(root.join("data/b.csv")).unwrap(), "b,synced");
+        assert!(!root.join("new.csv").exists());
+        assert_eq!(list(root).unwrap().len(), 1);
+
+        fs::write(root.join("data/a.csv"), "a,edited again").unwrap();
+        assert!(pop(root, None).is_err());
+        fs::write(root.join("data/a.csv"), "a,synced").unwrap();
+
+        let popped = pop(root, Some(&stash.id)).unwrap();
+        assert_eq!(popped.message.as_deref(), Some("wip"));
+        assert_eq!(fs::read_to_string(root.join("data/a.csv")).unwrap(), "a,edited locally");
+        assert_eq!(fs::read_to_string(root.join("new.csv")).unwrap(), "new");
+        assert!(!root.join("data/b.csv").exists());
+        assert!(list(root).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,121 @@
+use crate::changes::{ChangeDetector, ChangeType};
+use crate::error::Result;
+use crate::index::LocalIndex;
+use crate::listings::ListingCache;
+use lakefs_api::LakeFSApi;
+use std::collections::HashSet;
+use std::path::Path;
+
+/// What [`status`] found changed since the last sync.
+#[derive(Debug)]
+pub struct StatusReport {
+    /// Head commit of the branch when it was listed, or the pinned commit of
+    /// a snapshot
+    pub commit: String,
+    /// Whether the listing of `commit` came from the checkout's cache
+    pub cached: bool,
+    /// Files added, modified or removed on disk, sorted by path
+    pub local: Vec<(String, ChangeType)>,
+    /// Objects added, modified or removed on the branch, sorted by path
+    pub remote: Vec<(String, ChangeType)>,
+}
+
+/// Compare the checkout at `local_path` with its index on one side and with
+/// its branch on the other. The branch's listing is cached under its head
+/// commit, so until the head moves only the branch itself is fetched; changes
+/// staged by others in the meantime show once it does.
+pub async fn status(client: &dyn LakeFSApi, local_path: &Path) -> Result<StatusReport> {
+    let index = LocalIndex::load(local_path)?;
+    let commit = if index.snapshot {
+        index.reference.clone()
+    } else {
+        client.get_branch(&index.repository, &index.reference).await?.commit_id
+    };
+    let cache = ListingCache::new(local_path);
+    let (objects, cached) = cache.list(client, &index.repository, &index.reference, &commit).await?;
+    let listed: HashSet<String> = objects.iter().map(|obj| obj.path.clone()).collect();
+
+    let report = ChangeDetector::new(local_path.to_path_buf()).detect_changes(&index, objects)?;
+    let (mut local, mut remote) = (Vec::new(), Vec::new());
+    for change in report.changes {
+        // A tracked file missing from disk was deleted locally
+        let side = if change.local_path.is_some() || change.change_type == ChangeType::Removed {
+            &mut local
+        } else {
+            &mut remote
+        };
+        side.push((change.path, change.change_type));
+    }
+    remote.extend(
+        index
+            .entries
+            .keys()
+            .filter(|path| !listed.contains(*path))
+            .map(|path| (path.clone(), ChangeType::Removed)),
+    );
+    local.sort_by(|a, b| a.0.cmp(&b.0));
+    remote.sort_by(|a, b| a.0.cmp(&b.0));
+
+    Ok(StatusReport { commit, cached, local, remote })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::IndexEntry;
+    use chrono::Utc;
+    use lakefs_api::Checksum;
+    use lakefs_test_utils::scenario::MockLakeFS;
+    use md5::Digest;
+    use tempfile::TempDir;
+
+    fn entry(path: &str, size: u64) -> IndexEntry {
+        IndexEntry {
+            path: path.to_string(),
+            checksum: "old".into(),
+            size,
+            mtime: Utc::now() + chrono::Duration::days(1),
+            permissions: None,
+            blocks: Vec::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_status_reports_both_sides() {
+        let server = MockLakeFS::new()
+            .with_repo("repo")
+            .with_branch("main")
+            .with_object("kept.csv", "kept")
+            .with_object("new.csv", "new")
+            .with_object("changed.csv", "changed")
+            .start()
+            .await;
+        let client = lakefs_api::LakeFSClient::new(server.uri(), "Bearer test-token");
+        let temp_dir = TempDir::new().unwrap();
+
+        let mut index = LocalIndex::new("repo", "main", "c0");
+        for path in ["kept.csv", "changed.csv", "gone.csv", "deleted.csv"] {
+            index.add_entry(path.to_string(), entry(path, 4));
+        }
+        index.entries.get_mut("kept.csv").unwrap().checksum = Checksum::Md5(format!("{:x}", md5::Md5::digest(b"kept")));
+        index.save(temp_dir.path()).unwrap();
+        for path in ["kept.csv", "changed.csv", "gone.csv"] {
+            std::fs::write(temp_dir.path().join(path), "same").unwrap();
+        }
+        std::fs::write(temp_dir.path().join("local.csv"), "local").unwrap();
+
+        let report = status(&client, temp_dir.path()).await.unwrap();
+        assert!(!report.cached);
+        assert_eq!(report.local, [("deleted.csv".to_string(), ChangeType::Removed), ("local.csv".to_string(), ChangeType::Added)]);
+        assert_eq!(report.remote, [
+            ("changed.csv".to_string(), ChangeType::Modified),
+            ("deleted.csv".to_string(), ChangeType::Removed),
+            ("gone.csv".to_string(), ChangeType::Removed),
+            ("new.csv".to_string(), ChangeType::Added),
+        ]);
+
+        let report = status(&client, temp_dir.path()).await.unwrap();
+        assert!(report.cached);
+        assert_eq!(report.remote.len(), 4);
+    }
+}
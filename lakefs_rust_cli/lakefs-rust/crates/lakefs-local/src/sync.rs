@@ -1,39 +1,162 @@
+use crate::attributes;
 use crate::error::{Error, Result};
 use crate::index::{LocalIndex, IndexEntry};
-use crate::changes::{Change, ChangeType, ChangeDetector};
-use lakefs_api::{LakeFSClient, LakeFSUri, models::ObjectStats};
-use bytes::Bytes;
-use chrono::Utc;
-use indicatif::{ProgressBar, ProgressStyle};
-use std::path::Path;
+use crate::cache::{LinkStrategy, ObjectCache};
+use crate::changes::{Change, ChangeDetector, ChangeReport, ChangeType};
+use crate::compression::Compression;
+use crate::delta;
+use crate::events::{EventReporter, SyncEvent};
+use crate::filter::PathFilter;
+use crate::listings::ListingCache;
+use crate::metrics::SyncMetrics;
+use crate::paths;
+use crate::progress::{NoopProgress, ProgressReporter};
+use crate::throttle::BandwidthLimiter;
+use crate::transfer::{self, Hasher};
+use lakefs_api::{Checksum, LakeFSApi, LakeFSUri, PaginationParams, Precondition, RetryPolicy, UploadOptions, models::{DiffType, ObjectStats, PathType}};
+use futures::stream::{FuturesUnordered, Stream, StreamExt};
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Serializer};
+use std::collections::{BTreeSet, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 use tokio::fs;
 use tokio::sync::Semaphore;
+use tokio_util::sync::CancellationToken;
 use std::sync::Arc;
 
+/// Changed objects a differential pull stats one by one. A larger diff is
+/// fetched by listing instead, which returns a page of objects per request.
+const MAX_DIFF_OBJECTS: usize = 1000;
+
 pub struct SyncConfig {
     pub parallelism: usize,
-    pub show_progress: bool,
+    /// Leave file modes and modification times out of the sync. When off,
+    /// each uploaded file's are recorded in its user metadata and given back
+    /// to downloaded files, and the mode is kept in the index entry. Listings
+    /// then ask for user metadata, so no object needs stat'ing on its own.
     pub ignore_permissions: bool,
+    /// Re-stat listed objects the index already tracks, instead of trusting the listing
+    pub verify_remote: bool,
+    /// Upload rate limit in bytes per second, shared by all parallel uploads
+    pub max_upload_bandwidth: Option<u64>,
+    /// Download rate limit in bytes per second, shared by all parallel downloads
+    pub max_download_bandwidth: Option<u64>,
+    /// Include/exclude rules applied to both local files and remote objects
+    pub filter: PathFilter,
+    /// Delete tracked local files whose objects were removed from the branch.
+    /// When off they are left on disk and stay tracked.
+    pub delete_extraneous: bool,
+    /// Shared object cache directory. Downloads go through it, so an object
+    /// another checkout already fetched is not downloaded again.
+    pub object_cache: Option<PathBuf>,
+    /// How files are materialized from `object_cache`
+    pub link_strategy: LinkStrategy,
+    /// Ignore local files more than this many directories deep
+    pub max_depth: Option<usize>,
+    /// Descend into symlinked directories and junctions, skipping loops
+    pub follow_links: bool,
+    /// Stop once this many transfers have failed, cancelling the ones in
+    /// flight and skipping the rest; `Some(1)` fails fast. Unlimited if unset.
+    pub max_errors: Option<usize>,
+    /// Compress uploaded files. Downloads decompress any object whose
+    /// metadata names its encoding, whatever this is set to.
+    pub compression: Option<Compression>,
+    /// Record block checksums of files at least this large. A touched file
+    /// whose object was uploaded in parts, and so has no checksum to compare
+    /// with, is then only pushed when one of its blocks changed.
+    pub delta_threshold: Option<u64>,
+    /// How often, and after how long, a transfer that failed for a
+    /// transient reason is attempted again. This is on top of the client's
+    /// own `RetryPolicy`, which retries each request first, so a transfer
+    /// may be sent up to `(client retries + 1) * (retries + 1)` times.
+    pub retry: RetryPolicy,
 }
 
 impl Default for SyncConfig {
     fn default() -> Self {
         Self {
             parallelism: 10,
-            show_progress: true,
             ignore_permissions: true,
+            verify_remote: false,
+            max_upload_bandwidth: None,
+            max_download_bandwidth: None,
+            filter: PathFilter::default(),
+            delete_extraneous: false,
+            object_cache: None,
+            link_strategy: LinkStrategy::default(),
+            max_depth: None,
+            follow_links: false,
+            max_errors: None,
+            compression: None,
+            delta_threshold: None,
+            retry: RetryPolicy::exponential(3),
         }
     }
 }
 
+/// What processing a change did.
+enum Outcome {
+    /// Transferred or deleted, moving this many bytes
+    Applied(IndexEntry, u64),
+    /// An upload skipped because the remote object has the same content
+    Unchanged(IndexEntry),
+}
+
+/// Which side's changes a run applies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SyncMode {
+    Both,
+    Push,
+    Pull,
+}
+
+#[derive(Clone, Default)]
+struct Limiters {
+    upload: Option<Arc<BandwidthLimiter>>,
+    download: Option<Arc<BandwidthLimiter>>,
+}
+
 pub struct SyncManager {
-    client: LakeFSClient,
+    client: Arc<dyn LakeFSApi>,
     config: SyncConfig,
+    limiters: Limiters,
+    reporter: Arc<dyn ProgressReporter>,
+    cancel: CancellationToken,
+    metrics: Option<Arc<SyncMetrics>>,
+    cache: Option<Arc<ObjectCache>>,
 }
 
 impl SyncManager {
-    pub fn new(client: LakeFSClient, config: SyncConfig) -> Self {
-        Self { client, config }
+    pub fn new(client: Arc<dyn LakeFSApi>, config: SyncConfig) -> Self {
+        let limiters = Limiters {
+            upload: config.max_upload_bandwidth.map(|rate| Arc::new(BandwidthLimiter::new(rate))),
+            download: config.max_download_bandwidth.map(|rate| Arc::new(BandwidthLimiter::new(rate))),
+        };
+        let cache = config
+            .object_cache
+            .as_ref()
+            .map(|root| Arc::new(ObjectCache::new(root, config.link_strategy)));
+        Self { client, config, limiters, reporter: Arc::new(NoopProgress), cancel: CancellationToken::new(), metrics: None, cache }
+    }
+    
+    /// Report transfer progress to `reporter`; none is reported by default.
+    pub fn with_progress(mut self, reporter: Arc<dyn ProgressReporter>) -> Self {
+        self.reporter = reporter;
+        self
+    }
+    
+    /// Stop in-flight transfers when `cancel` fires. Completed transfers are
+    /// still recorded in the index and `sync` returns `Error::Cancelled`.
+    pub fn with_cancellation(mut self, cancel: CancellationToken) -> Self {
+        self.cancel = cancel;
+        self
+    }
+    
+    /// Record transfer counts, bytes and durations in `metrics`.
+    pub fn with_metrics(mut self, metrics: Arc<SyncMetrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
     }
     
     pub async fn sync(
@@ -41,6 +164,181 @@ impl SyncManager {
         local_path: &Path,
         remote: &LakeFSUri,
     ) -> Result<SyncResult> {
+        self.run(local_path, remote, SyncMode::Both, &self.reporter).await
+    }
+    
+    /// Sync as [`sync`](Self::sync) does, reporting progress as a stream of
+    /// events instead of to the manager's reporter. The sync runs while the
+    /// stream is polled and its result is the `Finished` event.
+    pub fn sync_with_events<'a>(
+        &'a self,
+        local_path: &'a Path,
+        remote: &'a LakeFSUri,
+    ) -> impl Stream<Item = SyncEvent> + 'a {
+        self.run_with_events(local_path, remote, SyncMode::Both)
+    }
+    
+    /// Upload local changes to the branch's staging area without downloading
+    /// remote changes or committing. The index keeps its head commit, so
+    /// several pushes can be made before a single commit.
+    pub async fn push(
+        &self,
+        local_path: &Path,
+        remote: &LakeFSUri,
+    ) -> Result<SyncResult> {
+        self.run(local_path, remote, SyncMode::Push, &self.reporter).await
+    }
+    
+    /// [`push`](Self::push), reporting progress as
+    /// [`sync_with_events`](Self::sync_with_events) does.
+    pub fn push_with_events<'a>(
+        &'a self,
+        local_path: &'a Path,
+        remote: &'a LakeFSUri,
+    ) -> impl Stream<Item = SyncEvent> + 'a {
+        self.run_with_events(local_path, remote, SyncMode::Push)
+    }
+    
+    /// Apply remote changes without uploading anything. Local edits are left
+    /// alone and tracked files deleted locally are downloaded again. This is
+    /// the only way to update a snapshot checkout, whose head becomes
+    /// `remote.reference`.
+    pub async fn pull(
+        &self,
+        local_path: &Path,
+        remote: &LakeFSUri,
+    ) -> Result<SyncResult> {
+        self.run(local_path, remote, SyncMode::Pull, &self.reporter).await
+    }
+    
+    /// [`pull`](Self::pull), reporting progress as
+    /// [`sync_with_events`](Self::sync_with_events) does.
+    pub fn pull_with_events<'a>(
+        &'a self,
+        local_path: &'a Path,
+        remote: &'a LakeFSUri,
+    ) -> impl Stream<Item = SyncEvent> + 'a {
+        self.run_with_events(local_path, remote, SyncMode::Pull)
+    }
+    
+    /// Check, before committing, that the uncommitted changes on `remote`'s
+    /// branch are the ones the checkout at `local_path` staged: every path
+    /// the diff lists, and every path `pushed` uploaded or deleted, must be
+    /// on the branch as the index records it. Returns the paths that are
+    /// not, which another writer changed.
+    pub async fn verify_staged(
+        &self,
+        local_path: &Path,
+        remote: &LakeFSUri,
+        pushed: &SyncResult,
+    ) -> Result<Vec<StagedMismatch>> {
+        let index = LocalIndex::load(local_path)?;
+        let prefix = remote.path.as_deref();
+        let mut paths: BTreeSet<String> = pushed.staged_paths().map(str::to_string).collect();
+        let mut params = PaginationParams {
+            prefix: prefix.map(|prefix| paths::to_remote(Some(prefix), "")),
+            ..Default::default()
+        };
+        loop {
+            let response = self.client.diff_uncommitted(&remote.repository, &remote.reference, &params).await?;
+            // The diff lists full keys; the index and the push use keys
+            // under the prefix
+            paths.extend(
+                response.results
+                    .iter()
+                    .filter(|diff| diff.path_type == PathType::Object)
+                    .filter_map(|diff| paths::from_remote(prefix, &diff.path))
+                    .map(str::to_string),
+            );
+            
+            match response.pagination.next_offset {
+                Some(next) if response.pagination.has_more => params.after = Some(next),
+                _ => break,
+            }
+        }
+        
+        let keys: Vec<String> = paths.iter().map(|path| paths::to_remote(prefix, path)).collect();
+        let results = self.client.stat_objects(
+            &remote.repository,
+            &remote.reference,
+            &keys,
+            self.config.parallelism,
+        ).await;
+        let mut mismatches = Vec::new();
+        for (path, (_, result)) in paths.into_iter().zip(results) {
+            let actual = match result {
+                Ok(stats) => Some(stats.checksum),
+                Err(lakefs_api::Error::NotFound(_)) => None,
+                Err(e) => return Err(e.into()),
+            };
+            let expected = index.entries.get(&path).map(|entry| entry.checksum.clone());
+            if expected != actual {
+                mismatches.push(StagedMismatch { path, expected, actual });
+            }
+        }
+        mismatches.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(mismatches)
+    }
+    
+    /// Undo the changes `pushed` staged on `remote`'s branch, except on the
+    /// paths in `mismatches`, which hold another writer's changes now, and
+    /// give every path it staged back the index entry `before` the push
+    /// had, so the next push stages the local changes again. Returns how
+    /// many were reverted.
+    pub async fn revert_staged(
+        &self,
+        local_path: &Path,
+        remote: &LakeFSUri,
+        pushed: &SyncResult,
+        before: &LocalIndex,
+        mismatches: &[StagedMismatch],
+    ) -> Result<usize> {
+        let skip: HashSet<&str> = mismatches.iter().map(|mismatch| mismatch.path.as_str()).collect();
+        let mut index = LocalIndex::load(local_path)?;
+        let mut reverted = 0;
+        for path in pushed.staged_paths() {
+            if !skip.contains(path) {
+                let key = paths::to_remote(remote.path.as_deref(), path);
+                self.client.reset_object(&remote.repository, &remote.reference, &key).await?;
+                reverted += 1;
+            }
+            match before.entries.get(path) {
+                Some(entry) => index.add_entry(path.to_string(), entry.clone()),
+                None => {
+                    index.remove_entry(path);
+                }
+            }
+        }
+        index.save(local_path)?;
+        ListingCache::new(local_path).clear()?;
+        Ok(reverted)
+    }
+    
+    fn run_with_events<'a>(
+        &'a self,
+        local_path: &'a Path,
+        remote: &'a LakeFSUri,
+        mode: SyncMode,
+    ) -> impl Stream<Item = SyncEvent> + 'a {
+        let (sender, receiver) = futures::channel::mpsc::unbounded();
+        let reporter: Arc<dyn ProgressReporter> = Arc::new(EventReporter::new(sender.clone()));
+        let finished = async move {
+            let result = self.run(local_path, remote, mode, &reporter).await;
+            let _ = sender.unbounded_send(SyncEvent::Finished(result));
+            None
+        };
+        futures::stream::select(receiver, futures::stream::once(finished).filter_map(futures::future::ready))
+    }
+    
+    async fn run(
+        &self,
+        local_path: &Path,
+        remote: &LakeFSUri,
+        mode: SyncMode,
+        reporter: &Arc<dyn ProgressReporter>,
+    ) -> Result<SyncResult> {
+        let sync_started = Instant::now();
+        
         // Load or create index
         let mut index = match LocalIndex::load(local_path) {
             Ok(idx) => idx,
@@ -50,48 +348,127 @@ impl SyncManager {
                 LocalIndex::new(&remote.repository, &remote.reference, &branch.commit_id)
             }
         };
+        if index.snapshot && mode != SyncMode::Pull {
+            return Err(Error::Sync(format!(
+                "{} is a read-only snapshot of commit {}",
+                local_path.display(),
+                index.head_commit
+            )));
+        }
+        
+        // Resolved before looking at the remote, so changes made while the
+        // pull runs are still seen by the next one
+        let pulled_commit = match mode {
+            SyncMode::Pull if index.snapshot => Some(remote.reference.clone()),
+            SyncMode::Pull => Some(self.client.get_branch(&remote.repository, &remote.reference).await?.commit_id),
+            _ => None,
+        };
+        let diffed = match (mode, index.pulled_commit.as_deref()) {
+            (SyncMode::Pull, Some(base)) => self.diff_remote_objects(remote, &index, base, local_path).await?,
+            _ => None,
+        };
+        let differential = diffed.is_some();
         
         // Get remote objects
-        let remote_objects = self.list_remote_objects(remote).await?;
+        let (mut remote_objects, diff_removed) = match diffed {
+            Some(diffed) => diffed,
+            None => (self.list_remote_objects(remote).await?, HashSet::new()),
+        };
+        remote_objects.retain(|obj| self.config.filter.is_included(&obj.path));
+        if mode != SyncMode::Push {
+            for obj in &remote_objects {
+                paths::check_key(&obj.path)?;
+            }
+        }
+        if mode != SyncMode::Push && paths::is_case_insensitive(local_path)? {
+            // Both objects would be written to the same file, one silently
+            // replacing the other
+            let mut keys: Vec<&str> = remote_objects.iter().map(|obj| obj.path.as_str()).collect();
+            if differential {
+                // A diff leaves out the objects already tracked
+                keys.extend(index.entries.keys().filter(|path| !diff_removed.contains(*path)).map(String::as_str));
+            }
+            let collisions = paths::case_collisions(keys);
+            if !collisions.is_empty() {
+                return Err(Error::CaseCollision(collisions));
+            }
+        }
+        // Objects from a diff were just fetched one by one
+        if self.config.verify_remote && !differential {
+            remote_objects = self.verify_remote_objects(remote, &index, remote_objects).await?;
+        }
         
-        // Detect changes
-        let detector = ChangeDetector::new(local_path.to_path_buf());
-        let changes = detector.detect_changes(&index, remote_objects)?;
-        
-        // Progress bar
-        let pb = if self.config.show_progress {
-            let pb = ProgressBar::new(changes.len() as u64);
-            pb.set_style(
-                ProgressStyle::default_bar()
-                    .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({eta})")
-                    .unwrap()
-                    .progress_chars("#>-"),
-            );
-            Some(pb)
+        // Tracked paths whose objects are gone from the remote
+        let remote_removed: HashSet<String> = if differential {
+            diff_removed
+        } else if self.config.delete_extraneous && mode != SyncMode::Push {
+            let listed: HashSet<&str> = remote_objects.iter().map(|obj| obj.path.as_str()).collect();
+            index.entries.keys().filter(|path| !listed.contains(path.as_str())).cloned().collect()
         } else {
-            None
+            HashSet::new()
         };
         
+        // Detect changes
+        let detector = ChangeDetector::new(local_path.to_path_buf())
+            .with_filter(self.config.filter.clone())
+            .with_max_depth(self.config.max_depth)
+            .follow_links(self.config.follow_links);
+        let ChangeReport { mut changes, errors: walk_errors } = detector.detect_changes(&index, remote_objects)?;
+        match mode {
+            SyncMode::Push => changes = Self::local_changes(changes, local_path),
+            SyncMode::Pull => changes = Self::remote_changes(changes),
+            SyncMode::Both => {}
+        }
+        if self.config.delete_extraneous && mode != SyncMode::Push {
+            let extraneous = self.extraneous_changes(&index, &remote_removed, &changes, local_path);
+            changes.extend(extraneous);
+        }
+        
+        // Progress totals
+        let mut total_bytes = 0;
+        for change in &changes {
+            total_bytes += Self::transfer_size(change).await;
+        }
+        reporter.set_total(changes.len() as u64, total_bytes);
+        
         // Process changes
         let semaphore = Arc::new(Semaphore::new(self.config.parallelism));
-        let mut tasks = Vec::new();
+        let mut tasks = FuturesUnordered::new();
+        // Cancelled on user request or when too many transfers fail
+        let abort = self.cancel.child_token();
         
         for change in changes {
             let client = self.client.clone();
             let remote = remote.clone();
             let local_path = local_path.to_path_buf();
             let sem = semaphore.clone();
-            let pb = pb.clone();
+            let limiters = self.limiters.clone();
+            let reporter = reporter.clone();
+            let cache = self.cache.clone();
+            let compression = self.config.compression;
+            let ignore_permissions = self.config.ignore_permissions;
+            let retry = self.config.retry.clone();
+            let delta_threshold = self.config.delta_threshold;
+            
+            let cancel = abort.clone();
             
             let task = tokio::spawn(async move {
-                let _permit = sem.acquire().await.unwrap();
-                let result = Self::process_change(&client, &change, &local_path, &remote).await;
+                let _permit = tokio::select! {
+                    _ = cancel.cancelled() => return (change, Err(Error::Cancelled), Duration::ZERO),
+                    permit = sem.acquire() => permit.unwrap(),
+                };
                 
-                if let Some(pb) = pb {
-                    pb.inc(1);
-                }
+                let started = Instant::now();
+                let result = tokio::select! {
+                    biased;
+                    _ = cancel.cancelled() => {
+                        Self::discard_partial(&change, &local_path).await;
+                        Err(Error::Cancelled)
+                    }
+                    result = Self::process_change(client.as_ref(), &change, &local_path, &remote, &limiters, &reporter, cache.as_deref(), compression, ignore_permissions, delta_threshold, &retry) => result,
+                };
                 
-                (change, result)
+                (change, result, started.elapsed())
             });
             
             tasks.push(task);
@@ -101,13 +478,31 @@ impl SyncManager {
         let mut uploaded = 0;
         let mut downloaded = 0;
         let mut removed = 0;
-        let mut errors = Vec::new();
+        let mut unchanged = 0;
+        let mut skipped = 0;
+        let mut failed = 0;
+        let mut errors = walk_errors;
+        let mut files = Vec::new();
         
-        for task in tasks {
-            let (change, result) = task.await.map_err(|e| Error::Sync(e.to_string()))?;
+        // In completion order, so the error limit takes effect promptly
+        while let Some(joined) = tasks.next().await {
+            let (change, result, duration) = joined.map_err(|e| Error::Sync(e.to_string()))?;
+            
+            let mut record = TransferRecord {
+                path: change.path.clone(),
+                direction: TransferDirection::of(&change),
+                bytes: 0,
+                duration,
+                error: None,
+            };
             
             match result {
-                Ok(entry) => {
+                Ok(Outcome::Unchanged(entry)) => {
+                    unchanged += 1;
+                    index.add_entry(change.path, entry);
+                }
+                Ok(Outcome::Applied(entry, bytes)) => {
+                    record.bytes = bytes;
                     match change.change_type {
                         ChangeType::Added | ChangeType::Modified => {
                             if change.local_path.is_some() {
@@ -123,102 +518,629 @@ impl SyncManager {
                         }
                     }
                 }
+                Err(Error::Cancelled) => {
+                    if !self.cancel.is_cancelled() {
+                        skipped += 1;
+                    }
+                    continue;
+                }
                 Err(e) => {
+                    reporter.file_failed(&change.path, &e);
+                    record.error = Some(e.to_string());
                     errors.push((change.path, e));
+                    failed += 1;
+                    if self.config.max_errors.is_some_and(|max| failed >= max) {
+                        abort.cancel();
+                    }
                 }
             }
+            
+            match &record.error {
+                Some(error) => tracing::warn!(path = %record.path, direction = ?record.direction, %error, "transfer failed"),
+                None => tracing::debug!(
+                    path = %record.path,
+                    direction = ?record.direction,
+                    bytes = record.bytes,
+                    duration_ms = record.duration.as_millis() as u64,
+                    "transferred"
+                ),
+            }
+            if let Some(metrics) = &self.metrics {
+                metrics.record_transfer(&record);
+            }
+            files.push(record);
         }
         
-        if let Some(pb) = pb {
-            pb.finish_with_message("Sync complete");
+        if self.cancel.is_cancelled() {
+            // Keep what completed so the next sync does not transfer it again
+            index.save(local_path)?;
+            reporter.finish("Sync cancelled");
+            return Err(Error::Cancelled);
+        }
+        
+        if skipped > 0 {
+            reporter.finish(&format!("Sync stopped after {} failed transfers", failed));
+        } else {
+            reporter.finish("Sync complete");
+        }
+        
+        // Objects excluded or not fetched this time must still be compared
+        // by the next pull
+        if errors.is_empty() && skipped == 0 && self.config.filter.is_empty() {
+            if let Some(commit) = pulled_commit {
+                index.pulled_commit = Some(commit);
+            }
+        }
+        
+        // The branch changed without its head moving
+        if mode != SyncMode::Pull && uploaded + removed > 0 {
+            ListingCache::new(local_path).clear()?;
         }
         
         // Update index
-        let branch = self.client.get_branch(&remote.repository, &remote.reference).await?;
-        index.update_head(&branch.commit_id);
+        match mode {
+            SyncMode::Push => {}
+            _ if index.snapshot => {
+                index.reference = remote.reference.clone();
+                index.update_head(&remote.reference);
+            }
+            _ => {
+                let branch = self.client.get_branch(&remote.repository, &remote.reference).await?;
+                index.update_head(&branch.commit_id);
+            }
+        }
         index.save(local_path)?;
         
+        let elapsed = sync_started.elapsed();
+        if let Some(metrics) = &self.metrics {
+            metrics.record_sync(elapsed);
+        }
+        tracing::debug!(
+            uploaded,
+            downloaded,
+            removed,
+            unchanged,
+            skipped,
+            errors = errors.len(),
+            duration_ms = elapsed.as_millis() as u64,
+            "sync finished"
+        );
+        
         Ok(SyncResult {
             uploaded,
             downloaded,
             removed,
+            unchanged,
+            skipped,
             errors,
+            files,
         })
     }
     
     async fn list_remote_objects(&self, remote: &LakeFSUri) -> Result<Vec<ObjectStats>> {
         let mut objects = Vec::new();
+        let mut params = PaginationParams {
+            prefix: remote.path.clone(),
+            after: None,
+            amount: None,
+            user_metadata: (!self.config.ignore_permissions).then_some(true),
+        };
+        
+        // Every page is needed: a missing object may be deleted locally
+        loop {
+            let response = self.client.list_objects_with(
+                &remote.repository,
+                &remote.reference,
+                &params,
+            ).await?;
+            objects.extend(response.results);
+            
+            match response.pagination.next_offset {
+                Some(next) if response.pagination.has_more => params.after = Some(next),
+                _ => break,
+            }
+        }
+        
+        Ok(objects)
+    }
+    
+    /// The objects that changed between `base`, the commit the last pull
+    /// fetched, and the remote, along with the tracked paths removed since.
+    /// A refs diff only sees commits, so objects staged on the branch are
+    /// added from its uncommitted diff. Tracked files missing from disk are
+    /// stat'd too, so they are downloaded again as after a listing. `None`
+    /// when the server cannot diff, for
+    /// example because `base` was garbage collected, or the diff is large
+    /// enough that listing is quicker.
+    async fn diff_remote_objects(
+        &self,
+        remote: &LakeFSUri,
+        index: &LocalIndex,
+        base: &str,
+        local_path: &Path,
+    ) -> Result<Option<(Vec<ObjectStats>, HashSet<String>)>> {
+        let mut changed = Vec::new();
+        let mut removed = HashSet::new();
+        let mut params = PaginationParams {
+            prefix: remote.path.clone(),
+            after: None,
+            amount: None,
+            ..Default::default()
+        };
+        
+        loop {
+            let response = match self.client.diff_with(&remote.repository, base, &remote.reference, &params).await {
+                Ok(response) => response,
+                Err(e) => {
+                    tracing::info!(base, error = %e, "diff unavailable, listing the remote instead");
+                    return Ok(None);
+                }
+            };
+            for diff in response.results {
+                if diff.path_type != PathType::Object || !self.config.filter.is_included(&diff.path) {
+                    continue;
+                }
+                match diff.diff_type {
+                    DiffType::Removed => {
+                        removed.insert(diff.path);
+                    }
+                    DiffType::Added | DiffType::Changed => changed.push(diff.path),
+                }
+            }
+            if changed.len() > MAX_DIFF_OBJECTS {
+                return Ok(None);
+            }
+            
+            match response.pagination.next_offset {
+                Some(next) if response.pagination.has_more => params.after = Some(next),
+                _ => break,
+            }
+        }
+        
+        // Staged changes of any kind are stat'd, which also settles whether
+        // an object the commits removed was staged again
+        if !index.snapshot {
+            params.after = None;
+            loop {
+                let response = match self.client.diff_uncommitted(&remote.repository, &remote.reference, &params).await {
+                    Ok(response) => response,
+                    Err(e) => {
+                        tracing::info!(error = %e, "uncommitted diff unavailable, listing the remote instead");
+                        return Ok(None);
+                    }
+                };
+                for diff in response.results {
+                    if diff.path_type != PathType::Object || !self.config.filter.is_included(&diff.path) {
+                        continue;
+                    }
+                    removed.remove(&diff.path);
+                    changed.push(diff.path);
+                }
+                if changed.len() > MAX_DIFF_OBJECTS {
+                    return Ok(None);
+                }
+                
+                match response.pagination.next_offset {
+                    Some(next) if response.pagination.has_more => params.after = Some(next),
+                    _ => break,
+                }
+            }
+            changed.sort();
+            changed.dedup();
+        }
+        
+        let changed_paths: HashSet<&str> = changed.iter().map(String::as_str).collect();
+        let missing: Vec<String> = index
+            .entries
+            .keys()
+            .filter(|path| self.config.filter.is_included(path))
+            .filter(|path| !changed_paths.contains(path.as_str()) && !removed.contains(*path))
+            .filter(|path| !paths::to_local(local_path, path).exists())
+            .cloned()
+            .collect();
+        changed.extend(missing);
         
-        let response = self.client.list_objects(
+        let mut objects = Vec::new();
+        let results = self.client.stat_objects(
             &remote.repository,
             &remote.reference,
-            remote.path.as_deref(),
-        ).await?;
+            &changed,
+            self.config.parallelism,
+        ).await;
+        for (path, result) in results {
+            match result {
+                Ok(stats) => objects.push(stats),
+                Err(lakefs_api::Error::NotFound(_)) => {
+                    removed.insert(path);
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
         
-        objects.extend(response.results);
+        Ok(Some((objects, removed)))
+    }
+    
+    /// Replace listing entries for tracked paths with fresh per-object stats.
+    /// Objects that vanished between listing and stat are dropped.
+    async fn verify_remote_objects(
+        &self,
+        remote: &LakeFSUri,
+        index: &LocalIndex,
+        remote_objects: Vec<ObjectStats>,
+    ) -> Result<Vec<ObjectStats>> {
+        let (tracked, mut verified): (Vec<_>, Vec<_>) = remote_objects
+            .into_iter()
+            .partition(|obj| index.get_entry(&obj.path).is_some());
         
-        // Handle pagination if needed
-        // TODO: Implement pagination handling
+        let paths: Vec<String> = tracked.into_iter().map(|obj| obj.path).collect();
+        let results = self.client.stat_objects(
+            &remote.repository,
+            &remote.reference,
+            &paths,
+            self.config.parallelism,
+        ).await;
         
-        Ok(objects)
+        for (_, result) in results {
+            match result {
+                Ok(stats) => verified.push(stats),
+                Err(lakefs_api::Error::NotFound(_)) => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+        
+        Ok(verified)
+    }
+    
+    /// Keep only changes made on the local side. A tracked file missing from
+    /// disk becomes a remote delete when the branch still has the object.
+    fn local_changes(changes: Vec<Change>, local_base: &Path) -> Vec<Change> {
+        changes
+            .into_iter()
+            .filter_map(|mut change| match change.change_type {
+                ChangeType::Removed => {
+                    if change.remote_stats.is_some() {
+                        change.local_path = Some(paths::to_local(local_base, &change.path));
+                    }
+                    Some(change)
+                }
+                _ => change.local_path.is_some().then_some(change),
+            })
+            .collect()
+    }
+    
+    /// Keep only changes coming from the remote. A tracked file missing from
+    /// disk is downloaded again while the remote still has it.
+    fn remote_changes(changes: Vec<Change>) -> Vec<Change> {
+        changes
+            .into_iter()
+            .filter_map(|mut change| match change.change_type {
+                ChangeType::Removed if change.remote_stats.is_some() => {
+                    change.change_type = ChangeType::Added;
+                    Some(change)
+                }
+                _ => change.local_path.is_none().then_some(change),
+            })
+            .collect()
     }
     
+    /// Local deletes for tracked, locally unchanged files whose objects are
+    /// gone from the branch.
+    fn extraneous_changes(
+        &self,
+        index: &LocalIndex,
+        remote_removed: &HashSet<String>,
+        changes: &[Change],
+        local_base: &Path,
+    ) -> Vec<Change> {
+        let changed: HashSet<&str> = changes.iter().map(|change| change.path.as_str()).collect();
+        index
+            .entries
+            .keys()
+            .filter(|path| self.config.filter.is_included(path))
+            .filter(|path| remote_removed.contains(*path) && !changed.contains(path.as_str()))
+            .filter(|path| paths::to_local(local_base, path).exists())
+            .map(|path| Change {
+                path: path.clone(),
+                change_type: ChangeType::Removed,
+                local_path: None,
+                remote_stats: None,
+            })
+            .collect()
+    }
+    
+    /// Remove the temp file of an interrupted download.
+    async fn discard_partial(change: &Change, local_base: &Path) {
+        if TransferDirection::of(change) == TransferDirection::Download {
+            let _ = fs::remove_file(transfer::temp_path(&paths::to_local(local_base, &change.path))).await;
+        }
+    }
+    
+    /// Bytes a change will move over the network, for progress totals.
+    async fn transfer_size(change: &Change) -> u64 {
+        match change.change_type {
+            ChangeType::Removed => 0,
+            ChangeType::Added | ChangeType::Modified => match (&change.local_path, &change.remote_stats) {
+                (Some(local_path), _) => fs::metadata(local_path).await.map(|m| m.len()).unwrap_or(0),
+                (None, Some(stats)) => stats.size_bytes.max(0) as u64,
+                (None, None) => 0,
+            },
+        }
+    }
+    
+    /// A failed precondition means someone else changed the object after it
+    /// was listed; report that rather than the bare API error.
+    fn raced(error: Error, path: &str) -> Error {
+        match error {
+            Error::Api(lakefs_api::Error::PreconditionFailed(_)) => Error::ConcurrentModification(path.to_string()),
+            error => error,
+        }
+    }
+    
+    /// Digest of the file at `path` of the same kind as `remote`, or its MD5
+    /// (the usual ETag) when `remote` cannot be recomputed.
+    async fn local_checksum(path: &Path, remote: &Checksum) -> Result<Checksum> {
+        let path = path.to_path_buf();
+        let hasher = tokio::task::spawn_blocking(move || Hasher::from_file(&path))
+            .await
+            .map_err(|e| Error::Sync(e.to_string()))??;
+        let [md5, sha256] = hasher.finish();
+        Ok(if remote.matches(&sha256).is_some() { sha256 } else { md5 })
+    }
+    
+    /// The mode the index keeps for the file at `path`, unless permissions
+    /// are ignored.
+    fn permissions(path: &Path, ignore_permissions: bool) -> Result<Option<u32>> {
+        match ignore_permissions {
+            true => Ok(None),
+            false => attributes::mode(path),
+        }
+    }
+    
+    /// Size and mtime of the file at `path`. The index records these for
+    /// compressed objects, whose own describe the compressed content, so the
+    /// file only counts as changed once it is written to again.
+    async fn local_size_and_mtime(path: &Path) -> Result<(u64, DateTime<Utc>)> {
+        let metadata = fs::metadata(path).await?;
+        Ok((metadata.len(), metadata.modified()?.into()))
+    }
+    
+    /// After an upload failed for a transient reason, look at the object it
+    /// was writing: the request may have gone through before the connection
+    /// dropped, and a retry would then fail its own precondition. An object
+    /// with the file's content counts as uploaded, one still as listed as
+    /// worth another attempt (`None`), and any other as a conflict.
+    async fn check_landed(client: &dyn LakeFSApi, change: &Change, remote: &LakeFSUri) -> Result<Option<Outcome>> {
+        let Some(local_path) = &change.local_path else {
+            return Ok(None);
+        };
+        if !matches!(change.change_type, ChangeType::Added | ChangeType::Modified) {
+            return Ok(None);
+        }
+        let remote_path = paths::to_remote(remote.path.as_deref(), &change.path);
+        let current = match client.get_object(&remote.repository, &remote.reference, &remote_path).await {
+            Ok(stats) => stats,
+            Err(lakefs_api::Error::NotFound(_)) => return Ok(None),
+            // Nothing to tell from; the retry's precondition still guards
+            Err(_) => return Ok(None),
+        };
+        if change.remote_stats.as_ref().is_some_and(|listed| listed.checksum == current.checksum) {
+            return Ok(None);
+        }
+        
+        let path = local_path.clone();
+        let hasher = tokio::task::spawn_blocking(move || Hasher::from_file(&path))
+            .await
+            .map_err(|e| Error::Sync(e.to_string()))??;
+        if hasher.matches(&current.checksum) != Some(true) {
+            return Err(Error::ConcurrentModification(change.path.clone()));
+        }
+        tracing::debug!(path = %change.path, "upload went through before the connection failed");
+        let bytes = fs::metadata(local_path).await?.len();
+        Ok(Some(Outcome::Applied(IndexEntry {
+            path: change.path.clone(),
+            checksum: current.checksum,
+            size: current.size_bytes as u64,
+            mtime: current.mtime,
+            permissions: None,
+            blocks: Vec::new(),
+        }, bytes)))
+    }
+    
+    /// Block checksums of the file at `path` of `size` bytes, if it is at
+    /// least `threshold` bytes large.
+    async fn block_checksums(path: &Path, size: u64, threshold: Option<u64>) -> Result<Vec<String>> {
+        if threshold.is_none_or(|threshold| size < threshold) {
+            return Ok(Vec::new());
+        }
+        let path = path.to_path_buf();
+        tokio::task::spawn_blocking(move || delta::block_checksums(&path))
+            .await
+            .map_err(|e| Error::Sync(e.to_string()))?
+    }
+    
+    /// Apply `change`, attempting it again after a backoff while it fails
+    /// for a transient reason and `retry` allows.
+    #[allow(clippy::too_many_arguments)]
     async fn process_change(
-        client: &LakeFSClient,
+        client: &dyn LakeFSApi,
+        change: &Change,
+        local_base: &Path,
+        remote: &LakeFSUri,
+        limiters: &Limiters,
+        reporter: &Arc<dyn ProgressReporter>,
+        cache: Option<&ObjectCache>,
+        compression: Option<Compression>,
+        ignore_permissions: bool,
+        delta_threshold: Option<u64>,
+        retry: &RetryPolicy,
+    ) -> Result<Outcome> {
+        let mut attempt = 0;
+        loop {
+            let result = Self::apply_change(client, change, local_base, remote, limiters, reporter, cache, compression, ignore_permissions, delta_threshold).await;
+            match result {
+                Err(error) if attempt < retry.max_retries && error.is_transient() => {
+                    if let Some(outcome) = Self::check_landed(client, change, remote).await? {
+                        return Ok(outcome);
+                    }
+                    let backoff = retry.backoff(attempt);
+                    tracing::debug!(path = %change.path, attempt = attempt + 1, %error, ?backoff, "retrying transfer");
+                    Self::discard_partial(change, local_base).await;
+                    tokio::time::sleep(backoff).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+    
+    #[allow(clippy::too_many_arguments)]
+    async fn apply_change(
+        client: &dyn LakeFSApi,
         change: &Change,
         local_base: &Path,
         remote: &LakeFSUri,
-    ) -> Result<IndexEntry> {
+        limiters: &Limiters,
+        reporter: &Arc<dyn ProgressReporter>,
+        cache: Option<&ObjectCache>,
+        compression: Option<Compression>,
+        ignore_permissions: bool,
+        delta_threshold: Option<u64>,
+    ) -> Result<Outcome> {
         match change.change_type {
             ChangeType::Added | ChangeType::Modified => {
                 if let Some(local_path) = &change.local_path {
                     // Upload file
-                    let data = fs::read(local_path).await?;
-                    let _metadata = fs::metadata(local_path).await?;
+                    let bytes = fs::metadata(local_path).await?.len();
+                    
+                    // Such as after cloning into a directory that already
+                    // holds the files: track them without uploading again
+                    if let Some(remote_stats) = &change.remote_stats {
+                        let path = local_path.clone();
+                        let hasher = tokio::task::spawn_blocking(move || Hasher::from_file(&path))
+                            .await
+                            .map_err(|e| Error::Sync(e.to_string()))??;
+                        if hasher.matches(&remote_stats.checksum) == Some(true) {
+                            return Ok(Outcome::Unchanged(IndexEntry {
+                                path: change.path.clone(),
+                                checksum: remote_stats.checksum.clone(),
+                                size: bytes,
+                                mtime: remote_stats.mtime,
+                                permissions: Self::permissions(local_path, ignore_permissions)?,
+                                blocks: Self::block_checksums(local_path, bytes, delta_threshold).await?,
+                            }));
+                        }
+                    }
+                    
+                    let blocks = Self::block_checksums(local_path, bytes, delta_threshold).await?;
                     
-                    let remote_path = remote.path.as_ref().map_or(
-                        change.path.clone(),
-                        |p| format!("{}/{}", p, change.path),
-                    );
+                    let remote_path = paths::to_remote(remote.path.as_deref(), &change.path);
+                    // Only replace the object as it was listed
+                    let precondition = match &change.remote_stats {
+                        Some(remote_stats) => Precondition::IfMatch(remote_stats.checksum.clone()),
+                        None => Precondition::IfAbsent,
+                    };
+                    let mut options = UploadOptions { precondition: Some(precondition), ..Default::default() };
+                    if !ignore_permissions {
+                        attributes::record(local_path, &mut options.metadata)?;
+                    }
                     
-                    let stats = client.upload_object(
+                    let stats = transfer::upload_file(
+                        client,
                         &remote.repository,
                         &remote.reference,
                         &remote_path,
-                        Bytes::from(data),
-                    ).await?;
+                        local_path,
+                        &change.path,
+                        &options,
+                        compression,
+                        limiters.upload.clone(),
+                        reporter,
+                    ).await.map_err(|e| Self::raced(e, &change.path))?;
                     
-                    Ok(IndexEntry {
+                    let (size, mtime) = match compression {
+                        Some(_) => Self::local_size_and_mtime(local_path).await?,
+                        None => (stats.size_bytes as u64, stats.mtime),
+                    };
+                    Ok(Outcome::Applied(IndexEntry {
                         path: change.path.clone(),
                         checksum: stats.checksum,
-                        size: stats.size_bytes as u64,
-                        mtime: stats.mtime,
-                        permissions: None,
-                    })
+                        size,
+                        mtime,
+                        permissions: Self::permissions(local_path, ignore_permissions)?,
+                        blocks,
+                    }, bytes))
                 } else if let Some(remote_stats) = &change.remote_stats {
                     // Download file
-                    let local_path = local_base.join(&change.path);
+                    let local_path = paths::to_local(local_base, &change.path);
                     
                     // Create parent directory if needed
                     if let Some(parent) = local_path.parent() {
                         fs::create_dir_all(parent).await?;
                     }
                     
-                    let data = client.download_object(
-                        &remote.repository,
-                        &remote.reference,
-                        &remote_stats.path,
-                    ).await?;
-                    
-                    fs::write(&local_path, &data).await?;
+                    let bytes = match cache {
+                        Some(cache) => cache.fetch(
+                            client,
+                            &remote.repository,
+                            &remote.reference,
+                            remote_stats,
+                            &local_path,
+                            &change.path,
+                            limiters.download.clone(),
+                            reporter,
+                        ).await?,
+                        None => {
+                            // The server skips the transfer when the file
+                            // already there has the object's checksum
+                            let current = match fs::metadata(&local_path).await {
+                                Ok(_) => Some(Self::local_checksum(&local_path, &remote_stats.checksum).await?),
+                                Err(_) => None,
+                            };
+                            let downloaded = transfer::download_if_changed(
+                                client,
+                                &remote.repository,
+                                &remote.reference,
+                                remote_stats,
+                                &local_path,
+                                current.as_ref(),
+                                &change.path,
+                                limiters.download.clone(),
+                                reporter,
+                            ).await?;
+                            let Some(bytes) = downloaded else {
+                                let restored = match ignore_permissions {
+                                    false => attributes::restore(&local_path, remote_stats)?,
+                                    true => None,
+                                };
+                                return Ok(Outcome::Unchanged(IndexEntry {
+                                    path: change.path.clone(),
+                                    checksum: remote_stats.checksum.clone(),
+                                    size: remote_stats.size_bytes as u64,
+                                    mtime: restored.unwrap_or(remote_stats.mtime),
+                                    permissions: Self::permissions(&local_path, ignore_permissions)?,
+                                    blocks: Self::block_checksums(&local_path, remote_stats.size_bytes as u64, delta_threshold).await?,
+                                }));
+                            };
+                            bytes
+                        }
+                    };
                     
-                    Ok(IndexEntry {
+                    let restored = match ignore_permissions {
+                        false => attributes::restore(&local_path, remote_stats)?,
+                        true => None,
+                    };
+                    let (size, mtime) = match Compression::of(remote_stats)? {
+                        Some(_) => Self::local_size_and_mtime(&local_path).await?,
+                        None => (remote_stats.size_bytes as u64, restored.unwrap_or(remote_stats.mtime)),
+                    };
+                    Ok(Outcome::Applied(IndexEntry {
                         path: change.path.clone(),
                         checksum: remote_stats.checksum.clone(),
-                        size: remote_stats.size_bytes as u64,
-                        mtime: remote_stats.mtime,
-                        permissions: None,
-                    })
+                        size,
+                        mtime,
+                        permissions: Self::permissions(&local_path, ignore_permissions)?,
+                        blocks: Self::block_checksums(&local_path, size, delta_threshold).await?,
+                    }, bytes))
                 } else {
                     Err(Error::Sync("No source for change".into()))
                 }
@@ -226,40 +1148,976 @@ impl SyncManager {
             ChangeType::Removed => {
                 if change.local_path.is_none() {
                     // Remove local file
-                    let local_path = local_base.join(&change.path);
+                    let local_path = paths::to_local(local_base, &change.path);
                     if local_path.exists() {
                         fs::remove_file(&local_path).await?;
                     }
                 } else {
                     // Remove remote file
-                    let remote_path = remote.path.as_ref().map_or(
-                        change.path.clone(),
-                        |p| format!("{}/{}", p, change.path),
-                    );
+                    let remote_path = paths::to_remote(remote.path.as_deref(), &change.path);
+                    let precondition = change.remote_stats
+                        .as_ref()
+                        .map(|remote_stats| Precondition::IfMatch(remote_stats.checksum.clone()));
                     
-                    client.delete_object(
+                    client.delete_object_if(
                         &remote.repository,
                         &remote.reference,
                         &remote_path,
-                    ).await?;
+                        precondition.as_ref(),
+                    ).await.map_err(|e| Self::raced(e.into(), &change.path))?;
                 }
                 
-                Ok(IndexEntry {
+                Ok(Outcome::Applied(IndexEntry {
                     path: change.path.clone(),
-                    checksum: String::new(),
+                    checksum: Checksum::default(),
                     size: 0,
                     mtime: Utc::now(),
                     permissions: None,
-                })
+                    blocks: Vec::new(),
+                }, 0))
             }
         }
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransferDirection {
+    Upload,
+    Download,
+    DeleteLocal,
+    DeleteRemote,
+}
+
+impl TransferDirection {
+    /// Direction `SyncManager::process_change` will take for `change`.
+    fn of(change: &Change) -> Self {
+        match (&change.change_type, change.local_path.is_some()) {
+            (ChangeType::Removed, true) => Self::DeleteRemote,
+            (ChangeType::Removed, false) => Self::DeleteLocal,
+            (_, true) => Self::Upload,
+            (_, false) => Self::Download,
+        }
+    }
+}
+
+/// Outcome of a single file in a sync.
+#[derive(Debug, Clone, Serialize)]
+pub struct TransferRecord {
+    pub path: String,
+    pub direction: TransferDirection,
+    pub bytes: u64,
+    #[serde(rename = "duration_ms", serialize_with = "serialize_millis")]
+    pub duration: Duration,
+    pub error: Option<String>,
+}
+
+/// A path whose state on the branch differs from what the checkout staged,
+/// as found by [`SyncManager::verify_staged`]. `None` means no object.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StagedMismatch {
+    pub path: String,
+    pub expected: Option<Checksum>,
+    pub actual: Option<Checksum>,
+}
+
+impl std::fmt::Display for StagedMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (&self.expected, &self.actual) {
+            (None, Some(_)) => write!(f, "{}: staged by someone else", self.path),
+            (Some(_), None) => write!(f, "{}: removed by someone else", self.path),
+            _ => write!(f, "{}: changed by someone else", self.path),
+        }
+    }
+}
+
+fn serialize_millis<S: Serializer>(duration: &Duration, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+    serializer.serialize_u64(duration.as_millis() as u64)
+}
+
 #[derive(Debug)]
 pub struct SyncResult {
     pub uploaded: usize,
     pub downloaded: usize,
     pub removed: usize,
+    /// Local files left alone because the remote already had their content
+    pub unchanged: usize,
+    /// Changes cancelled or never started because `max_errors` was reached
+    pub skipped: usize,
     pub errors: Vec<(String, Error)>,
-}
\ No newline at end of file
+    pub files: Vec<TransferRecord>,
+}
+
+impl SyncResult {
+    /// Paths whose changes this sync uploaded to or deleted from the branch.
+    pub fn staged_paths(&self) -> impl Iterator<Item = &str> {
+        self.files
+            .iter()
+            .filter(|f| matches!(f.direction, TransferDirection::Upload | TransferDirection::DeleteRemote) && f.error.is_none())
+            .map(|f| f.path.as_str())
+    }
+    
+    /// Machine-readable transfer report with totals and per-file records.
+    pub fn summary_json(&self) -> serde_json::Value {
+        let bytes_uploaded: u64 = self.files.iter()
+            .filter(|f| f.direction == TransferDirection::Upload && f.error.is_none())
+            .map(|f| f.bytes)
+            .sum();
+        let bytes_downloaded: u64 = self.files.iter()
+            .filter(|f| f.direction == TransferDirection::Download && f.error.is_none())
+            .map(|f| f.bytes)
+            .sum();
+        
+        serde_json::json!({
+            "uploaded": self.uploaded,
+            "downloaded": self.downloaded,
+            "removed": self.removed,
+            "unchanged": self.unchanged,
+            "failed": self.errors.len(),
+            "skipped": self.skipped,
+            "bytes_uploaded": bytes_uploaded,
+            "bytes_downloaded": bytes_downloaded,
+            "files": self.files,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lakefs_test_utils::fixtures::{test_branch, test_object_stats};
+    use lakefs_test_utils::scenario::MockLakeFS;
+    use tempfile::TempDir;
+    use md5::Digest;
+    use wiremock::matchers::{body_json, header, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_sync_downloads_remote_objects() {
+        let server = MockLakeFS::new()
+            .with_repo("repo")
+            .with_branch("main")
+            .with_object("data/a.csv", "a,b\n1,2\n")
+            .start()
+            .await;
+        
+        let client = Arc::new(lakefs_api::LakeFSClient::new(server.uri(), "Bearer test-token"));
+        let config = SyncConfig::default();
+        let temp_dir = TempDir::new().unwrap();
+        
+        let metrics = Arc::new(SyncMetrics::new());
+        let manager = SyncManager::new(client, config).with_metrics(metrics.clone());
+        let result = manager.sync(temp_dir.path(), &LakeFSUri::new("repo", "main")).await.unwrap();
+        
+        assert_eq!(metrics.syncs(), 1);
+        assert_eq!(metrics.objects(TransferDirection::Download), 1);
+        assert_eq!(metrics.bytes(TransferDirection::Download), 8);
+        assert_eq!(result.downloaded, 1);
+        assert!(result.errors.is_empty());
+        assert_eq!(std::fs::read_to_string(temp_dir.path().join("data/a.csv")).unwrap(), "a,b\n1,2\n");
+        assert!(LocalIndex::load(temp_dir.path()).unwrap().get_entry("data/a.csv").is_some());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_sync_keeps_permissions_in_index() {
+        use std::os::unix::fs::PermissionsExt;
+        
+        let server = MockLakeFS::new()
+            .with_repo("repo")
+            .with_branch("main")
+            .with_object("data/a.csv", "a,b\n1,2\n")
+            .start()
+            .await;
+        
+        let client = Arc::new(lakefs_api::LakeFSClient::new(server.uri(), "Bearer test-token"));
+        let temp_dir = TempDir::new().unwrap();
+        let config = SyncConfig { ignore_permissions: false, ..Default::default() };
+        SyncManager::new(client.clone(), config).sync(temp_dir.path(), &LakeFSUri::new("repo", "main")).await.unwrap();
+        
+        let mode = std::fs::metadata(temp_dir.path().join("data/a.csv")).unwrap().permissions().mode() & 0o7777;
+        let entry = LocalIndex::load(temp_dir.path()).unwrap().get_entry("data/a.csv").cloned().unwrap();
+        assert_eq!(entry.permissions, Some(mode));
+        
+        let temp_dir = TempDir::new().unwrap();
+        SyncManager::new(client, SyncConfig::default()).sync(temp_dir.path(), &LakeFSUri::new("repo", "main")).await.unwrap();
+        let entry = LocalIndex::load(temp_dir.path()).unwrap().get_entry("data/a.csv").cloned().unwrap();
+        assert_eq!(entry.permissions, None);
+    }
+
+    #[tokio::test]
+    async fn test_sync_with_events() {
+        let server = MockLakeFS::new()
+            .with_repo("repo")
+            .with_branch("main")
+            .with_object("data/a.csv", "a,b\n1,2\n")
+            .start()
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/repositories/repo/branches/main/objects"))
+            .respond_with(ResponseTemplate::new(400))
+            .mount(&server)
+            .await;
+        
+        let temp_dir = TempDir::new().unwrap();
+        LocalIndex::new("repo", "main", "c0").save(temp_dir.path()).unwrap();
+        std::fs::write(temp_dir.path().join("local.csv"), "data").unwrap();
+        
+        let client = Arc::new(lakefs_api::LakeFSClient::new(server.uri(), "Bearer test-token"));
+        let config = SyncConfig::default();
+        let manager = SyncManager::new(client, config);
+        let remote = LakeFSUri::new("repo", "main");
+        let mut events: Vec<SyncEvent> = manager.sync_with_events(temp_dir.path(), &remote).collect().await;
+        
+        let Some(SyncEvent::Finished(Ok(result))) = events.pop() else {
+            panic!("sync did not finish last: {:?}", events);
+        };
+        assert_eq!((result.downloaded, result.errors.len()), (1, 1));
+        assert!(matches!(events[0], SyncEvent::Scanned { files: 2, bytes: 12 }));
+        let progress: u64 = events
+            .iter()
+            .filter_map(|e| match e {
+                SyncEvent::Progress { path, bytes } if path == "data/a.csv" => Some(*bytes),
+                _ => None,
+            })
+            .sum();
+        assert_eq!(progress, 8);
+        assert!(events.iter().any(|e| matches!(e, SyncEvent::Completed { path } if path == "data/a.csv")));
+        assert!(events.iter().any(|e| matches!(e, SyncEvent::Failed { path, .. } if path == "local.csv")));
+    }
+
+    #[tokio::test]
+    async fn test_sync_records_blocks_of_large_files() {
+        let server = MockLakeFS::new()
+            .with_repo("repo")
+            .with_branch("main")
+            .with_object("small.csv", "a\n")
+            .with_object("large.csv", "a,b\n1,2\n")
+            .start()
+            .await;
+        
+        let client = Arc::new(lakefs_api::LakeFSClient::new(server.uri(), "Bearer test-token"));
+        let config = SyncConfig { delta_threshold: Some(4), ..Default::default() };
+        let temp_dir = TempDir::new().unwrap();
+        SyncManager::new(client, config).sync(temp_dir.path(), &LakeFSUri::new("repo", "main")).await.unwrap();
+        
+        let index = LocalIndex::load(temp_dir.path()).unwrap();
+        assert!(index.entries["small.csv"].blocks.is_empty());
+        let large = temp_dir.path().join("large.csv");
+        assert_eq!(index.entries["large.csv"].blocks, delta::block_checksums(&large).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_checkouts_share_object_cache() {
+        let server = MockLakeFS::new()
+            .with_repo("repo")
+            .with_branch("main")
+            .with_object("data/a.csv", "a,b\n1,2\n")
+            .start()
+            .await;
+        
+        let client = Arc::new(lakefs_api::LakeFSClient::new(server.uri(), "Bearer test-token"));
+        let temp_dir = TempDir::new().unwrap();
+        let remote = LakeFSUri::new("repo", "main");
+        
+        let mut downloaded = Vec::new();
+        for checkout in ["one", "two"] {
+            let config = SyncConfig {
+                object_cache: Some(temp_dir.path().join("cache")),
+                link_strategy: LinkStrategy::Hardlink,
+                ..Default::default()
+            };
+            let local = temp_dir.path().join(checkout);
+            let result = SyncManager::new(client.clone(), config).sync(&local, &remote).await.unwrap();
+            assert_eq!(std::fs::read_to_string(local.join("data/a.csv")).unwrap(), "a,b\n1,2\n");
+            downloaded.push(result.files[0].bytes);
+        }
+        
+        // The second checkout is linked from the cache without a transfer
+        assert_eq!(downloaded, vec![8, 0]);
+    }
+
+    #[tokio::test]
+    async fn test_push_uploads_without_downloading() {
+        let server = MockLakeFS::new()
+            .with_repo("repo")
+            .with_branch("main")
+            .with_object("remote.csv", "remote")
+            .with_object("gone.csv", "gone")
+            .start()
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/repositories/repo/branches/main/objects"))
+            .and(query_param("path", "local.csv"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(test_object_stats()))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path("/repositories/repo/branches/main/objects"))
+            .and(query_param("path", "gone.csv"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let mut index = LocalIndex::new("repo", "main", "c0");
+        index.add_entry("gone.csv".to_string(), IndexEntry {
+            path: "gone.csv".to_string(),
+            checksum: "old".into(),
+            size: 4,
+            mtime: Utc::now(),
+            permissions: None,
+            blocks: Vec::new(),
+        });
+        index.save(temp_dir.path()).unwrap();
+        std::fs::write(temp_dir.path().join("local.csv"), "local").unwrap();
+        
+        let client = Arc::new(lakefs_api::LakeFSClient::new(server.uri(), "Bearer test-token"));
+        let config = SyncConfig::default();
+        let manager = SyncManager::new(client, config);
+        let result = manager.push(temp_dir.path(), &LakeFSUri::new("repo", "main")).await.unwrap();
+        
+        assert_eq!((result.uploaded, result.downloaded, result.removed), (1, 0, 1));
+        assert!(!temp_dir.path().join("remote.csv").exists());
+        
+        let index = LocalIndex::load(temp_dir.path()).unwrap();
+        assert_eq!(index.head_commit, "c0");
+        assert!(index.get_entry("local.csv").is_some());
+        assert!(index.get_entry("gone.csv").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_sync_skips_upload_of_identical_content() {
+        let server = MockLakeFS::new()
+            .with_repo("repo")
+            .with_branch("main")
+            .with_object("data/a.csv", "a,b\n1,2\n")
+            .start()
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/repositories/repo/branches/main/objects"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(test_object_stats()))
+            .expect(0)
+            .mount(&server)
+            .await;
+        
+        // A fresh checkout over a directory that already has the file
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::create_dir(temp_dir.path().join("data")).unwrap();
+        std::fs::write(temp_dir.path().join("data/a.csv"), "a,b\n1,2\n").unwrap();
+        
+        let client = Arc::new(lakefs_api::LakeFSClient::new(server.uri(), "Bearer test-token"));
+        let config = SyncConfig::default();
+        let result = SyncManager::new(client, config).sync(temp_dir.path(), &LakeFSUri::new("repo", "main")).await.unwrap();
+        
+        assert_eq!((result.uploaded, result.downloaded, result.unchanged), (0, 0, 1));
+        assert!(result.errors.is_empty());
+        assert!(LocalIndex::load(temp_dir.path()).unwrap().get_entry("data/a.csv").is_some());
+    }
+
+    #[tokio::test]
+    async fn test_pull_skips_download_of_identical_content() {
+        let server = MockLakeFS::new()
+            .with_repo("repo")
+            .with_branch("main")
+            .with_object("a.csv", "new")
+            .start()
+            .await;
+        
+        // The file was replaced but the index not saved, as when a sync is
+        // interrupted
+        let temp_dir = TempDir::new().unwrap();
+        let mut index = LocalIndex::new("repo", "main", "c0");
+        index.add_entry("a.csv".to_string(), IndexEntry {
+            path: "a.csv".to_string(),
+            checksum: "old".into(),
+            size: 3,
+            mtime: Utc::now(),
+            permissions: None,
+            blocks: Vec::new(),
+        });
+        index.save(temp_dir.path()).unwrap();
+        std::fs::write(temp_dir.path().join("a.csv"), "new").unwrap();
+        
+        let client = Arc::new(lakefs_api::LakeFSClient::new(server.uri(), "Bearer test-token"));
+        let config = SyncConfig::default();
+        let result = SyncManager::new(client, config).pull(temp_dir.path(), &LakeFSUri::new("repo", "main")).await.unwrap();
+        
+        assert_eq!((result.downloaded, result.unchanged), (0, 1));
+        let entry = LocalIndex::load(temp_dir.path()).unwrap().get_entry("a.csv").cloned().unwrap();
+        assert_eq!(entry.checksum, Checksum::Md5(format!("{:x}", md5::Md5::digest(b"new"))));
+    }
+
+    #[tokio::test]
+    async fn test_push_reports_objects_changed_since_listing() {
+        let server = MockLakeFS::new()
+            .with_repo("repo")
+            .with_branch("main")
+            .with_object("a.csv", "remote")
+            .start()
+            .await;
+        let listed = format!("\"{:x}\"", md5::Md5::digest(b"remote"));
+        Mock::given(method("PUT"))
+            .and(path("/repositories/repo/branches/main/objects"))
+            .and(header("if-match", listed.as_str()))
+            .respond_with(ResponseTemplate::new(412).set_body_string("precondition failed"))
+            .expect(1)
+            .mount(&server)
+            .await;
+        
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("a.csv"), "local").unwrap();
+        
+        let client = Arc::new(lakefs_api::LakeFSClient::new(server.uri(), "Bearer test-token"));
+        let config = SyncConfig::default();
+        let result = SyncManager::new(client, config).push(temp_dir.path(), &LakeFSUri::new("repo", "main")).await.unwrap();
+        
+        assert_eq!(result.uploaded, 0);
+        assert_eq!(result.errors.len(), 1);
+        assert!(matches!(&result.errors[0], (path, Error::ConcurrentModification(_)) if path == "a.csv"));
+    }
+
+    #[tokio::test]
+    async fn test_sync_stops_at_max_errors() {
+        let server = MockLakeFS::new()
+            .with_repo("repo")
+            .with_branch("main")
+            .start()
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/repositories/repo/branches/main/objects"))
+            .respond_with(ResponseTemplate::new(400).set_delay(Duration::from_millis(200)))
+            .mount(&server)
+            .await;
+        
+        let temp_dir = TempDir::new().unwrap();
+        LocalIndex::new("repo", "main", "c0").save(temp_dir.path()).unwrap();
+        for i in 0..5 {
+            std::fs::write(temp_dir.path().join(format!("{}.csv", i)), "data").unwrap();
+        }
+        
+        let client = Arc::new(lakefs_api::LakeFSClient::new(server.uri(), "Bearer test-token"));
+        let config = SyncConfig {
+            parallelism: 1,
+            max_errors: Some(2),
+            ..Default::default()
+        };
+        let result = SyncManager::new(client, config).push(temp_dir.path(), &LakeFSUri::new("repo", "main")).await.unwrap();
+        
+        assert_eq!(result.errors.len(), 2);
+        assert_eq!(result.skipped, 3);
+        assert_eq!(result.uploaded, 0);
+    }
+
+    #[tokio::test]
+    async fn test_push_retries_transient_failures() {
+        let server = MockLakeFS::new()
+            .with_repo("repo")
+            .with_branch("main")
+            .start()
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/repositories/repo/branches/main/objects"))
+            .and(query_param("path", "flaky.csv"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(2)
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/repositories/repo/branches/main/objects"))
+            .and(query_param("path", "flaky.csv"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(test_object_stats()))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/repositories/repo/branches/main/objects"))
+            .and(query_param("path", "invalid.csv"))
+            .respond_with(ResponseTemplate::new(400))
+            .expect(1)
+            .mount(&server)
+            .await;
+        
+        let temp_dir = TempDir::new().unwrap();
+        LocalIndex::new("repo", "main", "c0").save(temp_dir.path()).unwrap();
+        std::fs::write(temp_dir.path().join("flaky.csv"), "data").unwrap();
+        std::fs::write(temp_dir.path().join("invalid.csv"), "data").unwrap();
+        
+        let client = Arc::new(lakefs_api::LakeFSClient::new(server.uri(), "Bearer test-token"));
+        let config = SyncConfig {
+            retry: RetryPolicy::exponential(2).with_backoff(Duration::from_millis(1), Duration::from_millis(5)),
+            ..Default::default()
+        };
+        let result = SyncManager::new(client, config).push(temp_dir.path(), &LakeFSUri::new("repo", "main")).await.unwrap();
+        
+        // Only the transient failure is attempted again
+        assert_eq!(result.uploaded, 1);
+        assert_eq!(result.errors.len(), 1);
+        assert_eq!(result.errors[0].0, "invalid.csv");
+    }
+
+    #[tokio::test]
+    async fn test_push_retry_checks_whether_upload_landed() {
+        let server = MockLakeFS::new()
+            .with_repo("repo")
+            .with_branch("main")
+            .start()
+            .await;
+        // Both uploads lose their reply, but one of them went through
+        for name in ["landed.csv", "taken.csv"] {
+            Mock::given(method("PUT"))
+                .and(path("/repositories/repo/branches/main/objects"))
+                .and(query_param("path", name))
+                .respond_with(ResponseTemplate::new(503))
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+        let stats = |checksum: &str| {
+            let mut stats = test_object_stats();
+            stats.checksum = checksum.into();
+            stats
+        };
+        Mock::given(method("GET"))
+            .and(path("/repositories/repo/refs/main/objects/stat"))
+            .and(query_param("path", "landed.csv"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(stats("8d777f385d3dfec8815d20f7496026dc")))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repositories/repo/refs/main/objects/stat"))
+            .and(query_param("path", "taken.csv"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(stats("0123456789abcdef0123456789abcdef")))
+            .mount(&server)
+            .await;
+        
+        let temp_dir = TempDir::new().unwrap();
+        LocalIndex::new("repo", "main", "c0").save(temp_dir.path()).unwrap();
+        std::fs::write(temp_dir.path().join("landed.csv"), "data").unwrap();
+        std::fs::write(temp_dir.path().join("taken.csv"), "data").unwrap();
+        
+        let client = Arc::new(lakefs_api::LakeFSClient::new(server.uri(), "Bearer test-token"));
+        let config = SyncConfig {
+            retry: RetryPolicy::exponential(2).with_backoff(Duration::from_millis(1), Duration::from_millis(5)),
+            ..Default::default()
+        };
+        let result = SyncManager::new(client, config).push(temp_dir.path(), &LakeFSUri::new("repo", "main")).await.unwrap();
+        
+        assert_eq!(result.uploaded, 1);
+        assert_eq!(result.errors.len(), 1);
+        assert_eq!(result.errors[0].0, "taken.csv");
+        assert!(matches!(result.errors[0].1, Error::ConcurrentModification(_)));
+        let index = LocalIndex::load(temp_dir.path()).unwrap();
+        assert_eq!(index.entries["landed.csv"].checksum, "8d777f385d3dfec8815d20f7496026dc".into());
+    }
+
+    #[tokio::test]
+    async fn test_verify_and_revert_staged() {
+        let server = MockLakeFS::new()
+            .with_repo("repo")
+            .with_branch("main")
+            .with_object("a.csv", "staged")
+            .with_object("x.csv", "theirs")
+            .start()
+            .await;
+        let client = Arc::new(lakefs_api::LakeFSClient::new(server.uri(), "Bearer test-token"));
+        // The branch serves what the upload staged
+        let staged = client.get_object("repo", "main", "a.csv").await.unwrap();
+        Mock::given(method("PUT"))
+            .and(path("/repositories/repo/branches/main/objects"))
+            .and(query_param("path", "a.csv"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(&staged))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let diff = |paths: &[&str]| ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "results": paths.iter().map(|path| serde_json::json!({"path": path, "path_type": "object", "type": "added"})).collect::<Vec<_>>(),
+            "pagination": { "has_more": false, "max_per_page": 1000, "next_offset": null, "results": paths.len() }
+        }));
+        Mock::given(method("GET"))
+            .and(path("/repositories/repo/branches/main/diff"))
+            .respond_with(diff(&["a.csv"]))
+            .up_to_n_times(1)
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        // Another writer stages x.csv
+        Mock::given(method("GET"))
+            .and(path("/repositories/repo/branches/main/diff"))
+            .respond_with(diff(&["a.csv", "x.csv"]))
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/repositories/repo/branches/main"))
+            .and(body_json(serde_json::json!({"type": "object", "path": "a.csv"})))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let before = LocalIndex::new("repo", "main", "c0");
+        before.save(temp_dir.path()).unwrap();
+        std::fs::write(temp_dir.path().join("a.csv"), "local").unwrap();
+        
+        let manager = SyncManager::new(client, SyncConfig::default());
+        let remote = LakeFSUri::new("repo", "main");
+        let pushed = manager.push(temp_dir.path(), &remote).await.unwrap();
+        assert_eq!(pushed.staged_paths().collect::<Vec<_>>(), ["a.csv"]);
+        assert!(manager.verify_staged(temp_dir.path(), &remote, &pushed).await.unwrap().is_empty());
+        
+        let mismatches = manager.verify_staged(temp_dir.path(), &remote, &pushed).await.unwrap();
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(mismatches[0].to_string(), "x.csv: staged by someone else");
+        
+        let reverted = manager.revert_staged(temp_dir.path(), &remote, &pushed, &before, &mismatches).await.unwrap();
+        assert_eq!(reverted, 1);
+        assert!(!LocalIndex::load(temp_dir.path()).unwrap().entries.contains_key("a.csv"));
+    }
+
+    #[tokio::test]
+    async fn test_verify_and_revert_staged_under_prefix() {
+        let server = MockLakeFS::new()
+            .with_repo("repo")
+            .with_branch("main")
+            .with_object("data/a.csv", "theirs")
+            .with_object("data/b.csv", "staged")
+            .start()
+            .await;
+        let client = Arc::new(lakefs_api::LakeFSClient::new(server.uri(), "Bearer test-token"));
+        let staged = client.get_object("repo", "main", "data/b.csv").await.unwrap();
+        // a.csv is overwritten by another writer after the upload
+        Mock::given(method("PUT"))
+            .and(path("/repositories/repo/branches/main/objects"))
+            .and(query_param("path", "data/a.csv"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(test_object_stats()))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/repositories/repo/branches/main/objects"))
+            .and(query_param("path", "data/b.csv"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(&staged))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repositories/repo/branches/main/diff"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "results": [
+                    {"path": "data/a.csv", "path_type": "object", "type": "changed"},
+                    {"path": "data/b.csv", "path_type": "object", "type": "changed"},
+                    {"path": "other/z.csv", "path_type": "object", "type": "added"}
+                ],
+                "pagination": { "has_more": false, "max_per_page": 1000, "next_offset": null, "results": 3 }
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/repositories/repo/branches/main"))
+            .and(body_json(serde_json::json!({"type": "object", "path": "data/b.csv"})))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/repositories/repo/branches/main"))
+            .and(body_json(serde_json::json!({"type": "object", "path": "data/a.csv"})))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(0)
+            .mount(&server)
+            .await;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let before = LocalIndex::new("repo", "main", "c0");
+        before.save(temp_dir.path()).unwrap();
+        std::fs::write(temp_dir.path().join("a.csv"), "mine").unwrap();
+        std::fs::write(temp_dir.path().join("b.csv"), "mine too").unwrap();
+        
+        let manager = SyncManager::new(client, SyncConfig::default());
+        let remote = LakeFSUri::new("repo", "main").with_path("data/");
+        let pushed = manager.push(temp_dir.path(), &remote).await.unwrap();
+        assert_eq!(pushed.uploaded, 2);
+        
+        let mismatches = manager.verify_staged(temp_dir.path(), &remote, &pushed).await.unwrap();
+        assert_eq!(mismatches.iter().map(ToString::to_string).collect::<Vec<_>>(), ["a.csv: changed by someone else"]);
+        
+        let reverted = manager.revert_staged(temp_dir.path(), &remote, &pushed, &before, &mismatches).await.unwrap();
+        assert_eq!(reverted, 1);
+        // Both local edits are pending again, including the one not reset
+        let index = LocalIndex::load(temp_dir.path()).unwrap();
+        assert!(index.entries.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_pull_never_uploads() {
+        // Listings are served per ref, so a branch stands in for the commit
+        let server = MockLakeFS::new()
+            .with_repo("repo")
+            .with_branch("c1")
+            .with_object("new.csv", "new")
+            .with_object("restored.csv", "restored")
+            .start()
+            .await;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let mut index = LocalIndex::snapshot("repo", "c0");
+        index.add_entry("restored.csv".to_string(), IndexEntry {
+            path: "restored.csv".to_string(),
+            checksum: "old".into(),
+            size: 3,
+            mtime: Utc::now(),
+            permissions: None,
+            blocks: Vec::new(),
+        });
+        index.save(temp_dir.path()).unwrap();
+        std::fs::write(temp_dir.path().join("local.csv"), "local").unwrap();
+        
+        let client = Arc::new(lakefs_api::LakeFSClient::new(server.uri(), "Bearer test-token"));
+        let config = SyncConfig::default();
+        let manager = SyncManager::new(client, config);
+        let remote = LakeFSUri::new("repo", "c1");
+        let result = manager.pull(temp_dir.path(), &remote).await.unwrap();
+        
+        assert_eq!((result.uploaded, result.downloaded), (0, 2));
+        assert!(result.errors.is_empty());
+        assert_eq!(std::fs::read_to_string(temp_dir.path().join("restored.csv")).unwrap(), "restored");
+        let index = LocalIndex::load(temp_dir.path()).unwrap();
+        assert_eq!((index.reference.as_str(), index.head_commit.as_str()), ("c1", "c1"));
+        assert!(index.get_entry("local.csv").is_none());
+        
+        assert!(matches!(manager.push(temp_dir.path(), &remote).await, Err(Error::Sync(_))));
+        assert!(matches!(manager.sync(temp_dir.path(), &remote).await, Err(Error::Sync(_))));
+    }
+
+    #[tokio::test]
+    async fn test_sync_lists_every_page() {
+        let server = MockLakeFS::new()
+            .page_size(1)
+            .with_repo("repo")
+            .with_branch("main")
+            .with_object("a.csv", "a")
+            .with_object("b.csv", "b")
+            .start()
+            .await;
+        
+        let client = Arc::new(lakefs_api::LakeFSClient::new(server.uri(), "Bearer test-token"));
+        let config = SyncConfig::default();
+        let temp_dir = TempDir::new().unwrap();
+        
+        let result = SyncManager::new(client, config)
+            .sync(temp_dir.path(), &LakeFSUri::new("repo", "main"))
+            .await
+            .unwrap();
+        assert_eq!(result.downloaded, 2);
+    }
+
+    #[tokio::test]
+    async fn test_sync_delete_extraneous() {
+        let server = MockLakeFS::new()
+            .with_repo("repo")
+            .with_branch("main")
+            .start()
+            .await;
+        let client = Arc::new(lakefs_api::LakeFSClient::new(server.uri(), "Bearer test-token"));
+        let temp_dir = TempDir::new().unwrap();
+        
+        // Tracked and unchanged locally, but deleted from the branch
+        std::fs::write(temp_dir.path().join("gone.csv"), "gone").unwrap();
+        let mut index = LocalIndex::new("repo", "main", "c0");
+        index.add_entry("gone.csv".to_string(), IndexEntry {
+            path: "gone.csv".to_string(),
+            checksum: "old".into(),
+            size: 4,
+            mtime: Utc::now() + chrono::Duration::days(1),
+            permissions: None,
+            blocks: Vec::new(),
+        });
+        index.save(temp_dir.path()).unwrap();
+        let uri = LakeFSUri::new("repo", "main");
+        
+        let config = SyncConfig::default();
+        let result = SyncManager::new(client.clone(), config).sync(temp_dir.path(), &uri).await.unwrap();
+        assert_eq!(result.removed, 0);
+        assert!(temp_dir.path().join("gone.csv").exists());
+        
+        let config = SyncConfig { delete_extraneous: true, ..Default::default() };
+        let result = SyncManager::new(client, config).sync(temp_dir.path(), &uri).await.unwrap();
+        assert_eq!(result.removed, 1);
+        assert!(!temp_dir.path().join("gone.csv").exists());
+        assert!(LocalIndex::load(temp_dir.path()).unwrap().get_entry("gone.csv").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_pull_fetches_only_the_diff() {
+        let server = MockLakeFS::new()
+            .with_repo("repo")
+            .with_branch("main")
+            .with_object("a.csv", "a")
+            .with_object("b.csv", "b2")
+            .with_object("c.csv", "c")
+            .start()
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repositories/repo/refs/c0/diff/main"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "results": [
+                    {"path": "b.csv", "path_type": "object", "type": "changed"},
+                    {"path": "old.csv", "path_type": "object", "type": "removed"}
+                ],
+                "pagination": { "has_more": false, "max_per_page": 1000, "next_offset": null, "results": 2 }
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        // c.csv is staged but not committed yet
+        Mock::given(method("GET"))
+            .and(path("/repositories/repo/branches/main/diff"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "results": [{"path": "c.csv", "path_type": "object", "type": "added"}],
+                "pagination": { "has_more": false, "max_per_page": 1000, "next_offset": null, "results": 1 }
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let client = Arc::new(lakefs_api::LakeFSClient::new(server.uri(), "Bearer test-token"));
+        let temp_dir = TempDir::new().unwrap();
+        
+        // Tracked and unchanged locally; a.csv is stale but not in the diff
+        let mut index = LocalIndex::new("repo", "main", "c0");
+        index.pulled_commit = Some("c0".to_string());
+        for (name, content) in [("a.csv", "stale"), ("b.csv", "b"), ("old.csv", "old")] {
+            std::fs::write(temp_dir.path().join(name), content).unwrap();
+            index.add_entry(name.to_string(), IndexEntry {
+                path: name.to_string(),
+                checksum: "old".into(),
+                size: content.len() as u64,
+                mtime: Utc::now() + chrono::Duration::days(1),
+                permissions: None,
+                blocks: Vec::new(),
+            });
+        }
+        index.save(temp_dir.path()).unwrap();
+        
+        let config = SyncConfig { delete_extraneous: true, ..Default::default() };
+        let result = SyncManager::new(client.clone(), config)
+            .pull(temp_dir.path(), &LakeFSUri::new("repo", "main"))
+            .await
+            .unwrap();
+        assert_eq!((result.downloaded, result.removed), (2, 1));
+        assert_eq!(std::fs::read_to_string(temp_dir.path().join("b.csv")).unwrap(), "b2");
+        assert_eq!(std::fs::read_to_string(temp_dir.path().join("c.csv")).unwrap(), "c");
+        assert_eq!(std::fs::read_to_string(temp_dir.path().join("a.csv")).unwrap(), "stale");
+        assert!(!temp_dir.path().join("old.csv").exists());
+        
+        let requests = server.received_requests().await.unwrap();
+        let listed = requests
+            .iter()
+            .any(|r| r.url.path().ends_with("/objects") && !r.url.query().unwrap_or_default().contains("path="));
+        assert!(!listed, "the branch was listed");
+        let head = client.get_branch("repo", "main").await.unwrap().commit_id;
+        assert_eq!(LocalIndex::load(temp_dir.path()).unwrap().pulled_commit, Some(head));
+    }
+    
+    #[tokio::test]
+    async fn test_pull_lists_when_diff_unavailable() {
+        let server = MockLakeFS::new()
+            .with_repo("repo")
+            .with_branch("main")
+            .with_object("a.csv", "a")
+            .with_object("b.txt", "b")
+            .start()
+            .await;
+        let client = Arc::new(lakefs_api::LakeFSClient::new(server.uri(), "Bearer test-token"));
+        let temp_dir = TempDir::new().unwrap();
+        let uri = LakeFSUri::new("repo", "main");
+        
+        // The server has no diff from a garbage-collected commit
+        let mut index = LocalIndex::new("repo", "main", "c0");
+        index.pulled_commit = Some("collected".to_string());
+        index.save(temp_dir.path()).unwrap();
+        
+        // A filtered pull leaves objects behind for the next one to compare
+        let config = SyncConfig { filter: PathFilter::new().exclude("*.txt").unwrap(), ..Default::default() };
+        let result = SyncManager::new(client.clone(), config).pull(temp_dir.path(), &uri).await.unwrap();
+        assert_eq!(result.downloaded, 1);
+        assert_eq!(LocalIndex::load(temp_dir.path()).unwrap().pulled_commit.as_deref(), Some("collected"));
+        
+        let result = SyncManager::new(client.clone(), SyncConfig::default()).pull(temp_dir.path(), &uri).await.unwrap();
+        assert_eq!(result.downloaded, 1);
+        let head = client.get_branch("repo", "main").await.unwrap().commit_id;
+        assert_eq!(LocalIndex::load(temp_dir.path()).unwrap().pulled_commit, Some(head));
+    }
+    
+    #[tokio::test]
+    async fn test_sync_cancelled_saves_index() {
+        let mock_server = MockServer::start().await;
+        
+        Mock::given(method("GET"))
+            .and(path("/repositories/test-repo/branches/main"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(test_branch()))
+            .mount(&mock_server)
+            .await;
+        
+        Mock::given(method("GET"))
+            .and(path("/repositories/test-repo/refs/main/objects"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "results": [test_object_stats()],
+                "pagination": {"has_more": false, "max_per_page": 100, "results": 1, "next_offset": ""}
+            })))
+            .mount(&mock_server)
+            .await;
+        
+        let client = Arc::new(lakefs_api::LakeFSClient::new(mock_server.uri(), "Bearer test-token"));
+        let config = SyncConfig::default();
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+        
+        let temp_dir = TempDir::new().unwrap();
+        let manager = SyncManager::new(client, config).with_cancellation(cancel);
+        let result = manager.sync(temp_dir.path(), &LakeFSUri::new("test-repo", "main")).await;
+        
+        assert!(matches!(result, Err(Error::Cancelled)));
+        assert!(LocalIndex::load(temp_dir.path()).is_ok());
+        assert!(!temp_dir.path().join("test/file.txt").exists());
+    }
+
+    #[test]
+    fn test_summary_json() {
+        let result = SyncResult {
+            uploaded: 1,
+            downloaded: 0,
+            removed: 0,
+            unchanged: 0,
+            skipped: 0,
+            errors: vec![("b.txt".into(), Error::Sync("boom".into()))],
+            files: vec![
+                TransferRecord {
+                    path: "a.txt".into(),
+                    direction: TransferDirection::Upload,
+                    bytes: 42,
+                    duration: Duration::from_millis(15),
+                    error: None,
+                },
+                TransferRecord {
+                    path: "b.txt".into(),
+                    direction: TransferDirection::Upload,
+                    bytes: 0,
+                    duration: Duration::from_millis(3),
+                    error: Some("boom".into()),
+                },
+            ],
+        };
+        
+        let summary = result.summary_json();
+        assert_eq!(summary["uploaded"], 1);
+        assert_eq!(summary["failed"], 1);
+        assert_eq!(summary["bytes_uploaded"], 42);
+        assert_eq!(summary["files"][0]["direction"], "upload");
+        assert_eq!(summary["files"][0]["duration_ms"], 15);
+        assert_eq!(summary["files"][1]["error"], "boom");
+    }
+}
@@ -0,0 +1,173 @@
+//! Progress bars on the terminal, for command-line front ends. Only built
+//! with the `cli` feature, so embedding the library pulls in no terminal
+//! dependencies.
+
+use crate::error::{Error, Result};
+use crate::events::SyncEvent;
+use crate::progress::ProgressReporter;
+use crate::sync::SyncResult;
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+/// Terminal display with an overall bytes bar plus one bar per active transfer.
+pub struct MultiProgressReporter {
+    multi: MultiProgress,
+    overall: ProgressBar,
+    files: Mutex<HashMap<String, ProgressBar>>,
+    total_files: AtomicU64,
+    finished_files: AtomicU64,
+}
+
+impl MultiProgressReporter {
+    pub fn new() -> Self {
+        let multi = MultiProgress::new();
+        let overall = multi.add(ProgressBar::new(0));
+        overall.set_style(
+            ProgressStyle::default_bar()
+                .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {bytes}/{total_bytes} ({bytes_per_sec}, {eta}) {msg}")
+                .unwrap()
+                .progress_chars("#>-"),
+        );
+
+        Self {
+            multi,
+            overall,
+            files: Mutex::new(HashMap::new()),
+            total_files: AtomicU64::new(0),
+            finished_files: AtomicU64::new(0),
+        }
+    }
+
+    /// Draw `event`, from [`SyncManager::sync_with_events`] and the like.
+    ///
+    /// [`SyncManager::sync_with_events`]: crate::SyncManager::sync_with_events
+    pub fn handle(&self, event: &SyncEvent) {
+        match event {
+            SyncEvent::Scanned { files, bytes } => self.set_total(*files, *bytes),
+            SyncEvent::Started { path, size } => self.file_started(path, *size),
+            SyncEvent::Progress { path, bytes } => self.bytes_transferred(path, *bytes),
+            SyncEvent::Completed { path } => self.file_finished(path),
+            SyncEvent::Failed { path, .. } => self.abandon(path),
+            SyncEvent::Finished(result) => self.finish(&finished_message(result)),
+        }
+    }
+
+    fn abandon(&self, path: &str) {
+        if let Some(pb) = self.files.lock().unwrap().remove(path) {
+            pb.abandon();
+            self.multi.remove(&pb);
+        }
+    }
+
+    fn update_message(&self) {
+        self.overall.set_message(format!(
+            "{}/{} files",
+            self.finished_files.load(Ordering::Relaxed),
+            self.total_files.load(Ordering::Relaxed),
+        ));
+    }
+}
+
+impl Default for MultiProgressReporter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProgressReporter for MultiProgressReporter {
+    fn set_total(&self, files: u64, bytes: u64) {
+        self.total_files.store(files, Ordering::Relaxed);
+        self.overall.set_length(bytes);
+        self.update_message();
+    }
+
+    fn file_started(&self, path: &str, size: u64) {
+        let pb = self.multi.add(ProgressBar::new(size));
+        pb.set_style(
+            ProgressStyle::default_bar()
+                .template("  {msg:40!} [{bar:20}] {bytes}/{total_bytes}")
+                .unwrap()
+                .progress_chars("=> "),
+        );
+        pb.set_message(path.to_string());
+        self.files.lock().unwrap().insert(path.to_string(), pb);
+    }
+
+    fn bytes_transferred(&self, path: &str, bytes: u64) {
+        if let Some(pb) = self.files.lock().unwrap().get(path) {
+            pb.inc(bytes);
+        }
+        self.overall.inc(bytes);
+    }
+
+    fn file_finished(&self, path: &str) {
+        if let Some(pb) = self.files.lock().unwrap().remove(path) {
+            pb.finish_and_clear();
+            self.multi.remove(&pb);
+        }
+        self.finished_files.fetch_add(1, Ordering::Relaxed);
+        self.update_message();
+    }
+
+    fn file_failed(&self, path: &str, _error: &Error) {
+        self.abandon(path);
+    }
+
+    fn finish(&self, message: &str) {
+        self.overall.finish_with_message(message.to_string());
+    }
+}
+
+fn finished_message(result: &Result<SyncResult>) -> String {
+    match result {
+        Ok(result) if result.skipped > 0 => format!("Sync stopped after {} failed transfers", result.errors.len()),
+        Ok(_) => "Sync complete".to_string(),
+        Err(Error::Cancelled) => "Sync cancelled".to_string(),
+        Err(_) => "Sync failed".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indicatif::ProgressDrawTarget;
+
+    #[test]
+    fn test_multi_progress_tracks_bytes_and_files() {
+        let reporter = MultiProgressReporter::new();
+        reporter.multi.set_draw_target(ProgressDrawTarget::hidden());
+
+        reporter.set_total(2, 300);
+        reporter.file_started("a.txt", 100);
+        reporter.file_started("b.txt", 200);
+        reporter.bytes_transferred("a.txt", 100);
+        reporter.bytes_transferred("b.txt", 50);
+        reporter.file_finished("a.txt");
+
+        assert_eq!(reporter.overall.position(), 150);
+        assert_eq!(reporter.overall.length(), Some(300));
+        assert_eq!(reporter.files.lock().unwrap().len(), 1);
+        assert_eq!(reporter.finished_files.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn test_multi_progress_handles_events() {
+        let reporter = MultiProgressReporter::new();
+        reporter.multi.set_draw_target(ProgressDrawTarget::hidden());
+
+        reporter.handle(&SyncEvent::Scanned { files: 2, bytes: 300 });
+        reporter.handle(&SyncEvent::Started { path: "a.txt".to_string(), size: 100 });
+        reporter.handle(&SyncEvent::Started { path: "b.txt".to_string(), size: 200 });
+        reporter.handle(&SyncEvent::Progress { path: "a.txt".to_string(), bytes: 100 });
+        reporter.handle(&SyncEvent::Completed { path: "a.txt".to_string() });
+        reporter.handle(&SyncEvent::Failed { path: "b.txt".to_string(), error: "denied".to_string() });
+        assert_eq!(reporter.overall.position(), 100);
+        assert!(reporter.files.lock().unwrap().is_empty());
+
+        reporter.handle(&SyncEvent::Finished(Err(Error::Cancelled)));
+        assert!(reporter.overall.is_finished());
+        assert_eq!(reporter.overall.message(), "Sync cancelled");
+    }
+}
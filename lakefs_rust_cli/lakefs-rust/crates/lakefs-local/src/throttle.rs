@@ -0,0 +1,75 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Token bucket shared by all transfers in one direction.
+///
+/// The bucket holds at most one second worth of bytes. Callers may take more
+/// than is available; the resulting debt is paid off by sleeping, which keeps
+/// the long-run rate at `bytes_per_sec` regardless of chunk size.
+pub struct BandwidthLimiter {
+    bytes_per_sec: f64,
+    state: Mutex<BucketState>,
+}
+
+struct BucketState {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl BandwidthLimiter {
+    pub fn new(bytes_per_sec: u64) -> Self {
+        let bytes_per_sec = bytes_per_sec.max(1) as f64;
+        Self {
+            bytes_per_sec,
+            state: Mutex::new(BucketState {
+                tokens: bytes_per_sec,
+                last_refill: Instant::now(),
+            }),
+        }
+    }
+    
+    /// Wait until `bytes` may be transferred.
+    pub async fn acquire(&self, bytes: usize) {
+        let wait = {
+            let mut state = self.state.lock().unwrap();
+            let now = Instant::now();
+            let elapsed = now.duration_since(state.last_refill).as_secs_f64();
+            state.tokens = (state.tokens + elapsed * self.bytes_per_sec).min(self.bytes_per_sec);
+            state.last_refill = now;
+            state.tokens -= bytes as f64;
+            
+            if state.tokens < 0.0 {
+                Duration::from_secs_f64(-state.tokens / self.bytes_per_sec)
+            } else {
+                Duration::ZERO
+            }
+        };
+        
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_acquire_within_burst_does_not_wait() {
+        let limiter = BandwidthLimiter::new(1_000_000);
+        let start = Instant::now();
+        limiter.acquire(1000).await;
+        assert!(start.elapsed() < Duration::from_millis(50));
+    }
+
+    #[tokio::test]
+    async fn test_acquire_over_budget_waits() {
+        let limiter = BandwidthLimiter::new(10_000);
+        let start = Instant::now();
+        // One second of burst, then another 2000 bytes at 10KB/s = ~200ms
+        limiter.acquire(10_000).await;
+        limiter.acquire(2_000).await;
+        assert!(start.elapsed() >= Duration::from_millis(150));
+    }
+}
@@ -0,0 +1,622 @@
+use crate::compression::{Compression, ENCODING_METADATA_KEY};
+use crate::error::{Error, Result};
+use crate::progress::{NoopProgress, ProgressReporter};
+use crate::throttle::BandwidthLimiter;
+use bytes::Bytes;
+use futures::channel::mpsc;
+use futures::{SinkExt, StreamExt};
+use lakefs_api::download::download_objects;
+use lakefs_api::{Checksum, DownloadEvent, DownloadOptions, DownloadRequest, LakeFSApi, UploadOptions, models::ObjectStats};
+use md5::Md5;
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use std::sync::Arc;
+use tokio::fs;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+/// Size of the chunks uploads are split into when they are throttled or observed.
+pub const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Split `data` into `CHUNK_SIZE` pieces for a streaming upload body, waiting
+/// on `limiter` (if any) and calling `on_chunk` as each piece is handed off.
+pub fn upload_chunks<F>(
+    data: Bytes,
+    limiter: Option<Arc<BandwidthLimiter>>,
+    on_chunk: F,
+) -> mpsc::Receiver<std::io::Result<Bytes>>
+where
+    F: Fn(usize) + Send + 'static,
+{
+    let (mut tx, rx) = mpsc::channel(4);
+
+    tokio::spawn(async move {
+        let mut offset = 0;
+        while offset < data.len() {
+            let end = (offset + CHUNK_SIZE).min(data.len());
+            if let Some(limiter) = &limiter {
+                limiter.acquire(end - offset).await;
+            }
+            if tx.send(Ok(data.slice(offset..end))).await.is_err() {
+                break;
+            }
+            on_chunk(end - offset);
+            offset = end;
+        }
+    });
+
+    rx
+}
+
+/// Read `file`, or any other reader, in `CHUNK_SIZE` pieces for a streaming
+/// upload body, with the same throttling and reporting as `upload_chunks`,
+/// so only a few chunks of it are in memory at a time.
+pub fn file_chunks<R, F>(
+    mut file: R,
+    limiter: Option<Arc<BandwidthLimiter>>,
+    on_chunk: F,
+) -> mpsc::Receiver<std::io::Result<Bytes>>
+where
+    R: AsyncRead + Unpin + Send + 'static,
+    F: Fn(usize) + Send + 'static,
+{
+    let (mut tx, rx) = mpsc::channel(4);
+
+    tokio::spawn(async move {
+        loop {
+            let mut buffer = vec![0; CHUNK_SIZE];
+            let read = match file.read(&mut buffer).await {
+                Ok(0) => break,
+                Ok(read) => read,
+                Err(e) => {
+                    let _ = tx.send(Err(e)).await;
+                    break;
+                }
+            };
+            buffer.truncate(read);
+            if let Some(limiter) = &limiter {
+                limiter.acquire(read).await;
+            }
+            if tx.send(Ok(Bytes::from(buffer))).await.is_err() {
+                break;
+            }
+            on_chunk(read);
+        }
+    });
+
+    rx
+}
+
+/// Files up to this size are uploaded from memory with `upload`, so the
+/// client's retry policy applies; larger ones are streamed from disk, or
+/// with the `mmap` feature sent from a memory map of the file.
+pub const IN_MEMORY_UPLOAD_LIMIT: u64 = 16 * 1024 * 1024;
+
+/// Upload the file at `source` to `path`, reporting progress under `label`.
+/// Large files are never read into memory whole. With `compression`, the
+/// file is compressed first and the encoding recorded in the object's
+/// metadata, so `download` undoes it.
+#[allow(clippy::too_many_arguments)]
+pub async fn upload_file(
+    client: &dyn LakeFSApi,
+    repository: &str,
+    branch: &str,
+    path: &str,
+    source: &Path,
+    label: &str,
+    options: &UploadOptions,
+    compression: Option<Compression>,
+    limiter: Option<Arc<BandwidthLimiter>>,
+    reporter: &Arc<dyn ProgressReporter>,
+) -> Result<ObjectStats> {
+    let mut options = options.clone();
+    // Deleted once dropped, after the upload
+    let encoded = match compression {
+        Some(compression) => {
+            options.metadata.insert(ENCODING_METADATA_KEY.to_string(), compression.to_string());
+            let source = source.to_path_buf();
+            let encoded = tokio::task::spawn_blocking(move || compression.encode_file(&source))
+                .await
+                .map_err(|e| Error::Sync(e.to_string()))??;
+            Some(encoded)
+        }
+        None => None,
+    };
+    let source = encoded.as_ref().map_or(source, |encoded| encoded.path());
+    let options = &options;
+
+    let file = fs::File::open(source).await?;
+    let size = file.metadata().await?.len();
+    if size <= IN_MEMORY_UPLOAD_LIMIT {
+        let data = fs::read(source).await?;
+        return upload(client, repository, branch, path, Bytes::from(data), label, options, limiter, reporter).await;
+    }
+
+    #[cfg(feature = "mmap")]
+    {
+        let data = map_file(&file.into_std().await)?;
+        upload(client, repository, branch, path, data, label, options, limiter, reporter).await
+    }
+    #[cfg(not(feature = "mmap"))]
+    {
+        reporter.file_started(label, size);
+        let progress = reporter.clone();
+        let owned_label = label.to_string();
+        let body = file_chunks(file, limiter, move |n| progress.bytes_transferred(&owned_label, n as u64));
+        let stats = client.upload_object_stream_with(repository, branch, path, Box::pin(body), options).await?;
+        reporter.file_finished(label);
+        Ok(stats)
+    }
+}
+
+/// Upload everything `reader` yields to `path`, such as standard input,
+/// reporting progress under `label` against `size_hint` when the size is
+/// known beforehand. The body can only be read once, so a failed request is
+/// not retried.
+#[allow(clippy::too_many_arguments)]
+pub async fn upload_reader<R>(
+    client: &dyn LakeFSApi,
+    repository: &str,
+    branch: &str,
+    path: &str,
+    reader: R,
+    size_hint: Option<u64>,
+    label: &str,
+    options: &UploadOptions,
+    limiter: Option<Arc<BandwidthLimiter>>,
+    reporter: &Arc<dyn ProgressReporter>,
+) -> Result<ObjectStats>
+where
+    R: AsyncRead + Unpin + Send + 'static,
+{
+    reporter.file_started(label, size_hint.unwrap_or_default());
+    let progress = reporter.clone();
+    let owned_label = label.to_string();
+    let body = file_chunks(reader, limiter, move |n| progress.bytes_transferred(&owned_label, n as u64));
+    let stats = client.upload_object_stream_with(repository, branch, path, Box::pin(body), options).await?;
+    reporter.file_finished(label);
+    Ok(stats)
+}
+
+/// Map `file` into memory read-only. Chunks of the result are slices of the
+/// map, so uploading it copies nothing into buffers of our own and pages
+/// are read in, and dropped again, by the kernel as they are sent.
+#[cfg(feature = "mmap")]
+pub fn map_file(file: &std::fs::File) -> Result<Bytes> {
+    // SAFETY: the map is only read. A file truncated by another process
+    // while it is mapped can still fault on access, which is why this is
+    // behind a feature for platforms and workloads where files are not
+    // changed during uploads.
+    let map = unsafe { memmap2::Mmap::map(file)? };
+    Ok(Bytes::from_owner(map))
+}
+
+/// Upload `data` to `path`, reporting progress under `label`.
+///
+/// The body is only streamed when it is throttled or progress is displayed;
+/// otherwise a single request is sent so the client's retry policy applies.
+#[allow(clippy::too_many_arguments)]
+pub async fn upload(
+    client: &dyn LakeFSApi,
+    repository: &str,
+    branch: &str,
+    path: &str,
+    data: Bytes,
+    label: &str,
+    options: &UploadOptions,
+    limiter: Option<Arc<BandwidthLimiter>>,
+    reporter: &Arc<dyn ProgressReporter>,
+) -> Result<ObjectStats> {
+    reporter.file_started(label, data.len() as u64);
+
+    let stats = if limiter.is_some() || reporter.is_enabled() {
+        let reporter = reporter.clone();
+        let owned_label = label.to_string();
+        let body = upload_chunks(data, limiter, move |n| {
+            reporter.bytes_transferred(&owned_label, n as u64)
+        });
+        client.upload_object_stream_with(repository, branch, path, Box::pin(body), options).await?
+    } else {
+        client.upload_object_with(repository, branch, path, data, options).await?
+    };
+
+    reporter.file_finished(label);
+    Ok(stats)
+}
+
+pub use lakefs_api::download::{temp_path, TEMP_SUFFIX};
+
+/// Stream the object described by `stats` into `destination`, reporting
+/// progress under `label`.
+///
+/// Data is written to `temp_path(destination)`, synced to disk and checked
+/// against the object checksum before being renamed over `destination`, so
+/// an interrupted or corrupt download never looks complete. Objects whose
+/// metadata names a [`Compression`] are decompressed on the way. Returns
+/// the number of bytes written.
+#[allow(clippy::too_many_arguments)]
+pub async fn download(
+    client: &dyn LakeFSApi,
+    repository: &str,
+    reference: &str,
+    stats: &ObjectStats,
+    destination: &Path,
+    label: &str,
+    limiter: Option<Arc<BandwidthLimiter>>,
+    reporter: &Arc<dyn ProgressReporter>,
+) -> Result<u64> {
+    let written = download_if_changed(client, repository, reference, stats, destination, None, label, limiter, reporter).await?;
+    Ok(written.unwrap_or_default())
+}
+
+/// Like `download`, but when `current` is the checksum of what is already
+/// at `destination` the server is asked to send the object only if its
+/// checksum differs. Returns `None`, leaving `destination` alone, when it
+/// does not.
+#[allow(clippy::too_many_arguments)]
+pub async fn download_if_changed(
+    client: &dyn LakeFSApi,
+    repository: &str,
+    reference: &str,
+    stats: &ObjectStats,
+    destination: &Path,
+    current: Option<&Checksum>,
+    label: &str,
+    limiter: Option<Arc<BandwidthLimiter>>,
+    reporter: &Arc<dyn ProgressReporter>,
+) -> Result<Option<u64>> {
+    let compression = Compression::of(stats)?;
+    // A compressed object is decoded at the temporary path before it takes
+    // the destination's place
+    let target = match compression {
+        Some(_) => temp_path(destination),
+        None => destination.to_path_buf(),
+    };
+    let request = DownloadRequest {
+        path: stats.path.clone(),
+        destination: target.clone(),
+        checksum: Some(stats.checksum.clone()),
+        if_none_match: current.cloned(),
+    };
+    let options = DownloadOptions { concurrency: 1, ..Default::default() };
+    let mut events = std::pin::pin!(download_objects(client, repository, reference, vec![request], options));
+    let mut outcome = Err(Error::Sync(format!("Download of {} ended without a result", stats.path)));
+    while let Some(event) = events.next().await {
+        match event {
+            DownloadEvent::Started { .. } => reporter.file_started(label, stats.size_bytes.max(0) as u64),
+            DownloadEvent::Progress { bytes, .. } => {
+                // Applied as the consumer, which the download waits for
+                // once enough events are queued
+                if let Some(limiter) = &limiter {
+                    limiter.acquire(bytes as usize).await;
+                }
+                reporter.bytes_transferred(label, bytes);
+            }
+            DownloadEvent::Retrying { attempt, error, .. } => {
+                tracing::debug!(path = %stats.path, attempt, %error, "resuming download");
+            }
+            DownloadEvent::Finished { bytes, .. } => {
+                reporter.file_finished(label);
+                outcome = Ok(Some(bytes));
+            }
+            DownloadEvent::Unchanged { .. } => outcome = Ok(None),
+            DownloadEvent::Failed { error, .. } => outcome = Err(downloaded(error)),
+        }
+    }
+
+    match (compression, outcome?) {
+        (Some(compression), Some(_)) => {
+            let result = async {
+                let encoded = target.clone();
+                let written = tokio::task::spawn_blocking(move || compression.decode_in_place(&encoded))
+                    .await
+                    .map_err(|e| Error::Sync(e.to_string()))??;
+                fs::rename(&target, destination).await?;
+                Ok(Some(written))
+            }
+            .await;
+            if result.is_err() {
+                let _ = fs::remove_file(&target).await;
+            }
+            result
+        }
+        (_, written) => Ok(written),
+    }
+}
+
+/// A failed download's error, with checksum mismatches reported as this
+/// crate's own.
+fn downloaded(error: lakefs_api::Error) -> Error {
+    match error {
+        lakefs_api::Error::ChecksumMismatch { path, expected, actual } => Error::ChecksumMismatch { path, expected, actual },
+        error => error.into(),
+    }
+}
+
+/// Write the object described by `stats` to `output`, such as standard
+/// output, returning the number of bytes written.
+///
+/// Content is streamed as it arrives and only checked against the object
+/// checksum at the end, so an error can follow output that was already
+/// consumed. Compressed objects are downloaded into a temporary file first
+/// and written out once decompressed.
+pub async fn download_to_writer<W>(
+    client: &dyn LakeFSApi,
+    repository: &str,
+    reference: &str,
+    stats: &ObjectStats,
+    output: &mut W,
+) -> Result<u64>
+where
+    W: AsyncWrite + Unpin,
+{
+    if Compression::of(stats)?.is_some() {
+        let decoded = tempfile::NamedTempFile::new()?;
+        let reporter: Arc<dyn ProgressReporter> = Arc::new(NoopProgress);
+        download(client, repository, reference, stats, decoded.path(), &stats.path, None, &reporter).await?;
+        let written = tokio::io::copy(&mut fs::File::open(decoded.path()).await?, output).await?;
+        output.flush().await?;
+        return Ok(written);
+    }
+
+    let mut stream = client.download_object_stream(repository, reference, &stats.path).await?;
+    let mut hasher = Hasher::default();
+    let mut written = 0u64;
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk?;
+        output.write_all(&chunk).await?;
+        hasher.update(&chunk);
+        written += chunk.len() as u64;
+    }
+    output.flush().await?;
+    hasher.verify(&stats.path, &stats.checksum)?;
+    Ok(written)
+}
+
+/// MD5 and SHA-256 of the file at `path`, to check against a [`Checksum`]
+/// with [`Checksum::matches`]. Blocks while the file is read.
+pub fn file_digests(path: &Path) -> Result<[Checksum; 2]> {
+    Ok(Hasher::from_file(path)?.finish())
+}
+
+/// Computes the digests lakeFS checksums may be expressed in.
+#[derive(Default)]
+pub(crate) struct Hasher {
+    md5: Md5,
+    sha256: Sha256,
+}
+
+impl Hasher {
+    pub(crate) fn update(&mut self, data: &[u8]) {
+        self.md5.update(data);
+        self.sha256.update(data);
+    }
+
+    /// Hash the file at `path`, reading it `CHUNK_SIZE` bytes at a time.
+    /// Blocks, so async code should run it with `spawn_blocking`.
+    pub(crate) fn from_file(path: &Path) -> Result<Self> {
+        let mut file = std::fs::File::open(path)?;
+        let mut hasher = Self::default();
+        let mut buffer = vec![0; CHUNK_SIZE];
+        loop {
+            match std::io::Read::read(&mut file, &mut buffer) {
+                Ok(0) => return Ok(hasher),
+                Ok(read) => hasher.update(&buffer[..read]),
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
+    /// The MD5 and SHA-256 of everything hashed so far.
+    pub(crate) fn finish(self) -> [Checksum; 2] {
+        [
+            Checksum::Md5(format!("{:x}", self.md5.finalize())),
+            Checksum::Sha256(format!("{:x}", self.sha256.finalize())),
+        ]
+    }
+
+    /// Whether the content hashed so far has checksum `expected`; `None` when
+    /// it cannot be recomputed.
+    pub(crate) fn matches(self, expected: &Checksum) -> Option<bool> {
+        self.finish().iter().find_map(|digest| expected.matches(digest))
+    }
+
+    /// Compare against `expected` when it is an MD5 (the usual lakeFS ETag)
+    /// or SHA-256 digest. Multipart ETags and other formats cannot be
+    /// recomputed locally and are accepted as-is.
+    fn verify(self, path: &str, expected: &Checksum) -> Result<()> {
+        for actual in self.finish() {
+            if expected.matches(&actual) == Some(false) {
+                return Err(Error::ChecksumMismatch {
+                    path: path.to_string(),
+                    expected: expected.to_string(),
+                    actual: actual.to_string(),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn test_upload_chunks_preserves_content() {
+        let data = Bytes::from(vec![7u8; CHUNK_SIZE * 2 + 10]);
+        let limiter = Arc::new(BandwidthLimiter::new(u64::MAX));
+
+        let chunks: Vec<Bytes> = upload_chunks(data.clone(), Some(limiter), |_| {})
+            .map(|chunk| chunk.unwrap())
+            .collect()
+            .await;
+
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks.concat(), data.to_vec());
+    }
+
+    #[tokio::test]
+    async fn test_upload_chunks_reports_each_chunk() {
+        let data = Bytes::from(vec![1u8; CHUNK_SIZE + 1]);
+        let seen = Arc::new(AtomicUsize::new(0));
+        let counter = seen.clone();
+
+        let chunks: Vec<_> = upload_chunks(data, None, move |n| {
+            counter.fetch_add(n, Ordering::SeqCst);
+        })
+        .collect()
+        .await;
+
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(seen.load(Ordering::SeqCst), CHUNK_SIZE + 1);
+    }
+
+    #[tokio::test]
+    async fn test_download_decompresses_encoded_objects() {
+        use crate::compression::ENCODING_METADATA_KEY;
+        use std::collections::HashMap;
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let source = dir.path().join("source.csv");
+        std::fs::write(&source, "id,value\n1,abc\n").unwrap();
+        let encoded = std::fs::read(Compression::Gzip.encode_file(&source).unwrap().path()).unwrap();
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repositories/repo/refs/main/objects"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(encoded.clone()))
+            .mount(&mock_server)
+            .await;
+        let client = lakefs_api::LakeFSClient::new(mock_server.uri(), "Bearer test-token");
+
+        let mut stats = lakefs_test_utils::fixtures::test_object_stats();
+        stats.checksum = format!("{:x}", Sha256::digest(&encoded)).as_str().into();
+        stats.size_bytes = encoded.len() as i64;
+        stats.metadata = Some(HashMap::from([(ENCODING_METADATA_KEY.to_string(), "gzip".to_string())]));
+        let reporter: Arc<dyn ProgressReporter> = Arc::new(NoopProgress);
+
+        let destination = dir.path().join("data.csv");
+        let written = download(&client, "repo", "main", &stats, &destination, "data.csv", None, &reporter).await.unwrap();
+        assert_eq!(written, 15);
+        assert_eq!(std::fs::read_to_string(&destination).unwrap(), "id,value\n1,abc\n");
+        assert!(!temp_path(&destination).exists());
+
+        let mut output = Vec::new();
+        download_to_writer(&client, "repo", "main", &stats, &mut output).await.unwrap();
+        assert_eq!(output, b"id,value\n1,abc\n");
+    }
+
+    #[tokio::test]
+    async fn test_upload_reader_streams_everything_read() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .and(path("/repositories/repo/branches/main/objects"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(lakefs_test_utils::fixtures::test_object_stats()))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        let client = lakefs_api::LakeFSClient::new(mock_server.uri(), "Bearer test-token");
+        let reporter: Arc<dyn ProgressReporter> = Arc::new(NoopProgress);
+
+        let data: Vec<u8> = (0..CHUNK_SIZE * 2 + 10).map(|i| i as u8).collect();
+        let reader = std::io::Cursor::new(data.clone());
+        let options = UploadOptions::default();
+        upload_reader(&client, "repo", "main", "data.bin", reader, None, "stdin", &options, None, &reporter)
+            .await
+            .unwrap();
+
+        let requests = mock_server.received_requests().await.unwrap();
+        assert_eq!(requests[0].body, data);
+    }
+
+    #[tokio::test]
+    async fn test_download_to_writer_verifies_checksum() {
+        let server = lakefs_test_utils::scenario::MockLakeFS::new()
+            .with_repo("repo")
+            .with_branch("main")
+            .with_object("a.csv", "a,b\n")
+            .start()
+            .await;
+        let client = lakefs_api::LakeFSClient::new(server.uri(), "Bearer test-token");
+        let mut stats = client.get_object("repo", "main", "a.csv").await.unwrap();
+
+        let mut output = Vec::new();
+        assert_eq!(download_to_writer(&client, "repo", "main", &stats, &mut output).await.unwrap(), 4);
+        assert_eq!(output, b"a,b\n");
+
+        stats.checksum = Checksum::Md5("0".repeat(32));
+        let result = download_to_writer(&client, "repo", "main", &stats, &mut Vec::new()).await;
+        assert!(matches!(result, Err(Error::ChecksumMismatch { .. })));
+    }
+
+    #[test]
+    fn test_temp_path() {
+        assert_eq!(temp_path(Path::new("data/file.csv")), PathBuf::from("data/file.csv.lakectl-tmp"));
+    }
+
+    #[tokio::test]
+    async fn test_file_chunks_preserves_content() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("data.bin");
+        let data: Vec<u8> = (0..CHUNK_SIZE * 2 + 10).map(|i| i as u8).collect();
+        std::fs::write(&path, &data).unwrap();
+        let seen = Arc::new(AtomicUsize::new(0));
+        let counter = seen.clone();
+
+        let file = fs::File::open(&path).await.unwrap();
+        let chunks: Vec<Bytes> = file_chunks(file, None, move |n| {
+            counter.fetch_add(n, Ordering::SeqCst);
+        })
+        .map(|chunk| chunk.unwrap())
+        .collect()
+        .await;
+
+        assert_eq!(chunks.concat(), data);
+        assert_eq!(seen.load(Ordering::SeqCst), data.len());
+
+        let mut hasher = Hasher::default();
+        hasher.update(&data);
+        assert_eq!(Hasher::from_file(&path).unwrap().finish(), hasher.finish());
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn test_map_file_preserves_content() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("data.bin");
+        let data: Vec<u8> = (0..CHUNK_SIZE * 2 + 10).map(|i| i as u8).collect();
+        std::fs::write(&path, &data).unwrap();
+
+        let mapped = map_file(&std::fs::File::open(&path).unwrap()).unwrap();
+        assert_eq!(mapped, data);
+        assert_eq!(mapped.slice(CHUNK_SIZE..CHUNK_SIZE + 3), data[CHUNK_SIZE..CHUNK_SIZE + 3]);
+    }
+
+    #[test]
+    fn test_hasher_verify() {
+        let hasher = || {
+            let mut hasher = Hasher::default();
+            hasher.update(b"hello");
+            hasher
+        };
+
+        // md5("hello") and sha256("hello")
+        assert!(hasher().verify("f", &Checksum::parse("5d41402abc4b2a76b9719d911017c592")).is_ok());
+        assert!(hasher().verify("f", &Checksum::parse("\"5d41402abc4b2a76b9719d911017c592\"")).is_ok());
+        assert!(hasher().verify("f", &Checksum::parse("sha256:2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824")).is_ok());
+        assert!(matches!(
+            hasher().verify("f", &Checksum::parse("00000000000000000000000000000000")),
+            Err(Error::ChecksumMismatch { .. })
+        ));
+        // Multipart ETags can't be recomputed
+        assert!(hasher().verify("f", &Checksum::parse("5d41402abc4b2a76b9719d911017c592-3")).is_ok());
+    }
+}
@@ -0,0 +1,155 @@
+use crate::api_error;
+use crate::runtime::spawn;
+use lakefs_api::models::ObjectStats;
+use lakefs_api::{ClientConfig, LakeFSClient, PaginationParams};
+use lakefs_auth::basic::BasicAuth;
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Client for a lakeFS server. Without a key pair, credentials are read from
+/// `LAKECTL_CREDENTIALS_ACCESS_KEY_ID` and
+/// `LAKECTL_CREDENTIALS_SECRET_ACCESS_KEY`.
+#[pyclass(name = "LakeFSClient", module = "lakefs_rust", frozen)]
+pub struct PyLakeFSClient {
+    pub(crate) inner: LakeFSClient,
+}
+
+#[pymethods]
+impl PyLakeFSClient {
+    #[new]
+    #[pyo3(signature = (endpoint, access_key_id=None, secret_access_key=None))]
+    fn new(endpoint: String, access_key_id: Option<String>, secret_access_key: Option<String>) -> PyResult<Self> {
+        let auth = match (access_key_id, secret_access_key) {
+            (Some(access_key_id), Some(secret_access_key)) => BasicAuth::new(access_key_id, secret_access_key),
+            (None, None) => BasicAuth::from_env().map_err(|e| api_error(e.into()))?,
+            _ => return Err(crate::LakeFSError::new_err("access_key_id and secret_access_key go together")),
+        };
+        let inner = LakeFSClient::from_provider(endpoint, Arc::new(auth), ClientConfig::default()).map_err(api_error)?;
+        Ok(Self { inner })
+    }
+
+    /// IDs of every repository
+    fn list_repositories<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.inner.clone();
+        spawn(py, async move {
+            let mut ids = Vec::new();
+            let mut params = PaginationParams::new();
+            loop {
+                let page = client.list_repositories_with(&params).await.map_err(api_error)?;
+                ids.extend(page.results.into_iter().map(|repository| repository.id));
+                match page.pagination.next_offset {
+                    Some(next) if page.pagination.has_more => params.after = Some(next),
+                    _ => return Ok(ids),
+                }
+            }
+        })
+    }
+
+    /// Every object under `prefix` at `reference`, following all pages
+    #[pyo3(signature = (repository, reference, prefix=None))]
+    fn list_objects<'py>(
+        &self,
+        py: Python<'py>,
+        repository: String,
+        reference: String,
+        prefix: Option<String>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.inner.clone();
+        spawn(py, async move {
+            let mut objects = Vec::new();
+            let mut params = PaginationParams { prefix, ..Default::default() };
+            loop {
+                let page = client.list_objects_with(&repository, &reference, &params).await.map_err(api_error)?;
+                objects.extend(page.results.into_iter().map(PyObjectStats::from));
+                match page.pagination.next_offset {
+                    Some(next) if page.pagination.has_more => params.after = Some(next),
+                    _ => return Ok(objects),
+                }
+            }
+        })
+    }
+
+    fn get_object<'py>(&self, py: Python<'py>, repository: String, reference: String, path: String) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.inner.clone();
+        spawn(py, async move {
+            let stats = client.get_object(&repository, &reference, &path).await.map_err(api_error)?;
+            Ok(PyObjectStats::from(stats))
+        })
+    }
+
+    /// Content of the object, as `bytes`
+    fn download_object<'py>(&self, py: Python<'py>, repository: String, reference: String, path: String) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.inner.clone();
+        spawn(py, async move {
+            let data = client.download_object(&repository, &reference, &path).await.map_err(api_error)?;
+            Ok(Python::with_gil(|py| PyBytes::new(py, &data).unbind()))
+        })
+    }
+
+    fn upload_object<'py>(
+        &self,
+        py: Python<'py>,
+        repository: String,
+        branch: String,
+        path: String,
+        data: Vec<u8>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.inner.clone();
+        spawn(py, async move {
+            let stats = client.upload_object(&repository, &branch, &path, data.into()).await.map_err(api_error)?;
+            Ok(PyObjectStats::from(stats))
+        })
+    }
+
+    fn delete_object<'py>(&self, py: Python<'py>, repository: String, branch: String, path: String) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.inner.clone();
+        spawn(py, async move {
+            client.delete_object(&repository, &branch, &path).await.map_err(api_error)
+        })
+    }
+
+    /// Commit the branch's staged changes, returning the commit ID
+    fn commit<'py>(&self, py: Python<'py>, repository: String, branch: String, message: String) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.inner.clone();
+        spawn(py, async move {
+            let commit = client.commit(&repository, &branch, &message).await.map_err(api_error)?;
+            Ok(commit.id)
+        })
+    }
+
+    fn __repr__(&self) -> String {
+        format!("LakeFSClient('{}')", self.inner.base_url())
+    }
+}
+
+#[pyclass(name = "ObjectStats", module = "lakefs_rust", frozen, get_all)]
+pub struct PyObjectStats {
+    pub path: String,
+    pub checksum: String,
+    pub size_bytes: i64,
+    pub mtime: chrono::DateTime<chrono::Utc>,
+    pub metadata: HashMap<String, String>,
+    pub content_type: Option<String>,
+}
+
+#[pymethods]
+impl PyObjectStats {
+    fn __repr__(&self) -> String {
+        format!("ObjectStats(path='{}', size_bytes={}, checksum='{}')", self.path, self.size_bytes, self.checksum)
+    }
+}
+
+impl From<ObjectStats> for PyObjectStats {
+    fn from(stats: ObjectStats) -> Self {
+        Self {
+            path: stats.path,
+            checksum: stats.checksum.to_string(),
+            size_bytes: stats.size_bytes,
+            mtime: stats.mtime,
+            metadata: stats.metadata.unwrap_or_default(),
+            content_type: stats.content_type,
+        }
+    }
+}
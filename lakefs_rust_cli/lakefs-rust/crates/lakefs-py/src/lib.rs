@@ -0,0 +1,59 @@
+//! Python bindings for the lakeFS client and the local sync engine.
+//!
+//! Built with maturin into the `lakefs_rust` module. Methods that talk to
+//! lakeFS return awaitables, run on a Tokio runtime shared by the module:
+//!
+//! ```python
+//! import asyncio
+//! from lakefs_rust import LakeFSClient, LakeFSUri, SyncManager
+//!
+//! async def main():
+//!     client = LakeFSClient("http://localhost:8000/api/v1", "AKIA...", "secret")
+//!     manager = SyncManager(client, parallelism=16)
+//!     result = await manager.pull("./data", LakeFSUri.parse("lakefs://repo/main/data"))
+//!     print(result.downloaded, result.errors)
+//!
+//! asyncio.run(main())
+//! ```
+
+use pyo3::create_exception;
+use pyo3::exceptions::PyException;
+use pyo3::prelude::*;
+
+mod client;
+mod runtime;
+mod sync;
+mod uri;
+
+pub use client::{PyLakeFSClient, PyObjectStats};
+pub use sync::{PySyncManager, PySyncResult};
+pub use uri::PyLakeFSUri;
+
+create_exception!(lakefs_rust, LakeFSError, PyException, "A lakeFS request or sync failed.");
+create_exception!(lakefs_rust, NotFoundError, LakeFSError, "The repository, reference or object does not exist.");
+
+pub(crate) fn api_error(error: lakefs_api::Error) -> PyErr {
+    match error {
+        lakefs_api::Error::NotFound(message) => NotFoundError::new_err(message),
+        error => LakeFSError::new_err(error.to_string()),
+    }
+}
+
+pub(crate) fn local_error(error: lakefs_local::Error) -> PyErr {
+    match error {
+        lakefs_local::Error::Api(error) => api_error(error),
+        error => LakeFSError::new_err(error.to_string()),
+    }
+}
+
+#[pymodule]
+fn lakefs_rust(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyLakeFSClient>()?;
+    m.add_class::<PyObjectStats>()?;
+    m.add_class::<PyLakeFSUri>()?;
+    m.add_class::<PySyncManager>()?;
+    m.add_class::<PySyncResult>()?;
+    m.add("LakeFSError", m.py().get_type::<LakeFSError>())?;
+    m.add("NotFoundError", m.py().get_type::<NotFoundError>())?;
+    runtime::register(m)
+}
@@ -0,0 +1,103 @@
+//! Running client calls on a Tokio runtime and awaiting them from asyncio.
+
+use pyo3::prelude::*;
+use pyo3::types::{PyCFunction, PyDict, PyTuple};
+use pyo3::IntoPyObjectExt;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::OnceLock;
+use std::time::Duration;
+use tokio::runtime::Runtime;
+
+/// Runtime threads holding or waiting for the GIL
+static IN_PYTHON: AtomicUsize = AtomicUsize::new(0);
+/// Set once the interpreter starts shutting down
+static SHUTTING_DOWN: AtomicBool = AtomicBool::new(false);
+
+fn runtime() -> &'static Runtime {
+    static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+    RUNTIME.get_or_init(|| {
+        tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .thread_name("lakefs-rust")
+            .build()
+            .expect("failed to start the Tokio runtime")
+    })
+}
+
+/// Take the GIL on a runtime thread, unless the interpreter is shutting
+/// down. A thread still holding or waiting for it once the interpreter is
+/// finalizing crashes the process, which otherwise happens whenever a
+/// script exits right after its last `await`.
+fn with_python<R>(f: impl FnOnce(Python<'_>) -> R) -> Option<R> {
+    IN_PYTHON.fetch_add(1, Ordering::SeqCst);
+    let result = (!SHUTTING_DOWN.load(Ordering::SeqCst)).then(|| Python::with_gil(f));
+    IN_PYTHON.fetch_sub(1, Ordering::SeqCst);
+    result
+}
+
+/// Registered with `atexit`: keep runtime threads away from the GIL from
+/// now on and wait for those already using it.
+#[pyfunction]
+fn shutdown(py: Python<'_>) {
+    SHUTTING_DOWN.store(true, Ordering::SeqCst);
+    py.allow_threads(|| {
+        while IN_PYTHON.load(Ordering::SeqCst) > 0 {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    });
+}
+
+pub(crate) fn register(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    let atexit = module.py().import("atexit")?;
+    atexit.call_method1("register", (wrap_pyfunction!(shutdown, module)?,))?;
+    Ok(())
+}
+
+/// Run `future` on the module's runtime, returning an asyncio future of its
+/// result on the running event loop. Cancelling the asyncio future aborts
+/// the task.
+pub(crate) fn spawn<'py, F, T>(py: Python<'py>, future: F) -> PyResult<Bound<'py, PyAny>>
+where
+    F: Future<Output = PyResult<T>> + Send + 'static,
+    T: for<'a> IntoPyObject<'a> + Send + 'static,
+{
+    let event_loop = py.import("asyncio")?.call_method0("get_running_loop")?;
+    let awaitable = event_loop.call_method0("create_future")?;
+    let (event_loop, target) = (event_loop.unbind(), awaitable.clone().unbind());
+
+    let task = runtime().spawn(async move {
+        let result = future.await;
+        with_python(move |py| {
+            let (ok, value) = match result.and_then(|value| value.into_py_any(py)) {
+                Ok(value) => (true, value),
+                Err(error) => (false, error.into_value(py).into_any()),
+            };
+            let settle = wrap_pyfunction!(settle, py).map(Bound::unbind);
+            // Fails only once the loop is closed, with nobody left to tell
+            if let Ok(settle) = settle {
+                let _ = event_loop.call_method1(py, "call_soon_threadsafe", (settle, target, ok, value));
+            }
+        });
+    });
+
+    let abort = task.abort_handle();
+    let on_done = PyCFunction::new_closure(py, None, None, move |args: &Bound<'_, PyTuple>, _: Option<&Bound<'_, PyDict>>| {
+        if args.get_item(0)?.call_method0("cancelled")?.is_truthy()? {
+            abort.abort();
+        }
+        PyResult::Ok(())
+    })?;
+    awaitable.call_method1("add_done_callback", (on_done,))?;
+    Ok(awaitable)
+}
+
+/// Complete `future` with `value`, unless it was cancelled meanwhile.
+#[pyfunction]
+fn settle(future: &Bound<'_, PyAny>, ok: bool, value: &Bound<'_, PyAny>) -> PyResult<()> {
+    if future.call_method0("done")?.is_truthy()? {
+        return Ok(());
+    }
+    future.call_method1(if ok { "set_result" } else { "set_exception" }, (value,))?;
+    Ok(())
+}
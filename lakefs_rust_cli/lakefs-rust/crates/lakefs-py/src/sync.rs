@@ -0,0 +1,127 @@
+use crate::client::PyLakeFSClient;
+use crate::local_error;
+use crate::runtime::spawn;
+use crate::uri::PyLakeFSUri;
+use lakefs_local::{Compression, MultiProgressReporter, SyncConfig, SyncManager, SyncResult};
+use pyo3::prelude::*;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Keeps local directories in step with lakeFS branches, the engine behind
+/// `lakectl local`. Each directory's index lives in its `.lakectl` folder.
+#[pyclass(name = "SyncManager", module = "lakefs_rust", frozen)]
+pub struct PySyncManager {
+    inner: Arc<SyncManager>,
+}
+
+#[derive(Clone, Copy)]
+enum Mode {
+    Sync,
+    Push,
+    Pull,
+}
+
+#[pymethods]
+impl PySyncManager {
+    #[new]
+    #[pyo3(signature = (
+        client,
+        parallelism=10,
+        delete_extraneous=false,
+        compression=None,
+        max_errors=None,
+        show_progress=false,
+    ))]
+    fn new(
+        client: &PyLakeFSClient,
+        parallelism: usize,
+        delete_extraneous: bool,
+        compression: Option<&str>,
+        max_errors: Option<usize>,
+        show_progress: bool,
+    ) -> PyResult<Self> {
+        let compression = compression.map(str::parse::<Compression>).transpose().map_err(local_error)?;
+        let config = SyncConfig {
+            parallelism,
+            delete_extraneous,
+            compression,
+            max_errors,
+            ..Default::default()
+        };
+        let mut manager = SyncManager::new(Arc::new(client.inner.clone()), config);
+        if show_progress {
+            manager = manager.with_progress(Arc::new(MultiProgressReporter::new()));
+        }
+        Ok(Self { inner: Arc::new(manager) })
+    }
+
+    /// Upload local changes and download remote ones
+    fn sync<'py>(&self, py: Python<'py>, local_path: PathBuf, remote: PyLakeFSUri) -> PyResult<Bound<'py, PyAny>> {
+        self.run(py, Mode::Sync, local_path, remote)
+    }
+
+    /// Upload local changes without downloading anything
+    fn push<'py>(&self, py: Python<'py>, local_path: PathBuf, remote: PyLakeFSUri) -> PyResult<Bound<'py, PyAny>> {
+        self.run(py, Mode::Push, local_path, remote)
+    }
+
+    /// Download remote changes without uploading anything
+    fn pull<'py>(&self, py: Python<'py>, local_path: PathBuf, remote: PyLakeFSUri) -> PyResult<Bound<'py, PyAny>> {
+        self.run(py, Mode::Pull, local_path, remote)
+    }
+}
+
+impl PySyncManager {
+    fn run<'py>(&self, py: Python<'py>, mode: Mode, local_path: PathBuf, remote: PyLakeFSUri) -> PyResult<Bound<'py, PyAny>> {
+        let manager = self.inner.clone();
+        spawn(py, async move {
+            let remote = remote.inner;
+            let result = match mode {
+                Mode::Sync => manager.sync(&local_path, &remote).await,
+                Mode::Push => manager.push(&local_path, &remote).await,
+                Mode::Pull => manager.pull(&local_path, &remote).await,
+            };
+            result.map(PySyncResult::from).map_err(local_error)
+        })
+    }
+}
+
+/// What a sync did. Failed transfers are listed in `errors` as
+/// `(path, message)` pairs rather than raised.
+#[pyclass(name = "SyncResult", module = "lakefs_rust", frozen, get_all)]
+pub struct PySyncResult {
+    pub uploaded: usize,
+    pub downloaded: usize,
+    pub removed: usize,
+    pub unchanged: usize,
+    pub skipped: usize,
+    pub errors: Vec<(String, String)>,
+}
+
+#[pymethods]
+impl PySyncResult {
+    fn __repr__(&self) -> String {
+        format!(
+            "SyncResult(uploaded={}, downloaded={}, removed={}, unchanged={}, skipped={}, errors={})",
+            self.uploaded,
+            self.downloaded,
+            self.removed,
+            self.unchanged,
+            self.skipped,
+            self.errors.len()
+        )
+    }
+}
+
+impl From<SyncResult> for PySyncResult {
+    fn from(result: SyncResult) -> Self {
+        Self {
+            uploaded: result.uploaded,
+            downloaded: result.downloaded,
+            removed: result.removed,
+            unchanged: result.unchanged,
+            skipped: result.skipped,
+            errors: result.errors.into_iter().map(|(path, error)| (path, error.to_string())).collect(),
+        }
+    }
+}
@@ -0,0 +1,64 @@
+use crate::api_error;
+use lakefs_api::LakeFSUri;
+use pyo3::prelude::*;
+
+/// `lakefs://repository/reference[/path]`
+#[pyclass(name = "LakeFSUri", module = "lakefs_rust", frozen, eq)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct PyLakeFSUri {
+    pub(crate) inner: LakeFSUri,
+}
+
+#[pymethods]
+impl PyLakeFSUri {
+    #[new]
+    #[pyo3(signature = (repository, reference, path=None))]
+    fn new(repository: String, reference: String, path: Option<String>) -> Self {
+        let mut inner = LakeFSUri::new(repository, reference);
+        inner.path = path;
+        Self { inner }
+    }
+
+    #[staticmethod]
+    fn parse(uri: &str) -> PyResult<Self> {
+        let inner = uri.parse().map_err(api_error)?;
+        Ok(Self { inner })
+    }
+
+    #[getter]
+    fn repository(&self) -> &str {
+        &self.inner.repository
+    }
+
+    #[getter]
+    fn reference(&self) -> &str {
+        &self.inner.reference
+    }
+
+    #[getter]
+    fn path(&self) -> Option<&str> {
+        self.inner.path.as_deref()
+    }
+
+    fn __str__(&self) -> String {
+        self.inner.to_string()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("LakeFSUri('{}')", self.inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_round_trips() {
+        let uri = PyLakeFSUri::parse("lakefs://repo/main/data/a.csv").unwrap();
+        assert_eq!((uri.repository(), uri.reference(), uri.path()), ("repo", "main", Some("data/a.csv")));
+        assert_eq!(uri.__str__(), "lakefs://repo/main/data/a.csv");
+        assert_eq!(uri, PyLakeFSUri::new("repo".into(), "main".into(), Some("data/a.csv".into())));
+        assert!(PyLakeFSUri::parse("s3://bucket/key").is_err());
+    }
+}
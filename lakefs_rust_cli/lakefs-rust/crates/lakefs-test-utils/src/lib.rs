@@ -2,6 +2,8 @@ use lakefs_api::models::{Repository, Branch, Commit, ObjectStats, PathType};
 use chrono::Utc;
 use std::collections::HashMap;
 
+pub mod scenario;
+
 pub mod fixtures {
     use super::*;
     
@@ -11,6 +13,8 @@ pub mod fixtures {
             storage_namespace: "s3://test-bucket".to_string(),
             default_branch: "main".to_string(),
             creation_date: Utc::now(),
+            read_only: None,
+            storage_id: None,
         }
     }
     
@@ -30,6 +34,8 @@ pub mod fixtures {
             creation_date: Utc::now(),
             meta_range_id: "meta123".to_string(),
             metadata: HashMap::new(),
+            generation: None,
+            version: None,
         }
     }
     
@@ -38,10 +44,12 @@ pub mod fixtures {
             path: "test/file.txt".to_string(),
             path_type: PathType::Object,
             physical_address: "s3://bucket/object".to_string(),
-            checksum: "sha256:123abc".to_string(),
+            checksum: "sha256:123abc".into(),
             size_bytes: 1024,
             mtime: Utc::now(),
             metadata: None,
+            physical_address_expiry: None,
+            content_type: None,
         }
     }
 }
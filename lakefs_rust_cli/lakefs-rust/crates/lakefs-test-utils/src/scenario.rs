@@ -0,0 +1,487 @@
+use chrono::Utc;
+use lakefs_api::models::{Branch, ObjectStats, PathType, Repository, Tag};
+use md5::{Digest, Md5};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use wiremock::matchers::{method, path, path_regex, query_param, query_param_is_missing};
+use wiremock::{Mock, MockServer, Request, Respond, ResponseTemplate};
+
+/// Mocks that must win over every scenario route.
+const ERROR_PRIORITY: u8 = 1;
+/// Catch-alls that only apply when no scenario route matched.
+const FALLBACK_PRIORITY: u8 = 10;
+
+/// Builds a wiremock server that answers like a small, consistent lakeFS
+/// installation.
+///
+/// ```no_run
+/// # async fn example() {
+/// use lakefs_test_utils::scenario::MockLakeFS;
+///
+/// let server = MockLakeFS::new()
+///     .with_repo("repo")
+///     .with_branch("main")
+///     .with_object("data/a.csv", "a,b\n1,2\n")
+///     .with_transient_error("GET", "/repositories/repo", 503, 1)
+///     .start()
+///     .await;
+/// # }
+/// ```
+///
+/// Branches and tags attach to the most recently added repository, objects to
+/// the most recently added branch, and a tag to that branch's head commit. Listings honour `prefix`, `after`, `amount`
+/// and, for objects, `delimiter`; objects carry an MD5 checksum that is also sent as the `ETag`, and
+/// downloads answer 304 when `If-None-Match` names it.
+pub struct MockLakeFS {
+    repos: Vec<RepoSpec>,
+    errors: Vec<ErrorSpec>,
+    page_size: usize,
+}
+
+struct RepoSpec {
+    id: String,
+    branches: Vec<BranchSpec>,
+    tags: Vec<Tag>,
+}
+
+struct BranchSpec {
+    id: String,
+    objects: BTreeMap<String, Vec<u8>>,
+}
+
+struct ErrorSpec {
+    method: String,
+    path: String,
+    status: u16,
+    times: Option<u64>,
+}
+
+impl MockLakeFS {
+    pub fn new() -> Self {
+        Self {
+            repos: Vec::new(),
+            errors: Vec::new(),
+            page_size: 100,
+        }
+    }
+
+    pub fn with_repo(mut self, id: &str) -> Self {
+        self.repos.push(RepoSpec {
+            id: id.to_string(),
+            branches: Vec::new(),
+            tags: Vec::new(),
+        });
+        self
+    }
+
+    /// Add a branch to the last repository. The first branch becomes the
+    /// repository's default branch.
+    pub fn with_branch(mut self, id: &str) -> Self {
+        let repo = self.repos.last_mut().expect("with_branch called before with_repo");
+        repo.branches.push(BranchSpec {
+            id: id.to_string(),
+            objects: BTreeMap::new(),
+        });
+        self
+    }
+
+    /// Tag the head commit of the last branch added.
+    pub fn with_tag(mut self, id: &str) -> Self {
+        let repo = self.repos.last_mut().expect("with_tag called before with_repo");
+        let branch = repo.branches.last().expect("with_tag called before with_branch");
+        let commit_id = branch.model(&repo.id).commit_id;
+        repo.tags.push(Tag { id: id.to_string(), commit_id });
+        self
+    }
+
+    pub fn with_object(mut self, path: &str, content: impl Into<Vec<u8>>) -> Self {
+        let branch = self.repos
+            .last_mut()
+            .and_then(|repo| repo.branches.last_mut())
+            .expect("with_object called before with_branch");
+        branch.objects.insert(path.to_string(), content.into());
+        self
+    }
+
+    /// Maximum number of results per listing page.
+    pub fn page_size(mut self, page_size: usize) -> Self {
+        self.page_size = page_size.max(1);
+        self
+    }
+
+    /// Answer every `method` request to `path` with `status`.
+    pub fn with_error(mut self, method: &str, path: &str, status: u16) -> Self {
+        self.errors.push(ErrorSpec {
+            method: method.to_string(),
+            path: path.to_string(),
+            status,
+            times: None,
+        });
+        self
+    }
+
+    /// Answer the first `times` `method` requests to `path` with `status`,
+    /// then fall through to the normal response. Useful for retry tests.
+    pub fn with_transient_error(mut self, method: &str, path: &str, status: u16, times: u64) -> Self {
+        self.errors.push(ErrorSpec {
+            method: method.to_string(),
+            path: path.to_string(),
+            status,
+            times: Some(times),
+        });
+        self
+    }
+
+    pub async fn start(self) -> MockServer {
+        let server = MockServer::start().await;
+
+        for error in &self.errors {
+            let mock = Mock::given(method(error.method.as_str()))
+                .and(path(error.path.as_str()))
+                .respond_with(error_response(error.status))
+                .with_priority(ERROR_PRIORITY);
+            match error.times {
+                Some(times) => mock.up_to_n_times(times).mount(&server).await,
+                None => mock.mount(&server).await,
+            }
+        }
+
+        let repositories: Vec<(String, Value)> = self.repos
+            .iter()
+            .map(|repo| (repo.id.clone(), json!(repo.model())))
+            .collect();
+        Mock::given(method("GET"))
+            .and(path("/repositories"))
+            .respond_with(Listing::new(repositories, self.page_size))
+            .mount(&server)
+            .await;
+
+        for repo in &self.repos {
+            self.mount_repo(&server, repo).await;
+        }
+
+        server
+    }
+
+    async fn mount_repo(&self, server: &MockServer, repo: &RepoSpec) {
+        let base = format!("/repositories/{}", repo.id);
+
+        Mock::given(method("GET"))
+            .and(path(base.as_str()))
+            .respond_with(ResponseTemplate::new(200).set_body_json(repo.model()))
+            .mount(server)
+            .await;
+
+        let branches: Vec<(String, Value)> = repo.branches
+            .iter()
+            .map(|branch| (branch.id.clone(), json!(branch.model(&repo.id))))
+            .collect();
+        Mock::given(method("GET"))
+            .and(path(format!("{}/branches", base)))
+            .respond_with(Listing::new(branches, self.page_size))
+            .mount(server)
+            .await;
+
+        let tags: Vec<(String, Value)> = repo.tags.iter().map(|tag| (tag.id.clone(), json!(tag))).collect();
+        Mock::given(method("GET"))
+            .and(path(format!("{}/tags", base)))
+            .respond_with(Listing::new(tags, self.page_size))
+            .mount(server)
+            .await;
+
+        for tag in &repo.tags {
+            Mock::given(method("GET"))
+                .and(path(format!("{}/tags/{}", base, tag.id)))
+                .respond_with(ResponseTemplate::new(200).set_body_json(tag))
+                .mount(server)
+                .await;
+        }
+
+        for branch in &repo.branches {
+            let refs = format!("{}/refs/{}", base, branch.id);
+
+            Mock::given(method("GET"))
+                .and(path(format!("{}/branches/{}", base, branch.id)))
+                .respond_with(ResponseTemplate::new(200).set_body_json(branch.model(&repo.id)))
+                .mount(server)
+                .await;
+
+            let mut listing = Vec::new();
+            for (object_path, content) in &branch.objects {
+                let stats = object_stats(&repo.id, object_path, content);
+                let etag = format!("\"{}\"", stats.checksum);
+
+                Mock::given(method("GET"))
+                    .and(path(format!("{}/objects/stat", refs)))
+                    .and(query_param("path", object_path.as_str()))
+                    .respond_with(ResponseTemplate::new(200)
+                        .insert_header("ETag", etag.as_str())
+                        .set_body_json(&stats))
+                    .mount(server)
+                    .await;
+
+                Mock::given(method("GET"))
+                    .and(path(format!("{}/objects", refs)))
+                    .and(query_param("path", object_path.as_str()))
+                    .respond_with(ObjectContent { etag: etag.clone(), content: content.clone() })
+                    .mount(server)
+                    .await;
+
+                listing.push((object_path.clone(), json!(stats)));
+            }
+
+            Mock::given(method("GET"))
+                .and(path(format!("{}/objects", refs)))
+                .and(query_param_is_missing("path"))
+                .respond_with(Listing::new(listing, self.page_size))
+                .mount(server)
+                .await;
+
+            // Any other object on a known ref does not exist
+            Mock::given(method("GET"))
+                .and(path_regex(format!("^{}/objects(/stat)?$", regex_escape(&refs))))
+                .respond_with(error_response(404))
+                .with_priority(FALLBACK_PRIORITY)
+                .mount(server)
+                .await;
+        }
+    }
+}
+
+impl Default for MockLakeFS {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RepoSpec {
+    fn model(&self) -> Repository {
+        Repository {
+            id: self.id.clone(),
+            storage_namespace: format!("s3://test-bucket/{}", self.id),
+            default_branch: self.branches.first().map_or_else(|| "main".to_string(), |b| b.id.clone()),
+            creation_date: Utc::now(),
+            read_only: None,
+            storage_id: None,
+        }
+    }
+}
+
+impl BranchSpec {
+    fn model(&self, repository: &str) -> Branch {
+        Branch {
+            id: self.id.clone(),
+            commit_id: md5_hex(format!("{}/{}", repository, self.id).as_bytes()),
+        }
+    }
+}
+
+fn object_stats(repository: &str, object_path: &str, content: &[u8]) -> ObjectStats {
+    let checksum = md5_hex(content);
+    ObjectStats {
+        path: object_path.to_string(),
+        path_type: PathType::Object,
+        physical_address: format!("s3://test-bucket/{}/data/{}", repository, checksum),
+        checksum: checksum.into(),
+        size_bytes: content.len() as i64,
+        mtime: Utc::now(),
+        metadata: None,
+        physical_address_expiry: None,
+        content_type: None,
+    }
+}
+
+fn md5_hex(data: &[u8]) -> String {
+    format!("{:x}", Md5::digest(data))
+}
+
+fn error_response(status: u16) -> ResponseTemplate {
+    ResponseTemplate::new(status).set_body_json(json!({
+        "message": format!("injected error ({})", status),
+    }))
+}
+
+fn regex_escape(text: &str) -> String {
+    text.chars()
+        .flat_map(|c| {
+            let escape = "\\.+*?()|[]{}^$".contains(c);
+            escape.then_some('\\').into_iter().chain(std::iter::once(c))
+        })
+        .collect()
+}
+
+/// Object body served with its ETag, answering a matching `If-None-Match`
+/// with 304.
+struct ObjectContent {
+    etag: String,
+    content: Vec<u8>,
+}
+
+impl Respond for ObjectContent {
+    fn respond(&self, request: &Request) -> ResponseTemplate {
+        let unchanged = request
+            .headers
+            .iter()
+            .filter(|(name, _)| name.as_str().eq_ignore_ascii_case("if-none-match"))
+            .flat_map(|(_, values)| values.iter())
+            .flat_map(|value| value.as_str().split(','))
+            .any(|tag| tag.trim() == self.etag || tag.trim() == "*");
+        let response = if unchanged {
+            ResponseTemplate::new(304)
+        } else {
+            ResponseTemplate::new(200).set_body_bytes(self.content.clone())
+        };
+        response.insert_header("ETag", self.etag.as_str())
+    }
+}
+
+/// Paginated listing that follows lakeFS `prefix`/`after`/`amount` semantics.
+struct Listing {
+    items: Vec<(String, Value)>,
+    page_size: usize,
+}
+
+impl Listing {
+    fn new(mut items: Vec<(String, Value)>, page_size: usize) -> Self {
+        items.sort_by(|a, b| a.0.cmp(&b.0));
+        Self { items, page_size }
+    }
+}
+
+impl Respond for Listing {
+    fn respond(&self, request: &Request) -> ResponseTemplate {
+        let mut prefix = String::new();
+        let mut after = String::new();
+        let mut amount = self.page_size;
+        let mut delimiter = String::new();
+        for (key, value) in request.url.query_pairs() {
+            match key.as_ref() {
+                "prefix" => prefix = value.into_owned(),
+                "after" => after = value.into_owned(),
+                "amount" => amount = value.parse().unwrap_or(self.page_size).clamp(1, self.page_size),
+                "delimiter" => delimiter = value.into_owned(),
+                _ => {}
+            }
+        }
+
+        // Paths with the delimiter past the prefix roll up into one common
+        // prefix each, which sorts before the paths it stands for
+        let mut rolled_up: Vec<(String, Value)> = Vec::new();
+        for (key, value) in self.items.iter().filter(|(key, _)| key.starts_with(&prefix)) {
+            let common = (!delimiter.is_empty())
+                .then(|| key[prefix.len()..].find(&delimiter))
+                .flatten()
+                .map(|end| key[..prefix.len() + end + delimiter.len()].to_string());
+            match common {
+                Some(common) if rolled_up.last().is_some_and(|(last, _)| *last == common) => {}
+                Some(common) => rolled_up.push((common.clone(), json!({
+                    "path": common,
+                    "path_type": "common_prefix",
+                    "physical_address": "",
+                    "checksum": "",
+                    "mtime": 0,
+                }))),
+                None => rolled_up.push((key.clone(), value.clone())),
+            }
+        }
+
+        let mut matching = rolled_up
+            .iter()
+            .filter(|(key, _)| key.as_str() > after.as_str());
+        let page: Vec<&(String, Value)> = matching.by_ref().take(amount).collect();
+        let has_more = matching.next().is_some();
+        let next_offset = if has_more {
+            page.last().map(|(key, _)| key.clone()).unwrap_or_default()
+        } else {
+            String::new()
+        };
+
+        ResponseTemplate::new(200).set_body_json(json!({
+            "results": page.iter().map(|(_, value)| value).collect::<Vec<_>>(),
+            "pagination": {
+                "has_more": has_more,
+                "next_offset": next_offset,
+                "max_per_page": self.page_size,
+                "results": page.len(),
+            }
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lakefs_api::{Checksum, Error, LakeFSClient, ListEntry, ListObjectsOptions, PaginationParams};
+
+    #[tokio::test]
+    async fn test_scenario_serves_consistent_objects() {
+        let server = MockLakeFS::new()
+            .with_repo("repo")
+            .with_branch("main")
+            .with_object("data/a.csv", "a")
+            .with_object("data/b.csv", "bb")
+            .with_object("readme.md", "hello")
+            .start()
+            .await;
+        let client = LakeFSClient::new(server.uri(), "Bearer test-token");
+
+        let repo = client.get_repository("repo").await.unwrap();
+        assert_eq!(repo.default_branch, "main");
+
+        let listing = client.list_objects("repo", "main", &ListObjectsOptions::new().with_prefix("data/")).await.unwrap();
+        assert_eq!(listing.results.len(), 2);
+
+        let listing = client.list_objects("repo", "main", &ListObjectsOptions::new().with_delimiter("/")).await.unwrap();
+        let entries: Vec<ListEntry> = listing.results.into_iter().map(ListEntry::from).collect();
+        assert!(matches!(&entries[..], [ListEntry::CommonPrefix(data), ListEntry::Object(readme)]
+            if data == "data/" && readme.path == "readme.md"));
+
+        let stats = client.get_object("repo", "main", "readme.md").await.unwrap();
+        assert_eq!(stats.checksum, Checksum::Md5("5d41402abc4b2a76b9719d911017c592".into()));
+        assert_eq!(client.download_object("repo", "main", "readme.md").await.unwrap(), "hello");
+
+        let missing = client.get_object("repo", "main", "nope").await;
+        assert!(matches!(missing, Err(Error::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_scenario_tags_branch_head() {
+        let server = MockLakeFS::new()
+            .with_repo("repo")
+            .with_branch("main")
+            .with_tag("v1")
+            .start()
+            .await;
+        let client = LakeFSClient::new(server.uri(), "Bearer test-token");
+
+        let tags = client.list_tags_with("repo", &PaginationParams::default()).await.unwrap();
+        assert_eq!(tags.results.len(), 1);
+        let tag = client.get_tag("repo", "v1").await.unwrap();
+        assert_eq!(tag.commit_id, client.get_branch("repo", "main").await.unwrap().commit_id);
+    }
+
+    #[tokio::test]
+    async fn test_scenario_paginates_and_injects_errors() {
+        let server = MockLakeFS::new()
+            .with_repo("a")
+            .with_repo("b")
+            .with_repo("c")
+            .page_size(2)
+            .with_transient_error("GET", "/repositories/a", 503, 1)
+            .start()
+            .await;
+        let client = LakeFSClient::new(server.uri(), "Bearer test-token");
+
+        let first = client.list_repositories().await.unwrap();
+        assert_eq!(first.results.len(), 2);
+        assert!(first.pagination.has_more);
+
+        let params = PaginationParams::new().with_after(first.pagination.next_offset.unwrap());
+        let second = client.list_repositories_with(&params).await.unwrap();
+        assert_eq!(second.results[0].id, "c");
+        assert!(!second.pagination.has_more);
+
+        assert!(matches!(client.get_repository("a").await, Err(Error::Api { status: 503, .. })));
+        assert!(client.get_repository("a").await.is_ok());
+    }
+}